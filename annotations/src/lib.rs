@@ -7,6 +7,14 @@
 
 #![allow(unexpected_cfgs)]
 
+// The macros refer to their helpers through the name of the crate, also when they are used in
+// the modules of the crate itself
+#[cfg(feature = "solana")]
+extern crate self as hepha_annotations;
+
+#[cfg(feature = "solana")]
+pub mod solana;
+
 /// Provides a way to specify a value that should be treated abstractly by the verifier.
 /// The concrete argument provides type information to the verifier and a meaning for
 /// the expression when compiled by the rust compiler.
@@ -16,10 +24,15 @@
 #[macro_export]
 macro_rules! abstract_value {
     ($value:expr) => {
-        if cfg!(hepha) {
-            hepha_annotations::hepha_abstract_value($value)
-        } else {
-            $value
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_abstract_value($value)
+            }
+            #[cfg(not(hepha))]
+            {
+                $value
+            }
         }
     };
 }
@@ -91,6 +104,11 @@ fn test_add_prop() {
 /// An enum type of controllable operations for HEPHA tag types.
 /// In general, the result of the operation corresponding to an enum value will
 /// get tagged with all of the tags of the operands.
+///
+/// `Deref`, `Field` and `Index` restrict `SubComponent` and `SuperComponent` to
+/// the kinds of projection they name. A set that has none of them propagates
+/// tags through every kind of projection, as do sets without them that were
+/// made before they existed.
 #[derive(Ord, PartialOrd, Eq, PartialEq, Debug, Copy, Clone)]
 pub enum TagPropagation {
     Add,
@@ -101,10 +119,16 @@ pub enum TagPropagation {
     BitOr,
     BitXor,
     Cast,
+    /// Components and containers include the values that pointers in them point to.
+    Deref,
     Div,
     Equals,
+    /// Components and containers include the fields of structs, tuples, enum variants and unions.
+    Field,
     GreaterOrEqual,
     GreaterThan,
+    /// Components and containers include the elements and slices of arrays and slices.
+    Index,
     IntrinsicBinary,
     IntrinsicBitVectorUnary,
     IntrinsicFloatingPointUnary,
@@ -158,10 +182,13 @@ pub const TAG_PROPAGATION_ALL: TagPropagationSet = tag_propagation_set!(
     TagPropagation::BitOr,
     TagPropagation::BitXor,
     TagPropagation::Cast,
+    TagPropagation::Deref,
     TagPropagation::Div,
     TagPropagation::Equals,
+    TagPropagation::Field,
     TagPropagation::GreaterOrEqual,
     TagPropagation::GreaterThan,
+    TagPropagation::Index,
     TagPropagation::IntrinsicBinary,
     TagPropagation::IntrinsicBitVectorUnary,
     TagPropagation::IntrinsicFloatingPointUnary,
@@ -188,16 +215,57 @@ pub const TAG_PROPAGATION_ALL: TagPropagationSet = tag_propagation_set!(
     TagPropagation::UninterpretedCall
 );
 
+/// The built-in tag that HEPHA attaches to the instruction data of the entrypoints of a Solana
+/// program and to the data of accounts that is borrowed with `try_borrow_data`. It is propagated
+/// by every operation other than comparisons. Unlike other tag types it takes no propagation set,
+/// so `has_tag!(&amount, UntrustedInput)` and `does_not_have_tag!(&amount, UntrustedInput)` can
+/// be used to check whether a value has been sanitized, for instance with remove_tag! once its
+/// range has been checked.
+pub struct UntrustedInput {}
+
 /// Equivalent to a no op when used with an unmodified Rust compiler.
 /// When compiled with HEPHA, this causes HEPHA to associate (tag) the value with the given type.
 /// Typically the type will be private to a scope so that only privileged code can add the tag.
-/// Once added, a tag cannot be removed and the tagged value may not be modified.
+/// The tagged value may not be modified. Once added, a tag can only be removed with remove_tag!.
 /// To determine if a value has been tagged, use the has_tag! macro.
 #[macro_export]
 macro_rules! add_tag {
     ($value:expr, $tag:ty) => {
-        if cfg!(hepha) {
-            hepha_annotations::hepha_add_tag::<_, $tag>($value)
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_add_tag::<_, $tag>($value)
+            }
+            #[cfg(not(hepha))]
+            {
+                if false {
+                    let _ = || ($value, ::core::marker::PhantomData::<$tag>);
+                }
+            }
+        }
+    };
+}
+
+/// Equivalent to a no op when used with an unmodified Rust compiler.
+/// When compiled with HEPHA, this causes HEPHA to remove the tag with the given type from the
+/// value, and from its components if the tag propagates to sub-components, for example after
+/// the value has been sanitized. Values that may or may not have gone via remove_tag!, such as
+/// the joins of values at the ends of different paths, may still have the tag.
+/// Typically the type will be private to a scope so that only privileged code can remove the tag.
+#[macro_export]
+macro_rules! remove_tag {
+    ($value:expr, $tag:ty) => {
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_remove_tag::<_, $tag>($value)
+            }
+            #[cfg(not(hepha))]
+            {
+                if false {
+                    let _ = || ($value, ::core::marker::PhantomData::<$tag>);
+                }
+            }
         }
     };
 }
@@ -209,10 +277,18 @@ macro_rules! add_tag {
 #[macro_export]
 macro_rules! has_tag {
     ($value:expr, $tag:ty) => {
-        if cfg!(hepha) {
-            hepha_annotations::hepha_has_tag::<_, $tag>($value)
-        } else {
-            true
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_has_tag::<_, $tag>($value)
+            }
+            #[cfg(not(hepha))]
+            {
+                if false {
+                    let _ = || ($value, ::core::marker::PhantomData::<$tag>);
+                }
+                true
+            }
         }
     };
 }
@@ -224,10 +300,18 @@ macro_rules! has_tag {
 #[macro_export]
 macro_rules! does_not_have_tag {
     ($value:expr, $tag:ty) => {
-        if cfg!(hepha) {
-            hepha_annotations::hepha_does_not_have_tag::<_, $tag>($value)
-        } else {
-            true
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_does_not_have_tag::<_, $tag>($value)
+            }
+            #[cfg(not(hepha))]
+            {
+                if false {
+                    let _ = || ($value, ::core::marker::PhantomData::<$tag>);
+                }
+                true
+            }
         }
     };
 }
@@ -238,8 +322,40 @@ macro_rules! does_not_have_tag {
 #[macro_export]
 macro_rules! assume {
     ($condition:expr) => {
-        if cfg!(hepha) {
-            hepha_annotations::hepha_assume($condition)
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_assume($condition)
+            }
+            #[cfg(not(hepha))]
+            {
+                if false {
+                    let _ = || -> bool { $condition };
+                }
+            }
+        }
+    };
+}
+
+/// Equivalent to a no op when used with an unmodified Rust compiler.
+/// When compiled with HEPHA, this states a condition that holds at the start of every iteration of
+/// the loop whose body it begins. HEPHA warns if the condition does not hold when the loop is
+/// entered, or if an iteration of the loop body falsifies it, and otherwise assumes it, so that
+/// the state of the loop is not widened beyond it.
+#[macro_export]
+macro_rules! loop_invariant {
+    ($condition:expr) => {
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_loop_invariant($condition)
+            }
+            #[cfg(not(hepha))]
+            {
+                if false {
+                    let _ = || -> bool { $condition };
+                }
+            }
         }
     };
 }
@@ -254,8 +370,11 @@ macro_rules! assume {
 #[macro_export]
 macro_rules! assume_preconditions {
     () => {
-        if cfg!(hepha) {
-            hepha_annotations::hepha_assume_preconditions()
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_assume_preconditions()
+            }
         }
     };
 }
@@ -266,17 +385,27 @@ macro_rules! assume_preconditions {
 #[macro_export]
 macro_rules! checked_assume {
     ($condition:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_assume($condition)
-        } else {
-            assert!($condition);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_assume($condition)
+            }
+            #[cfg(not(hepha))]
+            {
+                assert!($condition);
+            }
         }
     );
     ($condition:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_assume($condition);
-        } else {
-            assert!($condition, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_assume($condition);
+            }
+            #[cfg(not(hepha))]
+            {
+                assert!($condition, $($arg)*);
+            }
         }
     );
 }
@@ -287,17 +416,27 @@ macro_rules! checked_assume {
 #[macro_export]
 macro_rules! checked_assume_eq {
     ($left:expr, $right:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_assume($left == $right)
-        } else {
-            assert_eq!($left, $right);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_assume($left == $right)
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_eq!($left, $right);
+            }
         }
     );
     ($left:expr, $right:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_assume($left == $right);
-        } else {
-            assert_eq!($left, $right, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_assume($left == $right);
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_eq!($left, $right, $($arg)*);
+            }
         }
     );
 }
@@ -308,17 +447,27 @@ macro_rules! checked_assume_eq {
 #[macro_export]
 macro_rules! checked_assume_ne {
     ($left:expr, $right:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_assume($left != $right)
-        } else {
-            assert_ne!($left, $right);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_assume($left != $right)
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_ne!($left, $right);
+            }
         }
     );
     ($left:expr, $right:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_assume($left != $right);
-        } else {
-            assert_ne!($left, $right, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_assume($left != $right);
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_ne!($left, $right, $($arg)*);
+            }
         }
     );
 }
@@ -329,17 +478,27 @@ macro_rules! checked_assume_ne {
 #[macro_export]
 macro_rules! debug_checked_assume {
     ($condition:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_assume($condition)
-        } else {
-            debug_assert!($condition);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_assume($condition)
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert!($condition);
+            }
         }
     );
     ($condition:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_assume($condition);
-        } else {
-            debug_assert!($condition, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_assume($condition);
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert!($condition, $($arg)*);
+            }
         }
     );
 }
@@ -350,17 +509,27 @@ macro_rules! debug_checked_assume {
 #[macro_export]
 macro_rules! debug_checked_assume_eq {
     ($left:expr, $right:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_assume($left == $right)
-        } else {
-            debug_assert_eq!($left, $right);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_assume($left == $right)
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert_eq!($left, $right);
+            }
         }
     );
     ($left:expr, $right:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_assume($left == $right);
-        } else {
-            debug_assert_eq!($left, $right, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_assume($left == $right);
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert_eq!($left, $right, $($arg)*);
+            }
         }
     );
 }
@@ -371,17 +540,27 @@ macro_rules! debug_checked_assume_eq {
 #[macro_export]
 macro_rules! debug_checked_assume_ne {
     ($left:expr, $right:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_assume($left != $right)
-        } else {
-            debug_assert_ne!($left, $right);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_assume($left != $right)
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert_ne!($left, $right);
+            }
         }
     );
     ($left:expr, $right:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_assume($left != $right);
-        } else {
-            debug_assert_ne!($left, $right, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_assume($left != $right);
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert_ne!($left, $right, $($arg)*);
+            }
         }
     );
 }
@@ -396,16 +575,31 @@ macro_rules! postcondition {
         #[cfg(hepha)] {
             hepha_annotations::hepha_postcondition($condition, false, "unsatisfied postcondition");
         }
+        #[cfg(not(hepha))] {
+            if false {
+                let _ = || -> bool { $condition };
+            }
+        }
     };
     ($condition:expr, $message:literal) => {
         #[cfg(hepha)] {
             hepha_annotations::hepha_postcondition($condition, false,  concat!("unsatisfied postcondition: ", $message));
         }
+        #[cfg(not(hepha))] {
+            if false {
+                let _ = || -> bool { $condition };
+            }
+        }
     };
     ($condition:expr, $($arg:tt)*) => {
         #[cfg(hepha)] {
             hepha_annotations::hepha_postcondition($condition, false,  concat!("unsatisfied postcondition: ", stringify!($($arg)*)));
         }
+        #[cfg(not(hepha))] {
+            if false {
+                let _ = || -> bool { $condition };
+            }
+        }
     };
 }
 
@@ -466,24 +660,39 @@ macro_rules! checked_postcondition {
 #[macro_export]
 macro_rules! checked_postcondition_eq {
     ($left:expr, $right:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_postcondition($left == $right, false,  concat!("unsatisfied postcondition: ", stringify!($left == $right)))
-        } else {
-            assert_eq!($left, $right);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_postcondition($left == $right, false,  concat!("unsatisfied postcondition: ", stringify!($left == $right)))
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_eq!($left, $right);
+            }
         }
     );
     ($left:expr, $right:expr, $message:literal) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_postcondition($left == $right, false,  concat!("unsatisfied postcondition: ", stringify!($left == $right), ", ", $message))
-        } else {
-            assert_eq!($left, $right, $message);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_postcondition($left == $right, false,  concat!("unsatisfied postcondition: ", stringify!($left == $right), ", ", $message))
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_eq!($left, $right, $message);
+            }
         }
     );
     ($left:expr, $right:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_postcondition($left == $right, false,  concat!("unsatisfied postcondition: ", stringify!($left == $right), ", ", stringify!($($arg)*)));
-        } else {
-            assert_eq!($left, $right, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_postcondition($left == $right, false,  concat!("unsatisfied postcondition: ", stringify!($left == $right), ", ", stringify!($($arg)*)));
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_eq!($left, $right, $($arg)*);
+            }
         }
     );
 }
@@ -495,24 +704,39 @@ macro_rules! checked_postcondition_eq {
 #[macro_export]
 macro_rules! checked_postcondition_ne {
     ($left:expr, $right:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_postcondition($left != $right, false,  concat!("unsatisfied postcondition: ", stringify!($left != $right)))
-        } else {
-            assert_ne!($left, $right);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_postcondition($left != $right, false,  concat!("unsatisfied postcondition: ", stringify!($left != $right)))
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_ne!($left, $right);
+            }
         }
     );
     ($left:expr, $right:expr, $message:literal) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_postcondition($left != $right, false,  concat!("unsatisfied postcondition: ", stringify!($left != $right), ", ", $message))
-        } else {
-            assert_ne!($left, $right, $message);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_postcondition($left != $right, false,  concat!("unsatisfied postcondition: ", stringify!($left != $right), ", ", $message))
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_ne!($left, $right, $message);
+            }
         }
     );
     ($left:expr, $right:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_postcondition($left != $right, false,  concat!("unsatisfied postcondition: ", stringify!($left != $right), ", ", stringify!($($arg)*)));
-        } else {
-            assert_ne!($left, $right, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_postcondition($left != $right, false,  concat!("unsatisfied postcondition: ", stringify!($left != $right), ", ", stringify!($($arg)*)));
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_ne!($left, $right, $($arg)*);
+            }
         }
     );
 }
@@ -620,21 +844,48 @@ macro_rules! debug_checked_postcondition_ne {
 #[macro_export]
 macro_rules! precondition {
     ($condition:expr) => {
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($condition, "unsatisfied precondition")
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($condition, "unsatisfied precondition")
+            }
+            #[cfg(not(hepha))]
+            {
+                if false {
+                    let _ = || -> bool { $condition };
+                }
+            }
         }
     };
     ($condition:expr, $message:literal) => {
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($condition, concat!("unsatisfied precondition: ", $message))
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($condition, concat!("unsatisfied precondition: ", $message))
+            }
+            #[cfg(not(hepha))]
+            {
+                if false {
+                    let _ = || -> bool { $condition };
+                }
+            }
         }
     };
     ($condition:expr, $($arg:tt)*) => {
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($condition, concat!("unsatisfied precondition: ", stringify!($($arg)*)));
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($condition, concat!("unsatisfied precondition: ", stringify!($($arg)*)));
+            }
+            #[cfg(not(hepha))]
+            {
+                if false {
+                    let _ = || -> bool { $condition };
+                }
+            }
         }
     };
 }
@@ -646,27 +897,42 @@ macro_rules! precondition {
 #[macro_export]
 macro_rules! checked_precondition {
     ($condition:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($condition, "unsatisfied precondition")
-        } else {
-            assert!($condition);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($condition, "unsatisfied precondition")
+            }
+            #[cfg(not(hepha))]
+            {
+                assert!($condition);
+            }
         }
     );
     ($condition:expr, $message:literal) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($condition, concat!("unsatisfied precondition: ", $message))
-        } else {
-            assert!($condition, $message);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($condition, concat!("unsatisfied precondition: ", $message))
+            }
+            #[cfg(not(hepha))]
+            {
+                assert!($condition, $message);
+            }
         }
     );
     ($condition:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($condition, concat!("unsatisfied precondition: ", stringify!($($arg)*)));
-        } else {
-            assert!($condition, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($condition, concat!("unsatisfied precondition: ", stringify!($($arg)*)));
+            }
+            #[cfg(not(hepha))]
+            {
+                assert!($condition, $($arg)*);
+            }
         }
     );
 }
@@ -678,27 +944,42 @@ macro_rules! checked_precondition {
 #[macro_export]
 macro_rules! checked_precondition_eq {
     ($left:expr, $right:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($left == $right, concat!("unsatisfied precondition: ", stringify!($left == $right)))
-        } else {
-            assert_eq!($left, $right);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($left == $right, concat!("unsatisfied precondition: ", stringify!($left == $right)))
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_eq!($left, $right);
+            }
         }
     );
     ($left:expr, $right:expr, $message:literal) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($left == $right, concat!("unsatisfied precondition: ", stringify!($left == $right), ", ", $message))
-        } else {
-            assert_eq!($left, $right, $message);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($left == $right, concat!("unsatisfied precondition: ", stringify!($left == $right), ", ", $message))
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_eq!($left, $right, $message);
+            }
         }
     );
     ($left:expr, $right:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($left == $right, concat!("unsatisfied precondition: ", stringify!($left == $right), ", ", stringify!($($arg)*)));
-        } else {
-            assert_eq!($left, $right, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($left == $right, concat!("unsatisfied precondition: ", stringify!($left == $right), ", ", stringify!($($arg)*)));
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_eq!($left, $right, $($arg)*);
+            }
         }
     );
 }
@@ -710,27 +991,42 @@ macro_rules! checked_precondition_eq {
 #[macro_export]
 macro_rules! checked_precondition_ne {
     ($left:expr, $right:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($left != $right, concat!("unsatisfied precondition: ", stringify!($left != $right)))
-        } else {
-            assert_ne!($left, $right);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($left != $right, concat!("unsatisfied precondition: ", stringify!($left != $right)))
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_ne!($left, $right);
+            }
         }
     );
     ($left:expr, $right:expr, $message:literal) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($left != $right, concat!("unsatisfied precondition: ", stringify!($left != $right), ", ", $message))
-        } else {
-            assert_ne!($left, $right, $message);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($left != $right, concat!("unsatisfied precondition: ", stringify!($left != $right), ", ", $message))
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_ne!($left, $right, $message);
+            }
         }
     );
     ($left:expr, $right:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($left != $right, concat!("unsatisfied precondition: ", stringify!($left != $right), ", ", stringify!($($arg)*)));
-        } else {
-            assert_ne!($left, $right, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($left != $right, concat!("unsatisfied precondition: ", stringify!($left != $right), ", ", stringify!($($arg)*)));
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_ne!($left, $right, $($arg)*);
+            }
         }
     );
 }
@@ -742,27 +1038,42 @@ macro_rules! checked_precondition_ne {
 #[macro_export]
 macro_rules! debug_checked_precondition {
     ($condition:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($condition, "unsatisfied precondition")
-        } else {
-            debug_assert!($condition);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($condition, "unsatisfied precondition")
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert!($condition);
+            }
         }
     );
     ($condition:expr, $message:literal) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($condition, concat!("unsatisfied precondition: ", $message))
-        } else {
-            debug_assert!($condition, $message);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($condition, concat!("unsatisfied precondition: ", $message))
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert!($condition, $message);
+            }
         }
     );
     ($condition:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($condition, concat!("unsatisfied precondition: ", stringify!($($arg)*)));
-        } else {
-            debug_assert!($condition, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($condition, concat!("unsatisfied precondition: ", stringify!($($arg)*)));
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert!($condition, $($arg)*);
+            }
         }
     );
 }
@@ -774,27 +1085,42 @@ macro_rules! debug_checked_precondition {
 #[macro_export]
 macro_rules! debug_checked_precondition_eq {
     ($left:expr, $right:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($left == $right, concat!("unsatisfied precondition: ", stringify!($left == $right)))
-        } else {
-            debug_assert_eq!($left, $right);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($left == $right, concat!("unsatisfied precondition: ", stringify!($left == $right)))
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert_eq!($left, $right);
+            }
         }
     );
     ($left:expr, $right:expr, $message:literal) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($left == $right, concat!("unsatisfied precondition: ", stringify!($left == $right), ", ", $message))
-        } else {
-            debug_assert_eq!($left, $right, $message);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($left == $right, concat!("unsatisfied precondition: ", stringify!($left == $right), ", ", $message))
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert_eq!($left, $right, $message);
+            }
         }
     );
     ($left:expr, $right:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($left == $right, concat!("unsatisfied precondition: ", stringify!($left == $right), ", ", stringify!($($arg)*)));
-        } else {
-            debug_assert_eq!($left, $right, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($left == $right, concat!("unsatisfied precondition: ", stringify!($left == $right), ", ", stringify!($($arg)*)));
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert_eq!($left, $right, $($arg)*);
+            }
         }
     );
 }
@@ -806,27 +1132,42 @@ macro_rules! debug_checked_precondition_eq {
 #[macro_export]
 macro_rules! debug_checked_precondition_ne {
     ($left:expr, $right:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($left != $right, concat!("unsatisfied precondition: ", stringify!($left != $right)))
-        } else {
-            debug_assert_ne!($left, $right);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($left != $right, concat!("unsatisfied precondition: ", stringify!($left != $right)))
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert_ne!($left, $right);
+            }
         }
     );
     ($left:expr, $right:expr, $message:literal) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($left != $right, concat!("unsatisfied precondition: ", stringify!($left != $right), ", ", $message))
-        } else {
-            debug_assert_ne!($left, $right, $message);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($left != $right, concat!("unsatisfied precondition: ", stringify!($left != $right), ", ", $message))
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert_ne!($left, $right, $message);
+            }
         }
     );
     ($left:expr, $right:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_precondition_start();
-            hepha_annotations::hepha_precondition($left != $right, concat!("unsatisfied precondition: ", stringify!($left != $right), ", ", stringify!($($arg)*)));
-        } else {
-            debug_assert_ne!($left, $right, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_precondition_start();
+                hepha_annotations::hepha_precondition($left != $right, concat!("unsatisfied precondition: ", stringify!($left != $right), ", ", stringify!($($arg)*)));
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert_ne!($left, $right, $($arg)*);
+            }
         }
     );
 }
@@ -850,8 +1191,17 @@ macro_rules! unrecoverable {
 #[macro_export]
 macro_rules! verify {
     ($condition:expr) => {
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($condition, "false verification condition")
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($condition, "false verification condition")
+            }
+            #[cfg(not(hepha))]
+            {
+                if false {
+                    let _ = || -> bool { $condition };
+                }
+            }
         }
     };
 }
@@ -862,24 +1212,39 @@ macro_rules! verify {
 #[macro_export]
 macro_rules! checked_verify {
     ($condition:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($condition, "false verification condition")
-        } else {
-            assert!($condition);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($condition, "false verification condition")
+            }
+            #[cfg(not(hepha))]
+            {
+                assert!($condition);
+            }
         }
     );
     ($condition:expr, $message:literal) => {
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($condition, concat!("false verification condition: ", $message))
-        } else {
-            assert!($condition, $message);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($condition, concat!("false verification condition: ", $message))
+            }
+            #[cfg(not(hepha))]
+            {
+                assert!($condition, $message);
+            }
         }
     };
     ($condition:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($condition,  concat!("false verification condition: ", stringify!($($arg)*)));
-        } else {
-            assert!($condition, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($condition,  concat!("false verification condition: ", stringify!($($arg)*)));
+            }
+            #[cfg(not(hepha))]
+            {
+                assert!($condition, $($arg)*);
+            }
         }
     );
 }
@@ -890,24 +1255,39 @@ macro_rules! checked_verify {
 #[macro_export]
 macro_rules! checked_verify_eq {
     ($left:expr, $right:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($left == $right, concat!("false verification condition: ", stringify!($left == $right)))
-        } else {
-            assert_eq!($left, $right);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($left == $right, concat!("false verification condition: ", stringify!($left == $right)))
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_eq!($left, $right);
+            }
         }
     );
     ($left:expr, $right:expr, $message:literal) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($left == $right, concat!("false verification condition: ", stringify!($left == $right), ", ", $message))
-        } else {
-            assert_eq!($left, $right, $message);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($left == $right, concat!("false verification condition: ", stringify!($left == $right), ", ", $message))
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_eq!($left, $right, $message);
+            }
         }
     );
     ($left:expr, $right:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($left == $right, concat!("false verification condition: ", stringify!($left == $right), ", ", stringify!($($arg)*)));
-        } else {
-            assert_eq!($left, $right, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($left == $right, concat!("false verification condition: ", stringify!($left == $right), ", ", stringify!($($arg)*)));
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_eq!($left, $right, $($arg)*);
+            }
         }
     );
 }
@@ -918,24 +1298,39 @@ macro_rules! checked_verify_eq {
 #[macro_export]
 macro_rules! checked_verify_ne {
     ($left:expr, $right:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($left != $right, concat!("false verification condition: ", stringify!($left != $right)))
-        } else {
-            assert_ne!($left, $right);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($left != $right, concat!("false verification condition: ", stringify!($left != $right)))
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_ne!($left, $right);
+            }
         }
     );
     ($left:expr, $right:expr, $message:literal) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($left != $right, concat!("false verification condition: ", stringify!($left != $right), ", ", $message))
-        } else {
-            assert_ne!($left, $right, $message);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($left != $right, concat!("false verification condition: ", stringify!($left != $right), ", ", $message))
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_ne!($left, $right, $message);
+            }
         }
     );
     ($left:expr, $right:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($left != $right, concat!("false verification condition: ", stringify!($left != $right), ", ", stringify!($($arg)*)));
-        } else {
-            assert_ne!($left, $right, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($left != $right, concat!("false verification condition: ", stringify!($left != $right), ", ", stringify!($($arg)*)));
+            }
+            #[cfg(not(hepha))]
+            {
+                assert_ne!($left, $right, $($arg)*);
+            }
         }
     );
 }
@@ -946,24 +1341,39 @@ macro_rules! checked_verify_ne {
 #[macro_export]
 macro_rules! debug_checked_verify {
     ($condition:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($condition, "false verification condition")
-        } else {
-            debug_assert!($condition);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($condition, "false verification condition")
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert!($condition);
+            }
         }
     );
     ($condition:expr, $message:literal) => {
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($condition, concat!("false verification condition: ", $message))
-        } else {
-            debug_assert!($condition, $message);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($condition, concat!("false verification condition: ", $message))
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert!($condition, $message);
+            }
         }
     };
     ($condition:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($condition,  concat!("false verification condition: ", stringify!($($arg)*)));
-        } else {
-            debug_assert!($condition, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($condition,  concat!("false verification condition: ", stringify!($($arg)*)));
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert!($condition, $($arg)*);
+            }
         }
     );
 }
@@ -974,24 +1384,39 @@ macro_rules! debug_checked_verify {
 #[macro_export]
 macro_rules! debug_checked_verify_eq {
     ($left:expr, $right:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($left == $right, concat!("false verification condition: ", stringify!($left == $right)))
-        } else {
-            debug_assert_eq!($left, $right);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($left == $right, concat!("false verification condition: ", stringify!($left == $right)))
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert_eq!($left, $right);
+            }
         }
     );
     ($left:expr, $right:expr, $message:literal) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($left == $right, concat!("false verification condition: ", stringify!($left == $right), ", ", $message))
-        } else {
-            debug_assert_eq!($left, $right, $message);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($left == $right, concat!("false verification condition: ", stringify!($left == $right), ", ", $message))
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert_eq!($left, $right, $message);
+            }
         }
     );
     ($left:expr, $right:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($left == $right, concat!("false verification condition: ", stringify!($left == $right), ", ", stringify!($($arg)*)));
-        } else {
-            debug_assert_eq!($left, $right, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($left == $right, concat!("false verification condition: ", stringify!($left == $right), ", ", stringify!($($arg)*)));
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert_eq!($left, $right, $($arg)*);
+            }
         }
     );
 }
@@ -1002,24 +1427,39 @@ macro_rules! debug_checked_verify_eq {
 #[macro_export]
 macro_rules! debug_checked_verify_ne {
     ($left:expr, $right:expr) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($left != $right, concat!("false verification condition: ", stringify!($left != $right)))
-        } else {
-            debug_assert_ne!($left, $right);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($left != $right, concat!("false verification condition: ", stringify!($left != $right)))
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert_ne!($left, $right);
+            }
         }
     );
     ($left:expr, $right:expr, $message:literal) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($left != $right, concat!("false verification condition: ", stringify!($left != $right), ", ", $message))
-        } else {
-            debug_assert_ne!($left, $right, $message);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($left != $right, concat!("false verification condition: ", stringify!($left != $right), ", ", $message))
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert_ne!($left, $right, $message);
+            }
         }
     );
     ($left:expr, $right:expr, $($arg:tt)*) => (
-        if cfg!(hepha) {
-            hepha_annotations::hepha_verify($left != $right, concat!("false verification condition: ", stringify!($left != $right), ", ", stringify!($($arg)*)));
-        } else {
-            debug_assert_ne!($left, $right, $($arg)*);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify($left != $right, concat!("false verification condition: ", stringify!($left != $right), ", ", stringify!($($arg)*)));
+            }
+            #[cfg(not(hepha))]
+            {
+                debug_assert_ne!($left, $right, $($arg)*);
+            }
         }
     );
 }
@@ -1046,10 +1486,63 @@ macro_rules! get_model_field {
 #[macro_export]
 macro_rules! result {
     () => {
-        if cfg!(hepha) {
-            hepha_annotations::hepha_result()
-        } else {
-            unimplemented!()
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_result()
+            }
+            #[cfg(not(hepha))]
+            {
+                unimplemented!()
+            }
+        }
+    };
+}
+
+/// Provides a way to refer to the value that an expression had when the function was entered, in
+/// the condition of a postcondition, such as postcondition!(*balance == old!(*balance) - amount).
+/// The parameters in the expression are those of the function and the other variables have the
+/// values that they have at the postcondition.
+/// This macro expands to the expression itself unless the program is compiled with HEPHA.
+#[macro_export]
+macro_rules! old {
+    ($value:expr) => {
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_old($value)
+            }
+            #[cfg(not(hepha))]
+            {
+                $value
+            }
+        }
+    };
+}
+
+/// Provides a way to state that a condition holds for every value of a variable, such as
+/// postcondition!(forall!(|i: usize| i < buf.len() => buf[i] == 0)), which states that every
+/// element of buf is zero. The condition after => only needs to hold for the values that satisfy
+/// the condition before it. HEPHA leaves the quantified condition to the SMT solver, which can
+/// relate it to the elements at constant indices.
+/// This macro expands to true unless the program is compiled with HEPHA.
+#[macro_export]
+macro_rules! forall {
+    (|$var:ident : $ty:ty| $guard:expr => $condition:expr) => {
+        $crate::forall!(|$var: $ty| !($guard) || ($condition))
+    };
+    (|$var:ident : $ty:ty| $condition:expr) => {
+        {
+            #[cfg(hepha)]
+            {
+                let $var: $ty = hepha_annotations::hepha_bound_variable();
+                hepha_annotations::hepha_forall($var, $condition)
+            }
+            #[cfg(not(hepha))]
+            {
+                let _ = |$var: $ty| -> bool { $condition };
+                true
+            }
         }
     };
 }
@@ -1060,8 +1553,36 @@ macro_rules! result {
 #[macro_export]
 macro_rules! set_model_field {
     ($target:expr, $field_name:ident, $value:expr) => {
-        if cfg!(hepha) {
-            hepha_annotations::hepha_set_model_field($target, stringify!($field_name), $value);
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_set_model_field($target, stringify!($field_name), $value);
+            }
+            #[cfg(not(hepha))]
+            {
+                if false {
+                    let _ = || ($target, $value);
+                }
+            }
+        }
+    };
+}
+
+/// Equivalent to a no op when used with an unmodified Rust compiler.
+/// When compiled with HEPHA, this causes HEPHA to drop the findings of the named checker, such as
+/// "reentrancy", in the function where it appears, including those that callers would otherwise
+/// report because of what the summary of the function says.
+/// For example: suppress!("reentrancy").
+#[macro_export]
+macro_rules! suppress {
+    ($checker:literal) => {
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_suppress($checker)
+            }
+            #[cfg(not(hepha))]
+            {}
         }
     };
 }
@@ -1071,31 +1592,51 @@ macro_rules! set_model_field {
 #[macro_export]
 macro_rules! assume_unreachable {
     () => {
-        if cfg!(hepha) {
-            unreachable!()
-        } else {
-            unreachable!()
+        {
+            #[cfg(hepha)]
+            {
+                unreachable!()
+            }
+            #[cfg(not(hepha))]
+            {
+                unreachable!()
+            }
         }
     };
     ($message:literal) => {
-        if cfg!(hepha) {
-            unreachable!()
-        } else {
-            unreachable!($message)
+        {
+            #[cfg(hepha)]
+            {
+                unreachable!()
+            }
+            #[cfg(not(hepha))]
+            {
+                unreachable!($message)
+            }
         }
     };
-    ($msg:expr,) => ({
-        if cfg!(hepha) {
-            unreachable!()
-        } else {
-            unreachable!($msg)
+    ($msg:expr,) => {
+        {
+            #[cfg(hepha)]
+            {
+                unreachable!()
+            }
+            #[cfg(not(hepha))]
+            {
+                unreachable!($msg)
+            }
         }
-    });
+    };
     ($fmt:expr, $($arg:tt)*) => {
-        if cfg!(hepha) {
-            unreachable!()
-        } else {
-            unreachable!($fmt, $($arg)*)
+        {
+            #[cfg(hepha)]
+            {
+                unreachable!()
+            }
+            #[cfg(not(hepha))]
+            {
+                unreachable!($fmt, $($arg)*)
+            }
         }
     };
 }
@@ -1105,31 +1646,69 @@ macro_rules! assume_unreachable {
 #[macro_export]
 macro_rules! verify_unreachable {
     () => {
-        if cfg!(hepha) {
-            panic!("statement is reachable");
-        } else {
-            unreachable!()
+        {
+            #[cfg(hepha)]
+            {
+                panic!("statement is reachable");
+            }
+            #[cfg(not(hepha))]
+            {
+                unreachable!()
+            }
         }
     };
     ($message:literal) => {
-        if cfg!(hepha) {
-            panic!($message);
-        } else {
-            unreachable!($message)
+        {
+            #[cfg(hepha)]
+            {
+                panic!($message);
+            }
+            #[cfg(not(hepha))]
+            {
+                unreachable!($message)
+            }
         }
     };
-    ($msg:expr) => ({
-        if cfg!(hepha) {
-            panic!($message)
-        } else {
-            unreachable!($msg)
+    ($msg:expr) => {
+        {
+            #[cfg(hepha)]
+            {
+                panic!($msg)
+            }
+            #[cfg(not(hepha))]
+            {
+                unreachable!($msg)
+            }
         }
-    });
+    };
     ($fmt:expr, $($arg:tt)*) => {
-        if cfg!(hepha) {
-            panic!($fmt, $($arg)*);
-        } else {
-            unreachable!($fmt, $($arg)*)
+        {
+            #[cfg(hepha)]
+            {
+                panic!($fmt, $($arg)*);
+            }
+            #[cfg(not(hepha))]
+            {
+                unreachable!($fmt, $($arg)*)
+            }
+        }
+    };
+}
+
+/// Equivalent to a no op when used with an unmodified Rust compiler.
+/// When compiled with HEPHA, this causes HEPHA to warn if the assumptions that hold where the
+/// annotation appears make it unreachable, which shows that a code path that should be covered by
+/// the analysis has been pruned, for example by an assume! that is stronger than intended.
+#[macro_export]
+macro_rules! verify_reachable {
+    () => {
+        {
+            #[cfg(hepha)]
+            {
+                hepha_annotations::hepha_verify_reachable()
+            }
+            #[cfg(not(hepha))]
+            {}
         }
     };
 }
@@ -1144,6 +1723,10 @@ pub fn hepha_abstract_value<T>(x: T) -> T {
 #[doc(hidden)]
 pub fn hepha_add_tag<V: ?Sized, T>(_v: &V) {}
 
+// Helper function for HEPHA. Should only be called via the remove_tag! macro.
+#[doc(hidden)]
+pub fn hepha_remove_tag<V: ?Sized, T>(_v: &V) {}
+
 // Helper function for HEPHA. Should only be called via the has_tag! macro.
 #[doc(hidden)]
 pub fn hepha_has_tag<V: ?Sized, T>(_v: &V) -> bool {
@@ -1160,6 +1743,10 @@ pub fn hepha_does_not_have_tag<V: ?Sized, T>(_v: &V) -> bool {
 #[doc(hidden)]
 pub fn hepha_assume(_condition: bool) {}
 
+// Helper function for HEPHA. Should only be called via the loop_invariant! macro.
+#[doc(hidden)]
+pub fn hepha_loop_invariant(_condition: bool) {}
+
 // Helper function for HEPHA. Should only be called via the assume_precondition macro.
 #[doc(hidden)]
 pub fn hepha_assume_preconditions() {}
@@ -1168,6 +1755,24 @@ pub fn hepha_assume_preconditions() {}
 #[doc(hidden)]
 pub fn hepha_postcondition(_condition: bool, _assumed: bool, _message: &str) {}
 
+// Helper function for HEPHA. Should only be called via the old! macro.
+#[doc(hidden)]
+pub fn hepha_old<T>(value: T) -> T {
+    value
+}
+
+// Helper function for HEPHA. Should only be called via the forall! macro.
+#[doc(hidden)]
+pub fn hepha_bound_variable<T>() -> T {
+    unimplemented!()
+}
+
+// Helper function for HEPHA. Should only be called via the forall! macro.
+#[doc(hidden)]
+pub fn hepha_forall<T>(_bound_variable: T, _condition: bool) -> bool {
+    true
+}
+
 // Helper function for HEPHA. Should only be called via the precondition macros.
 #[doc(hidden)]
 pub fn hepha_precondition_start() {}
@@ -1195,3 +1800,11 @@ pub fn hepha_result<T>() -> T {
 // Helper function for HEPHA. Should only be called via the set_model_field macro.
 #[doc(hidden)]
 pub fn hepha_set_model_field<T, V>(_target: T, _field_name: &str, _value: V) {}
+
+// Helper function for HEPHA. Should only be called via the suppress! macro.
+#[doc(hidden)]
+pub fn hepha_suppress(_checker: &str) {}
+
+// Helper function for HEPHA. Should only be called via the verify_reachable! macro.
+#[doc(hidden)]
+pub fn hepha_verify_reachable() {}
@@ -0,0 +1,110 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Wrappers of common Solana operations that carry the annotations that HEPHA needs to know what
+//! they guarantee, so that a program can be checked without writing annotations of its own.
+//! The checkers of HEPHA recognize these functions and treat a call of one of them that returns
+//! without an error as a guard, so that the findings that the guard rules out are not reported.
+//!
+//! This module is only available with the `solana` feature.
+
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+use crate::postcondition;
+
+/// Returns an error unless the account has signed the transaction.
+/// When compiled with HEPHA, the callers may assume that the account is a signer after the call
+/// returns without an error.
+///
+/// ```
+/// use hepha_annotations::solana::require_signer;
+/// use solana_program::{account_info::AccountInfo, pubkey::Pubkey};
+///
+/// let (key, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+/// let (mut lamports, mut data) = (0, [0u8; 0]);
+/// let account = AccountInfo::new(&key, true, false, &mut lamports, &mut data, &owner, false, 0);
+/// assert!(require_signer(&account).is_ok());
+/// ```
+pub fn require_signer(account: &AccountInfo) -> ProgramResult {
+    if !account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    postcondition!(account.is_signer);
+    Ok(())
+}
+
+/// Returns an error unless the account is owned by the given program, which is usually the
+/// program id that the entrypoint is called with. The data of an account that another program
+/// owns can be anything, so it should not be read before this check.
+/// When compiled with HEPHA, the callers may assume that the owner of the account is the program
+/// after the call returns without an error, and the owner check does not report the borrows of
+/// the data of the account that follow the call.
+///
+/// ```
+/// use hepha_annotations::solana::require_owner;
+/// use solana_program::{account_info::AccountInfo, pubkey::Pubkey};
+///
+/// let (key, program_id) = (Pubkey::new_unique(), Pubkey::new_unique());
+/// let (mut lamports, mut data) = (0, [0u8; 8]);
+/// let account =
+///     AccountInfo::new(&key, false, true, &mut lamports, &mut data, &program_id, false, 0);
+/// assert!(require_owner(&account, &program_id).is_ok());
+/// assert!(require_owner(&account, &Pubkey::new_unique()).is_err());
+/// ```
+pub fn require_owner(account: &AccountInfo, program_id: &Pubkey) -> ProgramResult {
+    if account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    postcondition!(account.owner == program_id);
+    Ok(())
+}
+
+/// Moves the given amount of lamports from one account to another. Returns an error, and moves
+/// nothing, if the source has fewer lamports than the amount or if the balance of the destination
+/// would overflow. The source must be owned by the calling program and both accounts must be
+/// writable.
+/// When compiled with HEPHA, the callers may assume that the sum of the balances of the two
+/// accounts is unchanged after the call returns without an error.
+///
+/// ```
+/// use hepha_annotations::solana::checked_transfer;
+/// use solana_program::{account_info::AccountInfo, pubkey::Pubkey};
+///
+/// let (vault_key, user_key, program_id) =
+///     (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+/// let (mut vault_lamports, mut user_lamports) = (100, 0);
+/// let (mut vault_data, mut user_data) = ([0u8; 0], [0u8; 0]);
+/// let vault = AccountInfo::new(
+///     &vault_key, false, true, &mut vault_lamports, &mut vault_data, &program_id, false, 0,
+/// );
+/// let user = AccountInfo::new(
+///     &user_key, true, true, &mut user_lamports, &mut user_data, &program_id, false, 0,
+/// );
+/// assert!(checked_transfer(&vault, &user, 60).is_ok());
+/// assert!(checked_transfer(&vault, &user, 60).is_err());
+/// assert_eq!((vault.lamports(), user.lamports()), (40, 60));
+/// ```
+pub fn checked_transfer(from: &AccountInfo, to: &AccountInfo, amount: u64) -> ProgramResult {
+    let total = (from.lamports() as u128) + (to.lamports() as u128);
+    let from_lamports = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    // The accounts may be the same, in which case nothing moves
+    if from.key == to.key {
+        return Ok(());
+    }
+    let to_lamports = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **from.try_borrow_mut_lamports()? = from_lamports;
+    **to.try_borrow_mut_lamports()? = to_lamports;
+    postcondition!((from.lamports() as u128) + (to.lamports() as u128) == total);
+    Ok(())
+}
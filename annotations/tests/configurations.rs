@@ -0,0 +1,147 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// Compiles the annotation tests both for the normal configuration and for the configuration
+// seen by HEPHA (--cfg hepha), so that a plain `cargo test` covers both expansions of every macro.
+//
+// tests/side_effects.rs is compiled as a test crate and run in each configuration.
+// Every file in tests/configurations/const_eval must compile and run without HEPHA. These files
+// use annotations in const functions, which proves that the expansions refer to no helpers.
+// Every file in tests/configurations/fail must fail to compile in each configuration with the
+// errors given by its `//~ message` comments.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const CONFIGURATIONS: [&[&str]; 2] = [&[], &["--cfg", "hepha"]];
+
+#[test]
+fn side_effects_in_both_configurations() {
+    let out_dir = out_dir("side_effects");
+    for cfg in CONFIGURATIONS {
+        let exe = out_dir.join(exe_name(cfg));
+        let output = rustc(cfg)
+            .arg("--test")
+            .arg("tests/side_effects.rs")
+            .arg("-o")
+            .arg(&exe)
+            .output()
+            .unwrap();
+        assert_success("compiling side_effects.rs", cfg, &output);
+        let output = Command::new(&exe).output().unwrap();
+        assert_success("running side_effects.rs", cfg, &output);
+    }
+}
+
+#[test]
+fn const_eval() {
+    let out_dir = out_dir("const_eval");
+    for file in fixtures("const_eval") {
+        let exe = out_dir.join(file.file_stem().unwrap());
+        let output = rustc(&[]).arg(&file).arg("-o").arg(&exe).output().unwrap();
+        assert_success(&format!("compiling {}", file.display()), &[], &output);
+        let output = Command::new(&exe).output().unwrap();
+        assert_success(&format!("running {}", file.display()), &[], &output);
+    }
+}
+
+#[test]
+fn compile_fail() {
+    let out_dir = out_dir("fail");
+    for file in fixtures("fail") {
+        let expected_errors: Vec<String> = fs::read_to_string(&file)
+            .unwrap()
+            .lines()
+            .filter_map(|line| line.split_once("//~ "))
+            .map(|(_, message)| message.trim().to_string())
+            .collect();
+        assert!(!expected_errors.is_empty(), "{} has no //~ comments", file.display());
+        for cfg in CONFIGURATIONS {
+            let output = rustc(cfg)
+                .arg(&file)
+                .arg("-o")
+                .arg(out_dir.join(exe_name(cfg)))
+                .output()
+                .unwrap();
+            assert!(
+                !output.status.success(),
+                "{} compiled with {cfg:?}",
+                file.display()
+            );
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            for expected_error in &expected_errors {
+                assert!(
+                    stderr.contains(expected_error.as_str()),
+                    "{} with {cfg:?} did not report {expected_error:?}:\n{stderr}",
+                    file.display()
+                );
+            }
+        }
+    }
+}
+
+/// Returns a rustc command that compiles against the hepha_annotations library built by cargo
+/// for this test.
+fn rustc(cfg: &[&str]) -> Command {
+    let deps_dir = std::env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let mut command = Command::new(std::env::var("RUSTC").unwrap_or_else(|_| "rustc".into()));
+    command
+        .args(["--edition", "2021", "--crate-type", "bin"])
+        .args(cfg)
+        .arg("-L")
+        .arg(format!("dependency={}", deps_dir.display()))
+        .arg("--extern")
+        .arg(format!("hepha_annotations={}", find_library(&deps_dir).display()));
+    command
+}
+
+/// Returns the most recently built hepha_annotations rlib in the given directory.
+fn find_library(deps_dir: &Path) -> PathBuf {
+    fs::read_dir(deps_dir)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            let name = p.file_name().unwrap().to_string_lossy();
+            name.starts_with("libhepha_annotations-") && name.ends_with(".rlib")
+        })
+        .max_by_key(|p| p.metadata().and_then(|m| m.modified()).ok())
+        .expect("the hepha_annotations library has not been built")
+}
+
+fn fixtures(kind: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(Path::new("tests/configurations").join(kind))
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "rs"))
+        .collect();
+    files.sort();
+    files
+}
+
+fn out_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn exe_name(cfg: &[&str]) -> &'static str {
+    if cfg.is_empty() {
+        "normal"
+    } else {
+        "hepha"
+    }
+}
+
+fn assert_success(what: &str, cfg: &[&str], output: &Output) {
+    assert!(
+        output.status.success(),
+        "{what} with {cfg:?} failed:\n{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,35 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// Unchecked annotations must not leave a reference to the (non const) helper functions behind
+// when compiled without HEPHA, so they can be used in const functions and evaluated at compile time.
+
+use hepha_annotations::*;
+
+struct Marker {}
+
+const fn double(x: u32) -> u32 {
+    precondition!(x < 1000);
+    precondition!(x < 1000, "x is small");
+    precondition!(x < 1000, "x is {}", x);
+    assume!(x > 0);
+    let result = x * 2;
+    verify!(result >= x);
+    postcondition!(result == x * 2);
+    postcondition!(result == x * 2, "doubled");
+    add_tag!(&result, Marker);
+    if has_tag!(&result, Marker) && does_not_have_tag!(&result, Marker) {
+        result
+    } else {
+        0
+    }
+}
+
+const DOUBLED: u32 = double(21);
+const _: () = assert!(DOUBLED == 42);
+
+fn main() {
+    assert_eq!(DOUBLED, 42);
+}
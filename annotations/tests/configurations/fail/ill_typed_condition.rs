@@ -0,0 +1,13 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// The conditions of unchecked annotations are type checked in every configuration.
+
+use hepha_annotations::*;
+
+fn main() {
+    let x = 1u32;
+    verify!(x + 1); //~ mismatched types
+}
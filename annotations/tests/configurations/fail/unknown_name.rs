@@ -0,0 +1,12 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// Names used in unchecked annotations are resolved in every configuration.
+
+use hepha_annotations::*;
+
+fn main() {
+    precondition!(undefined_value > 0); //~ cannot find value `undefined_value`
+}
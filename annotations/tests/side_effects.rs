@@ -0,0 +1,107 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// Checks how often the conditions passed to the annotation macros are evaluated.
+// tests/configurations.rs also compiles and runs this file with `--cfg hepha`, which is the
+// configuration seen by HEPHA.
+
+#![allow(unexpected_cfgs)]
+
+use std::cell::Cell;
+
+use hepha_annotations::*;
+
+fn counted(counter: &Cell<u32>, result: bool) -> bool {
+    counter.set(counter.get() + 1);
+    result
+}
+
+/// Number of times a condition of an unchecked annotation is evaluated.
+const UNCHECKED: u32 = if cfg!(hepha) { 1 } else { 0 };
+
+/// Number of times a condition of a debug checked annotation is evaluated.
+const DEBUG_CHECKED: u32 = if cfg!(hepha) || cfg!(debug_assertions) {
+    1
+} else {
+    0
+};
+
+#[test]
+fn unchecked_conditions() {
+    let counter = Cell::new(0);
+    assume!(counted(&counter, true));
+    assert_eq!(counter.get(), UNCHECKED);
+
+    let counter = Cell::new(0);
+    precondition!(counted(&counter, true));
+    assert_eq!(counter.get(), UNCHECKED);
+
+    let counter = Cell::new(0);
+    verify!(counted(&counter, true));
+    assert_eq!(counter.get(), UNCHECKED);
+
+    let counter = Cell::new(0);
+    postcondition!(counted(&counter, true));
+    assert_eq!(counter.get(), UNCHECKED);
+}
+
+#[test]
+fn checked_conditions() {
+    let counter = Cell::new(0);
+    checked_assume!(counted(&counter, true));
+    assert_eq!(counter.get(), 1);
+
+    let counter = Cell::new(0);
+    checked_assume_eq!(counted(&counter, true), true);
+    assert_eq!(counter.get(), 1);
+
+    let counter = Cell::new(0);
+    checked_precondition!(counted(&counter, true), "message");
+    assert_eq!(counter.get(), 1);
+
+    let counter = Cell::new(0);
+    checked_postcondition_ne!(counted(&counter, true), false);
+    assert_eq!(counter.get(), 1);
+
+    let counter = Cell::new(0);
+    checked_verify!(counted(&counter, true));
+    assert_eq!(counter.get(), 1);
+}
+
+#[test]
+fn debug_checked_conditions() {
+    let counter = Cell::new(0);
+    debug_checked_assume!(counted(&counter, true));
+    assert_eq!(counter.get(), DEBUG_CHECKED);
+
+    let counter = Cell::new(0);
+    debug_checked_precondition_eq!(counted(&counter, true), true);
+    assert_eq!(counter.get(), DEBUG_CHECKED);
+
+    let counter = Cell::new(0);
+    debug_checked_verify_ne!(counted(&counter, true), false);
+    assert_eq!(counter.get(), DEBUG_CHECKED);
+}
+
+#[test]
+fn value_producing_macros() {
+    let counter = Cell::new(0);
+    let v = abstract_value!({
+        counter.set(counter.get() + 1);
+        7
+    });
+    assert_eq!(v, 7);
+    assert_eq!(counter.get(), 1);
+
+    #[allow(dead_code)]
+    struct SecretTaint {}
+    let x = 1u32;
+    add_tag!(&x, SecretTaint);
+    assert_eq!(x, 1);
+    // The helpers that stand in for tag queries at runtime answer false, whereas the
+    // annotations are vacuously true in the normal configuration.
+    assert_eq!(has_tag!(&x, SecretTaint), !cfg!(hepha));
+    assert_eq!(does_not_have_tag!(&x, SecretTaint), !cfg!(hepha));
+}
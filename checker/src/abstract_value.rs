@@ -313,6 +313,7 @@ impl AbstractValue {
             | Expression::CompileTimeConstant { .. }
             | Expression::HeapBlock { .. }
             | Expression::HeapBlockLayout { .. }
+            | Expression::Quantified { .. }
             | Expression::UnknownTagCheck { .. } => {
                 return Rc::new(FALSE);
             }
@@ -323,15 +324,17 @@ impl AbstractValue {
             | Expression::Variable { path, .. } => {
                 let expression_size = self.expression_size.saturating_add(1);
                 let root = path.get_path_root();
-                let operand =
-                    if root != path && tag.is_propagated_by(TagPropagation::SuperComponent) {
-                        AbstractValue::make_typed_unknown(
-                            ExpressionType::NonPrimitive,
-                            Path::new_tag_field(root.clone()),
-                        )
-                    } else {
-                        Rc::new(self.clone())
-                    };
+                let operand = if root != path
+                    && tag.is_propagated_by(TagPropagation::SuperComponent)
+                    && tag.is_propagated_between(root, path)
+                {
+                    AbstractValue::make_typed_unknown(
+                        ExpressionType::NonPrimitive,
+                        Path::new_tag_field(root.clone()),
+                    )
+                } else {
+                    Rc::new(self.clone())
+                };
                 return AbstractValue::make_from(
                     Expression::UnknownTagCheck {
                         operand,
@@ -491,6 +494,7 @@ impl AbstractValue {
             | Expression::CompileTimeConstant { .. }
             | Expression::HeapBlock { .. }
             | Expression::HeapBlockLayout { .. }
+            | Expression::Quantified { .. }
             | Expression::UnknownTagCheck { .. } => {
                 return Rc::new(TRUE);
             }
@@ -737,6 +741,29 @@ impl AbstractValue {
             expression_size,
         )
     }
+
+    /// Creates an abstract value which is true if operand is true for every value of the bound
+    /// variable, which is the variable with the given path and type.
+    #[logfn_inputs(TRACE)]
+    pub fn make_quantified(
+        bound_variable: Rc<Path>,
+        var_type: ExpressionType,
+        operand: Rc<AbstractValue>,
+    ) -> Rc<AbstractValue> {
+        if operand.as_bool_if_known().is_some() {
+            // The bound variable does not occur in a constant.
+            return operand;
+        }
+        let expression_size = operand.expression_size.saturating_add(1);
+        AbstractValue::make_from(
+            Expression::Quantified {
+                bound_variable,
+                var_type,
+                operand,
+            },
+            expression_size,
+        )
+    }
 }
 
 pub trait AbstractValueTrait: Sized {
@@ -759,6 +786,7 @@ pub trait AbstractValueTrait: Sized {
     fn bit_or(&self, other: Self) -> Self;
     #[must_use]
     fn bit_xor(&self, other: Self) -> Self;
+    fn bound_interval(&self) -> Option<(i128, i128)>;
     #[must_use]
     fn cast(&self, target_type: ExpressionType) -> Self;
     #[must_use]
@@ -791,6 +819,7 @@ pub trait AbstractValueTrait: Sized {
     fn intrinsic_floating_point_unary(&self, name: KnownNames) -> Self;
     fn inverse_implies(&self, other: &Rc<AbstractValue>) -> bool;
     fn inverse_implies_not(&self, other: &Rc<AbstractValue>) -> bool;
+    fn is_below(&self, other: &Self) -> bool;
     fn is_bottom(&self) -> bool;
     fn is_compile_time_constant(&self) -> bool;
     fn is_contained_in_zeroed_heap_block(&self) -> bool;
@@ -826,6 +855,8 @@ pub trait AbstractValueTrait: Sized {
     #[must_use]
     fn remove_conjuncts_that_depend_on(&self, variables: &HashSet<Rc<Path>>) -> Self;
     #[must_use]
+    fn remove_tag(&self, tag: Tag) -> Self;
+    #[must_use]
     fn shift_left(&self, other: Self) -> Self;
     #[must_use]
     fn shl_overflows(&self, other: Self, target_type: ExpressionType) -> Self;
@@ -907,6 +938,7 @@ pub trait AbstractValueTrait: Sized {
     ) -> Self;
     fn get_cached_interval(&self) -> Rc<IntervalDomain>;
     fn get_as_interval(&self) -> IntervalDomain;
+    fn get_as_bound_interval(&self) -> IntervalDomain;
     fn get_is_non_null(&self) -> bool;
     fn get_cached_tags(&self) -> Rc<TagDomain>;
     fn get_path_root<'a>(&'a self, default: &'a Rc<Path>) -> &'a Rc<Path>;
@@ -1902,6 +1934,18 @@ impl AbstractValueTrait for Rc<AbstractValue> {
         )
     }
 
+    /// Returns the smallest and largest values that self can have at runtime, if they are known
+    /// from the structure of its expression. Operations that may wrap around are assumed to
+    /// produce any value of their type, so the bounds hold regardless of overflow checks.
+    #[logfn_inputs(TRACE)]
+    fn bound_interval(&self) -> Option<(i128, i128)> {
+        let interval = self.get_as_bound_interval();
+        if interval.is_bottom() {
+            return None;
+        }
+        Some((interval.lower_bound()?, interval.upper_bound()?))
+    }
+
     /// Returns an element that is "self as target_type".
     #[logfn_inputs(TRACE)]
     fn cast(&self, target_type: ExpressionType) -> Rc<AbstractValue> {
@@ -3401,6 +3445,39 @@ impl AbstractValueTrait for Rc<AbstractValue> {
         false
     }
 
+    /// True if self is known to be below other in the partial order of which join is the least
+    /// upper bound, i.e. x and y are both below x.join(y). A value of a path that is derived from
+    /// a widened join of the path is below the widened join, which stands for all of the values
+    /// that flow into the path. Unlike subset, this holds for the result of every join, so it can
+    /// be used to check that a fixed point computation is monotone.
+    /// A false result does not imply that self is not below other.
+    #[logfn_inputs(TRACE)]
+    fn is_below(&self, other: &Rc<AbstractValue>) -> bool {
+        if self.expression.eq(&other.expression) || self.is_bottom() || other.is_top() {
+            return true;
+        }
+        match &other.expression {
+            // x is below (left join right) if x is below left or right.
+            Expression::Join { left, right } => {
+                if self.is_below(left) || self.is_below(right) {
+                    return true;
+                }
+            }
+            // x is below widen { z } at path if x is derived from the same widening or below z.
+            Expression::WidenedJoin { path, operand } => {
+                if self.get_widened_subexpression(path).is_some() || self.is_below(operand) {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+        match &self.expression {
+            // (left join right) is below x if both left and right are below x.
+            Expression::Join { left, right } => left.is_below(other) && right.is_below(other),
+            _ => false,
+        }
+    }
+
     /// True if the set of concrete values that correspond to this domain is empty.
     #[logfn_inputs(TRACE)]
     fn is_bottom(&self) -> bool {
@@ -4899,6 +4976,48 @@ impl AbstractValueTrait for Rc<AbstractValue> {
         }
     }
 
+    /// Returns an element that is self without the given tag, where the tag was attached to self,
+    /// or to the values that self may be, by add_tag. A tag that self has because one of its
+    /// operands has it stays.
+    #[logfn_inputs(TRACE)]
+    fn remove_tag(&self, tag: Tag) -> Rc<AbstractValue> {
+        if self.get_cached_tags().has_tag(&tag) == BoolDomain::False {
+            return self.clone();
+        }
+        match &self.expression {
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => condition
+                .conditional_expression(consequent.remove_tag(tag), alternate.remove_tag(tag)),
+            Expression::Join { left, right } => left.remove_tag(tag).join(right.remove_tag(tag)),
+            Expression::Switch {
+                discriminator,
+                cases,
+                default,
+            } => discriminator.switch(
+                cases
+                    .iter()
+                    .map(|(case_val, result_val)| (case_val.clone(), result_val.remove_tag(tag)))
+                    .collect(),
+                default.remove_tag(tag),
+            ),
+            Expression::TaggedExpression {
+                operand,
+                tag: operand_tag,
+            } => {
+                if *operand_tag == tag {
+                    operand.remove_tag(tag)
+                } else {
+                    operand.remove_tag(tag).add_tag(*operand_tag)
+                }
+            }
+            Expression::WidenedJoin { path, operand } => operand.remove_tag(tag).widen(path),
+            _ => self.clone(),
+        }
+    }
+
     /// Returns an element that is "self << other".
     #[logfn_inputs(TRACE)]
     fn shift_left(&self, other: Rc<AbstractValue>) -> Rc<AbstractValue> {
@@ -5693,6 +5812,182 @@ impl AbstractValueTrait for Rc<AbstractValue> {
         }
     }
 
+    /// Constructs an element of the Interval domain that contains every value that self can have
+    /// at runtime. Unlike get_as_interval, the result is never narrower than what the operations
+    /// of the expression can produce when they wrap around.
+    #[logfn_inputs(TRACE)]
+    fn get_as_bound_interval(&self) -> IntervalDomain {
+        // Constants are typed as 128 bit integers, so the type of an operation with a constant
+        // operand is that of the other operand.
+        let result_type = match &self.expression {
+            Expression::Add { left, right }
+            | Expression::BitAnd { left, right }
+            | Expression::Div { left, right }
+            | Expression::Mul { left, right }
+            | Expression::Rem { left, right }
+            | Expression::Sub { left, right } => {
+                if left.is_compile_time_constant() {
+                    right.expression.infer_type()
+                } else {
+                    left.expression.infer_type()
+                }
+            }
+            _ => self.expression.infer_type(),
+        };
+        let type_range = IntervalDomain::from(result_type);
+        // A result that does not fit its type has wrapped around and can be any value of the type.
+        let unless_wrapped = |interval: IntervalDomain| {
+            if interval.is_contained_in(result_type) {
+                interval
+            } else {
+                type_range.clone()
+            }
+        };
+        match &self.expression {
+            Expression::Add { left, right } => unless_wrapped(
+                left.get_as_bound_interval()
+                    .add(&right.get_as_bound_interval()),
+            ),
+            Expression::BitAnd { left, right } => {
+                // x & y is between 0 and y if y is not negative, whatever x is.
+                let mut interval = type_range.clone();
+                for operand in [left, right] {
+                    if let Some((lower, upper)) = operand.bound_interval() {
+                        if lower >= 0 {
+                            interval = interval.intersect(
+                                &IntervalDomain::from(0i128).widen(&IntervalDomain::from(upper)),
+                            );
+                        }
+                    }
+                }
+                interval
+            }
+            Expression::Cast {
+                operand,
+                target_type,
+            } => {
+                let interval = operand.get_as_bound_interval();
+                if interval.is_contained_in(*target_type) {
+                    interval
+                } else {
+                    IntervalDomain::from(*target_type)
+                }
+            }
+            Expression::CompileTimeConstant(ConstantDomain::I128(val)) => (*val).into(),
+            Expression::CompileTimeConstant(ConstantDomain::U128(val)) => {
+                if *val < (i128::MAX as u128) {
+                    (*val).into()
+                } else {
+                    interval_domain::BOTTOM
+                }
+            }
+            Expression::ConditionalExpression {
+                consequent,
+                alternate,
+                ..
+            } => consequent
+                .get_as_bound_interval()
+                .widen(&alternate.get_as_bound_interval()),
+            Expression::Div { left, right } => {
+                match (left.bound_interval(), right.bound_interval()) {
+                    (Some((lower, upper)), Some((min_divisor, max_divisor))) if min_divisor > 0 => {
+                        // Division truncates towards zero, so a negative lower bound is smallest
+                        // when divided by the smallest divisor.
+                        let quotients = [
+                            lower / min_divisor,
+                            lower / max_divisor,
+                            upper / min_divisor,
+                            upper / max_divisor,
+                        ];
+                        let smallest = quotients.iter().min().copied().unwrap_or(lower);
+                        let largest = quotients.iter().max().copied().unwrap_or(upper);
+                        unless_wrapped(
+                            IntervalDomain::from(smallest).widen(&IntervalDomain::from(largest)),
+                        )
+                    }
+                    _ => type_range,
+                }
+            }
+            Expression::IntrinsicBitVectorUnary {
+                name:
+                    KnownNames::StdIntrinsicsCtlz
+                    | KnownNames::StdIntrinsicsCtlzNonzero
+                    | KnownNames::StdIntrinsicsCtpop
+                    | KnownNames::StdIntrinsicsCttz
+                    | KnownNames::StdIntrinsicsCttzNonzero,
+                bit_length,
+                ..
+            } => IntervalDomain::from(0i128).widen(&IntervalDomain::from(*bit_length as i128)),
+            Expression::Join { left, right, .. } => left
+                .get_as_bound_interval()
+                .widen(&right.get_as_bound_interval()),
+            Expression::Mul { left, right } => unless_wrapped(
+                left.get_as_bound_interval()
+                    .mul(&right.get_as_bound_interval()),
+            ),
+            Expression::Neg { operand } => unless_wrapped(operand.get_as_bound_interval().neg()),
+            Expression::Rem { left, right } => {
+                let Some((lower, upper)) = right.bound_interval() else {
+                    return type_range;
+                };
+                // The magnitude of the remainder is less than that of the divisor.
+                let max_magnitude = lower.unsigned_abs().max(upper.unsigned_abs());
+                let Ok(max_remainder) = i128::try_from(max_magnitude.saturating_sub(1)) else {
+                    return type_range;
+                };
+                // A remainder is never negative if its dividend is not. An unsigned remainder,
+                // such as the ones of unsigned_modulo, is not negative either, even if its
+                // dividend has been retyped from a signed integer.
+                let unsigned = result_type.is_unsigned_integer()
+                    || matches!(
+                        right.expression,
+                        Expression::CompileTimeConstant(ConstantDomain::U128(_))
+                    );
+                // Otherwise the remainder has the sign of the dividend and its magnitude is at
+                // most that of the dividend.
+                let (lower, upper) = match left.bound_interval() {
+                    Some((lower, upper)) if lower >= 0 => (0, max_remainder.min(upper)),
+                    _ if unsigned => (0, max_remainder),
+                    Some((lower, upper)) if upper <= 0 => ((-max_remainder).max(lower), 0),
+                    _ => (-max_remainder, max_remainder),
+                };
+                IntervalDomain::from(lower).widen(&IntervalDomain::from(upper))
+            }
+            Expression::Shr { left, right } => {
+                match (left.bound_interval(), right.bound_interval()) {
+                    (Some((lower, upper)), Some((min_shift, max_shift)))
+                        if lower >= 0 && min_shift >= 0 && max_shift < 128 =>
+                    {
+                        IntervalDomain::from(lower >> max_shift)
+                            .widen(&IntervalDomain::from(upper >> min_shift))
+                    }
+                    _ => type_range,
+                }
+            }
+            Expression::Sub { left, right } => unless_wrapped(
+                left.get_as_bound_interval()
+                    .sub(&right.get_as_bound_interval()),
+            ),
+            Expression::Switch { cases, default, .. } => cases
+                .iter()
+                .fold(default.get_as_bound_interval(), |acc, (_, result)| {
+                    acc.widen(&result.get_as_bound_interval())
+                }),
+            Expression::TaggedExpression { operand, .. } => operand.get_as_bound_interval(),
+            Expression::Variable { .. } | Expression::WidenedJoin { .. } => {
+                // Values that were abstracted into variables, and widened loop variables, carry
+                // the bounds that were recorded for them.
+                let recorded = self.get_cached_interval();
+                if recorded.is_bottom() || !recorded.is_contained_in(result_type) {
+                    type_range
+                } else {
+                    recorded.intersect(&type_range)
+                }
+            }
+            _ => type_range,
+        }
+    }
+
     /// Determines if the expression is known at compile time to always be a non-null pointer.
     #[logfn_inputs(TRACE)]
     fn get_is_non_null(&self) -> bool {
@@ -5749,6 +6044,7 @@ impl AbstractValueTrait for Rc<AbstractValue> {
             | Expression::CompileTimeConstant { .. }
             | Expression::HeapBlock { .. }
             | Expression::HeapBlockLayout { .. }
+            | Expression::Quantified { .. }
             | Expression::Reference { .. }
             | Expression::UnknownTagCheck { .. } => return TagDomain::empty_set(),
 
@@ -5919,6 +6215,7 @@ impl AbstractValueTrait for Rc<AbstractValue> {
             | Expression::IntrinsicFloatingPointUnary { operand, .. }
             | Expression::Neg { operand }
             | Expression::LogicalNot { operand }
+            | Expression::Quantified { operand, .. }
             | Expression::TaggedExpression { operand, .. }
             | Expression::Transmute { operand, .. }
             | Expression::UnknownTagCheck { operand, .. } => {
@@ -6245,6 +6542,15 @@ impl AbstractValueTrait for Rc<AbstractValue> {
             Expression::Or { left, right } => left
                 .refine_parameters_and_paths(args, result, pre_env, post_env, fresh)
                 .or(right.refine_parameters_and_paths(args, result, pre_env, post_env, fresh)),
+            Expression::Quantified {
+                bound_variable,
+                var_type,
+                operand,
+            } => AbstractValue::make_quantified(
+                bound_variable.clone(),
+                *var_type,
+                operand.refine_parameters_and_paths(args, result, pre_env, post_env, fresh),
+            ),
             Expression::Reference(path) => {
                 // if the path is a parameter, the reference is an artifact of its type
                 // and needs to be removed in the call context
@@ -6679,6 +6985,15 @@ impl AbstractValueTrait for Rc<AbstractValue> {
                         .or(right.refine_with(path_condition, depth + 1))
                 }
             }
+            Expression::Quantified {
+                bound_variable,
+                var_type,
+                operand,
+            } => AbstractValue::make_quantified(
+                bound_variable.clone(),
+                *var_type,
+                operand.refine_with(path_condition, depth + 1),
+            ),
             Expression::Reference(..) | Expression::InitialParameterValue { .. } => {
                 // We could refine their paths, which will increase precision, but it does not
                 // currently seem cost-effective. This does not affect soundness.
@@ -7022,6 +7337,7 @@ impl AbstractValueTrait for Rc<AbstractValue> {
             | Expression::IntrinsicFloatingPointUnary { operand, .. }
             | Expression::Neg { operand }
             | Expression::LogicalNot { operand }
+            | Expression::Quantified { operand, .. }
             | Expression::TaggedExpression { operand, .. }
             | Expression::Transmute { operand, .. }
             | Expression::UnknownTagCheck { operand, .. } => operand.uses(variables),
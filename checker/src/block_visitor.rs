@@ -7,12 +7,14 @@ use std::borrow::Borrow;
 use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Formatter, Result};
 use std::rc::Rc;
+use std::time::Instant;
 
 use log_derive::*;
 
 use hepha_annotations::*;
 use rustc_hir::def_id::DefId;
 use rustc_index::{Idx, IndexVec};
+use rustc_errors::{Applicability, Diag};
 use rustc_middle::mir;
 use rustc_middle::mir::interpret::{alloc_range, GlobalAlloc, Scalar};
 use rustc_middle::mir::{ConstValue, UnwindTerminateReason};
@@ -29,10 +31,17 @@ use rustc_trait_selection::infer::TyCtxtInferExt;
 
 use crate::abstract_value::{AbstractValue, AbstractValueTrait, BOTTOM};
 use crate::body_visitor::BodyVisitor;
+use crate::call_states::CallState;
+use crate::checker_registry::{self, Severity};
 use crate::call_visitor::CallVisitor;
 use crate::constant_domain::{ConstantDomain, FunctionReference};
+use crate::diagnostic_export::FindingProperties;
 use crate::environment::Environment;
 use crate::expression::{Expression, ExpressionType};
+use crate::fact_export::Facts;
+use crate::fixed_point_visitor::render_condition;
+use crate::fixes;
+use crate::interval_domain::IntervalDomain;
 use crate::k_limits;
 use crate::known_names::KnownNames;
 use crate::options::DiagLevel;
@@ -44,7 +53,11 @@ use crate::tag_domain::Tag;
 use crate::type_visitor::TypeVisitor;
 use crate::utils;
 use crate::{abstract_value, known_names};
-use crate::contract_errors::BlockStatement;
+use crate::contract_errors::{
+    self, Balance, BalanceConservationChecker, BlockStatement, DataWriteEnd, LedgerChecker,
+    NoOpTransfer, ResultUnusedChecker, SolUnit, UncheckedIndexChecker,
+};
+use crate::solana_compat::{self, AccountField, AccountInfoLayout, SystemInstruction};
 
 /// Holds the state for the basic block visitor
 pub struct BlockVisitor<'block, 'analysis, 'compilation, 'tcx> {
@@ -72,12 +85,62 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         self.bv.type_visitor_mut()
     }
 
-    /// Visits each statement in order and then visits the terminator.
+    /// Visits each statement in order and then visits the terminator, adding the time that this
+    /// takes to the time spent on the block.
     #[logfn_inputs(TRACE)]
     pub fn visit_basic_block(
         &mut self,
         bb: mir::BasicBlock,
         terminator_state: &mut HashMap<mir::BasicBlock, Environment>,
+    ) {
+        let start_instant = Instant::now();
+        self.visit_statements_and_terminator(bb, terminator_state);
+        *self.bv.block_times.entry(bb).or_default() += start_instant.elapsed();
+    }
+
+    /// Returns the integer constants that the statements of bb compare values with, such as
+    /// the bound of a loop condition or the length of an array in a bounds check.
+    pub fn comparison_constants(&mut self, bb: mir::BasicBlock) -> Vec<i128> {
+        let mut constants = Vec::new();
+        for statement in self.bv.mir[bb].statements.iter() {
+            let mir::StatementKind::Assign(box (_, rvalue)) = &statement.kind else {
+                continue;
+            };
+            let mir::Rvalue::BinaryOp(
+                mir::BinOp::Eq
+                | mir::BinOp::Ne
+                | mir::BinOp::Lt
+                | mir::BinOp::Le
+                | mir::BinOp::Gt
+                | mir::BinOp::Ge,
+                box (left, right),
+            ) = rvalue
+            else {
+                continue;
+            };
+            for operand in [left, right] {
+                if let mir::Operand::Constant(..) = operand {
+                    match &self.visit_operand(operand).expression {
+                        Expression::CompileTimeConstant(ConstantDomain::I128(value)) => {
+                            constants.push(*value);
+                        }
+                        Expression::CompileTimeConstant(ConstantDomain::U128(value)) => {
+                            if let Ok(value) = i128::try_from(*value) {
+                                constants.push(value);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        constants
+    }
+
+    fn visit_statements_and_terminator(
+        &mut self,
+        bb: mir::BasicBlock,
+        terminator_state: &mut HashMap<mir::BasicBlock, Environment>,
     ) {
         let mir::BasicBlockData {
             ref statements,
@@ -96,6 +159,8 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
             terminator_state.insert(bb, self.bv.current_environment.clone());
         } else {
             check_for_early_return!(self.bv);
+            self.check_wrapping_operations(bb);
+            self.check_narrowing_casts(bb);
             location.statement_index = terminator_index;
         }
 
@@ -116,7 +181,8 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         debug!("env {:?}", self.bv.current_environment);
         self.bv.current_location = location;
         self.bv
-            .reentrancy_checker
+            .contract_checkers
+            .reentrancy
             .block_statements
             .entry(bb)
             .or_insert(Vec::new())
@@ -151,10 +217,209 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         }
     }
 
+    /// True if the current call is in the condition of a postcondition, that is, if the call of
+    /// a postcondition macro in the source of the body surrounds it.
+    pub fn is_in_postcondition(&mut self) -> bool {
+        let span = self.bv.current_span.source_callsite();
+        let (tcx, mir) = (self.bv.tcx, self.bv.mir);
+        mir.basic_blocks.iter().any(|data| {
+            let mir::TerminatorKind::Call { func, .. } = &data.terminator().kind else {
+                return false;
+            };
+            let Some((def_id, _)) = func.const_fn_def() else {
+                return false;
+            };
+            self.bv.cv.known_names_cache.get(tcx, def_id) == KnownNames::MiraiPostcondition
+                && data
+                    .terminator()
+                    .source_info
+                    .span
+                    .source_callsite()
+                    .contains(span)
+        })
+    }
+
+    /// Returns the value that the argument of the current call had when the function was
+    /// entered. The assignments of the current block that compute the argument are visited again
+    /// in the first environment of the body, where the parameters are not yet provisioned, so
+    /// that the parts of the value that the parameters give are their initial values, which the
+    /// callers of the function refine against their arguments. The other locals that the
+    /// assignments read keep the values that they have now.
+    fn value_at_function_entry(&mut self, argument: &mir::Operand<'tcx>) -> Rc<AbstractValue> {
+        let Some(argument_place) = argument.place() else {
+            return self.visit_operand(argument);
+        };
+        let mir = self.bv.mir;
+        let location = self.bv.current_location;
+        let span = self.bv.current_span;
+        let statements = &mir.basic_blocks[location.block].statements;
+        let mut read_locals = HashSet::from([argument_place.local]);
+        let mut assignments = Vec::new();
+        for (index, statement) in statements.iter().enumerate().rev() {
+            let mir::StatementKind::Assign(box (place, rvalue)) = &statement.kind else {
+                continue;
+            };
+            if !read_locals.contains(&place.local) {
+                continue;
+            }
+            // The fields of aggregates are assigned one by one
+            if place.projection.is_empty() {
+                read_locals.remove(&place.local);
+            }
+            add_read_locals(rvalue, &mut read_locals);
+            assignments.push(index);
+        }
+        let mut entry_environment = self.bv.first_environment.clone();
+        entry_environment.entry_condition = self.bv.current_environment.entry_condition.clone();
+        for local in read_locals {
+            if (1..=mir.arg_count).contains(&local.as_usize()) {
+                continue;
+            }
+            let root = self.get_path_for_place(&mir::Place::from(local));
+            for (path, value) in self.bv.current_environment.value_map.iter() {
+                if *path == root || path.is_rooted_by(&root) {
+                    entry_environment
+                        .value_map
+                        .insert_mut(path.clone(), value.clone());
+                }
+            }
+        }
+        let current_environment =
+            std::mem::replace(&mut self.bv.current_environment, entry_environment);
+        for index in assignments.into_iter().rev() {
+            let statement = &statements[index];
+            if let mir::StatementKind::Assign(box (place, rvalue)) = &statement.kind {
+                self.bv.current_location = mir::Location {
+                    block: location.block,
+                    statement_index: index,
+                };
+                self.bv.current_span = statement.source_info.span;
+                self.visit_assign(place, rvalue);
+            }
+        }
+        self.bv.current_location = location;
+        self.bv.current_span = span;
+        let value = self.visit_operand(argument);
+        self.bv.current_environment = current_environment;
+        value
+    }
+
     /// Write the RHS Rvalue to the LHS Place.
     #[logfn_inputs(TRACE)]
     fn visit_assign(&mut self, place: &mir::Place<'tcx>, rvalue: &mir::Rvalue<'tcx>) {
-        self.bv.reentrancy_checker.current_assign_destination = Some(*place);
+        self.bv.contract_checkers.reentrancy.current_assign_destination = Some(*place);
+        self.bv.contract_checkers.reentrancy.visit_assign(
+            self.bv.tcx,
+            self.bv.mir,
+            place,
+            rvalue,
+        );
+        self.bv.contract_checkers.time_unit.visit_assign(
+            self.bv.tcx,
+            self.bv.mir,
+            place,
+            rvalue,
+            self.bv.current_span,
+        );
+        self.bv.contract_checkers.time_manipulation.visit_assign(
+            self.bv.tcx,
+            self.bv.mir,
+            place,
+            rvalue,
+            self.bv.current_span,
+        );
+        self.bv.contract_checkers.numerical_precision.visit_assign(
+            self.bv.tcx,
+            self.bv.mir,
+            place,
+            rvalue,
+            self.bv.current_span,
+        );
+        self.bv.contract_checkers.account_window.visit_assign(
+            self.bv.tcx,
+            self.bv.mir,
+            self.bv.current_location,
+            place,
+            rvalue,
+        );
+        self.bv.contract_checkers.account_fields.visit_assign(
+            self.bv.tcx,
+            self.bv.mir,
+            place,
+            rvalue,
+        );
+        let data_length = &mut self.bv.contract_checkers.account_data_length;
+        if let Some((account, end)) =
+            data_length.visit_assign(self.bv.tcx, self.bv.mir, place, rvalue)
+        {
+            self.check_account_data_write(account, end);
+        }
+        self.bv.contract_checkers.owner_check.visit_assign(
+            self.bv.tcx,
+            self.bv.mir,
+            place,
+            rvalue,
+        );
+        self.bv.contract_checkers.anchor_accounts.visit_assign(
+            self.bv.tcx,
+            self.bv.mir,
+            place,
+            rvalue,
+        );
+        let checkers = &mut self.bv.contract_checkers;
+        checkers.writable_check.visit_assign(
+            self.bv.tcx,
+            self.bv.mir,
+            &checkers.amount_provenance,
+            self.bv.current_location,
+            place,
+            rvalue,
+        );
+        let checkers = &mut self.bv.contract_checkers;
+        if let Some((balance, left, right)) = checkers.lamport_underflow.visit_assign(
+            self.bv.tcx,
+            self.bv.mir,
+            &checkers.amount_provenance,
+            self.bv.current_location,
+            place,
+            rvalue,
+        ) {
+            self.check_balance_subtraction(balance, left, right);
+        }
+        let provenance = &self.bv.contract_checkers.amount_provenance;
+        self.bv.contract_checkers.account_close.visit_assign(
+            self.bv.tcx,
+            self.bv.mir,
+            self.bv.current_location.block,
+            place,
+            rvalue,
+            |account| provenance.account_of(account),
+        );
+        let checkers = &mut self.bv.contract_checkers;
+        checkers.distinct_accounts.visit_assign(
+            self.bv.tcx,
+            self.bv.mir,
+            &checkers.amount_provenance,
+            place,
+            rvalue,
+        );
+        self.bv.contract_checkers.non_persistent_state.visit_assign(
+            self.bv.tcx,
+            self.bv.mir,
+            place,
+            rvalue,
+            self.bv.current_span,
+        );
+        self.bv
+            .contract_checkers
+            .unvalidated_deserialization
+            .visit_assign(self.bv.tcx, self.bv.mir, place, rvalue);
+        if let Some((account, amount, credit)) =
+            self.bv.contract_checkers.lamport_underflow.transfer(rvalue)
+        {
+            let distinct_accounts = &mut self.bv.contract_checkers.distinct_accounts;
+            distinct_accounts.record_transfer(account, amount, credit);
+        }
         let mut path = self.visit_lh_place(place);
         match &path.value {
             PathEnum::PhantomData => {
@@ -173,7 +438,201 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
             .get_rustc_place_type(place, self.bv.current_span);
         self.type_visitor_mut()
             .set_path_rustc_type(path.clone(), pty);
-        self.visit_rvalue(path, rvalue);
+        let written_balance = self
+            .bv
+            .contract_checkers
+            .lamport_underflow
+            .written_balance(place);
+        let delta_field = written_balance
+            .and_then(|balance| self.bv.contract_checkers.ledger.delta_field(balance));
+        let writes_lamports =
+            self.bv.check_for_errors && matches!(written_balance, Some(Balance::Lamports(_)));
+        // Writes of the lamports of accounts, for --export-facts, are here
+        if let Some(Balance::Lamports(account)) = written_balance {
+            let account = account.and_then(|local| contract_errors::debug_name(self.bv.mir, local));
+            if let Some((facts, function, span)) = self.facts_at_current_span() {
+                facts.add_lamports_write(&function, account.as_deref().unwrap_or("_"), span);
+            }
+        }
+        let total_fields = written_balance
+            .filter(|_| pty.is_integral())
+            .map(BalanceConservationChecker::fields_of);
+        // Balances are checked for amounts that are computed with floating point arithmetic
+        let writes_balance = self.bv.check_for_errors && written_balance.is_some();
+        if delta_field.is_none() && !writes_balance && total_fields.is_none() {
+            self.visit_rvalue(path, rvalue);
+            return;
+        }
+        let old_value = self.bv.lookup_path_and_refine_result(path.clone(), pty);
+        self.visit_rvalue(path.clone(), rvalue);
+        let new_value = self.bv.lookup_path_and_refine_result(path, pty);
+        if writes_lamports {
+            self.check_lamport_change(&old_value, &new_value);
+            if let Some(Balance::Lamports(account)) = written_balance {
+                self.check_lamport_drain(account, &new_value);
+            }
+        }
+        if writes_balance {
+            let sink = match written_balance {
+                Some(Balance::Lamports(_)) => "written to the lamports of an account",
+                _ => "written to an entry of a map of balances",
+            };
+            self.check_float_amount(&new_value, sink);
+        }
+        let delta = new_value.subtract(old_value);
+        if let Some(delta_field) = delta_field {
+            self.update_ledger(delta_field, delta.clone());
+        }
+        if let Some(total_fields) = total_fields {
+            self.update_balance_total(total_fields, delta);
+        }
+    }
+
+    /// Checks if a write of the lamports of an account leaves them unchanged on every path that
+    /// reaches it, which is a transfer of zero lamports.
+    fn check_lamport_change(
+        &mut self,
+        old_value: &Rc<AbstractValue>,
+        new_value: &Rc<AbstractValue>,
+    ) {
+        let unchanged = new_value.equals(old_value.clone());
+        let (unchanged_as_bool, entry_cond_as_bool) =
+            self.bv.check_condition_value_and_reachability(&unchanged);
+        if entry_cond_as_bool == Some(false) || unchanged_as_bool != Some(true) {
+            return;
+        }
+        self.bv
+            .contract_checkers
+            .no_op_transfer
+            .record(self.bv.current_span, NoOpTransfer::ZeroAmount);
+    }
+
+    /// Checks if a write of the lamports of an account leaves it without lamports on every path
+    /// that reaches it, which closes the account.
+    fn check_lamport_drain(&mut self, account: Option<mir::Local>, new_value: &Rc<AbstractValue>) {
+        let zero = self.get_u128_const_val(0);
+        let drained = new_value.equals(zero);
+        let (drained_as_bool, entry_cond_as_bool) =
+            self.bv.check_condition_value_and_reachability(&drained);
+        if entry_cond_as_bool == Some(false) || drained_as_bool != Some(true) {
+            return;
+        }
+        let anchor_accounts = &self.bv.contract_checkers.anchor_accounts;
+        let anchor_account = account.and_then(|account| anchor_accounts.account_of(account));
+        self.bv.contract_checkers.account_close.record_drain(
+            self.bv.current_span,
+            self.bv.current_location.block,
+            account,
+            anchor_account,
+        );
+    }
+
+    /// Records an amount that is computed with floating point arithmetic, which is rounded and may
+    /// create or lose money, if it reaches the given sink, such as the lamports of an account.
+    fn check_float_amount(&mut self, amount: &Rc<AbstractValue>, sink: &'static str) {
+        if !self.carries_tag(amount, Tag::FLOATING_POINT) {
+            return;
+        }
+        self.bv
+            .contract_checkers
+            .float_in_money_path
+            .record(self.bv.current_span, sink);
+    }
+
+    /// Adds the given change to the given model field of the account that the ledger is declared
+    /// to add up to, and records whether the ledger and the lamports of the account are known to
+    /// have changed by the same amount afterwards.
+    fn update_ledger(&mut self, field: &str, delta: Rc<AbstractValue>) {
+        let Some(field_path) = self.bv.ledger_delta_path(field) else {
+            return;
+        };
+        let old_delta = self.ledger_delta(field);
+        self.bv
+            .update_value_at(field_path, old_delta.addition(delta));
+        let balanced = self.ledger_is_balanced();
+        let (balanced_as_bool, entry_cond_as_bool) =
+            self.bv.check_condition_value_and_reachability(&balanced);
+        if entry_cond_as_bool == Some(false) {
+            return;
+        }
+        self.bv.contract_checkers.ledger.record_update(
+            self.bv.current_location,
+            self.bv.current_span,
+            balanced_as_bool == Some(true),
+        );
+    }
+
+    // Returns the change of the ledger or of the lamports of the account held by the given model
+    // field, which is zero unless the function has updated them
+    fn ledger_delta(&mut self, field: &str) -> Rc<AbstractValue> {
+        let value = self
+            .bv
+            .ledger_delta_path(field)
+            .and_then(|path| self.bv.current_environment.value_at(&path).cloned());
+        value.unwrap_or_else(|| self.get_i128_const_val(0))
+    }
+
+    // Returns the condition that the ledger and the lamports of the account have changed by the
+    // same amount, which keeps the declared invariant that they add up
+    fn ledger_is_balanced(&mut self) -> Rc<AbstractValue> {
+        let ledger_delta = self.ledger_delta(LedgerChecker::LEDGER_DELTA);
+        let lamports_delta = self.ledger_delta(LedgerChecker::LAMPORTS_DELTA);
+        ledger_delta.equals(lamports_delta)
+    }
+
+    /// Adds the given change of a balance to the total of the balances of its kind, held by the
+    /// given model fields along with the number of their updates, and records whether the total
+    /// is known to be unchanged afterwards, unless this is the first update of the kind.
+    pub fn update_balance_total(
+        &mut self,
+        (delta_field, updates_field): (&'static str, &'static str),
+        delta: Rc<AbstractValue>,
+    ) {
+        let old_delta = self.balance_total(delta_field);
+        let delta_path = self.bv.balance_total_path(delta_field);
+        self.bv
+            .update_value_at(delta_path, old_delta.addition(delta));
+        let old_updates = self.balance_total(updates_field);
+        let one = self.get_i128_const_val(1);
+        let updates_path = self.bv.balance_total_path(updates_field);
+        self.bv
+            .update_value_at(updates_path, old_updates.addition(one));
+        let conserved = self.balance_total_is_conserved(delta_field, updates_field);
+        let (conserved_as_bool, entry_cond_as_bool) =
+            self.bv.check_condition_value_and_reachability(&conserved);
+        if entry_cond_as_bool == Some(false) {
+            return;
+        }
+        let (location, span) = (self.bv.current_location, self.bv.current_span);
+        let balance_conservation = &mut self.bv.contract_checkers.balance_conservation;
+        balance_conservation.record_update(
+            location,
+            delta_field,
+            span,
+            conserved_as_bool == Some(true),
+        );
+    }
+
+    // Returns the total change or the number of updates of a kind of balance held by the given
+    // model field, which is zero unless the function has updated a balance of the kind
+    fn balance_total(&mut self, field: &str) -> Rc<AbstractValue> {
+        let path = self.bv.balance_total_path(field);
+        let value = self.bv.current_environment.value_at(&path).cloned();
+        value.unwrap_or_else(|| self.get_i128_const_val(0))
+    }
+
+    // Returns the condition that the total of a kind of balance is unchanged, or that fewer than
+    // two balances of the kind have been updated, so that no amount has been moved between them
+    fn balance_total_is_conserved(
+        &mut self,
+        delta_field: &str,
+        updates_field: &str,
+    ) -> Rc<AbstractValue> {
+        let delta = self.balance_total(delta_field);
+        let updates = self.balance_total(updates_field);
+        let zero = self.get_i128_const_val(0);
+        let two = self.get_i128_const_val(2);
+        updates.less_than(two).or(delta.equals(zero))
     }
 
     fn visit_non_diverging_intrinsic(
@@ -304,7 +763,8 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
 
         info!("Kind {:?}", kind);
         self.bv
-            .reentrancy_checker
+            .contract_checkers
+            .reentrancy
             .block_statements
             .entry(bb)
             .or_insert(Vec::new())
@@ -384,8 +844,33 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         targets: &rustc_middle::mir::SwitchTargets,
     ) {
         let mut default_exit_condition = self.bv.current_environment.entry_condition.clone();
+        self.bv
+            .contract_checkers
+            .time_manipulation
+            .visit_switch_int(
+                self.bv.tcx,
+                self.bv.mir,
+                self.bv.current_location.block,
+                discr,
+                self.bv.current_span,
+            );
+        self.bv
+            .contract_checkers
+            .unvalidated_deserialization
+            .visit_switch_int(discr, targets);
+        let discr_place = discr.place();
         let discr = self.visit_operand(discr);
 
+        // Comparisons of the keys of two accounts that the branch is taken on are here
+        if self.bv.check_for_errors {
+            if let Some(place) = discr_place {
+                self.bv
+                    .contract_checkers
+                    .distinct_accounts
+                    .record_key_condition(place, &discr);
+            }
+        }
+
         // Check if the discriminant is not attached with the tag for constant-time verification.
         if self.bv.check_for_errors {
             if let Some(tag_name) = &self.bv.cv.options.constant_time_tag_name {
@@ -415,17 +900,39 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
             }
         }
 
+        // Branches on values that validators can predict are here
+        if self.bv.check_for_errors {
+            let tag = Tag::PREDICTABLE_ENTROPY;
+            if discr.has_tag(&tag).as_bool_if_known().unwrap_or(false)
+                || discr
+                    .expression
+                    .has_tagged_subcomponent(&tag, &self.bv.current_environment)
+            {
+                let block = self.bv.current_location.block;
+                self.bv
+                    .contract_checkers
+                    .bad_randomness
+                    .predictable_branches
+                    .push((block, self.bv.current_span));
+                if let Some((facts, function, span)) = self.facts_at_current_span() {
+                    facts.add_tagged_flow(&function, "predictable_entropy", span);
+                }
+            }
+        }
+
         // Continue to deal with the branch targets.
         let discr = discr.as_int_if_known().unwrap_or(discr);
         for (i, target) in targets.iter() {
             let val = self.get_int_const_val(i, switch_ty);
             let cond = discr.equals(val);
+            self.record_branch_condition_span(target, &cond);
             let exit_condition = self
                 .bv
                 .current_environment
                 .entry_condition
                 .and(cond.clone());
             let not_cond = cond.logical_not();
+            self.record_branch_condition_span(targets.otherwise(), &not_cond);
             default_exit_condition = default_exit_condition.and(not_cond);
             let existing_exit_condition = self
                 .bv
@@ -453,6 +960,30 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
             .insert_mut(targets.otherwise(), default_exit_condition);
     }
 
+    /// Remembers the given branch condition on the edge to target, so that verify_reachable! can
+    /// find the assumption that contradicts it. When reporting path constraints, also remembers
+    /// the span of the current terminator as the origin of the condition, so that the conjuncts
+    /// of entry conditions can be traced back to the branches introducing them.
+    fn record_branch_condition_span(
+        &mut self,
+        target: mir::BasicBlock,
+        condition: &Rc<AbstractValue>,
+    ) {
+        if self.bv.check_for_errors {
+            self.bv
+                .branch_conditions
+                .entry(target)
+                .or_default()
+                .push(condition.clone());
+        }
+        if self.bv.cv.options.reports_path_constraints() {
+            self.bv.branch_condition_spans.insert(
+                (target, condition.clone()),
+                (self.bv.current_span, self.bv.current_location.block),
+            );
+        }
+    }
+
     /// Indicates that the landing pad is finished and unwinding should
     /// continue. Emitted by build::scope::diverge_cleanup.
     #[logfn_inputs(TRACE)]
@@ -487,6 +1018,97 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
             }
             // When the summary is prepared the current environment might be different, so remember this one.
             self.bv.exit_environment = Some(self.bv.current_environment.clone());
+            if self.bv.cv.options.factor_exits {
+                self.record_exit_classes();
+            }
+            self.check_ledger_at_exit();
+            self.check_balance_totals_at_exit();
+        }
+    }
+
+    /// Records the exit environment of each kind of result that the function returns, the Ok and
+    /// the Err variant of a Result, with the values refined by the condition that the result is
+    /// of that kind, so that the summary keeps the effects of the error paths apart from those
+    /// of the success paths. Nothing is recorded if the condition depends on the locals of the
+    /// function, which a caller cannot relate to its own path condition.
+    fn record_exit_classes(&mut self) {
+        self.bv.exit_environments.clear();
+        let Some(is_err) = self.is_err_value(&mir::Place::return_place()) else {
+            return;
+        };
+        if is_err.expression.contains_local_variable(false) {
+            return;
+        }
+        for condition in [is_err.logical_not(), is_err] {
+            let entry_condition = self
+                .bv
+                .current_environment
+                .entry_condition
+                .and(condition.clone());
+            if entry_condition.as_bool_if_known() == Some(false) {
+                continue;
+            }
+            let mut environment = self.bv.current_environment.clone();
+            for (path, value) in self.bv.current_environment.value_map.iter() {
+                let refined_value = value.refine_with(&condition, 0);
+                if refined_value != *value {
+                    environment
+                        .value_map
+                        .insert_mut(path.clone(), refined_value);
+                }
+            }
+            environment.entry_condition = entry_condition;
+            self.bv.exit_environments.push((condition, environment));
+        }
+    }
+
+    /// Records drift of the ledger from the lamports of the account that it is declared to add
+    /// up to, if the function may return Ok without having changed both by the same amount.
+    fn check_ledger_at_exit(&mut self) {
+        if self.bv.contract_checkers.ledger.updates.is_empty() {
+            return;
+        }
+        let balanced = self.ledger_is_balanced();
+        // Returning an error reverts the updates
+        let kept = match self.is_err_value(&mir::Place::return_place()) {
+            Some(is_err) => is_err.or(balanced),
+            None => balanced,
+        };
+        let (kept_as_bool, entry_cond_as_bool) =
+            self.bv.check_condition_value_and_reachability(&kept);
+        if kept_as_bool == Some(true) || entry_cond_as_bool == Some(false) {
+            return;
+        }
+        self.bv
+            .contract_checkers
+            .ledger
+            .record_drift(self.bv.current_span);
+    }
+
+    /// Records an imbalance of the balances of a kind if the function may return Ok after moving
+    /// amounts between them without leaving their total unchanged.
+    fn check_balance_totals_at_exit(&mut self) {
+        let balance_conservation = &self.bv.contract_checkers.balance_conservation;
+        if balance_conservation.updates.is_empty() {
+            return;
+        }
+        // Returning an error reverts the updates
+        let is_err = self.is_err_value(&mir::Place::return_place());
+        for (delta_field, updates_field) in BalanceConservationChecker::KINDS {
+            let conserved = self.balance_total_is_conserved(delta_field, updates_field);
+            let kept = match &is_err {
+                Some(is_err) => is_err.or(conserved),
+                None => conserved,
+            };
+            let (kept_as_bool, entry_cond_as_bool) =
+                self.bv.check_condition_value_and_reachability(&kept);
+            if kept_as_bool == Some(true) || entry_cond_as_bool == Some(false) {
+                continue;
+            }
+            self.bv
+                .contract_checkers
+                .balance_conservation
+                .record_imbalance(delta_field, self.bv.current_span);
         }
     }
 
@@ -600,6 +1222,49 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         }
     }
 
+    /// Records what is known right before the current call, for --record-call-states.
+    fn record_call_state(&mut self, func: &mir::Operand<'tcx>) {
+        let tcx = self.bv.tcx;
+        let callee = func.const_fn_def().map_or_else(
+            || format!("{func:?}"),
+            |(def_id, _)| tcx.def_path_str(def_id),
+        );
+        let state = CallState::new(tcx, self.bv.mir, &self.bv.current_environment, callee);
+        let span = self.bv.current_span.source_callsite();
+        let location = self.bv.cv.session.source_map().lookup_char_pos(span.lo());
+        let location = format!(
+            "{}:{}:{}",
+            location.file.name.prefer_local(),
+            location.line,
+            location.col_display + 1
+        );
+        let function = utils::summary_key_str(tcx, self.bv.def_id);
+        let limit = self.bv.cv.options.call_states_limit;
+        self.bv
+            .cv
+            .call_states
+            .record(&function, &self.bv.function_name, location, state, limit);
+    }
+
+    /// Returns the facts for --export-facts, with the summary key of the function being analyzed
+    /// and the location of the current span, if the facts are exported and the fixed point of
+    /// the body has been reached.
+    fn facts_at_current_span(&mut self) -> Option<(&mut Facts, Rc<str>, String)> {
+        if !self.bv.check_for_errors || self.bv.cv.options.export_facts.is_none() {
+            return None;
+        }
+        let span = self.bv.current_span.source_callsite();
+        let location = self.bv.cv.session.source_map().lookup_char_pos(span.lo());
+        let location = format!(
+            "{}:{}:{}",
+            location.file.name.prefer_local(),
+            location.line,
+            location.col_display + 1
+        );
+        let function = utils::summary_key_str(self.bv.tcx, self.bv.def_id);
+        Some((&mut self.bv.cv.facts, function, location))
+    }
+
     /// Block ends with the call of a function.
     ///
     /// #Arguments
@@ -646,6 +1311,10 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         );
         info!("destination {:?}", destination);
         trace!("env {:?}", self.bv.current_environment);
+        // The environment is only final once the fixed point has been reached
+        if self.bv.check_for_errors && self.bv.records_call_states {
+            self.record_call_state(func);
+        }
 
         let func_to_call = self.visit_operand(func);
         let func_ref = self.get_func_ref(&func_to_call);
@@ -658,52 +1327,397 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                     .already_reported_errors_for_call_to
                     .insert(func_to_call)
             {
-                self.report_missing_summary();
+                self.report_missing_summary(None);
             }
             return;
         };
-        // Time manipulation is here 
-        let argument_type_key = func_ref_to_call.argument_type_key.clone();
-        if argument_type_key.contains("__solana_clock_Clock") {
-            self.bv.time_manipulation_checker.check_for_clock_lib = true;
-            self.bv.time_manipulation_checker.time_manipulation_span = self.bv.current_span;
+        // Time manipulation is here
+        let reads_clock = func_ref_to_call
+            .argument_type_key
+            .contains("__solana_clock_Clock");
+        let time_manipulation = &mut self.bv.contract_checkers.time_manipulation;
+        if reads_clock {
+            time_manipulation.record_clock_read(self.bv.current_span);
         }
+        time_manipulation.visit_call(self.bv.tcx, self.bv.mir, args);
 
         let callee_def_id = func_ref_to_call
             .def_id
             .expect("callee obtained via operand should have def id");
         
         let callee_name =  utils::summary_key_str(self.bv.tcx, callee_def_id);
+        // What the registry of foreign models says about the callee, if it is a foreign function
+        let foreign_model = self
+            .bv
+            .cv
+            .foreign_models
+            .model_for(self.bv.tcx, callee_def_id);
+        if self.bv.check_for_errors
+            && foreign_model
+                .as_ref()
+                .is_some_and(|model| model.forbidden_in_entrypoint)
+        {
+            let callee = self.bv.tcx.def_path_str(callee_def_id);
+            self.bv
+                .contract_checkers
+                .forbidden_call
+                .record_call(self.bv.current_span, callee);
+        }
+        // Calls and reads of the clock, for --export-facts, are here
+        if let Some((facts, function, span)) = self.facts_at_current_span() {
+            facts.add_call(&function, &callee_name, span.clone());
+            if reads_clock {
+                facts.add_clock_read(&function, span);
+            }
+        }
         // Numerical precision error is here
         if callee_name.contains(".round") {
-            self.bv.numerical_precision_checker.check_for_round_func = true;
-            self.bv.numerical_precision_checker.numerical_precision_error_span = self.bv.current_span;
+            self.bv.contract_checkers.numerical_precision.check_for_round_func = true;
+            self.bv.contract_checkers.numerical_precision.numerical_precision_error_span = self.bv.current_span;
         }
 
-        // Bad randomness is here
-        if callee_name.contains("fastrand") 
-        || callee_name.contains("oorandom.implement_oorandom") 
-        || callee_name.contains("nanorand.rand")
+        // Accounts dropped by an iterator window are here
+        self.bv.contract_checkers.account_window.visit_call(
+            self.bv.tcx,
+            self.bv.mir,
+            self.bv.current_location,
+            &callee_name,
+            args,
+            destination,
+            self.bv.current_span,
+        );
+
+        // Reentrancy is here
+        let account_access = self.bv.contract_checkers.account_fields.visit_call(
+            self.bv.tcx,
+            callee_def_id,
+            args,
+            &destination,
+        );
+        let (tcx, mir) = (self.bv.tcx, self.bv.mir);
+
+        // Writes past the data of accounts are here
+        let borrows_data =
+            account_access == Some((AccountField::Data, rustc_hir::Mutability::Mut));
+        let item_name = tcx.opt_item_name(callee_def_id);
+        let data_write = self.bv.contract_checkers.account_data_length.visit_call(
+            tcx,
+            mir,
+            item_name.as_ref().map_or("", |name| name.as_str()),
+            borrows_data,
+            args,
+            &destination,
+        );
+        if let Some((account, end)) = data_write {
+            self.check_account_data_write(account, end);
+        }
+
+        // Divisions whose quotient is multiplied are here
+        self.bv.contract_checkers.numerical_precision.visit_call(
+            tcx,
+            mir,
+            callee_def_id,
+            item_name.as_ref().map_or("", |name| name.as_str()),
+            args,
+            &destination,
+            self.bv.current_span,
+        );
+
+        // Subtractions from balances that are not compared with the amount are here
+        let checkers = &mut self.bv.contract_checkers;
+        checkers.lamport_underflow.visit_call(
+            &checkers.amount_provenance,
+            &callee_name,
+            item_name.as_ref().map_or("", |name| name.as_str()),
+            account_access,
+            args,
+            &destination,
+        );
+
+        // Credits of created accounts that may fall short of the rent-exempt minimum are here
+        if self.bv.check_for_errors {
+            if let Some(instruction) = solana_compat::system_instruction(tcx, callee_def_id) {
+                self.check_rent_exempt_credits(instruction, args);
+                self.check_no_op_transfer(instruction, args);
+            }
+            match (account_access, item_name.as_ref().map(|name| name.as_str())) {
+                (
+                    Some((AccountField::Data, rustc_hir::Mutability::Mut)),
+                    Some("realloc" | "resize"),
+                ) => self.check_rent_exempt_resize(args),
+                (Some((AccountField::Lamports, rustc_hir::Mutability::Not)), Some("lamports")) => {
+                    let key = args
+                        .first()
+                        .and_then(|arg| arg.node.place())
+                        .and_then(|account| self.account_key(account.local));
+                    if let (Some(key), true) = (key, destination.projection.is_empty()) {
+                        let rent_dust = &mut self.bv.contract_checkers.rent_dust;
+                        rent_dust.balance_locals.push((key, destination.local));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Amounts of instructions that are computed with floating point arithmetic are here
+        if self.bv.check_for_errors && solana_compat::builds_instruction(tcx, callee_def_id) {
+            for arg in args {
+                let value = self.visit_operand(&arg.node);
+                self.check_float_amount(
+                    &value,
+                    "passed to an instruction of the system program or of a token program",
+                );
+            }
+        }
+
+        // Conversions of amounts that are untrusted input whose results are unwrapped are here
+        if self.bv.check_for_errors {
+            match item_name.as_ref().map(|name| name.as_str()) {
+                Some("try_into" | "try_from")
+                    if tcx.crate_name(callee_def_id.krate).as_str() == "core" =>
+                {
+                    self.record_integer_conversion(args, destination);
+                }
+                Some("unwrap" | "expect") => self.check_unwrapped_conversion(args),
+                _ => {}
+            }
+        }
+
+        // Slices of the instruction data that may be out of bounds are here
+        if self.bv.check_for_errors
+            && matches!(
+                item_name.as_ref().map(|name| name.as_str()),
+                Some("index" | "index_mut")
+            )
         {
-            self.bv.bad_randomness_checker.check_for_rand_lib = true;
-            self.bv.bad_randomness_checker.bad_randomness_span = self.bv.current_span;
+            self.check_instruction_data_slice(args);
         }
 
-        // Reentrancy is here
-        if callee_name.contains("try_borrow_mut_lamports") {
-            self.bv.reentrancy_checker.function_lamport_transfer.entry(bb).or_insert(callee_name.clone());
+        // Comparisons of the keys of accounts are here
+        let checkers = &mut self.bv.contract_checkers;
+        checkers.distinct_accounts.visit_call(
+            tcx,
+            mir,
+            &checkers.amount_provenance,
+            item_name.as_ref().map_or("", |name| name.as_str()),
+            args,
+            &destination,
+        );
+
+        // State that an entrypoint creates rather than reads from the data of an account is here
+        self.bv.contract_checkers.non_persistent_state.visit_call(
+            tcx,
+            mir,
+            item_name.as_ref().map_or("", |name| name.as_str()),
+            args,
+            &destination,
+            self.bv.current_span,
+        );
+
+        // Values deserialized with Borsh, whose variants arms of a match may rely on, are here
+        self.bv
+            .contract_checkers
+            .unvalidated_deserialization
+            .visit_call(
+                item_name.as_ref().map_or("", |name| name.as_str()),
+                args,
+                &destination,
+            );
+
+        // Changes to the lamports or data of the unchecked accounts of an Anchor instruction are
+        // here. Those to lamports are findings if no account signs the instruction, and both are
+        // findings if no constraint validates the account.
+        if let Some((
+            field @ (AccountField::Lamports | AccountField::Data),
+            rustc_hir::Mutability::Mut,
+        )) = account_access
+        {
+            let anchor_accounts = &self.bv.contract_checkers.anchor_accounts;
+            let account = args
+                .first()
+                .and_then(|arg| arg.node.place())
+                .and_then(|receiver| anchor_accounts.account_of(receiver.local));
+            if field == AccountField::Lamports {
+                self.bv.contract_checkers.missing_signer.record_lamports_change(
+                    self.bv.current_span,
+                    self.bv.current_location.block,
+                    account,
+                    anchor_accounts.has_signer,
+                );
+            }
+            self.bv
+                .contract_checkers
+                .account_constraint
+                .record_change(self.bv.current_span, account);
+        }
+        self.bv.contract_checkers.anchor_accounts.visit_call(
+            item_name.as_ref().map_or("", |name| name.as_str()),
+            args,
+            &destination,
+        );
+
+        // Writes of the data of accounts, which closed accounts need to be safe from revival, are
+        // here
+        let provenance = &self.bv.contract_checkers.amount_provenance;
+        self.bv.contract_checkers.account_close.visit_call(
+            tcx,
+            mir,
+            bb,
+            item_name.as_ref().map_or("", |name| name.as_str()),
+            account_access,
+            args,
+            &destination,
+            |account| provenance.account_of(account),
+        );
+
+        // Borrows of the data of accounts whose owner is not checked are here
+        let borrowed_account = self.bv.contract_checkers.owner_check.visit_call(
+            tcx,
+            mir,
+            item_name.as_ref().map_or("", |name| name.as_str()),
+            account_access,
+            args,
+            &destination,
+        );
+        if func_ref_to_call.known_name == KnownNames::HephaSolanaRequireOwner {
+            if let Some(account) = args.first().and_then(|arg| arg.node.place()) {
+                let owner_check = &mut self.bv.contract_checkers.owner_check;
+                owner_check.record_owner_guard(bb, account.local);
+            }
+        }
+        if let Some(account) = borrowed_account {
+            let owner_is_checked = self
+                .bv
+                .contract_checkers
+                .owner_check
+                .is_owner_guarded(mir, bb, account)
+                || self
+                    .bv
+                    .contract_checkers
+                    .anchor_accounts
+                    .account_of(account)
+                    .is_some_and(|(kind, _)| kind.owner_is_checked())
+                || self.account_owner_is_checked(account);
+            self.bv.contract_checkers.owner_check.record_borrow(
+                tcx,
+                self.bv.def_id,
+                self.bv.current_span,
+                owner_is_checked,
+            );
+        }
+
+        // Mutable borrows of the lamports or data of accounts that may be the same account are here
+        if self.bv.check_for_errors {
+            let checkers = &self.bv.contract_checkers;
+            let mutably_borrowed = checkers.distinct_accounts.mutably_borrowed_account(
+                tcx,
+                mir,
+                &checkers.amount_provenance,
+                account_access,
+                args,
+            );
+            if let Some(account) = mutably_borrowed {
+                self.check_account_aliasing(account);
+            }
+        }
+
+        // Changes to the lamports and data of accounts that are not checked to be writable are here
+        let checkers = &mut self.bv.contract_checkers;
+        let written_account = checkers.writable_check.visit_call(
+            tcx,
+            mir,
+            &checkers.amount_provenance,
+            item_name.as_ref().map_or("", |name| name.as_str()),
+            account_access,
+            args,
+            &destination,
+        );
+        if let Some(account) = written_account {
+            self.bv.contract_checkers.writable_check.record_write(
+                tcx,
+                mir,
+                self.bv.def_id,
+                bb,
+                account,
+                self.bv.current_span,
+            );
+        }
+
+        // Instructions that are built for another program but never invoked are here
+        let uninvoked_instruction = &mut self.bv.contract_checkers.uninvoked_instruction;
+        if solana_compat::builds_instruction(tcx, callee_def_id) {
+            if destination.projection.is_empty() {
+                uninvoked_instruction
+                    .builder_calls
+                    .insert(bb, (destination.local, self.bv.current_span));
+            }
+        } else if func_ref_to_call.known_name == KnownNames::SolanaProgramInvoke
+            || callee_def_id.is_local()
+        {
+            uninvoked_instruction.invoking_locals.extend(
+                args.iter()
+                    .filter_map(|arg| arg.node.place())
+                    .map(|place| place.local),
+            );
+        }
+
+        // Cross-program invocations are here
+        if func_ref_to_call.known_name == KnownNames::SolanaProgramInvoke
+            && self.bv.cv.call_graph.needs_edges()
+        {
+            self.bv
+                .cv
+                .call_graph
+                .add_cross_program_invocation(self.bv.def_id, callee_def_id);
+        }
+
+        // Transfers of lamports that a random value or the clock may decide are here
+        if account_access == Some((AccountField::Lamports, rustc_hir::Mutability::Mut))
+            || func_ref_to_call.known_name == KnownNames::SolanaProgramInvoke
+        {
+            self.bv
+                .contract_checkers
+                .bad_randomness
+                .transfer_blocks
+                .insert(bb);
+            self.bv
+                .contract_checkers
+                .time_manipulation
+                .transfer_blocks
+                .insert(bb);
+        }
+
+        let reentrancy = &mut self.bv.contract_checkers.reentrancy;
+        if account_access == Some((AccountField::Lamports, rustc_hir::Mutability::Mut)) {
+            reentrancy.record_interaction(tcx, mir, bb, args.first().map(|arg| &arg.node));
+        } else if func_ref_to_call.known_name == KnownNames::SolanaProgramInvoke {
+            // The account infos passed to the invoked program are its second argument
+            reentrancy.record_interaction(tcx, mir, bb, args.get(1).map(|arg| &arg.node));
+        } else if foreign_model
+            .as_ref()
+            .is_some_and(|model| model.external_call)
+        {
+            reentrancy.record_external_call(tcx, mir, bb, args);
         }
         if callee_name.contains("std.collections.hash.map") {
-            self.bv.reentrancy_checker.check_for_balance_variable = true;
-            self.bv.reentrancy_checker.temporary_variable_for_balance = Some(destination);
-            self.bv.reentrancy_checker.starting_reentrancy_span = self.bv.current_span.lo();
+            reentrancy.record_balance_accounts(tcx, mir, args);
         }
-        if self.bv.reentrancy_checker.check_for_balance_variable {
-            for arg in args {
-                let operand = arg.node.clone();
-                if let mir::Operand::Copy(place) | mir::Operand::Move(place) = operand {
-                    if self.bv.reentrancy_checker.temporary_variable_for_balance == Some(place) {
-                        self.bv.reentrancy_checker.temporary_variable_for_balance = Some(destination);
+        reentrancy.visit_call(tcx, mir, args, &destination);
+        // The statements that move the balance into other locals are not visited again while
+        // checking, so neither are the calls that it is passed to
+        if !self.bv.check_for_errors {
+            let reentrancy = &mut self.bv.contract_checkers.reentrancy;
+            if callee_name.contains("std.collections.hash.map") {
+                reentrancy.check_for_balance_variable = true;
+                reentrancy.temporary_variable_for_balance = Some(destination);
+                reentrancy.balance_span = self.bv.current_span;
+            }
+            if reentrancy.check_for_balance_variable {
+                for arg in args {
+                    if let mir::Operand::Copy(place) | mir::Operand::Move(place) = arg.node {
+                        if reentrancy.temporary_variable_for_balance == Some(place) {
+                            reentrancy.temporary_variable_for_balance = Some(destination);
+                        }
                     }
                 }
             }
@@ -718,7 +1732,7 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         let mut callee_generic_arguments = self
             .type_visitor()
             .specialize_generic_args(generic_args, &self.type_visitor().generic_argument_map);
-        let actual_args: Vec<(Rc<Path>, Rc<AbstractValue>)> = args
+        let mut actual_args: Vec<(Rc<Path>, Rc<AbstractValue>)> = args
             .iter()
             .map(|arg| {
                 (
@@ -727,6 +1741,12 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                 )
             })
             .collect();
+        // The argument of old! in a postcondition is its value at entry
+        if func_ref_to_call.known_name == KnownNames::MiraiOld && self.is_in_postcondition() {
+            if let Some(arg) = args.first() {
+                actual_args[0].1 = self.value_at_function_entry(&arg.node);
+            }
+        }
         let actual_argument_types: Vec<Ty<'tcx>> = args
             .iter()
             .map(|arg| {
@@ -780,74 +1800,649 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         } else {
             false
         };
-        let adt_map = self
-            .type_visitor()
-            .get_adt_map(&actual_args, &self.bv.current_environment);
-
-        let known_name = func_ref_to_call.known_name;
-        let func_const = ConstantDomain::Function(func_ref_to_call);
-        let func_const_args =
-            &self.get_function_constant_args(&actual_args, &actual_argument_types);
+        let adt_map = self
+            .type_visitor()
+            .get_adt_map(&actual_args, &self.bv.current_environment);
+
+        let known_name = func_ref_to_call.known_name;
+        let func_const = ConstantDomain::Function(func_ref_to_call);
+        let func_const_args =
+            &self.get_function_constant_args(&actual_args, &actual_argument_types);
+
+        let current_location = self.bv.current_location;
+        self.bv
+            .block_to_call
+            .insert(current_location, callee_def_id);
+
+        let tcx = self.bv.tcx;
+        let mut call_visitor = CallVisitor::new(
+            self,
+            callee_def_id,
+            Some(callee_generic_arguments),
+            callee_generic_argument_map,
+            self.bv.current_environment.clone(),
+            func_const,
+        );
+        call_visitor.actual_args = actual_args;
+        call_visitor.actual_argument_types = actual_argument_types;
+        call_visitor.unwind = unwind;
+        call_visitor.destination = destination;
+        call_visitor.target = target;
+        call_visitor.callee_fun_val = func_to_call;
+        call_visitor.function_constant_args = func_const_args;
+        call_visitor.initial_type_cache = adt_map;
+        info!("callee_func_ref {:?}", call_visitor.callee_func_ref);
+        // Invocations with an argument that validators can predict are here
+        if known_name == KnownNames::SolanaProgramInvoke
+            && call_visitor.block_visitor.bv.check_for_errors
+            && (0..call_visitor.actual_args.len())
+                .any(|ordinal| call_visitor.argument_has_tag(ordinal, Tag::PREDICTABLE_ENTROPY))
+        {
+            let bv = &mut call_visitor.block_visitor.bv;
+            bv.contract_checkers
+                .bad_randomness
+                .predictable_invocations
+                .push(bv.current_span);
+            if let Some((facts, function, span)) =
+                call_visitor.block_visitor.facts_at_current_span()
+            {
+                facts.add_tagged_flow(&function, "predictable_entropy", span);
+            }
+        }
+        // Invocations with an instruction that is built from untrusted input are here
+        if known_name == KnownNames::SolanaProgramInvoke
+            && call_visitor.block_visitor.bv.check_for_errors
+            && !call_visitor.actual_args.is_empty()
+            && call_visitor.argument_has_tag(0, Tag::UNTRUSTED_INPUT)
+        {
+            let bv = &mut call_visitor.block_visitor.bv;
+            bv.contract_checkers
+                .reentrancy
+                .untrusted_interactions
+                .insert(bv.current_location.block);
+            if let Some((facts, function, span)) =
+                call_visitor.block_visitor.facts_at_current_span()
+            {
+                facts.add_tagged_flow(&function, "untrusted_input", span);
+            }
+        }
+        // Deserializations of a prefix of the instruction data with Borsh or bincode are here
+        if call_visitor.block_visitor.bv.check_for_errors
+            && call_visitor
+                .block_visitor
+                .bv
+                .contract_checkers
+                .amount_provenance
+                .in_entrypoint
+            && !call_visitor.actual_args.is_empty()
+            && call_visitor.argument_has_tag(0, Tag::UNTRUSTED_INPUT)
+        {
+            if let Some(format) = solana_compat::deserialization_format(tcx, callee_def_id) {
+                call_visitor.record_deserialization(format);
+            }
+        }
+        // Deserializations with Borsh from slices that may be too short are here
+        if known_name == KnownNames::BorshTryFromSlice
+            && call_visitor.block_visitor.bv.check_for_errors
+            && !call_visitor.actual_args.is_empty()
+        {
+            call_visitor.check_deserialized_length();
+        }
+        if call_visitor.handled_as_special_function_call() {
+            return;
+        }
+        let function_summary = call_visitor.get_function_summary().unwrap_or_default();
+        call_visitor.compose_external_effects(&function_summary);
+        if function_summary.external_effects.transfers_lamports
+            && call_visitor.block_visitor.bv.check_for_errors
+        {
+            let bv = &mut call_visitor.block_visitor.bv;
+            bv.contract_checkers
+                .non_persistent_state
+                .record_transfer_call(bv.current_span, args);
+        }
+        if !function_summary.distinct_accounts.is_empty()
+            && !function_summary.suppresses("distinct_accounts")
+        {
+            let bv = &mut call_visitor.block_visitor.bv;
+            let checkers = &mut bv.contract_checkers;
+            checkers.distinct_accounts.record_call(
+                &checkers.amount_provenance,
+                bv.current_location.block,
+                bv.current_span,
+                tcx.item_name(callee_def_id).as_str(),
+                args,
+                &function_summary.distinct_accounts,
+            );
+        }
+        if call_visitor.handled_as_type_erasing_conversion(&function_summary) {
+            return;
+        }
+
+        if !function_summary.is_computed {
+            if (known_name != KnownNames::StdCloneClone || !self_ty_is_fn_ptr)
+                && call_visitor
+                    .block_visitor
+                    .bv
+                    .already_reported_errors_for_call_to
+                    .insert(call_visitor.callee_fun_val.clone())
+            {
+                let _kn = known_names::KnownNamesCache::get_known_name_for(tcx, callee_def_id);
+                call_visitor
+                    .block_visitor
+                    .report_missing_summary(Some(callee_def_id));
+                if known_name != KnownNames::StdCloneClone
+                    && !call_visitor.block_visitor.bv.analysis_is_incomplete
+                {
+                    return;
+                }
+            }
+        } else if function_summary.is_incomplete
+            && call_visitor
+                .block_visitor
+                .bv
+                .already_reported_errors_for_call_to
+                .insert(call_visitor.callee_fun_val.clone())
+        {
+            call_visitor.report_incomplete_summary();
+        }
+
+        if known_name == KnownNames::StdCloneClone {
+            call_visitor.handle_clone(&function_summary);
+        } else {
+            call_visitor.transfer_and_refine_into_current_environment(&function_summary);
+        }
+
+        // Random values that validators can predict are here
+        let model_tag = foreign_model.as_ref().and_then(|model| model.tag);
+        let is_predictable = call_visitor.result_is_predictable_entropy()
+            || model_tag == Some(Tag::PREDICTABLE_ENTROPY);
+        let bad_randomness = &mut self.bv.contract_checkers.bad_randomness;
+        if self.bv.check_for_errors
+            && is_predictable
+            && contract_errors::is_random_number_generator(tcx, callee_def_id)
+            && bad_randomness.bad_randomness_span.is_none()
+        {
+            bad_randomness.bad_randomness_span = Some(self.bv.current_span);
+        }
+        if self.bv.check_for_errors
+            && is_predictable
+            && contract_errors::is_predictable_entropy_type(tcx, destination.ty(mir, tcx).ty)
+            && bad_randomness.entropy_source_span.is_none()
+        {
+            bad_randomness.entropy_source_span = Some(self.bv.current_span);
+        }
+
+        // The built-in tags of the result are attached together, so that the environment is
+        // walked once for all of them
+        let mut implicit_tags = Vec::new();
+        if is_predictable {
+            implicit_tags.push(Tag::PREDICTABLE_ENTROPY);
+        }
+        // The lamports of accounts are amounts in lamports
+        if account_access == Some((AccountField::Lamports, rustc_hir::Mutability::Not))
+            && tcx.item_name(callee_def_id).as_str() == "lamports"
+        {
+            implicit_tags.push(Tag::LAMPORTS);
+        }
+        // The results of functions that return floats are computed with floating point arithmetic
+        if destination.ty(mir, tcx).ty.is_floating_point() {
+            implicit_tags.push(Tag::FLOATING_POINT);
+            self.bv
+                .contract_checkers
+                .float_in_money_path
+                .record_source(self.bv.current_span);
+        }
+        // The data of accounts is untrusted input
+        if self.bv.cv.is_solana_program
+            && account_access == Some((AccountField::Data, rustc_hir::Mutability::Not))
+            && tcx.item_name(callee_def_id).as_str() == "try_borrow_data"
+        {
+            implicit_tags.push(Tag::UNTRUSTED_INPUT);
+        }
+        // The tags of the models of foreign functions
+        if let Some(tag) = model_tag {
+            if !implicit_tags.contains(&tag) {
+                implicit_tags.push(tag);
+            }
+        }
+        if !implicit_tags.is_empty() {
+            let destination_path = self.visit_rh_place(&destination);
+            let destination_type = self
+                .type_visitor()
+                .get_rustc_place_type(&destination, self.bv.current_span);
+            self.bv.attach_tags_to_value_at_path(
+                &implicit_tags,
+                destination_path,
+                destination_type,
+            );
+        }
+
+        // Results that may be dropped are here
+        if callee_def_id.is_local() {
+            self.record_fallible_call(bb, &destination);
+        }
+
+        // The length of the data of an account is modeled by a path, so that the checks of
+        // data_len() constrain the writes into the data
+        if account_access == Some((AccountField::Data, rustc_hir::Mutability::Not))
+            && tcx.item_name(callee_def_id).as_str() == "data_len"
+        {
+            let account = args.first().and_then(|arg| arg.node.place());
+            let length_path = account.and_then(|a| self.account_data_length_path(a.local));
+            if let (Some(length_path), true) = (length_path, destination.projection.is_empty()) {
+                let length = self
+                    .bv
+                    .lookup_path_and_refine_result(length_path, tcx.types.usize);
+                let destination_path = self.visit_lh_place(&destination);
+                self.bv.update_value_at(destination_path, length);
+            }
+        }
+    }
+
+    /// Returns the path of the length of the data of the account that the local refers to, which
+    /// models the value of data_len() for the account.
+    fn account_data_length_path(&mut self, account: mir::Local) -> Option<Rc<Path>> {
+        let TyKind::Ref(_, account_info_ty, _) = self.bv.mir.local_decls[account].ty.kind() else {
+            return None;
+        };
+        let data_index = AccountInfoLayout::of(self.bv.tcx, *account_info_ty)?
+            .index_of(AccountField::Data)?;
+        let account_path = Path::new_deref(
+            self.visit_rh_place(&mir::Place::from(account)),
+            ExpressionType::NonPrimitive,
+        );
+        let data_path = Path::new_field(account_path, data_index.as_usize());
+        Some(Path::new_length(data_path).canonicalize(&self.bv.current_environment))
+    }
+
+    /// Returns the paths of the value of the reference held at the given path, and of the fields
+    /// of that value if it is a struct, such as the bytes of a Pubkey.
+    fn referenced_key_paths(&self, path: Rc<Path>, ty: Ty<'tcx>) -> HashSet<Rc<Path>> {
+        let env = &self.bv.current_environment;
+        let key_path =
+            Path::new_deref(path.clone(), ExpressionType::NonPrimitive).canonicalize(env);
+        let mut paths = HashSet::from([path, key_path.clone()]);
+        if let TyKind::Ref(_, key_ty, _) = ty.kind() {
+            if let TyKind::Adt(def, _) = key_ty.kind() {
+                if def.is_struct() {
+                    for index in 0..def.non_enum_variant().fields.len() {
+                        paths.insert(Path::new_field(key_path.clone(), index).canonicalize(env));
+                    }
+                }
+            }
+        }
+        paths
+    }
+
+    /// Returns the value of the key of the account that the local refers to, which is a reference
+    /// to the Pubkey of the account.
+    fn account_key(&mut self, account: mir::Local) -> Option<Rc<AbstractValue>> {
+        let TyKind::Ref(_, account_info_ty, _) = self.bv.mir.local_decls[account].ty.kind() else {
+            return None;
+        };
+        let TyKind::Adt(def, args) = account_info_ty.kind() else {
+            return None;
+        };
+        let key_index =
+            AccountInfoLayout::of(self.bv.tcx, *account_info_ty)?.index_of(AccountField::Key)?;
+        let key_ty = self
+            .type_visitor()
+            .get_field_type(def, args, key_index.as_usize());
+        let account_path = Path::new_deref(
+            self.visit_rh_place(&mir::Place::from(account)),
+            ExpressionType::NonPrimitive,
+        );
+        let key_path = Path::new_field(account_path, key_index.as_usize())
+            .canonicalize(&self.bv.current_environment);
+        Some(self.bv.lookup_path_and_refine_result(key_path, key_ty))
+    }
+
+    /// Checks that the key of the account whose lamports or data the current call mutably borrows
+    /// is known to differ from the keys of the other accounts whose lamports or data are mutably
+    /// borrowed. If both can be the same account, a debit of one and a credit of the other cancel
+    /// out, or a write through one overwrites the other.
+    fn check_account_aliasing(&mut self, account: mir::Local) {
+        let distinct_accounts = &self.bv.contract_checkers.distinct_accounts;
+        let others: Vec<mir::Local> = distinct_accounts
+            .mutably_borrowed
+            .iter()
+            .copied()
+            .filter(|other| *other != account)
+            .collect();
+        if !distinct_accounts.mutably_borrowed.contains(&account) {
+            self.bv
+                .contract_checkers
+                .distinct_accounts
+                .mutably_borrowed
+                .push(account);
+        }
+        if self
+            .bv
+            .contract_checkers
+            .distinct_accounts
+            .aliased_borrow
+            .is_some()
+        {
+            return;
+        }
+        let Some(key) = self.account_key(account) else {
+            return;
+        };
+        for other in others {
+            let Some(other_key) = self.account_key(other) else {
+                continue;
+            };
+            let mut distinct_keys = vec![key.not_equals(other_key)];
+            let key_conditions = self
+                .bv
+                .contract_checkers
+                .distinct_accounts
+                .key_conditions_of(account, other);
+            for (value, when_distinct) in key_conditions {
+                distinct_keys.push(if when_distinct {
+                    value
+                } else {
+                    value.logical_not()
+                });
+            }
+            let known_distinct = distinct_keys.iter().any(|distinct| {
+                let (distinct_as_bool, entry_cond_as_bool) =
+                    self.bv.check_condition_value_and_reachability(distinct);
+                entry_cond_as_bool == Some(false) || distinct_as_bool == Some(true)
+            });
+            if !known_distinct {
+                self.bv.contract_checkers.distinct_accounts.aliased_borrow =
+                    Some(self.bv.current_span);
+                return;
+            }
+        }
+    }
+
+    /// Checks if the entry condition requires the owner of the account that the local refers to
+    /// to equal the program id parameter of the entrypoint. In other functions, including the
+    /// handlers of Anchor programs, the owner of the account is not known to be checked.
+    fn account_owner_is_checked(&mut self, account: mir::Local) -> bool {
+        if self.bv.cv.anchor_handlers.contains(&self.bv.def_id) {
+            return false;
+        }
+        let Some(program_id) = contract_errors::entrypoint_program_id(self.bv.tcx, self.bv.mir)
+        else {
+            return false;
+        };
+        let TyKind::Ref(_, account_info_ty, _) = self.bv.mir.local_decls[account].ty.kind() else {
+            return false;
+        };
+        let TyKind::Adt(def, args) = account_info_ty.kind() else {
+            return false;
+        };
+        let Some(owner_index) = AccountInfoLayout::of(self.bv.tcx, *account_info_ty)
+            .and_then(|layout| layout.index_of(AccountField::Owner))
+        else {
+            return false;
+        };
+        let owner_ty = self
+            .type_visitor()
+            .get_field_type(def, args, owner_index.as_usize());
+        let account_path = Path::new_deref(
+            self.visit_rh_place(&mir::Place::from(account)),
+            ExpressionType::NonPrimitive,
+        );
+        let owner_path = Path::new_field(account_path, owner_index.as_usize())
+            .canonicalize(&self.bv.current_environment);
+        let owner_paths = self.referenced_key_paths(owner_path, owner_ty);
+        let program_id_path = self.visit_rh_place(&mir::Place::from(program_id));
+        let program_id_paths =
+            self.referenced_key_paths(program_id_path, self.bv.mir.local_decls[program_id].ty);
+        requires_equal(
+            &self.bv.current_environment.entry_condition,
+            &owner_paths,
+            &program_id_paths,
+        )
+    }
+
+    /// Checks that a write into the data of the account that the local account refers to, whose
+    /// byte range ends at end, is within the length of the data under the entry condition.
+    fn check_account_data_write(&mut self, account: mir::Local, end: DataWriteEnd<'tcx>) {
+        let usize_ty = self.bv.tcx.types.usize;
+        let Some(length_path) = self.account_data_length_path(account) else {
+            return;
+        };
+        let length = self
+            .bv
+            .lookup_path_and_refine_result(length_path.clone(), usize_ty);
+        let one = self.get_int_const_val(1, usize_ty);
+        let end_value = match end {
+            DataWriteEnd::RangeField(range, field, inclusive) => {
+                let field_path = Path::new_field(self.visit_rh_place(&range), field)
+                    .canonicalize(&self.bv.current_environment);
+                let value = self.bv.lookup_path_and_refine_result(field_path, usize_ty);
+                if inclusive {
+                    value.addition(one)
+                } else {
+                    value
+                }
+            }
+            DataWriteEnd::Index(index) => {
+                let index_path = self.visit_rh_place(&mir::Place::from(index));
+                let value = self.bv.lookup_path_and_refine_result(index_path, usize_ty);
+                value.addition(one)
+            }
+            DataWriteEnd::SourceLength(source) => {
+                let source_length_path = Path::new_length(self.visit_rh_place(&source))
+                    .canonicalize(&self.bv.current_environment);
+                self.bv
+                    .lookup_path_and_refine_result(source_length_path, usize_ty)
+            }
+        };
+        let fits = end_value.less_or_equal(length.clone());
+        let (fits_as_bool, entry_cond_as_bool) =
+            self.bv.check_condition_value_and_reachability(&fits);
+        if entry_cond_as_bool == Some(false) {
+            return;
+        }
+        let length_is_checked = length.is_compile_time_constant()
+            || self
+                .bv
+                .current_environment
+                .entry_condition
+                .uses(&HashSet::from([length_path]));
+        self.bv.contract_checkers.account_data_length.record_write(
+            self.bv.current_span,
+            fits_as_bool,
+            length_is_checked,
+        );
+    }
 
-        let current_location = self.bv.current_location;
-        self.bv
-            .block_to_call
-            .insert(current_location, callee_def_id);
+    /// Checks that the amount subtracted from a balance that has not been compared with it can
+    /// exceed the balance under the entry condition.
+    fn check_balance_subtraction(
+        &mut self,
+        balance: Balance,
+        left: &mir::Operand<'tcx>,
+        right: &mir::Operand<'tcx>,
+    ) {
+        let balance_value = self.visit_operand(left);
+        let amount = self.visit_operand(right);
+        let no_underflow = amount.less_or_equal(balance_value);
+        let (no_underflow_as_bool, entry_cond_as_bool) =
+            self.bv.check_condition_value_and_reachability(&no_underflow);
+        if entry_cond_as_bool == Some(false) {
+            return;
+        }
+        self.bv.contract_checkers.lamport_underflow.record_subtraction(
+            self.bv.current_span,
+            balance,
+            no_underflow_as_bool,
+        );
+    }
 
-        let tcx = self.bv.tcx;
-        let mut call_visitor = CallVisitor::new(
-            self,
-            callee_def_id,
-            Some(callee_generic_arguments),
-            callee_generic_argument_map,
-            self.bv.current_environment.clone(),
-            func_const,
+    /// Records an instruction of the system program that allocates the data of an account or
+    /// credits it with lamports, and checks that the lamports credited to an account that the
+    /// function creates reach the rent-exempt minimum for the length of its data.
+    fn check_rent_exempt_credits(
+        &mut self,
+        instruction: SystemInstruction,
+        args: &[Spanned<mir::Operand<'tcx>>],
+    ) {
+        let mut argument = |position: Option<usize>| {
+            let arg = args.get(position?)?;
+            Some(self.visit_operand(&arg.node))
+        };
+        let Some(key) = argument(Some(instruction.key)) else {
+            return;
+        };
+        let lamports = argument(instruction.lamports);
+        let space = argument(instruction.space);
+        let credits = self.bv.contract_checkers.rent_dust.visit_instruction(
+            key,
+            lamports,
+            space,
+            self.bv.current_span,
         );
-        call_visitor.actual_args = actual_args;
-        call_visitor.actual_argument_types = actual_argument_types;
-        call_visitor.unwind = unwind;
-        call_visitor.destination = destination;
-        call_visitor.target = target;
-        call_visitor.callee_fun_val = func_to_call;
-        call_visitor.function_constant_args = func_const_args;
-        call_visitor.initial_type_cache = adt_map;
-        info!("callee_func_ref {:?}", call_visitor.callee_func_ref);
-        if call_visitor.handled_as_special_function_call() {
+        self.check_rent_exempt(credits);
+    }
+
+    /// Records a reallocation of the data of the account that the receiver of the current call
+    /// refers to, with the lamports of the account, if the function reads them, and checks that
+    /// the lamports credited to the account reach the rent-exempt minimum for the new length.
+    fn check_rent_exempt_resize(&mut self, args: &[Spanned<mir::Operand<'tcx>>]) {
+        let (Some(account), Some(new_len)) = (
+            args.first().and_then(|arg| arg.node.place()),
+            args.get(1),
+        ) else {
+            return;
+        };
+        let Some(key) = self.account_key(account.local) else {
             return;
+        };
+        let space = self.visit_operand(&new_len.node);
+        let balance = self
+            .bv
+            .contract_checkers
+            .rent_dust
+            .balance_local(&key)
+            .map(|local| self.visit_operand(&mir::Operand::Copy(mir::Place::from(local))));
+        let credits = self.bv.contract_checkers.rent_dust.visit_realloc(
+            key,
+            space,
+            balance,
+            self.bv.current_span,
+        );
+        self.check_rent_exempt(credits);
+    }
+
+    /// Checks that the given credits of accounts, with the lengths of their data and the spans of
+    /// the credits and of the allocations, reach the rent-exempt minimum for the lengths.
+    fn check_rent_exempt(
+        &mut self,
+        credits: Vec<(
+            Rc<AbstractValue>,
+            Rc<AbstractValue>,
+            rustc_span::Span,
+            rustc_span::Span,
+        )>,
+    ) {
+        let u64_ty = self.bv.tcx.types.u64;
+        for (lamports, space, credit_span, creation_span) in credits {
+            let overhead =
+                self.get_int_const_val(contract_errors::ACCOUNT_STORAGE_OVERHEAD, u64_ty);
+            let lamports_per_byte =
+                self.get_int_const_val(contract_errors::RENT_EXEMPT_LAMPORTS_PER_BYTE, u64_ty);
+            let minimum_balance = space.addition(overhead).multiply(lamports_per_byte);
+            let exempt = lamports.greater_or_equal(minimum_balance.clone());
+            let (exempt_as_bool, entry_cond_as_bool) =
+                self.bv.check_condition_value_and_reachability(&exempt);
+            if entry_cond_as_bool == Some(false) || exempt_as_bool == Some(true) {
+                continue;
+            }
+            let shortfall = render_condition(self.bv.mir, &minimum_balance.subtract(lamports));
+            self.bv.contract_checkers.rent_dust.record_credit(
+                credit_span,
+                creation_span,
+                exempt_as_bool,
+                shortfall,
+            );
         }
-        let function_summary = call_visitor.get_function_summary().unwrap_or_default();
+    }
 
-        if !function_summary.is_computed {
-            if (known_name != KnownNames::StdCloneClone || !self_ty_is_fn_ptr)
-                && call_visitor
-                    .block_visitor
-                    .bv
-                    .already_reported_errors_for_call_to
-                    .insert(call_visitor.callee_fun_val.clone())
-            {
-                let _kn = known_names::KnownNamesCache::get_known_name_for(tcx, callee_def_id);
-                call_visitor.block_visitor.report_missing_summary();
-                if known_name != KnownNames::StdCloneClone
-                    && !call_visitor.block_visitor.bv.analysis_is_incomplete
-                {
-                    return;
-                }
+    /// Checks if an instruction of the system program that transfers lamports transfers zero
+    /// lamports, or transfers them from an account to itself, on every path that reaches it.
+    fn check_no_op_transfer(
+        &mut self,
+        instruction: SystemInstruction,
+        args: &[Spanned<mir::Operand<'tcx>>],
+    ) {
+        let (Some(source), Some(lamports)) = (instruction.source, instruction.lamports) else {
+            return;
+        };
+        let (Some(source), Some(key), Some(lamports)) = (
+            args.get(source),
+            args.get(instruction.key),
+            args.get(lamports),
+        ) else {
+            return;
+        };
+        let source = self.visit_operand(&source.node);
+        let key = self.visit_operand(&key.node);
+        let lamports = self.visit_operand(&lamports.node);
+        let zero = self.get_int_const_val(0, self.bv.tcx.types.u64);
+        let checks = [
+            (lamports.equals(zero), NoOpTransfer::ZeroAmount),
+            (source.equals(key), NoOpTransfer::SameAccount),
+        ];
+        for (condition, no_op) in checks {
+            let (condition_as_bool, entry_cond_as_bool) =
+                self.bv.check_condition_value_and_reachability(&condition);
+            if entry_cond_as_bool == Some(false) {
+                return;
+            }
+            if condition_as_bool == Some(true) {
+                self.bv
+                    .contract_checkers
+                    .no_op_transfer
+                    .record(self.bv.current_span, no_op);
+                return;
             }
-        } else if function_summary.is_incomplete
-            && call_visitor
-                .block_visitor
-                .bv
-                .already_reported_errors_for_call_to
-                .insert(call_visitor.callee_fun_val.clone())
-        {
-            call_visitor.report_incomplete_summary();
         }
+    }
 
-        if known_name == KnownNames::StdCloneClone {
-            call_visitor.handle_clone(&function_summary);
-        } else {
-            call_visitor.transfer_and_refine_into_current_environment(&function_summary);
+    /// Records a call of a function of the crate that returns a Result, if the summary of the
+    /// function, which has been transferred into the destination, allows the result to be an
+    /// error at this point.
+    fn record_fallible_call(&mut self, bb: mir::BasicBlock, destination: &mir::Place<'tcx>) {
+        let Some(is_err) = self.is_err_value(destination) else {
+            return;
+        };
+        let (is_err_as_bool, entry_cond_as_bool) =
+            self.bv.check_condition_value_and_reachability(&is_err);
+        if is_err_as_bool == Some(false) || entry_cond_as_bool == Some(false) {
+            return;
         }
+        self.bv
+            .contract_checkers
+            .result_unused
+            .fallible_calls
+            .insert(bb, (destination.local, self.bv.current_span));
+    }
+
+    /// Returns the condition that the value at the given place, a local that holds a Result, is
+    /// an error, or None if the local does not hold a Result.
+    fn is_err_value(&mut self, place: &mir::Place<'tcx>) -> Option<Rc<AbstractValue>> {
+        let err_variant = ResultUnusedChecker::err_variant(self.bv.mir, place)?;
+        let tcx = self.bv.tcx;
+        let result_type = self.bv.mir.local_decls[place.local].ty;
+        let discr_type = result_type.discriminant_ty(tcx);
+        let err_discr_bits = match result_type.discriminant_for_variant(tcx, err_variant) {
+            Some(discr) => discr.val,
+            None => err_variant.as_u32() as u128,
+        };
+        let discr_path = Path::new_discriminant(self.visit_rh_place(place))
+            .canonicalize(&self.bv.current_environment);
+        let discr_value = self
+            .bv
+            .lookup_path_and_refine_result(discr_path, discr_type);
+        Some(discr_value.equals(self.get_int_const_val(err_discr_bits, discr_type)))
     }
 
     #[logfn_inputs(TRACE)]
@@ -965,6 +2560,9 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                 }
             }
         }
+        // The paths are found by iterating over hash maps, so they are sorted to make the key of
+        // the summary of the call the same in every run
+        result.sort_by(|(p1, ..), (p2, ..)| p1.cmp(p2));
         result
     }
 
@@ -1011,10 +2609,15 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         result
     }
 
-    /// Give diagnostic, depending on self.bv.options.diag_level
+    /// Give diagnostic, depending on self.bv.options.diag_level, and record the callee, if it is
+    /// known, as one whose results and side effects are unknown to the findings of the body.
     #[logfn_inputs(TRACE)]
-    pub fn report_missing_summary(&mut self) {
+    pub fn report_missing_summary(&mut self, callee: Option<DefId>) {
         if self.might_be_reachable().unwrap_or(true) {
+            if let Some(callee) = callee {
+                let name = self.bv.tcx.def_path_str(callee);
+                self.bv.havocked_callees.insert(name);
+            }
             if let Some(promotable_entry_condition) = self
                 .bv
                 .current_environment
@@ -1231,12 +2834,25 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
             }
         }
         let span = self.bv.current_span;
-        let mut warning = self
-            .bv
-            .cv
-            .session
-            .dcx()
-            .struct_span_warn(span, diagnostic.as_ref().to_string());
+        let (mut warning, severity) = if let Some(code) = arithmetic_code(&precondition.message) {
+            // The operation that may overflow is where the precondition was first made
+            let operation = precondition.spans.first().copied().unwrap_or(span);
+            let message = diagnostic.as_ref().to_string();
+            let (warning, severity, properties) =
+                self.arithmetic_diagnostic(span, operation, message, code, condition);
+            (warning, Some((severity, properties)))
+        } else {
+            let mut warning = self
+                .bv
+                .cv
+                .session
+                .dcx()
+                .struct_span_warn(span, diagnostic.as_ref().to_string());
+            if let Some(counterexample) = self.bv.counterexample(condition) {
+                warning.note(counterexample);
+            }
+            (warning, None)
+        };
         for pc_span in precondition.spans.iter() {
             let snippet = self.bv.tcx.sess.source_map().span_to_snippet(*pc_span);
             if snippet.is_ok() {
@@ -1251,7 +2867,10 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                 warning.span_note(*pc_span, format!("related location {span_str}"));
             }
         }
-        self.bv.emit_diagnostic(warning);
+        match severity {
+            Some((severity, properties)) => self.bv.emit_finding(warning, severity, properties),
+            None => self.bv.emit_diagnostic(warning),
+        }
     }
 
     /// Extend the current post condition by the given `cond`. If none was set before,
@@ -1319,7 +2938,33 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         }
 
         // If the condition is always true when we get here there is nothing to report.
+        let span = self.bv.current_span.source_callsite();
+        let condition = (self.bv.def_id, span);
         if cond_as_bool.unwrap_or(false) {
+            if function_name == KnownNames::MiraiVerify
+                && self.bv.cv.options.dual_build
+                && !self.bv.cv.shipped_semantics
+                && !self.bv.cv.verified_conditions.contains(&condition)
+            {
+                self.bv.cv.verified_conditions.push(condition);
+            }
+            return None;
+        }
+
+        // With the semantics of the shipped code, only the conditions that are no longer proven
+        // are of interest.
+        if self.bv.cv.shipped_semantics {
+            if function_name == KnownNames::MiraiVerify
+                && self
+                    .bv
+                    .cv
+                    .verified_conditions
+                    .iter()
+                    .any(|(_, verified_span)| *verified_span == span)
+                && !self.bv.cv.assumption_ledger.contains(&condition)
+            {
+                self.bv.cv.assumption_ledger.push(condition);
+            }
             return None;
         }
 
@@ -1397,12 +3042,15 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                 )
             {
                 let span = self.bv.current_span.source_callsite();
-                let warning = self
+                let mut warning = self
                     .bv
                     .cv
                     .session
                     .dcx()
                     .struct_span_warn(span, warning.clone());
+                if let Some(counterexample) = self.bv.counterexample(cond) {
+                    warning.note(counterexample);
+                }
                 self.bv.emit_diagnostic(warning);
             }
         }
@@ -1447,9 +3095,15 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                             | Expression::Variable { path, .. } = &operand.expression
                             {
                                 let mut path_prefix = path;
-                                while let PathEnum::QualifiedPath { qualifier, .. } =
-                                    &path_prefix.value
+                                while let PathEnum::QualifiedPath {
+                                    qualifier,
+                                    selector,
+                                    ..
+                                } = &path_prefix.value
                                 {
+                                    if !tag.is_propagated_by_selector(selector) {
+                                        break;
+                                    }
                                     path_prefix = qualifier;
 
                                     let path_prefix_rustc_type = self
@@ -1568,15 +3222,13 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         let cond_val = self.visit_operand(cond);
         let not_cond_val = cond_val.logical_not();
         if let mir::UnwindAction::Cleanup(unwind_target) = unwind {
-            let panic_exit_condition =
-                self.bv
-                    .current_environment
-                    .entry_condition
-                    .and(if expected {
-                        not_cond_val.clone()
-                    } else {
-                        cond_val.clone()
-                    });
+            let panic_condition = if expected { &not_cond_val } else { &cond_val };
+            self.record_branch_condition_span(unwind_target, panic_condition);
+            let panic_exit_condition = self
+                .bv
+                .current_environment
+                .entry_condition
+                .and(panic_condition.clone());
             if !panic_exit_condition.is_bottom() {
                 self.bv
                     .current_environment
@@ -1584,15 +3236,23 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                     .insert_mut(unwind_target, panic_exit_condition);
             }
         };
+        let normal_condition = if expected { &cond_val } else { &not_cond_val };
+        self.record_branch_condition_span(target, normal_condition);
+        // Indices are recorded while the fixed point is computed, with the entry condition of the
+        // block, and not again while checking, except for indices into the instruction data
+        if let mir::AssertKind::BoundsCheck { len, index } = msg {
+            let in_bounds = normal_condition.clone();
+            if self.bv.check_for_errors {
+                self.check_instruction_data_index(len, index, in_bounds);
+            } else {
+                self.check_caller_controlled_index(len, index, target, in_bounds);
+            }
+        }
         let normal_exit_condition = self
             .bv
             .current_environment
             .entry_condition
-            .and(if expected {
-                cond_val.clone()
-            } else {
-                not_cond_val.clone()
-            });
+            .and(normal_condition.clone());
         if !normal_exit_condition.is_bottom() {
             self.bv
                 .current_environment
@@ -1605,138 +3265,438 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                     // Do not complain about compile time constants known to the compiler.
                     // Leave that to the compiler.
                 } else {
-                    let (cond_as_bool_opt, entry_cond_as_bool) =
-                        self.bv.check_condition_value_and_reachability(&cond_val);
-
-                    // Quick exit if things are known.
-                    if let Some(false) = entry_cond_as_bool {
-                        // We can't reach this assertion, so just return.
-                        return;
-                    }
-                    if let Some(cond_as_bool) = cond_as_bool_opt {
-                        if expected == cond_as_bool {
-                            // If the condition is always as expected when we get here, so there is nothing to report.
-                            return;
-                        }
-                        // The condition is known to differ from expected so if we always get here if called,
-                        // emit a diagnostic.
-                        if entry_cond_as_bool.unwrap_or(false) {
-                            let error = get_assert_msg_description(msg);
-                            let span = self.bv.current_span;
-                            let warning = self
-                                .bv
-                                .cv
-                                .session
-                                .dcx()
-                                .struct_span_warn(span, error.to_string());
-                            self.bv.emit_diagnostic(warning);
-                            // No need to push a precondition, the caller can never satisfy it.
-                            return;
-                        }
+                    let description = get_assert_msg_description(msg);
+                    if is_overflow_check(msg) {
+                        // The assertion is only present if overflow checks are enabled.
+                        self.check_assertion(
+                            cond_val,
+                            not_cond_val,
+                            expected,
+                            &format!("{description}, which aborts execution"),
+                            Some("HEPHA-ARITH-ABORT"),
+                        );
+                    } else if is_division_check(msg) && self.carries_untrusted_input(&cond_val) {
+                        self.check_division_by_zero(cond_val, expected);
+                    } else {
+                        self.check_assertion(cond_val, not_cond_val, expected, description, None);
                     }
+                }
+            }
+        }
 
-                    // At this point, we don't know that this assert is unreachable and we don't know
-                    // that the condition is as expected, so we need to warn about it somewhere.
-                    check_for_early_return!(self.bv);
-                    // Get a condition which, if true, guarantees that cond_val will match the expected value.
-                    // The expression will not contain any local variables, so the caller will be able to
-                    // deal with it. If may not, however, be weak enough for the caller to satisfy
-                    // leading to false positives. When this arises in practice, it would be because
-                    // some weakness in the analysis of the current function has lead to an imprecise
-                    // value for cond_val.
-                    let promotable_cond_val = (if expected { cond_val } else { not_cond_val })
-                        .extract_promotable_disjuncts(false);
-                    check_for_early_return!(self.bv);
-                    let promotable_entry_cond = self
-                        .bv
-                        .current_environment
-                        .entry_condition
-                        .extract_promotable_conjuncts(false);
+        // Overflow checks of these operations are controlled by the overflow-checks setting.
+        fn is_overflow_check(msg: &mir::AssertMessage<'_>) -> bool {
+            use mir::AssertKind::*;
+            use mir::BinOp;
+            matches!(
+                msg,
+                Overflow(
+                    BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Shl | BinOp::Shr,
+                    _,
+                    _
+                ) | OverflowNeg(_)
+            )
+        }
+
+        // The divisor of these operations is checked against zero, whatever the settings.
+        fn is_division_check(msg: &mir::AssertMessage<'_>) -> bool {
+            matches!(
+                msg,
+                mir::AssertKind::DivisionByZero(_) | mir::AssertKind::RemainderByZero(_)
+            )
+        }
+    }
+
+    /// Checks that the divisor of a division or a remainder, which is untrusted input, is not zero
+    /// when the operation is reached, and issues a warning with the provenance of the divisor if
+    /// it may be. The caller can make such a divisor zero, so that the program panics. A division
+    /// that may be by zero is left out of the findings of numerical precision, which would report
+    /// it again.
+    fn check_division_by_zero(&mut self, cond_val: Rc<AbstractValue>, expected: bool) {
+        let (cond_as_bool, entry_cond_as_bool) =
+            self.bv.check_condition_value_and_reachability(&cond_val);
+        if entry_cond_as_bool == Some(false) || cond_as_bool == Some(expected) {
+            return;
+        }
+        let span = self.bv.current_span;
+        self.bv
+            .contract_checkers
+            .numerical_precision
+            .unchecked_divisions
+            .insert(span);
+        let code = "HEPHA-DIV-BY-ZERO";
+        let message = if cond_as_bool.is_some() && entry_cond_as_bool == Some(true) {
+            format!("[{code}] division by zero (panics in BPF)")
+        } else {
+            format!("[{code}] possible division by zero (panics in BPF)")
+        };
+        let (warning, severity, properties) =
+            self.arithmetic_diagnostic(span, span, message, code, &cond_val);
+        self.bv.emit_finding(warning, severity, properties);
+    }
+
+    /// Records an index into the instruction data of an entrypoint, such as `instruction_data[0]`,
+    /// that is not known to be within the length of the data. The instruction data is the
+    /// collection whose length is checked.
+    fn check_instruction_data_index(
+        &mut self,
+        len: &mir::Operand<'tcx>,
+        index: &mir::Operand<'tcx>,
+        in_bounds: Rc<AbstractValue>,
+    ) {
+        let Some(data_length) = self.instruction_data_length() else {
+            return;
+        };
+        if self.visit_operand(len) != data_length {
+            return;
+        }
+        let required_length = match &self.visit_operand(index).expression {
+            Expression::CompileTimeConstant(ConstantDomain::U128(index)) => Some(index + 1),
+            _ => None,
+        };
+        if let Some(end) = required_length {
+            self.record_instruction_data_read(end - 1, Some(end));
+        }
+        let offset = required_length.map(|end| end - 1);
+        self.check_instruction_data_bounds(in_bounds, offset, required_length);
+    }
+
+    /// Records a slice of the instruction data of an entrypoint, such as `instruction_data[1..9]`,
+    /// whose range is not known to be within the length of the data. The slice is taken by the
+    /// call of Index::index with the data and the range, which is checked here rather than in
+    /// the core library, so that the finding is on the slice expression.
+    fn check_instruction_data_slice(&mut self, args: &[Spanned<mir::Operand<'tcx>>]) {
+        let [data, range] = args else {
+            return;
+        };
+        let Some(data_length) = self.instruction_data_length() else {
+            return;
+        };
+        let data = self.visit_operand(&data.node);
+        if self.slice_length(data) != data_length {
+            return;
+        }
+        let Some(range) = range.node.place() else {
+            return;
+        };
+        let range_ty = self
+            .type_visitor()
+            .get_rustc_place_type(&range, self.bv.current_span);
+        let TyKind::Adt(range_def, _) = range_ty.kind() else {
+            return;
+        };
+        if self.bv.tcx.crate_name(range_def.did().krate).as_str() != "core" {
+            return;
+        }
+        // The field of the range that holds the bound that must be within the data, true if the
+        // bound is included in the slice, and the field that holds the start of the slice, if any
+        let range_name = self.bv.tcx.item_name(range_def.did());
+        let (field, inclusive, start_field) = match range_name.as_str() {
+            "Range" => (1, false, Some(0)),
+            "RangeFrom" => (0, false, Some(0)),
+            "RangeTo" => (0, false, None),
+            "RangeInclusive" => (1, true, Some(0)),
+            "RangeToInclusive" => (0, true, None),
+            _ => return,
+        };
+        let range_path = self.visit_rh_place(&range);
+        let bound_path = Path::new_field(range_path.clone(), field);
+        let bound = self
+            .bv
+            .lookup_path_and_refine_result(bound_path, self.bv.tcx.types.usize);
+        let constant_bound = match &bound.expression {
+            Expression::CompileTimeConstant(ConstantDomain::U128(bound)) => Some(*bound),
+            _ => None,
+        };
+        let (in_bounds, required_length) = if inclusive {
+            (
+                bound.less_than(data_length),
+                constant_bound.map(|bound| bound + 1),
+            )
+        } else {
+            (bound.less_or_equal(data_length), constant_bound)
+        };
+        let start = match start_field {
+            Some(start_field) => {
+                let start_path = Path::new_field(range_path, start_field);
+                let start = self
+                    .bv
+                    .lookup_path_and_refine_result(start_path, self.bv.tcx.types.usize);
+                match &start.expression {
+                    Expression::CompileTimeConstant(ConstantDomain::U128(start)) => Some(*start),
+                    _ => None,
+                }
+            }
+            None => Some(0),
+        };
+        if let Some(start) = start {
+            // A range with a start and no end reads to the end of the data
+            let end = if range_name.as_str() == "RangeFrom" {
+                None
+            } else {
+                required_length
+            };
+            self.record_instruction_data_read(start, end);
+        }
+        self.check_instruction_data_bounds(in_bounds, start, required_length);
+    }
+
+    /// Records an index or slice of the instruction data that is not known to be in bounds under
+    /// the entry condition, with the offset where it starts, if that is a constant.
+    fn check_instruction_data_bounds(
+        &mut self,
+        in_bounds: Rc<AbstractValue>,
+        offset: Option<u128>,
+        required_length: Option<u128>,
+    ) {
+        let (in_bounds_as_bool, entry_cond_as_bool) =
+            self.bv.check_condition_value_and_reachability(&in_bounds);
+        if entry_cond_as_bool == Some(false) || in_bounds_as_bool == Some(true) {
+            return;
+        }
+        if let Some(offset) = offset {
+            self.bv
+                .contract_checkers
+                .unvalidated_deserialization
+                .unchecked_reads
+                .push((self.bv.current_span, self.bv.current_location.block, offset));
+        }
+        self.bv
+            .contract_checkers
+            .instruction_data_bounds
+            .record(self.bv.current_span, required_length);
+    }
+
+    /// Records a read of the instruction data at a constant offset, which ends at the given offset
+    /// or at the end of the data, for the comparison with a deserialized prefix of the data.
+    fn record_instruction_data_read(&mut self, offset: u128, end: Option<u128>) {
+        self.bv
+            .contract_checkers
+            .deserialization_offsets
+            .reads
+            .push((self.bv.current_span, offset, end));
+    }
+
+    // Returns the length of the instruction data, if the function being analyzed is an entrypoint
+    fn instruction_data_length(&mut self) -> Option<Rc<AbstractValue>> {
+        if !self.bv.contract_checkers.amount_provenance.in_entrypoint {
+            return None;
+        }
+        let data = self.visit_operand(&mir::Operand::Copy(mir::Place::from(
+            mir::Local::from_usize(3),
+        )));
+        Some(self.slice_length(data))
+    }
+
+    // Returns the length of the slice that the given slice pointer refers to, which is the
+    // metadata of the pointer, the way PtrMetadata reads it
+    pub fn slice_length(&mut self, slice_pointer: Rc<AbstractValue>) -> Rc<AbstractValue> {
+        let length_path = Path::new_field(Path::get_as_path(slice_pointer), 1);
+        self.bv
+            .lookup_path_and_refine_result(length_path, self.bv.tcx.types.usize)
+    }
+
+    /// Records an index into a local array or slice of an entrypoint that is read from data that
+    /// the caller controls, if the index is not known to be within the length of the collection.
+    /// The block that follows the bounds check is the one that indexes the collection.
+    fn check_caller_controlled_index(
+        &mut self,
+        len: &mir::Operand<'tcx>,
+        index: &mir::Operand<'tcx>,
+        target: mir::BasicBlock,
+        in_bounds: Rc<AbstractValue>,
+    ) {
+        let provenance = &self.bv.contract_checkers.amount_provenance;
+        let Some(source) = provenance.source_of(index) else {
+            return;
+        };
+        let Some(index) = index.place().map(|place| place.local) else {
+            return;
+        };
+        if !provenance.is_caller_controlled(source) {
+            return;
+        }
+        let description = provenance.describe_value(self.bv.mir, source, "index");
+        let class = provenance.class_of(source);
+        let Some(collection) =
+            UncheckedIndexChecker::indexed_collection(self.bv.mir, target, index)
+        else {
+            return;
+        };
+        // Indices into the data of an account are checked against the length of the data
+        let data_locals = &self.bv.contract_checkers.account_data_length.data_locals;
+        if data_locals.contains_key(&collection) {
+            return;
+        }
+        let (in_bounds_as_bool, entry_cond_as_bool) =
+            self.bv.check_condition_value_and_reachability(&in_bounds);
+        if entry_cond_as_bool == Some(false) || in_bounds_as_bool == Some(true) {
+            return;
+        }
+        let length = match &self.visit_operand(len).expression {
+            Expression::CompileTimeConstant(ConstantDomain::U128(length)) => Some(*length),
+            _ => None,
+        };
+        self.bv.contract_checkers.unchecked_index.record_index(
+            self.bv.mir,
+            self.bv.current_span,
+            collection,
+            (index, description, class),
+            length,
+        );
+    }
 
-                    if promotable_cond_val.is_none()
-                        || promotable_entry_cond.is_none()
-                        || self.bv.preconditions.len() >= k_limits::MAX_INFERRED_PRECONDITIONS
-                        || (self.bv.function_being_analyzed_is_root()
-                            && self.bv.cv.options.diag_level >= DiagLevel::Library)
-                    {
-                        // Can't make this the caller's problem.
-                        let warning = format!("possible {}", get_assert_msg_description(msg));
-                        let span = self.bv.current_span;
-                        let warning = self.bv.cv.session.dcx().struct_span_warn(span, warning);
-                        self.bv.emit_diagnostic(warning);
-                        return;
-                    }
+    /// Issues a warning if cond_val might not have the expected value when the current block is
+    /// reached, or makes this the caller's problem by inferring a precondition.
+    /// The message of a warning starts with the code of the finding, if there is one.
+    #[logfn_inputs(TRACE)]
+    fn check_assertion(
+        &mut self,
+        cond_val: Rc<AbstractValue>,
+        not_cond_val: Rc<AbstractValue>,
+        expected: bool,
+        description: &str,
+        code: Option<&str>,
+    ) {
+        let with_code = |message: String| match code {
+            Some(code) => format!("[{code}] {message}"),
+            None => message,
+        };
+        if !expected && self.overflow_is_excluded_by_bounds(&cond_val) {
+            // The operands are known to be small enough without asking the solver.
+            return;
+        }
+        let (cond_as_bool_opt, entry_cond_as_bool) =
+            self.bv.check_condition_value_and_reachability(&cond_val);
+        let checked_condition = cond_val.clone();
 
-                    // Make it the caller's problem by pushing a precondition.
-                    // After, of course, removing any promoted preconditions that match the current
-                    // source span.
-                    let sp = self.bv.current_span;
-                    self.bv
-                        .preconditions
-                        .retain(|pc| pc.spans.last() != Some(&sp));
-                    // To make sure that this assertion never fails, we should either never
-                    // get here (!entry_condition) or expected_cond should be true.
-                    let condition = promotable_entry_cond
-                        .unwrap()
-                        .logical_not()
-                        .or(promotable_cond_val.unwrap());
-                    let message = Rc::from(String::from(get_assert_msg_description(msg)));
-                    let precondition = Precondition {
-                        condition,
-                        message,
-                        provenance: None,
-                        spans: vec![self.bv.current_span],
-                    };
-                    self.bv.preconditions.push(precondition);
+        // Quick exit if things are known.
+        if let Some(false) = entry_cond_as_bool {
+            // We can't reach this assertion, so just return.
+            return;
+        }
+        if let Some(cond_as_bool) = cond_as_bool_opt {
+            if expected == cond_as_bool {
+                // If the condition is always as expected when we get here, so there is nothing to report.
+                return;
+            }
+            // The condition is known to differ from expected so if we always get here if called,
+            // emit a diagnostic.
+            if entry_cond_as_bool.unwrap_or(false) {
+                let span = self.bv.current_span;
+                let message = with_code(description.to_string());
+                if let Some(code) = code {
+                    let (warning, severity, properties) =
+                        self.arithmetic_diagnostic(span, span, message, code, &cond_val);
+                    self.bv.emit_finding(warning, severity, properties);
+                } else {
+                    let warning = self.bv.cv.session.dcx().struct_span_warn(span, message);
+                    self.bv.emit_diagnostic(warning);
                 }
+                // No need to push a precondition, the caller can never satisfy it.
+                return;
             }
         }
 
-        fn get_assert_msg_description<'tcx>(msg: &mir::AssertMessage<'tcx>) -> &'tcx str {
-            use mir::AssertKind::*;
-            use mir::BinOp;
-            use rustc_hir::CoroutineDesugaring;
-            use rustc_hir::CoroutineKind;
-            match msg {
-                BoundsCheck { .. } => "index out of bounds",
-                MisalignedPointerDereference { .. } => "misaligned pointer dereference",
-                Overflow(BinOp::Add, _, _) => "attempt to add with overflow",
-                Overflow(BinOp::Sub, _, _) => "attempt to subtract with underflow",
-                Overflow(BinOp::Mul, _, _) => "attempt to multiply with overflow",
-                Overflow(BinOp::Div, _, _) => "attempt to divide with overflow",
-                Overflow(BinOp::Rem, _, _) => "attempt to calculate the remainder with overflow",
-                OverflowNeg(_) => "attempt to negate with overflow",
-                Overflow(BinOp::Shr, _, _) => "attempt to shift right with overflow",
-                Overflow(BinOp::Shl, _, _) => "attempt to shift left with overflow",
-                Overflow(..) => "bug, op cannot overflow",
-                DivisionByZero(_) => "attempt to divide by zero",
-                RemainderByZero(_) => "attempt to calculate the remainder with a divisor of zero",
-                ResumedAfterReturn(CoroutineKind::Coroutine(_)) => {
-                    "coroutine resumed after completion"
-                }
-                ResumedAfterReturn(CoroutineKind::Desugared(CoroutineDesugaring::Async, _)) => {
-                    "`async fn` resumed after completion"
-                }
-                ResumedAfterReturn(CoroutineKind::Desugared(CoroutineDesugaring::AsyncGen, _)) => {
-                    "`async gen fn` resumed after completion"
-                }
-                ResumedAfterReturn(CoroutineKind::Desugared(CoroutineDesugaring::Gen, _)) => {
-                    "`gen fn` should just keep returning `None` after completion"
-                }
-                ResumedAfterPanic(CoroutineKind::Coroutine(_)) => {
-                    "coroutine resumed after panicking"
-                }
-                ResumedAfterPanic(CoroutineKind::Desugared(CoroutineDesugaring::Async, _)) => {
-                    "`async fn` resumed after panicking"
-                }
-                ResumedAfterPanic(CoroutineKind::Desugared(CoroutineDesugaring::AsyncGen, _)) => {
-                    "`async gen fn` resumed after panicking"
-                }
-                ResumedAfterPanic(CoroutineKind::Desugared(CoroutineDesugaring::Gen, _)) => {
-                    "`gen fn` should just keep returning `None` after panicking"
-                }
+        // At this point, we don't know that this assert is unreachable and we don't know
+        // that the condition is as expected, so we need to warn about it somewhere.
+        check_for_early_return!(self.bv);
+        // Get a condition which, if true, guarantees that cond_val will match the expected value.
+        // The expression will not contain any local variables, so the caller will be able to
+        // deal with it. If may not, however, be weak enough for the caller to satisfy
+        // leading to false positives. When this arises in practice, it would be because
+        // some weakness in the analysis of the current function has lead to an imprecise
+        // value for cond_val.
+        let promotable_cond_val =
+            (if expected { cond_val } else { not_cond_val }).extract_promotable_disjuncts(false);
+        check_for_early_return!(self.bv);
+        let promotable_entry_cond = self
+            .bv
+            .current_environment
+            .entry_condition
+            .extract_promotable_conjuncts(false);
+
+        if promotable_cond_val.is_none()
+            || promotable_entry_cond.is_none()
+            || self.bv.preconditions.len() >= k_limits::MAX_INFERRED_PRECONDITIONS
+            || (self.bv.function_being_analyzed_is_root()
+                && self.bv.cv.options.diag_level >= DiagLevel::Library)
+        {
+            // Can't make this the caller's problem.
+            let message = with_code(format!("possible {description}"));
+            let span = self.bv.current_span;
+            if let Some(code) = code {
+                let (warning, severity, properties) =
+                    self.arithmetic_diagnostic(span, span, message, code, &checked_condition);
+                self.bv.emit_finding(warning, severity, properties);
+            } else {
+                let warning = self.bv.cv.session.dcx().struct_span_warn(span, message);
+                self.bv.emit_diagnostic(warning);
             }
+            return;
         }
+
+        // Make it the caller's problem by pushing a precondition.
+        // After, of course, removing any promoted preconditions that match the current
+        // source span.
+        let sp = self.bv.current_span;
+        self.bv
+            .preconditions
+            .retain(|pc| pc.spans.last() != Some(&sp));
+        // To make sure that this assertion never fails, we should either never
+        // get here (!entry_condition) or expected_cond should be true.
+        let condition = promotable_entry_cond
+            .unwrap()
+            .logical_not()
+            .or(promotable_cond_val.unwrap());
+        let message = Rc::from(description);
+        let precondition = Precondition {
+            condition,
+            message,
+            provenance: None,
+            spans: vec![self.bv.current_span],
+        };
+        self.bv.preconditions.push(precondition);
+    }
+
+    /// True if the given value is the overflow flag of an operation whose operands have bounds
+    /// that exclude an overflow.
+    fn overflow_is_excluded_by_bounds(&mut self, overflow_flag: &Rc<AbstractValue>) -> bool {
+        let (bin_op, left, right, result_type) = match &overflow_flag.expression {
+            Expression::AddOverflows {
+                left,
+                right,
+                result_type,
+            } => (mir::BinOp::Add, left, right, result_type),
+            Expression::MulOverflows {
+                left,
+                right,
+                result_type,
+            } => (mir::BinOp::Mul, left, right, result_type),
+            Expression::ShlOverflows {
+                left,
+                right,
+                result_type,
+            } => (mir::BinOp::Shl, left, right, result_type),
+            Expression::ShrOverflows {
+                left,
+                right,
+                result_type,
+            } => (mir::BinOp::Shr, left, right, result_type),
+            Expression::SubOverflows {
+                left,
+                right,
+                result_type,
+            } => (mir::BinOp::Sub, left, right, result_type),
+            _ => return false,
+        };
+        let Some(left_bounds) = self.bv.bound_interval_of(left) else {
+            return false;
+        };
+        let Some(right_bounds) = self.bv.bound_interval_of(right) else {
+            return false;
+        };
+        Self::bounds_exclude_overflow(bin_op, *result_type, left_bounds, right_bounds)
     }
 
     /// Checks if the current entry condition is not known to be false.
@@ -1754,7 +3714,7 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
             .current_environment
             .entry_condition
             .as_bool_if_known();
-        if entry_cond_as_bool.is_none() {
+        if entry_cond_as_bool.is_none() && !self.bv.is_cancelled() {
             // The abstract domains are unable to decide if the entry condition is always true or
             // always false.
             // See if the SMT solver can prove that the entry condition is always false.
@@ -1818,14 +3778,46 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                 let specialized_ty = self
                     .type_visitor()
                     .specialize_type(*ty, &self.type_visitor().generic_argument_map);
-                self.visit_cast(path, *cast_kind, operand, specialized_ty);
+                self.visit_cast(path.clone(), *cast_kind, operand, specialized_ty);
+                if specialized_ty.is_floating_point() {
+                    self.bv
+                        .attach_tag_to_value_at_path(Tag::FLOATING_POINT, path, specialized_ty);
+                    self.bv
+                        .contract_checkers
+                        .float_in_money_path
+                        .record_source(self.bv.current_span);
+                }
             }
             mir::Rvalue::BinaryOp(bin_op, box (left_operand, right_operand)) => {
-                if let Some(bin_op) = bin_op.overflowing_to_wrapping() {
-                    self.visit_checked_binary_op(path, bin_op, left_operand, right_operand);
+                // The units are read before the assignment, which may overwrite an operand
+                let sol_unit = self.visit_sol_units(*bin_op, left_operand, right_operand);
+                let result_path = if let Some(bin_op) = bin_op.overflowing_to_wrapping() {
+                    self.visit_checked_binary_op(path.clone(), bin_op, left_operand, right_operand);
+                    Path::new_field(path, 0)
                 } else {
-                    self.visit_binary_op(path, *bin_op, left_operand, right_operand);
+                    self.visit_binary_op(path.clone(), *bin_op, left_operand, right_operand);
+                    path
+                };
+                let mut tags = match sol_unit {
+                    Some(SolUnit::Lamports) => vec![Tag::LAMPORTS],
+                    Some(SolUnit::Sol) => vec![Tag::SOL],
+                    _ => vec![],
                 };
+                let result_type = self
+                    .type_visitor()
+                    .get_path_rustc_type(&result_path, self.bv.current_span);
+                // Arithmetic on floats is floating point arithmetic, unlike comparisons of floats
+                if result_type.is_floating_point() {
+                    tags.push(Tag::FLOATING_POINT);
+                    self.bv
+                        .contract_checkers
+                        .float_in_money_path
+                        .record_source(self.bv.current_span);
+                }
+                if !tags.is_empty() {
+                    self.bv
+                        .attach_tags_to_value_at_path(&tags, result_path, result_type);
+                }
             }
             mir::Rvalue::NullaryOp(null_op, ty) => {
                 let specialized_ty = self
@@ -1856,17 +3848,17 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
     fn visit_use(&mut self, path: Rc<Path>, operand: &mir::Operand<'tcx>) {
         match operand {
             mir::Operand::Copy(place) => {
-                if self.bv.reentrancy_checker.check_for_balance_variable && self.bv.reentrancy_checker.temporary_variable_for_balance == Some(*place) {
-                    self.bv.reentrancy_checker.temporary_variable_for_balance = self.bv.reentrancy_checker.current_assign_destination;
+                if self.bv.contract_checkers.reentrancy.check_for_balance_variable && self.bv.contract_checkers.reentrancy.temporary_variable_for_balance == Some(*place) {
+                    self.bv.contract_checkers.reentrancy.temporary_variable_for_balance = self.bv.contract_checkers.reentrancy.current_assign_destination;
                 }
                 self.visit_used_copy(path, place);
             }
             mir::Operand::Move(place) => {
-                if self.bv.reentrancy_checker.check_for_balance_variable {
-                    if let Some(temporary_place) = self.bv.reentrancy_checker.temporary_variable_for_balance {
+                if self.bv.contract_checkers.reentrancy.check_for_balance_variable {
+                    if let Some(temporary_place) = self.bv.contract_checkers.reentrancy.temporary_variable_for_balance {
                         if *place == temporary_place || place.local == temporary_place.local {
-                            self.bv.reentrancy_checker.temporary_variable_for_balance = self.bv.reentrancy_checker.current_assign_destination;
-                            self.bv.reentrancy_checker.check_for_balance_variable = false;
+                            self.bv.contract_checkers.reentrancy.temporary_variable_for_balance = self.bv.contract_checkers.reentrancy.current_assign_destination;
+                            self.bv.contract_checkers.reentrancy.check_for_balance_variable = false;
                         }
                     }
                 }
@@ -2310,6 +4302,9 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                     source_value = self.bv.lookup_path_and_refine_result(discr_path, ty);
                 }
                 let result = source_value.cast(ExpressionType::from(ty.kind()));
+                if matches!(cast_kind, mir::CastKind::IntToInt) {
+                    self.record_narrowing_cast(&source_value, source_type, ty);
+                }
                 if result != source_value || (source_type.is_trait() && !ty.is_trait()) {
                     self.type_visitor_mut()
                         .set_path_rustc_type(Path::get_as_path(result.clone()), ty);
@@ -2383,6 +4378,24 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
     ) {
         let left = self.visit_operand(left_operand);
         let right = self.visit_operand(right_operand);
+        if self.wraps_around_on_overflow()
+            && matches!(
+                bin_op,
+                mir::BinOp::Add
+                    | mir::BinOp::Sub
+                    | mir::BinOp::Mul
+                    | mir::BinOp::Shl
+                    | mir::BinOp::Shr
+            )
+        {
+            let target_type = self
+                .type_visitor()
+                .get_target_path_type(&path, self.bv.current_span);
+            if target_type.is_integer() {
+                self.visit_wrapping_binary_op(path, bin_op, target_type, left, right);
+                return;
+            }
+        }
         let mut result = match bin_op {
             mir::BinOp::Add | mir::BinOp::AddUnchecked | mir::BinOp::AddWithOverflow => {
                 left.addition(right)
@@ -2494,6 +4507,298 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         }
     }
 
+    /// Without overflow checks, an arithmetic operation that overflows wraps around rather than
+    /// aborting. Assign the wrapped result to path, so that the code that follows sees the value
+    /// that is actually computed, and report the possibility of wrapping around.
+    #[logfn_inputs(TRACE)]
+    fn visit_wrapping_binary_op(
+        &mut self,
+        path: Rc<Path>,
+        bin_op: mir::BinOp,
+        target_type: ExpressionType,
+        left: Rc<AbstractValue>,
+        right: Rc<AbstractValue>,
+    ) {
+        let (result, overflow_flag) =
+            Self::do_checked_binary_op(bin_op, target_type, left.clone(), right.clone());
+        let modulo = target_type.modulo_value();
+        let wrapped_result = if overflow_flag.as_bool_if_known() == Some(false) {
+            result
+        } else if matches!(bin_op, mir::BinOp::Shl | mir::BinOp::Shr) {
+            // The shift amount is taken modulo the bit length of the type.
+            let bit_length: Rc<AbstractValue> = Rc::new((target_type.bit_length() as u128).into());
+            let masked_right = right.remainder(bit_length);
+            let masked_result = if bin_op == mir::BinOp::Shl {
+                left.shift_left(masked_right)
+            } else {
+                left.shr(masked_right)
+            };
+            overflow_flag.conditional_expression(masked_result, result)
+        } else if modulo.is_bottom() {
+            // todo: figure out an expression that represents the truncated overflow of a
+            // signed operation.
+            let unknown_typed_value = AbstractValue::make_typed_unknown(target_type, path.clone());
+            overflow_flag.conditional_expression(unknown_typed_value, result)
+        } else if bin_op == mir::BinOp::Sub {
+            overflow_flag.conditional_expression(result.addition(modulo), result)
+        } else {
+            result.remainder(modulo)
+        };
+        self.bv.update_value_at(path, wrapped_result);
+        self.bv.wrapping_operations.insert(
+            self.bv.current_location,
+            (mir::AssertKind::Overflow(bin_op, (), ()), overflow_flag),
+        );
+    }
+
+    /// Creates the diagnostic of an arithmetic finding at the current location. In an entrypoint,
+    /// a note names the range of data that an operand, or an argument of the call, is read from,
+    /// or else tells where the amounts of the operation, or the arguments of the call, come from.
+    /// The finding is only a note if they come from an account that the program owns. The message
+    /// says so if the condition that fails, which is stated in terms of the operands, carries
+    /// untrusted input, so that such findings can be triaged first.
+    fn arithmetic_diagnostic(
+        &self,
+        span: rustc_span::Span,
+        operation: rustc_span::Span,
+        message: String,
+        code: &str,
+        condition: &Rc<AbstractValue>,
+    ) -> (Diag<'compilation, ()>, Severity, FindingProperties) {
+        let provenance = &self.bv.contract_checkers.amount_provenance;
+        let source = provenance.source_at(self.bv.mir, self.bv.current_location);
+        let severity = if source.is_some_and(|source| provenance.is_program_owned(source)) {
+            Severity::Note
+        } else {
+            Severity::Warning
+        };
+        let mut classes: Vec<String> = source
+            .map(|source| provenance.class_of(source).to_string())
+            .into_iter()
+            .collect();
+        let message = if self.carries_untrusted_input(condition) {
+            classes.push("untrusted_input".to_string());
+            format!("{message}, with an operand that is untrusted input")
+        } else {
+            message
+        };
+        let severity = checker_registry::configured_severity(code, severity, self.bv.cv.options);
+        let mut diagnostic =
+            Diag::new(self.bv.cv.session.dcx(), severity.level(), message).with_span(span);
+        let operand = self.bv.contract_checkers.arithmetic_overflow.describe_operand_at(
+            self.bv.mir,
+            self.bv.current_location,
+            provenance,
+        );
+        if let Some(description) =
+            operand.or_else(|| source.map(|source| provenance.describe(self.bv.mir, source)))
+        {
+            diagnostic.note(description);
+        }
+        if let Some(counterexample) = self.bv.counterexample(condition) {
+            diagnostic.note(counterexample);
+        }
+        if let Some(parts) = fixes::checked_arithmetic(self.bv.tcx, operation) {
+            diagnostic.multipart_suggestion(
+                "use checked arithmetic",
+                parts,
+                Applicability::MachineApplicable,
+            );
+        }
+        let properties = self
+            .bv
+            .finding_properties(Some(&self.bv.current_environment.entry_condition), classes);
+        (diagnostic, severity, properties)
+    }
+
+    /// True if the value carries the built-in tag of untrusted input, or refers to a value that
+    /// does.
+    fn carries_untrusted_input(&self, value: &Rc<AbstractValue>) -> bool {
+        self.carries_tag(value, Tag::UNTRUSTED_INPUT)
+    }
+
+    /// True if the value carries the given tag, or refers to a value that does.
+    fn carries_tag(&self, value: &Rc<AbstractValue>, tag: Tag) -> bool {
+        value.has_tag(&tag).as_bool_if_known().unwrap_or(false)
+            || value
+                .expression
+                .has_tagged_subcomponent(&tag, &self.bv.current_environment)
+    }
+
+    /// True if arithmetic operations in the body being analyzed wrap around when they overflow.
+    /// Bodies from other crates are compiled with their own settings and may wrap on purpose,
+    /// so only local bodies are treated this way.
+    fn wraps_around_on_overflow(&self) -> bool {
+        !self.bv.cv.overflow_checks && self.bv.def_id.is_local()
+    }
+
+    /// Checks the operations of the block that wrap around if they overflow. Statements are not
+    /// visited when checking for errors, so this uses the overflow flags from the last visit.
+    #[logfn_inputs(TRACE)]
+    fn check_wrapping_operations(&mut self, bb: mir::BasicBlock) {
+        let mut operations: Vec<(mir::Location, mir::AssertKind<()>, Rc<AbstractValue>)> = self
+            .bv
+            .wrapping_operations
+            .iter()
+            .filter(|(location, _)| location.block == bb)
+            .map(|(location, (kind, overflow_flag))| {
+                (*location, kind.clone(), overflow_flag.clone())
+            })
+            .collect();
+        operations.sort_by_key(|(location, _, _)| location.statement_index);
+        for (location, kind, overflow_flag) in operations {
+            self.bv.current_location = location;
+            self.bv.current_span = self.bv.mir.source_info(location).span;
+            let description = get_assert_msg_description(&kind);
+            let not_overflow_flag = overflow_flag.logical_not();
+            self.check_assertion(
+                overflow_flag,
+                not_overflow_flag,
+                false,
+                &format!("{description}, which wraps around"),
+                Some("HEPHA-ARITH-WRAP"),
+            );
+        }
+    }
+
+    /// Records an integer cast of an amount that is untrusted input to a type whose range does
+    /// not include every value of the type of the amount, such as `amount as u32` for a u64.
+    fn record_narrowing_cast(
+        &mut self,
+        amount: &Rc<AbstractValue>,
+        source_type: Ty<'tcx>,
+        target_type: Ty<'tcx>,
+    ) {
+        let source = ExpressionType::from(source_type.kind());
+        let target = ExpressionType::from(target_type.kind());
+        if narrowing_condition(amount, source, target).is_none()
+            || !self.carries_untrusted_input(amount)
+        {
+            return;
+        }
+        self.bv
+            .narrowing_casts
+            .insert(self.bv.current_location, (source, target, amount.clone()));
+    }
+
+    /// Checks the integer casts of the block that may truncate amounts that are untrusted input.
+    /// Statements are not visited when checking for errors, so this uses the amounts from the
+    /// last visit.
+    #[logfn_inputs(TRACE)]
+    fn check_narrowing_casts(&mut self, bb: mir::BasicBlock) {
+        let mut casts: Vec<_> = self
+            .bv
+            .narrowing_casts
+            .iter()
+            .filter(|(location, _)| location.block == bb)
+            .map(|(location, (source, target, amount))| {
+                (*location, *source, *target, amount.clone())
+            })
+            .collect();
+        casts.sort_by_key(|(location, ..)| location.statement_index);
+        for (location, source, target, amount) in casts {
+            self.bv.current_location = location;
+            self.bv.current_span = self.bv.mir.source_info(location).span;
+            if let Some(guaranteed) = self.check_narrowing(&amount, source, target) {
+                let span = self.bv.current_span;
+                self.bv
+                    .contract_checkers
+                    .truncation
+                    .record(span, source, target, guaranteed, None);
+            }
+        }
+    }
+
+    /// Records a conversion with try_into or try_from of an amount that is untrusted input to an
+    /// integer type whose range does not include every value of the type of the amount, so that
+    /// an unwrap of the result can be checked like a cast.
+    fn record_integer_conversion(
+        &mut self,
+        args: &[Spanned<mir::Operand<'tcx>>],
+        destination: mir::Place<'tcx>,
+    ) {
+        let Some(amount) = args.first() else {
+            return;
+        };
+        if !destination.projection.is_empty() {
+            return;
+        }
+        // The result is a Result whose first type argument is the target type
+        let result_type = self
+            .type_visitor()
+            .get_rustc_place_type(&destination, self.bv.current_span);
+        let TyKind::Adt(_, generic_args) = result_type.kind() else {
+            return;
+        };
+        let Some(target_type) = generic_args.types().next() else {
+            return;
+        };
+        let source = ExpressionType::from(self.get_operand_rustc_type(&amount.node).kind());
+        let target = ExpressionType::from(target_type.kind());
+        let amount = self.visit_operand(&amount.node);
+        if narrowing_condition(&amount, source, target).is_none()
+            || !self.carries_untrusted_input(&amount)
+        {
+            return;
+        }
+        self.bv.contract_checkers.truncation.conversions.insert(
+            destination.local,
+            (self.bv.current_span, source, target, amount),
+        );
+    }
+
+    /// Checks the conversion whose result the receiver of the current call of unwrap or expect
+    /// holds, if any, the way a cast of the amount is checked.
+    fn check_unwrapped_conversion(&mut self, args: &[Spanned<mir::Operand<'tcx>>]) {
+        let Some(result) = args.first().and_then(|arg| arg.node.place()) else {
+            return;
+        };
+        if !result.projection.is_empty() {
+            return;
+        }
+        let truncation = &self.bv.contract_checkers.truncation;
+        let Some((span, source, target, amount)) =
+            truncation.conversions.get(&result.local).cloned()
+        else {
+            return;
+        };
+        if let Some(guaranteed) = self.check_narrowing(&amount, source, target) {
+            let unwrap_span = self.bv.current_span;
+            self.bv.contract_checkers.truncation.record(
+                span,
+                source,
+                target,
+                guaranteed,
+                Some(unwrap_span),
+            );
+        }
+    }
+
+    /// Returns None if the given amount is known to be within the range of the target type when
+    /// the current location is reached, which is decided with the bounds of the amount and then
+    /// with the solver. Otherwise returns true if it is known to be out of range.
+    fn check_narrowing(
+        &mut self,
+        amount: &Rc<AbstractValue>,
+        source: ExpressionType,
+        target: ExpressionType,
+    ) -> Option<bool> {
+        let in_range = narrowing_condition(amount, source, target)?;
+        let (target_min, target_max) = integer_bounds(target);
+        if let Some((lower, upper)) = self.bv.bound_interval_of(amount) {
+            // A negative upper bound is within the range of the target type if the lower one is
+            if lower >= target_min && u128::try_from(upper).unwrap_or(0) <= target_max {
+                return None;
+            }
+        }
+        let (in_range_as_bool, entry_cond_as_bool) =
+            self.bv.check_condition_value_and_reachability(&in_range);
+        if entry_cond_as_bool == Some(false) || in_range_as_bool == Some(true) {
+            return None;
+        }
+        Some(in_range_as_bool == Some(false))
+    }
+
     /// Apply the given binary operator to the two operands, with overflow checking where appropriate
     /// and assign the result to path.
     #[logfn_inputs(TRACE)]
@@ -2525,6 +4830,7 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         left: Rc<AbstractValue>,
         right: Rc<AbstractValue>,
     ) -> (Rc<AbstractValue>, Rc<AbstractValue>) {
+        let bounds = left.bound_interval().zip(right.bound_interval());
         let (result, overflow_flag) = match bin_op {
             mir::BinOp::Add => (
                 left.addition(right.clone()),
@@ -2548,9 +4854,52 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
             ),
             _ => assume_unreachable!(),
         };
+        if let Some((left_bounds, right_bounds)) = bounds {
+            if Self::bounds_exclude_overflow(bin_op, target_type, left_bounds, right_bounds) {
+                return (result, Rc::new(abstract_value::FALSE));
+            }
+        }
         (result, overflow_flag)
     }
 
+    /// True if an operation with operands that lie within the given bounds cannot overflow the
+    /// target type, or, for shifts, cannot shift by more than the bit length of the type.
+    pub fn bounds_exclude_overflow(
+        bin_op: mir::BinOp,
+        target_type: ExpressionType,
+        (left_lower, left_upper): (i128, i128),
+        (right_lower, right_upper): (i128, i128),
+    ) -> bool {
+        let results = match bin_op {
+            mir::BinOp::Add => vec![
+                left_lower.checked_add(right_lower),
+                left_upper.checked_add(right_upper),
+            ],
+            mir::BinOp::Mul => vec![
+                left_lower.checked_mul(right_lower),
+                left_lower.checked_mul(right_upper),
+                left_upper.checked_mul(right_lower),
+                left_upper.checked_mul(right_upper),
+            ],
+            mir::BinOp::Sub => vec![
+                left_lower.checked_sub(right_upper),
+                left_upper.checked_sub(right_lower),
+            ],
+            mir::BinOp::Shl | mir::BinOp::Shr => {
+                return right_lower >= 0 && right_upper < i128::from(target_type.bit_length());
+            }
+            _ => return false,
+        };
+        let Some(results) = results.into_iter().collect::<Option<Vec<i128>>>() else {
+            return false;
+        };
+        let lower = results.iter().copied().min().unwrap_or(i128::MIN);
+        let upper = results.iter().copied().max().unwrap_or(i128::MAX);
+        IntervalDomain::from(lower)
+            .widen(&IntervalDomain::from(upper))
+            .is_contained_in(target_type)
+    }
+
     /// Create a value based on the given type and assign it to path.
     #[logfn_inputs(TRACE)]
     fn visit_nullary_op(
@@ -2616,7 +4965,23 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
     fn visit_unary_op(&mut self, path: Rc<Path>, un_op: mir::UnOp, operand: &mir::Operand<'tcx>) {
         let operand = self.visit_operand(operand);
         let result = match un_op {
-            mir::UnOp::Neg => operand.negate(),
+            mir::UnOp::Neg => {
+                let result_type = self
+                    .type_visitor()
+                    .get_target_path_type(&path, self.bv.current_span);
+                if self.wraps_around_on_overflow() && result_type.is_signed_integer() {
+                    // Negating the minimum value wraps around to the minimum value.
+                    let min_value: Rc<AbstractValue> = Rc::new(result_type.min_value().into());
+                    let overflow_flag = operand.equals(min_value);
+                    self.bv.wrapping_operations.insert(
+                        self.bv.current_location,
+                        (mir::AssertKind::OverflowNeg(()), overflow_flag.clone()),
+                    );
+                    overflow_flag.conditional_expression(operand.clone(), operand.negate())
+                } else {
+                    operand.negate()
+                }
+            }
             mir::UnOp::Not => {
                 let result_type = self
                     .type_visitor()
@@ -2834,6 +5199,58 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         }
     }
 
+    /// Returns the unit that the binary operation gives its result, if it converts an amount
+    /// between SOL and lamports or adds a named amount of SOL, and records the operations that
+    /// mix the two units or convert an amount in lamports again.
+    fn visit_sol_units(
+        &mut self,
+        bin_op: mir::BinOp,
+        left_operand: &mir::Operand<'tcx>,
+        right_operand: &mir::Operand<'tcx>,
+    ) -> Option<SolUnit> {
+        let left_unit = self.sol_unit_of_operand(left_operand);
+        let right_unit = self.sol_unit_of_operand(right_operand);
+        if left_unit.is_none() && right_unit.is_none() {
+            return None;
+        }
+        let span = self.bv.current_span;
+        self.bv
+            .contract_checkers
+            .sol_units
+            .visit_binary_op(bin_op, left_unit, right_unit, span)
+    }
+
+    /// Returns the unit of an amount of SOL that the operand holds, which is known from the
+    /// value and the name of a constant, or from the built-in tags of the value of a place.
+    fn sol_unit_of_operand(&mut self, operand: &mir::Operand<'tcx>) -> Option<SolUnit> {
+        match operand {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => {
+                let value = self.visit_copy(place);
+                let environment = &self.bv.current_environment;
+                let has_tag = |tag: Tag| {
+                    value.has_tag(&tag).as_bool_if_known().unwrap_or(false)
+                        || value.expression.has_tagged_subcomponent(&tag, environment)
+                };
+                match (has_tag(Tag::LAMPORTS), has_tag(Tag::SOL)) {
+                    (true, false) => Some(SolUnit::Lamports),
+                    (false, true) => Some(SolUnit::Sol),
+                    _ => None,
+                }
+            }
+            mir::Operand::Constant(constant) => {
+                let value = match &self.visit_literal(&constant.const_).expression {
+                    Expression::CompileTimeConstant(ConstantDomain::U128(value)) => Some(*value),
+                    _ => None,
+                };
+                let source_map = self.bv.tcx.sess.source_map();
+                let text = source_map
+                    .span_to_snippet(constant.span)
+                    .unwrap_or_default();
+                SolUnit::of_constant(value, &text)
+            }
+        }
+    }
+
     /// Operand defines the values that can appear inside an rvalue. They are intentionally
     /// limited to prevent rvalues from being nested in one another.
     #[logfn_inputs(TRACE)]
@@ -4371,3 +6788,174 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         }
     }
 }
+
+/// Returns the code of the finding if the message of a precondition describes an arithmetic
+/// operation that may overflow, as checked by check_assertion.
+pub fn arithmetic_code(message: &str) -> Option<&'static str> {
+    if message.ends_with(", which aborts execution") {
+        Some("HEPHA-ARITH-ABORT")
+    } else if message.ends_with(", which wraps around") {
+        Some("HEPHA-ARITH-WRAP")
+    } else {
+        None
+    }
+}
+
+// Adds the locals that the operands and the places of the given rvalue read, including those
+// that index the places, to the given set.
+fn add_read_locals(rvalue: &mir::Rvalue<'_>, locals: &mut HashSet<mir::Local>) {
+    let mut add_place = |place: &mir::Place<'_>| {
+        locals.insert(place.local);
+        locals.extend(place.projection.iter().filter_map(|elem| match elem {
+            mir::ProjectionElem::Index(local) => Some(local),
+            _ => None,
+        }));
+    };
+    let operands: Vec<&mir::Operand<'_>> = match rvalue {
+        mir::Rvalue::Use(operand)
+        | mir::Rvalue::Repeat(operand, _)
+        | mir::Rvalue::Cast(_, operand, _)
+        | mir::Rvalue::UnaryOp(_, operand)
+        | mir::Rvalue::ShallowInitBox(operand, _) => vec![operand],
+        mir::Rvalue::BinaryOp(_, box (left, right)) => vec![left, right],
+        mir::Rvalue::Aggregate(_, operands) => operands.iter().collect(),
+        mir::Rvalue::Ref(_, _, place)
+        | mir::Rvalue::RawPtr(_, place)
+        | mir::Rvalue::Len(place)
+        | mir::Rvalue::Discriminant(place)
+        | mir::Rvalue::CopyForDeref(place) => {
+            add_place(place);
+            vec![]
+        }
+        _ => vec![],
+    };
+    for operand in operands {
+        if let Some(place) = operand.place() {
+            add_place(&place);
+        }
+    }
+}
+
+// Returns the condition under which the given amount of the source type is within the range of
+// the target type, if the range does not include every value of the source type.
+fn narrowing_condition(
+    amount: &Rc<AbstractValue>,
+    source: ExpressionType,
+    target: ExpressionType,
+) -> Option<Rc<AbstractValue>> {
+    if !source.is_integer() || !target.is_integer() {
+        return None;
+    }
+    let (source_min, source_max) = integer_bounds(source);
+    let (target_min, target_max) = integer_bounds(target);
+    let lower =
+        (source_min < target_min).then(|| amount.greater_or_equal(Rc::new(target_min.into())));
+    let upper = (source_max > target_max).then(|| {
+        // The bound is a constant of the signedness of the amount, which it is compared with
+        let target_max: Rc<AbstractValue> = match i128::try_from(target_max) {
+            Ok(target_max) if source.is_signed_integer() => Rc::new(target_max.into()),
+            _ => Rc::new(target_max.into()),
+        };
+        amount.less_or_equal(target_max)
+    });
+    match (lower, upper) {
+        (Some(lower), Some(upper)) => Some(lower.and(upper)),
+        (lower, upper) => lower.or(upper),
+    }
+}
+
+// Returns the minimum and the maximum values of the given integer type.
+fn integer_bounds(ty: ExpressionType) -> (i128, u128) {
+    let min = match ty.min_value() {
+        ConstantDomain::I128(min) => min,
+        _ => 0,
+    };
+    let max = match ty.max_value() {
+        ConstantDomain::I128(max) => max as u128,
+        ConstantDomain::U128(max) => max,
+        _ => 0,
+    };
+    (min, max)
+}
+
+// Describes the failure of an assertion, or of an operation that wraps around instead.
+fn get_assert_msg_description<O>(msg: &mir::AssertKind<O>) -> &'static str {
+    use mir::AssertKind::*;
+    use mir::BinOp;
+    use rustc_hir::CoroutineDesugaring;
+    use rustc_hir::CoroutineKind;
+    match msg {
+        BoundsCheck { .. } => "index out of bounds",
+        MisalignedPointerDereference { .. } => "misaligned pointer dereference",
+        Overflow(BinOp::Add, _, _) => "attempt to add with overflow",
+        Overflow(BinOp::Sub, _, _) => "attempt to subtract with underflow",
+        Overflow(BinOp::Mul, _, _) => "attempt to multiply with overflow",
+        Overflow(BinOp::Div, _, _) => "attempt to divide with overflow",
+        Overflow(BinOp::Rem, _, _) => "attempt to calculate the remainder with overflow",
+        OverflowNeg(_) => "attempt to negate with overflow",
+        Overflow(BinOp::Shr, _, _) => "attempt to shift right with overflow",
+        Overflow(BinOp::Shl, _, _) => "attempt to shift left with overflow",
+        Overflow(..) => "bug, op cannot overflow",
+        DivisionByZero(_) => "attempt to divide by zero",
+        RemainderByZero(_) => "attempt to calculate the remainder with a divisor of zero",
+        ResumedAfterReturn(CoroutineKind::Coroutine(_)) => "coroutine resumed after completion",
+        ResumedAfterReturn(CoroutineKind::Desugared(CoroutineDesugaring::Async, _)) => {
+            "`async fn` resumed after completion"
+        }
+        ResumedAfterReturn(CoroutineKind::Desugared(CoroutineDesugaring::AsyncGen, _)) => {
+            "`async gen fn` resumed after completion"
+        }
+        ResumedAfterReturn(CoroutineKind::Desugared(CoroutineDesugaring::Gen, _)) => {
+            "`gen fn` should just keep returning `None` after completion"
+        }
+        ResumedAfterPanic(CoroutineKind::Coroutine(_)) => "coroutine resumed after panicking",
+        ResumedAfterPanic(CoroutineKind::Desugared(CoroutineDesugaring::Async, _)) => {
+            "`async fn` resumed after panicking"
+        }
+        ResumedAfterPanic(CoroutineKind::Desugared(CoroutineDesugaring::AsyncGen, _)) => {
+            "`async gen fn` resumed after panicking"
+        }
+        ResumedAfterPanic(CoroutineKind::Desugared(CoroutineDesugaring::Gen, _)) => {
+            "`gen fn` should just keep returning `None` after panicking"
+        }
+    }
+}
+
+// True if a conjunct of the condition is an equality, or the negation of an inequality, of a
+// value that uses the left paths with a value that uses the right paths.
+fn requires_equal(
+    condition: &Rc<AbstractValue>,
+    left_paths: &HashSet<Rc<Path>>,
+    right_paths: &HashSet<Rc<Path>>,
+) -> bool {
+    match &condition.expression {
+        Expression::And { left, right } => {
+            requires_equal(left, left_paths, right_paths)
+                || requires_equal(right, left_paths, right_paths)
+        }
+        Expression::Equals { left, right } => compares(left, right, left_paths, right_paths),
+        Expression::LogicalNot { operand } => match &operand.expression {
+            Expression::Ne { left, right } => compares(left, right, left_paths, right_paths),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+// True if one of the operands uses the left paths and the other the right paths, directly or as
+// the operands of a memcmp, which is how the bytes of two keys are compared.
+fn compares(
+    left: &Rc<AbstractValue>,
+    right: &Rc<AbstractValue>,
+    left_paths: &HashSet<Rc<Path>>,
+    right_paths: &HashSet<Rc<Path>>,
+) -> bool {
+    if let Expression::Memcmp { left, right, .. } = &left.expression {
+        return compares(left, right, left_paths, right_paths);
+    }
+    if let Expression::Memcmp { left, right, .. } = &right.expression {
+        return compares(left, right, left_paths, right_paths);
+    }
+    (left.uses(left_paths) && right.uses(right_paths))
+        || (left.uses(right_paths) && right.uses(left_paths))
+}
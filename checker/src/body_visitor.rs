@@ -4,16 +4,16 @@
 // LICENSE file in the root directory of this source tree.
 
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::{Debug, Formatter, Result};
 use std::rc::Rc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use log_derive::*;
 use rpds::HashTrieMap;
 
 use hepha_annotations::*;
-use rustc_errors::Diag;
+use rustc_errors::{Diag, Level, Suggestions};
 use rustc_hir::def_id::DefId;
 use rustc_middle::mir;
 use rustc_middle::ty::{AdtDef, Const, GenericArgsRef, Ty, TyCtxt, TyKind, UintTy};
@@ -21,30 +21,31 @@ use rustc_middle::ty::{AdtDef, Const, GenericArgsRef, Ty, TyCtxt, TyKind, UintTy
 use crate::abstract_value::{self, AbstractValue, AbstractValueTrait, BOTTOM};
 use crate::block_visitor::BlockVisitor;
 use crate::call_visitor::CallVisitor;
+use crate::checker_registry::{self, ContractCheckers, DiagnosticOrigin, Severity};
 use crate::constant_domain::ConstantDomain;
-use crate::contract_errors::{BadrandomnessChecker, NumericalPrecisionErrorChecker, ReentrancyChecker, TimeManipulationChecker};
+use crate::contract_errors;
 use crate::crate_visitor::CrateVisitor;
+use crate::diagnostic_export::{FindingProperties, FINDING_PROPERTIES_ARG};
 use crate::environment::Environment;
 use crate::expression::{Expression, ExpressionType, LayoutSource};
-use crate::fixed_point_visitor::FixedPointVisitor;
-use crate::options::DiagLevel;
+use crate::fixed_point_visitor::{self, FixedPointVisitor};
+use crate::k_limits;
+use crate::options::{DiagLevel, Options};
 use crate::path::{Path, PathEnum, PathSelector};
 use crate::path::{PathRefinement, PathRoot};
-#[cfg(not(feature = "z3"))]
-use crate::smt_solver::SolverStub;
-use crate::smt_solver::{SmtResult, SmtSolver};
+use crate::smt_solver::{BackendSolver, SmtResult, SmtSolver};
+use crate::solana_compat::{self, AnchorAccounts};
 use crate::summaries;
 use crate::summaries::{Precondition, Summary};
 use crate::tag_domain::Tag;
+use crate::trace_scope::TraceScope;
 use crate::type_visitor::{self, TypeCache, TypeVisitor};
-#[cfg(feature = "z3")]
-use crate::z3_solver::Z3Solver;
-use crate::{k_limits, utils};
+use crate::utils;
 
 #[derive(Debug, Clone)]
 pub enum BlockStatement<'tcx> {
     Statement(mir::Statement<'tcx>),
-    TerminatorKind(mir::TerminatorKind<'tcx>)
+    TerminatorKind(mir::TerminatorKind<'tcx>),
 }
 
 /// Holds the state for the function body visitor.
@@ -53,7 +54,7 @@ pub struct BodyVisitor<'analysis, 'compilation, 'tcx> {
     pub tcx: TyCtxt<'tcx>,
     pub def_id: DefId,
     pub mir: &'tcx mir::Body<'tcx>,
-    pub buffered_diagnostics: &'analysis mut Vec<Diag<'compilation, ()>>,
+    pub buffered_diagnostics: &'analysis mut Vec<(Diag<'compilation, ()>, DiagnosticOrigin)>,
     pub active_calls_map: &'analysis mut HashMap<DefId, u64>,
 
     pub already_reported_errors_for_call_to: HashSet<Rc<AbstractValue>>,
@@ -64,31 +65,60 @@ pub struct BodyVisitor<'analysis, 'compilation, 'tcx> {
     pub async_fn_summary: Option<Summary>,
     pub check_for_errors: bool,
     pub check_for_unconditional_precondition: bool,
+    // True if the states at the calls of this function are recorded for --record-call-states
+    pub records_call_states: bool,
     pub current_environment: Environment,
     pub current_location: mir::Location,
     pub current_span: rustc_span::Span,
     pub start_instant: Instant,
     // The current environments when the return statement was executed
     pub exit_environment: Option<Environment>,
+    // The exit environment of each kind of result that the function returns, such as the Ok and
+    // the Err variant of a Result, with the condition that the result is of that kind
+    pub exit_environments: Vec<(Rc<AbstractValue>, Environment)>,
     pub first_environment: Environment,
     pub function_name: Rc<str>,
     pub heap_addresses: HashMap<mir::Location, Rc<AbstractValue>>,
     pub post_condition: Option<Rc<AbstractValue>>,
     pub post_condition_block: Option<mir::BasicBlock>,
     pub preconditions: Vec<Precondition>,
+    // The names of the checkers whose findings are dropped for this function, given with suppress!
+    // or by the suppressions of the configuration file for the file of the function
+    pub suppressed_checkers: HashSet<&'static str>,
     pub fresh_variable_offset: usize,
-    #[cfg(not(feature = "z3"))]
-    pub smt_solver: SolverStub,
-    #[cfg(feature = "z3")]
-    pub smt_solver: Z3Solver,
-    pub block_to_call: HashMap<mir::Location, DefId>,
+    pub smt_solver: BackendSolver,
+    // Ordered by location, so that the dominance relations between calls are added to the call
+    // graph in the same order in every run
+    pub block_to_call: BTreeMap<mir::Location, DefId>,
+    // The time spent visiting each basic block, summed over the passes of the fixed point and the
+    // pass that checks for errors
+    pub block_times: HashMap<mir::BasicBlock, Duration>,
+    // The spans and blocks of the branches that introduced the conditions of the edges to their targets
+    pub branch_condition_spans:
+        HashMap<(mir::BasicBlock, Rc<AbstractValue>), (rustc_span::Span, mir::BasicBlock)>,
+    // The conditions of the assume! calls that the pass that checks for errors has visited, with
+    // the blocks and spans of the calls
+    pub assumptions: Vec<(mir::BasicBlock, rustc_span::Span, Rc<AbstractValue>)>,
+    // The names of the called functions without a MIR body or a summary, whose results and side
+    // effects are unknown values
+    pub havocked_callees: BTreeSet<String>,
+    // The conditions of the edges to each block, as seen by the pass that checks for errors
+    pub branch_conditions: HashMap<mir::BasicBlock, Vec<Rc<AbstractValue>>>,
+    // The blocks that end with loop_invariant! calls, with the anchors of their loops and the
+    // iterations of the fixed points of the loops that are being computed
+    pub loop_invariant_iterations: HashMap<mir::BasicBlock, (mir::BasicBlock, usize)>,
+    // The blocks that end with loop_invariant! calls whose invariants are possibly not preserved
+    pub unpreserved_loop_invariants: HashSet<mir::BasicBlock>,
     pub treat_as_foreign: bool,
+    // The overflow flags of the arithmetic operations that wrap around when overflow checks are off
+    pub wrapping_operations: HashMap<mir::Location, (mir::AssertKind<()>, Rc<AbstractValue>)>,
+    // The integer casts that may not preserve their operands, with the types of the operands and
+    // of the results, and the conditions under which the operands are within the result types
+    pub narrowing_casts:
+        HashMap<mir::Location, (ExpressionType, ExpressionType, Rc<AbstractValue>)>,
     type_visitor: TypeVisitor<'tcx>,
     // Vulnerability detection for smart contracts
-    pub reentrancy_checker: ReentrancyChecker<'tcx>,
-    pub time_manipulation_checker: TimeManipulationChecker,
-    pub bad_randomness_checker: BadrandomnessChecker,
-    pub numerical_precision_checker: NumericalPrecisionErrorChecker
+    pub contract_checkers: ContractCheckers<'tcx>,
 }
 
 impl Debug for BodyVisitor<'_, '_, '_> {
@@ -98,20 +128,14 @@ impl Debug for BodyVisitor<'_, '_, '_> {
 }
 
 impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
-    #[cfg(feature = "z3")]
-    fn get_solver() -> Z3Solver {
-        Z3Solver::new()
-    }
-
-    #[cfg(not(feature = "z3"))]
-    fn get_solver() -> SolverStub {
-        SolverStub::default()
+    fn get_solver(options: &Options) -> BackendSolver {
+        BackendSolver::new(options.smt_backend, options.smt_timeout_ms)
     }
 
     pub fn new(
         crate_visitor: &'analysis mut CrateVisitor<'compilation, 'tcx>,
         def_id: DefId,
-        buffered_diagnostics: &'analysis mut Vec<Diag<'compilation, ()>>,
+        buffered_diagnostics: &'analysis mut Vec<(Diag<'compilation, ()>, DiagnosticOrigin)>,
         active_calls_map: &'analysis mut HashMap<DefId, u64>,
         type_cache: Rc<RefCell<TypeCache<'tcx>>>,
     ) -> BodyVisitor<'analysis, 'compilation, 'tcx> {
@@ -127,6 +151,73 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
             tcx.instance_mir(instance)
         };
         crate_visitor.call_graph.add_root(def_id);
+        let records_call_states = crate_visitor
+            .options
+            .record_call_states
+            .as_deref()
+            .is_some_and(|function| tcx.def_path_str(def_id).contains(function));
+        let mut contract_checkers = ContractCheckers::new();
+        contract_checkers.account_window.in_entrypoint = contract_errors::is_entrypoint(tcx, mir);
+        contract_checkers.owner_check.in_entrypoint =
+            contract_checkers.account_window.in_entrypoint;
+        contract_checkers.writable_check.in_entrypoint =
+            contract_checkers.account_window.in_entrypoint;
+        contract_checkers.distinct_accounts.in_entrypoint =
+            contract_checkers.account_window.in_entrypoint;
+        contract_checkers.non_persistent_state.in_entrypoint =
+            contract_checkers.account_window.in_entrypoint;
+        contract_checkers.forbidden_call.in_entrypoint =
+            contract_checkers.account_window.in_entrypoint;
+        // The handlers of an Anchor program are entrypoints whose accounts come from a Context
+        // rather than from a slice of AccountInfo values
+        if crate_visitor.anchor_handlers.contains(&def_id) {
+            contract_checkers.owner_check.in_entrypoint = true;
+            contract_checkers.non_persistent_state.in_entrypoint = true;
+            contract_checkers.forbidden_call.in_entrypoint = true;
+            contract_checkers.missing_signer = contract_errors::MissingSignerChecker::new(tcx, mir);
+            contract_checkers.anchor_accounts = AnchorAccounts::new(tcx, mir);
+            contract_checkers.account_constraint =
+                contract_errors::AccountConstraintChecker::new(tcx, mir);
+            contract_checkers.account_close = contract_errors::AccountCloseChecker::new(tcx, mir);
+        }
+        contract_checkers.amount_provenance = contract_errors::AmountProvenance::new(tcx, mir);
+        contract_checkers.arithmetic_overflow = contract_errors::ArithmeticOverflowChecker::new(
+            tcx,
+            mir,
+            &contract_checkers.amount_provenance,
+        );
+        contract_checkers.token_conservation =
+            contract_errors::TokenConservationChecker::new(tcx, mir);
+        contract_checkers.ledger = contract_errors::LedgerChecker::new(
+            tcx,
+            mir,
+            crate_visitor.options.ledger_account.as_deref(),
+        );
+        contract_checkers.replay_protection = contract_errors::ReplayProtectionChecker::new(
+            tcx,
+            mir,
+            &contract_checkers.amount_provenance,
+        );
+        contract_checkers.signer_seeds = contract_errors::SignerSeedsChecker::new(
+            tcx,
+            mir,
+            &contract_checkers.amount_provenance,
+        );
+        let file_name = tcx
+            .sess
+            .source_map()
+            .span_to_filename(tcx.def_span(def_id))
+            .prefer_local()
+            .to_string();
+        let suppressed_checkers = crate_visitor.options.checkers_suppressed_in(&file_name);
+        let smt_solver = Self::get_solver(&crate_visitor.options);
+        let type_visitor = TypeVisitor::new(def_id, mir, tcx, type_cache);
+        contract_checkers.type_cosplay = contract_errors::TypeCosplayChecker::new(
+            tcx,
+            def_id,
+            &crate_visitor.account_deserializations,
+            &type_visitor,
+        );
         BodyVisitor {
             cv: crate_visitor,
             tcx,
@@ -142,26 +233,35 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
             async_fn_summary: None,
             check_for_errors: false,
             check_for_unconditional_precondition: false, // logging + new mir code gen breaks this for now
+            records_call_states,
             current_environment: Environment::default(),
             current_location: mir::Location::START,
             current_span: rustc_span::DUMMY_SP,
             start_instant: Instant::now(),
             exit_environment: None,
+            exit_environments: Vec::new(),
             first_environment: Environment::default(),
             function_name,
             heap_addresses: HashMap::default(),
             post_condition: None,
             post_condition_block: None,
             preconditions: Vec::new(),
+            suppressed_checkers,
             fresh_variable_offset: 0,
-            smt_solver: Self::get_solver(),
-            block_to_call: HashMap::default(),
+            smt_solver,
+            block_to_call: BTreeMap::default(),
+            block_times: HashMap::default(),
+            branch_condition_spans: HashMap::default(),
+            assumptions: Vec::new(),
+            havocked_callees: BTreeSet::new(),
+            branch_conditions: HashMap::default(),
+            loop_invariant_iterations: HashMap::default(),
+            unpreserved_loop_invariants: HashSet::default(),
             treat_as_foreign: false,
-            type_visitor: TypeVisitor::new(def_id, mir, tcx, type_cache),
-            reentrancy_checker: ReentrancyChecker::new(),
-            time_manipulation_checker: TimeManipulationChecker::new(),
-            bad_randomness_checker: BadrandomnessChecker::new(),
-            numerical_precision_checker: NumericalPrecisionErrorChecker::new()
+            wrapping_operations: HashMap::default(),
+            narrowing_casts: HashMap::default(),
+            type_visitor,
+            contract_checkers,
         }
     }
 
@@ -178,12 +278,21 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
         self.current_span = rustc_span::DUMMY_SP;
         self.start_instant = Instant::now();
         self.exit_environment = None;
+        self.exit_environments = Vec::new();
         self.heap_addresses = HashMap::default();
         self.post_condition = None;
         self.post_condition_block = None;
         self.preconditions = Vec::new();
         self.fresh_variable_offset = 1000;
-        self.block_to_call = HashMap::default();
+        self.block_to_call = BTreeMap::default();
+        self.block_times = HashMap::default();
+        self.branch_condition_spans = HashMap::default();
+        self.assumptions = Vec::new();
+        self.branch_conditions = HashMap::default();
+        self.loop_invariant_iterations = HashMap::default();
+        self.unpreserved_loop_invariants = HashSet::default();
+        self.wrapping_operations = HashMap::default();
+        self.narrowing_casts = HashMap::default();
         self.type_visitor_mut().reset_visitor_state();
     }
 
@@ -204,6 +313,12 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
     ) -> Summary {
         let diag_level = self.cv.options.diag_level;
         let max_analysis_time_for_body = self.cv.options.max_analysis_time_for_body;
+        let (tcx, def_id) = (self.tcx, self.def_id);
+        let _trace_scope = TraceScope::enter(
+            self.cv.options.trace_function.as_deref(),
+            self.cv.options.trace_depth,
+            || tcx.def_path_str(def_id),
+        );
         if option_env!("PRETTY_PRINT_MIR").is_some() {
             utils::pretty_print_mir(self.tcx, self.def_id);
         }
@@ -226,16 +341,76 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
         }
         first_state.exit_conditions = HashTrieMap::default();
 
+        // The ledger and the lamports of the account it is declared to add up to are unchanged
+        // at entry
+        for field in [
+            contract_errors::LedgerChecker::LEDGER_DELTA,
+            contract_errors::LedgerChecker::LAMPORTS_DELTA,
+        ] {
+            if let Some(path) = self.ledger_delta_path(field) {
+                let zero = self.get_i128_const_val(0);
+                first_state.value_map.insert_mut(path, zero);
+            }
+        }
+
+        // No balance has been updated at entry
+        for (delta_field, updates_field) in contract_errors::BalanceConservationChecker::KINDS {
+            for field in [delta_field, updates_field] {
+                let zero = self.get_i128_const_val(0);
+                first_state
+                    .value_map
+                    .insert_mut(self.balance_total_path(field), zero);
+            }
+        }
+
+        // The instruction data of an entrypoint of a Solana program is untrusted input
+        if self.cv.is_solana_program && self.contract_checkers.account_window.in_entrypoint {
+            let instruction_data_type = self.mir.local_decls[mir::Local::from_usize(3)].ty;
+            let saved_environment = std::mem::replace(&mut self.current_environment, first_state);
+            self.attach_tag_to_value_at_path(
+                Tag::UNTRUSTED_INPUT,
+                Path::new_parameter(3),
+                instruction_data_type,
+            );
+            first_state = std::mem::replace(&mut self.current_environment, saved_environment);
+        }
+
+        // The accounts of an Anchor instruction are the fields of the Accounts struct that its
+        // Context refers to, and the other parameters of its handler are the arguments of the
+        // instruction, which are deserialized from the instruction data
+        let mut instruction_arguments = Vec::new();
+        for arg in self.mir.args_iter() {
+            let path = Path::new_parameter(arg.as_usize());
+            let ty = self.mir.local_decls[arg].ty;
+            if solana_compat::anchor_context(self.tcx, ty).is_some() {
+                self.type_visitor_mut().add_any_anchor_account_fields_for(
+                    ty,
+                    &path,
+                    &mut first_state,
+                );
+            } else {
+                instruction_arguments.push((path, ty));
+            }
+        }
+        if self.cv.is_solana_program && self.contract_checkers.missing_signer.in_anchor_handler {
+            let saved_environment = std::mem::replace(&mut self.current_environment, first_state);
+            for (path, ty) in instruction_arguments {
+                self.attach_tag_to_value_at_path(Tag::UNTRUSTED_INPUT, path, ty);
+            }
+            first_state = std::mem::replace(&mut self.current_environment, saved_environment);
+        }
+
         // Update the current environment
         self.first_environment = first_state;
         let mut fixed_point_visitor = FixedPointVisitor::new(self);
         fixed_point_visitor.visit_blocks();
+        if fixed_point_visitor.bv.cv.options.reports_path_constraints() {
+            fixed_point_visitor.report_path_constraints();
+        }
 
-        let elapsed_time_in_seconds = fixed_point_visitor.bv.start_instant.elapsed().as_secs();
-        if elapsed_time_in_seconds >= max_analysis_time_for_body {
-            fixed_point_visitor
-                .bv
-                .report_timeout(elapsed_time_in_seconds);
+        let elapsed_time = fixed_point_visitor.bv.start_instant.elapsed();
+        if elapsed_time >= max_analysis_time_for_body {
+            fixed_point_visitor.bv.report_timeout(elapsed_time);
         }
         let mut result = Summary {
             is_computed: true,
@@ -243,8 +418,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
             ..Summary::default()
         };
         if !fixed_point_visitor.bv.analysis_is_incomplete
-            || (elapsed_time_in_seconds < max_analysis_time_for_body
-                && diag_level == DiagLevel::Paranoid)
+            || (elapsed_time < max_analysis_time_for_body && diag_level == DiagLevel::Paranoid)
         {
             // Now traverse the blocks again, doing checks and emitting diagnostics.
             // terminator_state[bb] is now complete for every basic block bb in the body.
@@ -252,15 +426,18 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                 &fixed_point_visitor.block_indices,
                 &mut fixed_point_visitor.terminator_state,
             );
+            if self.cv.options.adversarial_public_api && self.function_being_analyzed_is_root() {
+                self.report_caller_obligations();
+            }
             let entry = self.active_calls_map.entry(self.def_id).or_insert(0);
             if *entry <= 1 {
                 self.active_calls_map.remove(&self.def_id);
             } else {
                 *entry -= 1;
             }
-            let elapsed_time_in_seconds = self.start_instant.elapsed().as_secs();
-            if elapsed_time_in_seconds >= max_analysis_time_for_body {
-                self.report_timeout(elapsed_time_in_seconds);
+            let elapsed_time = self.start_instant.elapsed();
+            if elapsed_time >= max_analysis_time_for_body {
+                self.report_timeout(elapsed_time);
             } else {
                 // Now create a summary of the body that can be in-lined into call sites.
                 if self.async_fn_summary.is_some() {
@@ -276,6 +453,11 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                         }
                         self.exit_environment = Some(env);
                     }
+                    for (_, env) in self.exit_environments.iter_mut() {
+                        for (p, _, _) in function_constant_args {
+                            env.value_map.remove_mut(p);
+                        }
+                    }
                 }
 
                 let return_type = if matches!(self.cv.options.diag_level, DiagLevel::Paranoid) {
@@ -294,6 +476,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                 result = summaries::summarize(
                     self.mir.arg_count,
                     self.exit_environment.as_ref(),
+                    &self.exit_environments,
                     &self.preconditions,
                     &self.post_condition,
                     return_type_index,
@@ -301,10 +484,29 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                 );
             }
         }
+        result.external_effects = self.contract_checkers.reentrancy.external_effects(self.mir);
+        result.distinct_accounts = self
+            .contract_checkers
+            .distinct_accounts
+            .required_distinct(self.mir);
+        result.suppressed_checkers = self.suppressed_checkers.iter().copied().collect();
+        result.suppressed_checkers.sort();
         self.cv
             .constant_value_cache
             .swap_heap_counter(saved_heap_counter);
 
+        let statistics = self.smt_solver.statistics();
+        debug!(
+            "solver statistics of {}: {} queries, {} timed out, {} ms",
+            self.function_name,
+            statistics.queries,
+            statistics.timeouts,
+            statistics.time.as_millis()
+        );
+        if !self.cv.shipped_semantics {
+            self.cv.run_summary.count_solver_queries(&statistics);
+        }
+
         // Compute dominance information for calls
         let dominators = self.mir.basic_blocks.dominators();
         for (location1, callee_defid1) in self.block_to_call.iter() {
@@ -318,20 +520,39 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
         result
     }
 
-    fn report_timeout(&mut self, elapsed_time_in_seconds: u64) {
+    fn report_timeout(&mut self, elapsed_time: Duration) {
         // This body is beyond HEPHA for now
+        let hot_blocks = self.hot_blocks();
         if self.cv.options.diag_level != DiagLevel::Default {
-            let warning = self
+            let mut warning = self
                 .cv
                 .session
                 .dcx()
                 .struct_span_warn(self.current_span, "The analysis of this function timed out");
-            self.emit_diagnostic(warning);
+            for (bb, span, time) in hot_blocks.iter() {
+                warning.span_note(
+                    *span,
+                    format!("{} ms were spent in {bb:?}", time.as_millis()),
+                );
+            }
+            self.emit_diagnostic_of(DiagnosticOrigin::Timeout, warning);
+        }
+        if !self.cv.shipped_semantics {
+            self.cv.run_summary.functions_timed_out += 1;
         }
         warn!(
-            "analysis of {} timed out after {} seconds",
-            self.function_name, elapsed_time_in_seconds,
+            "analysis of {} timed out after {} ms",
+            self.function_name,
+            elapsed_time.as_millis(),
         );
+        for (bb, span, time) in hot_blocks {
+            let location = self.cv.session.source_map().span_to_diagnostic_string(span);
+            self.cv.timed_out_blocks.push(format!(
+                "{} {bb:?} {location} {} ms",
+                self.function_name,
+                time.as_millis()
+            ));
+        }
         let call_entry = self.active_calls_map.entry(self.def_id).or_insert(0);
         if *call_entry > 1 {
             *call_entry -= 1;
@@ -341,11 +562,67 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
         self.analysis_is_incomplete = true;
     }
 
+    /// Returns the three blocks on which the most time was spent, with the spans of their first
+    /// statements and the time spent on them, the slowest first.
+    fn hot_blocks(&self) -> Vec<(mir::BasicBlock, rustc_span::Span, Duration)> {
+        let mut blocks: Vec<(mir::BasicBlock, rustc_span::Span, Duration)> = self
+            .block_times
+            .iter()
+            .map(|(bb, time)| (*bb, self.block_span(*bb), *time))
+            .collect();
+        blocks.sort_by(|(bb1, _, time1), (bb2, _, time2)| time2.cmp(time1).then(bb1.cmp(bb2)));
+        blocks.truncate(3);
+        blocks
+    }
+
+    /// Returns the span of the first statement of the block, or of its terminator if it has no
+    /// statements.
+    pub fn block_span(&self, bb: mir::BasicBlock) -> rustc_span::Span {
+        self.mir.basic_blocks[bb]
+            .statements
+            .first()
+            .map(|s| s.source_info.span)
+            .unwrap_or_else(|| self.mir.basic_blocks[bb].terminator().source_info.span)
+    }
+
+    /// Reports the preconditions inferred for an entry point of the public API of a library
+    /// crate, since an adversary that calls it need not meet them. Each is reported at the
+    /// statement of the entry point that gives rise to it.
+    fn report_caller_obligations(&mut self) {
+        for precondition in self.preconditions.clone() {
+            let Some((span, related_spans)) = precondition.spans.split_first() else {
+                continue;
+            };
+            let message = format!("caller obligation: {}", precondition.message);
+            let mut warning = self.cv.session.dcx().struct_span_warn(*span, message);
+            for related_span in related_spans {
+                warning.span_note(*related_span, "related location");
+            }
+            self.emit_diagnostic(warning);
+        }
+    }
+
     /// Adds the given diagnostic builder to the buffer.
     /// Buffering diagnostics gives us the chance to sort them before printing them out,
     /// which is desirable for tools that compare the diagnostics from one run of HEPHA with another.
+    /// The diagnostic is counted as a finding of the checker whose code its message starts with,
+    /// if any.
     #[logfn_inputs(TRACE)]
     pub fn emit_diagnostic(&mut self, diagnostic_builder: Diag<'compilation, ()>) {
+        let origin = diagnostic_builder.messages[0]
+            .0
+            .as_str()
+            .map_or(DiagnosticOrigin::Verification, DiagnosticOrigin::of_message);
+        self.emit_diagnostic_of(origin, diagnostic_builder);
+    }
+
+    /// Adds the given diagnostic builder, which the given origin gave rise to, to the buffer.
+    #[logfn_inputs(TRACE)]
+    pub fn emit_diagnostic_of(
+        &mut self,
+        origin: DiagnosticOrigin,
+        mut diagnostic_builder: Diag<'compilation, ()>,
+    ) {
         if (self.treat_as_foreign || !self.def_id.is_local())
             && !matches!(self.cv.options.diag_level, DiagLevel::Paranoid)
         {
@@ -367,7 +644,106 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
             diagnostic_builder.cancel();
             return;
         }
-        self.buffered_diagnostics.push(diagnostic_builder);
+        if let Some(callable_at_runtime) = self.cv.compile_time_roots.get(&self.def_id) {
+            if let Some(message) = diagnostic_builder.messages[0].0.as_str() {
+                let message = format!("{message} (in compile-time evaluation)");
+                diagnostic_builder.primary_message(message);
+            }
+            // The constants that use the function were evaluated without a panic, so a finding
+            // is only a note unless the function can also run with other arguments at runtime
+            if !callable_at_runtime {
+                let message = diagnostic_builder.messages[0].0.clone();
+                let mut note = Diag::new(self.cv.session.dcx(), Level::Note, message);
+                note.span = diagnostic_builder.span.clone();
+                note.children = std::mem::take(&mut diagnostic_builder.children);
+                note.suggestions = std::mem::replace(
+                    &mut diagnostic_builder.suggestions,
+                    Suggestions::Enabled(Vec::new()),
+                );
+                note.sort_span = diagnostic_builder.sort_span;
+                note.args = std::mem::take(&mut diagnostic_builder.args);
+                diagnostic_builder.cancel();
+                diagnostic_builder = note;
+            }
+        }
+        self.buffered_diagnostics.push((diagnostic_builder, origin));
+    }
+
+    /// True if the function being analyzed is a const fn that only runs at compile time.
+    fn runs_only_at_compile_time(&self) -> bool {
+        self.cv.compile_time_roots.get(&self.def_id) == Some(&false)
+    }
+
+    /// Emits the diagnostic of a finding with the given severity and records the severity for
+    /// the exit status of the run, unless the diagnostic is suppressed, or its checker is
+    /// suppressed in this function with suppress!.
+    pub fn emit_finding(
+        &mut self,
+        mut diagnostic_builder: Diag<'compilation, ()>,
+        severity: Severity,
+        properties: FindingProperties,
+    ) {
+        let suppressed = diagnostic_builder.messages[0]
+            .0
+            .as_str()
+            .and_then(checker_registry::finding_code)
+            .and_then(checker_registry::checker_for_code)
+            .is_some_and(|checker| self.suppressed_checkers.contains(checker.name));
+        if suppressed {
+            diagnostic_builder.cancel();
+            return;
+        }
+        let severity = if self.runs_only_at_compile_time() {
+            Severity::Note
+        } else {
+            severity
+        };
+        diagnostic_builder.arg(FINDING_PROPERTIES_ARG, properties.to_json());
+        let buffered = self.buffered_diagnostics.len();
+        self.emit_diagnostic(diagnostic_builder);
+        if self.buffered_diagnostics.len() > buffered {
+            self.cv.most_severe_finding = self.cv.most_severe_finding.max(Some(severity));
+        }
+    }
+
+    /// Returns the facts about the analysis that a finding with the given provenance classes
+    /// depends on. If the finding is reported while a block is checked, the entry condition of
+    /// the block is given, and only the assume! calls that dominate the block are included.
+    pub fn finding_properties(
+        &self,
+        entry_condition: Option<&Rc<AbstractValue>>,
+        provenance: Vec<String>,
+    ) -> FindingProperties {
+        let source_map = self.tcx.sess.source_map();
+        let dominators = self.mir.basic_blocks.dominators();
+        let assumptions = self
+            .assumptions
+            .iter()
+            .filter(|(block, ..)| {
+                entry_condition.is_none()
+                    || dominators.dominates(*block, self.current_location.block)
+            })
+            .map(|(_, span, _)| {
+                let location = source_map.lookup_char_pos(span.source_callsite().lo());
+                let file = location.file.name.prefer_local().to_string();
+                (file, location.line, location.col.0 + 1)
+            })
+            .collect();
+        let entry_condition = entry_condition.map(|condition| {
+            let mut conjuncts = Vec::new();
+            fixed_point_visitor::collect_conjuncts(condition, &mut conjuncts);
+            conjuncts
+                .iter()
+                .map(|conjunct| fixed_point_visitor::render_condition(self.mir, conjunct))
+                .collect::<Vec<_>>()
+                .join(" && ")
+        });
+        FindingProperties::new(
+            entry_condition,
+            provenance,
+            assumptions,
+            self.havocked_callees.iter().cloned().collect(),
+        )
     }
 
     pub fn get_char_const_val(&mut self, val: u128) -> Rc<AbstractValue> {
@@ -380,6 +756,27 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
         )
     }
 
+    /// Returns the path of the given model field of the account that the ledger of the function
+    /// is declared to add up to, if the function has one. The path is rooted in the local that
+    /// holds the account even if it is a parameter, so that the field stays out of the summary.
+    pub fn ledger_delta_path(&mut self, field: &str) -> Option<Rc<Path>> {
+        let account = self.contract_checkers.ledger.account?;
+        let ty = self.type_visitor().get_loc_ty(account);
+        let type_index = self.type_visitor().get_index_for(ty);
+        let account_path = Path::new_local(account.as_usize(), type_index);
+        Some(Path::new_model_field(account_path, Rc::from(field)))
+    }
+
+    /// Returns the path of the given model field of the totals of the balances that the function
+    /// updates. The path is rooted in the local of the return value rather than in the result, so
+    /// that the field stays out of the summary.
+    pub fn balance_total_path(&mut self, field: &str) -> Rc<Path> {
+        let ty = self.type_visitor().get_loc_ty(mir::RETURN_PLACE);
+        let type_index = self.type_visitor().get_index_for(ty);
+        let return_path = Path::new_local(mir::RETURN_PLACE.as_usize(), type_index);
+        Path::new_model_field(return_path, Rc::from(field))
+    }
+
     pub fn get_i128_const_val(&mut self, val: i128) -> Rc<AbstractValue> {
         Rc::new(
             self.cv
@@ -460,7 +857,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
             }
         };
         let result = if refined_val.is_top() {
-            if path.path_length() < k_limits::MAX_PATH_LENGTH {
+            if path.path_length() < self.cv.options.k_limits.max_path_length {
                 let mut result = None;
                 if let PathEnum::QualifiedPath {
                     qualifier,
@@ -587,7 +984,8 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
         if self.analyzing_static_var {
             return None;
         }
-        for (path, value) in self.current_environment.value_map.iter() {
+        // The first matching path wins, so the paths are visited in a deterministic order
+        for (path, value) in self.current_environment.sorted_entries() {
             if let PathEnum::QualifiedPath {
                 qualifier,
                 selector,
@@ -825,12 +1223,19 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
         terminator_state: &mut HashMap<mir::BasicBlock, Environment>,
     ) {
         self.check_for_errors = true;
+        if self.cv.options.batch_solver_queries {
+            // All of the queries of the body are solved under assumptions in this context.
+            self.smt_solver.set_backtrack_position();
+        }
         for bb in block_indices.iter() {
             check_for_early_break!(self);
             let t_state = terminator_state[bb].clone();
             self.current_environment = t_state;
             self.visit_basic_block(*bb, terminator_state);
         }
+        if self.cv.options.batch_solver_queries {
+            self.smt_solver.backtrack();
+        }
         if self.exit_environment.is_none() {
             // Can only happen if there has been no return statement in the body,
             // which only happens if there is no way for the function to return to its
@@ -850,30 +1255,24 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
             return environment;
         }
         trace!("def_id {:?}", self.tcx.def_kind(self.def_id));
-        let saved_type_visitor = self.type_visitor.clone();
-        let saved_mir = self.mir;
-        let saved_def_id = self.def_id;
         for (ordinal, constant_mir) in self.tcx.promoted_mir(self.def_id).iter().enumerate() {
-            self.mir = constant_mir;
-            self.type_visitor_mut().mir = self.mir;
-            let result_rustc_type = self.mir.local_decls[mir::Local::from(0usize)].ty;
-            self.visit_promoted_constants_block();
-            if let Some(exit_environment) = &self.exit_environment {
-                self.current_environment = exit_environment.clone();
+            let mut scope = PromotedConstantScope::enter(self, constant_mir);
+            let result_rustc_type = constant_mir.local_decls[mir::Local::from(0usize)].ty;
+            scope.visit_promoted_constants_block();
+            if let Some(exit_environment) = scope.exit_environment.clone() {
+                scope.current_environment = exit_environment.clone();
                 let mut result_root: Rc<Path> = Path::new_result();
                 let mut promoted_root: Rc<Path> =
                     Rc::new(PathEnum::PromotedConstant { ordinal }.into());
-                self.type_visitor_mut()
+                scope
+                    .type_visitor_mut()
                     .set_path_rustc_type(promoted_root.clone(), result_rustc_type);
-                if self
+                if scope
                     .type_visitor()
                     .is_slice_pointer(result_rustc_type.kind())
                 {
                     let source_length_path = Path::new_length(result_root.clone());
-                    let length_val = self
-                        .exit_environment
-                        .as_ref()
-                        .unwrap()
+                    let length_val = exit_environment
                         .value_map
                         .get(&source_length_path)
                         .expect("collection to have a length");
@@ -883,14 +1282,11 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                     result_root = Path::new_field(result_root, 0);
                 }
                 let value =
-                    self.lookup_path_and_refine_result(result_root.clone(), result_rustc_type);
+                    scope.lookup_path_and_refine_result(result_root.clone(), result_rustc_type);
                 match &value.expression {
                     Expression::HeapBlock { .. } => {
                         let heap_root: Rc<Path> = Path::new_heap_block(value.clone());
-                        for (path, value) in self
-                            .exit_environment
-                            .as_ref()
-                            .unwrap()
+                        for (path, value) in exit_environment
                             .value_map
                             .iter()
                             .filter(|(p, _)| p.is_rooted_by(&heap_root))
@@ -900,7 +1296,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                         environment.strong_update_value_at(promoted_root.clone(), value.clone());
                     }
                     Expression::Reference(local_path) => {
-                        self.promote_reference(
+                        scope.promote_reference(
                             &mut environment,
                             result_rustc_type,
                             &promoted_root,
@@ -908,10 +1304,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                         );
                     }
                     _ => {
-                        for (path, value) in self
-                            .exit_environment
-                            .as_ref()
-                            .unwrap()
+                        for (path, value) in exit_environment
                             .value_map
                             .iter()
                             .filter(|(p, _)| p.is_rooted_by(&result_root))
@@ -929,11 +1322,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                     }
                 }
             }
-            self.reset_visitor_state();
-            *self.type_visitor_mut() = saved_type_visitor.clone();
         }
-        self.def_id = saved_def_id;
-        self.mir = saved_mir;
         environment
     }
 
@@ -1136,6 +1525,14 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                     }
                 }
             };
+            // The bounds of the offset and of the length often settle the check without the solver.
+            if let (Some((lower, upper)), Some((len_lower, _))) =
+                (self.bound_interval_of(right), self.bound_interval_of(&len))
+            {
+                if lower >= 0 && upper <= len_lower.saturating_add(1) {
+                    return;
+                }
+            }
             let le_one_past =
                 right.less_or_equal(len.addition(Rc::new(ConstantDomain::I128(1).into())));
             let in_range = ge_zero.and(le_one_past);
@@ -1428,9 +1825,9 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
     }
 
     /// When we transfer a side effect of the form (root_path.$tags, tag_field_value), i.e., the callee
-    /// has attached some tags to the value located at root_path, we go through all the tags recorded in
-    /// tag_field_value, and invoke `attach_tag_to_elements` to properly propagate tags to elements rooted
-    /// by root_path.
+    /// has attached some tags to the value located at root_path, we collect all the tags recorded in
+    /// tag_field_value, and invoke `attach_tags_to_value_at_path` to properly propagate them to elements
+    /// rooted by root_path in a single walk.
     #[logfn_inputs(TRACE)]
     fn transfer_and_propagate_tags(
         &mut self,
@@ -1438,9 +1835,16 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
         root_path: &Rc<Path>,
         root_rustc_type: Ty<'tcx>,
     ) {
-        if let Expression::TaggedExpression { tag, operand } = &tag_field_value.expression {
-            self.transfer_and_propagate_tags(operand, root_path, root_rustc_type);
-            self.attach_tag_to_value_at_path(*tag, root_path.clone(), root_rustc_type);
+        let mut tags = Vec::new();
+        let mut tag_field_value = tag_field_value;
+        while let Expression::TaggedExpression { tag, operand } = &tag_field_value.expression {
+            tags.push(*tag);
+            tag_field_value = operand;
+        }
+        // The innermost tag was attached first
+        tags.reverse();
+        if !tags.is_empty() {
+            self.attach_tags_to_value_at_path(&tags, root_path.clone(), root_rustc_type);
         }
     }
 
@@ -1637,29 +2041,41 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                 cond_as_bool = self.solve_condition(cond_val);
             }
         } else {
+            // The abstract domains do not know what a quantified condition means, so it is
+            // left to the SMT solver.
+            let is_quantified = cond_val.expression.is_quantified();
             // Check if path implies condition
             if cond_as_bool.unwrap_or(false)
-                || self.current_environment.entry_condition.implies(cond_val)
+                || (!is_quantified && self.current_environment.entry_condition.implies(cond_val))
             {
                 return (Some(true), entry_cond_as_bool);
             }
             if !cond_as_bool.unwrap_or(true)
-                || self
-                    .current_environment
-                    .entry_condition
-                    .implies_not(cond_val)
+                || (!is_quantified
+                    && self
+                        .current_environment
+                        .entry_condition
+                        .implies_not(cond_val))
             {
                 return (Some(false), entry_cond_as_bool);
             }
             // The abstract domains are unable to decide if the entry condition is always true.
             // (If it could decide that the condition is always false, we wouldn't be here.)
             // See if the SMT solver can prove that the entry condition is always true.
+            if self.is_cancelled() {
+                return (cond_as_bool, entry_cond_as_bool);
+            }
+            if self.batches_solver_queries() {
+                return self.solve_condition_and_reachability(cond_val, cond_as_bool);
+            }
             self.smt_solver.set_backtrack_position();
             let smt_expr = {
                 let ec = &self.current_environment.entry_condition.expression;
                 self.smt_solver.get_as_smt_predicate(ec)
             };
             self.smt_solver.assert(&smt_expr);
+            // A query that times out is Undefined, like one that the solver cannot decide, so
+            // that the answers of the abstract domains are kept.
             let smt_result = self.smt_solver.solve();
             if smt_result == SmtResult::Unsatisfiable {
                 // The solver can prove that the entry condition is always false.
@@ -1674,9 +2090,94 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
         (cond_as_bool, entry_cond_as_bool)
     }
 
+    /// True if the analysis has been cancelled, in which case the solver is not asked any more
+    /// queries and the current function is marked as angelic.
+    pub fn is_cancelled(&mut self) -> bool {
+        if self.cv.cancellation.is_cancelled() {
+            self.analysis_is_incomplete = true;
+            return true;
+        }
+        false
+    }
+
+    /// True if the queries of the body are solved under assumptions in a single solver context,
+    /// rather than in a context of their own.
+    fn batches_solver_queries(&self) -> bool {
+        self.check_for_errors && self.cv.options.batch_solver_queries
+    }
+
+    /// Like the tail of check_condition_value_and_reachability, but the entry condition and the
+    /// condition are passed to the solver as assumptions, so that the context of the body is
+    /// left unchanged.
     #[logfn_inputs(TRACE)]
+    fn solve_condition_and_reachability(
+        &mut self,
+        cond_val: &Rc<AbstractValue>,
+        mut cond_as_bool: Option<bool>,
+    ) -> (Option<bool>, Option<bool>) {
+        let mut entry_cond_as_bool = None;
+        let entry_smt_expr = {
+            let ec = &self.current_environment.entry_condition.expression;
+            self.smt_solver.get_as_smt_predicate(ec)
+        };
+        if self.smt_solver.solve_under_assumptions(&[entry_smt_expr]) == SmtResult::Unsatisfiable {
+            // The solver can prove that the entry condition is always false.
+            entry_cond_as_bool = Some(false);
+        }
+        if cond_as_bool.is_none() && entry_cond_as_bool.unwrap_or(true) {
+            let cond_smt_expr = self.smt_solver.get_as_smt_predicate(&cond_val.expression);
+            cond_as_bool = solve_condition_under_assumptions(
+                &self.smt_solver,
+                &[entry_smt_expr],
+                cond_smt_expr,
+            );
+        }
+        (cond_as_bool, entry_cond_as_bool)
+    }
+
+    #[logfn_inputs(TRACE)]
+    /// Returns the span of the closest assume! call on every path to the given block whose
+    /// condition contradicts the condition of an edge that leads to the block, if there is one.
+    pub fn conflicting_assumption(&mut self, block: mir::BasicBlock) -> Option<rustc_span::Span> {
+        let dominators = self.mir.basic_blocks.dominators();
+        let mut edge_conditions = Vec::new();
+        let mut dominator = Some(block);
+        while let Some(bb) = dominator {
+            if let Some(conditions) = self.branch_conditions.get(&bb) {
+                edge_conditions.extend(conditions.iter().cloned());
+            }
+            dominator = dominators.immediate_dominator(bb);
+        }
+        let assumptions: Vec<(rustc_span::Span, Rc<AbstractValue>)> = self
+            .assumptions
+            .iter()
+            .rev()
+            .filter(|(bb, ..)| dominators.dominates(*bb, block))
+            .map(|(_, span, condition)| (*span, condition.clone()))
+            .collect();
+        for (span, assumption) in assumptions {
+            for condition in edge_conditions.iter() {
+                let conjunction = assumption.and(condition.clone());
+                let conjunction_as_bool = conjunction
+                    .as_bool_if_known()
+                    .or_else(|| self.solve_condition(&conjunction));
+                if conjunction_as_bool == Some(false) {
+                    return Some(span);
+                }
+            }
+        }
+        None
+    }
+
     fn solve_condition(&mut self, cond_val: &Rc<AbstractValue>) -> Option<bool> {
+        if self.is_cancelled() {
+            return None;
+        }
         let ce = &cond_val.expression;
+        if self.batches_solver_queries() {
+            let cond_smt_expr = self.smt_solver.get_as_smt_predicate(ce);
+            return solve_condition_under_assumptions(&self.smt_solver, &[], cond_smt_expr);
+        }
         self.smt_solver.set_backtrack_position();
         let cond_smt_expr = self.smt_solver.get_as_smt_predicate(ce);
         let inv_cond_smt_expr = self.smt_solver.invert_predicate(&cond_smt_expr);
@@ -1702,6 +2203,119 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
         result
     }
 
+    /// Returns the smallest and largest values that the given value can have at runtime, as they
+    /// follow from the structure of its expression. With --tighten-intervals, the solver is also
+    /// asked which of those values the value can actually have when the current location is
+    /// reached, by a binary search for each bound that gives up after a few queries.
+    pub fn bound_interval_of(&mut self, value: &Rc<AbstractValue>) -> Option<(i128, i128)> {
+        let (mut lower, mut upper) = value.bound_interval()?;
+        if !self.cv.options.tighten_intervals
+            || lower == upper
+            || self.batches_solver_queries()
+            || self.is_cancelled()
+        {
+            return Some((lower, upper));
+        }
+        let value_type = value.expression.infer_type();
+        let constant = |i: i128| -> Rc<AbstractValue> {
+            match u128::try_from(i) {
+                Ok(u) if value_type.is_unsigned_integer() => Rc::new(u.into()),
+                _ => Rc::new(i.into()),
+            }
+        };
+        self.smt_solver.set_backtrack_position();
+        let entry_smt_expr = {
+            let ec = &self.current_environment.entry_condition.expression;
+            self.smt_solver.get_as_smt_predicate(ec)
+        };
+        self.smt_solver.assert(&entry_smt_expr);
+        let mut queries = 0;
+        // The value is known to be at most upper. Look for a smaller upper bound in [lower, upper].
+        let mut smallest = lower;
+        while smallest < upper && queries < k_limits::MAX_INTERVAL_TIGHTENING_QUERIES {
+            if self.is_cancelled() {
+                break;
+            }
+            queries += 1;
+            // The average, rounded down, without overflowing.
+            let middle = (smallest & upper) + ((smallest ^ upper) >> 1);
+            let above = value.greater_than(constant(middle));
+            let above_smt_expr = self.smt_solver.get_as_smt_predicate(&above.expression);
+            if self.smt_solver.solve_expression(&above_smt_expr) == SmtResult::Unsatisfiable {
+                upper = middle;
+            } else {
+                smallest = middle + 1;
+            }
+        }
+        // The value is known to be at least lower. Look for a larger lower bound in [lower, upper].
+        let mut largest = upper;
+        while lower < largest && queries < k_limits::MAX_INTERVAL_TIGHTENING_QUERIES {
+            if self.is_cancelled() {
+                break;
+            }
+            queries += 1;
+            // The average, rounded up, without overflowing.
+            let middle = (lower | largest) - ((lower ^ largest) >> 1);
+            let below = value.less_than(constant(middle));
+            let below_smt_expr = self.smt_solver.get_as_smt_predicate(&below.expression);
+            if self.smt_solver.solve_expression(&below_smt_expr) == SmtResult::Unsatisfiable {
+                lower = middle;
+            } else {
+                largest = middle - 1;
+            }
+        }
+        self.smt_solver.backtrack();
+        Some((lower, upper))
+    }
+
+    /// Returns a note with the values that the solver assigns to the variables of the condition
+    /// in a state that reaches the current location with the condition false, such as
+    /// "counterexample: amount = 18446744073709551615, balance = 0", if --counterexamples is
+    /// given and the solver finds such a state. The variables are named as in the source, and
+    /// the other variables of the model, such as those of the entry condition, are left out.
+    pub fn counterexample(&self, cond_val: &Rc<AbstractValue>) -> Option<String> {
+        if !self.cv.options.counterexamples || self.cv.cancellation.is_cancelled() {
+            return None;
+        }
+        let mut variables = Vec::new();
+        cond_val.expression.record_variables(&mut variables);
+        if variables.is_empty() {
+            return None;
+        }
+        self.smt_solver.set_backtrack_position();
+        let entry_smt_expr = {
+            let ec = &self.current_environment.entry_condition.expression;
+            self.smt_solver.get_as_smt_predicate(ec)
+        };
+        self.smt_solver.assert(&entry_smt_expr);
+        let cond_smt_expr = self.smt_solver.get_as_smt_predicate(&cond_val.expression);
+        self.smt_solver
+            .assert(&self.smt_solver.invert_predicate(&cond_smt_expr));
+        let model = match self.smt_solver.solve() {
+            SmtResult::Satisfiable => self.smt_solver.get_model_string(),
+            _ => None,
+        };
+        self.smt_solver.backtrack();
+        let model = model?;
+        let values: HashMap<&str, &str> = model
+            .lines()
+            .filter_map(|line| line.rsplit_once(" = "))
+            .collect();
+        let mut assignments: Vec<String> = variables
+            .iter()
+            .filter_map(|path| {
+                let value = values.get(format!("{path:?}").as_str())?;
+                let name = fixed_point_visitor::render_path(self.mir, path);
+                Some(format!("{name} = {value}"))
+            })
+            .collect();
+        if assignments.is_empty() {
+            return None;
+        }
+        assignments.sort();
+        Some(format!("counterexample: {}", assignments.join(", ")))
+    }
+
     /// Copies/moves all paths rooted in source_path to corresponding paths rooted in target_path.
     /// source_path and/or target_path may be pattern paths and will be expanded as needed.
     #[logfn_inputs(TRACE)]
@@ -2223,7 +2837,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                         } else {
                             to
                         };
-                        if to - from < k_limits::MAX_ELEMENTS_TO_TRACK as u64 {
+                        if to - from < self.cv.options.k_limits.max_elements_to_track as u64 {
                             self.expand_slice(
                                 target_path,
                                 qualifier,
@@ -2403,7 +3017,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                 if let Expression::CompileTimeConstant(ConstantDomain::U128(val)) =
                     &count.expression
                 {
-                    if *val < k_limits::MAX_ELEMENTS_TO_TRACK as u128 {
+                    if *val < self.cv.options.k_limits.max_elements_to_track as u128 {
                         self.expand_slice(
                             qualifier,
                             source_path,
@@ -2435,7 +3049,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
         // Assigning to a fixed length array is like a slice.
         if let TyKind::Array(_, length) = root_rustc_type.kind() {
             let length = self.get_array_length(length);
-            if length < k_limits::MAX_ELEMENTS_TO_TRACK {
+            if length < self.cv.options.k_limits.max_elements_to_track {
                 self.expand_slice(
                     target_path,
                     source_path,
@@ -2636,7 +3250,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                     if let Expression::CompileTimeConstant(ConstantDomain::U128(val)) =
                         &count.expression
                     {
-                        if *val < (k_limits::MAX_ELEMENTS_TO_TRACK as u128) {
+                        if *val < (self.cv.options.k_limits.max_elements_to_track as u128) {
                             for i in 0..*val {
                                 let target_index_val = Rc::new(i.into());
                                 let indexed_target =
@@ -2652,7 +3266,9 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                     to,
                     from_end: false,
                 } => {
-                    if *to >= *from && (*to - *from) < (k_limits::MAX_ELEMENTS_TO_TRACK as u64) {
+                    if *to >= *from
+                        && (*to - *from) < (self.cv.options.k_limits.max_elements_to_track as u64)
+                    {
                         for i in *from..*to {
                             let target_index_val = Rc::new((i as u128).into());
                             let indexed_target =
@@ -2667,7 +3283,9 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                     to,
                     from_end: true,
                 } => {
-                    if *to >= *from && (*to - *from) < (k_limits::MAX_ELEMENTS_TO_TRACK as u64) {
+                    if *to >= *from
+                        && (*to - *from) < (self.cv.options.k_limits.max_elements_to_track as u64)
+                    {
                         let one = Rc::new(0u128.into());
                         let end_index = self.get_len(qualifier.clone()).subtract(one);
                         for i in *from..*to {
@@ -2771,23 +3389,67 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
 
     /// Attach `tag` to the value located at `value_path`. The `value_path` may be pattern paths
     /// and need be expanded.
-    #[allow(clippy::suspicious_else_formatting)]
     #[logfn_inputs(TRACE)]
     pub fn attach_tag_to_value_at_path(
         &mut self,
         tag: Tag,
         value_path: Rc<Path>,
         root_rustc_type: Ty<'tcx>,
+    ) {
+        self.update_tags_of_value_at_path(&[tag], value_path, root_rustc_type, |value, tag| {
+            value.add_tag(tag)
+        });
+    }
+
+    /// Attach all of `tags` to the value located at `value_path` in a single walk over the
+    /// environment. The result is the same as that of attaching the tags one after the other.
+    #[logfn_inputs(TRACE)]
+    pub fn attach_tags_to_value_at_path(
+        &mut self,
+        tags: &[Tag],
+        value_path: Rc<Path>,
+        root_rustc_type: Ty<'tcx>,
+    ) {
+        self.update_tags_of_value_at_path(tags, value_path, root_rustc_type, |value, tag| {
+            value.add_tag(tag)
+        });
+    }
+
+    /// Remove `tag` from the value located at `value_path`, from its tag field and, if the tag
+    /// propagates to sub-components, from the values and tag fields of its sub-components.
+    /// The `value_path` may be pattern paths and need be expanded.
+    #[logfn_inputs(TRACE)]
+    pub fn detach_tag_from_value_at_path(
+        &mut self,
+        tag: Tag,
+        value_path: Rc<Path>,
+        root_rustc_type: Ty<'tcx>,
+    ) {
+        self.update_tags_of_value_at_path(&[tag], value_path, root_rustc_type, |value, tag| {
+            value.remove_tag(tag)
+        });
+    }
+
+    /// Replace the values that carry the tags of the value located at `value_path` with
+    /// update(value, tag), for each of the given tags that reaches them, in the order of `tags`.
+    /// See attach_tag_to_value_at_path and detach_tag_from_value_at_path.
+    #[allow(clippy::suspicious_else_formatting)]
+    fn update_tags_of_value_at_path(
+        &mut self,
+        tags: &[Tag],
+        value_path: Rc<Path>,
+        root_rustc_type: Ty<'tcx>,
+        update: fn(&Rc<AbstractValue>, Tag) -> Rc<AbstractValue>,
     ) {
         // The value path contains an index/slice selector, e.g., arr[i]. If the index pattern is
         // concrete, e.g. the index i is a constant, we need to expand it and then use a helper
-        // function to call attach_tag_to_elements recursively on each expansion.
+        // function to call update_tags_of_value_at_path recursively on each expansion.
         let expanded_source_pattern = self.try_expand_source_pattern(
             &value_path,
             &value_path,
             root_rustc_type,
             |_self, _target_path, expanded_path, root_rustc_type| {
-                _self.attach_tag_to_value_at_path(tag, expanded_path, root_rustc_type);
+                _self.update_tags_of_value_at_path(tags, expanded_path, root_rustc_type, update);
             },
         );
         if expanded_source_pattern {
@@ -2796,7 +3458,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
 
         // Get here if value_path is not a source pattern.
         // Now see if value_path is a target pattern.
-        // First look for union fields. We have to attach the tag to every field of the union
+        // First look for union fields. We have to attach the tags to every field of the union
         // because union fields all alias the same underlying storage.
         if let PathEnum::QualifiedPath {
             qualifier,
@@ -2813,10 +3475,13 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                         root_rustc_type,
                         false,
                         |_self, path, new_value| {
+                            let new_value = tags
+                                .iter()
+                                .fold(new_value, |value, tag| update(&value, *tag));
                             _self
                                 .current_environment
                                 .value_map
-                                .insert_mut(path, new_value.add_tag(tag));
+                                .insert_mut(path, new_value);
                         },
                     );
                 }
@@ -2827,28 +3492,32 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
         // Get here if value path is not a pattern, or it contains an abstract index/slice selector.
         // Consider the case where the value path contains an abstract index/slice selector.
         // If the index/slice selector is a compile-time constant, then we use a helper function
-        // to call attach_tag_to_elements recursively on each expansion.
-        // If not, all the paths that can match the pattern are weakly attached with the tag.
+        // to call update_tags_of_value_at_path recursively on each expansion.
+        // If not, all the paths that can match the pattern are weakly attached with the tags.
         let expanded_target_pattern = self.try_expand_target_pattern(
             &value_path,
             &value_path,
             root_rustc_type,
             |_self, _target_path, expanded_path, root_rustc_type| {
-                _self.attach_tag_to_value_at_path(tag, expanded_path, root_rustc_type);
+                _self.update_tags_of_value_at_path(tags, expanded_path, root_rustc_type, update);
             },
         );
         if expanded_target_pattern {
             return;
         }
 
+        let mut tags = tags.to_vec();
         if !root_rustc_type.is_scalar() {
             let (tag_field_path, tag_field_value) =
                 self.extract_tag_field_of_non_scalar_value_at(&value_path, root_rustc_type);
-            self.update_value_at(tag_field_path, tag_field_value.add_tag(tag));
-            if !tag.is_propagated_by(TagPropagation::SubComponent) {
+            let tag_field_value = tags
+                .iter()
+                .fold(tag_field_value, |value, tag| update(&value, *tag));
+            self.update_value_at(tag_field_path, tag_field_value);
+            // Only the tags that are propagated to sub-components are left.
+            tags.retain(|tag| tag.is_propagated_by(TagPropagation::SubComponent));
+            if tags.is_empty() {
                 return;
-            } else {
-                // fall through, the tag is propagated to sub-components.
             }
         }
 
@@ -2870,8 +3539,18 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                     }
                 }
 
+                // A tag may be blocked by the projections that lead from value_path to path.
+                let propagated_tags: Vec<Tag> = tags
+                    .iter()
+                    .filter(|tag| tag.is_propagated_between(&value_path, &path))
+                    .copied()
+                    .collect();
+                if propagated_tags.is_empty() {
+                    return;
+                }
+
                 // We should update the tag fields of non-scalar values.
-                // The logic is implemented in propagate_tag_to_tag_fields.
+                // The logic is implemented in propagate_tags_to_tag_fields.
                 let path_rustc_type = _self
                     .type_visitor()
                     .get_path_rustc_type(&path, _self.current_span);
@@ -2879,16 +3558,15 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                     return;
                 }
 
-                _self.update_value_at(path, new_value.add_tag(tag));
+                let new_value = propagated_tags
+                    .iter()
+                    .fold(new_value, |value, tag| update(&value, *tag));
+                _self.update_value_at(path, new_value);
             },
         );
 
-        // Propagate the tag to all tag fields rooted by value_path.
-        self.propagate_tag_to_tag_fields(
-            value_path,
-            &|value| value.add_tag(tag),
-            &mut HashSet::new(),
-        );
+        // Propagate the tags to all tag fields rooted by value_path.
+        self.propagate_tags_to_tag_fields(value_path, &tags, update, &mut HashMap::new());
     }
 
     /// Extract the path and the value of the tag field of the value located at `qualifier`.
@@ -2942,17 +3620,19 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
         (tag_field_path, tag_field_value)
     }
 
-    /// Attach a tag to all tag field paths that are rooted by root_path.
-    /// If v is the value at a tag field path, then it is updated to attach_tag(v).
-    fn propagate_tag_to_tag_fields<F>(
+    /// Attach the tags to all tag field paths that are rooted by root_path, unless the projections
+    /// that lead to them block the tags.
+    /// If v is the value at a tag field path, then it is updated to update(v, tag) for each tag.
+    /// The tags that have already been propagated to a path prefix are recorded in
+    /// visited_path_prefixes, so that each of them is propagated to it once.
+    fn propagate_tags_to_tag_fields(
         &mut self,
         root_path: Rc<Path>,
-        attach_tag: &F,
-        visited_path_prefixes: &mut HashSet<Rc<Path>>,
-    ) where
-        F: Fn(Rc<AbstractValue>) -> Rc<AbstractValue>,
-    {
-        trace!("propagate_tag_to_tag_fields(root_path: {:?})", root_path);
+        tags: &[Tag],
+        update: fn(&Rc<AbstractValue>, Tag) -> Rc<AbstractValue>,
+        visited_path_prefixes: &mut HashMap<Rc<Path>, Vec<Tag>>,
+    ) {
+        trace!("propagate_tags_to_tag_fields(root_path: {:?})", root_path);
         let old_value_map = self.current_environment.value_map.clone();
 
         for (path, val) in old_value_map
@@ -2960,29 +3640,61 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
             .filter(|(p, _)| p.is_rooted_by(&root_path))
         {
             if let Expression::Reference(p) = &val.expression {
-                self.propagate_tag_to_tag_fields(p.clone(), attach_tag, visited_path_prefixes);
+                let dereferenced_tags: Vec<Tag> = tags
+                    .iter()
+                    .filter(|tag| {
+                        tag.is_propagated_by_selector(&PathSelector::Deref)
+                            && tag.is_propagated_between(&root_path, path)
+                    })
+                    .copied()
+                    .collect();
+                if !dereferenced_tags.is_empty() {
+                    self.propagate_tags_to_tag_fields(
+                        p.clone(),
+                        &dereferenced_tags,
+                        update,
+                        visited_path_prefixes,
+                    );
+                }
             }
             let mut path_prefix = path;
+            let mut tags = tags.to_vec();
 
             loop {
-                // If path_prefix has been visited, we exit the loop.
-                if !visited_path_prefixes.insert(path_prefix.clone()) {
+                // Only the tags that have not yet been propagated to path_prefix are left.
+                // If there are none, we exit the loop.
+                let visited_tags = visited_path_prefixes
+                    .entry(path_prefix.clone())
+                    .or_default();
+                tags.retain(|tag| !visited_tags.contains(tag));
+                if tags.is_empty() {
                     break;
                 }
+                visited_tags.extend(tags.iter().copied());
 
                 // We get here if path_prefix is rooted by root_path and is not yet visited.
                 let path_prefix_rustc_type = self
                     .type_visitor()
                     .get_path_rustc_type(path_prefix, self.current_span);
                 if !path_prefix_rustc_type.is_scalar() {
-                    let (tag_field_path, tag_field_value) = self
-                        .extract_tag_field_of_non_scalar_value_at(
-                            path_prefix,
-                            path_prefix_rustc_type,
-                        );
-                    self.current_environment
-                        .value_map
-                        .insert_mut(tag_field_path.clone(), attach_tag(tag_field_value));
+                    let propagated_tags: Vec<Tag> = tags
+                        .iter()
+                        .filter(|tag| tag.is_propagated_between(&root_path, path_prefix))
+                        .copied()
+                        .collect();
+                    if !propagated_tags.is_empty() {
+                        let (tag_field_path, tag_field_value) = self
+                            .extract_tag_field_of_non_scalar_value_at(
+                                path_prefix,
+                                path_prefix_rustc_type,
+                            );
+                        let tag_field_value = propagated_tags
+                            .iter()
+                            .fold(tag_field_value, |value, tag| update(&value, *tag));
+                        self.current_environment
+                            .value_map
+                            .insert_mut(tag_field_path.clone(), tag_field_value);
+                    }
                 }
 
                 if let PathEnum::QualifiedPath { qualifier, .. } = &path_prefix.value {
@@ -3001,3 +3713,103 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
         }
     }
 }
+
+/// The state of a body visitor that belongs to the body being analyzed, which is set aside while
+/// a promoted constant of the body is analyzed and put back when the scope is dropped, so that
+/// nothing the analysis of the constant leaves behind is seen by the analysis of the body.
+struct PromotedConstantScope<'scope, 'analysis, 'compilation, 'tcx> {
+    bv: &'scope mut BodyVisitor<'analysis, 'compilation, 'tcx>,
+    saved_mir: &'tcx mir::Body<'tcx>,
+    saved_type_visitor: TypeVisitor<'tcx>,
+    saved_contract_checkers: ContractCheckers<'tcx>,
+}
+
+impl<'scope, 'analysis, 'compilation, 'tcx>
+    PromotedConstantScope<'scope, 'analysis, 'compilation, 'tcx>
+{
+    /// Sets aside the state of the body and prepares the visitor to analyze the given constant.
+    fn enter(
+        bv: &'scope mut BodyVisitor<'analysis, 'compilation, 'tcx>,
+        constant_mir: &'tcx mir::Body<'tcx>,
+    ) -> Self {
+        let saved_mir = std::mem::replace(&mut bv.mir, constant_mir);
+        let saved_type_visitor = bv.type_visitor.clone();
+        bv.type_visitor.mir = constant_mir;
+        // The checkers track locals, blocks and locations, which the constant numbers anew.
+        let saved_contract_checkers = std::mem::take(&mut bv.contract_checkers);
+        PromotedConstantScope {
+            bv,
+            saved_mir,
+            saved_type_visitor,
+            saved_contract_checkers,
+        }
+    }
+}
+
+impl<'analysis, 'compilation, 'tcx> std::ops::Deref
+    for PromotedConstantScope<'_, 'analysis, 'compilation, 'tcx>
+{
+    type Target = BodyVisitor<'analysis, 'compilation, 'tcx>;
+
+    fn deref(&self) -> &Self::Target {
+        self.bv
+    }
+}
+
+impl std::ops::DerefMut for PromotedConstantScope<'_, '_, '_, '_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.bv
+    }
+}
+
+impl Drop for PromotedConstantScope<'_, '_, '_, '_> {
+    fn drop(&mut self) {
+        self.bv.reset_visitor_state();
+        self.bv.mir = self.saved_mir;
+        std::mem::swap(&mut self.bv.type_visitor, &mut self.saved_type_visitor);
+        std::mem::swap(
+            &mut self.bv.contract_checkers,
+            &mut self.saved_contract_checkers,
+        );
+        debug_assert!(self.bv.current_environment.value_map.is_empty());
+        debug_assert!(self.bv.exit_environment.is_none());
+        debug_assert!(self.bv.heap_addresses.is_empty());
+        debug_assert!(self.bv.preconditions.is_empty() && self.bv.post_condition.is_none());
+        debug_assert!(self.bv.block_to_call.is_empty());
+        debug_assert!(self.bv.block_times.is_empty());
+        debug_assert!(self.bv.branch_condition_spans.is_empty());
+        debug_assert!(self.bv.assumptions.is_empty() && self.bv.branch_conditions.is_empty());
+        debug_assert!(self.bv.loop_invariant_iterations.is_empty());
+        debug_assert!(self.bv.unpreserved_loop_invariants.is_empty());
+        debug_assert!(self.bv.wrapping_operations.is_empty());
+        debug_assert!(self.bv.narrowing_casts.is_empty());
+        debug_assert!(std::ptr::eq(self.bv.type_visitor.mir, self.bv.mir));
+    }
+}
+
+/// Uses the solver to decide the value of the given condition under the given assumptions,
+/// without changing the context of the solver.
+fn solve_condition_under_assumptions<E: Copy>(
+    smt_solver: &impl SmtSolver<E>,
+    assumptions: &[E],
+    condition: E,
+) -> Option<bool> {
+    let mut assumptions = assumptions.to_vec();
+    assumptions.push(condition);
+    match smt_solver.solve_under_assumptions(&assumptions) {
+        SmtResult::Unsatisfiable => {
+            // The solver can prove that the condition is always false.
+            Some(false)
+        }
+        SmtResult::Satisfiable => {
+            // See if the inverse of the condition is provably false.
+            *assumptions.last_mut().unwrap() = smt_solver.invert_predicate(&condition);
+            if smt_solver.solve_under_assumptions(&assumptions) == SmtResult::Unsatisfiable {
+                Some(true)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
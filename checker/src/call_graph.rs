@@ -5,9 +5,11 @@
 
 use core::fmt;
 use std::collections::hash_map::Entry;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::fs;
+use std::io::Write;
 use std::path::Path;
+use std::rc::Rc;
 
 use petgraph::dot::{Config, Dot};
 use petgraph::graph::{DefaultIx, NodeIndex};
@@ -119,6 +121,9 @@ pub struct CallGraphConfig {
     datalog_config: Option<DatalogConfig>,
     /// If true, collect all call sites.
     pub include_calls_in_summaries: bool,
+    /// If true, record the edges of the graph for --call-graph-out.
+    #[serde(default)]
+    pub include_edges_in_export: bool,
 }
 
 impl CallGraphConfig {
@@ -136,6 +141,7 @@ impl CallGraphConfig {
             included_crates,
             datalog_config,
             include_calls_in_summaries: false,
+            include_edges_in_export: false,
         }
     }
 
@@ -313,6 +319,27 @@ impl CallGraphEdge {
     }
 }
 
+/// The kind of an edge of the call graph that is written with --call-graph-out.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum CallEdgeKind {
+    /// The caller calls the callee.
+    Call,
+    /// The calls of the caller are dominated by calls of the callee, as recorded by `add_dom`.
+    DominatedBy,
+    /// The caller invokes another program through the callee, such as invoke or invoke_signed.
+    Cpi,
+}
+
+impl CallEdgeKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            CallEdgeKind::Call => "call",
+            CallEdgeKind::DominatedBy => "dominated_by",
+            CallEdgeKind::Cpi => "cpi",
+        }
+    }
+}
+
 /// `CallGraph` stores all information related to the call graph
 /// and it's associated information.
 #[derive(Clone)]
@@ -336,6 +363,10 @@ pub struct CallGraph<'tcx> {
     edge_types: HashMap<Box<str>, EdgeType>,
     /// Dominance information
     dominance: HashMap<DefId, HashSet<DefId>>,
+    /// The (caller, callee) pairs of the calls that invoke another program
+    cross_program_invocations: HashSet<(DefId, DefId)>,
+    /// The functions in which a checker reported a finding
+    flagged: HashSet<DefId>,
 }
 
 impl<'tcx> CallGraph<'tcx> {
@@ -353,6 +384,8 @@ impl<'tcx> CallGraph<'tcx> {
             nodes: HashMap::<DefId, NodeId>::new(),
             edge_types: HashMap::<Box<str>, EdgeType>::new(),
             dominance: HashMap::<DefId, HashSet<DefId>>::new(),
+            cross_program_invocations: HashSet::new(),
+            flagged: HashSet::new(),
         }
     }
 
@@ -369,7 +402,9 @@ impl<'tcx> CallGraph<'tcx> {
     }
 
     pub fn needs_edges(&self) -> bool {
-        self.config.dot_output_path.is_some() || self.config.datalog_config.is_some()
+        self.config.dot_output_path.is_some()
+            || self.config.datalog_config.is_some()
+            || self.config.include_edges_in_export
     }
 
     /// Produce an updated call graph structure that preserves all the
@@ -384,6 +419,8 @@ impl<'tcx> CallGraph<'tcx> {
             nodes: self.nodes.clone(),
             edge_types: self.edge_types.clone(),
             dominance: self.dominance.clone(),
+            cross_program_invocations: self.cross_program_invocations.clone(),
+            flagged: self.flagged.clone(),
         }
     }
 
@@ -454,6 +491,18 @@ impl<'tcx> CallGraph<'tcx> {
         self.dominance.entry(defid1).or_default().insert(defid2);
     }
 
+    /// Record that `caller` invokes another program by calling `callee`.
+    pub fn add_cross_program_invocation(&mut self, caller: DefId, callee: DefId) {
+        self.get_or_insert_node(caller);
+        self.get_or_insert_node(callee);
+        self.cross_program_invocations.insert((caller, callee));
+    }
+
+    /// Record that a checker reported a finding in the function with the given DefId.
+    pub fn add_flagged(&mut self, defid: DefId) {
+        self.flagged.insert(defid);
+    }
+
     /// Add a new EdgeType to the call graph's `edge_types`.
     fn add_edge_type(&mut self, edge_type_str: Box<str>) -> TypeId {
         let new_type_id = self.edge_types.len() as TypeId;
@@ -1045,7 +1094,7 @@ impl<'tcx> CallGraph<'tcx> {
 
     /// Produce a dot file representation of the call graph
     /// for displaying with Graphviz.
-    fn to_dot(&self, dot_path: &Path) {
+    fn to_dot_file(&self, dot_path: &Path) {
         let output = format!(
             "{:?}",
             Dot::with_config(&self.shortened_node_names(), &[Config::EdgeNoLabel])
@@ -1083,18 +1132,140 @@ impl<'tcx> CallGraph<'tcx> {
             );
         }
         if let Some(dot_path) = &self.config.dot_output_path {
-            call_graph.to_dot(Path::new(dot_path.as_ref()));
+            call_graph.to_dot_file(Path::new(dot_path.as_ref()));
         }
         if let Some(call_path) = &self.config.call_sites_output_path {
             call_graph.to_call_sites(Path::new(call_path.as_ref()));
         }
     }
 
+    /// Returns the nodes of the graph, labeled with the summary keys of their functions and with
+    /// true if a checker reported a finding in one of them, and the edges of the graph, by the
+    /// indices of their ends in the nodes and by kind. Both are sorted, so that the graph is
+    /// exported the same way in every run.
+    fn exported_nodes_and_edges(
+        &self,
+    ) -> (Vec<(Rc<str>, bool)>, Vec<(usize, usize, CallEdgeKind)>) {
+        let label = |defid: DefId| crate::utils::summary_key_str(self.tcx, defid);
+        let mut nodes = BTreeMap::<Rc<str>, bool>::new();
+        let mut edges = BTreeSet::<(Rc<str>, Rc<str>, CallEdgeKind)>::new();
+        for node in self.graph.node_weights() {
+            let flagged = self.flagged.contains(&node.defid);
+            *nodes.entry(label(node.defid)).or_default() |= flagged;
+        }
+        for edge in self.graph.raw_edges() {
+            let caller = self.graph[edge.source()].defid;
+            let callee = self.graph[edge.target()].defid;
+            let kind = if self.cross_program_invocations.contains(&(caller, callee)) {
+                CallEdgeKind::Cpi
+            } else {
+                CallEdgeKind::Call
+            };
+            edges.insert((label(caller), label(callee), kind));
+        }
+        for (caller, callee) in self.cross_program_invocations.iter() {
+            edges.insert((label(*caller), label(*callee), CallEdgeKind::Cpi));
+        }
+        for (dominated, dominators) in self.dominance.iter() {
+            for dominator in dominators {
+                for defid in [dominated, dominator] {
+                    let flagged = self.flagged.contains(defid);
+                    *nodes.entry(label(*defid)).or_default() |= flagged;
+                }
+                edges.insert((
+                    label(*dominated),
+                    label(*dominator),
+                    CallEdgeKind::DominatedBy,
+                ));
+            }
+        }
+        let index: HashMap<&Rc<str>, usize> = nodes
+            .keys()
+            .enumerate()
+            .map(|(index, label)| (label, index))
+            .collect();
+        let edges = edges
+            .iter()
+            .map(|(caller, callee, kind)| (index[caller], index[callee], *kind))
+            .collect();
+        (nodes.into_iter().collect(), edges)
+    }
+
+    /// Writes the call graph in the dot format of Graphviz, with the nodes of the functions in
+    /// which a checker reported a finding colored red and with the kind of each edge.
+    pub fn to_dot(&self, mut w: impl Write) -> std::io::Result<()> {
+        let escape = |label: &str| label.replace('\\', "\\\\").replace('"', "\\\"");
+        let (nodes, edges) = self.exported_nodes_and_edges();
+        writeln!(w, "digraph call_graph {{")?;
+        for (index, (label, flagged)) in nodes.iter().enumerate() {
+            let color = if *flagged { "red" } else { "black" };
+            writeln!(
+                w,
+                "    n{index} [label=\"{}\", flagged={flagged}, color={color}];",
+                escape(label)
+            )?;
+        }
+        for (caller, callee, kind) in edges {
+            writeln!(w, "    n{caller} -> n{callee} [kind={}];", kind.as_str())?;
+        }
+        writeln!(w, "}}")
+    }
+
+    /// Writes the call graph in GraphML, with the label of each node, whether a checker reported
+    /// a finding in it and the kind of each edge as data.
+    pub fn to_graphml(&self, mut w: impl Write) -> std::io::Result<()> {
+        let escape = |label: &str| {
+            label
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+        };
+        let (nodes, edges) = self.exported_nodes_and_edges();
+        writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            w,
+            r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+        )?;
+        writeln!(
+            w,
+            r#"  <key id="label" for="node" attr.name="label" attr.type="string"/>"#
+        )?;
+        writeln!(
+            w,
+            r#"  <key id="flagged" for="node" attr.name="flagged" attr.type="boolean"/>"#
+        )?;
+        writeln!(
+            w,
+            r#"  <key id="kind" for="edge" attr.name="kind" attr.type="string"/>"#
+        )?;
+        writeln!(w, r#"  <graph id="call_graph" edgedefault="directed">"#)?;
+        for (index, (label, flagged)) in nodes.iter().enumerate() {
+            writeln!(
+                w,
+                r#"    <node id="n{index}"><data key="label">{}</data><data key="flagged">{flagged}</data></node>"#,
+                escape(label)
+            )?;
+        }
+        for (caller, callee, kind) in edges {
+            writeln!(
+                w,
+                r#"    <edge source="n{caller}" target="n{callee}"><data key="kind">{}</data></edge>"#,
+                kind.as_str()
+            )?;
+        }
+        writeln!(w, "  </graph>")?;
+        writeln!(w, "</graphml>")
+    }
+
     pub fn get_calls_for_def_ids(&self) -> HashMap<DefId, Vec<(Span, DefId)>> {
         let mut calls = HashMap::<DefId, Vec<(Span, DefId)>>::new();
         for (span, (caller, callee)) in self.call_sites.iter() {
             calls.entry(*caller).or_default().push((*span, *callee));
         }
+        for call_sites in calls.values_mut() {
+            call_sites.sort_by_key(|(span, _)| *span);
+        }
         calls
     }
 }
@@ -0,0 +1,171 @@
+// Snapshots of what HEPHA believes at the calls of the functions chosen with
+// --record-call-states, so that auditors can ask what the analysis knew about a balance right
+// before an invoke. A snapshot is taken at every call of a matching function once the fixed point
+// of its body has been reached. It keeps the entry condition of the call and the values at the
+// paths rooted in parameters, in locals that hold accounts and in user variables that refer to
+// integers, such as a balance taken from a map or the lamports of an account. Values are rendered
+// as in the summary views.
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use rustc_middle::mir;
+use rustc_middle::ty::{GenericArgKind, Ty, TyCtxt, TyKind};
+use serde::{Deserialize, Serialize};
+
+use crate::contract_errors;
+use crate::environment::Environment;
+use crate::fixed_point_visitor::render_path;
+use crate::path::{Path, PathEnum, PathRoot};
+use crate::summary_view::ExpressionView;
+
+/// The version of the schema of call states, which changes whenever a field is removed or
+/// changes its meaning.
+pub const CALL_STATES_VERSION: u32 = 1;
+
+/// The states at the calls of the recorded functions of a crate, by the stable ids of the
+/// functions.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct CallStates {
+    pub version: u32,
+    pub functions: BTreeMap<String, FunctionCallStates>,
+}
+
+impl Default for CallStates {
+    fn default() -> Self {
+        CallStates {
+            version: CALL_STATES_VERSION,
+            functions: BTreeMap::new(),
+        }
+    }
+}
+
+impl CallStates {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("call states should serialize")
+    }
+
+    /// Writes the call states as JSON to the file at the given path.
+    pub fn write(&self, path: &std::path::Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    /// Records the state at the call with the given source location in the function with the
+    /// given stable id. If the function already has states for the given number of other calls,
+    /// the state is dropped and the states of the function are marked as truncated.
+    pub fn record(
+        &mut self,
+        function: &str,
+        name: &str,
+        location: String,
+        state: CallState,
+        limit: usize,
+    ) {
+        let states = self
+            .functions
+            .entry(function.to_string())
+            .or_insert_with(|| FunctionCallStates {
+                name: name.to_string(),
+                truncated: false,
+                calls: BTreeMap::new(),
+            });
+        if states.calls.len() >= limit && !states.calls.contains_key(&location) {
+            states.truncated = true;
+            return;
+        }
+        states.calls.insert(location, state);
+    }
+}
+
+/// The states at the calls of a function.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct FunctionCallStates {
+    /// The name of the function as it is shown in diagnostics.
+    pub name: String,
+    /// True if the function has more calls than the states that are kept for a function.
+    pub truncated: bool,
+    /// The states by the source location of the call, as file:line:column.
+    pub calls: BTreeMap<String, CallState>,
+}
+
+/// What HEPHA believes right before a call.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CallState {
+    /// The def path of the called function.
+    pub callee: String,
+    /// The condition under which the call is reached.
+    pub entry_condition: ExpressionView,
+    /// The values at the recorded paths, sorted by path.
+    pub values: Vec<RecordedValue>,
+}
+
+/// The value at a path right before a call.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RecordedValue {
+    /// The path, rendered as a Rust place expression.
+    pub path: String,
+    pub value: ExpressionView,
+}
+
+impl CallState {
+    /// Returns the state of the given environment before a call of the given function.
+    pub fn new<'tcx>(
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        environment: &Environment,
+        callee: String,
+    ) -> CallState {
+        let mut values: Vec<RecordedValue> = environment
+            .value_map
+            .iter()
+            .filter(|(path, _)| is_recorded_path(tcx, mir, path))
+            .map(|(path, value)| RecordedValue {
+                path: render_path(mir, path),
+                value: ExpressionView::new(mir, value),
+            })
+            .collect();
+        // The value map is a hash map, so the values are sorted to make the output stable
+        values.sort_by(|a, b| a.path.cmp(&b.path));
+        CallState {
+            callee,
+            entry_condition: ExpressionView::new(mir, &environment.entry_condition),
+            values,
+        }
+    }
+}
+
+// True if the path is rooted in a parameter, in a local that holds accounts or in a user variable
+// that refers to an integer
+fn is_recorded_path<'tcx>(tcx: TyCtxt<'tcx>, mir: &mir::Body<'tcx>, path: &Rc<Path>) -> bool {
+    match &path.get_path_root().value {
+        PathEnum::Parameter { .. } => true,
+        PathEnum::LocalVariable { ordinal, .. } => {
+            // Locals that leak out of called functions are numbered past the locals of the body
+            if *ordinal >= mir.local_decls.len() {
+                return false;
+            }
+            let local = mir::Local::from_usize(*ordinal);
+            let ty = mir.local_decls[local].ty;
+            contract_errors::is_account_info_type(tcx, ty)
+                || (is_user_variable(mir, local) && refers_to_integer(ty))
+        }
+        _ => false,
+    }
+}
+
+fn is_user_variable(mir: &mir::Body<'_>, local: mir::Local) -> bool {
+    mir.var_debug_info.iter().any(|info| {
+        matches!(info.value, mir::VarDebugInfoContents::Place(place)
+            if place.local == local && place.projection.is_empty())
+    })
+}
+
+// True if the type is a reference or a smart pointer to an integer, such as &mut u64 or the
+// RefMut<&mut u64> that borrows the lamports of an account
+fn refers_to_integer(ty: Ty<'_>) -> bool {
+    matches!(ty.kind(), TyKind::Ref(..) | TyKind::Adt(..))
+        && ty.walk().any(|arg| {
+            matches!(arg.unpack(), GenericArgKind::Type(ty)
+                if matches!(ty.kind(), TyKind::Int(..) | TyKind::Uint(..)))
+        })
+}
@@ -4,7 +4,7 @@
 // LICENSE file in the root directory of this source tree.
 
 // use std::{f16, f64, f128};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Formatter, Result};
 use std::rc::Rc;
 use std::{f16, f64};
@@ -15,20 +15,26 @@ use hepha_annotations::*;
 use rustc_hir::def_id::DefId;
 use rustc_middle::mir;
 use rustc_middle::ty::ConstKind;
-use rustc_middle::ty::{GenericArg, GenericArgKind, GenericArgsRef, Ty, TyKind, UintTy};
-use rustc_target::abi::VariantIdx;
+use rustc_middle::ty::{
+    GenericArg, GenericArgKind, GenericArgsRef, Ty, TyKind, TypingMode, UintTy,
+};
+use rustc_target::abi::{FieldIdx, VariantIdx};
+use rustc_trait_selection::infer::{InferCtxtExt, TyCtxtInferExt};
 
 use crate::abstract_value::{AbstractValue, AbstractValueTrait};
-use crate::block_visitor::BlockVisitor;
+use crate::block_visitor::{arithmetic_code, BlockVisitor};
 use crate::body_visitor::BodyVisitor;
+use crate::checker_registry::{self, CHECKERS};
 use crate::constant_domain::{ConstantDomain, FunctionReference};
+use crate::contract_errors;
 use crate::environment::Environment;
 use crate::expression::{Expression, ExpressionType, LayoutSource};
 use crate::k_limits;
 use crate::known_names::KnownNames;
 use crate::options::DiagLevel;
 use crate::path::{Path, PathEnum, PathRefinement, PathRoot, PathSelector};
-use crate::summaries::{Precondition, Summary};
+use crate::solana_compat::SerializationFormat;
+use crate::summaries::{approximation_uses, CallSiteKey, Precondition, RecursiveSummary, Summary};
 use crate::tag_domain::Tag;
 use crate::type_visitor::TypeVisitor;
 use crate::{abstract_value, utils};
@@ -117,6 +123,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
         trace!("summarizing {:?}: {:?}", self.callee_def_id, func_type);
         let tcx = self.block_visitor.bv.tcx;
         if tcx.is_mir_available(self.callee_def_id) {
+            let uses_before = approximation_uses(&self.block_visitor.bv.cv.recursive_summaries);
             let mut body_visitor = BodyVisitor::new(
                 self.block_visitor.bv.cv,
                 self.callee_def_id,
@@ -141,7 +148,11 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
             }
             let mut summary = body_visitor.visit_body(self.function_constant_args);
             trace!("summary {:?} {:?}", self.callee_def_id, summary);
-            if let Some(func_ref) = &self.callee_func_ref {
+            // A summary that depends on the summary of a function whose fixed point is still
+            // being computed is not final, so it is not cached
+            let is_final =
+                approximation_uses(&self.block_visitor.bv.cv.recursive_summaries) == uses_before;
+            if let (Some(func_ref), true) = (&self.callee_func_ref, is_final) {
                 // If there is already a computed summary in the cache, we are in a recursive loop
                 // and hence have to join the summaries.
                 let previous_summary = self
@@ -195,7 +206,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
         {
             return;
         }
-        if let Some(gen_args) = self.callee_generic_arguments {
+        if let Some(mut gen_args) = self.callee_generic_arguments {
             // The parameter environment of the caller provides a resolution context for the callee.
             let typing_env = rustc_middle::ty::TypingEnv::fully_monomorphized();
             trace!(
@@ -210,7 +221,29 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                     // Instance::resolve panics if it can't find a vtable entry for the given def_id
                     // It is hard to figure out exactly when this will be the case, but it does
                     // happen in a case where the first generic argument type is Dynamic.
-                    return;
+                    // If the receiver is known to point to an object of a concrete type that
+                    // implements the trait, that type is used as the Self type instead.
+                    let Some(receiver_ty) = self.concrete_receiver_type() else {
+                        return;
+                    };
+                    let mut args: Vec<GenericArg<'tcx>> = gen_args.iter().collect();
+                    args[0] = receiver_ty.into();
+                    let trait_def_id = tcx.parent(self.callee_def_id);
+                    let trait_arg_count = tcx.generics_of(trait_def_id).count();
+                    let implements_trait = tcx
+                        .infer_ctxt()
+                        .build(TypingMode::non_body_analysis())
+                        .type_implements_trait(
+                            trait_def_id,
+                            args.iter().take(trait_arg_count).copied(),
+                            rustc_middle::ty::ParamEnv::empty(),
+                        )
+                        .must_apply_modulo_regions();
+                    if !implements_trait {
+                        return;
+                    }
+                    trace!("devirtualize receiver type {:?}", receiver_ty);
+                    gen_args = tcx.mk_args(&args);
                 }
             }
             let abi = tcx
@@ -292,6 +325,33 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
         }
     }
 
+    /// If the first argument of the call is a reference (or a fat pointer) to a heap block or
+    /// local whose recorded type is a concrete struct or enum, returns that type.
+    fn concrete_receiver_type(&self) -> Option<Ty<'tcx>> {
+        let (receiver_path, receiver) = self.actual_args.first()?;
+        let thin_pointer = if let Expression::Reference(..) = &receiver.expression {
+            receiver.clone()
+        } else {
+            let thin_pointer_path = Path::new_field(receiver_path.clone(), 0);
+            self.block_visitor
+                .bv
+                .current_environment
+                .value_at(&thin_pointer_path)?
+                .clone()
+        };
+        let Expression::Reference(target_path) = &thin_pointer.expression else {
+            return None;
+        };
+        let target_ty = self
+            .type_visitor()
+            .get_path_rustc_type(target_path, self.block_visitor.bv.current_span);
+        if matches!(target_ty.kind(), TyKind::Adt(..)) && utils::is_concrete(target_ty.kind()) {
+            Some(target_ty)
+        } else {
+            None
+        }
+    }
+
     /// Extract a list of function references from an environment of function constant arguments
     #[logfn_inputs(TRACE)]
     fn get_function_constant_signature(
@@ -332,6 +392,9 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                 }
             }
         }
+        if let Some(summary) = self.foreign_model_summary() {
+            return Some(summary);
+        }
         if let Some(func_ref) = &self.callee_func_ref.clone() {
             // If the actual arguments include any function constants, collect them together
             // and pass them to get_summary_for_function_constant so that their signatures
@@ -373,51 +436,269 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
             if result.is_computed || func_ref.def_id.is_none() {
                 return Some(result);
             }
-            if call_depth < 4 {
-                let mut summary = self.create_and_cache_function_summary(&func_args, &type_args);
-                if call_depth >= 1 {
-                    summary.post_condition = None;
-
-                    // Widen summary at call level 1 so that the level 0 call sees the widened values.
-                    if call_depth == 1 {
-                        summary.widen_side_effects();
-                    }
-                    self.block_visitor
-                        .bv
-                        .cv
-                        .summary_cache
-                        .set_summary_for_call_site(
-                            func_ref,
-                            &func_args,
-                            &self.initial_type_cache,
-                            summary.clone(),
-                        );
-                }
-                return Some(summary);
-            } else {
-                // Probably a statically unbounded self recursive call. Use an empty summary and let
-                // earlier calls do the joining and widening required.
-                let mut summary = Summary::default();
-                summary
-                    .side_effects
-                    .push((Path::new_result(), Rc::new(abstract_value::BOTTOM)));
-                summary.is_computed = true;
-                self.block_visitor
-                    .bv
-                    .cv
-                    .summary_cache
-                    .set_summary_for_call_site(
-                        func_ref,
-                        &func_args,
-                        &self.initial_type_cache,
-                        summary.clone(),
-                    );
+            if call_depth == 0 {
+                return Some(self.create_and_cache_function_summary(&func_args, &type_args));
+            }
+            // The callee is already being summarized further up the call stack, so the call is
+            // recursive.
+            if let (Some(function_id), true) = (func_ref.function_id, call_depth < 4) {
+                let key = CallSiteKey::new(func_args.clone(), type_args.clone(), function_id);
+                let summary = self.recursive_function_summary(func_ref, key, &func_args);
                 return Some(summary);
             }
+            // Probably a recursion through ever more specialized generic functions, which has
+            // no fixed point. Use an empty summary and let earlier calls do the joining and
+            // widening required.
+            let mut summary = Summary::default();
+            summary
+                .side_effects
+                .push((Path::new_result(), Rc::new(abstract_value::BOTTOM)));
+            summary.is_computed = true;
+            self.block_visitor
+                .bv
+                .cv
+                .summary_cache
+                .set_summary_for_call_site(
+                    func_ref,
+                    &func_args,
+                    &self.initial_type_cache,
+                    summary.clone(),
+                );
+            return Some(summary);
         }
         None
     }
 
+    /// Returns the summary of a foreign function that the registry models as pure, with a range
+    /// of integers for its result, or None if the callee has no such model. The result is an
+    /// unknown value that the post condition keeps in the range. The analysis stops with the
+    /// line of the model if a bound of the range is not a value of the type of the result.
+    fn foreign_model_summary(&mut self) -> Option<Summary> {
+        let tcx = self.block_visitor.bv.tcx;
+        let model = self
+            .block_visitor
+            .bv
+            .cv
+            .foreign_models
+            .model_for(tcx, self.callee_def_id)?;
+        let (min, max) = model.returns?;
+        let result_type = self
+            .type_visitor()
+            .get_rustc_place_type(&self.destination, self.block_visitor.bv.current_span);
+        if !result_type.is_integral() {
+            return None;
+        }
+        let expression_type = ExpressionType::from(result_type.kind());
+        let is_value_of_type =
+            |bound: i128| match (expression_type.min_value(), expression_type.max_value()) {
+                (ConstantDomain::I128(least), ConstantDomain::I128(greatest)) => {
+                    least <= bound && bound <= greatest
+                }
+                (ConstantDomain::U128(_), ConstantDomain::U128(greatest)) => {
+                    bound >= 0 && bound as u128 <= greatest
+                }
+                _ => false,
+            };
+        if !is_value_of_type(min) || !is_value_of_type(max) {
+            self.block_visitor.bv.cv.session.dcx().fatal(format!(
+                "{}: the return range of `{}` is not a range of {result_type}: {min}..={max}",
+                model.returns_location, model.function
+            ));
+        }
+        let result_path = Path::new_result();
+        let result = AbstractValue::make_typed_unknown(expression_type, result_path.clone());
+        // The bounds are values of the type, so they are the same values once they are
+        // converted to its bits
+        let min = self
+            .block_visitor
+            .get_int_const_val(min as u128, result_type);
+        let max = self
+            .block_visitor
+            .get_int_const_val(max as u128, result_type);
+        let mut summary = Summary::default();
+        summary.post_condition = Some(result.greater_or_equal(min).and(result.less_or_equal(max)));
+        summary.side_effects.push((result_path, result));
+        summary.is_computed = true;
+        Some(summary)
+    }
+
+    /// Returns the summary of a function that is called while its summary is being computed.
+    /// If the fixed point of the summary is being computed further up the call stack, the
+    /// summary of its current iteration is returned. Otherwise the fixed point is computed
+    /// here: the body is summarized with calls of the function that it reaches using the
+    /// summary of the previous iteration, starting from a summary that returns bottom, until
+    /// the summary no longer grows. The summaries are widened after a few iterations so that
+    /// the iteration ends, and the summary is incomplete if it still grows after the last one.
+    /// A call of a function that was already being summarized when the fixed point started
+    /// closes a cycle of mutually recursive functions. The function joins the cycle, its
+    /// summary starts from bottom as well, and the fixed point goes on until the summaries of
+    /// all of the functions of the cycle stop growing.
+    fn recursive_function_summary(
+        &mut self,
+        func_ref: &Rc<FunctionReference>,
+        key: CallSiteKey<'tcx>,
+        func_args: &Option<Rc<Vec<Rc<FunctionReference>>>>,
+    ) -> Summary {
+        let type_args = self.initial_type_cache.clone();
+        let mut bottom = Summary::default();
+        bottom
+            .side_effects
+            .push((Path::new_result(), Rc::new(abstract_value::BOTTOM)));
+        bottom.is_computed = true;
+        let callee_def_id = func_ref.def_id.unwrap_or(self.callee_def_id);
+        let active_functions = self
+            .block_visitor
+            .bv
+            .active_calls_map
+            .keys()
+            .copied()
+            .collect();
+        let cv = &mut self.block_visitor.bv.cv;
+        if let Some(index) = cv.recursive_summaries.iter().position(|r| r.key == key) {
+            let recursive = &cv.recursive_summaries[index];
+            if let Some(head) = recursive.cycle_head {
+                if recursive.iteration != cv.recursive_summaries[head].iteration {
+                    return self.cycle_member_summary(index, func_args);
+                }
+            }
+            let recursive = &mut cv.recursive_summaries[index];
+            recursive.uses += 1;
+            return recursive.summary.clone();
+        }
+        let cycle_head = cv
+            .recursive_summaries
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, r)| r.enclosing_functions.contains(&callee_def_id))
+            .map(|(index, r)| r.cycle_head.unwrap_or(index));
+        if let Some(head) = cycle_head {
+            // A summary cached for the function was computed without the other functions of
+            // the cycle, so it gives way to the summary of the fixed point of the cycle.
+            cv.summary_cache
+                .remove_summary_for_call_site(func_ref, func_args, &type_args);
+            cv.recursive_summaries.push(RecursiveSummary {
+                key,
+                func_ref: func_ref.clone(),
+                func_args: func_args.clone(),
+                type_args,
+                summary: bottom,
+                uses: 0,
+                enclosing_functions: HashSet::new(),
+                cycle_head: Some(head),
+                iteration: None,
+                grew: false,
+            });
+            let index = cv.recursive_summaries.len() - 1;
+            return self.cycle_member_summary(index, func_args);
+        }
+        let outer_uses = approximation_uses(&cv.recursive_summaries);
+        let head = cv.recursive_summaries.len();
+        cv.recursive_summaries.push(RecursiveSummary {
+            key,
+            func_ref: func_ref.clone(),
+            func_args: func_args.clone(),
+            type_args: type_args.clone(),
+            summary: bottom,
+            uses: 0,
+            enclosing_functions: active_functions,
+            cycle_head: None,
+            iteration: None,
+            grew: false,
+        });
+        let max_iterations = cv.options.k_limits.max_recursive_iterations;
+        let mut iteration = 0;
+        let summary = loop {
+            let recursive_summaries = &mut self.block_visitor.bv.cv.recursive_summaries;
+            recursive_summaries[head].iteration = Some(iteration);
+            for member in recursive_summaries[head + 1..].iter_mut() {
+                member.grew = false;
+            }
+            let uses_before = recursive_summaries[head].uses;
+            let next = self.create_and_cache_function_summary(func_args, &type_args);
+            let recursive_summaries = &mut self.block_visitor.bv.cv.recursive_summaries;
+            let has_members = recursive_summaries.len() > head + 1;
+            let members_grew = recursive_summaries[head + 1..].iter().any(|r| r.grew);
+            let current = &mut recursive_summaries[head];
+            if current.uses == uses_before && !has_members {
+                // The body did not reach a call of the function, so its summary is exact
+                break next;
+            }
+            if next.is_subset_of(&current.summary) && !members_grew {
+                break current.summary.clone();
+            }
+            iteration += 1;
+            let mut summary = current.summary.clone();
+            summary.join_iteration(&next);
+            if iteration >= k_limits::MAX_RECURSIVE_ITERATIONS_BEFORE_WIDENING {
+                summary.widen_side_effects();
+            }
+            if iteration >= max_iterations {
+                summary.is_incomplete = true;
+                break summary;
+            }
+            current.summary = summary;
+        };
+        let cv = &mut self.block_visitor.bv.cv;
+        let members = cv.recursive_summaries.split_off(head + 1);
+        cv.recursive_summaries.pop();
+        // A summary that depends on the summary of a function whose fixed point is still being
+        // computed is not final, so it is not cached
+        if approximation_uses(&cv.recursive_summaries) == outer_uses {
+            cv.summary_cache.set_summary_for_call_site(
+                func_ref,
+                func_args,
+                &self.initial_type_cache,
+                summary.clone(),
+            );
+            // The summaries of the other functions of the cycle are final as well
+            for member in members {
+                let mut member_summary = member.summary;
+                member_summary.is_incomplete |= summary.is_incomplete;
+                cv.summary_cache.set_summary_for_call_site(
+                    &member.func_ref,
+                    &member.func_args,
+                    &member.type_args,
+                    member_summary,
+                );
+            }
+        }
+        summary
+    }
+
+    /// Summarizes the body of the function of a cycle of recursive functions whose summary is
+    /// at the given position, once in every iteration of the fixed point of the cycle. Calls of
+    /// the function that are reached while its body is summarized use the summary of the
+    /// previous iteration. The summary joins the summaries of the iterations and is widened
+    /// like the summary of the function that drives the fixed point.
+    fn cycle_member_summary(
+        &mut self,
+        index: usize,
+        func_args: &Option<Rc<Vec<Rc<FunctionReference>>>>,
+    ) -> Summary {
+        let recursive_summaries = &mut self.block_visitor.bv.cv.recursive_summaries;
+        let head = recursive_summaries[index]
+            .cycle_head
+            .expect("a member of a cycle");
+        let iteration = recursive_summaries[head].iteration;
+        let member = &mut recursive_summaries[index];
+        member.iteration = iteration;
+        member.uses += 1;
+        let type_args = self.initial_type_cache.clone();
+        let next = self.create_and_cache_function_summary(func_args, &type_args);
+        let member = &mut self.block_visitor.bv.cv.recursive_summaries[index];
+        let mut summary = member.summary.clone();
+        summary.join_iteration(&next);
+        if iteration.unwrap_or(0) >= k_limits::MAX_RECURSIVE_ITERATIONS_BEFORE_WIDENING {
+            summary.widen_side_effects();
+        }
+        if !summary.approx_eq(&member.summary) {
+            member.grew = true;
+            member.summary = summary;
+        }
+        member.summary.clone()
+    }
+
     /// If this call is to an implementation of the std::clone::Clone::clone trait method
     /// then make sure any model fields and tag fields are copied to the result as well.
     /// If there is no MIR implementation available for the clone method, then fall back to a
@@ -468,6 +749,174 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
         self.use_entry_condition_as_exit_condition();
     }
 
+    /// Composes the interactions with other programs and the balance writes of the called
+    /// function, as given by its summary, with those of the calling body, so that the checkers
+    /// of the caller can find reentrancy that spans several functions.
+    #[logfn_inputs(TRACE)]
+    pub fn compose_external_effects(&mut self, summary: &Summary) {
+        if summary.suppresses("reentrancy") {
+            return;
+        }
+        let bv = &mut self.block_visitor.bv;
+        bv.contract_checkers.reentrancy.compose_external_effects(
+            bv.current_location.block,
+            bv.current_span,
+            &summary.external_effects,
+        );
+    }
+
+    /// If this call converts a value into a boxed trait object with From::from or Into::into, as
+    /// done by ? in functions that return Result<_, Box<dyn Error>>, and the conversion could not
+    /// be summarized, the result of the call becomes an opaque value and the analysis carries on.
+    /// The type of the converted value is erased anyway, and this keeps the condition under which
+    /// the error is returned, rather than turning the path of the error into a precondition.
+    /// Returns true if the call was handled this way.
+    #[logfn_inputs(TRACE)]
+    pub fn handled_as_type_erasing_conversion(&mut self, summary: &Summary) -> bool {
+        if summary.is_computed && !summary.is_incomplete {
+            return false;
+        }
+        let tcx = self.block_visitor.bv.tcx;
+        let trait_def_id = tcx.trait_of_item(self.callee_def_id).or_else(|| {
+            tcx.impl_of_method(self.callee_def_id)
+                .and_then(|impl_def_id| tcx.trait_id_of_impl(impl_def_id))
+        });
+        let is_conversion = trait_def_id.is_some_and(|def_id| {
+            tcx.is_diagnostic_item(rustc_span::sym::From, def_id)
+                || tcx.is_diagnostic_item(rustc_span::sym::Into, def_id)
+        });
+        if !is_conversion {
+            return false;
+        }
+        let target_type = self
+            .type_visitor()
+            .get_rustc_place_type(&self.destination, self.block_visitor.bv.current_span);
+        if !target_type.boxed_ty().is_some_and(|ty| ty.is_trait()) {
+            return false;
+        }
+        self.handle_abstract_value();
+        true
+    }
+
+    /// Records that the findings of the checker named by the argument of suppress! are dropped in
+    /// the function being analyzed. The name is recorded whenever the call is visited, since the
+    /// contract checkers report their findings at the end of the fixed point pass, before the pass
+    /// that checks for errors, which reports names that are not those of checkers.
+    #[logfn_inputs(TRACE)]
+    fn handle_suppress(&mut self) {
+        let name = self.coerce_to_string(&self.actual_args[0].0.clone());
+        if let Some(checker) = CHECKERS.iter().find(|c| c.name == name.as_ref()) {
+            self.block_visitor
+                .bv
+                .suppressed_checkers
+                .insert(checker.name);
+        } else if self.block_visitor.bv.check_for_errors {
+            let span = self.block_visitor.bv.current_span.source_callsite();
+            let warning = self.block_visitor.bv.cv.session.dcx().struct_span_warn(
+                span,
+                format!("suppress! names no checker: `{name}`, see --list-checks for the names"),
+            );
+            self.block_visitor.bv.emit_diagnostic(warning);
+        }
+    }
+
+    // Remembers the condition of the current assume! call, unless it is known to be true, so that
+    // check_reachable can point at it.
+    fn record_assumption(&mut self) {
+        let condition = self.actual_args[0].1.clone();
+        if condition.as_bool_if_known().unwrap_or(false) {
+            return;
+        }
+        let bv = &mut self.block_visitor.bv;
+        let span = bv.current_span.source_callsite();
+        bv.assumptions
+            .push((bv.current_location.block, span, condition));
+    }
+
+    // Warns if the entry condition of the current verify_reachable! call is provably false, with a
+    // note at the assume! call that contradicts the branch to the call, if it can be found.
+    fn check_reachable(&mut self) {
+        let bv = &mut self.block_visitor.bv;
+        let (_, entry_cond_as_bool) =
+            bv.check_condition_value_and_reachability(&Rc::new(abstract_value::TRUE));
+        if entry_cond_as_bool != Some(false) {
+            return;
+        }
+        let span = bv.current_span.source_callsite();
+        let conflicting_assumption = bv.conflicting_assumption(bv.current_location.block);
+        let mut warning = bv.cv.session.dcx().struct_span_warn(
+            span,
+            "annotated-reachable statement is unreachable under current assumptions",
+        );
+        if let Some(assumption_span) = conflicting_assumption {
+            warning.span_note(
+                assumption_span,
+                "this assumption contradicts the condition of the branch to the statement",
+            );
+        }
+        bv.emit_diagnostic(warning);
+    }
+
+    // Records the loop invariant of the current call as possibly not preserved if it does not
+    // provably hold in the first iteration of the fixed point of its loop, which starts from the
+    // state on entry to the loop, or if it provably does not hold in a later iteration, which
+    // starts from the states at the ends of the earlier iterations. Once the state of the loop is
+    // widened it is too coarse to prove the invariant, which is why it is then only assumed.
+    fn check_loop_invariant(&mut self) {
+        let bv = &mut self.block_visitor.bv;
+        let block = bv.current_location.block;
+        let Some((_, iteration)) = bv.loop_invariant_iterations.get(&block).copied() else {
+            return;
+        };
+        let (cond_as_bool, entry_cond_as_bool) =
+            bv.check_condition_value_and_reachability(&self.actual_args[0].1);
+        if entry_cond_as_bool == Some(false) {
+            return;
+        }
+        let holds = if iteration == 1 {
+            cond_as_bool == Some(true)
+        } else {
+            cond_as_bool != Some(false)
+        };
+        if !holds {
+            bv.unpreserved_loop_invariants.insert(block);
+        }
+    }
+
+    // Warns about the loop invariant of the current call if the fixed point of its loop found
+    // that it is possibly not preserved, with a note at the loop. An invariant that is not in a
+    // loop must hold where it is.
+    fn report_unpreserved_loop_invariant(&mut self) {
+        let bv = &mut self.block_visitor.bv;
+        let block = bv.current_location.block;
+        let anchor = match bv.loop_invariant_iterations.get(&block) {
+            Some((anchor, _)) => {
+                if !bv.unpreserved_loop_invariants.contains(&block) {
+                    return;
+                }
+                Some(*anchor)
+            }
+            None => {
+                let (cond_as_bool, entry_cond_as_bool) =
+                    bv.check_condition_value_and_reachability(&self.actual_args[0].1);
+                if cond_as_bool == Some(true) || entry_cond_as_bool == Some(false) {
+                    return;
+                }
+                None
+            }
+        };
+        let span = bv.current_span.source_callsite();
+        let mut warning = bv
+            .cv
+            .session
+            .dcx()
+            .struct_span_warn(span, "loop invariant possibly not preserved");
+        if let Some(anchor) = anchor {
+            warning.span_note(bv.block_span(anchor), "in the iterations of this loop");
+        }
+        bv.emit_diagnostic(warning);
+    }
+
     /// If the current call is to a well known function for which we don't have a cached summary,
     /// this function will update the environment as appropriate and return true. If the return
     /// result is false, just carry on with the normal logic.
@@ -496,8 +945,14 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
             }
             KnownNames::MiraiAssume => {
                 checked_assume!(self.actual_args.len() == 1);
+                if self.block_visitor.bv.cv.shipped_semantics {
+                    // Without cfg(hepha) the assumption is not there at all
+                    self.use_entry_condition_as_exit_condition();
+                    return true;
+                }
                 if self.block_visitor.bv.check_for_errors {
                     self.report_calls_to_special_functions();
+                    self.record_assumption();
                 }
                 self.handle_assume();
                 return true;
@@ -508,11 +963,45 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                 self.block_visitor.bv.assume_preconditions_of_next_call = true;
                 return true;
             }
+            KnownNames::MiraiBoundVariable => {
+                checked_assume!(self.actual_args.is_empty());
+                // The variable of forall! is not a value of the function, but the variable that
+                // the quantified expression binds.
+                let target_path = self.block_visitor.visit_rh_place(&self.destination);
+                let var_type = self
+                    .type_visitor()
+                    .get_place_type(&self.destination, self.block_visitor.bv.current_span);
+                let bound_variable = Path::new_bound_variable(self.destination.local.as_usize());
+                let value = AbstractValue::make_typed_unknown(var_type, bound_variable);
+                self.block_visitor.bv.update_value_at(target_path, value);
+                self.use_entry_condition_as_exit_condition();
+                return true;
+            }
             KnownNames::MiraiDoesNotHaveTag => {
                 checked_assume!(self.actual_args.len() == 1);
                 self.handle_check_tag(false);
                 return true;
             }
+            KnownNames::MiraiForall => {
+                checked_assume!(self.actual_args.len() == 2);
+                let target_path = self.block_visitor.visit_rh_place(&self.destination);
+                let value = match &self.actual_args[0].1.expression {
+                    Expression::Variable { path, var_type } if path.is_bound_variable() => {
+                        AbstractValue::make_quantified(
+                            path.clone(),
+                            *var_type,
+                            self.actual_args[1].1.clone(),
+                        )
+                    }
+                    _ => {
+                        // The variable is not one that forall! binds, so nothing is known
+                        AbstractValue::make_typed_unknown(ExpressionType::Bool, target_path.clone())
+                    }
+                };
+                self.block_visitor.bv.update_value_at(target_path, value);
+                self.use_entry_condition_as_exit_condition();
+                return true;
+            }
             KnownNames::MiraiGetModelField => {
                 self.handle_get_model_field();
                 return true;
@@ -522,6 +1011,41 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                 self.handle_check_tag(true);
                 return true;
             }
+            KnownNames::MiraiLoopInvariant => {
+                checked_assume!(self.actual_args.len() == 1);
+                if self.block_visitor.bv.cv.shipped_semantics {
+                    // Without cfg(hepha) the invariant is not there at all
+                    self.use_entry_condition_as_exit_condition();
+                    return true;
+                }
+                if self.block_visitor.bv.check_for_errors {
+                    self.report_unpreserved_loop_invariant();
+                } else {
+                    self.check_loop_invariant();
+                }
+                self.handle_assume();
+                return true;
+            }
+            KnownNames::MiraiOld => {
+                checked_assume!(self.actual_args.len() == 1);
+                // In a postcondition, the block visitor has already replaced the argument with
+                // its value at entry
+                if self.block_visitor.bv.check_for_errors
+                    && !self.block_visitor.is_in_postcondition()
+                {
+                    let bv = &mut self.block_visitor.bv;
+                    let warning = bv.cv.session.dcx().struct_span_warn(
+                        bv.current_span.source_callsite(),
+                        "old! only refers to the value at entry in the condition of a postcondition",
+                    );
+                    bv.emit_diagnostic(warning);
+                }
+                let target_path = self.block_visitor.visit_lh_place(&self.destination);
+                let value = self.actual_args[0].1.clone();
+                self.block_visitor.bv.update_value_at(target_path, value);
+                self.use_entry_condition_as_exit_condition();
+                return true;
+            }
             KnownNames::MiraiPostcondition => {
                 checked_assume!(self.actual_args.len() == 3);
                 if self.block_visitor.bv.check_for_errors {
@@ -540,6 +1064,11 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                 self.handle_assume();
                 return true;
             }
+            KnownNames::MiraiRemoveTag => {
+                checked_assume!(self.actual_args.len() == 1);
+                self.handle_remove_tag();
+                return true;
+            }
             KnownNames::MiraiSetModelField => {
                 self.handle_set_model_field();
                 return true;
@@ -560,6 +1089,12 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                 self.use_entry_condition_as_exit_condition();
                 return true;
             }
+            KnownNames::MiraiSuppress => {
+                checked_assume!(self.actual_args.len() == 1);
+                self.handle_suppress();
+                self.use_entry_condition_as_exit_condition();
+                return true;
+            }
             KnownNames::MiraiVerify => {
                 checked_assume!(self.actual_args.len() == 2);
                 if self.block_visitor.bv.check_for_errors {
@@ -568,11 +1103,61 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                 self.handle_assume();
                 return true;
             }
+            KnownNames::MiraiVerifyReachable => {
+                checked_assume!(self.actual_args.is_empty());
+                if self.block_visitor.bv.check_for_errors {
+                    self.check_reachable();
+                }
+                self.use_entry_condition_as_exit_condition();
+                return true;
+            }
             KnownNames::RustDealloc => {
                 self.handle_rust_dealloc();
                 self.use_entry_condition_as_exit_condition();
                 return true;
             }
+            KnownNames::SolanaProgramGetReturnData => {
+                checked_assume!(self.actual_args.is_empty());
+                return self.handled_get_return_data();
+            }
+            KnownNames::SolanaProgramInvoke => {
+                checked_assume!(!self.actual_args.is_empty());
+                self.handle_invoke();
+                return true;
+            }
+            KnownNames::SolanaProgramSetReturnData => {
+                self.handle_set_return_data();
+                return false;
+            }
+            KnownNames::SolanaRentMinimumBalance => {
+                checked_assume!(self.actual_args.len() == 2);
+                self.handle_rent_minimum_balance();
+                return true;
+            }
+            KnownNames::StdBorrowCowDeref => {
+                checked_assume!(self.actual_args.len() == 1);
+                return self.handled_cow_deref();
+            }
+            KnownNames::StdBorrowCowToMut => {
+                checked_assume!(self.actual_args.len() == 1);
+                return self.handled_cow_to_mut();
+            }
+            KnownNames::StdCollectionsHashMapEntry => {
+                checked_assume!(self.actual_args.len() == 2);
+                return self.handled_hash_map_entry();
+            }
+            KnownNames::StdCollectionsHashMapEntryOrInsert => {
+                checked_assume!(self.actual_args.len() == 2);
+                return self.handled_hash_map_or_insert();
+            }
+            KnownNames::StdCollectionsHashMapGet | KnownNames::StdCollectionsHashMapGetMut => {
+                checked_assume!(self.actual_args.len() == 2);
+                return self.handled_hash_map_get();
+            }
+            KnownNames::StdCollectionsHashMapInsert => {
+                checked_assume!(self.actual_args.len() == 3);
+                return self.handled_hash_map_insert();
+            }
             KnownNames::StdFutureFromGenerator => {
                 checked_assume!(self.actual_args.len() == 1);
                 let generator_fun_val = self.actual_args[0].1.clone();
@@ -623,6 +1208,26 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                 self.handle_mem_replace();
                 return true;
             }
+            KnownNames::StdOpsTryBranch => {
+                checked_assume!(self.actual_args.len() == 1);
+                return self.handled_try_branch();
+            }
+            KnownNames::StdOptionCopied => {
+                checked_assume!(self.actual_args.len() == 1);
+                return self.handled_option_copied();
+            }
+            KnownNames::StdOptionMap => {
+                checked_assume!(self.actual_args.len() == 2);
+                return self.handled_option_map();
+            }
+            KnownNames::StdOptionOkOr => {
+                checked_assume!(self.actual_args.len() == 2);
+                return self.handled_option_ok_or();
+            }
+            KnownNames::StdOptionUnwrapOr => {
+                checked_assume!(self.actual_args.len() == 2);
+                return self.handled_option_unwrap_or();
+            }
             KnownNames::StdPtrSwapNonOverlapping => {
                 self.handle_swap_non_overlapping();
                 return true;
@@ -1252,49 +1857,72 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
             self.function_constant_args
         );
         // Get the function to call (it is either a function pointer or a closure)
-        let callee = self.actual_args[0].1.clone();
-        let callee_func_ref = self.block_visitor.get_func_ref(&callee);
+        let callee_arg = self.actual_args[0].clone();
+        let callee_ty = self.actual_argument_types[0];
 
         // Get the path of the tuple containing the arguments.
         let callee_arg_array_path = self.actual_args[1].0.clone();
+
+        // Unpack the type of the second argument, which should be a tuple.
+        checked_assume!(self.actual_argument_types.len() == 2);
+        let actual_argument_types: Vec<Ty<'tcx>> =
+            if let TyKind::Tuple(tuple_types) = self.actual_argument_types[1].kind() {
+                tuple_types.iter().collect()
+            } else {
+                assume_unreachable!("expected second type argument to be a tuple type");
+            };
+
+        // Unpack the second argument, which should be a tuple
+        let actual_args: Vec<(Rc<Path>, Rc<AbstractValue>)> = actual_argument_types
+            .iter()
+            .enumerate()
+            .map(|(i, t)| {
+                let arg_path = Path::new_field(callee_arg_array_path.clone(), i)
+                    .canonicalize(&self.block_visitor.bv.current_environment);
+                let arg_val = self
+                    .block_visitor
+                    .bv
+                    .lookup_path_and_refine_result(arg_path.clone(), *t);
+                (arg_path, arg_val)
+            })
+            .collect();
+
+        let callee_by_value = self.callee_known_name == KnownNames::StdOpsFunctionFnOnceCallOnce;
+        self.call_function_value(
+            callee_arg,
+            callee_ty,
+            actual_args,
+            actual_argument_types,
+            callee_by_value,
+            self.destination,
+        );
+    }
+
+    /// Calls the function pointer or closure that is the value of callee_arg with the given
+    /// arguments, which do not include the closure itself, and stores the result in destination.
+    /// If callee_by_value is true, the callee is consumed by the call, as with FnOnce::call_once.
+    fn call_function_value(
+        &mut self,
+        callee_arg: (Rc<Path>, Rc<AbstractValue>),
+        callee_ty: Ty<'tcx>,
+        mut actual_args: Vec<(Rc<Path>, Rc<AbstractValue>)>,
+        mut actual_argument_types: Vec<Ty<'tcx>>,
+        callee_by_value: bool,
+        destination: mir::Place<'tcx>,
+    ) {
+        let callee = callee_arg.1.clone();
+        let callee_func_ref = self.block_visitor.get_func_ref(&callee);
         if let Some(func_ref) = &callee_func_ref {
             let def_id = func_ref.def_id.expect("defined when used here");
             let func_const = ConstantDomain::Function(func_ref.clone());
 
-            // Unpack the type of the second argument, which should be a tuple.
-            checked_assume!(self.actual_argument_types.len() == 2);
-            let mut actual_argument_types: Vec<Ty<'tcx>> =
-                if let TyKind::Tuple(tuple_types) = self.actual_argument_types[1].kind() {
-                    tuple_types.iter().collect()
-                } else {
-                    assume_unreachable!("expected second type argument to be a tuple type");
-                };
-
-            // Unpack the second argument, which should be a tuple
-            let mut actual_args: Vec<(Rc<Path>, Rc<AbstractValue>)> = actual_argument_types
-                .iter()
-                .enumerate()
-                .map(|(i, t)| {
-                    let arg_path = Path::new_field(callee_arg_array_path.clone(), i)
-                        .canonicalize(&self.block_visitor.bv.current_environment);
-                    let arg_val = self
-                        .block_visitor
-                        .bv
-                        .lookup_path_and_refine_result(arg_path.clone(), *t);
-                    (arg_path, arg_val)
-                })
-                .collect();
-
             // Prepend the callee closure/generator/function to the unpacked arguments vector
             // if the called function actually expects it.
             let tcx = self.block_visitor.bv.tcx;
-            let callee_ty = self.actual_argument_types[0];
             if !callee_ty.is_fn() || tcx.is_closure_like(def_id) {
-                actual_args.insert(0, self.actual_args[0].clone());
+                actual_args.insert(0, callee_arg.clone());
                 actual_argument_types.insert(0, callee_ty);
-                if self.callee_known_name == KnownNames::StdOpsFunctionFnOnceCallOnce
-                    && self.block_visitor.bv.tcx.is_mir_available(def_id)
-                {
+                if callee_by_value && self.block_visitor.bv.tcx.is_mir_available(def_id) {
                     // call_once consumes it's callee argument. If the callee does not,
                     // we have to provide it with a reference.
                     // Sadly, the easiest way to get hold of the type of the first parameter
@@ -1304,7 +1932,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                     let mir = tcx.optimized_mir(def_id);
                     if let Some(decl) = mir.local_decls.get(mir::Local::from(1usize)) {
                         if decl.ty.is_ref() {
-                            let closure_path = self.actual_args[0].0.clone();
+                            let closure_path = callee_arg.0.clone();
                             let closure_reference = AbstractValue::make_reference(closure_path);
                             actual_args[0] = (
                                 Path::get_as_path(closure_reference.clone()),
@@ -1373,7 +2001,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
             indirect_call_visitor.function_constant_args = &function_constant_args;
             indirect_call_visitor.callee_fun_val = callee.clone();
             indirect_call_visitor.callee_known_name = KnownNames::None;
-            indirect_call_visitor.destination = self.destination;
+            indirect_call_visitor.destination = destination;
             indirect_call_visitor.target = self.target;
             let summary = indirect_call_visitor.get_function_summary();
             if let Some(summary) = summary {
@@ -1402,7 +2030,8 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
             .insert(callee.clone())
         {
             debug!("unknown callee {:?}", callee);
-            self.block_visitor.report_missing_summary();
+            self.block_visitor
+                .report_missing_summary(Some(self.callee_def_id));
         }
     }
 
@@ -1433,17 +2062,53 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
         }
     }
 
-    /// Attach a tag to the first and only value in actual_args.
-    /// The tag type is indicated by a generic argument.
+    /// Rent::minimum_balance(&self, data_len) is modeled with the default rent of the clusters, so
+    /// that the rent-exempt minimum of an account is known as a function of the length of its data.
     #[logfn_inputs(TRACE)]
-    fn handle_add_tag(&mut self) {
-        precondition!(self.actual_args.len() == 1);
-
-        if let Some(tag) = self.extract_tag_kind_and_propagation_set() {
-            let (source_path, source_rustc_type) = self.deref_tag_source();
-            trace!("MiraiAddTag: tagging {:?} with {:?}", source_path, tag);
-
-            // Check if the tagged value has a pointer type (e.g., a reference).
+    fn handle_rent_minimum_balance(&mut self) {
+        precondition!(self.actual_args.len() == 2);
+        if let Some(target) = &self.target {
+            let usize_ty = self.block_visitor.bv.tcx.types.usize;
+            let overhead = self
+                .block_visitor
+                .get_int_const_val(contract_errors::ACCOUNT_STORAGE_OVERHEAD, usize_ty);
+            let lamports_per_byte = self
+                .block_visitor
+                .get_int_const_val(contract_errors::RENT_EXEMPT_LAMPORTS_PER_BYTE, usize_ty);
+            let data_len = self.actual_args[1].1.clone();
+            let minimum_balance = data_len
+                .addition(overhead)
+                .multiply(lamports_per_byte)
+                .cast(ExpressionType::U64);
+            let path = self.block_visitor.visit_rh_place(&self.destination);
+            self.block_visitor.bv.update_value_at(path, minimum_balance);
+            let exit_condition = self
+                .block_visitor
+                .bv
+                .current_environment
+                .entry_condition
+                .clone();
+            self.block_visitor
+                .bv
+                .current_environment
+                .exit_conditions
+                .insert_mut(*target, exit_condition);
+        } else {
+            assume_unreachable!();
+        }
+    }
+
+    /// Attach a tag to the first and only value in actual_args.
+    /// The tag type is indicated by a generic argument.
+    #[logfn_inputs(TRACE)]
+    fn handle_add_tag(&mut self) {
+        precondition!(self.actual_args.len() == 1);
+
+        if let Some(tag) = self.extract_tag_kind_and_propagation_set() {
+            let (source_path, source_rustc_type) = self.deref_tag_source();
+            trace!("MiraiAddTag: tagging {:?} with {:?}", source_path, tag);
+
+            // Check if the tagged value has a pointer type (e.g., a reference).
             // Emit an warning message if so.
             if self.block_visitor.bv.check_for_errors && source_rustc_type.is_any_ptr() {
                 let warning = self.block_visitor.bv.cv.session.dcx().struct_span_warn(
@@ -1463,6 +2128,35 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
         self.use_entry_condition_as_exit_condition();
     }
 
+    /// Remove a tag from the first and only value in actual_args.
+    /// The tag type is indicated by a generic argument.
+    #[logfn_inputs(TRACE)]
+    fn handle_remove_tag(&mut self) {
+        precondition!(self.actual_args.len() == 1);
+
+        if let Some(tag) = self.extract_tag_kind_and_propagation_set() {
+            let (source_path, source_rustc_type) = self.deref_tag_source();
+            trace!("MiraiRemoveTag: untagging {:?} with {:?}", source_path, tag);
+
+            if self.block_visitor.bv.check_for_errors && source_rustc_type.is_any_ptr() {
+                let warning = self.block_visitor.bv.cv.session.dcx().struct_span_warn(
+                    self.block_visitor.bv.current_span,
+                    "the macro remove_tag! expects its argument to be a reference to a non-reference value",
+                );
+                self.block_visitor.bv.emit_diagnostic(warning);
+            }
+
+            self.block_visitor.bv.detach_tag_from_value_at_path(
+                tag,
+                source_path,
+                source_rustc_type,
+            );
+        }
+
+        // Update exit conditions.
+        self.use_entry_condition_as_exit_condition();
+    }
+
     /// Returns a canonicalized dereferenced path to the first argument, along with the dereferenced
     /// rustc type. If the dereferenced argument is a slice pointer, or a box, then return the
     /// thin pointer path to the dereferenced value. In the case of a box, the argument path will
@@ -1593,8 +2287,18 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
             // the tag (when checking_presence = false).
             if tag.is_propagated_by(TagPropagation::SubComponent) {
                 let mut path_prefix = &tag_field_path;
-                while let PathEnum::QualifiedPath { qualifier, .. } = &path_prefix.value {
+                while let PathEnum::QualifiedPath {
+                    qualifier,
+                    selector,
+                    ..
+                } = &path_prefix.value
+                {
                     debug!("qualifier {:?}", qualifier);
+                    // The tags of this prefix, and of the ones that contain it, are blocked by
+                    // the selector.
+                    if !tag.is_propagated_by_selector(selector) {
+                        break;
+                    }
                     path_prefix = qualifier;
 
                     let path_prefix_rustc_type = self
@@ -1645,7 +2349,10 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
             if tag.is_propagated_by(TagPropagation::SuperComponent) {
                 let root = source_path.get_path_root();
                 let value_map = self.block_visitor.bv.current_environment.value_map.clone();
-                for (_, value) in value_map.iter().filter(|(p, _)| p.is_rooted_by(root)) {
+                for (_, value) in value_map
+                    .iter()
+                    .filter(|(p, _)| p.is_rooted_by(root) && tag.is_propagated_between(root, p))
+                {
                     let mut value = value.clone();
                     if let Expression::Reference(p) = &value.expression {
                         if let PathEnum::HeapBlock { .. } = &p.value {
@@ -1797,6 +2504,166 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
         }
     }
 
+    /// Returns true if the result of the call carries the built-in tag of predictable entropy,
+    /// which is the case if the result is the clock, the slot hashes or the system time, if the
+    /// callee is in another crate and an argument carries the tag, such as a random number
+    /// generator with a predictable seed, or if the callee is a random number generator that is
+    /// seeded implicitly or with a constant. The tag is attached by the caller, together with the
+    /// other built-in tags of the result.
+    #[logfn_inputs(TRACE)]
+    pub fn result_is_predictable_entropy(&mut self) -> bool {
+        let tag = Tag::PREDICTABLE_ENTROPY;
+        let tcx = self.block_visitor.bv.tcx;
+        let destination_type = self
+            .type_visitor()
+            .get_rustc_place_type(&self.destination, self.block_visitor.bv.current_span);
+        contract_errors::is_predictable_entropy_type(tcx, destination_type)
+            || (!self.callee_def_id.is_local()
+                && (0..self.actual_args.len()).any(|ordinal| self.argument_has_tag(ordinal, tag)))
+            || (contract_errors::is_random_number_generator(tcx, self.callee_def_id)
+                && self.has_predictable_seed())
+    }
+
+    /// Returns true if the argument with the given ordinal, or the value that it refers to, is
+    /// known to carry the given tag.
+    #[logfn_inputs(TRACE)]
+    pub fn argument_has_tag(&mut self, ordinal: usize, tag: Tag) -> bool {
+        let (path, value) = self.actual_args[ordinal].clone();
+        let environment = &self.block_visitor.bv.current_environment;
+        if value.has_tag(&tag).as_bool_if_known().unwrap_or(false)
+            || value.expression.has_tagged_subcomponent(&tag, environment)
+        {
+            return true;
+        }
+        let argument_type = self.actual_argument_types[ordinal];
+        if !argument_type.is_ref() {
+            return false;
+        }
+        let target_type = self.type_visitor().get_dereferenced_type(argument_type);
+        let target_path = Path::new_deref(path, ExpressionType::from(target_type.kind()))
+            .canonicalize(&self.block_visitor.bv.current_environment);
+        let (_, tag_field_value) =
+            self.get_value_to_check_for_tag(tag, true, target_path.clone(), target_type);
+        tag_field_value
+            .has_tag(&tag)
+            .as_bool_if_known()
+            .unwrap_or(false)
+            || target_path.has_tagged_subcomponent(&tag, &self.block_visitor.bv.current_environment)
+    }
+
+    /// Records the deserialization of a prefix of the instruction data by the call, with the length
+    /// of the prefix, if known, and the serialized size of the type of the value that it returns
+    /// in a Result. The bytes are passed as a slice, or as a mutable reference to a slice that
+    /// the deserialization advances past the bytes that it reads.
+    pub fn record_deserialization(&mut self, format: SerializationFormat) {
+        let Some((value_type, bytes)) = self.deserialized_type_and_bytes() else {
+            return;
+        };
+        let prefix_length = match &self.block_visitor.slice_length(bytes).expression {
+            Expression::CompileTimeConstant(ConstantDomain::U128(length)) => Some(*length),
+            _ => None,
+        };
+        let size = self
+            .type_visitor()
+            .fixed_serialized_size(value_type, format.variant_index_size());
+        let type_name = self.deserialized_type_name(value_type);
+        let span = self.block_visitor.bv.current_span;
+        self.block_visitor
+            .bv
+            .contract_checkers
+            .deserialization_offsets
+            .record_deserialization(span, type_name, prefix_length, size);
+    }
+
+    /// Records the deserialization of a value by BorshDeserialize::try_from_slice, with the size
+    /// of the shortest serialization of the value, if the slice that it is deserialized from is
+    /// not known to be that long under the entry condition.
+    pub fn check_deserialized_length(&mut self) {
+        let Some((value_type, bytes)) = self.deserialized_type_and_bytes() else {
+            return;
+        };
+        let length = self.block_visitor.slice_length(bytes);
+        let constant_length = match &length.expression {
+            Expression::CompileTimeConstant(ConstantDomain::U128(length)) => Some(*length),
+            _ => None,
+        };
+        let size = self
+            .type_visitor()
+            .min_serialized_size(value_type, SerializationFormat::Borsh.variant_index_size());
+        let long_enough = length.greater_or_equal(Rc::new(size.into()));
+        let (long_enough_as_bool, entry_cond_as_bool) = self
+            .block_visitor
+            .bv
+            .check_condition_value_and_reachability(&long_enough);
+        if entry_cond_as_bool == Some(false) {
+            return;
+        }
+        let short_of = (size > 0 && long_enough_as_bool != Some(true)).then_some(size);
+        let type_name = self.deserialized_type_name(value_type);
+        let span = self.block_visitor.bv.current_span;
+        let destination = self.destination.local;
+        self.block_visitor
+            .bv
+            .contract_checkers
+            .unvalidated_deserialization
+            .record_deserialization(span, type_name, destination, constant_length, short_of);
+    }
+
+    // Returns the type of the value that the call deserializes, which it returns in a Result, and
+    // the slice of bytes that it is deserialized from
+    fn deserialized_type_and_bytes(&mut self) -> Option<(Ty<'tcx>, Rc<AbstractValue>)> {
+        let result_type = self
+            .type_visitor()
+            .get_rustc_place_type(&self.destination, self.block_visitor.bv.current_span);
+        let TyKind::Adt(_, args) = result_type.kind() else {
+            return None;
+        };
+        let value_type = args.types().next()?;
+        let (mut bytes_path, mut bytes_type) =
+            (self.actual_args[0].0.clone(), self.actual_argument_types[0]);
+        if let TyKind::Ref(_, target_type, _) = bytes_type.kind() {
+            if target_type.is_ref() {
+                bytes_path = Path::new_deref(bytes_path, ExpressionType::ThinPointer)
+                    .canonicalize(&self.block_visitor.bv.current_environment);
+                bytes_type = *target_type;
+            }
+        }
+        let bytes = self
+            .block_visitor
+            .bv
+            .lookup_path_and_refine_result(bytes_path, bytes_type);
+        Some((value_type, bytes))
+    }
+
+    // Returns the name of the deserialized type, which is the name of the item for a struct or an
+    // enum
+    fn deserialized_type_name(&self, value_type: Ty<'tcx>) -> String {
+        match value_type.kind() {
+            TyKind::Adt(def, _) => self.block_visitor.bv.tcx.item_name(def.did()).to_string(),
+            _ => format!("{value_type:?}"),
+        }
+    }
+
+    // True if no argument of a call of a random number generator seeds it with a value that may
+    // be unpredictable, which is another generator, an integer that is not a constant, or bytes.
+    // The range that a value is drawn from is not a seed.
+    fn has_predictable_seed(&self) -> bool {
+        let tcx = self.block_visitor.bv.tcx;
+        self.actual_argument_types
+            .iter()
+            .zip(self.actual_args.iter())
+            .all(
+                |(ty, (_, value))| match self.type_visitor().get_dereferenced_type(*ty).kind() {
+                    TyKind::Adt(def, _) => {
+                        !contract_errors::is_random_number_generator(tcx, def.did())
+                    }
+                    TyKind::Int(..) | TyKind::Uint(..) => value.is_compile_time_constant(),
+                    TyKind::Array(..) | TyKind::Slice(..) => false,
+                    _ => true,
+                },
+            )
+    }
+
     /// Update the state so that the call result is the value of the model field (or the default
     /// value if there is no field).
     #[logfn_inputs(TRACE)]
@@ -1873,124 +2740,926 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
         self.use_entry_condition_as_exit_condition();
     }
 
-    fn handle_post_condition(&mut self) {
-        precondition!(self.actual_args.len() == 3);
-        let condition = self.actual_args[0].1.clone();
-        let exit_condition = self
+    fn handle_post_condition(&mut self) {
+        precondition!(self.actual_args.len() == 3);
+        let condition = self.actual_args[0].1.clone();
+        let exit_condition = self
+            .block_visitor
+            .bv
+            .current_environment
+            .entry_condition
+            .and(condition);
+        if let Some(target) = &self.target {
+            self.block_visitor
+                .bv
+                .current_environment
+                .exit_conditions
+                .insert_mut(*target, exit_condition);
+        } else {
+            assume_unreachable!();
+        }
+    }
+
+    /// It is bad style for a precondition to be reached conditionally, since well, that condition
+    /// should be part of the precondition.
+    #[logfn_inputs(TRACE)]
+    fn handle_precondition_start(&mut self) {
+        if self.block_visitor.bv.check_for_errors
+            && self.block_visitor.bv.check_for_unconditional_precondition
+            && !self
+                .block_visitor
+                .bv
+                .current_environment
+                .entry_condition
+                .as_bool_if_known()
+                .unwrap_or(false)
+        {
+            let span = self.block_visitor.bv.current_span;
+            let warning = self
+                .block_visitor
+                .bv
+                .cv
+                .session
+                .dcx()
+                .struct_span_warn(span, "preconditions should be reached unconditionally");
+            self.block_visitor.bv.emit_diagnostic(warning);
+            self.block_visitor.bv.check_for_unconditional_precondition = false;
+        }
+        let exit_condition = self
+            .block_visitor
+            .bv
+            .current_environment
+            .entry_condition
+            .clone();
+        if let Some(target) = &self.target {
+            self.block_visitor
+                .bv
+                .current_environment
+                .exit_conditions
+                .insert_mut(*target, exit_condition);
+        } else {
+            assume_unreachable!();
+        }
+    }
+
+    /// Adds the first and only value in actual_args to the current list of preconditions.
+    /// No check is performed, since we get to assume the caller has verified this condition.
+    #[logfn_inputs(TRACE)]
+    fn handle_precondition(&mut self) {
+        precondition!(self.actual_args.len() == 2);
+        if self.block_visitor.bv.check_for_errors {
+            let condition = self.actual_args[0].1.clone();
+            //todo: give diagnostic if the condition contains a local variable.
+            let message = self.coerce_to_string(&self.actual_args[1].0.clone());
+            let precondition = Precondition {
+                condition,
+                message,
+                provenance: None,
+                spans: vec![self.block_visitor.bv.current_span],
+            };
+            self.block_visitor.bv.preconditions.push(precondition);
+        }
+    }
+
+    /// Update the state to reflect the assignment of the model field.
+    #[logfn_inputs(TRACE)]
+    fn handle_set_model_field(&mut self) {
+        checked_assume!(self.actual_args.len() == 3);
+
+        let mut qualifier = self.actual_args[0].0.clone();
+        if matches!(&self.actual_argument_types[0].kind(), TyKind::Ref { .. }) {
+            let target_type = ExpressionType::from(
+                self.type_visitor()
+                    .get_dereferenced_type(self.actual_argument_types[0])
+                    .kind(),
+            );
+            qualifier = Path::new_deref(qualifier, target_type);
+        }
+        let field_name = self.coerce_to_string(&self.actual_args[1].0.clone());
+        let target_path = Path::new_model_field(qualifier, field_name)
+            .canonicalize(&self.block_visitor.bv.current_environment);
+        let source_path = self.actual_args[2].0.clone();
+        let target_type = self.actual_argument_types[2];
+        self.block_visitor
+            .bv
+            .copy_or_move_elements(target_path, source_path, target_type, true);
+        if let Some(target) = &self.target {
+            let exit_condition = self
+                .block_visitor
+                .bv
+                .current_environment
+                .entry_condition
+                .clone();
+            self.block_visitor
+                .bv
+                .current_environment
+                .exit_conditions
+                .insert_mut(*target, exit_condition);
+        } else {
+            assume_unreachable!();
+        }
+    }
+
+    /// Returns the path of a field of the return data slot of the current transaction, which
+    /// holds the program id of the program that owns the return data (field 0) and a flag that
+    /// is true if that program id is known (field 1). The bytes of the return data are not
+    /// modeled, so a call to get_return_data gives an unknown buffer.
+    fn return_data_path(field_index: usize) -> Rc<Path> {
+        let slot: Rc<Path> = Rc::new(
+            PathEnum::StaticVariable {
+                def_id: None,
+                summary_cache_key: Rc::from("solana_program::program::RETURN_DATA"),
+                expression_type: ExpressionType::NonPrimitive,
+            }
+            .into(),
+        );
+        Path::new_field(slot, field_index)
+    }
+
+    /// The runtime resets the return data when it invokes a program, so after a call to
+    /// invoke, any return data belongs to the program id of the invoked instruction.
+    /// Whether the call succeeds is decided by the invoked program, so its result is unknown.
+    #[logfn_inputs(TRACE)]
+    fn handle_invoke(&mut self) {
+        let known_program_id = self.copy_invoked_program_id();
+        self.block_visitor.bv.update_value_at(
+            Self::return_data_path(1),
+            Rc::new(if known_program_id {
+                abstract_value::TRUE
+            } else {
+                abstract_value::FALSE
+            }),
+        );
+        self.forget_variant_of_destination();
+        self.use_entry_condition_as_exit_condition();
+    }
+
+    // Gives the enum value at the destination of the call an unknown discriminant and returns
+    // the path of the destination.
+    fn forget_variant_of_destination(&mut self) -> Rc<Path> {
+        let target_type = self
+            .type_visitor()
+            .get_rustc_place_type(&self.destination, self.block_visitor.bv.current_span);
+        let discr_type = target_type.discriminant_ty(self.block_visitor.bv.tcx);
+        let target_path = self.block_visitor.visit_rh_place(&self.destination);
+        let discr_path = Path::new_discriminant(target_path.clone());
+        let discr_value = AbstractValue::make_typed_unknown(
+            ExpressionType::from(discr_type.kind()),
+            discr_path.clone(),
+        );
+        self.block_visitor
+            .bv
+            .update_value_at(discr_path, discr_value);
+        target_path
+    }
+
+    // Copies the program_id field of the instruction argument of a call to invoke into the
+    // return data slot and returns true, or returns false if the argument has no such field.
+    fn copy_invoked_program_id(&mut self) -> bool {
+        let instruction_type = self
+            .type_visitor()
+            .get_dereferenced_type(self.actual_argument_types[0]);
+        let TyKind::Adt(def, args) = instruction_type.kind() else {
+            return false;
+        };
+        if !def.is_struct() {
+            return false;
+        }
+        let tcx = self.block_visitor.bv.tcx;
+        let Some((index, field)) = def
+            .non_enum_variant()
+            .fields
+            .iter_enumerated()
+            .find(|(_, field)| field.name.as_str() == "program_id")
+        else {
+            return false;
+        };
+        let program_id_type = field.ty(tcx, args);
+        let instruction_path = Path::new_deref(
+            self.actual_args[0].0.clone(),
+            ExpressionType::from(instruction_type.kind()),
+        )
+        .canonicalize(&self.block_visitor.bv.current_environment);
+        let source_path = Path::new_field(instruction_path, index.as_usize());
+        self.block_visitor.bv.copy_or_move_elements(
+            Self::return_data_path(0),
+            source_path,
+            program_id_type,
+            false,
+        );
+        true
+    }
+
+    /// The return data set by the current program belongs to its own program id, which is not
+    /// known to the analysis.
+    #[logfn_inputs(TRACE)]
+    fn handle_set_return_data(&mut self) {
+        self.block_visitor
+            .bv
+            .update_value_at(Self::return_data_path(1), Rc::new(abstract_value::FALSE));
+    }
+
+    /// If the program that owns the return data is known, a call to get_return_data gives
+    /// a value that is either None or Some((program_id, data)), where data is unknown.
+    /// Returns false if the call should be analyzed as usual.
+    #[logfn_inputs(TRACE)]
+    fn handled_get_return_data(&mut self) -> bool {
+        let known_program_id = self
+            .block_visitor
+            .bv
+            .current_environment
+            .value_at(&Self::return_data_path(1))
+            .and_then(|flag| flag.as_bool_if_known())
+            .unwrap_or(false);
+        if !known_program_id {
+            return false;
+        }
+        let target_type = self
+            .type_visitor()
+            .get_rustc_place_type(&self.destination, self.block_visitor.bv.current_span);
+        let TyKind::Adt(def, args) = target_type.kind() else {
+            return false;
+        };
+        let TyKind::Tuple(elements) = args.type_at(0).kind() else {
+            return false;
+        };
+        if !def.is_enum() || elements.len() != 2 {
+            return false;
+        }
+        let tcx = self.block_visitor.bv.tcx;
+        let some_index = VariantIdx::from_usize(1);
+        let discr_type = target_type.discriminant_ty(tcx);
+        let some_discr_bits = match target_type.discriminant_for_variant(tcx, some_index) {
+            Some(discr) => discr.val,
+            None => some_index.as_u32() as u128,
+        };
+        // Whether the invoked program set any return data is not known
+        let target_path = self.forget_variant_of_destination();
+
+        let some_discr = self
+            .block_visitor
+            .get_int_const_val(some_discr_bits, discr_type);
+        let some_path = Path::new_qualified(
+            target_path,
+            Rc::new(PathSelector::Downcast(Rc::from("Some"), 1, some_discr)),
+        );
+        let program_id_path = Path::new_field(Path::new_field(some_path, 0), 0);
+        self.block_visitor.bv.copy_or_move_elements(
+            program_id_path,
+            Self::return_data_path(0),
+            elements[0],
+            false,
+        );
+        self.use_entry_condition_as_exit_condition();
+        true
+    }
+
+    // A hash map is modeled as a collection of the values that it associates with its keys, which
+    // are indexed by the value of the key, along with a collection of flags, indexed the same way,
+    // that tell if the map contains a key. Updates with keys that may be equal are weak.
+
+    /// Returns the path of the hash map that the first argument refers to, along with the type of
+    /// its values, or None if the argument is not a reference to a HashMap.
+    fn hash_map_path_and_value_type(&mut self) -> Option<(Rc<Path>, Ty<'tcx>)> {
+        let map_type = self
+            .type_visitor()
+            .get_dereferenced_type(self.actual_argument_types[0]);
+        let TyKind::Adt(_, args) = map_type.kind() else {
+            return None;
+        };
+        let value_type = args.types().nth(1)?;
+        let map_path = Path::new_deref(self.actual_args[0].0.clone(), ExpressionType::NonPrimitive)
+            .canonicalize(&self.block_visitor.bv.current_environment);
+        Some((map_path, value_type))
+    }
+
+    /// Returns the value of the key that is the argument at the given position, which is passed
+    /// by value, or by reference if by_reference is true.
+    fn hash_map_key(&mut self, position: usize, by_reference: bool) -> Rc<AbstractValue> {
+        let (key_path, key_type) = if by_reference {
+            let key_type = self
+                .type_visitor()
+                .get_dereferenced_type(self.actual_argument_types[position]);
+            let key_path = Path::new_deref(
+                self.actual_args[position].0.clone(),
+                ExpressionType::from(key_type.kind()),
+            )
+            .canonicalize(&self.block_visitor.bv.current_environment);
+            (key_path, key_type)
+        } else {
+            (
+                self.actual_args[position].0.clone(),
+                self.actual_argument_types[position],
+            )
+        };
+        self.block_visitor
+            .bv
+            .lookup_path_and_refine_result(key_path, key_type)
+    }
+
+    /// Returns the path of the value that the hash map at map_path associates with the key.
+    fn hash_map_value_path(
+        &mut self,
+        map_path: &Rc<Path>,
+        key: &Rc<AbstractValue>,
+        value_type: Ty<'tcx>,
+    ) -> Rc<Path> {
+        let value_path = Path::new_index(map_path.clone(), key.clone());
+        self.type_visitor_mut()
+            .set_path_rustc_type(value_path.clone(), value_type);
+        value_path
+    }
+
+    /// Returns the path of the flag that tells if the hash map at map_path contains the key.
+    fn hash_map_contains_path(map_path: &Rc<Path>, key: &Rc<AbstractValue>) -> Rc<Path> {
+        let keys_path = Path::new_model_field(map_path.clone(), Rc::from("keys"));
+        Path::new_index(keys_path, key.clone())
+    }
+
+    /// Returns the discriminant of the variant with the given index of the given enum type.
+    fn variant_discriminant(&mut self, enum_type: Ty<'tcx>, index: usize) -> Rc<AbstractValue> {
+        let tcx = self.block_visitor.bv.tcx;
+        let variant_index = VariantIdx::from_usize(index);
+        let discr_bits = match enum_type.discriminant_for_variant(tcx, variant_index) {
+            Some(discr) => discr.val,
+            None => index as u128,
+        };
+        self.block_visitor
+            .get_int_const_val(discr_bits, enum_type.discriminant_ty(tcx))
+    }
+
+    /// Returns the paths of the values of the two variants with the given names of the enum of
+    /// the given type at enum_path.
+    fn enum_variant_value_paths(
+        &mut self,
+        enum_type: Ty<'tcx>,
+        enum_path: &Rc<Path>,
+        variants: [&str; 2],
+    ) -> [Rc<Path>; 2] {
+        [0, 1].map(|index| {
+            let discr = self.variant_discriminant(enum_type, index);
+            let variant_path = Path::new_qualified(
+                enum_path.clone(),
+                Rc::new(PathSelector::Downcast(
+                    Rc::from(variants[index]),
+                    index,
+                    discr,
+                )),
+            );
+            Path::new_field(variant_path, 0)
+        })
+    }
+
+    /// Returns the condition that the enum of the given type at enum_path is of the variant with
+    /// the given index.
+    fn enum_is_variant(
+        &mut self,
+        enum_type: Ty<'tcx>,
+        enum_path: &Rc<Path>,
+        index: usize,
+    ) -> Rc<AbstractValue> {
+        let discr_type = enum_type.discriminant_ty(self.block_visitor.bv.tcx);
+        let discr = self
+            .block_visitor
+            .bv
+            .lookup_path_and_refine_result(Path::new_discriminant(enum_path.clone()), discr_type);
+        discr.equals(self.variant_discriminant(enum_type, index))
+    }
+
+    /// Sets the variant of the enum that is the destination of the call to the second of the two
+    /// variants with the given names if is_second holds and to the first otherwise, and returns
+    /// the type of the destination along with the paths of the values of the variants.
+    fn enum_destination_variants(
+        &mut self,
+        is_second: Rc<AbstractValue>,
+        variants: [&str; 2],
+    ) -> Option<(Ty<'tcx>, [Rc<Path>; 2])> {
+        let target_type = self
+            .type_visitor()
+            .get_rustc_place_type(&self.destination, self.block_visitor.bv.current_span);
+        if !matches!(target_type.kind(), TyKind::Adt(def, _) if def.is_enum()) {
+            return None;
+        }
+        let [first_discr, second_discr] =
+            [0, 1].map(|index| self.variant_discriminant(target_type, index));
+        let target_path = self.block_visitor.visit_rh_place(&self.destination);
+        let discr_value = is_second.conditional_expression(second_discr, first_discr);
+        self.block_visitor
+            .bv
+            .update_value_at(Path::new_discriminant(target_path.clone()), discr_value);
+        let value_paths = self.enum_variant_value_paths(target_type, &target_path, variants);
+        Some((target_type, value_paths))
+    }
+
+    /// Sets the variant of the Option that is the destination of the call to Some if is_some
+    /// holds and to None otherwise, and returns the path of the value of Some.
+    fn option_destination_some_value(&mut self, is_some: Rc<AbstractValue>) -> Option<Rc<Path>> {
+        let (_, [_, some_value_path]) =
+            self.enum_destination_variants(is_some, ["None", "Some"])?;
+        Some(some_value_path)
+    }
+
+    /// Models HashMap::get and HashMap::get_mut, which return a reference to the value that the
+    /// map associates with the key, if the map contains the key.
+    fn handled_hash_map_get(&mut self) -> bool {
+        let Some((map_path, value_type)) = self.hash_map_path_and_value_type() else {
+            return false;
+        };
+        let key = self.hash_map_key(1, true);
+        let bool_type = self.block_visitor.bv.tcx.types.bool;
+        let contains_path = Self::hash_map_contains_path(&map_path, &key);
+        let contains = self
             .block_visitor
             .bv
-            .current_environment
-            .entry_condition
-            .and(condition);
-        if let Some(target) = &self.target {
-            self.block_visitor
-                .bv
-                .current_environment
-                .exit_conditions
-                .insert_mut(*target, exit_condition);
-        } else {
-            assume_unreachable!();
-        }
+            .lookup_path_and_refine_result(contains_path, bool_type);
+        let value_path = self.hash_map_value_path(&map_path, &key, value_type);
+        let Some(some_value_path) = self.option_destination_some_value(contains) else {
+            return false;
+        };
+        self.block_visitor
+            .bv
+            .update_value_at(some_value_path, AbstractValue::make_reference(value_path));
+        self.use_entry_condition_as_exit_condition();
+        true
     }
 
-    /// It is bad style for a precondition to be reached conditionally, since well, that condition
-    /// should be part of the precondition.
-    #[logfn_inputs(TRACE)]
-    fn handle_precondition_start(&mut self) {
-        if self.block_visitor.bv.check_for_errors
-            && self.block_visitor.bv.check_for_unconditional_precondition
-            && !self
+    /// Models HashMap::insert, which associates the value with the key and returns the value that
+    /// the map associated with the key before, if the map contained the key.
+    fn handled_hash_map_insert(&mut self) -> bool {
+        let Some((map_path, value_type)) = self.hash_map_path_and_value_type() else {
+            return false;
+        };
+        let key = self.hash_map_key(1, false);
+        let contains_path = Self::hash_map_contains_path(&map_path, &key);
+        let bool_type = self.block_visitor.bv.tcx.types.bool;
+        let contains = self
+            .block_visitor
+            .bv
+            .lookup_path_and_refine_result(contains_path.clone(), bool_type);
+        let value_path = self.hash_map_value_path(&map_path, &key, value_type);
+        // A balance that the map did not contain is credited with all of the value
+        let old_balance = value_type.is_integral().then(|| {
+            let old_value = self
                 .block_visitor
                 .bv
-                .current_environment
-                .entry_condition
-                .as_bool_if_known()
-                .unwrap_or(false)
-        {
-            let span = self.block_visitor.bv.current_span;
-            let warning = self
+                .lookup_path_and_refine_result(value_path.clone(), value_type);
+            let zero = self.block_visitor.get_int_const_val(0, value_type);
+            contains.conditional_expression(old_value, zero)
+        });
+        let Some(some_value_path) = self.option_destination_some_value(contains) else {
+            return false;
+        };
+        self.block_visitor.bv.copy_or_move_elements(
+            some_value_path,
+            value_path.clone(),
+            value_type,
+            false,
+        );
+        self.block_visitor.bv.copy_or_move_elements(
+            value_path.clone(),
+            self.actual_args[2].0.clone(),
+            value_type,
+            true,
+        );
+        if let Some(old_balance) = old_balance {
+            let new_balance = self
                 .block_visitor
                 .bv
-                .cv
-                .session
-                .dcx()
-                .struct_span_warn(span, "preconditions should be reached unconditionally");
-            self.block_visitor.bv.emit_diagnostic(warning);
-            self.block_visitor.bv.check_for_unconditional_precondition = false;
+                .lookup_path_and_refine_result(value_path, value_type);
+            self.block_visitor.update_balance_total(
+                contract_errors::BalanceConservationChecker::fields_of(
+                    contract_errors::Balance::Entry,
+                ),
+                new_balance.subtract(old_balance),
+            );
         }
-        let exit_condition = self
+        self.block_visitor
+            .bv
+            .update_value_at(contains_path, Rc::new(abstract_value::TRUE));
+        self.use_entry_condition_as_exit_condition();
+        true
+    }
+
+    /// Models HashMap::entry by recording the map and the key in model fields of the entry, so
+    /// that a call to or_insert on the entry can find the value that the map associates with it.
+    fn handled_hash_map_entry(&mut self) -> bool {
+        let Some((map_path, _)) = self.hash_map_path_and_value_type() else {
+            return false;
+        };
+        let key = self.hash_map_key(1, false);
+        // Whether the entry is occupied or vacant is not known
+        let entry_path = self.forget_variant_of_destination();
+        self.block_visitor.bv.update_value_at(
+            Path::new_model_field(entry_path.clone(), Rc::from("map")),
+            AbstractValue::make_reference(map_path),
+        );
+        self.block_visitor
+            .bv
+            .update_value_at(Path::new_model_field(entry_path, Rc::from("key")), key);
+        self.use_entry_condition_as_exit_condition();
+        true
+    }
+
+    /// Models Entry::or_insert on an entry obtained from HashMap::entry, which associates the
+    /// default with the key if the map does not contain the key, and returns a reference to the
+    /// value that the map associates with the key.
+    fn handled_hash_map_or_insert(&mut self) -> bool {
+        let entry_path = self.actual_args[0].0.clone();
+        let environment = &self.block_visitor.bv.current_environment;
+        let map = environment.value_at(&Path::new_model_field(entry_path.clone(), Rc::from("map")));
+        let key = environment.value_at(&Path::new_model_field(entry_path, Rc::from("key")));
+        let (Some(map), Some(key)) = (map, key) else {
+            return false;
+        };
+        let Expression::Reference(map_path) = &map.expression else {
+            return false;
+        };
+        let (map_path, key) = (map_path.clone(), key.clone());
+        let value_type = self.actual_argument_types[1];
+        let contains_path = Self::hash_map_contains_path(&map_path, &key);
+        let bool_type = self.block_visitor.bv.tcx.types.bool;
+        let contains = self
             .block_visitor
             .bv
-            .current_environment
-            .entry_condition
-            .clone();
-        if let Some(target) = &self.target {
-            self.block_visitor
-                .bv
-                .current_environment
-                .exit_conditions
-                .insert_mut(*target, exit_condition);
-        } else {
-            assume_unreachable!();
+            .lookup_path_and_refine_result(contains_path.clone(), bool_type);
+        let value_path = self.hash_map_value_path(&map_path, &key, value_type);
+        if contains.as_bool_if_known() != Some(true) {
+            let default = self.actual_args[1].1.clone();
+            if default.expression.infer_type() == ExpressionType::NonPrimitive {
+                // A structured value cannot be selected by a condition
+                if contains.as_bool_if_known().is_none() {
+                    return false;
+                }
+                self.block_visitor.bv.copy_or_move_elements(
+                    value_path.clone(),
+                    self.actual_args[1].0.clone(),
+                    value_type,
+                    true,
+                );
+            } else {
+                let value = self
+                    .block_visitor
+                    .bv
+                    .lookup_path_and_refine_result(value_path.clone(), value_type);
+                self.block_visitor.bv.update_value_at(
+                    value_path.clone(),
+                    contains.conditional_expression(value, default),
+                );
+            }
         }
+        self.block_visitor
+            .bv
+            .update_value_at(contains_path, Rc::new(abstract_value::TRUE));
+        let target_path = self.block_visitor.visit_rh_place(&self.destination);
+        self.block_visitor
+            .bv
+            .update_value_at(target_path, AbstractValue::make_reference(value_path));
+        self.use_entry_condition_as_exit_condition();
+        true
     }
 
-    /// Adds the first and only value in actual_args to the current list of preconditions.
-    /// No check is performed, since we get to assume the caller has verified this condition.
-    #[logfn_inputs(TRACE)]
-    fn handle_precondition(&mut self) {
-        precondition!(self.actual_args.len() == 2);
-        if self.block_visitor.bv.check_for_errors {
-            let condition = self.actual_args[0].1.clone();
-            //todo: give diagnostic if the condition contains a local variable.
-            let message = self.coerce_to_string(&self.actual_args[1].0.clone());
-            let precondition = Precondition {
-                condition,
-                message,
-                provenance: None,
-                spans: vec![self.block_visitor.bv.current_span],
-            };
-            self.block_visitor.bv.preconditions.push(precondition);
+    // A Cow is modeled as the enum that it is. A deref of a Cow refers to the data that the
+    // reference of its Borrowed variant refers to, or to the value of its Owned variant, which is a
+    // heap block of its own, depending on the discriminant of the Cow. So what is known about the
+    // borrowed data does not constrain the owned copy, nor the other way around.
+
+    /// Returns the type and the path of the Cow that the first argument refers to, along with the
+    /// paths of the values of its Borrowed and its Owned variant, or None if the argument is not a
+    /// reference to an enum.
+    fn cow_paths(&mut self) -> Option<(Ty<'tcx>, Rc<Path>, Rc<Path>, Rc<Path>)> {
+        let cow_type = self
+            .type_visitor()
+            .get_dereferenced_type(self.actual_argument_types[0]);
+        if !matches!(cow_type.kind(), TyKind::Adt(def, _) if def.is_enum()) {
+            return None;
         }
+        let cow_path = Path::new_deref(self.actual_args[0].0.clone(), ExpressionType::NonPrimitive)
+            .canonicalize(&self.block_visitor.bv.current_environment);
+        let [borrowed_path, owned_path] = [(0, "Borrowed"), (1, "Owned")].map(|(index, name)| {
+            let discr = self.variant_discriminant(cow_type, index);
+            let variant_path = Path::new_qualified(
+                cow_path.clone(),
+                Rc::new(PathSelector::Downcast(Rc::from(name), index, discr)),
+            );
+            Path::new_field(variant_path, 0)
+        });
+        Some((cow_type, cow_path, borrowed_path, owned_path))
     }
 
-    /// Update the state to reflect the assignment of the model field.
-    #[logfn_inputs(TRACE)]
-    fn handle_set_model_field(&mut self) {
-        checked_assume!(self.actual_args.len() == 3);
+    /// Returns the condition that the Cow at cow_path is of its Borrowed variant.
+    fn cow_is_borrowed(&mut self, cow_type: Ty<'tcx>, cow_path: &Rc<Path>) -> Rc<AbstractValue> {
+        let discr_type = cow_type.discriminant_ty(self.block_visitor.bv.tcx);
+        let discr = self
+            .block_visitor
+            .bv
+            .lookup_path_and_refine_result(Path::new_discriminant(cow_path.clone()), discr_type);
+        discr.equals(self.variant_discriminant(cow_type, 0))
+    }
 
-        let mut qualifier = self.actual_args[0].0.clone();
-        if matches!(&self.actual_argument_types[0].kind(), TyKind::Ref { .. }) {
-            let target_type = ExpressionType::from(
-                self.type_visitor()
-                    .get_dereferenced_type(self.actual_argument_types[0])
-                    .kind(),
+    /// Models the deref of a Cow, which returns the reference of the Borrowed variant if the Cow
+    /// is borrowed, and a reference to the value of the Owned variant otherwise.
+    fn handled_cow_deref(&mut self) -> bool {
+        let Some((cow_type, cow_path, borrowed_path, owned_path)) = self.cow_paths() else {
+            return false;
+        };
+        let is_borrowed = self.cow_is_borrowed(cow_type, &cow_path);
+        let tcx = self.block_visitor.bv.tcx;
+        let target_type = self
+            .type_visitor()
+            .get_rustc_place_type(&self.destination, self.block_visitor.bv.current_span);
+        let target_path = self.block_visitor.visit_rh_place(&self.destination);
+        let owned_reference = AbstractValue::make_reference(owned_path.clone());
+        if self.type_visitor().is_slice_pointer(target_type.kind()) {
+            // The thin pointer and the length of the slice are selected separately
+            let borrowed_pointer = self.block_visitor.bv.lookup_path_and_refine_result(
+                Path::new_field(borrowed_path.clone(), 0),
+                ExpressionType::ThinPointer.as_rustc_type(tcx),
+            );
+            let length_type = ExpressionType::Usize.as_rustc_type(tcx);
+            let borrowed_length = self
+                .block_visitor
+                .bv
+                .lookup_path_and_refine_result(Path::new_length(borrowed_path), length_type);
+            let owned_length = self
+                .block_visitor
+                .bv
+                .lookup_path_and_refine_result(Path::new_length(owned_path), length_type);
+            self.block_visitor.bv.update_value_at(
+                Path::new_field(target_path.clone(), 0),
+                is_borrowed.conditional_expression(borrowed_pointer, owned_reference),
+            );
+            self.block_visitor.bv.update_value_at(
+                Path::new_length(target_path),
+                is_borrowed.conditional_expression(borrowed_length, owned_length),
+            );
+        } else {
+            let borrowed_reference = self
+                .block_visitor
+                .bv
+                .lookup_path_and_refine_result(borrowed_path, target_type);
+            self.block_visitor.bv.update_value_at(
+                target_path,
+                is_borrowed.conditional_expression(borrowed_reference, owned_reference),
             );
-            qualifier = Path::new_deref(qualifier, target_type);
         }
-        let field_name = self.coerce_to_string(&self.actual_args[1].0.clone());
-        let target_path = Path::new_model_field(qualifier, field_name)
-            .canonicalize(&self.block_visitor.bv.current_environment);
-        let source_path = self.actual_args[2].0.clone();
-        let target_type = self.actual_argument_types[2];
+        self.use_entry_condition_as_exit_condition();
+        true
+    }
+
+    /// Models Cow::to_mut, which makes a borrowed Cow owned by copying the borrowed data into a
+    /// heap block of its own, and returns a reference to the value of the Owned variant. The copy
+    /// is not constrained by what is known about the borrowed data, since it may be changed
+    /// through the returned reference.
+    fn handled_cow_to_mut(&mut self) -> bool {
+        let Some((cow_type, cow_path, _, owned_path)) = self.cow_paths() else {
+            return false;
+        };
+        let owned_discr = self.variant_discriminant(cow_type, 1);
         self.block_visitor
             .bv
-            .copy_or_move_elements(target_path, source_path, target_type, true);
-        if let Some(target) = &self.target {
-            let exit_condition = self
-                .block_visitor
-                .bv
-                .current_environment
-                .entry_condition
-                .clone();
+            .update_value_at(Path::new_discriminant(cow_path), owned_discr);
+        let target_path = self.block_visitor.visit_rh_place(&self.destination);
+        self.block_visitor
+            .bv
+            .update_value_at(target_path, AbstractValue::make_reference(owned_path));
+        self.use_entry_condition_as_exit_condition();
+        true
+    }
+
+    // An Option or a Result is modeled as the enum that it is. The models of the combinators
+    // below select the variant of their result with the discriminant of their receiver and copy
+    // the value of the variant across, so that what is known about the value of Some or Ok carries
+    // over to the result, rather than being lost in a summary that joins the variants.
+
+    /// Returns the type and the path of the enum that is the first argument, which is passed by
+    /// value, along with the type of the value of its Some or Ok variant, or None if the argument
+    /// is not an enum.
+    fn enum_receiver(&mut self) -> Option<(Ty<'tcx>, Rc<Path>, Ty<'tcx>)> {
+        let enum_type = self.actual_argument_types[0];
+        let TyKind::Adt(def, args) = enum_type.kind() else {
+            return None;
+        };
+        if !def.is_enum() {
+            return None;
+        }
+        let value_type = args.types().next()?;
+        let enum_path = self.actual_args[0]
+            .0
+            .canonicalize(&self.block_visitor.bv.current_environment);
+        Some((enum_type, enum_path, value_type))
+    }
+
+    /// Models Option::ok_or, which returns Ok with the value of Some if the option is Some, and
+    /// Err with the error otherwise.
+    fn handled_option_ok_or(&mut self) -> bool {
+        let Some((option_type, option_path, value_type)) = self.enum_receiver() else {
+            return false;
+        };
+        let is_some = self.enum_is_variant(option_type, &option_path, 1);
+        let [_, some_value_path] =
+            self.enum_variant_value_paths(option_type, &option_path, ["None", "Some"]);
+        let Some((_, [ok_value_path, err_value_path])) =
+            self.enum_destination_variants(is_some.logical_not(), ["Ok", "Err"])
+        else {
+            return false;
+        };
+        self.block_visitor.bv.copy_or_move_elements(
+            ok_value_path,
+            some_value_path,
+            value_type,
+            true,
+        );
+        self.block_visitor.bv.copy_or_move_elements(
+            err_value_path,
+            self.actual_args[1].0.clone(),
+            self.actual_argument_types[1],
+            true,
+        );
+        self.use_entry_condition_as_exit_condition();
+        true
+    }
+
+    /// Models Option::copied, which returns Some with a copy of the value that the reference of
+    /// Some refers to if the option is Some, and None otherwise.
+    fn handled_option_copied(&mut self) -> bool {
+        let Some((option_type, option_path, reference_type)) = self.enum_receiver() else {
+            return false;
+        };
+        let value_type = self.type_visitor().get_dereferenced_type(reference_type);
+        let is_some = self.enum_is_variant(option_type, &option_path, 1);
+        let [_, some_value_path] =
+            self.enum_variant_value_paths(option_type, &option_path, ["None", "Some"]);
+        let Some(target_value_path) = self.option_destination_some_value(is_some) else {
+            return false;
+        };
+        let referenced_path =
+            Path::new_deref(some_value_path, ExpressionType::from(value_type.kind()))
+                .canonicalize(&self.block_visitor.bv.current_environment);
+        self.block_visitor.bv.copy_or_move_elements(
+            target_value_path,
+            referenced_path,
+            value_type,
+            false,
+        );
+        self.use_entry_condition_as_exit_condition();
+        true
+    }
+
+    /// Models Option::unwrap_or, which returns the value of Some if the option is Some, and the
+    /// default otherwise.
+    fn handled_option_unwrap_or(&mut self) -> bool {
+        let Some((option_type, option_path, value_type)) = self.enum_receiver() else {
+            return false;
+        };
+        let is_some = self.enum_is_variant(option_type, &option_path, 1);
+        let [_, some_value_path] =
+            self.enum_variant_value_paths(option_type, &option_path, ["None", "Some"]);
+        let default = self.actual_args[1].1.clone();
+        if default.expression.infer_type() == ExpressionType::NonPrimitive {
+            // A structured value cannot be selected by a condition
+            let source_path = match is_some.as_bool_if_known() {
+                Some(true) => some_value_path,
+                Some(false) => self.actual_args[1].0.clone(),
+                None => return false,
+            };
+            let target_path = self.block_visitor.visit_rh_place(&self.destination);
             self.block_visitor
                 .bv
-                .current_environment
-                .exit_conditions
-                .insert_mut(*target, exit_condition);
+                .copy_or_move_elements(target_path, source_path, value_type, true);
         } else {
-            assume_unreachable!();
+            let some_value = self
+                .block_visitor
+                .bv
+                .lookup_path_and_refine_result(some_value_path, value_type);
+            let target_path = self.block_visitor.visit_rh_place(&self.destination);
+            self.block_visitor.bv.update_value_at(
+                target_path,
+                is_some.conditional_expression(some_value, default),
+            );
+        }
+        self.use_entry_condition_as_exit_condition();
+        true
+    }
+
+    /// Models Option::map when it is known whether the option is Some, by calling the function
+    /// with the value of Some, if it is, and storing the result of the call as the value of Some
+    /// of the destination. Otherwise the summary of map is used, since the function must only be
+    /// called if the option is Some.
+    fn handled_option_map(&mut self) -> bool {
+        let Some((option_type, option_path, value_type)) = self.enum_receiver() else {
+            return false;
+        };
+        let is_some = self.enum_is_variant(option_type, &option_path, 1);
+        let Some(is_some_value) = is_some.as_bool_if_known() else {
+            return false;
+        };
+        let Some((target_type, _)) = self.enum_destination_variants(is_some, ["None", "Some"])
+        else {
+            return false;
+        };
+        if !is_some_value {
+            self.use_entry_condition_as_exit_condition();
+            return true;
+        }
+        let TyKind::Adt(_, target_args) = target_type.kind() else {
+            return false;
+        };
+        let Some(mapped_type) = target_args.types().next() else {
+            return false;
+        };
+        let [_, some_value_path] =
+            self.enum_variant_value_paths(option_type, &option_path, ["None", "Some"]);
+        let some_value = self
+            .block_visitor
+            .bv
+            .lookup_path_and_refine_result(some_value_path.clone(), value_type);
+        let tcx = self.block_visitor.bv.tcx;
+        let destination = self.destination.project_deeper(
+            &[
+                mir::ProjectionElem::Downcast(
+                    Some(rustc_span::sym::Some),
+                    VariantIdx::from_usize(1),
+                ),
+                mir::ProjectionElem::Field(FieldIdx::from_usize(0), mapped_type),
+            ],
+            tcx,
+        );
+        // The summary of the function provides the exit condition of the call
+        self.call_function_value(
+            self.actual_args[1].clone(),
+            self.actual_argument_types[1],
+            vec![(some_value_path, some_value)],
+            vec![value_type],
+            true,
+            destination,
+        );
+        true
+    }
+
+    /// Models Try::branch for Option and Result, which the ? operator calls. It returns Continue
+    /// with the value of Some or Ok, and Break with the residual otherwise, which is None or Err
+    /// with the error of the receiver.
+    fn handled_try_branch(&mut self) -> bool {
+        let Some((enum_type, enum_path, output_type)) = self.enum_receiver() else {
+            return false;
+        };
+        let tcx = self.block_visitor.bv.tcx;
+        let TyKind::Adt(def, args) = enum_type.kind() else {
+            return false;
+        };
+        let (variants, output_index, error_type) =
+            if tcx.is_diagnostic_item(rustc_span::sym::Option, def.did()) {
+                (["None", "Some"], 1, None)
+            } else if tcx.is_diagnostic_item(rustc_span::sym::Result, def.did()) {
+                (["Ok", "Err"], 0, args.types().nth(1))
+            } else {
+                return false;
+            };
+        let is_output = self.enum_is_variant(enum_type, &enum_path, output_index);
+        let [first_value_path, second_value_path] =
+            self.enum_variant_value_paths(enum_type, &enum_path, variants);
+        let Some((control_flow_type, [continue_value_path, residual_path])) =
+            self.enum_destination_variants(is_output.logical_not(), ["Continue", "Break"])
+        else {
+            return false;
+        };
+        let TyKind::Adt(_, control_flow_args) = control_flow_type.kind() else {
+            return false;
+        };
+        let Some(residual_type) = control_flow_args.types().next() else {
+            return false;
+        };
+        let (output_value_path, error_value_path) = if output_index == 1 {
+            (second_value_path, first_value_path)
+        } else {
+            (first_value_path, second_value_path)
+        };
+        self.block_visitor.bv.copy_or_move_elements(
+            continue_value_path,
+            output_value_path,
+            output_type,
+            true,
+        );
+        // The residual has the variant of the receiver that is not the output, along with its
+        // error if it is a Result
+        let residual_index = 1 - output_index;
+        let residual_discr = self.variant_discriminant(residual_type, residual_index);
+        self.block_visitor.bv.update_value_at(
+            Path::new_discriminant(residual_path.clone()),
+            residual_discr,
+        );
+        if let Some(error_type) = error_type {
+            let [_, residual_error_path] =
+                self.enum_variant_value_paths(residual_type, &residual_path, variants);
+            self.block_visitor.bv.copy_or_move_elements(
+                residual_error_path,
+                error_value_path,
+                error_type,
+                true,
+            );
         }
+        self.use_entry_condition_as_exit_condition();
+        true
     }
 
     /// Removes the heap block and all paths rooted in it from the current environment.
@@ -2765,6 +4434,13 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
         // from overflowing.
         let result = Some(self.block_visitor.visit_rh_place(&self.destination));
         for precondition in &function_summary.preconditions {
+            let suppressed = arithmetic_code(&precondition.message)
+                .and_then(checker_registry::checker_for_code)
+                .is_some_and(|checker| function_summary.suppresses(checker.name));
+            if suppressed {
+                // The called function suppresses the findings of the checker of the precondition
+                continue;
+            }
             let mut refined_condition = precondition.condition.refine_parameters_and_paths(
                 &self.actual_args,
                 &result,
@@ -3024,8 +4700,9 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
         let mut pre_environment = self.environment_before_call.clone();
         // Transfer side effects
         if function_summary.is_computed && !function_summary.is_incomplete {
+            let side_effects = function_summary.side_effects_by_exit_class();
             // Effects on the heap
-            for (path, value) in function_summary.side_effects.iter() {
+            for (path, value) in side_effects.iter() {
                 if path.is_rooted_by_non_local_structure() {
                     let rvalue = value.clone().refine_parameters_and_paths(
                         &self.actual_args,
@@ -3066,7 +4743,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
 
             // Effects on the call result
             self.block_visitor.bv.transfer_and_refine(
-                &function_summary.side_effects,
+                &side_effects,
                 target_path,
                 &return_value_path,
                 result_path,
@@ -3078,7 +4755,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
             for (i, (target_path, _)) in self.actual_args.iter().enumerate() {
                 let parameter_path = Path::new_parameter(i + 1);
                 self.block_visitor.bv.transfer_and_refine(
-                    &function_summary.side_effects,
+                    &side_effects,
                     target_path.clone(),
                     &parameter_path,
                     result_path,
@@ -3119,7 +4796,8 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                 .entry_condition
                 .clone();
             if exit_condition.as_bool_if_known().unwrap_or(true) {
-                if let Some(post_condition) = &function_summary.post_condition {
+                if let Some(post_condition) = &Self::post_condition_by_exit_class(function_summary)
+                {
                     let refined_post_condition = post_condition.refine_parameters_and_paths(
                         &self.actual_args,
                         result_path,
@@ -3141,6 +4819,23 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
         }
     }
 
+    /// Returns the post condition of the summary. If the summary has exit classes, this is the
+    /// post condition of each class, assumed only when the result is of the class, rather than
+    /// the post condition of the joined exit.
+    fn post_condition_by_exit_class(function_summary: &Summary) -> Option<Rc<AbstractValue>> {
+        if function_summary.exit_classes.is_empty() {
+            return function_summary.post_condition.clone();
+        }
+        function_summary
+            .exit_classes
+            .iter()
+            .filter_map(|class| {
+                let post_condition = class.post_condition.as_ref()?;
+                Some(class.condition.logical_not().or(post_condition.clone()))
+            })
+            .reduce(|conjunction, post_condition| conjunction.and(post_condition))
+    }
+
     /// Extracts the string from an AbstractDomain that is required to be a reference to a string literal.
     /// This is the case for helper HEPHA helper functions that are hidden in the documentation
     /// and that are required to be invoked via macros that ensure that the argument providing
@@ -3190,6 +4885,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
     fn extract_tag_kind_and_propagation_set(&mut self) -> Option<Tag> {
         precondition!(
             self.callee_known_name == KnownNames::MiraiAddTag
+                || self.callee_known_name == KnownNames::MiraiRemoveTag
                 || self.callee_known_name == KnownNames::MiraiHasTag
                 || self.callee_known_name == KnownNames::MiraiDoesNotHaveTag
         );
@@ -3212,6 +4908,16 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                     }
                 };
 
+                // The tag type of untrusted input stands for a built-in tag.
+                if let TyKind::Adt(adt_def, _) = tag_rustc_type.kind() {
+                    let bv = &mut self.block_visitor.bv;
+                    if bv.cv.known_names_cache.get(bv.tcx, adt_def.did())
+                        == KnownNames::HephaUntrustedInput
+                    {
+                        return Some(Tag::UNTRUSTED_INPUT);
+                    }
+                }
+
                 // The tag type should be a generic ADT whose first parameter is a constant.
                 let tag_adt_def;
                 let tag_substs_ref = match tag_rustc_type.kind() {
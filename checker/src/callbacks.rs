@@ -4,25 +4,20 @@
 // LICENSE file in the root directory of this source tree.
 #![allow(clippy::borrowed_box)]
 
-use crate::call_graph::CallGraph;
-use crate::constant_domain::ConstantValueCache;
+use crate::checker_registry::Severity;
 use crate::crate_visitor::CrateVisitor;
-use crate::known_names::KnownNamesCache;
+use crate::k_limits;
 use crate::options::Options;
-use crate::summaries::SummaryCache;
+use crate::summary_view::SummaryViews;
 
-use crate::type_visitor::TypeCache;
 use crate::utils;
 use log::info;
 use log_derive::*;
 use rustc_driver::Compilation;
 use rustc_interface::interface;
 use rustc_middle::ty::TyCtxt;
-use std::cell::RefCell;
-use std::collections::HashMap;
 use std::fmt::{Debug, Formatter, Result};
 use std::path::PathBuf;
-use std::rc::Rc;
 use tempfile::TempDir;
 
 /// Private state used to implement the callbacks.
@@ -35,6 +30,10 @@ pub struct MiraiCallbacks {
     output_directory: PathBuf,
     /// True if this run is done via cargo test
     test_run: bool,
+    /// The severity of the most severe finding reported for the crate, if any.
+    pub most_severe_finding: Option<Severity>,
+    /// The views of the summaries of the analyzed functions, by their stable ids.
+    pub summaries: SummaryViews,
 }
 
 /// Constructors
@@ -45,6 +44,8 @@ impl MiraiCallbacks {
             file_name: String::new(),
             output_directory: PathBuf::default(),
             test_run: false,
+            most_severe_finding: None,
+            summaries: SummaryViews::default(),
         }
     }
 
@@ -54,6 +55,8 @@ impl MiraiCallbacks {
             file_name: String::new(),
             output_directory: PathBuf::default(),
             test_run: true,
+            most_severe_finding: None,
+            summaries: SummaryViews::default(),
         }
     }
 }
@@ -85,6 +88,11 @@ impl rustc_driver::Callbacks for MiraiCallbacks {
             self.options.test_only = true;
         }
         config.crate_cfg.push("hepha".to_string());
+        if let Some(threads) = self.options.threads {
+            // The components of the call graph are analyzed on the thread pool of the compiler,
+            // which only runs work in parallel if it has more than one thread.
+            config.opts.unstable_opts.threads = threads;
+        }
         match &config.output_dir {
             None => {
                 self.output_directory = std::env::temp_dir();
@@ -140,39 +148,44 @@ impl MiraiCallbacks {
             return;
         }
         let output_dir = String::from(self.output_directory.to_str().expect("valid string"));
-        let summary_store_path = if std::env::var("HEPHA_SHARE_PERSISTENT_STORE").is_ok() {
-            output_dir
-        } else {
-            let temp_dir = TempDir::new().expect("failed to create a temp dir");
-            String::from(temp_dir.into_path().to_str().expect("valid string"))
-        };
+        // The records of --incremental are kept in the store of the output directory, which the
+        // crates of a workspace share
+        let summary_store_path =
+            if std::env::var("HEPHA_SHARE_PERSISTENT_STORE").is_ok() || self.options.incremental {
+                output_dir
+            } else {
+                let temp_dir = TempDir::new().expect("failed to create a temp dir");
+                String::from(temp_dir.into_path().to_str().expect("valid string"))
+            };
         info!(
             "storing summaries for {} at {}/.summary_store.sled",
             self.file_name, summary_store_path
         );
-        let call_graph_config = self.options.call_graph_config.to_owned();
-        let mut crate_visitor = CrateVisitor {
-            buffered_diagnostics: Vec::new(),
-            constant_time_tag_cache: None,
-            constant_time_tag_not_found: false,
-            constant_value_cache: ConstantValueCache::default(),
-            diagnostics_for: HashMap::new(),
-            file_name: self.file_name.as_str(),
-            known_names_cache: KnownNamesCache::create_cache_from_language_items(),
-            options: &std::mem::take(&mut self.options),
-            session: &compiler.sess,
-            generic_args_cache: HashMap::new(),
-            summary_cache: SummaryCache::new(summary_store_path),
+        k_limits::set_max_path_length(self.options.k_limits.max_path_length);
+        let options = std::mem::take(&mut self.options);
+        let mut crate_visitor = CrateVisitor::new(
+            &compiler.sess,
             tcx,
-            test_run: self.test_run,
-            type_cache: Rc::new(RefCell::new(TypeCache::new())),
-            call_graph: CallGraph::new(call_graph_config, tcx),
-        };
+            &options,
+            self.file_name.as_str(),
+            summary_store_path,
+            self.test_run,
+        );
         if crate_visitor.options.print_summaries {
             crate_visitor.call_graph.config.include_calls_in_summaries = true;
         }
+        if crate_visitor.options.call_graph_out.is_some() {
+            crate_visitor.call_graph.config.include_edges_in_export = true;
+        }
         crate_visitor.analyze_some_bodies();
         crate_visitor.call_graph.output();
+        crate_visitor.write_call_graph();
         crate_visitor.print_summaries();
+        crate_visitor.write_path_constraints_report();
+        crate_visitor.write_summaries();
+        crate_visitor.write_call_states();
+        crate_visitor.write_facts();
+        self.most_severe_finding = crate_visitor.most_severe_finding;
+        self.summaries = std::mem::take(&mut crate_visitor.summary_views);
     }
 }
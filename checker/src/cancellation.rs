@@ -0,0 +1,83 @@
+// The cancellation of the analysis of a crate, by Ctrl-C or by the deadline that
+// --crate_analysis_timeout sets. The analysis looks at the token where it looks at the time taken
+// for the current body, before each block and each solver query, so that it stops within the time
+// of a block or of a query. The diagnostics found so far are then emitted as usual and the run
+// summary and the diagnostics written as JSON are marked as truncated.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+// Set by the handler of SIGINT.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+const SIGINT: i32 = 2;
+#[cfg(unix)]
+const SIG_DFL: usize = 0;
+
+#[cfg(unix)]
+extern "C" {
+    fn signal(signum: i32, handler: usize) -> usize;
+}
+
+// Only does what is safe in a signal handler. The default handler is restored, so that a second
+// Ctrl-C terminates HEPHA at once.
+#[cfg(unix)]
+extern "C" fn on_interrupt(_signum: i32) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+    unsafe {
+        signal(SIGINT, SIG_DFL);
+    }
+}
+
+/// Installs a handler of SIGINT that cancels the analysis rather than terminating HEPHA, so that
+/// the diagnostics found so far are still emitted.
+pub fn install_interrupt_handler() {
+    #[cfg(unix)]
+    unsafe {
+        signal(SIGINT, on_interrupt as extern "C" fn(i32) as usize);
+    }
+}
+
+/// Tells the analysis of a crate whether to stop.
+#[derive(Debug)]
+pub struct CancellationToken {
+    cancelled: AtomicBool,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    /// Returns a token that is cancelled once the given time has passed, if any, or on Ctrl-C.
+    pub fn new(timeout: Option<Duration>) -> CancellationToken {
+        CancellationToken {
+            cancelled: AtomicBool::new(false),
+            deadline: timeout.and_then(|timeout| Instant::now().checked_add(timeout)),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// True if the token has been cancelled, Ctrl-C has been pressed or the deadline has passed.
+    pub fn is_cancelled(&self) -> bool {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return true;
+        }
+        if INTERRUPTED.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.cancel();
+            return true;
+        }
+        false
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        CancellationToken::new(None)
+    }
+}
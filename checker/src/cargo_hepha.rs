@@ -11,17 +11,28 @@
 
 use std::ffi::OsString;
 use std::ops::Index;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use cargo_metadata::{Package, Target, TargetKind};
+use cargo_metadata::{Metadata, Package, Target, TargetKind};
 
 const CARGO_HEPHA_HELP: &str = r#"Static analysis tool for Rust programs
 
 Usage:
     cargo hepha
+    cargo hepha config dump
 "#;
 
+// The exit status of hepha when it reports findings of at least the severity given by --fail-on
+const EXIT_FINDINGS: i32 = 2;
+
+// The environment variable that holds the file that hepha runs mark when they exit with EXIT_FINDINGS
+const FINDINGS_MARKER: &str = "HEPHA_FINDINGS_MARKER";
+
+// The environment variable that holds the configuration file that hepha reads, which is the
+// hepha.toml at the root of the workspace unless it is already set
+const CONFIG_VARIABLE: &str = "HEPHA_CONFIG";
+
 pub fn main() {
     if std::env::args().any(|a| a == "--help" || a == "-h") {
         println!("{CARGO_HEPHA_HELP}");
@@ -35,6 +46,10 @@ pub fn main() {
                 println!("{version_info}");
                 return;
             }
+            if std::env::args().nth(2).as_deref() == Some("config") {
+                dump_config();
+                return;
+            }
             call_cargo();
         }
         Some(s) if s.ends_with("rustc") || s.ends_with("rustc.exe") => {
@@ -63,6 +78,49 @@ pub fn main() {
 /// Read the toml associated with the current directory and
 /// recursively execute cargo for each applicable package target/workspace member in the toml
 fn call_cargo() {
+    let metadata = get_metadata();
+    let config = workspace_config(&metadata);
+
+    if let Some(root) = metadata.root_package() {
+        call_cargo_on_each_package_target(root, config.as_deref());
+        return;
+    }
+
+    // There is no root, this must be a workspace, so call_cargo_on_each_package_target on each workspace member
+    for package_id in &metadata.workspace_members {
+        let package = metadata.index(package_id);
+        call_cargo_on_each_package_target(package, config.as_deref());
+    }
+}
+
+/// Print the configuration that hepha uses for the workspace, which merges the configuration file
+/// with the options of HEPHA_FLAGS.
+fn dump_config() {
+    if std::env::args().nth(3).as_deref() != Some("dump") {
+        eprintln!("`cargo hepha config` expects the subcommand `dump`");
+        std::process::exit(1);
+    }
+    let metadata = get_metadata();
+    let mut cmd = Command::new(hepha_path());
+    if let Some(config) = workspace_config(&metadata) {
+        cmd.env(CONFIG_VARIABLE, config);
+    }
+    // Options beyond a -- in HEPHA_FLAGS are for rustc, so the flag goes first
+    let flags = std::env::var("HEPHA_FLAGS").unwrap_or_default();
+    cmd.env("HEPHA_FLAGS", format!("--dump-config {flags}"));
+    let exit_status = cmd
+        .spawn()
+        .expect("could not run hepha")
+        .wait()
+        .expect("failed to wait for hepha");
+    if !exit_status.success() {
+        std::process::exit(exit_status.code().unwrap_or(-1))
+    }
+}
+
+// The metadata of the package or the workspace of the manifest given with --manifest-path, or of
+// the current directory
+fn get_metadata() -> Metadata {
     let manifest_path =
         get_arg_flag_value("--manifest-path").map(|m| Path::new(&m).canonicalize().unwrap());
 
@@ -71,26 +129,25 @@ fn call_cargo() {
         cmd.manifest_path(manifest_path);
     }
 
-    let metadata = if let Ok(metadata) = cmd.exec() {
+    if let Ok(metadata) = cmd.exec() {
         metadata
     } else {
         eprintln!("Could not obtain Cargo metadata; likely an ill-formed manifest");
         std::process::exit(1);
-    };
-
-    if let Some(root) = metadata.root_package() {
-        call_cargo_on_each_package_target(root);
-        return;
     }
+}
 
-    // There is no root, this must be a workspace, so call_cargo_on_each_package_target on each workspace member
-    for package_id in &metadata.workspace_members {
-        let package = metadata.index(package_id);
-        call_cargo_on_each_package_target(package);
+// The hepha.toml at the root of the workspace, if there is one and HEPHA_CONFIG does not already
+// name a configuration file
+fn workspace_config(metadata: &Metadata) -> Option<PathBuf> {
+    if std::env::var_os(CONFIG_VARIABLE).is_some() {
+        return None;
     }
+    let path = metadata.workspace_root.join("hepha.toml");
+    path.exists().then(|| path.into_std_path_buf())
 }
 
-fn call_cargo_on_each_package_target(package: &Package) {
+fn call_cargo_on_each_package_target(package: &Package, config: Option<&Path>) {
     let lib_only = get_arg_flag_presence("--lib");
     for target in &package.targets {
         let kind = target
@@ -100,11 +157,11 @@ fn call_cargo_on_each_package_target(package: &Package) {
         if lib_only && !target.is_lib() {
             continue;
         }
-        call_cargo_on_target(target, kind);
+        call_cargo_on_target(target, kind, config);
     }
 }
 
-fn call_cargo_on_target(target: &Target, kind: &TargetKind) {
+fn call_cargo_on_target(target: &Target, kind: &TargetKind, config: Option<&Path>) {
     // Build a cargo command for target
     let mut cmd =
         Command::new(std::env::var_os("CARGO").unwrap_or_else(|| OsString::from("cargo")));
@@ -151,6 +208,11 @@ fn call_cargo_on_target(target: &Target, kind: &TargetKind) {
         );
     }
 
+    // Let the calls to hepha read the configuration file of the workspace.
+    if let Some(config) = config {
+        cmd.env(CONFIG_VARIABLE, config);
+    }
+
     // Force cargo to recompile all dependencies with HEPHA friendly flags
     cmd.env("RUSTFLAGS", "--cfg hepha -Z always_encode_mir");
 
@@ -172,6 +234,12 @@ fn call_cargo_on_target(target: &Target, kind: &TargetKind) {
         cmd.env("RUSTUP_TOOLCHAIN", toolchain);
     }
 
+    // Cargo reports the failure of any rustc invocation in the same way, so let the call to
+    // hepha leave a marker when it fails because of findings.
+    let findings_marker =
+        std::env::temp_dir().join(format!("hepha-findings-{}", std::process::id()));
+    cmd.env(FINDINGS_MARKER, &findings_marker);
+
     // Execute cmd
     let exit_status = cmd
         .spawn()
@@ -179,6 +247,9 @@ fn call_cargo_on_target(target: &Target, kind: &TargetKind) {
         .wait()
         .expect("failed to wait for cargo");
 
+    if std::fs::remove_file(&findings_marker).is_ok() {
+        std::process::exit(EXIT_FINDINGS)
+    }
     if !exit_status.success() {
         std::process::exit(exit_status.code().unwrap_or(-1))
     }
@@ -210,14 +281,7 @@ fn call_rustc_or_hepha() {
 }
 
 fn call_hepha() {
-    let mut path = std::env::current_exe().expect("current executable path invalid");
-    let extension = path.extension().map(|e| e.to_owned());
-    path.pop(); // remove the cargo_hepha bit
-    path.push("hepha");
-    if let Some(ext) = extension {
-        path.set_extension(ext);
-    }
-    let mut cmd = Command::new(path);
+    let mut cmd = Command::new(hepha_path());
     cmd.args(std::env::args().skip(2));
     let exit_status = cmd
         .spawn()
@@ -225,11 +289,28 @@ fn call_hepha() {
         .wait()
         .expect("failed to wait for hepha");
 
+    if exit_status.code() == Some(EXIT_FINDINGS) {
+        if let Some(findings_marker) = std::env::var_os(FINDINGS_MARKER) {
+            std::fs::write(findings_marker, "").expect("could not mark the findings");
+        }
+    }
     if !exit_status.success() {
         std::process::exit(exit_status.code().unwrap_or(-1))
     }
 }
 
+// The hepha executable, which is next to this one
+fn hepha_path() -> PathBuf {
+    let mut path = std::env::current_exe().expect("current executable path invalid");
+    let extension = path.extension().map(|e| e.to_owned());
+    path.pop(); // remove the cargo_hepha bit
+    path.push("hepha");
+    if let Some(ext) = extension {
+        path.set_extension(ext);
+    }
+    path
+}
+
 fn call_rustc() {
     let mut args = std::env::args_os().skip(1);
     // The rustc to use is passed by Cargo as the first argument to RUSTC_WRAPPER
@@ -0,0 +1,1084 @@
+// The registry of the smart contract vulnerability checkers. Every checker that runs over a
+// function body is instantiated here and described by an entry in CHECKERS. The severity and
+// code of every finding, --list-checks and --explain are all taken from CHECKERS, so that they
+// cannot drift apart from the code that runs the checkers.
+
+use serde::{Deserialize, Serialize};
+
+use rustc_errors::Level;
+use rustc_middle::mir;
+use rustc_span::Span;
+
+use crate::contract_errors::{
+    AccountCloseChecker, AccountConstraintChecker, AccountDataLengthChecker, AccountWindowChecker,
+    AmountProvenance, ArithmeticOverflowChecker, BadrandomnessChecker, BalanceConservationChecker,
+    DeserializationOffsetChecker, DistinctAccountsChecker, FloatInMoneyPathChecker,
+    ForbiddenCallChecker, InstructionDataBoundsChecker, LamportUnderflowChecker, LedgerChecker,
+    MissingSignerChecker, NoOpTransferChecker, NonPersistentStateChecker,
+    NumericalPrecisionErrorChecker, OwnerCheckChecker, ReentrancyChecker, RentDustChecker,
+    ReplayProtectionChecker, ResultUnusedChecker, SignerSeedsChecker, SolUnitChecker,
+    TimeManipulationChecker, TimeUnitChecker, TokenConservationChecker, TruncationChecker,
+    TypeCosplayChecker, UncheckedIndexChecker, UninvokedInstructionChecker,
+    UnvalidatedDeserializationChecker, WritableCheckChecker,
+};
+use crate::options::{Options, OutputFormat};
+use crate::solana_compat::{AccountFieldBorrows, AnchorAccounts};
+
+/// How serious a finding is when the configuration does not say otherwise.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Note,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// The level of the diagnostics of findings with this severity.
+    pub fn level(self) -> Level {
+        match self {
+            Severity::Note => Level::Note,
+            Severity::Warning => Level::Warning,
+            Severity::Error => Level::Error,
+        }
+    }
+
+    /// The severity of a finding whose diagnostic has the given level, if it has one.
+    pub fn of_level(level: Level) -> Option<Severity> {
+        match level {
+            Level::Note => Some(Severity::Note),
+            Level::Warning => Some(Severity::Warning),
+            Level::Error => Some(Severity::Error),
+            _ => None,
+        }
+    }
+}
+
+/// Describes a checker and the findings it can report.
+#[derive(Debug, Serialize)]
+pub struct CheckerInfo {
+    /// The name used to refer to the checker in options and output.
+    pub name: &'static str,
+    /// A one line description of what the checker looks for.
+    pub description: &'static str,
+    pub default_severity: Severity,
+    /// The codes of the findings that the checker can emit.
+    pub codes: &'static [&'static str],
+    /// The cargo features that must be enabled for the checker to be effective.
+    pub required_features: &'static [&'static str],
+}
+
+/// All of the checkers known to HEPHA.
+pub const CHECKERS: &[CheckerInfo] = &[
+    CheckerInfo {
+        name: "arithmetic",
+        description: "Arithmetic operations that may overflow, aborting with overflow checks and wrapping around without.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-ARITH-ABORT", "HEPHA-ARITH-WRAP"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "reentrancy",
+        description: "Balances of accounts that are updated after the lamports of the account have been transferred, or the account has been passed to an invoked program.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-REENTRANCY"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "time_manipulation",
+        description: "Modulos or bit masks of the timestamp or the slot of the clock sysvar that decide a transfer of lamports, and combinations of the timestamp with the slot that are used as random values. Validators can influence both.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-TIME-MANIPULATION"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "bad_randomness",
+        description: "Random values from predictable seeds, such as the clock, slot hashes or constants, that decide a transfer of lamports. Other uses of such values are only noted.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-BAD-RANDOMNESS"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "numerical_precision",
+        description: "Rounding of floating point values, and integer divisions whose quotient is then multiplied, which amplifies the remainder that the division discards.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-NUMERICAL-PRECISION"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "time_unit",
+        description: "Arithmetic that mixes seconds, slots and epochs.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-TIME-UNIT-MIXING"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "account_window",
+        description: "Accounts read by index in one branch but skipped by an iterator in another.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-ACCOUNT-WINDOW"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "token_conservation",
+        description: "Token amounts of Pack-modeled accounts that are credited or debited without a matching adjustment, mint or burn.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-TOKEN-CONSERVATION"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "result_unused",
+        description: "Results of fallible calls that are dropped without looking at the error before the state is updated.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-RESULT-UNUSED"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "uninvoked_instruction",
+        description: "Instructions of the system program or of a token program that are built, such as with system_instruction::transfer, and then dropped without being passed to invoke or invoke_signed, so that no lamports or tokens move.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-UNINVOKED-INSTRUCTION"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "account_data_length",
+        description: "Writes into the data of an account whose byte range is not known to end within the length of the data.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-ACCOUNT-DATA-LENGTH"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "lamport_underflow",
+        description: "Subtractions from the lamports of an account, or from a balance kept in a map, that are not preceded by a comparison of the balance with the amount.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-LAMPORT-UNDERFLOW"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "ledger",
+        description: "Functions that return Ok after changing the total of the balances kept in maps and the lamports of the account that they are declared to add up to by different amounts. Only with --ledger-account.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-LEDGER-DRIFT"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "balance_conservation",
+        description: "Functions that return Ok after moving amounts between the balances kept in maps, or between the lamports of accounts, with credits and debits that do not cancel out.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-BALANCE-CONSERVATION"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "replay_protection",
+        description: "Transfers and invocations that act on an identifier from the instruction data, such as an order or claim id, without a guard that checks a processed marker or a stored identifier first.",
+        default_severity: Severity::Note,
+        codes: &["HEPHA-REPLAY-PROTECTION"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "owner_check",
+        description: "Data of accounts that is borrowed by an entrypoint without checking first that the owner of the account is the program, so that a fake account can be passed.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-OWNER-CHECK"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "writable_check",
+        description: "Lamports or data of accounts that an entrypoint changes without reading first whether the caller passed the account as writable, so that the runtime rejects the change far from its cause.",
+        default_severity: Severity::Note,
+        codes: &["HEPHA-WRITABLE-CHECK"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "signer_seeds",
+        description: "Calls of invoke_signed by an entrypoint that move lamports or tokens with the signature of a program derived address whose seeds the caller controls, or that sign for a derived address that is not compared with the key of any account passed to the call.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-SIGNER-SEEDS"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "unchecked_index",
+        description: "Indexing of local arrays and slices by an entrypoint with an index that is read from the instruction data or from the data of an account the caller controls, and that is not known to be within the length of the collection.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-UNCHECKED-INDEX"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "distinct_accounts",
+        description: "Calls of functions that move lamports between two of their account parameters, with accounts whose keys are not compared before the call, so that the same account can be passed twice and the lamports do not move.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-DISTINCT-ACCOUNTS"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "account_aliasing",
+        description: "Mutable borrows of the lamports or data of two accounts whose keys are not known to differ where the second borrow is made, so that the same account can be passed twice and a debit and a credit cancel out or one write overwrites the other.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-ACCOUNT-ALIASING"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "sol_units",
+        description: "Amounts in lamports that are multiplied by LAMPORTS_PER_SOL again, and amounts in lamports that are added to or compared with amounts in SOL.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-SOL-UNIT-MIXING"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "rent_dust",
+        description: "Accounts created by a function, with create_account or with allocate and assign, or whose data it reallocates, that are credited with lamports that are not known to reach the rent-exempt minimum for their data, so that the runtime may reap them with the lamports.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-RENT-DUST"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "no_op_transfer",
+        description: "Changes of the lamports of an account by an amount that is zero, and transfers of the system program of zero lamports or from an account to itself, on every path that reaches them, which have no effect.",
+        default_severity: Severity::Note,
+        codes: &["HEPHA-NO-OP-TRANSFER"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "instruction_data_bounds",
+        description: "Indexing and slicing of the instruction data of an entrypoint, such as instruction_data[1..9], that is not known to be within the length of the data, so that instruction data that is too short aborts execution.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-INSTRUCTION-DATA-BOUNDS"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "deserialization_offsets",
+        description: "Reads of the instruction data of an entrypoint at constant offsets, after a prefix of it is deserialized with Borsh or bincode, where the first offset after the prefix is not the serialized size of the deserialized type, or that type has no fixed serialized size.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-DESERIALIZATION-OFFSET"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "non_persistent_state",
+        description: "Maps, sets, vectors and structs that an entrypoint creates, such as with HashMap::new(), rather than deserializes from the data of an account, and passes to a function that transfers lamports, so that the state they hold is lost at the end of every instruction.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-NON-PERSISTENT-STATE"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "unvalidated_deserialization",
+        description: "Values deserialized with BorshDeserialize::try_from_slice from slices that are not known to be as long as their shortest serialization, and reads of the instruction data past a deserialized prefix, in an arm of a match on the variant of the deserialized value, that the arm does not check the length of the data for.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-UNVALIDATED-DESERIALIZATION"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "forbidden_call",
+        description: "Calls in entrypoints of foreign functions that the registry of foreign models, extended with --models, marks as forbidden in entrypoints, such as std::process::exit or std::thread::spawn.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-FORBIDDEN-CALL"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "missing_signer",
+        description: "Lamports of UncheckedAccount fields that the handler of an instruction of an Anchor program changes, when the Accounts struct of the instruction has no Signer field, so that anyone can move the lamports of the accounts that they pass.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-MISSING-SIGNER"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "account_constraint",
+        description: "UncheckedAccount and AccountInfo fields of the Accounts struct of an Anchor instruction whose lamports or data the handler changes, when no has_one, address or constraint constraint validates them, so that the caller can pass any account.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-ACCOUNT-CONSTRAINT"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "type_cosplay",
+        description: "Deserializations with Borsh or bincode of two structs with the same layout from the data of accounts, when neither checks a discriminant in the data, such as the 8 bytes of an Anchor discriminator, or both the owner and the key of the account, so that an account of one type can be passed as the other.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-TYPE-COSPLAY"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "account_close",
+        description: "Writes of the lamports of an account that leave it without lamports, which close it, on paths that do not overwrite its data from the start, with zeros or a closed discriminator, or through the close constraint of Anchor, so that the account can be revived in the same transaction.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-ACCOUNT-CLOSE"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "truncation",
+        description: "Integer casts of amounts that are untrusted input, such as a u64 read from the instruction data cast to u32, or an i64 cast to u64, whose operands are not known to be within the range of the result type, so that they are silently truncated. Conversions with try_into or try_from whose results are unwrapped count as casts that may panic.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-TRUNCATION"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "division_by_zero",
+        description: "Divisions and remainders whose divisor is untrusted input, such as an amount read from the instruction data, that is not known to be non-zero where the operation is reached, so that the caller can make the program panic. Divisions with checked_div and its kin are not reported.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-DIV-BY-ZERO"],
+        required_features: &[],
+    },
+    CheckerInfo {
+        name: "float_in_money_path",
+        description: "Amounts that are computed with floating point arithmetic, such as a float cast back to an integer, and then written to the lamports of an account or to a map of balances, or passed to an instruction of the system program or of a token program. Floats that are only logged are not reported.",
+        default_severity: Severity::Warning,
+        codes: &["HEPHA-FLOAT-MONEY"],
+        required_features: &[],
+    },
+];
+
+/// Returns the description of the checker that can emit findings with the given code.
+pub fn checker_for_code(code: &str) -> Option<&'static CheckerInfo> {
+    CHECKERS.iter().find(|c| c.codes.contains(&code))
+}
+
+/// Renders the list of checkers in the given format.
+pub fn list_checkers(format: OutputFormat) -> String {
+    if format == OutputFormat::Json {
+        serde_json::to_string_pretty(CHECKERS).expect("checker list should serialize")
+    } else {
+        let mut result = String::new();
+        for checker in CHECKERS {
+            result.push_str(&format!(
+                "{} ({:?}): {}\n    codes: {}\n",
+                checker.name,
+                checker.default_severity,
+                checker.description,
+                checker.codes.join(", ")
+            ));
+            if !checker.required_features.is_empty() {
+                result.push_str(&format!(
+                    "    requires: {}\n",
+                    checker.required_features.join(", ")
+                ));
+            }
+        }
+        result
+    }
+}
+
+/// Returns the code at the start of the message of the diagnostic of a finding, such as
+/// HEPHA-REENTRANCY.
+pub fn finding_code(message: &str) -> Option<&str> {
+    let code = message.strip_prefix('[')?.split_once(']')?.0;
+    code.starts_with("HEPHA-").then_some(code)
+}
+
+/// What gave rise to a buffered diagnostic, by which the diagnostics of a run are counted.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum DiagnosticOrigin {
+    /// A finding of the checker with the given name.
+    Checker(&'static str),
+    /// A condition, assertion or precondition that may not hold, or another diagnostic of the
+    /// abstract interpreter itself.
+    Verification,
+    /// The analysis of a function that timed out.
+    Timeout,
+}
+
+impl DiagnosticOrigin {
+    /// The origin of a diagnostic with the given message, which is a checker if the message
+    /// starts with the code of one of its findings.
+    pub fn of_message(message: &str) -> DiagnosticOrigin {
+        finding_code(message)
+            .and_then(checker_for_code)
+            .map_or(DiagnosticOrigin::Verification, |checker| {
+                DiagnosticOrigin::Checker(checker.name)
+            })
+    }
+
+    /// The name under which the diagnostics of this origin are counted.
+    pub fn name(self) -> &'static str {
+        match self {
+            DiagnosticOrigin::Checker(name) => name,
+            DiagnosticOrigin::Verification => "verification",
+            DiagnosticOrigin::Timeout => "timeout",
+        }
+    }
+}
+
+/// Renders the explanation of the given finding code in the given format, or returns None if
+/// no checker emits findings with that code.
+pub fn explain(code: &str, format: OutputFormat) -> Option<String> {
+    let checker = checker_for_code(code)?;
+    if format == OutputFormat::Json {
+        let explanation = serde_json::json!({ "code": code, "checker": checker });
+        Some(serde_json::to_string_pretty(&explanation).expect("explanation should serialize"))
+    } else {
+        let mut result = format!(
+            "{code}: reported by the {} checker ({:?})\n    {}\n",
+            checker.name, checker.default_severity, checker.description
+        );
+        if !checker.required_features.is_empty() {
+            result.push_str(&format!(
+                "    requires: {}\n",
+                checker.required_features.join(", ")
+            ));
+        }
+        Some(result)
+    }
+}
+
+/// Returns the severity of a finding with the given code and severity once the given options are
+/// applied: Error if the code is denied, or else the severity that the configuration file gives
+/// the code, if it gives one.
+pub fn configured_severity(code: &str, severity: Severity, options: &Options) -> Severity {
+    if options.deny.iter().any(|denied_code| denied_code == code) {
+        Severity::Error
+    } else {
+        options.severities.get(code).copied().unwrap_or(severity)
+    }
+}
+
+/// A vulnerability found by one of the checkers, ready to be turned into a diagnostic.
+#[derive(Debug)]
+pub struct Finding {
+    pub code: &'static str,
+    pub message: String,
+    pub span: Span,
+    /// Notes on related locations, such as the entrypoint that the finding is in.
+    pub notes: Vec<(Span, String)>,
+    /// A machine applicable change that resolves the finding, with a message that describes it.
+    pub suggestion: Option<(String, Vec<(Span, String)>)>,
+    /// True if the finding is only a note, whatever the severity of the checker that emits it.
+    pub downgraded: bool,
+    /// The provenance classes of the values that the finding is about, such as instruction_data.
+    pub provenance: Vec<&'static str>,
+}
+
+impl Finding {
+    /// The severity of the checker that emits findings with this code, unless the finding is
+    /// downgraded to a note.
+    pub fn severity(&self) -> Severity {
+        if self.downgraded {
+            return Severity::Note;
+        }
+        checker_for_code(self.code).map_or(Severity::Warning, |c| c.default_severity)
+    }
+
+    /// The severity of this finding once the given options are applied.
+    pub fn configured_severity(&self, options: &Options) -> Severity {
+        configured_severity(self.code, self.severity(), options)
+    }
+
+    /// The message of the diagnostic for this finding, which starts with its code.
+    pub fn diagnostic_message(&self) -> String {
+        format!("[{}] {}", self.code, self.message)
+    }
+}
+
+/// The state of all of the checkers that observe the analysis of a function body.
+pub struct ContractCheckers<'tcx> {
+    pub reentrancy: ReentrancyChecker<'tcx>,
+    pub time_manipulation: TimeManipulationChecker,
+    pub bad_randomness: BadrandomnessChecker,
+    pub numerical_precision: NumericalPrecisionErrorChecker,
+    pub time_unit: TimeUnitChecker,
+    pub account_window: AccountWindowChecker,
+    pub account_fields: AccountFieldBorrows,
+    pub amount_provenance: AmountProvenance,
+    pub arithmetic_overflow: ArithmeticOverflowChecker,
+    pub token_conservation: TokenConservationChecker,
+    pub result_unused: ResultUnusedChecker,
+    pub account_data_length: AccountDataLengthChecker,
+    pub lamport_underflow: LamportUnderflowChecker,
+    pub ledger: LedgerChecker,
+    pub balance_conservation: BalanceConservationChecker,
+    pub replay_protection: ReplayProtectionChecker,
+    pub owner_check: OwnerCheckChecker,
+    pub writable_check: WritableCheckChecker,
+    pub signer_seeds: SignerSeedsChecker,
+    pub unchecked_index: UncheckedIndexChecker,
+    pub distinct_accounts: DistinctAccountsChecker,
+    pub sol_units: SolUnitChecker,
+    pub rent_dust: RentDustChecker,
+    pub no_op_transfer: NoOpTransferChecker,
+    pub instruction_data_bounds: InstructionDataBoundsChecker,
+    pub deserialization_offsets: DeserializationOffsetChecker,
+    pub non_persistent_state: NonPersistentStateChecker,
+    pub unvalidated_deserialization: UnvalidatedDeserializationChecker,
+    pub uninvoked_instruction: UninvokedInstructionChecker,
+    pub forbidden_call: ForbiddenCallChecker,
+    pub anchor_accounts: AnchorAccounts,
+    pub missing_signer: MissingSignerChecker,
+    pub account_constraint: AccountConstraintChecker,
+    pub type_cosplay: TypeCosplayChecker,
+    pub account_close: AccountCloseChecker,
+    pub truncation: TruncationChecker,
+    pub float_in_money_path: FloatInMoneyPathChecker,
+}
+
+impl Default for ContractCheckers<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'tcx> ContractCheckers<'tcx> {
+    pub fn new() -> ContractCheckers<'tcx> {
+        ContractCheckers {
+            reentrancy: ReentrancyChecker::new(),
+            time_manipulation: TimeManipulationChecker::new(),
+            bad_randomness: BadrandomnessChecker::new(),
+            numerical_precision: NumericalPrecisionErrorChecker::new(),
+            time_unit: TimeUnitChecker::new(),
+            account_window: AccountWindowChecker::new(),
+            account_fields: AccountFieldBorrows::default(),
+            amount_provenance: AmountProvenance::default(),
+            arithmetic_overflow: ArithmeticOverflowChecker::default(),
+            token_conservation: TokenConservationChecker::default(),
+            result_unused: ResultUnusedChecker::default(),
+            account_data_length: AccountDataLengthChecker::default(),
+            lamport_underflow: LamportUnderflowChecker::default(),
+            ledger: LedgerChecker::default(),
+            balance_conservation: BalanceConservationChecker::default(),
+            replay_protection: ReplayProtectionChecker::default(),
+            owner_check: OwnerCheckChecker::default(),
+            writable_check: WritableCheckChecker::default(),
+            signer_seeds: SignerSeedsChecker::default(),
+            unchecked_index: UncheckedIndexChecker::default(),
+            distinct_accounts: DistinctAccountsChecker::default(),
+            sol_units: SolUnitChecker::default(),
+            rent_dust: RentDustChecker::default(),
+            no_op_transfer: NoOpTransferChecker::default(),
+            instruction_data_bounds: InstructionDataBoundsChecker::default(),
+            deserialization_offsets: DeserializationOffsetChecker::default(),
+            non_persistent_state: NonPersistentStateChecker::default(),
+            unvalidated_deserialization: UnvalidatedDeserializationChecker::default(),
+            uninvoked_instruction: UninvokedInstructionChecker::default(),
+            forbidden_call: ForbiddenCallChecker::default(),
+            anchor_accounts: AnchorAccounts::default(),
+            missing_signer: MissingSignerChecker::default(),
+            account_constraint: AccountConstraintChecker::default(),
+            type_cosplay: TypeCosplayChecker::default(),
+            account_close: AccountCloseChecker::default(),
+            truncation: TruncationChecker::default(),
+            float_in_money_path: FloatInMoneyPathChecker::default(),
+        }
+    }
+
+    /// Returns the findings of all checkers, once the body has been fully visited.
+    pub fn findings(&mut self, mir: &mir::Body<'tcx>) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        if let Some((span, trace)) = self.reentrancy.trace(mir) {
+            findings.push(Finding {
+                code: "HEPHA-REENTRANCY",
+                message: "possible reentrancy for the smart contract".to_string(),
+                span,
+                notes: trace.into_notes(),
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        // A finding is reported at the read of the clock, with a note at the sensitive use of it
+        let time_uses = [
+            (
+                self.time_manipulation.reduced_transfer(mir),
+                "a modulo of the clock decides a transfer of lamports",
+                "the transfer is decided here, by a modulo or a bit mask of the timestamp or the slot",
+            ),
+            (
+                self.time_manipulation.random_source_span,
+                "the timestamp and the slot are combined into a random value",
+                "they are combined here, into a value that is used later",
+            ),
+        ];
+        for (use_span, what, note) in time_uses {
+            let Some(use_span) = use_span else {
+                continue;
+            };
+            // The clock may be read by the caller
+            let (span, notes) = match self.time_manipulation.clock_read_span {
+                Some(read_span) => (read_span, vec![(use_span, note.to_string())]),
+                None => (use_span, Vec::new()),
+            };
+            findings.push(Finding {
+                code: "HEPHA-TIME-MANIPULATION",
+                message: format!("possible time manipulation for the smart contract, {what}"),
+                span,
+                notes,
+                suggestion: None,
+                downgraded: false,
+                provenance: vec!["clock"],
+            });
+        }
+        if let Some(random_span) = self.bad_randomness.bad_randomness_span {
+            // A random value that decides no transfer of lamports, such as one that is only
+            // logged, is only worth a note
+            let finding = match self.bad_randomness.trace(mir) {
+                (Some(span), trace) => Finding {
+                    code: "HEPHA-BAD-RANDOMNESS",
+                    message: "possible bad randomness for the smart contract, a random value that validators can predict decides a transfer of lamports".to_string(),
+                    span,
+                    notes: trace.into_notes(),
+                    suggestion: None,
+                    downgraded: false,
+                    provenance: vec!["clock"],
+                },
+                (None, _) => Finding {
+                    code: "HEPHA-BAD-RANDOMNESS",
+                    message: "random value that validators can predict, which decides no transfer of lamports, for the smart contract".to_string(),
+                    span: random_span,
+                    notes: Vec::new(),
+                    suggestion: None,
+                    downgraded: true,
+                    provenance: vec!["clock"],
+                },
+            };
+            findings.push(finding);
+        }
+        if self.numerical_precision.check() {
+            findings.push(Finding {
+                code: "HEPHA-NUMERICAL-PRECISION",
+                message: "possible numerical precision error for the smart contract".to_string(),
+                span: self.numerical_precision.numerical_precision_error_span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        let unchecked_divisions = &self.numerical_precision.unchecked_divisions;
+        if let Some((span, division_span)) = self
+            .numerical_precision
+            .division_before_multiplication
+            .filter(|(_, division_span)| !unchecked_divisions.contains(division_span))
+        {
+            findings.push(Finding {
+                code: "HEPHA-NUMERICAL-PRECISION",
+                message: "possible numerical precision error for the smart contract, the result of an integer division is multiplied, which amplifies the remainder that the division discards; multiply first, as in `a * c / b` instead of `a / b * c`".to_string(),
+                span,
+                notes: vec![(division_span, "the division is here".to_string())],
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some((span, _, source_span)) = self.float_in_money_path.float_amount {
+            findings.push(Finding {
+                code: "HEPHA-FLOAT-MONEY",
+                message: self.float_in_money_path.describe(),
+                span,
+                notes: vec![(
+                    source_span,
+                    "the amount is computed with floating point arithmetic here; compute it with fixed-point integer arithmetic, such as integers scaled by a power of ten, instead".to_string(),
+                )],
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if self.time_unit.check() {
+            findings.push(Finding {
+                code: "HEPHA-TIME-UNIT-MIXING",
+                message: self.time_unit.describe(),
+                span: self.time_unit.time_unit_mixing_span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        self.account_window.find_dropped_position(mir);
+        if self.account_window.check() {
+            findings.push(Finding {
+                code: "HEPHA-ACCOUNT-WINDOW",
+                message: self.account_window.describe(),
+                span: self.account_window.dropped_account_span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some((_, span)) = self.token_conservation.unbalanced_adjustment() {
+            findings.push(Finding {
+                code: "HEPHA-TOKEN-CONSERVATION",
+                message: self.token_conservation.describe(),
+                span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        self.result_unused.find_dropped_result(mir);
+        if let Some(span) = self.result_unused.dropped_result_span {
+            findings.push(Finding {
+                code: "HEPHA-RESULT-UNUSED",
+                message: "possible unchecked error of a call whose result is dropped before the state is updated, for the smart contract".to_string(),
+                span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        self.uninvoked_instruction.find_uninvoked_instruction(mir);
+        if let Some(span) = self.uninvoked_instruction.uninvoked_span {
+            findings.push(Finding {
+                code: "HEPHA-UNINVOKED-INSTRUCTION",
+                message: "possible transfer that never happens, because the instruction built by this call is dropped without being passed to invoke or invoke_signed, for the smart contract".to_string(),
+                span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some((span, _)) = self.account_data_length.overflowing_write {
+            findings.push(Finding {
+                code: "HEPHA-ACCOUNT-DATA-LENGTH",
+                message: self.account_data_length.describe(),
+                span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some((span, _, _)) = self.lamport_underflow.underflow {
+            findings.push(Finding {
+                code: "HEPHA-LAMPORT-UNDERFLOW",
+                message: self.lamport_underflow.describe(),
+                span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some((span, exit_span)) = self.ledger.drift {
+            findings.push(Finding {
+                code: "HEPHA-LEDGER-DRIFT",
+                message: "possible drift of the balances from the lamports of the account that they are declared to add up to, for the smart contract".to_string(),
+                span,
+                notes: vec![(
+                    exit_span,
+                    "the function may return Ok here with the balances and the lamports changed by different amounts".to_string(),
+                )],
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some((span, exit_span, _)) = self.balance_conservation.imbalance {
+            findings.push(Finding {
+                code: "HEPHA-BALANCE-CONSERVATION",
+                message: self.balance_conservation.describe(),
+                span,
+                notes: vec![(
+                    exit_span,
+                    "the function may return Ok here with the total of the balances changed"
+                        .to_string(),
+                )],
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some((identifier, span)) = &self.replay_protection.unprotected_effect {
+            findings.push(Finding {
+                code: "HEPHA-REPLAY-PROTECTION",
+                message: format!("no replay protection detected for externally provided identifier `{identifier}`, for the smart contract"),
+                span: *span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: vec!["untrusted_input"],
+            });
+        }
+        if let Some((span, entrypoint_span)) = self.owner_check.unchecked_borrow {
+            let guard = self.owner_check.guard.take();
+            findings.push(Finding {
+                code: "HEPHA-OWNER-CHECK",
+                message: "possible fake account, whose data is read without checking that its owner is the program".to_string(),
+                span,
+                notes: vec![(entrypoint_span, "in this entrypoint, which does not compare the owner of the account with the program id".to_string())],
+                suggestion: guard.map(|parts| ("check the owner of the account".to_string(), parts)),
+                downgraded: false,
+                provenance: vec!["account_data"],
+            });
+        }
+        if let Some((span, entrypoint_span)) = self.writable_check.unchecked_write {
+            findings.push(Finding {
+                code: "HEPHA-WRITABLE-CHECK",
+                message: "possible write to an account that is not passed as writable, whose lamports or data are changed without reading its is_writable flag".to_string(),
+                span,
+                notes: vec![(entrypoint_span, "in this entrypoint, which does not check that the account is writable".to_string())],
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some((span, seeds_span, description)) = &self.signer_seeds.caller_chosen_signer {
+            findings.push(Finding {
+                code: "HEPHA-SIGNER-SEEDS",
+                message: "possible signature of a program derived address that the caller chooses, whose signer seeds the caller controls, in a transfer of value for the smart contract".to_string(),
+                span: *span,
+                notes: vec![(*seeds_span, description.clone())],
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some((span, derivation_span)) = self.signer_seeds.unmatched_signer {
+            findings.push(Finding {
+                code: "HEPHA-SIGNER-SEEDS",
+                message: "possible signature of a program derived address that is not one of the accounts passed to the invoked program, for the smart contract".to_string(),
+                span,
+                notes: vec![(derivation_span, "the address is derived here, but it is not compared with the key of any account passed to invoke_signed".to_string())],
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some((span, collection)) = &self.unchecked_index.unchecked_index {
+            findings.push(Finding {
+                code: "HEPHA-UNCHECKED-INDEX",
+                message: format!("possible index out of bounds of {collection} with an index that the caller controls, which aborts execution, for the smart contract"),
+                span: *span,
+                notes: self.unchecked_index.notes.clone(),
+                suggestion: None,
+                downgraded: false,
+                provenance: self.unchecked_index.provenance.into_iter().collect(),
+            });
+        }
+        if let Some((span, requirement)) = self.distinct_accounts.unchecked_call(mir) {
+            findings.push(Finding {
+                code: "HEPHA-DISTINCT-ACCOUNTS",
+                message: format!("possible transfer of lamports from an account to itself, {requirement} but their keys are not compared before the call, for the smart contract"),
+                span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some(span) = self.distinct_accounts.aliased_borrow {
+            findings.push(Finding {
+                code: "HEPHA-ACCOUNT-ALIASING",
+                message: "accounts may alias; add a key inequality check".to_string(),
+                span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some(span) = self.sol_units.double_conversion_span {
+            findings.push(Finding {
+                code: "HEPHA-SOL-UNIT-MIXING",
+                message: "possible conversion to lamports of an amount that is already in lamports, which multiplies it by LAMPORTS_PER_SOL twice, for the smart contract".to_string(),
+                span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some(span) = self.sol_units.mixed_units_span {
+            findings.push(Finding {
+                code: "HEPHA-SOL-UNIT-MIXING",
+                message: "possible mixing of an amount in lamports with an amount in SOL for the smart contract".to_string(),
+                span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some((span, creation_span, _, shortfall)) = &self.rent_dust.underfunded {
+            findings.push(Finding {
+                code: "HEPHA-RENT-DUST",
+                message: self.rent_dust.describe(),
+                span: *span,
+                notes: vec![(*creation_span, format!("the data of the account is allocated here, and the credit may fall short of the rent-exempt minimum by {shortfall} lamports"))],
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some((span, _)) = self.instruction_data_bounds.out_of_bounds {
+            findings.push(Finding {
+                code: "HEPHA-INSTRUCTION-DATA-BOUNDS",
+                message: self.instruction_data_bounds.describe(),
+                span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: vec!["instruction_data"],
+            });
+        }
+        if let Some((span, description)) = self.deserialization_offsets.misaligned_read() {
+            let notes = self
+                .deserialization_offsets
+                .deserialization
+                .iter()
+                .map(|(deserialization_span, ..)| {
+                    (
+                        *deserialization_span,
+                        "the prefix of the instruction data is deserialized here".to_string(),
+                    )
+                })
+                .collect();
+            findings.push(Finding {
+                code: "HEPHA-DESERIALIZATION-OFFSET",
+                message: description,
+                span,
+                notes,
+                suggestion: None,
+                downgraded: false,
+                provenance: vec!["instruction_data"],
+            });
+        }
+        if let Some((span, description, transfer_span)) =
+            self.non_persistent_state.unpersisted_state()
+        {
+            findings.push(Finding {
+                code: "HEPHA-NON-PERSISTENT-STATE",
+                message: description,
+                span,
+                notes: vec![
+                    (
+                        transfer_span,
+                        "it is passed to a function that transfers lamports here".to_string(),
+                    ),
+                    (
+                        span,
+                        "serialize it into the data of an account, such as with Borsh, and deserialize it from there in every instruction".to_string(),
+                    ),
+                ],
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some((span, description)) = self.unvalidated_deserialization.short_slice() {
+            findings.push(Finding {
+                code: "HEPHA-UNVALIDATED-DESERIALIZATION",
+                message: description,
+                span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some((span, description, deserialization_span)) =
+            self.unvalidated_deserialization.unguarded_variant_read(mir)
+        {
+            findings.push(Finding {
+                code: "HEPHA-UNVALIDATED-DESERIALIZATION",
+                message: description,
+                span,
+                notes: vec![(
+                    deserialization_span,
+                    "the variant is deserialized here".to_string(),
+                )],
+                suggestion: None,
+                downgraded: false,
+                provenance: vec!["instruction_data"],
+            });
+        }
+        if let Some((span, _)) = self.no_op_transfer.no_op {
+            findings.push(Finding {
+                code: "HEPHA-NO-OP-TRANSFER",
+                message: self.no_op_transfer.describe(),
+                span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some((span, account)) = &self.missing_signer.unsigned_change {
+            findings.push(Finding {
+                code: "HEPHA-MISSING-SIGNER",
+                message: format!("possible missing signer check for the smart contract, the lamports of {account}, an UncheckedAccount, are changed by an instruction that no account signs"),
+                span: *span,
+                notes: vec![(
+                    *span,
+                    "make the authority of the account a Signer field of the Accounts struct".to_string(),
+                )],
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some((declaration, account, change)) = &self.account_constraint.unconstrained_change
+        {
+            findings.push(Finding {
+                code: "HEPHA-ACCOUNT-CONSTRAINT",
+                message: format!("possible missing constraint on {account}, an UncheckedAccount whose lamports or data the instruction changes, for the smart contract"),
+                span: *declaration,
+                notes: vec![
+                    (*change, "the account is changed here".to_string()),
+                    (
+                        *declaration,
+                        "validate the account with a has_one, address or constraint constraint".to_string(),
+                    ),
+                ],
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        for (span, name, other_span, other_name) in &self.type_cosplay.cosplays {
+            findings.push(Finding {
+                code: "HEPHA-TYPE-COSPLAY",
+                message: format!("possible type cosplay for the smart contract, {name} is deserialized from the data of an account without a discriminant, and {other_name} has the same layout"),
+                span: *span,
+                notes: vec![(
+                    *other_span,
+                    format!("{other_name} is deserialized here, from data that {name} also deserializes from"),
+                )],
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        if let Some(span) = self.account_close.revivable_close(mir) {
+            findings.push(Finding {
+                code: "HEPHA-ACCOUNT-CLOSE",
+                message: "possible revival of a closed account for the smart contract, its lamports are drained but its data is not overwritten".to_string(),
+                span,
+                notes: vec![(
+                    span,
+                    "fill the data of the account with zeros, or write a closed discriminator at its start, on the same path".to_string(),
+                )],
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        for (span, source, target, guaranteed, unwrap_span) in &self.truncation.truncations {
+            let notes = match unwrap_span {
+                Some(unwrap_span) => vec![(
+                    *unwrap_span,
+                    "the result of the conversion is unwrapped here, which may panic".to_string(),
+                )],
+                None => Vec::new(),
+            };
+            findings.push(Finding {
+                code: "HEPHA-TRUNCATION",
+                message: TruncationChecker::describe(*source, *target, *guaranteed),
+                span: *span,
+                notes,
+                suggestion: None,
+                downgraded: false,
+                provenance: vec!["untrusted_input"],
+            });
+        }
+        for (span, callee) in &self.forbidden_call.calls {
+            findings.push(Finding {
+                code: "HEPHA-FORBIDDEN-CALL",
+                message: format!("call of {callee}, which the models of foreign functions forbid in an entrypoint, for the smart contract"),
+                span: *span,
+                notes: Vec::new(),
+                suggestion: None,
+                downgraded: false,
+                provenance: Vec::new(),
+            });
+        }
+        findings
+    }
+}
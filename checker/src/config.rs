@@ -0,0 +1,267 @@
+// The configuration file of HEPHA, hepha.toml, which gathers the options that would otherwise be
+// given as flags, the severities of findings and the files whose findings are suppressed. cargo
+// hepha passes the hepha.toml at the root of the workspace on with HEPHA_CONFIG, unless --config
+// names another file. The options of the file are turned into flags that come before those of
+// HEPHA_FLAGS, so that the flags take precedence and are checked in the same way.
+//
+// [options]
+// diag = "library"
+// max_elements_to_track = 200
+// deny = ["HEPHA-REENTRANCY"]
+//
+// [severities]
+// HEPHA-SOL-UNIT-MIXING = "error"
+//
+// [[suppress]]
+// file = "src/legacy.rs"
+// checkers = ["arithmetic"]
+
+use std::collections::BTreeMap;
+
+use rustc_session::EarlyDiagCtxt;
+use serde::{Deserialize, Serialize};
+
+use crate::checker_registry::{self, Severity, CHECKERS};
+use crate::options::{DiagLevel, Options, OutputFormat};
+
+/// The environment variable with which cargo hepha passes on the configuration file of the
+/// workspace.
+pub const CONFIG_VARIABLE: &str = "HEPHA_CONFIG";
+
+/// The contents of a configuration file.
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Config {
+    pub options: ConfigOptions,
+    /// The severities of the findings with the given codes, such as HEPHA-REENTRANCY.
+    pub severities: BTreeMap<String, Severity>,
+    /// The files whose findings are dropped.
+    pub suppress: Vec<FileSuppression>,
+    // The keys that are not known, which are warned about rather than rejected
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+/// The options of a configuration file, named like their flags but with underscores. Options
+/// that are flags without values can only be turned on.
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct ConfigOptions {
+    pub diag: Option<String>,
+    pub body_analysis_timeout: Option<f64>,
+    pub crate_analysis_timeout: Option<u64>,
+    pub max_elements_to_track: Option<usize>,
+    pub max_path_length: Option<usize>,
+    pub max_outer_fixpoint_iterations: Option<usize>,
+    pub max_loop_unrolling: Option<usize>,
+    pub max_recursive_iterations: Option<usize>,
+    pub constant_time: Option<String>,
+    pub ledger_account: Option<String>,
+    pub adversarial_public_api: Option<bool>,
+    pub dual_build: Option<bool>,
+    pub factor_exits: Option<bool>,
+    pub batch_solver_queries: Option<bool>,
+    pub tighten_intervals: Option<bool>,
+    pub quiet: Option<bool>,
+    pub deny: Vec<String>,
+    pub fail_on: Option<String>,
+    pub diag_format: Option<String>,
+    pub baseline: Option<String>,
+    pub models: Option<String>,
+    pub incremental: Option<bool>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+/// The checkers whose findings are dropped in the source files whose names end with the given
+/// path, in the same way as if every function of the files started with suppress!.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct FileSuppression {
+    pub file: String,
+    pub checkers: Vec<String>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+impl Config {
+    /// Reads the configuration file at the given path, or the one that cargo hepha found at the
+    /// root of the workspace if no path is given, and warns about the keys that it does not know.
+    /// Returns None if there is no configuration file.
+    pub fn load(path: Option<&str>, handler: &EarlyDiagCtxt) -> Option<Config> {
+        let path = path
+            .map(str::to_string)
+            .or_else(|| std::env::var(CONFIG_VARIABLE).ok())?;
+        let text = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| handler.early_fatal(format!("cannot read {path}: {e}")));
+        let config = Config::parse(&text).unwrap_or_else(|e| {
+            handler.early_fatal(format!("invalid configuration in {path}: {e}"))
+        });
+        for key in config.unknown_keys() {
+            handler.early_warn(format!("{path}: unknown key `{key}` is ignored"));
+        }
+        Some(config)
+    }
+
+    /// Parses the contents of a configuration file. The errors give the line and the column of
+    /// the offending value.
+    pub fn parse(text: &str) -> Result<Config, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    /// Returns the dotted names of the keys that are not known.
+    pub fn unknown_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.unknown.keys().cloned().collect();
+        keys.extend(
+            self.options
+                .unknown
+                .keys()
+                .map(|key| format!("options.{key}")),
+        );
+        for suppression in &self.suppress {
+            keys.extend(
+                suppression
+                    .unknown
+                    .keys()
+                    .map(|key| format!("suppress.{key}")),
+            );
+        }
+        keys
+    }
+
+    /// Sets the severities and the suppressions of the given options to those of this file.
+    pub fn apply(&self, options: &mut Options, handler: &EarlyDiagCtxt) {
+        for code in self.severities.keys() {
+            if checker_registry::checker_for_code(code).is_none() {
+                handler.early_fatal(format!("[severities]: no checker reports {code}"));
+            }
+        }
+        for name in self.suppress.iter().flat_map(|s| s.checkers.iter()) {
+            if !CHECKERS.iter().any(|c| c.name == name) {
+                handler.early_fatal(format!(
+                    "[[suppress]] names no checker: `{name}`, see --list-checks for the names"
+                ));
+            }
+        }
+        options.severities = self.severities.clone();
+        options.suppressions = self.suppress.clone();
+    }
+
+    /// Returns the configuration that gives the given options, which --dump-config prints.
+    pub fn of_options(options: &Options) -> Config {
+        let diag = match options.diag_level {
+            DiagLevel::Default => "default",
+            DiagLevel::Verify => "verify",
+            DiagLevel::Library => "library",
+            DiagLevel::Paranoid => "paranoid",
+        };
+        let format = |format: OutputFormat| match format {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+        };
+        let fail_on = options.fail_on.map(|severity| match severity {
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        });
+        Config {
+            options: ConfigOptions {
+                diag: Some(diag.to_string()),
+                body_analysis_timeout: Some(options.max_analysis_time_for_body.as_secs_f64()),
+                crate_analysis_timeout: Some(options.max_analysis_time_for_crate),
+                max_elements_to_track: Some(options.k_limits.max_elements_to_track),
+                max_path_length: Some(options.k_limits.max_path_length),
+                max_outer_fixpoint_iterations: Some(options.k_limits.max_fixpoint_iterations),
+                max_loop_unrolling: Some(options.k_limits.max_loop_unrolling),
+                max_recursive_iterations: Some(options.k_limits.max_recursive_iterations),
+                constant_time: options.constant_time_tag_name.clone(),
+                ledger_account: options.ledger_account.clone(),
+                adversarial_public_api: Some(options.adversarial_public_api),
+                dual_build: Some(options.dual_build),
+                factor_exits: Some(options.factor_exits),
+                batch_solver_queries: Some(options.batch_solver_queries),
+                tighten_intervals: Some(options.tighten_intervals),
+                quiet: Some(options.quiet),
+                deny: options.deny.clone(),
+                fail_on: fail_on.map(str::to_string),
+                diag_format: Some(format(options.diag_format).to_string()),
+                baseline: options.baseline.clone(),
+                models: options.models.clone(),
+                incremental: Some(options.incremental),
+                unknown: BTreeMap::new(),
+            },
+            severities: options.severities.clone(),
+            suppress: options.suppressions.clone(),
+            unknown: BTreeMap::new(),
+        }
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("configurations should serialize")
+    }
+}
+
+impl ConfigOptions {
+    /// Returns the flags that give these options.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        let mut add = |flag: &str, value: Option<String>| {
+            if let Some(value) = value {
+                args.push(flag.to_string());
+                args.push(value);
+            }
+        };
+        add("--diag", self.diag.clone());
+        add(
+            "--body_analysis_timeout",
+            self.body_analysis_timeout.map(|v| v.to_string()),
+        );
+        add(
+            "--crate_analysis_timeout",
+            self.crate_analysis_timeout.map(|v| v.to_string()),
+        );
+        add(
+            "--max-elements-to-track",
+            self.max_elements_to_track.map(|v| v.to_string()),
+        );
+        add(
+            "--max-path-length",
+            self.max_path_length.map(|v| v.to_string()),
+        );
+        add(
+            "--max-outer-fixpoint-iterations",
+            self.max_outer_fixpoint_iterations.map(|v| v.to_string()),
+        );
+        add(
+            "--max-loop-unrolling",
+            self.max_loop_unrolling.map(|v| v.to_string()),
+        );
+        add(
+            "--max-recursive-iterations",
+            self.max_recursive_iterations.map(|v| v.to_string()),
+        );
+        add("--constant_time", self.constant_time.clone());
+        add("--ledger-account", self.ledger_account.clone());
+        for code in &self.deny {
+            add("--deny", Some(code.clone()));
+        }
+        add("--fail-on", self.fail_on.clone());
+        add("--diag-format", self.diag_format.clone());
+        add("--baseline", self.baseline.clone());
+        add("--models", self.models.clone());
+        for (flag, value) in [
+            ("--adversarial-public-api", self.adversarial_public_api),
+            ("--dual-build", self.dual_build),
+            ("--factor-exits", self.factor_exits),
+            ("--batch-solver-queries", self.batch_solver_queries),
+            ("--tighten-intervals", self.tighten_intervals),
+            ("--quiet", self.quiet),
+            ("--incremental", self.incremental),
+        ] {
+            if value == Some(true) {
+                args.push(flag.to_string());
+            }
+        }
+        args
+    }
+}
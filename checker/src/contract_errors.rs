@@ -1,7 +1,21 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 use std::rc::Rc;
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::DefId;
 use rustc_middle::mir;
-use rustc_span::{BytePos, Span};
+use rustc_middle::mir::visit::{MutatingUseContext, PlaceContext, Visitor};
+use rustc_middle::ty::{GenericArgKind, Ty, TyCtxt, TyKind};
+use rustc_span::source_map::Spanned;
+use rustc_span::Span;
+use rustc_target::abi::FieldIdx;
+
+use crate::abstract_value::{AbstractValue, AbstractValueTrait};
+use crate::expression::ExpressionType;
+use crate::fixes;
+use crate::solana_compat::{self, AccountField, AnchorAccount};
+use crate::summaries::{DistinctAccounts, ExternalEffects};
+use crate::type_visitor::TypeVisitor;
 
 #[derive(Debug, Clone)]
 pub enum BlockStatement<'tcx> {
@@ -9,79 +23,380 @@ pub enum BlockStatement<'tcx> {
     TerminatorKind(mir::TerminatorKind<'tcx>)
 }
 
+// The steps of the flow that leads to a finding, in the order in which they happen, each with the
+// span where it happens and a note that says what happens there. The steps are where a value that
+// the caller or a validator controls enters the function, where it passes through an external call
+// or a random number generator, and where it decides a dangerous write.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VulnerabilityTrace {
+    pub steps: Vec<(Span, String)>,
+}
+
+impl VulnerabilityTrace {
+    /// Adds a step at the given span, if the span is known.
+    pub fn step(&mut self, span: Option<Span>, note: impl Into<String>) {
+        if let Some(span) = span.filter(|span| !span.is_dummy()) {
+            self.steps.push((span, note.into()));
+        }
+    }
+
+    /// Returns the notes of a finding, in the order of the steps.
+    pub fn into_notes(self) -> Vec<(Span, String)> {
+        self.steps
+    }
+}
+
+// The accounts that a value is derived from, named by the locals of the function that hold them
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AccountRoots {
+    pub locals: BTreeSet<mir::Local>,
+    // True if the value may be derived from any account, such as the accounts slice
+    pub any: bool,
+}
+
+impl AccountRoots {
+    pub fn any() -> AccountRoots {
+        AccountRoots { locals: BTreeSet::new(), any: true }
+    }
+
+    pub fn of(local: mir::Local) -> AccountRoots {
+        AccountRoots { locals: BTreeSet::from([local]), any: false }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.any && self.locals.is_empty()
+    }
+
+    pub fn union(&mut self, other: &AccountRoots) {
+        self.any |= other.any;
+        self.locals.extend(other.locals.iter().copied());
+    }
+
+    pub fn overlaps(&self, other: &AccountRoots) -> bool {
+        self.any || other.any || !self.locals.is_disjoint(&other.locals)
+    }
+}
+
 // Hold states for the reentrancy
 pub struct ReentrancyChecker<'tcx> {
     // The block statements are belong to a function
     pub block_statements: HashMap<mir::BasicBlock, Vec<BlockStatement<'tcx>>>,
-    // The function call transfers tokens in solana contract
-    pub function_lamport_transfer: HashMap<mir::BasicBlock, Rc<str>>,
+    // The blocks that transfer lamports or invoke another program, with the accounts they touch
+    pub interactions: BTreeMap<mir::BasicBlock, AccountRoots>,
+    // The blocks that invoke another program with an instruction that carries untrusted input
+    pub untrusted_interactions: HashSet<mir::BasicBlock>,
+    // The accounts that the values of locals are derived from
+    pub account_roots: HashMap<mir::Local, AccountRoots>,
     // The temporary variable holds the balance of an user in the solana contract
     pub temporary_variable_for_balance: Option<mir::Place<'tcx>>,
+    // The accounts whose keys select the balance from the map holding it
+    pub balance_accounts: AccountRoots,
     // Check for detecting the variable holding the balance of an user in the solana contract
     pub check_for_balance_variable: bool,
     //  Current assign destination in the statement
     pub current_assign_destination: Option<mir::Place<'tcx>>,
-    // The starting spans contain reentrancy codes
-    pub starting_reentrancy_span: BytePos,
-    // The ending spans contain reentrancy codes
-    pub ending_reentrancy_span: BytePos
+    // The span of the call that loads the balance
+    pub balance_span: Span,
+    // The blocks that end in a call of a function that writes a balance
+    pub state_writes: BTreeMap<mir::BasicBlock, Span>,
+    // Check if a called function writes a balance after a transfer
+    pub callee_writes_state_after_transfer: bool,
 }
 
 impl<'tcx> ReentrancyChecker<'tcx> {
     pub fn new() -> ReentrancyChecker<'tcx> {
         ReentrancyChecker {
             block_statements: HashMap::default(),
-            function_lamport_transfer: HashMap::default(),
+            interactions: BTreeMap::default(),
+            untrusted_interactions: HashSet::default(),
+            account_roots: HashMap::default(),
             temporary_variable_for_balance: None,
+            balance_accounts: AccountRoots::default(),
             check_for_balance_variable: false,
             current_assign_destination: None,
-            starting_reentrancy_span: BytePos(0),
-            ending_reentrancy_span: BytePos(0)
+            balance_span: rustc_span::DUMMY_SP,
+            state_writes: BTreeMap::default(),
+            callee_writes_state_after_transfer: false,
         }
     }
 
-    /// Check if the reentrancy happens. The reentrancy will possibly happens if the following executions
-    /// happen. First, a ``LOAD`` instruction occurs. Second, the ``TRANSFER`` instruction occurs.
-    /// Lastly, a ``STORE`` instruction executes, interacting with the same location accessed by
-    /// the former ``LOAD`` instruction.
-    pub fn check(&self) -> bool {
-        info!("Check for reentrancy");
-        let mut is_reentrancy = false;
-        if self.function_lamport_transfer.is_empty() {
-            return is_reentrancy;
+    /// Returns the accounts that the value of the given operand is derived from. A local that
+    /// holds a single account is an account of its own, unless it is known to be a copy of
+    /// another one, and a local that holds several accounts may hold any of them.
+    pub fn accounts_of_operand(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        operand: &mir::Operand<'tcx>,
+    ) -> AccountRoots {
+        let (mir::Operand::Copy(place) | mir::Operand::Move(place)) = operand else {
+            return AccountRoots::default();
+        };
+        self.accounts_of_place(tcx, mir, place)
+    }
+
+    fn accounts_of_place(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        place: &mir::Place<'tcx>,
+    ) -> AccountRoots {
+        if let Some(roots) = self.account_roots.get(&place.local) {
+            return roots.clone();
         }
-        if let Some((last_bb, _)) = self.function_lamport_transfer.iter().last() {
-            info!("Last function lamport {:?}", last_bb);
-            info!("Variable for balance {:?}", self.temporary_variable_for_balance);
-            for (bb, block_statements) in &self.block_statements {
-                if bb <= last_bb {
-                    continue;
+        let ty = mir.local_decls[place.local].ty;
+        if is_single_account_type(tcx, ty) {
+            AccountRoots::of(place.local)
+        } else if is_account_info_type(tcx, ty) {
+            AccountRoots::any()
+        } else {
+            AccountRoots::default()
+        }
+    }
+
+    /// Records the accounts that the value assigned to a local is derived from.
+    pub fn visit_assign(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+    ) {
+        if !place.projection.is_empty() {
+            return;
+        }
+        let roots = match rvalue {
+            mir::Rvalue::Use(operand) | mir::Rvalue::Cast(_, operand, _) => {
+                self.accounts_of_operand(tcx, mir, operand)
+            }
+            mir::Rvalue::Ref(_, _, source)
+            | mir::Rvalue::RawPtr(_, source)
+            | mir::Rvalue::CopyForDeref(source) => self.accounts_of_place(tcx, mir, source),
+            mir::Rvalue::Aggregate(_, operands) => {
+                let mut roots = AccountRoots::default();
+                for operand in operands {
+                    roots.union(&self.accounts_of_operand(tcx, mir, operand));
+                }
+                roots
+            }
+            _ => AccountRoots::default(),
+        };
+        self.assign_roots(tcx, mir, place.local, roots);
+    }
+
+    /// Records that the result of a call is derived from the accounts of its arguments.
+    pub fn visit_call(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        args: &[Spanned<mir::Operand<'tcx>>],
+        destination: &mir::Place<'tcx>,
+    ) {
+        if !destination.projection.is_empty() {
+            return;
+        }
+        let mut roots = AccountRoots::default();
+        for arg in args {
+            roots.union(&self.accounts_of_operand(tcx, mir, &arg.node));
+        }
+        self.assign_roots(tcx, mir, destination.local, roots);
+    }
+
+    /// Records an interaction of the given block with the accounts of the given operand. If
+    /// the accounts are not known, the interaction may involve any account.
+    pub fn record_interaction(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        bb: mir::BasicBlock,
+        accounts: Option<&mir::Operand<'tcx>>,
+    ) {
+        let mut roots = accounts
+            .map(|operand| self.accounts_of_operand(tcx, mir, operand))
+            .unwrap_or_default();
+        if roots.is_empty() {
+            roots = AccountRoots::any();
+        }
+        self.interactions.entry(bb).or_default().union(&roots);
+    }
+
+    /// Records a call of a foreign function that the registry models as a call out of the
+    /// program, which interacts with the accounts of all of its arguments, or with any account
+    /// if none are known.
+    pub fn record_external_call(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        bb: mir::BasicBlock,
+        args: &[Spanned<mir::Operand<'tcx>>],
+    ) {
+        let mut roots = AccountRoots::default();
+        for arg in args {
+            roots.union(&self.accounts_of_operand(tcx, mir, &arg.node));
+        }
+        if roots.is_empty() {
+            roots = AccountRoots::any();
+        }
+        self.interactions.entry(bb).or_default().union(&roots);
+    }
+
+    /// Records the accounts whose keys are passed to a call of a method of the balances map,
+    /// or to the entry that such a call returns.
+    pub fn record_balance_accounts(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        args: &[Spanned<mir::Operand<'tcx>>],
+    ) {
+        let mut roots = AccountRoots::default();
+        for arg in args {
+            roots.union(&self.accounts_of_operand(tcx, mir, &arg.node));
+        }
+        if roots.is_empty() {
+            roots = AccountRoots::any();
+        }
+        self.balance_accounts = roots;
+    }
+
+    /// Composes the external effects of the function that the given block calls with those of
+    /// the body. The accounts that the callee transfers lamports of are not known here, so the
+    /// call may involve any account.
+    pub fn compose_external_effects(
+        &mut self,
+        bb: mir::BasicBlock,
+        call_span: Span,
+        effects: &ExternalEffects,
+    ) {
+        if effects.transfers_lamports {
+            self.interactions.entry(bb).or_default().union(&AccountRoots::any());
+        }
+        if effects.writes_state {
+            self.state_writes.insert(bb, call_span);
+        }
+        self.callee_writes_state_after_transfer |= effects.writes_state_after_transfer;
+    }
+
+    /// Returns the external effects of the body, including those of the functions it calls,
+    /// for its summary.
+    pub fn external_effects(&self, mir: &mir::Body<'tcx>) -> ExternalEffects {
+        let writes_balance = self.block_statements.values().flatten().any(|block_statement| {
+            match block_statement {
+                BlockStatement::Statement(statement) => {
+                    self.visit_reentrancy_statement(&statement.kind)
                 }
-                info!("bb {:?}, last_bb {:?}, greater {:?}", bb, last_bb, bb > last_bb);
-                for block_statement in block_statements {
-                    // If the balance is assigned to a constant
-                    if let BlockStatement::Statement(statement) = block_statement {
-                        let mir::Statement { kind, .. } = statement;
-                        let status = self.visit_reentrancy_statement(kind);
-                        is_reentrancy = status || is_reentrancy;
-                        if is_reentrancy {
-                            break;
+                BlockStatement::TerminatorKind(kind) => self.visit_reentrancy_terminator(kind),
+            }
+        });
+        ExternalEffects {
+            transfers_lamports: !self.interactions.is_empty(),
+            writes_state: writes_balance || !self.state_writes.is_empty(),
+            writes_state_after_transfer: self.callee_writes_state_after_transfer
+                || self.reentrant_write(mir).is_some(),
+        }
+    }
+
+    // A local holding a single account that is picked out of several is an account of its own.
+    fn assign_roots(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        local: mir::Local,
+        roots: AccountRoots,
+    ) {
+        if roots.is_empty() || roots.any && is_single_account_type(tcx, mir.local_decls[local].ty)
+        {
+            self.account_roots.remove(&local);
+        } else {
+            self.account_roots.insert(local, roots);
+        }
+    }
+
+    /// Returns the span of a write of the balance that follows an interaction with an account
+    /// whose key selects the balance, together with the block of the interaction. The reentrancy
+    /// will possibly happen if the following executions happen. First, a ``LOAD`` instruction
+    /// occurs. Second, the ``TRANSFER`` or ``INVOKE`` instruction occurs, touching an account
+    /// whose key selects the loaded balance. Lastly, a ``STORE`` instruction executes on every
+    /// path through the interaction, interacting with the same location accessed by the former
+    /// ``LOAD`` instruction. The transfer and the store may also happen in called functions.
+    pub fn reentrant_write(&self, mir: &mir::Body<'tcx>) -> Option<(Span, mir::BasicBlock)> {
+        info!("Check for reentrancy");
+        let dominators = mir.basic_blocks.dominators();
+        let mut blocks: Vec<mir::BasicBlock> = self.block_statements.keys().copied().collect();
+        blocks.sort();
+        for (interaction, accounts) in &self.interactions {
+            info!("Interaction {:?}", interaction);
+            info!("Variable for balance {:?}", self.temporary_variable_for_balance);
+            // Only an interaction with an account whose balance is written later can reenter it.
+            // The balances that called functions write may belong to any account.
+            let reenters_balance = accounts.overlaps(&self.balance_accounts);
+            // The interaction is the call that ends its block, so the writes after it are in the
+            // blocks that it dominates
+            for bb in blocks.iter().filter(|bb| {
+                *bb != interaction && dominators.dominates(*interaction, **bb)
+            }) {
+                let statements = if reenters_balance { &self.block_statements[bb][..] } else { &[] };
+                for block_statement in statements {
+                    match block_statement {
+                        // If the balance is assigned to a constant
+                        BlockStatement::Statement(statement)
+                            if self.visit_reentrancy_statement(&statement.kind) =>
+                        {
+                            return Some((statement.source_info.span, *interaction));
                         }
-                    }
-                    // If the balance is related to arithmetic operations. E.g., balance -= amount
-                    if let BlockStatement::TerminatorKind(kind) = block_statement {
-                        let status = self.visit_reentrancy_terminator(kind);
-                        is_reentrancy = status || is_reentrancy;
-                        if is_reentrancy {
-                            break;
+                        // If the balance is updated by arithmetic operations, e.g. balance -= amount
+                        BlockStatement::TerminatorKind(kind)
+                            if self.visit_reentrancy_terminator(kind) =>
+                        {
+                            return Some((mir[*bb].terminator().source_info.span, *interaction));
                         }
+                        _ => {}
                     }
                 }
+                if let Some(call_span) = self.state_writes.get(bb) {
+                    return Some((*call_span, *interaction));
+                }
             }
         }
-        return is_reentrancy;
+        None
     }
-    
+
+    /// Returns the span of a reentrant write of the balance, with the trace that leads to it: the
+    /// read of the balance, the call that may reenter the program and the write itself. A balance
+    /// that a called function writes is also read there.
+    pub fn trace(&self, mir: &mir::Body<'tcx>) -> Option<(Span, VulnerabilityTrace)> {
+        let (span, interaction) = self.reentrant_write(mir)?;
+        let mut trace = VulnerabilityTrace::default();
+        if !self.state_writes.values().any(|write| *write == span) {
+            let account_names: Vec<String> = self
+                .balance_accounts
+                .locals
+                .iter()
+                .filter_map(|local| debug_name(mir, *local))
+                .map(|name| format!("`{name}`"))
+                .collect();
+            let note = if account_names.len() == 1 && !self.balance_accounts.any {
+                format!(
+                    "the balance that the key of {} selects is read here",
+                    account_names[0]
+                )
+            } else {
+                "the balance is read here".to_string()
+            };
+            trace.step(Some(self.balance_span), note);
+        }
+        let note = if self.untrusted_interactions.contains(&interaction) {
+            "then this call may reenter the program, with an instruction that carries untrusted input"
+        } else {
+            "then this call may reenter the program"
+        };
+        trace.step(Some(mir[interaction].terminator().source_info.span), note);
+        trace.step(
+            Some(span),
+            "and the balance is written here, after the call",
+        );
+        Some((span, trace))
+    }
+
     fn visit_reentrancy_terminator(&self, kind: &mir::TerminatorKind<'_>) -> bool {
         if let mir::TerminatorKind::Assert { msg, .. } = kind {
             if let mir::AssertKind::Overflow(mir::BinOp::Sub, ref left_operand, _) = **msg {
@@ -110,65 +425,406 @@ impl<'tcx> ReentrancyChecker<'tcx> {
     
 }
 
-// Hold states for the bad radomness
+// Hold states for the bad randomness. Values that validators can predict carry the built-in tag
+// Tag::PREDICTABLE_ENTROPY, which is attached to the clock, the slot hashes, the system time and to
+// the values of random number generators that are seeded implicitly, with a constant or with a
+// predictable value. A random value is only a problem if it decides a transfer of lamports.
 pub struct BadrandomnessChecker {
-    // Check if the rand lib is used
-    pub check_for_rand_lib: bool,
-     // The span contains codes related to bad randomness
-     pub bad_randomness_span: Span,
+    // The span of the first call whose result is the clock, the slot hashes or the system time
+    pub entropy_source_span: Option<Span>,
+    // The span of the first call of a random number generator whose value is predictable
+    pub bad_randomness_span: Option<Span>,
+    // The branches on predictable values, by the blocks that end with them
+    pub predictable_branches: Vec<(mir::BasicBlock, Span)>,
+    // The spans of the calls that invoke another program with a predictable argument
+    pub predictable_invocations: Vec<Span>,
+    // The blocks that borrow the lamports of an account mutably or invoke another program
+    pub transfer_blocks: HashSet<mir::BasicBlock>,
 }
 
 impl BadrandomnessChecker {
     pub fn new() -> BadrandomnessChecker {
-        return BadrandomnessChecker { 
-            check_for_rand_lib: false, 
-            bad_randomness_span: rustc_span::DUMMY_SP
+        BadrandomnessChecker {
+            entropy_source_span: None,
+            bad_randomness_span: None,
+            predictable_branches: Vec::new(),
+            predictable_invocations: Vec::new(),
+            transfer_blocks: HashSet::new(),
         }
     }
 
-    /// Check if the bad randomness happens. The bad randomness will possibly happens if 
-    /// ``solana_program::sysvar::clock::Clock`` is used
-    pub fn check(&self) -> bool {
-        return self.check_for_rand_lib;
+    /// Returns the span of the first invocation with a predictable argument, or of the first
+    /// branch on a predictable value that is the only way into a block that transfers lamports,
+    /// with the span of the transfer that the branch decides.
+    pub fn influenced_transfer(&self, mir: &mir::Body<'_>) -> Option<(Span, Option<Span>)> {
+        if let Some(span) = self.predictable_invocations.first() {
+            return Some((*span, None));
+        }
+        self.predictable_branches.iter().find_map(|(bb, span)| {
+            let transfer = decided_transfer(mir, *bb, &self.transfer_blocks)?;
+            Some((*span, Some(mir[transfer].terminator().source_info.span)))
+        })
+    }
+
+    /// Returns the trace of a random value that validators can predict: where the predictable
+    /// entropy is read, where the random value is drawn and, if the value decides a transfer of
+    /// lamports, the transfer. The span of the decision is returned with the trace, if there is
+    /// one.
+    pub fn trace(&self, mir: &mir::Body<'_>) -> (Option<Span>, VulnerabilityTrace) {
+        let mut trace = VulnerabilityTrace::default();
+        trace.step(
+            self.entropy_source_span,
+            "the entropy is read here, from a value that validators can predict",
+        );
+        trace.step(
+            self.bad_randomness_span,
+            "the random value is drawn here, from a predictable seed",
+        );
+        let Some((decision_span, transfer_span)) = self.influenced_transfer(mir) else {
+            return (None, trace);
+        };
+        trace.step(transfer_span, "and decides this transfer of lamports");
+        (Some(decision_span), trace)
     }
 }
 
-// Hold states for the time manipulation
+/// True if a successor of the branch at the end of the given block is only entered from the
+/// branch and dominates one of the given blocks that transfer lamports.
+pub fn branch_decides_transfer(
+    mir: &mir::Body<'_>,
+    branch: mir::BasicBlock,
+    transfer_blocks: &HashSet<mir::BasicBlock>,
+) -> bool {
+    decided_transfer(mir, branch, transfer_blocks).is_some()
+}
+
+/// Returns the first of the given blocks that transfer lamports that is dominated by a successor
+/// of the branch at the end of the given block, which is only entered from the branch.
+pub fn decided_transfer(
+    mir: &mir::Body<'_>,
+    branch: mir::BasicBlock,
+    transfer_blocks: &HashSet<mir::BasicBlock>,
+) -> Option<mir::BasicBlock> {
+    let dominators = mir.basic_blocks.dominators();
+    let predecessors = mir.basic_blocks.predecessors();
+    mir[branch]
+        .terminator()
+        .successors()
+        .filter(|successor| predecessors[*successor].len() == 1)
+        .filter_map(|successor| {
+            transfer_blocks
+                .iter()
+                .filter(|transfer| dominators.dominates(successor, **transfer))
+                .min()
+                .copied()
+        })
+        .min()
+}
+
+/// True if the type is, or contains, a value that validators can predict or influence, such as
+/// the clock, the slot hashes or the system time.
+pub fn is_predictable_entropy_type<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> bool {
+    ty.walk().any(|arg| {
+        let GenericArgKind::Type(ty) = arg.unpack() else {
+            return false;
+        };
+        let TyKind::Adt(def, _) = ty.kind() else {
+            return false;
+        };
+        let path = tcx.def_path_str(def.did());
+        let name = path.rsplit("::").next().unwrap_or_default();
+        (path.starts_with("solana")
+            && matches!(
+                name,
+                "Clock" | "RecentBlockhashes" | "SlotHashes" | "SlotHistory"
+            ))
+            || matches!(
+                path.as_str(),
+                "std::time::Instant" | "std::time::SystemTime"
+            )
+    })
+}
+
+/// True if the function is defined in a crate of random number generators.
+pub fn is_random_number_generator<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId) -> bool {
+    matches!(
+        tcx.crate_name(def_id.krate).as_str(),
+        "fastrand" | "nanorand" | "oorandom" | "rand" | "rand_chacha" | "rand_core"
+    )
+}
+
+// What a local that is derived from the timestamp or the slot of the clock sysvar holds
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ClockValue {
+    // Derived from the unix timestamp
+    pub timestamp: bool,
+    // Derived from the slot
+    pub slot: bool,
+    // Reduced with a modulo or a bit mask, to one of a few values that a validator can pick from
+    pub reduced: bool,
+    // The span of the arithmetic that combines the timestamp with the slot, if any
+    pub combined: Option<Span>,
+}
+
+// Hold states for the time manipulation. Validators can shift the timestamp of the clock and the
+// leader picks the slot, so a small value computed from them is one that they can choose. Only
+// two uses of such values are reported: a modulo or a bit mask of the timestamp or the slot that
+// decides a transfer of lamports, and the timestamp combined with the slot into a value that is
+// used later, the usual way to fabricate a random number. Logging the clock or comparing it with
+// a deadline is fine.
 pub struct TimeManipulationChecker {
-    // Check if the clock lib is used
-    pub check_for_clock_lib: bool,
-     // The span contains codes related to time manipulation
-     pub time_manipulation_span: Span,
+    // The span of the first call that reads the clock sysvar
+    pub clock_read_span: Option<Span>,
+    // What the locals that are derived from the timestamp or the slot hold
+    pub clock_values: HashMap<mir::Local, ClockValue>,
+    // The locals that hold the result of comparing a reduced timestamp or slot
+    pub reduced_conditions: HashSet<mir::Local>,
+    // The branches on a reduced timestamp or slot, by the blocks that end with them
+    pub reduced_branches: Vec<(mir::BasicBlock, Span)>,
+    // The blocks that borrow the lamports of an account mutably or invoke another program
+    pub transfer_blocks: HashSet<mir::BasicBlock>,
+    // The span of the first combination of the timestamp with the slot whose value is used
+    pub random_source_span: Option<Span>,
+}
+
+impl Default for TimeManipulationChecker {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TimeManipulationChecker {
     pub fn new() -> TimeManipulationChecker {
-        return TimeManipulationChecker { 
-            check_for_clock_lib: false, 
-            time_manipulation_span: rustc_span::DUMMY_SP
+        TimeManipulationChecker {
+            clock_read_span: None,
+            clock_values: HashMap::default(),
+            reduced_conditions: HashSet::default(),
+            reduced_branches: Vec::new(),
+            transfer_blocks: HashSet::default(),
+            random_source_span: None,
         }
     }
 
-    /// Check if the bad randomness happens. The bad randomness will possibly happens if 
-    /// ``solana_program::sysvar::clock::Clock`` is used
-    pub fn check(&self) -> bool {
-        return self.check_for_clock_lib;
+    /// Records a call that reads ``solana_program::sysvar::clock::Clock``.
+    pub fn record_clock_read(&mut self, span: Span) {
+        if self.clock_read_span.is_none() {
+            self.clock_read_span = Some(span);
+        }
+    }
+
+    /// Returns the span of the first branch on a reduced timestamp or slot that is the only way
+    /// into a block that transfers lamports.
+    pub fn reduced_transfer(&self, mir: &mir::Body<'_>) -> Option<Span> {
+        self.reduced_branches
+            .iter()
+            .find(|(bb, _)| branch_decides_transfer(mir, *bb, &self.transfer_blocks))
+            .map(|(_, span)| *span)
+    }
+
+    /// Updates what the locals hold with the assignment of rvalue to place.
+    pub fn visit_assign<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+        span: Span,
+    ) {
+        let mut is_reduced_condition = false;
+        let value = match rvalue {
+            mir::Rvalue::Use(operand) | mir::Rvalue::Cast(_, operand, _) => {
+                self.value_of_operand(tcx, mir, operand)
+            }
+            mir::Rvalue::CopyForDeref(place) => self.value_of_place(tcx, mir, place),
+            // Borrowing a combination of the timestamp and the slot, to log it or to pass it on,
+            // is a use of it
+            mir::Rvalue::Ref(_, _, borrowed) => {
+                let value = self.value_of_place(tcx, mir, borrowed);
+                self.use_value(value);
+                None
+            }
+            mir::Rvalue::UnaryOp(mir::UnOp::Not, operand) => {
+                is_reduced_condition = self.is_reduced_condition(operand);
+                self.value_of_operand(tcx, mir, operand)
+            }
+            mir::Rvalue::BinaryOp(bin_op, box (left, right)) => {
+                let left_value = self.value_of_operand(tcx, mir, left);
+                let right_value = self.value_of_operand(tcx, mir, right);
+                match bin_op {
+                    mir::BinOp::Eq
+                    | mir::BinOp::Ne
+                    | mir::BinOp::Lt
+                    | mir::BinOp::Le
+                    | mir::BinOp::Gt
+                    | mir::BinOp::Ge
+                    | mir::BinOp::Cmp => {
+                        self.use_value(left_value);
+                        self.use_value(right_value);
+                        is_reduced_condition = left_value.is_some_and(|v| v.reduced)
+                            || right_value.is_some_and(|v| v.reduced);
+                        None
+                    }
+                    _ => Self::visit_binary_op(*bin_op, left_value, right_value, span),
+                }
+            }
+            _ => None,
+        };
+        if !place.projection.is_empty() {
+            return;
+        }
+        if let Some(value) = value {
+            self.clock_values.insert(place.local, value);
+        } else {
+            self.clock_values.remove(&place.local);
+        }
+        if is_reduced_condition {
+            self.reduced_conditions.insert(place.local);
+        } else {
+            self.reduced_conditions.remove(&place.local);
+        }
+    }
+
+    /// Passing a combination of the timestamp and the slot to a function is a use of it.
+    pub fn visit_call<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        args: &[Spanned<mir::Operand<'tcx>>],
+    ) {
+        for arg in args {
+            let value = self.value_of_operand(tcx, mir, &arg.node);
+            self.use_value(value);
+        }
+    }
+
+    /// Records a branch on a reduced timestamp or slot, or on a comparison of one.
+    pub fn visit_switch_int<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        block: mir::BasicBlock,
+        discr: &mir::Operand<'tcx>,
+        span: Span,
+    ) {
+        let value = self.value_of_operand(tcx, mir, discr);
+        self.use_value(value);
+        if (value.is_some_and(|v| v.reduced) || self.is_reduced_condition(discr))
+            && !self.reduced_branches.iter().any(|(bb, _)| *bb == block)
+        {
+            self.reduced_branches.push((block, span));
+        }
+    }
+
+    fn visit_binary_op(
+        bin_op: mir::BinOp,
+        left_value: Option<ClockValue>,
+        right_value: Option<ClockValue>,
+        span: Span,
+    ) -> Option<ClockValue> {
+        let (left, right) = match (left_value, right_value) {
+            (None, None) => return None,
+            (left, right) => (left.unwrap_or_default(), right.unwrap_or_default()),
+        };
+        let timestamp = left.timestamp || right.timestamp;
+        let slot = left.slot || right.slot;
+        let combined = left
+            .combined
+            .or(right.combined)
+            .or_else(|| (timestamp && slot).then_some(span));
+        // Only the dividend of a modulo is reduced by it
+        let reduces = match bin_op {
+            mir::BinOp::Rem => left_value.is_some(),
+            mir::BinOp::BitAnd => true,
+            _ => false,
+        };
+        Some(ClockValue {
+            timestamp,
+            slot,
+            reduced: reduces || left.reduced || right.reduced,
+            combined,
+        })
+    }
+
+    fn use_value(&mut self, value: Option<ClockValue>) {
+        if self.random_source_span.is_none() {
+            self.random_source_span = value.and_then(|v| v.combined);
+        }
+    }
+
+    fn is_reduced_condition(&self, operand: &mir::Operand<'_>) -> bool {
+        match operand {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => {
+                place.projection.is_empty() && self.reduced_conditions.contains(&place.local)
+            }
+            mir::Operand::Constant(..) => false,
+        }
+    }
+
+    fn value_of_operand<'tcx>(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        operand: &mir::Operand<'tcx>,
+    ) -> Option<ClockValue> {
+        match operand {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => {
+                self.value_of_place(tcx, mir, place)
+            }
+            mir::Operand::Constant(..) => None,
+        }
+    }
+
+    fn value_of_place<'tcx>(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        place: &mir::Place<'tcx>,
+    ) -> Option<ClockValue> {
+        if let Some(value) = self.clock_values.get(&place.local) {
+            return Some(*value);
+        }
+        match clock_field_unit(tcx, mir, place)? {
+            TimeUnit::Seconds => Some(ClockValue {
+                timestamp: true,
+                ..ClockValue::default()
+            }),
+            TimeUnit::Slots => Some(ClockValue {
+                slot: true,
+                ..ClockValue::default()
+            }),
+            TimeUnit::Epochs => None,
+        }
     }
 }
 
-// Hold states for the numerical precision error
+// Hold states for the numerical precision error. Besides the rounding of floats, an integer
+// division whose quotient is then multiplied, as in `amount / 10_000 * fee_bps`, is reported,
+// because the multiplication amplifies the remainder that the division throws away. Quotients are
+// followed through copies, casts, checked_div and its kin, the unwrapping of the Option or Result
+// they return, and through functions of the crate that return a quotient.
 pub struct NumericalPrecisionErrorChecker {
     // Check if the round function used to round up a number
     pub check_for_round_func: bool,
     // The span contains codes related to numerical precision error
     pub numerical_precision_error_span: Span,
+    // The locals that hold a quotient, or an Option or Result of one, with the span of the
+    // division and the divisor if it is a constant
+    pub quotients: HashMap<mir::Local, (Span, Option<u128>)>,
+    // The span of the first multiplication of a quotient and the span of the division
+    pub division_before_multiplication: Option<(Span, Span)>,
+    // The spans of the divisions whose divisors are untrusted input that may be zero, which are
+    // reported as divisions by zero instead
+    pub unchecked_divisions: HashSet<Span>,
 }
 
 impl NumericalPrecisionErrorChecker {
     pub fn new() -> NumericalPrecisionErrorChecker {
         return NumericalPrecisionErrorChecker {
             check_for_round_func: false,
-            numerical_precision_error_span: rustc_span::DUMMY_SP
+            numerical_precision_error_span: rustc_span::DUMMY_SP,
+            quotients: HashMap::default(),
+            division_before_multiplication: None,
+            unchecked_divisions: HashSet::default(),
         }
     }
 
@@ -177,9 +833,5656 @@ impl NumericalPrecisionErrorChecker {
     pub fn check(&self) -> bool {
         return self.check_for_round_func;
     }
+
+    /// Follows the quotients of integer divisions through the assignment of rvalue to place and
+    /// records the multiplication of one.
+    pub fn visit_assign<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+        span: Span,
+    ) {
+        if !place.projection.is_empty() {
+            return;
+        }
+        let quotient = match rvalue {
+            mir::Rvalue::Use(operand) | mir::Rvalue::Cast(_, operand, _) => {
+                self.quotient_of_operand(operand)
+            }
+            mir::Rvalue::CopyForDeref(source) => self.quotient_of_place(source),
+            mir::Rvalue::BinaryOp(mir::BinOp::Div, box (left, right))
+                if left.ty(mir, tcx).is_integral() =>
+            {
+                Some((span, constant_of(right)))
+            }
+            mir::Rvalue::BinaryOp(
+                mir::BinOp::Mul | mir::BinOp::MulWithOverflow | mir::BinOp::MulUnchecked,
+                box (left, right),
+            ) => {
+                self.visit_multiplication(left, right, span);
+                None
+            }
+            _ => None,
+        };
+        if let Some(quotient) = quotient {
+            self.quotients.insert(place.local, quotient);
+        } else {
+            self.quotients.remove(&place.local);
+        }
+    }
+
+    /// Follows the quotients through the calls of checked_div and its kin, of functions that
+    /// unwrap their first argument and of functions of the crate that return a quotient, and
+    /// records the multiplication of one with checked_mul and its kin.
+    pub fn visit_call<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        callee_def_id: DefId,
+        item_name: &str,
+        args: &[Spanned<mir::Operand<'tcx>>],
+        destination: &mir::Place<'tcx>,
+        span: Span,
+    ) {
+        if !destination.projection.is_empty() {
+            return;
+        }
+        let receiver = args.first().map(|arg| &arg.node);
+        let quotient = match item_name {
+            "checked_div"
+            | "checked_div_euclid"
+            | "div"
+            | "div_euclid"
+            | "saturating_div"
+            | "wrapping_div"
+            | "wrapping_div_euclid" => {
+                let divisor = args.get(1).and_then(|arg| constant_of(&arg.node));
+                receiver
+                    .filter(|receiver| receiver.ty(mir, tcx).is_integral())
+                    .map(|_| (span, divisor))
+            }
+            "checked_mul" | "mul" | "overflowing_mul" | "saturating_mul" | "wrapping_mul" => {
+                if let (Some(left), Some(right)) = (receiver, args.get(1)) {
+                    self.visit_multiplication(left, &right.node, span);
+                }
+                None
+            }
+            "branch" | "expect" | "ok_or" | "ok_or_else" | "unwrap" | "unwrap_or"
+            | "unwrap_or_default" => {
+                receiver.and_then(|receiver| self.quotient_of_operand(receiver))
+            }
+            _ if callee_def_id.is_local() && returns_quotient(tcx, callee_def_id) => {
+                Some((span, None))
+            }
+            _ => None,
+        };
+        if let Some(quotient) = quotient {
+            self.quotients.insert(destination.local, quotient);
+        } else {
+            self.quotients.remove(&destination.local);
+        }
+    }
+
+    fn visit_multiplication(
+        &mut self,
+        left: &mir::Operand<'_>,
+        right: &mir::Operand<'_>,
+        span: Span,
+    ) {
+        let (quotient, factor) = match (
+            self.quotient_of_operand(left),
+            self.quotient_of_operand(right),
+        ) {
+            (Some(quotient), _) => (quotient, right),
+            (None, Some(quotient)) => (quotient, left),
+            (None, None) => return,
+        };
+        // Dividing by a power of two and multiplying by it again aligns the value down, which is
+        // what the division is for
+        let (division_span, divisor) = quotient;
+        if divisor.is_some_and(|divisor| {
+            divisor.is_power_of_two() && constant_of(factor) == Some(divisor)
+        }) {
+            return;
+        }
+        if self.division_before_multiplication.is_none() {
+            self.division_before_multiplication = Some((span, division_span));
+        }
+    }
+
+    fn quotient_of_operand(&self, operand: &mir::Operand<'_>) -> Option<(Span, Option<u128>)> {
+        match operand {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => self.quotient_of_place(place),
+            mir::Operand::Constant(..) => None,
+        }
+    }
+
+    // Returns the quotient that the place holds, if it is reached from a local holding a quotient
+    // through dereferences and the fields of wrappers, such as the Some of a checked division
+    fn quotient_of_place(&self, place: &mir::Place<'_>) -> Option<(Span, Option<u128>)> {
+        let quotient = self.quotients.get(&place.local)?;
+        place
+            .projection
+            .iter()
+            .all(|elem| {
+                matches!(
+                    elem,
+                    mir::ProjectionElem::Deref
+                        | mir::ProjectionElem::Field(..)
+                        | mir::ProjectionElem::Downcast(..)
+                )
+            })
+            .then_some(*quotient)
+    }
+}
+
+// Hold the first amount that is computed with floating point arithmetic, as in
+// `(amount as f64 * rate) as u64`, and then written to the lamports of an account or to an entry
+// of a map of balances, or passed to an instruction of the system program or of a token program,
+// such as a transfer. Floats are slow in BPF and round amounts, which creates or loses money.
+// Floats whose values only reach logs or metrics are not held, since they decide no amount.
+#[derive(Default)]
+pub struct FloatInMoneyPathChecker {
+    // The span of the write or of the call that the amount reaches, how it reaches it, and the
+    // span of the floating point arithmetic that it was computed with last
+    pub float_amount: Option<(Span, &'static str, Span)>,
+    // The span of the last floating point arithmetic, cast to a float or call that returns a float
+    // of the function so far
+    float_source_span: Option<Span>,
+}
+
+impl FloatInMoneyPathChecker {
+    /// Describes the first amount that is computed with floating point arithmetic.
+    pub fn describe(&self) -> String {
+        let Some((_, sink, _)) = self.float_amount else {
+            return String::new();
+        };
+        format!("possible loss of precision in an amount for the smart contract, it is computed with floating point arithmetic and {sink}")
+    }
+
+    /// Records an amount that is computed with floating point arithmetic and reaches a sink,
+    /// unless one has been recorded. The amount is taken to be computed with the floating point
+    /// arithmetic that comes last before the sink, or at the sink if the function has none, such
+    /// as when the amount is returned by a function that it calls.
+    pub fn record(&mut self, span: Span, sink: &'static str) {
+        if self.float_amount.is_none() {
+            self.float_amount = Some((span, sink, self.float_source_span.unwrap_or(span)));
+        }
+    }
+
+    /// Records floating point arithmetic, a cast to a float or a call that returns a float.
+    pub fn record_source(&mut self, span: Span) {
+        self.float_source_span = Some(span);
+    }
+}
+
+// True if the function of the crate returns the quotient of an integer division, found by
+// following the quotients through its body without looking into the functions it calls
+fn returns_quotient(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    if !tcx.is_mir_available(def_id) {
+        return false;
+    }
+    let mir = tcx.optimized_mir(def_id);
+    let mut checker = NumericalPrecisionErrorChecker::new();
+    for data in mir.basic_blocks.iter() {
+        if data.is_cleanup {
+            continue;
+        }
+        for statement in &data.statements {
+            if let mir::StatementKind::Assign(box (place, rvalue)) = &statement.kind {
+                checker.visit_assign(tcx, mir, place, rvalue, statement.source_info.span);
+            }
+        }
+        if let mir::TerminatorKind::Call {
+            func,
+            args,
+            destination,
+            fn_span,
+            ..
+        } = &data.terminator().kind
+        {
+            if let Some((callee_def_id, _)) = func.const_fn_def() {
+                if !callee_def_id.is_local() {
+                    let item_name = tcx.opt_item_name(callee_def_id);
+                    checker.visit_call(
+                        tcx,
+                        mir,
+                        callee_def_id,
+                        item_name.as_ref().map_or("", |name| name.as_str()),
+                        args,
+                        destination,
+                        *fn_span,
+                    );
+                }
+            }
+        }
+    }
+    checker.quotients.contains_key(&mir::RETURN_PLACE)
+}
+
+// Returns the value of the operand if it is an integer constant
+fn constant_of(operand: &mir::Operand<'_>) -> Option<u128> {
+    let mir::Operand::Constant(constant) = operand else {
+        return None;
+    };
+    let scalar_int = constant.const_.try_to_scalar_int()?;
+    scalar_int.try_to_bits(scalar_int.size()).ok()
+}
+
+/// The unit of a value that is derived from a field of the Solana clock sysvar.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TimeUnit {
+    Seconds,
+    Slots,
+    Epochs,
+}
+
+impl TimeUnit {
+    /// Returns the unit of the named field of ``solana_program::clock::Clock``.
+    pub fn of_clock_field(field_name: &str) -> Option<TimeUnit> {
+        match field_name {
+            "unix_timestamp" | "epoch_start_timestamp" => Some(TimeUnit::Seconds),
+            "slot" => Some(TimeUnit::Slots),
+            "epoch" | "leader_schedule_epoch" => Some(TimeUnit::Epochs),
+            _ => None,
+        }
+    }
+}
+
+impl Display for TimeUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeUnit::Seconds => f.write_str("seconds"),
+            TimeUnit::Slots => f.write_str("slots"),
+            TimeUnit::Epochs => f.write_str("epochs"),
+        }
+    }
+}
+
+// Hold states for the time unit mixing
+pub struct TimeUnitChecker {
+    // The units of the locals that hold values derived from clock fields
+    pub local_units: HashMap<mir::Local, TimeUnit>,
+    // The units of the first arithmetic operation found to mix units or to multiply two times
+    pub mixed_units: Option<(TimeUnit, TimeUnit)>,
+    // The span contains codes related to time unit mixing
+    pub time_unit_mixing_span: Span,
+}
+
+impl Default for TimeUnitChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeUnitChecker {
+    pub fn new() -> TimeUnitChecker {
+        TimeUnitChecker {
+            local_units: HashMap::default(),
+            mixed_units: None,
+            time_unit_mixing_span: rustc_span::DUMMY_SP,
+        }
+    }
+
+    /// Check if time units are mixed. This happens if values of different units are added,
+    /// subtracted or compared, or if two values that both carry a unit are multiplied.
+    pub fn check(&self) -> bool {
+        self.mixed_units.is_some()
+    }
+
+    /// Updates the units of the locals with the assignment of rvalue to place.
+    pub fn visit_assign<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+        span: Span,
+    ) {
+        let unit = match rvalue {
+            mir::Rvalue::Use(operand) | mir::Rvalue::Cast(_, operand, _) => {
+                self.unit_of_operand(tcx, mir, operand)
+            }
+            mir::Rvalue::CopyForDeref(place) => self.unit_of_place(tcx, mir, place),
+            mir::Rvalue::BinaryOp(bin_op, box (left, right)) => {
+                let left_unit = self.unit_of_operand(tcx, mir, left);
+                let right_unit = self.unit_of_operand(tcx, mir, right);
+                self.visit_binary_op(*bin_op, left_unit, right_unit, span)
+            }
+            _ => None,
+        };
+        if !place.projection.is_empty() {
+            return;
+        }
+        if let Some(unit) = unit {
+            self.local_units.insert(place.local, unit);
+        } else {
+            self.local_units.remove(&place.local);
+        }
+    }
+
+    fn visit_binary_op(
+        &mut self,
+        bin_op: mir::BinOp,
+        left_unit: Option<TimeUnit>,
+        right_unit: Option<TimeUnit>,
+        span: Span,
+    ) -> Option<TimeUnit> {
+        match bin_op {
+            mir::BinOp::Add
+            | mir::BinOp::AddUnchecked
+            | mir::BinOp::AddWithOverflow
+            | mir::BinOp::Sub
+            | mir::BinOp::SubUnchecked
+            | mir::BinOp::SubWithOverflow => match (left_unit, right_unit) {
+                (Some(l), Some(r)) if l != r => {
+                    self.report(l, r, span);
+                    None
+                }
+                _ => left_unit.or(right_unit),
+            },
+            mir::BinOp::Eq
+            | mir::BinOp::Ne
+            | mir::BinOp::Lt
+            | mir::BinOp::Le
+            | mir::BinOp::Gt
+            | mir::BinOp::Ge
+            | mir::BinOp::Cmp => {
+                if let (Some(l), Some(r)) = (left_unit, right_unit) {
+                    if l != r {
+                        self.report(l, r, span);
+                    }
+                }
+                None
+            }
+            mir::BinOp::Mul | mir::BinOp::MulUnchecked | mir::BinOp::MulWithOverflow => {
+                match (left_unit, right_unit) {
+                    (Some(l), Some(r)) => {
+                        self.report(l, r, span);
+                        None
+                    }
+                    _ => left_unit.or(right_unit),
+                }
+            }
+            // Scaling a value by a unitless value keeps the unit, a ratio of two times does not.
+            mir::BinOp::Div | mir::BinOp::Rem => {
+                if right_unit.is_none() {
+                    left_unit
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Describes the first operation found to misuse time units.
+    pub fn describe(&self) -> String {
+        match self.mixed_units {
+            Some((left_unit, right_unit)) if left_unit == right_unit => {
+                format!("possible product of two times ({left_unit} times {right_unit}) for the smart contract")
+            }
+            Some((left_unit, right_unit)) => {
+                format!("possible mixing of time units ({left_unit} and {right_unit}) for the smart contract")
+            }
+            None => String::new(),
+        }
+    }
+
+    fn report(&mut self, left_unit: TimeUnit, right_unit: TimeUnit, span: Span) {
+        if self.mixed_units.is_none() {
+            self.mixed_units = Some((left_unit, right_unit));
+            self.time_unit_mixing_span = span;
+        }
+    }
+
+    fn unit_of_operand<'tcx>(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        operand: &mir::Operand<'tcx>,
+    ) -> Option<TimeUnit> {
+        match operand {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => {
+                self.unit_of_place(tcx, mir, place)
+            }
+            mir::Operand::Constant(..) => None,
+        }
+    }
+
+    /// A place has a unit if it is a field of a clock, or if it is rooted in a local
+    /// that has a unit. The latter covers the results of checked arithmetic, which are tuples.
+    fn unit_of_place<'tcx>(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        place: &mir::Place<'tcx>,
+    ) -> Option<TimeUnit> {
+        if let Some(unit) = self.local_units.get(&place.local) {
+            return Some(*unit);
+        }
+        clock_field_unit(tcx, mir, place)
+    }
+}
+
+/// Returns the unit of the place if it is a field of ``solana_program::clock::Clock``.
+fn clock_field_unit<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mir: &mir::Body<'tcx>,
+    place: &mir::Place<'tcx>,
+) -> Option<TimeUnit> {
+    for (base, elem) in place.iter_projections() {
+        if let mir::ProjectionElem::Field(field_index, _) = elem {
+            let base_ty = base.ty(mir, tcx).ty;
+            if let TyKind::Adt(def, _) = base_ty.kind() {
+                if def.is_struct() && tcx.def_path_str(def.did()).ends_with("clock::Clock") {
+                    let field_name = def.non_enum_variant().fields[field_index].name;
+                    return TimeUnit::of_clock_field(field_name.as_str());
+                }
+            }
+        }
+    }
+    None
 }
 
+/// The number of lamports in one SOL.
+pub const LAMPORTS_PER_SOL: u128 = 1_000_000_000;
+
+/// The unit of an amount of SOL, as told by the built-in tags Tag::LAMPORTS and Tag::SOL of a
+/// value, or by the value and the name of a constant.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SolUnit {
+    Lamports,
+    Sol,
+    /// LAMPORTS_PER_SOL or one of its multiples, which converts an amount of SOL to lamports.
+    ConversionFactor,
+}
+
+impl SolUnit {
+    /// Returns the unit of a constant with the given value, if known, that is written in the
+    /// source as the given text. Multiples of LAMPORTS_PER_SOL are conversion factors, whatever
+    /// their name, and the other constants whose names have SOL as a word, such as
+    /// MIN_DEPOSIT_SOL, are amounts of SOL.
+    pub fn of_constant(value: Option<u128>, text: &str) -> Option<SolUnit> {
+        let name = text.rsplit("::").next().unwrap_or(text).trim();
+        let is_name = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if value.is_some_and(|v| v > 0 && v % LAMPORTS_PER_SOL == 0)
+            || (is_name && name == "LAMPORTS_PER_SOL")
+        {
+            Some(SolUnit::ConversionFactor)
+        } else if is_name && name.split('_').any(|word| word == "SOL") {
+            Some(SolUnit::Sol)
+        } else {
+            None
+        }
+    }
+}
 
+// Hold states for the mixing of SOL and lamports. The lamports of accounts are amounts in
+// lamports, and so are the products of an amount with LAMPORTS_PER_SOL, while the constants that
+// are named after SOL and the quotients of an amount in lamports by LAMPORTS_PER_SOL are amounts
+// in SOL. The units are carried by built-in tags, so they follow the amounts through the
+// functions that compute them. An amount in lamports that is multiplied again by LAMPORTS_PER_SOL,
+// and an amount in lamports that is added to or compared with an amount in SOL, are reported.
+#[derive(Default)]
+pub struct SolUnitChecker {
+    // The first multiplication of an amount in lamports by LAMPORTS_PER_SOL
+    pub double_conversion_span: Option<Span>,
+    // The first arithmetic operation or comparison of an amount in lamports with one in SOL
+    pub mixed_units_span: Option<Span>,
+}
 
+impl SolUnitChecker {
+    /// Returns the unit of the result of the binary operation on operands of the given units,
+    /// if the operation itself gives the result a unit, and records the operations that misuse
+    /// the units.
+    pub fn visit_binary_op(
+        &mut self,
+        bin_op: mir::BinOp,
+        left_unit: Option<SolUnit>,
+        right_unit: Option<SolUnit>,
+        span: Span,
+    ) -> Option<SolUnit> {
+        match bin_op {
+            mir::BinOp::Mul | mir::BinOp::MulUnchecked | mir::BinOp::MulWithOverflow => {
+                match (left_unit, right_unit) {
+                    (Some(SolUnit::ConversionFactor), unit)
+                    | (unit, Some(SolUnit::ConversionFactor)) => {
+                        if unit == Some(SolUnit::Lamports) {
+                            self.double_conversion_span.get_or_insert(span);
+                        }
+                        Some(SolUnit::Lamports)
+                    }
+                    _ => None,
+                }
+            }
+            mir::BinOp::Div => match (left_unit, right_unit) {
+                (Some(SolUnit::Lamports), Some(SolUnit::ConversionFactor)) => Some(SolUnit::Sol),
+                _ => None,
+            },
+            mir::BinOp::Add
+            | mir::BinOp::AddUnchecked
+            | mir::BinOp::AddWithOverflow
+            | mir::BinOp::Sub
+            | mir::BinOp::SubUnchecked
+            | mir::BinOp::SubWithOverflow => match (left_unit, right_unit) {
+                (Some(SolUnit::Lamports), Some(SolUnit::Sol))
+                | (Some(SolUnit::Sol), Some(SolUnit::Lamports)) => {
+                    self.mixed_units_span.get_or_insert(span);
+                    None
+                }
+                // Constants are not tagged, so the unit of a named amount of SOL is passed on here
+                (Some(SolUnit::Sol), _) | (_, Some(SolUnit::Sol)) => Some(SolUnit::Sol),
+                _ => None,
+            },
+            mir::BinOp::Eq
+            | mir::BinOp::Ne
+            | mir::BinOp::Lt
+            | mir::BinOp::Le
+            | mir::BinOp::Gt
+            | mir::BinOp::Ge
+            | mir::BinOp::Cmp => {
+                if matches!(
+                    (left_unit, right_unit),
+                    (Some(SolUnit::Lamports), Some(SolUnit::Sol))
+                        | (Some(SolUnit::Sol), Some(SolUnit::Lamports))
+                ) {
+                    self.mixed_units_span.get_or_insert(span);
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+}
 
+// A range of positions of the accounts slice that an iterator can yield
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AccountWindow {
+    pub start: u64,
+    // The first position past the window, None if the window extends to the end of the slice
+    pub end: Option<u64>,
+}
 
+impl AccountWindow {
+    pub fn contains(&self, position: u64) -> bool {
+        position >= self.start && self.end.is_none_or(|end| position < end)
+    }
+}
+
+// Hold states for the accounts that are dropped by an iterator window
+pub struct AccountWindowChecker {
+    // True if the function is a program entrypoint, the only kind of function that is checked
+    pub in_entrypoint: bool,
+    // The constant values of locals that are used as indices or iterator arguments
+    pub constant_locals: HashMap<mir::Local, u64>,
+    // The locals that hold the length of the accounts slice
+    pub length_locals: HashSet<mir::Local>,
+    // The comparisons of the length of the accounts slice with a constant, by the local holding
+    // the result, normalized to the form length op constant
+    pub length_comparisons: HashMap<mir::Local, (mir::BinOp, u64)>,
+    // The number of accounts that the client must supply, according to checks of accounts.len()
+    // that guard the return of an error
+    pub counted_accounts: u64,
+    // The windows of the locals that hold iterators over the accounts slice
+    pub iterator_windows: HashMap<mir::Local, AccountWindow>,
+    // The blocks that read each position of the accounts slice by index
+    pub indexed_positions: BTreeMap<u64, Vec<mir::BasicBlock>>,
+    // The windows that are created by the function, with the blocks and spans creating them
+    pub windows: Vec<(mir::BasicBlock, AccountWindow, Span)>,
+    // The first position found to be skipped by a window, and whether it was counted rather than read
+    pub dropped_position: Option<(u64, bool)>,
+    // The span contains codes related to the dropped account
+    pub dropped_account_span: Span,
+}
+
+impl Default for AccountWindowChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AccountWindowChecker {
+    pub fn new() -> AccountWindowChecker {
+        AccountWindowChecker {
+            in_entrypoint: false,
+            constant_locals: HashMap::default(),
+            length_locals: HashSet::default(),
+            length_comparisons: HashMap::default(),
+            counted_accounts: 0,
+            iterator_windows: HashMap::default(),
+            indexed_positions: BTreeMap::default(),
+            windows: Vec::new(),
+            dropped_position: None,
+            dropped_account_span: rustc_span::DUMMY_SP,
+        }
+    }
+
+    /// Check if an account is dropped. This happens if a position of the accounts slice is read
+    /// by index in one branch, or counted by a check of accounts.len(), while an iterator that
+    /// skips over that position is used on a path that does not read it.
+    pub fn check(&self) -> bool {
+        self.dropped_position.is_some()
+    }
+
+    /// Describes the first account found to be dropped.
+    pub fn describe(&self) -> String {
+        match self.dropped_position {
+            Some((position, false)) => format!(
+                "possible unchecked account at position {position}, which is read in another branch but skipped by an iterator, for the smart contract"
+            ),
+            Some((position, true)) => format!(
+                "possible unchecked account at position {position}, which is counted by accounts.len() but skipped by an iterator, for the smart contract"
+            ),
+            None => String::new(),
+        }
+    }
+
+    /// Records the constants assigned to locals, the accounts read by a constant index
+    /// and the number of accounts required by comparisons with the length of the accounts slice.
+    pub fn visit_assign<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        location: mir::Location,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+    ) {
+        if !self.in_entrypoint {
+            return;
+        }
+        let mut is_length = false;
+        match rvalue {
+            mir::Rvalue::Use(operand) => {
+                if let Some(value) = self.constant_of_operand(operand) {
+                    if place.projection.is_empty() {
+                        self.constant_locals.insert(place.local, value);
+                    }
+                    return;
+                }
+                if let mir::Operand::Copy(source) | mir::Operand::Move(source) = operand {
+                    self.visit_read(tcx, mir, location, source);
+                }
+            }
+            mir::Rvalue::Ref(_, _, source) | mir::Rvalue::RawPtr(_, source) => {
+                self.visit_read(tcx, mir, location, source);
+            }
+            // This is what a call of len on a slice is lowered to
+            mir::Rvalue::UnaryOp(mir::UnOp::PtrMetadata, operand) => {
+                is_length = is_account_info_type(tcx, operand.ty(mir, tcx));
+            }
+            mir::Rvalue::BinaryOp(bin_op, box (left, right)) => {
+                if place.projection.is_empty() {
+                    self.visit_comparison(place.local, *bin_op, left, right);
+                }
+            }
+            _ => {}
+        }
+        if place.projection.is_empty() {
+            self.constant_locals.remove(&place.local);
+            if is_length {
+                self.length_locals.insert(place.local);
+            } else {
+                self.length_locals.remove(&place.local);
+            }
+        }
+    }
+
+    /// Tracks the windows of iterators over the accounts slice through calls to skip and take,
+    /// as well as calls to len that have not been lowered.
+    #[allow(clippy::too_many_arguments)]
+    pub fn visit_call<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        location: mir::Location,
+        callee_name: &str,
+        args: &[Spanned<mir::Operand<'tcx>>],
+        destination: mir::Place<'tcx>,
+        span: Span,
+    ) {
+        if !self.in_entrypoint || !destination.projection.is_empty() {
+            return;
+        }
+        let Some(receiver) = args.first() else {
+            return;
+        };
+        if !is_account_info_type(tcx, receiver.node.ty(mir, tcx)) {
+            return;
+        }
+        if callee_name.ends_with(".len") {
+            self.length_locals.insert(destination.local);
+            return;
+        }
+        let receiver_window = match &receiver.node {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => {
+                self.iterator_windows.get(&place.local).copied()
+            }
+            mir::Operand::Constant(..) => None,
+        }
+        .unwrap_or(AccountWindow {
+            start: 0,
+            end: None,
+        });
+        let argument = args.get(1).and_then(|arg| self.constant_of_operand(&arg.node));
+        let window = if callee_name.ends_with("Iterator.skip") {
+            let Some(count) = argument else {
+                return;
+            };
+            AccountWindow {
+                start: receiver_window.start.saturating_add(count),
+                end: receiver_window.end,
+            }
+        } else if callee_name.ends_with("Iterator.take") {
+            let Some(count) = argument else {
+                return;
+            };
+            let end = receiver_window.start.saturating_add(count);
+            AccountWindow {
+                start: receiver_window.start,
+                end: Some(receiver_window.end.map_or(end, |e| e.min(end))),
+            }
+        } else {
+            return;
+        };
+        self.iterator_windows.insert(destination.local, window);
+        let block = location.block;
+        if !self
+            .windows
+            .iter()
+            .any(|(b, w, _)| *b == block && *w == window)
+        {
+            self.windows.push((block, window, span));
+        }
+    }
+
+    /// Records a comparison of the length of the accounts slice with a constant. Whether the
+    /// comparison requires the client to supply a number of accounts depends on whether it
+    /// guards the return of an error, which is known once the body has been visited.
+    fn visit_comparison(
+        &mut self,
+        result: mir::Local,
+        bin_op: mir::BinOp,
+        left: &mir::Operand<'_>,
+        right: &mir::Operand<'_>,
+    ) {
+        let is_length = |operand: &mir::Operand<'_>| match operand {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => {
+                place.projection.is_empty() && self.length_locals.contains(&place.local)
+            }
+            mir::Operand::Constant(..) => false,
+        };
+        // Normalize the comparison to the form length op constant.
+        let (bin_op, constant) = if is_length(left) {
+            (bin_op, self.constant_of_operand(right))
+        } else if is_length(right) {
+            let bin_op = match bin_op {
+                mir::BinOp::Lt => mir::BinOp::Gt,
+                mir::BinOp::Le => mir::BinOp::Ge,
+                mir::BinOp::Gt => mir::BinOp::Lt,
+                mir::BinOp::Ge => mir::BinOp::Le,
+                _ => bin_op,
+            };
+            (bin_op, self.constant_of_operand(left))
+        } else {
+            return;
+        };
+        let Some(constant) = constant else {
+            return;
+        };
+        self.length_comparisons.insert(result, (bin_op, constant));
+    }
+
+    /// Works out the number of accounts that the client must supply, from the comparisons of the
+    /// length of the accounts slice that must hold, or must not hold, to avoid returning an error.
+    fn count_required_accounts(&mut self, guards: &[ErrorGuard]) {
+        self.counted_accounts = 0;
+        for guard in guards {
+            let Some((bin_op, constant)) = self.length_comparisons.get(&guard.condition) else {
+                continue;
+            };
+            // Normalize to the comparison that holds when no error is returned.
+            let bin_op = if guard.passes_when {
+                *bin_op
+            } else {
+                match bin_op {
+                    mir::BinOp::Lt => mir::BinOp::Ge,
+                    mir::BinOp::Le => mir::BinOp::Gt,
+                    mir::BinOp::Gt => mir::BinOp::Le,
+                    mir::BinOp::Ge => mir::BinOp::Lt,
+                    mir::BinOp::Eq => mir::BinOp::Ne,
+                    mir::BinOp::Ne => mir::BinOp::Eq,
+                    _ => continue,
+                }
+            };
+            let count = match bin_op {
+                mir::BinOp::Ge | mir::BinOp::Eq => *constant,
+                mir::BinOp::Gt => constant.saturating_add(1),
+                _ => continue,
+            };
+            self.counted_accounts = self.counted_accounts.max(count);
+        }
+    }
+
+    fn visit_read<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        location: mir::Location,
+        place: &mir::Place<'tcx>,
+    ) {
+        for (base, elem) in place.iter_projections() {
+            let position = match elem {
+                mir::ProjectionElem::Index(local) => self.constant_locals.get(&local).copied(),
+                mir::ProjectionElem::ConstantIndex {
+                    offset,
+                    from_end: false,
+                    ..
+                } => Some(offset),
+                _ => None,
+            };
+            let Some(position) = position else {
+                continue;
+            };
+            if !is_account_info_type(tcx, base.ty(mir, tcx).ty) {
+                continue;
+            }
+            let blocks = self.indexed_positions.entry(position).or_default();
+            if !blocks.contains(&location.block) {
+                blocks.push(location.block);
+            }
+        }
+    }
+
+    /// Looks for a position that is excluded by a window and not read on the path through the
+    /// window, i.e. not read in a block that dominates or is dominated by the block creating the
+    /// window. Such a position is dropped if it is read by index on a different branch, or if it
+    /// is below the number of accounts counted by checks of accounts.len().
+    /// This is done once, after the body has been visited.
+    pub fn find_dropped_position(&mut self, mir: &mir::Body<'_>) {
+        self.dropped_position = None;
+        if self.windows.is_empty() {
+            return;
+        }
+        self.count_required_accounts(&find_error_guards(mir));
+        let dominators = mir.basic_blocks.dominators();
+        let related = |a: mir::BasicBlock, b: mir::BasicBlock| {
+            dominators.dominates(a, b) || dominators.dominates(b, a)
+        };
+        for (window_block, window, span) in &self.windows {
+            let read_positions = self.indexed_positions.keys().copied();
+            let counted_positions = 0..self.counted_accounts;
+            for position in counted_positions.chain(read_positions) {
+                if window.contains(position) {
+                    continue;
+                }
+                let blocks = self
+                    .indexed_positions
+                    .get(&position)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                if blocks.iter().any(|b| related(*b, *window_block)) {
+                    continue;
+                }
+                self.dropped_position = Some((position, position < self.counted_accounts));
+                self.dropped_account_span = *span;
+                return;
+            }
+        }
+    }
+
+    fn constant_of_operand(
+        &self,
+        operand: &mir::Operand<'_>,
+    ) -> Option<u64> {
+        match operand {
+            mir::Operand::Constant(constant) => {
+                if !matches!(constant.ty().kind(), TyKind::Uint(rustc_middle::ty::UintTy::Usize)) {
+                    return None;
+                }
+                let scalar_int = constant.const_.try_to_scalar_int()?;
+                scalar_int.try_to_bits(scalar_int.size()).ok().map(|v| v as u64)
+            }
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => {
+                if place.projection.is_empty() {
+                    self.constant_locals.get(&place.local).copied()
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+// A conditional branch that returns an error on one of its edges, such as the expansion of
+// `if !cond { return Err(err.into()) }` by helper macros like require!. Guards are recognized by
+// the structure of the MIR, so it does not matter how the source expressed them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ErrorGuard {
+    // The block that ends with the branch
+    pub block: mir::BasicBlock,
+    // The boolean local that the branch switches on
+    pub condition: mir::Local,
+    // The value of the condition on the edge that does not return an error
+    pub passes_when: bool,
+}
+
+// The maximum number of blocks followed from a branch edge to the return of an error
+const MAX_ERROR_PATH_LENGTH: usize = 16;
+
+// Finds the branches of the body that return an error on exactly one of their edges
+pub fn find_error_guards(mir: &mir::Body<'_>) -> Vec<ErrorGuard> {
+    let mut guards = Vec::new();
+    for (block, data) in mir.basic_blocks.iter_enumerated() {
+        if data.is_cleanup {
+            continue;
+        }
+        let mir::TerminatorKind::SwitchInt { discr, targets } = &data.terminator().kind else {
+            continue;
+        };
+        let Some(place) = discr.place() else {
+            continue;
+        };
+        if !place.projection.is_empty() || !mir.local_decls[place.local].ty.is_bool() {
+            continue;
+        }
+        let mut values = targets.iter();
+        let (Some((value, target)), None) = (values.next(), values.next()) else {
+            continue;
+        };
+        let errs_on_value = returns_error(mir, target);
+        let errs_otherwise = returns_error(mir, targets.otherwise());
+        if errs_on_value == errs_otherwise {
+            continue;
+        }
+        // The otherwise edge is taken for any value of the condition other than value.
+        let passes_when = if errs_on_value {
+            value == 0
+        } else {
+            value != 0
+        };
+        guards.push(ErrorGuard {
+            block,
+            condition: place.local,
+            passes_when,
+        });
+    }
+    guards
+}
+
+// Check if the path from the block, which must not branch, ends with the return of an Err value
+fn returns_error(mir: &mir::Body<'_>, start: mir::BasicBlock) -> bool {
+    let mut block = start;
+    let mut assigns_error = false;
+    for _ in 0..MAX_ERROR_PATH_LENGTH {
+        let data = &mir.basic_blocks[block];
+        if data.is_cleanup {
+            return false;
+        }
+        for statement in &data.statements {
+            match &statement.kind {
+                mir::StatementKind::Assign(box (place, rvalue))
+                    if place.local == mir::RETURN_PLACE =>
+                {
+                    assigns_error = matches!(rvalue,
+                        mir::Rvalue::Aggregate(box mir::AggregateKind::Adt(_, variant, ..), _)
+                            if is_err_variant(mir, *variant));
+                }
+                mir::StatementKind::SetDiscriminant {
+                    place,
+                    variant_index,
+                } if place.local == mir::RETURN_PLACE => {
+                    assigns_error = is_err_variant(mir, *variant_index);
+                }
+                _ => {}
+            }
+        }
+        match &data.terminator().kind {
+            mir::TerminatorKind::Return => return assigns_error,
+            mir::TerminatorKind::Goto { target }
+            | mir::TerminatorKind::Drop { target, .. }
+            | mir::TerminatorKind::Assert { target, .. }
+            | mir::TerminatorKind::FalseEdge {
+                real_target: target,
+                ..
+            }
+            | mir::TerminatorKind::FalseUnwind {
+                real_target: target,
+                ..
+            } => block = *target,
+            mir::TerminatorKind::Call {
+                destination,
+                target: Some(target),
+                ..
+            } => {
+                if destination.local == mir::RETURN_PLACE {
+                    assigns_error = false;
+                }
+                block = *target;
+            }
+            _ => return false,
+        }
+    }
+    false
+}
+
+// Check if the variant of the return type of the body is the Err variant of a Result
+fn is_err_variant(mir: &mir::Body<'_>, variant: rustc_target::abi::VariantIdx) -> bool {
+    match mir.local_decls[mir::RETURN_PLACE].ty.kind() {
+        TyKind::Adt(def, _) if def.is_enum() => def.variant(variant).name.as_str() == "Err",
+        _ => false,
+    }
+}
+
+// Where an amount used by an entrypoint comes from. Both instruction data and account data are
+// controlled by the caller, unless the account is owned by the program.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AmountSource {
+    // The instruction data of the entrypoint
+    InstructionData,
+    // The data of the account held by the local
+    AccountData(mir::Local),
+    // A constant of the program
+    Constant,
+    // A computation over values of different sources
+    Computed,
+}
+
+// The maximum number of passes over the body that propagate the sources of values
+const MAX_PROVENANCE_PASSES: usize = 3;
+
+// Hold the accounts that the locals of a body hold, and for an entrypoint, the sources of the
+// values of its locals and the accounts that it checks to be owned by the program
+#[derive(Default)]
+pub struct AmountProvenance {
+    // True if the function is a program entrypoint, the only kind of function that is classified
+    pub in_entrypoint: bool,
+    // The source of the value of each local, if known
+    pub sources: HashMap<mir::Local, AmountSource>,
+    // The first local to hold each account, by the locals that hold copies or reborrows of it
+    pub account_aliases: HashMap<mir::Local, mir::Local>,
+    // The locals that hold the owner of an account
+    pub owner_locals: HashMap<mir::Local, mir::Local>,
+    // The locals that hold the program id
+    pub program_id_locals: HashSet<mir::Local>,
+    // The comparisons of the owner of an account with the program id, by the local holding the
+    // result, with true if the result is true when the owner is the program
+    pub owner_comparisons: HashMap<mir::Local, (mir::Local, bool)>,
+    // The accounts whose owner is checked to be the program by a guard that returns an error
+    pub program_owned_accounts: HashSet<mir::Local>,
+}
+
+impl AmountProvenance {
+    pub fn new<'tcx>(tcx: TyCtxt<'tcx>, mir: &mir::Body<'tcx>) -> AmountProvenance {
+        let mut provenance = AmountProvenance {
+            account_aliases: find_account_aliases(tcx, mir),
+            ..AmountProvenance::default()
+        };
+        if !is_entrypoint(tcx, mir) {
+            return provenance;
+        }
+        provenance.in_entrypoint = true;
+        provenance
+            .program_id_locals
+            .insert(mir::Local::from_usize(1));
+        provenance
+            .sources
+            .insert(mir::Local::from_usize(3), AmountSource::InstructionData);
+        for _ in 0..MAX_PROVENANCE_PASSES {
+            let known_sources = provenance.sources.clone();
+            for data in mir.basic_blocks.iter() {
+                if data.is_cleanup {
+                    continue;
+                }
+                for statement in &data.statements {
+                    if let mir::StatementKind::Assign(box (place, rvalue)) = &statement.kind {
+                        provenance.visit_assign(tcx, mir, place, rvalue);
+                    }
+                }
+                if let mir::TerminatorKind::Call {
+                    func,
+                    args,
+                    destination,
+                    ..
+                } = &data.terminator().kind
+                {
+                    if let Some((callee_def_id, _)) = func.const_fn_def() {
+                        provenance.visit_call(tcx, callee_def_id, args, destination);
+                    }
+                }
+            }
+            if provenance.sources == known_sources {
+                break;
+            }
+        }
+        for guard in find_error_guards(mir) {
+            if let Some((account, true_when_owned)) =
+                provenance.owner_comparisons.get(&guard.condition)
+            {
+                if guard.passes_when == *true_when_owned {
+                    provenance.program_owned_accounts.insert(*account);
+                }
+            }
+        }
+        provenance
+    }
+
+    fn visit_assign<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+    ) {
+        let source = match rvalue {
+            mir::Rvalue::Use(operand)
+            | mir::Rvalue::Cast(_, operand, _)
+            | mir::Rvalue::Repeat(operand, _) => self.source_of_operand(operand),
+            mir::Rvalue::UnaryOp(un_op, operand) => {
+                if let (mir::UnOp::Not, Some(local)) = (un_op, operand.place().map(|p| p.local)) {
+                    if let Some((account, true_when_owned)) =
+                        self.owner_comparisons.get(&local).copied()
+                    {
+                        self.owner_comparisons
+                            .insert(place.local, (account, !true_when_owned));
+                    }
+                }
+                self.source_of_operand(operand)
+            }
+            mir::Rvalue::Ref(_, _, source)
+            | mir::Rvalue::RawPtr(_, source)
+            | mir::Rvalue::CopyForDeref(source) => {
+                match solana_compat::projected_account_field(tcx, mir, source) {
+                    Some((account, AccountField::Data)) => {
+                        Some(AmountSource::AccountData(self.account_of(account)))
+                    }
+                    Some((account, AccountField::Owner)) => {
+                        self.owner_locals
+                            .insert(place.local, self.account_of(account));
+                        None
+                    }
+                    _ => self.sources.get(&source.local).copied(),
+                }
+            }
+            mir::Rvalue::BinaryOp(_, box (left, right)) => {
+                match (self.source_of_operand(left), self.source_of_operand(right)) {
+                    (Some(AmountSource::Constant), Some(AmountSource::Constant)) => {
+                        Some(AmountSource::Constant)
+                    }
+                    (None, None) => None,
+                    _ => Some(AmountSource::Computed),
+                }
+            }
+            mir::Rvalue::Aggregate(_, operands) => self.join(operands.iter()),
+            _ => None,
+        };
+        if !place.projection.is_empty() {
+            return;
+        }
+        if let mir::Rvalue::Use(mir::Operand::Copy(source) | mir::Operand::Move(source))
+        | mir::Rvalue::Ref(_, _, source)
+        | mir::Rvalue::CopyForDeref(source) = rvalue
+        {
+            if source
+                .projection
+                .iter()
+                .all(|elem| matches!(elem, mir::ProjectionElem::Deref))
+            {
+                if self.program_id_locals.contains(&source.local) {
+                    self.program_id_locals.insert(place.local);
+                }
+                if let Some(account) = self.owner_locals.get(&source.local).copied() {
+                    self.owner_locals.insert(place.local, account);
+                }
+            }
+        }
+        if let Some(source) = source {
+            self.sources.insert(place.local, source);
+        }
+    }
+
+    fn visit_call<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        callee_def_id: DefId,
+        args: &[Spanned<mir::Operand<'tcx>>],
+        destination: &mir::Place<'tcx>,
+    ) {
+        if !destination.projection.is_empty() {
+            return;
+        }
+        let arg_local = |i: usize| {
+            args.get(i)
+                .and_then(|arg| arg.node.place())
+                .map(|place| place.local)
+        };
+        if let Some((AccountField::Data, _)) = solana_compat::accessed_field(tcx, callee_def_id) {
+            if let Some(account) = arg_local(0) {
+                let account = self.account_of(account);
+                self.sources
+                    .insert(destination.local, AmountSource::AccountData(account));
+            }
+            return;
+        }
+        let callee_name = tcx.item_name(callee_def_id);
+        if matches!(callee_name.as_str(), "eq" | "ne") {
+            if let (Some(left), Some(right)) = (arg_local(0), arg_local(1)) {
+                let account = match (self.owner_locals.get(&left), self.owner_locals.get(&right)) {
+                    (Some(account), _) if self.program_id_locals.contains(&right) => Some(*account),
+                    (_, Some(account)) if self.program_id_locals.contains(&left) => Some(*account),
+                    _ => None,
+                };
+                if let Some(account) = account {
+                    let true_when_owned = callee_name.as_str() == "eq";
+                    self.owner_comparisons
+                        .insert(destination.local, (account, true_when_owned));
+                    return;
+                }
+            }
+        }
+        if let Some(source) = self.join(args.iter().map(|arg| &arg.node)) {
+            self.sources.insert(destination.local, source);
+        }
+    }
+
+    /// Returns the first local to hold the account that the given local holds.
+    pub fn account_of(&self, local: mir::Local) -> mir::Local {
+        self.account_aliases.get(&local).copied().unwrap_or(local)
+    }
+
+    fn source_of_operand(&self, operand: &mir::Operand<'_>) -> Option<AmountSource> {
+        match operand {
+            mir::Operand::Constant(..) => Some(AmountSource::Constant),
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => {
+                self.sources.get(&place.local).copied()
+            }
+        }
+    }
+
+    // Returns the source of a value that is computed from the given operands, if the sources of
+    // all of them are known. Constants do not change the source of the other operands.
+    fn join<'a, 'tcx: 'a>(
+        &self,
+        operands: impl Iterator<Item = &'a mir::Operand<'tcx>>,
+    ) -> Option<AmountSource> {
+        let mut result = None;
+        for operand in operands {
+            let source = self.source_of_operand(operand)?;
+            result = match (result, source) {
+                (None, source) | (Some(AmountSource::Constant), source) => Some(source),
+                (Some(result), AmountSource::Constant) => Some(result),
+                (Some(result), source) if result == source => Some(result),
+                _ => Some(AmountSource::Computed),
+            };
+        }
+        result
+    }
+
+    /// Returns the source of the amounts that are operands of the arithmetic operation or the call
+    /// at the given location, if known.
+    pub fn source_at(&self, mir: &mir::Body<'_>, location: mir::Location) -> Option<AmountSource> {
+        if !self.in_entrypoint {
+            return None;
+        }
+        let data = &mir.basic_blocks[location.block];
+        if location.statement_index < data.statements.len() {
+            return match &data.statements[location.statement_index].kind {
+                mir::StatementKind::Assign(box (
+                    _,
+                    mir::Rvalue::BinaryOp(_, box (left, right)),
+                )) => self.join([left, right].into_iter()),
+                mir::StatementKind::Assign(box (_, mir::Rvalue::UnaryOp(_, operand))) => {
+                    self.source_of_operand(operand)
+                }
+                _ => None,
+            };
+        }
+        match &data.terminator().kind {
+            mir::TerminatorKind::Assert { msg, .. } => match &**msg {
+                mir::AssertKind::Overflow(_, left, right) => self.join([left, right].into_iter()),
+                mir::AssertKind::OverflowNeg(operand) => self.source_of_operand(operand),
+                _ => None,
+            },
+            mir::TerminatorKind::Call { args, .. } => self.join(args.iter().map(|arg| &arg.node)),
+            _ => None,
+        }
+    }
+
+    /// Returns the source of the value of the given operand of the entrypoint, if known.
+    pub fn source_of(&self, operand: &mir::Operand<'_>) -> Option<AmountSource> {
+        if !self.in_entrypoint {
+            return None;
+        }
+        self.source_of_operand(operand)
+    }
+
+    /// True if the amount comes from the data of an account that is checked to be owned by the
+    /// program, which the caller cannot write to.
+    pub fn is_program_owned(&self, source: AmountSource) -> bool {
+        match source {
+            AmountSource::AccountData(account) => self.program_owned_accounts.contains(&account),
+            _ => false,
+        }
+    }
+
+    /// True if the value is read from the instruction data or from the data of an account that
+    /// the caller can write to.
+    pub fn is_caller_controlled(&self, source: AmountSource) -> bool {
+        match source {
+            AmountSource::InstructionData => true,
+            AmountSource::AccountData(_) => !self.is_program_owned(source),
+            AmountSource::Constant | AmountSource::Computed => false,
+        }
+    }
+
+    /// The provenance class of the amount in the properties of a finding.
+    pub fn class_of(&self, source: AmountSource) -> &'static str {
+        match source {
+            AmountSource::InstructionData => "instruction_data",
+            AmountSource::AccountData(_) if self.is_program_owned(source) => {
+                "program_owned_account_data"
+            }
+            AmountSource::AccountData(_) => "account_data",
+            AmountSource::Constant => "constant",
+            AmountSource::Computed => "computed",
+        }
+    }
+
+    /// Describes where the amount comes from, for a note on a finding.
+    pub fn describe(&self, mir: &mir::Body<'_>, source: AmountSource) -> String {
+        self.describe_value(mir, source, "amount")
+    }
+
+    /// Describes where the value, which is named by the given noun, comes from.
+    pub fn describe_value(&self, mir: &mir::Body<'_>, source: AmountSource, noun: &str) -> String {
+        match source {
+            AmountSource::InstructionData => {
+                format!("the {noun} is read from the instruction data, which the caller controls")
+            }
+            AmountSource::AccountData(account) => {
+                let account_name = debug_name(mir, account)
+                    .map(|name| format!("`{name}`"))
+                    .unwrap_or_else(|| "an account".to_string());
+                if self.is_program_owned(source) {
+                    format!("the {noun} is read from the data of {account_name}, which is checked to be owned by the program")
+                } else {
+                    format!("the {noun} is read from the data of {account_name}, whose owner is not checked, so the caller controls it")
+                }
+            }
+            AmountSource::Constant => format!("the {noun} is a constant"),
+            AmountSource::Computed => {
+                format!("the {noun} is computed from values of different sources")
+            }
+        }
+    }
+}
+
+/// Returns the name of the variable that the local holds, if it is a user variable
+pub fn debug_name(mir: &mir::Body<'_>, local: mir::Local) -> Option<String> {
+    mir.var_debug_info
+        .iter()
+        .find(|info| {
+            matches!(info.value, mir::VarDebugInfoContents::Place(place)
+                if place.local == local && place.projection.is_empty())
+        })
+        .map(|info| info.name.to_string())
+}
+
+// The names of the functions that pass the data of their first argument on to their result, such
+// as the conversions of bytes into an integer and the unwrapping of a borrow of the data
+const DATA_PRESERVING_CALLS: &[&str] = &[
+    "as_ref",
+    "branch",
+    "deref",
+    "deref_mut",
+    "expect",
+    "from_be_bytes",
+    "from_le_bytes",
+    "from_ne_bytes",
+    "try_from",
+    "try_into",
+    "unwrap",
+];
+
+// A range of the bytes of the instruction data or of the data of an account. The start or the end
+// is None if the range leaves it open.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DataRange {
+    // The source of the data, either the instruction data or the data of an account
+    pub source: AmountSource,
+    pub start: Option<u128>,
+    pub end: Option<u128>,
+}
+
+impl DataRange {
+    // Returns the range of the bytes at the given offsets within this range
+    fn subrange(self, start: Option<u128>, end: Option<u128>) -> DataRange {
+        let offset = self.start.unwrap_or(0);
+        DataRange {
+            source: self.source,
+            start: start.map(|start| offset + start).or(self.start),
+            end: end.map(|end| offset + end).or(self.end),
+        }
+    }
+}
+
+impl Display for DataRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) if end == start + 1 => write!(f, "[{start}]"),
+            (start, end) => {
+                f.write_str("[")?;
+                if let Some(start) = start {
+                    write!(f, "{start}")?;
+                }
+                f.write_str("..")?;
+                if let Some(end) = end {
+                    write!(f, "{end}")?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+// Hold the ranges of the instruction data and of the data of accounts that the values of an
+// entrypoint are read from, so that the finding of an arithmetic operation that may overflow can
+// name the operand that the caller controls, such as a value read from instruction_data[1..9].
+// The operations themselves are checked with the overflow flags of the abstract values, using the
+// path condition, so an operation that is bounded by a preceding comparison, or that is done with
+// checked_add or saturating_add instead, is not reported.
+#[derive(Default)]
+pub struct ArithmeticOverflowChecker {
+    // The range of the data that the value of each local is read from, if known. The data itself
+    // is held by locals with an open range.
+    pub ranges: HashMap<mir::Local, DataRange>,
+    // The locals that hold a constant index or bound of a range
+    constant_locals: HashMap<mir::Local, u128>,
+    // The bounds of the constant ranges held by locals
+    range_bounds: HashMap<mir::Local, (Option<u128>, Option<u128>)>,
+}
+
+impl ArithmeticOverflowChecker {
+    pub fn new<'tcx>(
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        provenance: &AmountProvenance,
+    ) -> ArithmeticOverflowChecker {
+        let mut checker = ArithmeticOverflowChecker::default();
+        if !provenance.in_entrypoint {
+            return checker;
+        }
+        checker.ranges.insert(
+            mir::Local::from_usize(3),
+            DataRange {
+                source: AmountSource::InstructionData,
+                start: None,
+                end: None,
+            },
+        );
+        for block in mir.basic_blocks.reverse_postorder() {
+            let data = &mir.basic_blocks[*block];
+            if data.is_cleanup {
+                continue;
+            }
+            for statement in &data.statements {
+                if let mir::StatementKind::Assign(box (place, rvalue)) = &statement.kind {
+                    checker.visit_assign(tcx, mir, provenance, place, rvalue);
+                }
+            }
+            if let mir::TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } = &data.terminator().kind
+            {
+                if let Some((callee_def_id, _)) = func.const_fn_def() {
+                    checker.visit_call(tcx, provenance, callee_def_id, args, destination);
+                }
+            }
+        }
+        checker
+    }
+
+    fn visit_assign<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        provenance: &AmountProvenance,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+    ) {
+        if !place.projection.is_empty() {
+            return;
+        }
+        let range = match rvalue {
+            mir::Rvalue::Use(operand @ mir::Operand::Constant(..)) => {
+                if let Some(value) = self.constant_of_operand(operand) {
+                    self.constant_locals.insert(place.local, value);
+                }
+                None
+            }
+            mir::Rvalue::Use(mir::Operand::Copy(source) | mir::Operand::Move(source))
+            | mir::Rvalue::Cast(_, mir::Operand::Copy(source) | mir::Operand::Move(source), _) => {
+                self.range_of_place(source)
+            }
+            mir::Rvalue::Ref(_, _, source)
+            | mir::Rvalue::RawPtr(_, source)
+            | mir::Rvalue::CopyForDeref(source) => {
+                match solana_compat::projected_account_field(tcx, mir, source) {
+                    Some((_, AccountField::Data)) => {
+                        provenance
+                            .sources
+                            .get(&place.local)
+                            .map(|source| DataRange {
+                                source: *source,
+                                start: None,
+                                end: None,
+                            })
+                    }
+                    _ => self.range_of_place(source),
+                }
+            }
+            mir::Rvalue::Aggregate(box mir::AggregateKind::Adt(def_id, ..), operands) => {
+                let bounds: Vec<Option<u128>> = operands
+                    .iter()
+                    .map(|operand| self.constant_of_operand(operand))
+                    .collect();
+                let bounds = match (tcx.item_name(*def_id).as_str(), bounds.as_slice()) {
+                    ("Range", [Some(start), Some(end)]) => (Some(*start), Some(*end)),
+                    ("RangeFrom", [Some(start)]) => (Some(*start), None),
+                    ("RangeTo", [Some(end)]) => (None, Some(*end)),
+                    ("RangeFull", []) => (None, None),
+                    _ => return,
+                };
+                self.range_bounds.insert(place.local, bounds);
+                None
+            }
+            _ => None,
+        };
+        if let Some(range) = range {
+            self.ranges.insert(place.local, range);
+        }
+    }
+
+    fn visit_call<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        provenance: &AmountProvenance,
+        callee_def_id: DefId,
+        args: &[Spanned<mir::Operand<'tcx>>],
+        destination: &mir::Place<'tcx>,
+    ) {
+        if !destination.projection.is_empty() {
+            return;
+        }
+        if let Some((AccountField::Data, _)) = solana_compat::accessed_field(tcx, callee_def_id) {
+            if let Some(source) = provenance.sources.get(&destination.local) {
+                let range = DataRange {
+                    source: *source,
+                    start: None,
+                    end: None,
+                };
+                self.ranges.insert(destination.local, range);
+            }
+            return;
+        }
+        let Some(range) = args
+            .first()
+            .and_then(|arg| arg.node.place())
+            .and_then(|place| self.ranges.get(&place.local).copied())
+        else {
+            return;
+        };
+        let range = match tcx.item_name(callee_def_id).as_str() {
+            "index" | "index_mut" | "get" | "get_mut" => {
+                let Some(index) = args.get(1).map(|arg| &arg.node) else {
+                    return;
+                };
+                if let Some(position) = self.constant_of_operand(index) {
+                    range.subrange(Some(position), Some(position + 1))
+                } else if let Some((start, end)) = index
+                    .place()
+                    .and_then(|place| self.range_bounds.get(&place.local))
+                {
+                    range.subrange(*start, *end)
+                } else {
+                    return;
+                }
+            }
+            "first" => range.subrange(Some(0), Some(1)),
+            name if DATA_PRESERVING_CALLS.contains(&name) => range,
+            _ => return,
+        };
+        self.ranges.insert(destination.local, range);
+    }
+
+    // Returns the range of the data that the value at the place is read from, which is a single
+    // byte if the place indexes the data with a constant
+    fn range_of_place(&self, place: &mir::Place<'_>) -> Option<DataRange> {
+        let mut range = self.ranges.get(&place.local).copied()?;
+        for elem in place.projection.iter() {
+            range = match elem {
+                mir::ProjectionElem::Deref
+                | mir::ProjectionElem::Field(..)
+                | mir::ProjectionElem::Downcast(..) => range,
+                mir::ProjectionElem::Index(local) => {
+                    let position = self.constant_locals.get(&local).copied()?;
+                    range.subrange(Some(position), Some(position + 1))
+                }
+                mir::ProjectionElem::ConstantIndex {
+                    offset,
+                    from_end: false,
+                    ..
+                } => range.subrange(Some(offset as u128), Some(offset as u128 + 1)),
+                _ => return None,
+            };
+        }
+        Some(range)
+    }
+
+    fn constant_of_operand(&self, operand: &mir::Operand<'_>) -> Option<u128> {
+        match operand {
+            mir::Operand::Constant(constant) => {
+                if !matches!(constant.ty().kind(), TyKind::Uint(rustc_middle::ty::UintTy::Usize)) {
+                    return None;
+                }
+                let scalar_int = constant.const_.try_to_scalar_int()?;
+                scalar_int.try_to_bits(scalar_int.size()).ok()
+            }
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => {
+                if place.projection.is_empty() {
+                    self.constant_locals.get(&place.local).copied()
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Describes the first operand of the arithmetic operation or of the call at the given
+    /// location that is read from a known range of the instruction data or of the data of an
+    /// account, for a note on a finding.
+    pub fn describe_operand_at(
+        &self,
+        mir: &mir::Body<'_>,
+        location: mir::Location,
+        provenance: &AmountProvenance,
+    ) -> Option<String> {
+        let data = &mir.basic_blocks[location.block];
+        let operands: Vec<&mir::Operand<'_>> = if location.statement_index < data.statements.len()
+        {
+            match &data.statements[location.statement_index].kind {
+                mir::StatementKind::Assign(box (
+                    _,
+                    mir::Rvalue::BinaryOp(_, box (left, right)),
+                )) => vec![left, right],
+                mir::StatementKind::Assign(box (_, mir::Rvalue::UnaryOp(_, operand))) => {
+                    vec![operand]
+                }
+                _ => vec![],
+            }
+        } else {
+            match &data.terminator().kind {
+                mir::TerminatorKind::Assert { msg, .. } => match &**msg {
+                    mir::AssertKind::Overflow(_, left, right) => vec![left, right],
+                    mir::AssertKind::OverflowNeg(operand)
+                    | mir::AssertKind::DivisionByZero(operand)
+                    | mir::AssertKind::RemainderByZero(operand) => vec![operand],
+                    _ => vec![],
+                },
+                mir::TerminatorKind::Call { args, .. } => {
+                    args.iter().map(|arg| &arg.node).collect()
+                }
+                _ => vec![],
+            }
+        };
+        operands.into_iter().find_map(|operand| {
+            let range = self.ranges.get(&operand.place()?.local)?;
+            // The data itself is not an operand of arithmetic
+            if range.start.is_none() && range.end.is_none() {
+                return None;
+            }
+            Some(describe_range(mir, provenance, *range))
+        })
+    }
+}
+
+// Describes where a value that is read from the given range of data comes from
+fn describe_range(mir: &mir::Body<'_>, provenance: &AmountProvenance, range: DataRange) -> String {
+    match range.source {
+        AmountSource::AccountData(account) => {
+            let account_name = debug_name(mir, account)
+                .map(|name| format!("`{name}`"))
+                .unwrap_or_else(|| "an account".to_string());
+            if provenance.is_program_owned(range.source) {
+                format!("the value is read from the bytes {range} of the data of {account_name}, which is checked to be owned by the program")
+            } else {
+                format!("the value is read from the bytes {range} of the data of {account_name}, whose owner is not checked, so the caller controls it")
+            }
+        }
+        _ => {
+            let data_name = debug_name(mir, mir::Local::from_usize(3))
+                .unwrap_or_else(|| "instruction_data".to_string());
+            format!("the value is read from {data_name}{range}, which the caller controls")
+        }
+    }
+}
+
+// Whether an adjustment of a token amount adds to it or subtracts from it
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AmountDelta {
+    Credit,
+    Debit,
+}
+
+// The value by which a token amount is adjusted, identified by the local that first held it or by
+// its constant value
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AdjustedValue {
+    Local(mir::Local),
+    Constant(u128),
+    Unknown,
+}
+
+// Hold the adjustments of the amount fields of token accounts that an entrypoint unpacks and packs
+// through the Pack trait, so that tokens that are credited without being debited elsewhere, or
+// debited without being credited, are found unless the entrypoint mints or burns tokens
+#[derive(Default)]
+pub struct TokenConservationChecker {
+    // True if the function is a program entrypoint, the only kind of function that is checked
+    pub in_entrypoint: bool,
+    // The locals that hold a copy of the amount of a token account
+    pub amount_locals: HashSet<mir::Local>,
+    // The first local to hold each value, by the locals that hold copies of it
+    pub value_aliases: HashMap<mir::Local, mir::Local>,
+    // The adjustments of token amounts, with the values they adjust by and their spans
+    pub adjustments: Vec<(AmountDelta, AdjustedValue, Span)>,
+    // True if the entrypoint writes the amount of a token account
+    pub writes_amounts: bool,
+    // True if the entrypoint calls an spl_token instruction that mints or burns tokens
+    pub mints_or_burns: bool,
+}
+
+impl TokenConservationChecker {
+    pub fn new<'tcx>(tcx: TyCtxt<'tcx>, mir: &mir::Body<'tcx>) -> TokenConservationChecker {
+        let mut checker = TokenConservationChecker::default();
+        if !is_entrypoint(tcx, mir) {
+            return checker;
+        }
+        checker.in_entrypoint = true;
+        let Some(pack_trait) = tcx
+            .all_traits()
+            .find(|trait_def_id| tcx.item_name(*trait_def_id).as_str() == "Pack")
+        else {
+            return checker;
+        };
+        let is_pack_state = |ty: Ty<'tcx>| match ty.kind() {
+            TyKind::Adt(def, _) => tcx.all_impls(pack_trait).any(|impl_def_id| {
+                matches!(tcx.type_of(impl_def_id).instantiate_identity().kind(),
+                    TyKind::Adt(impl_def, _) if impl_def.did() == def.did())
+            }),
+            _ => false,
+        };
+        // True if the place is the amount field of a value of a type that implements Pack
+        let is_token_amount = |place: &mir::Place<'tcx>| {
+            let Some((base, mir::ProjectionElem::Field(index, _))) =
+                place.as_ref().iter_projections().last()
+            else {
+                return false;
+            };
+            let base_ty = base.ty(mir, tcx).ty;
+            match base_ty.kind() {
+                TyKind::Adt(def, _) if def.is_struct() => {
+                    def.non_enum_variant().fields[index].name.as_str() == "amount"
+                        && is_pack_state(base_ty)
+                }
+                _ => false,
+            }
+        };
+        for data in mir.basic_blocks.iter() {
+            if data.is_cleanup {
+                continue;
+            }
+            for statement in &data.statements {
+                let mir::StatementKind::Assign(box (place, rvalue)) = &statement.kind else {
+                    continue;
+                };
+                if is_token_amount(place) {
+                    checker.writes_amounts = true;
+                }
+                match rvalue {
+                    mir::Rvalue::Use(mir::Operand::Copy(source) | mir::Operand::Move(source))
+                        if place.projection.is_empty() =>
+                    {
+                        if is_token_amount(source) || checker.amount_locals.contains(&source.local)
+                        {
+                            checker.amount_locals.insert(place.local);
+                        } else if source.projection.is_empty() {
+                            let value = checker.value_of_local(source.local);
+                            checker.value_aliases.insert(place.local, value);
+                        }
+                    }
+                    mir::Rvalue::BinaryOp(bin_op, box (left, right)) => {
+                        let delta = match bin_op {
+                            mir::BinOp::Add
+                            | mir::BinOp::AddUnchecked
+                            | mir::BinOp::AddWithOverflow => AmountDelta::Credit,
+                            mir::BinOp::Sub
+                            | mir::BinOp::SubUnchecked
+                            | mir::BinOp::SubWithOverflow => AmountDelta::Debit,
+                            _ => continue,
+                        };
+                        if checker.is_amount(left, &is_token_amount) {
+                            let value = checker.value_of_operand(right);
+                            checker
+                                .adjustments
+                                .push((delta, value, statement.source_info.span));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let terminator = data.terminator();
+            let mir::TerminatorKind::Call { func, args, .. } = &terminator.kind else {
+                continue;
+            };
+            let Some((callee_def_id, _)) = func.const_fn_def() else {
+                continue;
+            };
+            let callee_name = tcx.item_name(callee_def_id);
+            let delta = match callee_name.as_str() {
+                "checked_add" | "saturating_add" | "wrapping_add" | "overflowing_add" => {
+                    AmountDelta::Credit
+                }
+                "checked_sub" | "saturating_sub" | "wrapping_sub" | "overflowing_sub" => {
+                    AmountDelta::Debit
+                }
+                "mint_to" | "mint_to_checked" | "burn" | "burn_checked" => {
+                    let crate_name = tcx.crate_name(callee_def_id.krate);
+                    if crate_name.as_str().starts_with("spl_token") {
+                        checker.mints_or_burns = true;
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            if let [left, right] = &args[..] {
+                if checker.is_amount(&left.node, &is_token_amount) {
+                    let value = checker.value_of_operand(&right.node);
+                    checker
+                        .adjustments
+                        .push((delta, value, terminator.source_info.span));
+                }
+            }
+        }
+        checker
+    }
+
+    fn is_amount<'tcx>(
+        &self,
+        operand: &mir::Operand<'tcx>,
+        is_token_amount: &impl Fn(&mir::Place<'tcx>) -> bool,
+    ) -> bool {
+        match operand {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => {
+                is_token_amount(place)
+                    || (place.projection.is_empty() && self.amount_locals.contains(&place.local))
+            }
+            mir::Operand::Constant(..) => false,
+        }
+    }
+
+    fn value_of_local(&self, local: mir::Local) -> mir::Local {
+        self.value_aliases.get(&local).copied().unwrap_or(local)
+    }
+
+    fn value_of_operand(&self, operand: &mir::Operand<'_>) -> AdjustedValue {
+        match operand {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => {
+                if place.projection.is_empty() {
+                    AdjustedValue::Local(self.value_of_local(place.local))
+                } else {
+                    AdjustedValue::Unknown
+                }
+            }
+            mir::Operand::Constant(constant) => constant
+                .const_
+                .try_to_scalar_int()
+                .map_or(AdjustedValue::Unknown, |scalar_int| {
+                    AdjustedValue::Constant(scalar_int.to_bits_unchecked())
+                }),
+        }
+    }
+
+    /// Returns the span of an adjustment of a token amount that no adjustment in the other
+    /// direction by the same value balances, if the entrypoint neither mints nor burns tokens.
+    pub fn unbalanced_adjustment(&self) -> Option<(AmountDelta, Span)> {
+        if !self.in_entrypoint || !self.writes_amounts || self.mints_or_burns {
+            return None;
+        }
+        let mut balanced = vec![false; self.adjustments.len()];
+        for (i, (delta, value, _)) in self.adjustments.iter().enumerate() {
+            if balanced[i] || *value == AdjustedValue::Unknown {
+                continue;
+            }
+            if let Some(j) = (i + 1..self.adjustments.len()).find(|j| {
+                !balanced[*j]
+                    && self.adjustments[*j].0 != *delta
+                    && self.adjustments[*j].1 == *value
+            }) {
+                balanced[i] = true;
+                balanced[j] = true;
+            }
+        }
+        self.adjustments
+            .iter()
+            .zip(balanced)
+            .find(|((_, value, _), balanced)| !balanced && *value != AdjustedValue::Unknown)
+            .map(|((delta, _, span), _)| (*delta, *span))
+    }
+
+    pub fn check(&self) -> bool {
+        self.unbalanced_adjustment().is_some()
+    }
+
+    pub fn describe(&self) -> String {
+        match self.unbalanced_adjustment() {
+            Some((AmountDelta::Credit, _)) => "possible unbalanced token amounts, which are credited to a token account without being debited from another".to_string(),
+            _ => "possible unbalanced token amounts, which are debited from a token account without being credited to another".to_string(),
+        }
+    }
+}
+
+// Hold the calls of fallible functions of the crate whose result may be an error, so that results
+// that are dropped without being looked at before the state is updated can be found
+#[derive(Default)]
+pub struct ResultUnusedChecker {
+    // The local holding the result of each such call and the span of the call, by the block
+    // that ends with the call
+    pub fallible_calls: BTreeMap<mir::BasicBlock, (mir::Local, Span)>,
+    // The span of the first call whose result is dropped before the state is updated
+    pub dropped_result_span: Option<Span>,
+}
+
+impl ResultUnusedChecker {
+    /// Returns the Err variant of the type of the destination, if it is a local holding a Result.
+    pub fn err_variant<'tcx>(
+        mir: &mir::Body<'tcx>,
+        destination: &mir::Place<'tcx>,
+    ) -> Option<rustc_target::abi::VariantIdx> {
+        if !destination.projection.is_empty() {
+            return None;
+        }
+        match mir.local_decls[destination.local].ty.kind() {
+            TyKind::Adt(def, _) if def.is_enum() => def
+                .variants()
+                .iter_enumerated()
+                .find(|(_, variant)| variant.name.as_str() == "Err")
+                .map(|(index, _)| index),
+            _ => None,
+        }
+    }
+
+    /// Finds the first fallible call whose result is never read, although a block that can
+    /// follow the call updates state that is not owned by the function.
+    pub fn find_dropped_result(&mut self, mir: &mir::Body<'_>) {
+        for (block, (local, span)) in &self.fallible_calls {
+            let mut reads = LocalReads {
+                local: *local,
+                is_read: false,
+            };
+            reads.visit_body(mir);
+            if !reads.is_read && updates_state_after(mir, *block) {
+                self.dropped_result_span = Some(*span);
+                return;
+            }
+        }
+    }
+}
+
+// Finds out if a local is read, rather than only assigned, dropped or marked as live or dead
+struct LocalReads {
+    local: mir::Local,
+    is_read: bool,
+}
+
+impl Visitor<'_> for LocalReads {
+    fn visit_local(&mut self, local: mir::Local, context: PlaceContext, _location: mir::Location) {
+        if local == self.local
+            && context.is_use()
+            && !matches!(
+                context,
+                PlaceContext::MutatingUse(
+                    MutatingUseContext::Call
+                        | MutatingUseContext::Store
+                        | MutatingUseContext::Drop
+                        | MutatingUseContext::Retag
+                )
+            )
+        {
+            self.is_read = true;
+        }
+    }
+}
+
+// Hold the calls of functions that build an instruction for another program, such as
+// system_instruction::transfer, and the locals that are passed to invoke, so that instructions that
+// are built and then dropped without being invoked can be found. Building an instruction does not
+// move any lamports or tokens by itself, while the rest of the function acts as if it did.
+#[derive(Default)]
+pub struct UninvokedInstructionChecker {
+    // The local holding the instruction, or the Result of it, that each call builds and the span
+    // of the call, by the block that ends with the call
+    pub builder_calls: BTreeMap<mir::BasicBlock, (mir::Local, Span)>,
+    // The locals that are passed to invoke or invoke_signed, or to a function of the crate, which
+    // may invoke them
+    pub invoking_locals: HashSet<mir::Local>,
+    // The span of the first call whose instruction is dropped without being invoked
+    pub uninvoked_span: Option<Span>,
+}
+
+impl UninvokedInstructionChecker {
+    /// Finds the first call that builds an instruction that neither flows into a call that may
+    /// invoke it, nor is returned or stored through a reference.
+    pub fn find_uninvoked_instruction(&mut self, mir: &mir::Body<'_>) {
+        for (local, span) in self.builder_calls.values() {
+            let Some(derived) = derived_locals(mir, *local) else {
+                continue;
+            };
+            if !derived.contains(&mir::RETURN_PLACE) && derived.is_disjoint(&self.invoking_locals) {
+                self.uninvoked_span = Some(*span);
+                return;
+            }
+        }
+    }
+}
+
+// Returns the locals that hold values computed from the value of the given local, through
+// assignments and calls, or None if such a value is stored through a reference
+fn derived_locals(mir: &mir::Body<'_>, local: mir::Local) -> Option<HashSet<mir::Local>> {
+    let mut derived = HashSet::from([local]);
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (block, data) in mir.basic_blocks.iter_enumerated() {
+            for (index, statement) in data.statements.iter().enumerate() {
+                let mir::StatementKind::Assign(box (place, rvalue)) = &statement.kind else {
+                    continue;
+                };
+                let mut reads = ReadLocals::default();
+                let location = mir::Location {
+                    block,
+                    statement_index: index,
+                };
+                reads.visit_rvalue(rvalue, location);
+                if reads.locals.is_disjoint(&derived) {
+                    continue;
+                }
+                if place.is_indirect() {
+                    return None;
+                }
+                changed |= derived.insert(place.local);
+            }
+            if let mir::TerminatorKind::Call {
+                args, destination, ..
+            } = &data.terminator().kind
+            {
+                let reads_derived = args.iter().any(|arg| {
+                    arg.node
+                        .place()
+                        .is_some_and(|place| derived.contains(&place.local))
+                });
+                if reads_derived {
+                    if destination.is_indirect() {
+                        return None;
+                    }
+                    changed |= derived.insert(destination.local);
+                }
+            }
+        }
+    }
+    Some(derived)
+}
+
+// Collects the locals that are read by an rvalue
+#[derive(Default)]
+struct ReadLocals {
+    locals: HashSet<mir::Local>,
+}
+
+impl Visitor<'_> for ReadLocals {
+    fn visit_local(&mut self, local: mir::Local, context: PlaceContext, _location: mir::Location) {
+        if context.is_use() {
+            self.locals.insert(local);
+        }
+    }
+}
+
+// Check if a block that can follow the given block, without unwinding, writes through a reference
+fn updates_state_after(mir: &mir::Body<'_>, block: mir::BasicBlock) -> bool {
+    let mut visited = HashSet::new();
+    let mut pending: Vec<mir::BasicBlock> =
+        mir.basic_blocks[block].terminator().successors().collect();
+    while let Some(block) = pending.pop() {
+        let data = &mir.basic_blocks[block];
+        if data.is_cleanup || !visited.insert(block) {
+            continue;
+        }
+        let writes_through_reference = data.statements.iter().any(|statement| {
+            matches!(&statement.kind,
+                mir::StatementKind::Assign(box (place, _)) if place.is_indirect())
+        });
+        if writes_through_reference {
+            return true;
+        }
+        pending.extend(data.terminator().successors());
+    }
+    false
+}
+
+// The end of the byte range of a write into the data of an account
+#[derive(Clone, Copy, Debug)]
+pub enum DataWriteEnd<'tcx> {
+    // The given field of a range, plus one if the range is inclusive
+    RangeField(mir::Place<'tcx>, usize, bool),
+    // The byte after the one at the index held by the local
+    Index(mir::Local),
+    // The length of the slice that is copied into the data
+    SourceLength(mir::Place<'tcx>),
+}
+
+// Hold the writes into the data of accounts whose byte ranges may exceed the length of the data
+#[derive(Default)]
+pub struct AccountDataLengthChecker {
+    // The locals that hold a reference to the data of an account, or a borrow of it, by the
+    // locals holding the accounts
+    pub data_locals: HashMap<mir::Local, mir::Local>,
+    // The span of the first write that may end past the data, with true if the length of the
+    // data is not checked at all before the write
+    pub overflowing_write: Option<(Span, bool)>,
+}
+
+impl AccountDataLengthChecker {
+    /// Describes the first write that may end past the data of its account.
+    pub fn describe(&self) -> String {
+        match self.overflowing_write {
+            Some((_, true)) => "possible write past the end of the data of an account whose length is not checked, for the smart contract; compare data_len() with the size of the layout before writing".to_string(),
+            Some((_, false)) => "possible write past the end of the data of an account, for the smart contract".to_string(),
+            None => String::new(),
+        }
+    }
+
+    /// Follows the references to the data of accounts and returns the account and the end of
+    /// the byte range of an assignment to an element of the data.
+    pub fn visit_assign<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+    ) -> Option<(mir::Local, DataWriteEnd<'tcx>)> {
+        if !place.projection.is_empty() {
+            let account = *self.data_locals.get(&place.local)?;
+            return match place.projection.last() {
+                Some(mir::ProjectionElem::Index(index)) => {
+                    Some((account, DataWriteEnd::Index(*index)))
+                }
+                _ => None,
+            };
+        }
+        let account = match rvalue {
+            mir::Rvalue::Ref(_, _, source) => {
+                match solana_compat::projected_account_field(tcx, mir, source) {
+                    Some((account, AccountField::Data)) => Some(account),
+                    _ => self.account_of_data(source),
+                }
+            }
+            mir::Rvalue::Use(mir::Operand::Copy(source) | mir::Operand::Move(source))
+            | mir::Rvalue::CopyForDeref(source) => self.account_of_data(source),
+            _ => None,
+        };
+        if let Some(account) = account {
+            self.data_locals.insert(place.local, account);
+        } else {
+            self.data_locals.remove(&place.local);
+        }
+        None
+    }
+
+    /// Follows the data of accounts through the calls that borrow or dereference it, and returns
+    /// the account and the end of the byte range of a call that writes into the data, either by
+    /// indexing it mutably with a range or by copying a slice into it. The receiver of a call
+    /// that borrows the data mutably is either the account or a reference to its data.
+    pub fn visit_call<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        callee_name: &str,
+        borrows_data: bool,
+        args: &[Spanned<mir::Operand<'tcx>>],
+        destination: &mir::Place<'tcx>,
+    ) -> Option<(mir::Local, DataWriteEnd<'tcx>)> {
+        let receiver = args.first().and_then(|arg| arg.node.place());
+        let receiver_data = receiver.and_then(|place| self.account_of_data(&place));
+        let tracked_account = if borrows_data {
+            receiver_data.or(receiver
+                .filter(|place| is_account_reference(tcx, place.ty(mir, tcx).ty))
+                .map(|place| place.local))
+        } else if matches!(callee_name, "deref" | "deref_mut" | "branch" | "unwrap" | "expect") {
+            receiver_data
+        } else {
+            None
+        };
+        if destination.projection.is_empty() {
+            if let Some(account) = tracked_account {
+                self.data_locals.insert(destination.local, account);
+            } else {
+                self.data_locals.remove(&destination.local);
+            }
+        }
+        let account = receiver_data?;
+        let argument = args.get(1)?.node.place()?;
+        let end = match callee_name {
+            "index_mut" => {
+                let TyKind::Adt(def, _) = argument.ty(mir, tcx).ty.kind() else {
+                    return None;
+                };
+                match tcx.item_name(def.did()).as_str() {
+                    "Range" => DataWriteEnd::RangeField(argument, 1, false),
+                    "RangeTo" => DataWriteEnd::RangeField(argument, 0, false),
+                    "RangeInclusive" => DataWriteEnd::RangeField(argument, 1, true),
+                    "RangeToInclusive" => DataWriteEnd::RangeField(argument, 0, true),
+                    _ => return None,
+                }
+            }
+            "copy_from_slice" => DataWriteEnd::SourceLength(argument),
+            _ => return None,
+        };
+        Some((account, end))
+    }
+
+    /// Records a write into the data of an account, which may end past the data unless it is
+    /// known to fit.
+    pub fn record_write(&mut self, span: Span, fits: Option<bool>, length_is_checked: bool) {
+        if fits != Some(true) && self.overflowing_write.is_none() {
+            self.overflowing_write = Some((span, !length_is_checked));
+        }
+    }
+
+    // Returns the account whose data the place refers to, if the place is reached from a local
+    // holding the data of the account without indexing
+    fn account_of_data(&self, place: &mir::Place<'_>) -> Option<mir::Local> {
+        let account = self.data_locals.get(&place.local)?;
+        place
+            .projection
+            .iter()
+            .all(|elem| {
+                matches!(
+                    elem,
+                    mir::ProjectionElem::Deref
+                        | mir::ProjectionElem::Field(..)
+                        | mir::ProjectionElem::Downcast(..)
+                )
+            })
+            .then_some(*account)
+    }
+}
+
+// Hold the first index into a local array or slice of an entrypoint that the caller controls and
+// that is not known to be within the length of the collection, so that the caller can abort the
+// program by passing an index out of bounds
+#[derive(Default)]
+pub struct UncheckedIndexChecker {
+    // The span of the indexing and the name of the collection
+    pub unchecked_index: Option<(Span, String)>,
+    // Notes on where the index comes from and on the length of the collection
+    pub notes: Vec<(Span, String)>,
+    // The provenance class of the index
+    pub provenance: Option<&'static str>,
+}
+
+impl UncheckedIndexChecker {
+    /// Returns the local holding the collection that the block following a bounds check indexes
+    /// with the checked index.
+    pub fn indexed_collection(
+        mir: &mir::Body<'_>,
+        block: mir::BasicBlock,
+        index: mir::Local,
+    ) -> Option<mir::Local> {
+        let mut visitor = IndexedCollection {
+            index,
+            collection: None,
+        };
+        let data = &mir.basic_blocks[block];
+        let mut location = block.start_location();
+        for statement in &data.statements {
+            visitor.visit_statement(statement, location);
+            location.statement_index += 1;
+        }
+        visitor.visit_terminator(data.terminator(), location);
+        visitor.collection
+    }
+
+    /// Records an index into the collection held by the given local that the caller controls,
+    /// unless an earlier one has been recorded.
+    pub fn record_index(
+        &mut self,
+        mir: &mir::Body<'_>,
+        span: Span,
+        collection: mir::Local,
+        source: (mir::Local, String, &'static str),
+        length: Option<u128>,
+    ) {
+        if self.unchecked_index.is_some() {
+            return;
+        }
+        let name = debug_name(mir, collection)
+            .map(|name| format!("`{name}`"))
+            .unwrap_or_else(|| "a collection".to_string());
+        let (index, description, class) = source;
+        self.provenance = Some(class);
+        self.notes
+            .push((mir.local_decls[index].source_info.span, description));
+        if let Some(length) = length {
+            self.notes.push((
+                mir.local_decls[collection].source_info.span,
+                format!("{name} has {length} elements"),
+            ));
+        }
+        self.unchecked_index = Some((span, name));
+    }
+}
+
+// Finds the first place that is indexed with the given local
+struct IndexedCollection {
+    index: mir::Local,
+    collection: Option<mir::Local>,
+}
+
+impl<'tcx> Visitor<'tcx> for IndexedCollection {
+    fn visit_place(
+        &mut self,
+        place: &mir::Place<'tcx>,
+        _context: PlaceContext,
+        _location: mir::Location,
+    ) {
+        if self.collection.is_none()
+            && place
+                .projection
+                .iter()
+                .any(|elem| elem == mir::ProjectionElem::Index(self.index))
+        {
+            self.collection = Some(place.local);
+        }
+    }
+}
+
+// Hold the first index into, or slice of, the instruction data of an entrypoint that is not known
+// to be within the length of the data, such as `instruction_data[1..9]` without a check of
+// instruction_data.len() before it, so that the caller can abort the program with instruction data
+// that is too short
+#[derive(Default)]
+pub struct InstructionDataBoundsChecker {
+    // The span of the indexing or slicing, and the length that the instruction data must have for
+    // it to be within bounds, if known
+    pub out_of_bounds: Option<(Span, Option<u128>)>,
+}
+
+impl InstructionDataBoundsChecker {
+    /// Describes the first index or slice that may be out of bounds.
+    pub fn describe(&self) -> String {
+        let Some((_, required_length)) = self.out_of_bounds else {
+            return String::new();
+        };
+        let length = match required_length {
+            Some(1) => "1 byte".to_string(),
+            Some(length) => format!("{length} bytes"),
+            None => "the index".to_string(),
+        };
+        format!("possible index out of bounds: instruction data may be shorter than {length}, which aborts execution, for the smart contract")
+    }
+
+    /// Records an index or slice that may be out of bounds, unless an earlier one has been
+    /// recorded.
+    pub fn record(&mut self, span: Span, required_length: Option<u128>) {
+        if self.out_of_bounds.is_none() {
+            self.out_of_bounds = Some((span, required_length));
+        }
+    }
+}
+
+// Hold the integer casts of amounts that are untrusted input, such as `amount as u32` where amount
+// is a u64 read from the instruction data, whose operands are not known to be within the range of
+// the result type, so that a large amount is silently truncated, or changes its sign, before it is
+// used. A conversion with try_into or try_from whose result is unwrapped counts as a cast that may
+// panic, while one whose error is handled does not count.
+#[derive(Default)]
+pub struct TruncationChecker {
+    // The locals that hold the results of the conversions of amounts that are untrusted input,
+    // with the spans of the conversions, the types of the amounts and of the results, and the
+    // conditions under which the amounts are within the result types
+    pub conversions: HashMap<mir::Local, (Span, ExpressionType, ExpressionType, Rc<AbstractValue>)>,
+    // The spans of the casts that may truncate, with the types of the amounts and of the results,
+    // true if the amounts are out of range whenever the casts are reached, and the spans of the
+    // calls that unwrap the results of conversions
+    pub truncations: Vec<(Span, ExpressionType, ExpressionType, bool, Option<Span>)>,
+}
+
+impl TruncationChecker {
+    /// Describes a cast that may truncate an amount.
+    pub fn describe(source: ExpressionType, target: ExpressionType, guaranteed: bool) -> String {
+        let truncation = if guaranteed {
+            "guaranteed truncation"
+        } else {
+            "possible truncation"
+        };
+        // The names of the variants of the integer types are the names of the types, capitalized
+        let name = |ty: ExpressionType| format!("{ty:?}").to_lowercase();
+        format!(
+            "{truncation} of an amount that is untrusted input for the smart contract, it is cast from {} ({} bits) to {} ({} bits)",
+            name(source),
+            source.bit_length(),
+            name(target),
+            target.bit_length()
+        )
+    }
+
+    /// Records a cast that may truncate, unless one has been recorded at the same span.
+    pub fn record(
+        &mut self,
+        span: Span,
+        source: ExpressionType,
+        target: ExpressionType,
+        guaranteed: bool,
+        unwrap_span: Option<Span>,
+    ) {
+        if self.truncations.iter().all(|(cast_span, ..)| *cast_span != span) {
+            self.truncations
+                .push((span, source, target, guaranteed, unwrap_span));
+        }
+    }
+}
+
+// Hold the deserialization of a prefix of the instruction data of an entrypoint with Borsh or
+// bincode, such as `Instruction::try_from_slice(&[instruction_data[0]])`, and the reads of the
+// instruction data at constant offsets, so that the offset where the reads after the prefix start
+// can be compared with the serialized size of the value in the prefix
+#[derive(Default)]
+pub struct DeserializationOffsetChecker {
+    // The span of the first deserialization, the type of the value, the length of the prefix, if
+    // known, and the serialized size of the value, if every value of its type has the same size
+    pub deserialization: Option<(Span, String, Option<u128>, Option<u128>)>,
+    // The span of each read of the instruction data at a constant offset, the offset, and the
+    // offset where the read ends, unless it reads to the end of the data
+    pub reads: Vec<(Span, u128, Option<u128>)>,
+}
+
+impl DeserializationOffsetChecker {
+    /// Returns the span of the first read of the instruction data after the deserialized prefix,
+    /// along with a description, if its offset is not the serialized size of the value in the
+    /// prefix, or if that size is not fixed.
+    pub fn misaligned_read(&self) -> Option<(Span, String)> {
+        let (_, type_name, prefix_length, size) = self.deserialization.as_ref()?;
+        let prefix_end = prefix_length.or(*size).unwrap_or(0);
+        let (span, offset, _) = self
+            .reads
+            .iter()
+            .filter(|(_, offset, end)| *offset > 0 && end.is_none_or(|end| end > prefix_end))
+            .min_by_key(|(_, offset, _)| *offset)?;
+        let description = match size {
+            None => format!("possible misaligned read of instruction data at offset {offset}: {type_name} has no fixed serialized size, so the offset may not follow the bytes that are deserialized before it, for the smart contract"),
+            Some(size) if size != offset => {
+                let bytes = if *size == 1 { "byte" } else { "bytes" };
+                format!("possible misaligned read of instruction data at offset {offset}: the {type_name} that is deserialized before it occupies {size} {bytes}, for the smart contract")
+            }
+            Some(_) => return None,
+        };
+        Some((*span, description))
+    }
+
+    /// Records the deserialization of a prefix of the instruction data, unless an earlier one has
+    /// been recorded.
+    pub fn record_deserialization(
+        &mut self,
+        span: Span,
+        type_name: String,
+        prefix_length: Option<u128>,
+        size: Option<u128>,
+    ) {
+        if self.deserialization.is_none() {
+            self.deserialization = Some((span, type_name, prefix_length, size));
+        }
+    }
+}
+
+// Hold the values that a function deserializes with BorshDeserialize::try_from_slice, with what
+// is known about the length of the slice, and, in an entrypoint, the reads of the instruction data
+// that are not known to be in bounds. A read past the deserialized prefix in an arm of a match on
+// the variant of the deserialized value relies on the length that the variant implies, which the
+// instruction data need not have, unless the arm checks it.
+#[derive(Default)]
+pub struct UnvalidatedDeserializationChecker {
+    // The span of each deserialization, the type of the value, the length of the slice, if it is
+    // a constant, and the size of the shortest serialization of the value, if the slice is not
+    // known to be that long
+    pub deserializations: Vec<(Span, String, Option<u128>, Option<u128>)>,
+    // The deserializations whose results, or the values in them, the locals hold
+    pub deserialized_locals: HashMap<mir::Local, usize>,
+    // The deserializations whose variants the locals hold the discriminants of
+    pub discriminant_locals: HashMap<mir::Local, usize>,
+    // The deserializations whose variants are matched by the blocks that switches lead to
+    pub variant_blocks: HashMap<mir::BasicBlock, usize>,
+    // The span and block of each read of the instruction data that is not known to be in bounds,
+    // with the offset where it starts
+    pub unchecked_reads: Vec<(Span, mir::BasicBlock, u128)>,
+}
+
+impl UnvalidatedDeserializationChecker {
+    /// Returns the span of the first deserialization from a slice that is not known to be long
+    /// enough, along with a description.
+    pub fn short_slice(&self) -> Option<(Span, String)> {
+        let (span, type_name, size) = self
+            .deserializations
+            .iter()
+            .find_map(|(span, type_name, _, size)| Some((span, type_name, (*size)?)))?;
+        let bytes = if size == 1 { "byte" } else { "bytes" };
+        let description = format!("possible deserialization of {type_name} from a slice that may be shorter than the {size} {bytes} of its shortest serialization, which fails the instruction, for the smart contract");
+        Some((*span, description))
+    }
+
+    /// Returns the span of the first read of the instruction data past a deserialized prefix in
+    /// an arm of a match on the variant of the deserialized value that does not check the length
+    /// of the data, along with a description and the span of the deserialization.
+    pub fn unguarded_variant_read(&self, mir: &mir::Body<'_>) -> Option<(Span, String, Span)> {
+        let dominators = mir.basic_blocks.dominators();
+        self.unchecked_reads.iter().find_map(|(span, block, offset)| {
+            let index = self
+                .variant_blocks
+                .iter()
+                .filter(|(variant_block, _)| dominators.dominates(**variant_block, *block))
+                .map(|(_, index)| *index)
+                .min()?;
+            let (deserialization_span, type_name, prefix_length, _) =
+                self.deserializations.get(index)?;
+            // The index of the variant occupies at least the first byte of the prefix
+            if *offset < prefix_length.unwrap_or(1) {
+                return None;
+            }
+            let description = format!("possible read of instruction data at offset {offset} that the matched variant of the deserialized {type_name} relies on, while the length of the data is not checked in the arm, for the smart contract");
+            Some((*span, description, *deserialization_span))
+        })
+    }
+
+    /// Follows the deserialized values into other locals, and their variants into discriminants.
+    pub fn visit_assign<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+    ) {
+        if !place.projection.is_empty() {
+            return;
+        }
+        self.deserialized_locals.remove(&place.local);
+        self.discriminant_locals.remove(&place.local);
+        match rvalue {
+            mir::Rvalue::Use(mir::Operand::Copy(source) | mir::Operand::Move(source))
+            | mir::Rvalue::CopyForDeref(source) => {
+                if let Some(index) = self.deserialized_locals.get(&source.local).copied() {
+                    self.deserialized_locals.insert(place.local, index);
+                }
+            }
+            // The discriminants of the Result and of the ControlFlow of ? do not select a variant
+            mir::Rvalue::Discriminant(source) => {
+                let is_local_enum = matches!(source.ty(mir, tcx).ty.kind(),
+                    TyKind::Adt(def, _) if def.is_enum() && def.did().is_local());
+                if let Some(index) = self.deserialized_locals.get(&source.local).copied() {
+                    if is_local_enum {
+                        self.discriminant_locals.insert(place.local, index);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Follows the result of a deserialization through the branch of `?`.
+    pub fn visit_call(
+        &mut self,
+        item_name: &str,
+        args: &[Spanned<mir::Operand<'_>>],
+        destination: &mir::Place<'_>,
+    ) {
+        if !destination.projection.is_empty() {
+            return;
+        }
+        self.deserialized_locals.remove(&destination.local);
+        if item_name != "branch" {
+            return;
+        }
+        let index = args
+            .first()
+            .and_then(|arg| arg.node.place())
+            .and_then(|place| self.deserialized_locals.get(&place.local).copied());
+        if let Some(index) = index {
+            self.deserialized_locals.insert(destination.local, index);
+        }
+    }
+
+    /// Records the blocks that a switch on the variant of a deserialized value leads to.
+    pub fn visit_switch_int(&mut self, discr: &mir::Operand<'_>, targets: &mir::SwitchTargets) {
+        let index = discr
+            .place()
+            .and_then(|place| self.discriminant_locals.get(&place.local).copied());
+        if let Some(index) = index {
+            for target in targets.all_targets() {
+                self.variant_blocks.insert(*target, index);
+            }
+        }
+    }
+
+    /// Records a deserialization whose result the given local holds, and the size of the
+    /// shortest serialization of the value, if the slice is not known to be that long.
+    pub fn record_deserialization(
+        &mut self,
+        span: Span,
+        type_name: String,
+        destination: mir::Local,
+        length: Option<u128>,
+        short_of: Option<u128>,
+    ) {
+        let index = match self
+            .deserializations
+            .iter()
+            .position(|(deserialization_span, ..)| *deserialization_span == span)
+        {
+            Some(index) => index,
+            None => {
+                self.deserializations
+                    .push((span, type_name, length, short_of));
+                self.deserializations.len() - 1
+            }
+        };
+        self.deserialized_locals.insert(destination, index);
+    }
+}
+
+// A deserialization with Borsh or bincode of a struct of the analyzed crate from the data of an
+// account, and whether a guard of the function that returns an error checks a discriminant in the
+// data, or both the owner and the key of the account, which tell the type of the data apart.
+#[derive(Clone, Debug)]
+pub struct AccountDeserialization<'tcx> {
+    pub function: DefId,
+    pub span: Span,
+    pub ty: Ty<'tcx>,
+    pub guarded: bool,
+}
+
+// The parts of an account that the value of a local is derived from, by the first local to hold
+// the account
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum AccountPart {
+    Data(mir::Local),
+    Owner(mir::Local),
+    Key(mir::Local),
+}
+
+/// Returns the deserializations of the structs of the crate from the data of accounts, in all the
+/// functions of the crate, so that the layouts of the structs can be compared across functions.
+pub fn account_deserializations(tcx: TyCtxt<'_>) -> Vec<AccountDeserialization<'_>> {
+    tcx.hir()
+        .body_owners()
+        .map(|owner| owner.to_def_id())
+        .filter(|def_id| {
+            matches!(tcx.def_kind(*def_id), DefKind::Fn | DefKind::AssocFn)
+                && !tcx.is_const_fn(*def_id)
+                && !tcx.generics_of(*def_id).requires_monomorphization(tcx)
+        })
+        .flat_map(|def_id| account_deserializations_in(tcx, def_id))
+        .collect()
+}
+
+// Returns the deserializations of structs of the crate from the data of accounts in the function
+fn account_deserializations_in(
+    tcx: TyCtxt<'_>,
+    function: DefId,
+) -> Vec<AccountDeserialization<'_>> {
+    let mir = tcx.optimized_mir(function);
+    let mut parts: HashMap<mir::Local, HashSet<AccountPart>> = HashMap::new();
+    // The first local to hold each account, by the locals that hold copies or reborrows of it
+    let mut roots: HashMap<mir::Local, mir::Local> = HashMap::new();
+    let parts_of = |parts: &HashMap<mir::Local, HashSet<AccountPart>>,
+                    roots: &HashMap<mir::Local, mir::Local>,
+                    place: &mir::Place<'_>| {
+        let root_of = |local: mir::Local| roots.get(&local).copied().unwrap_or(local);
+        match solana_compat::projected_account_field(tcx, mir, place) {
+            Some((account, AccountField::Data)) => {
+                HashSet::from([AccountPart::Data(root_of(account))])
+            }
+            Some((account, AccountField::Owner)) => {
+                HashSet::from([AccountPart::Owner(root_of(account))])
+            }
+            Some((account, AccountField::Key)) => {
+                HashSet::from([AccountPart::Key(root_of(account))])
+            }
+            Some(_) => HashSet::new(),
+            None => parts.get(&place.local).cloned().unwrap_or_default(),
+        }
+    };
+    for _ in 0..MAX_PROVENANCE_PASSES {
+        let known_parts = parts.clone();
+        for data in mir.basic_blocks.iter() {
+            if data.is_cleanup {
+                continue;
+            }
+            for statement in &data.statements {
+                let mir::StatementKind::Assign(box (place, rvalue)) = &statement.kind else {
+                    continue;
+                };
+                let read_places: Vec<&mir::Place<'_>> = match rvalue {
+                    mir::Rvalue::Use(operand)
+                    | mir::Rvalue::Cast(_, operand, _)
+                    | mir::Rvalue::Repeat(operand, _) => operand.place().into_iter().collect(),
+                    // The length of the data says nothing about the type that it holds
+                    mir::Rvalue::UnaryOp(mir::UnOp::PtrMetadata, _) | mir::Rvalue::Len(_) => {
+                        Vec::new()
+                    }
+                    mir::Rvalue::UnaryOp(_, operand) => operand.place().into_iter().collect(),
+                    mir::Rvalue::Ref(_, _, source)
+                    | mir::Rvalue::RawPtr(_, source)
+                    | mir::Rvalue::CopyForDeref(source) => vec![source],
+                    mir::Rvalue::BinaryOp(_, box (left, right)) => {
+                        left.place().into_iter().chain(right.place()).collect()
+                    }
+                    mir::Rvalue::Aggregate(_, operands) => operands
+                        .iter()
+                        .filter_map(|operand| operand.place())
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                if !place.projection.is_empty() {
+                    continue;
+                }
+                if let mir::Rvalue::Use(mir::Operand::Copy(source) | mir::Operand::Move(source))
+                | mir::Rvalue::Ref(_, _, source)
+                | mir::Rvalue::CopyForDeref(source) = rvalue
+                {
+                    if source
+                        .projection
+                        .iter()
+                        .all(|elem| matches!(elem, mir::ProjectionElem::Deref))
+                    {
+                        let root = roots.get(&source.local).copied().unwrap_or(source.local);
+                        roots.insert(place.local, root);
+                    }
+                }
+                let derived: HashSet<AccountPart> = read_places
+                    .into_iter()
+                    .flat_map(|source| parts_of(&parts, &roots, source))
+                    .collect();
+                if !derived.is_empty() {
+                    parts.entry(place.local).or_default().extend(derived);
+                }
+            }
+            let mir::TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } = &data.terminator().kind
+            else {
+                continue;
+            };
+            let Some((callee_def_id, _)) = func.const_fn_def() else {
+                continue;
+            };
+            // The deserialized value is not the data, and the length of the data says nothing
+            // about the type that it holds
+            if solana_compat::deserialization_format(tcx, callee_def_id).is_some()
+                || tcx
+                    .opt_item_name(callee_def_id)
+                    .is_some_and(|name| name.as_str() == "len")
+            {
+                continue;
+            }
+            let mut derived: HashSet<AccountPart> = args
+                .iter()
+                .filter_map(|arg| arg.node.place())
+                .flat_map(|source| parts_of(&parts, &roots, &source))
+                .collect();
+            if tcx.opt_item_name(callee_def_id).is_some_and(|name| {
+                matches!(name.as_str(), "try_borrow_data" | "try_borrow_mut_data")
+            }) {
+                if let Some(receiver) = args.first().and_then(|arg| arg.node.place()) {
+                    let root = roots
+                        .get(&receiver.local)
+                        .copied()
+                        .unwrap_or(receiver.local);
+                    derived.insert(AccountPart::Data(root));
+                }
+            }
+            if !derived.is_empty() {
+                parts.entry(destination.local).or_default().extend(derived);
+            }
+        }
+        if parts == known_parts {
+            break;
+        }
+    }
+    let checked: HashSet<AccountPart> = find_error_guards(mir)
+        .into_iter()
+        .filter_map(|guard| parts.get(&guard.condition))
+        .flatten()
+        .copied()
+        .collect();
+    let mut deserializations = Vec::new();
+    for data in mir.basic_blocks.iter() {
+        let mir::TerminatorKind::Call {
+            func,
+            args,
+            destination,
+            ..
+        } = &data.terminator().kind
+        else {
+            continue;
+        };
+        let Some((callee_def_id, _)) = func.const_fn_def() else {
+            continue;
+        };
+        if data.is_cleanup || solana_compat::deserialization_format(tcx, callee_def_id).is_none() {
+            continue;
+        }
+        // The value is returned in a Result
+        let TyKind::Adt(_, result_args) = destination.ty(mir, tcx).ty.kind() else {
+            continue;
+        };
+        let Some(ty) = result_args.types().next() else {
+            continue;
+        };
+        let TyKind::Adt(def, _) = ty.kind() else {
+            continue;
+        };
+        if !def.is_struct() || !def.did().is_local() {
+            continue;
+        }
+        let accounts: Vec<mir::Local> = args
+            .iter()
+            .filter_map(|arg| arg.node.place())
+            .flat_map(|source| parts_of(&parts, &roots, &source))
+            .filter_map(|part| match part {
+                AccountPart::Data(account) => Some(account),
+                _ => None,
+            })
+            .collect();
+        if accounts.is_empty() {
+            continue;
+        }
+        let guarded = has_anchor_discriminator(tcx, def.did())
+            || accounts.iter().any(|account| {
+                checked.contains(&AccountPart::Data(*account))
+                    || (checked.contains(&AccountPart::Owner(*account))
+                        && checked.contains(&AccountPart::Key(*account)))
+            });
+        deserializations.push(AccountDeserialization {
+            function,
+            span: data.terminator().source_info.span,
+            ty,
+            guarded,
+        });
+    }
+    deserializations
+}
+
+// Check if the struct implements the Discriminator trait of Anchor, whose deserialization of
+// account data checks the first 8 bytes of the data against the discriminator of the struct
+fn has_anchor_discriminator(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    tcx.all_local_trait_impls(())
+        .iter()
+        .any(|(trait_def_id, impls)| {
+            tcx.crate_name(trait_def_id.krate).as_str() == "anchor_lang"
+                && tcx.item_name(*trait_def_id).as_str() == "Discriminator"
+                && impls.iter().any(|impl_def_id| {
+                    matches!(tcx.type_of(*impl_def_id).instantiate_identity().kind(),
+                    TyKind::Adt(def, _) if def.did() == def_id)
+                })
+        })
+}
+
+// Hold the deserializations of the function from the data of accounts whose structs have the same
+// layout as the struct of a deserialization elsewhere in the crate, when neither is guarded. The
+// data of an account of one type then deserializes as the other, so that the caller can pass an
+// account of one type where the program expects the other.
+#[derive(Default)]
+pub struct TypeCosplayChecker {
+    // The span of each deserialization of the function, the name of its struct, and the span and
+    // the name of the struct of a deserialization of another struct with the same layout
+    pub cosplays: Vec<(Span, String, Span, String)>,
+}
+
+impl TypeCosplayChecker {
+    /// Returns the checker for the given function, with its deserializations that cosplay as
+    /// others among the given deserializations of the crate.
+    pub fn new<'tcx>(
+        tcx: TyCtxt<'tcx>,
+        function: DefId,
+        deserializations: &[AccountDeserialization<'tcx>],
+        type_visitor: &TypeVisitor<'tcx>,
+    ) -> TypeCosplayChecker {
+        let mut checker = TypeCosplayChecker::default();
+        let unguarded: Vec<&AccountDeserialization<'tcx>> = deserializations
+            .iter()
+            .filter(|deserialization| !deserialization.guarded)
+            .collect();
+        for deserialization in unguarded
+            .iter()
+            .filter(|deserialization| deserialization.function == function)
+        {
+            let Some(layout) = field_layout(type_visitor, deserialization.ty) else {
+                continue;
+            };
+            let other = unguarded.iter().find(|other| {
+                other.ty != deserialization.ty
+                    && field_layout(type_visitor, other.ty).as_ref() == Some(&layout)
+            });
+            if let Some(other) = other {
+                checker.cosplays.push((
+                    deserialization.span,
+                    struct_name(tcx, deserialization.ty),
+                    other.span,
+                    struct_name(tcx, other.ty),
+                ));
+            }
+        }
+        checker
+    }
+}
+
+// Returns the sizes of the fields of the given struct, in the order in which they are serialized,
+// if the struct has fields and their sizes are known
+fn field_layout<'tcx>(type_visitor: &TypeVisitor<'tcx>, ty: Ty<'tcx>) -> Option<Vec<u64>> {
+    let TyKind::Adt(def, args) = ty.kind() else {
+        return None;
+    };
+    let field_count = def.non_enum_variant().fields.len();
+    if field_count == 0 {
+        return None;
+    }
+    let sizes: Vec<u64> = (0..field_count)
+        .map(|ordinal| type_visitor.get_type_size(type_visitor.get_field_type(def, args, ordinal)))
+        .collect();
+    (!sizes.contains(&0)).then_some(sizes)
+}
+
+// Returns the name of the item of the given struct
+fn struct_name<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> String {
+    match ty.kind() {
+        TyKind::Adt(def, _) => tcx.item_name(def.did()).to_string(),
+        _ => format!("{ty:?}"),
+    }
+}
+
+// Hold the state that an entrypoint creates rather than deserializes from the data of an account,
+// such as `let mut balances: HashMap<Pubkey, u64> = HashMap::new()`, and the calls of functions
+// that transfer lamports that the state is passed to. The state is dropped at the end of the
+// instruction, so the transfers that it decides, such as a withdrawal that is limited by the
+// balance recorded by an earlier deposit, never see what earlier instructions recorded.
+#[derive(Default)]
+pub struct NonPersistentStateChecker {
+    // True if the function is a program entrypoint
+    pub in_entrypoint: bool,
+    // The span where the state is created and the name of its variable, if known, by the locals
+    // that hold the state or a reference to it
+    pub state_locals: HashMap<mir::Local, (Span, Option<String>)>,
+    // The spans where the state that is serialized into the data of an account is created
+    pub persisted: HashSet<Span>,
+    // The span where the state is created, the name of its variable, and the span of the first
+    // call of a function that transfers lamports that the state is passed to
+    pub transfers: Vec<(Span, Option<String>, Span)>,
+}
+
+impl NonPersistentStateChecker {
+    /// Returns the span where the state is created, a description, and the span of a call that
+    /// transfers lamports with it, if the state is not serialized into the data of an account.
+    pub fn unpersisted_state(&self) -> Option<(Span, String, Span)> {
+        let (created, name, transfer) = self
+            .transfers
+            .iter()
+            .find(|(created, ..)| !self.persisted.contains(created))?;
+        let state = name.as_deref().unwrap_or("the state");
+        let description = format!("possible loss of state: {state} is created by the instruction rather than read from the data of an account, so it is empty in every transaction that transfers lamports with it, for the smart contract");
+        Some((*created, description, *transfer))
+    }
+
+    /// Follows the state into copies of it and references to it, in an entrypoint, and records
+    /// the structs that are built from constants.
+    pub fn visit_assign<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+        span: Span,
+    ) {
+        if !self.in_entrypoint || !place.projection.is_empty() {
+            return;
+        }
+        let state = match rvalue {
+            mir::Rvalue::Use(mir::Operand::Copy(source) | mir::Operand::Move(source))
+            | mir::Rvalue::Ref(_, _, source)
+            | mir::Rvalue::CopyForDeref(source) => self.state_locals.get(&source.local).cloned(),
+            mir::Rvalue::Aggregate(kind, operands)
+                if matches!(**kind, mir::AggregateKind::Adt(..))
+                    && operands
+                        .iter()
+                        .all(|operand| matches!(operand, mir::Operand::Constant(_)))
+                    && is_state_type(tcx, mir.local_decls[place.local].ty) =>
+            {
+                Some((span, debug_name(mir, place.local)))
+            }
+            _ => None,
+        };
+        match state {
+            Some(state) => self.state_locals.insert(place.local, state),
+            None => self.state_locals.remove(&place.local),
+        };
+    }
+
+    /// Records the state that a constructor without arguments other than constants creates, such
+    /// as HashMap::new(), in an entrypoint, and the state that is serialized.
+    pub fn visit_call<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        item_name: &str,
+        args: &[Spanned<mir::Operand<'tcx>>],
+        destination: &mir::Place<'tcx>,
+        span: Span,
+    ) {
+        if !self.in_entrypoint {
+            return;
+        }
+        if matches!(item_name, "serialize" | "serialize_into" | "to_writer") {
+            for arg in args {
+                if let Some((created, _)) = arg
+                    .node
+                    .place()
+                    .and_then(|place| self.state_locals.get(&place.local))
+                {
+                    self.persisted.insert(*created);
+                }
+            }
+        }
+        if !destination.projection.is_empty() {
+            return;
+        }
+        if matches!(item_name, "new" | "default" | "with_capacity")
+            && args
+                .iter()
+                .all(|arg| matches!(arg.node, mir::Operand::Constant(_)))
+            && is_state_type(tcx, mir.local_decls[destination.local].ty)
+        {
+            let name = debug_name(mir, destination.local);
+            self.state_locals.insert(destination.local, (span, name));
+        } else {
+            self.state_locals.remove(&destination.local);
+        }
+    }
+
+    /// Records a call of a function that transfers lamports, if the state is passed to it.
+    pub fn record_transfer_call(&mut self, span: Span, args: &[Spanned<mir::Operand<'_>>]) {
+        for arg in args {
+            let Some((created, name)) = arg
+                .node
+                .place()
+                .and_then(|place| self.state_locals.get(&place.local))
+            else {
+                continue;
+            };
+            if !self.transfers.iter().any(|(span, ..)| span == created) {
+                self.transfers.push((*created, name.clone(), span));
+            }
+        }
+    }
+}
+
+// Check if a value of the type holds the state of a contract when an entrypoint creates it, that
+// is, if the type is a map, a set or a vector of the standard library, or a struct of the crate,
+// which holds neither references, such as those to accounts, nor the metas of accounts that are
+// gathered for an instruction
+fn is_state_type<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> bool {
+    let TyKind::Adt(def, _) = ty.kind() else {
+        return false;
+    };
+    let holds_accounts = ty.walk().any(|arg| match arg.unpack() {
+        GenericArgKind::Lifetime(_) => true,
+        GenericArgKind::Type(ty) => matches!(ty.kind(), TyKind::Adt(def, _)
+            if matches!(tcx.item_name(def.did()).as_str(), "AccountMeta" | "Instruction")),
+        GenericArgKind::Const(_) => false,
+    });
+    if holds_accounts {
+        return false;
+    }
+    if def.did().is_local() {
+        return def.is_struct();
+    }
+    matches!(tcx.crate_name(def.did().krate).as_str(), "std" | "alloc")
+        && matches!(
+            tcx.item_name(def.did()).as_str(),
+            "HashMap" | "BTreeMap" | "HashSet" | "BTreeSet" | "Vec" | "VecDeque"
+        )
+}
+
+// Hold the changes to the lamports of the UncheckedAccount fields of the Accounts struct of an
+// Anchor handler whose Accounts struct has no Signer field. Anchor checks the signature of every
+// Signer field before the handler runs, but nothing about an UncheckedAccount, so an instruction
+// that no account signs lets anyone move the lamports of whatever account they pass. A handler
+// that checks the is_signer flag of an account itself, with a guard such as
+// `require!(ctx.accounts.authority.is_signer, ...)`, is signed in the blocks after the guard.
+#[derive(Default)]
+pub struct MissingSignerChecker {
+    // True if the function is the handler of an instruction of an Anchor program
+    pub in_anchor_handler: bool,
+    // The blocks of the handler that are only reached once a guard has checked that an account
+    // signs the instruction
+    pub signer_checked_blocks: HashSet<mir::BasicBlock>,
+    // The span of the first change to the lamports of an unchecked account of an instruction that
+    // no account signs, and the name of the account
+    pub unsigned_change: Option<(Span, String)>,
+}
+
+impl MissingSignerChecker {
+    /// Returns the checker for the given handler, with the blocks that its guards of the
+    /// is_signer flags of accounts lead to.
+    pub fn new<'tcx>(tcx: TyCtxt<'tcx>, mir: &mir::Body<'tcx>) -> MissingSignerChecker {
+        // The locals that hold the is_signer flag of an account, or its negation if false
+        let mut signer_flags: HashMap<mir::Local, bool> = HashMap::new();
+        for _ in 0..MAX_PROVENANCE_PASSES {
+            for data in mir.basic_blocks.iter() {
+                for statement in &data.statements {
+                    let mir::StatementKind::Assign(box (place, rvalue)) = &statement.kind else {
+                        continue;
+                    };
+                    if !place.projection.is_empty() {
+                        continue;
+                    }
+                    let flag = match rvalue {
+                        mir::Rvalue::Use(operand) => operand.place().and_then(|read| {
+                            if matches!(
+                                solana_compat::projected_account_field(tcx, mir, &read),
+                                Some((_, AccountField::IsSigner))
+                            ) {
+                                Some(true)
+                            } else if read.projection.is_empty() {
+                                signer_flags.get(&read.local).copied()
+                            } else {
+                                None
+                            }
+                        }),
+                        mir::Rvalue::UnaryOp(mir::UnOp::Not, operand) => operand
+                            .place()
+                            .filter(|read| read.projection.is_empty())
+                            .and_then(|read| signer_flags.get(&read.local))
+                            .map(|flag| !flag),
+                        _ => None,
+                    };
+                    if let Some(flag) = flag {
+                        signer_flags.insert(place.local, flag);
+                    }
+                }
+            }
+        }
+        let dominators = mir.basic_blocks.dominators();
+        let predecessors = mir.basic_blocks.predecessors();
+        let mut signer_checked_blocks = HashSet::new();
+        for guard in find_error_guards(mir) {
+            let Some(flag) = signer_flags.get(&guard.condition) else {
+                continue;
+            };
+            // The guard must pass only if the account signs
+            if guard.passes_when != *flag {
+                continue;
+            }
+            let mir::TerminatorKind::SwitchInt { targets, .. } =
+                &mir.basic_blocks[guard.block].terminator().kind
+            else {
+                continue;
+            };
+            let passed = targets.target_for_value(u128::from(guard.passes_when));
+            if predecessors[passed].len() != 1 {
+                continue;
+            }
+            signer_checked_blocks.extend(
+                mir.basic_blocks
+                    .indices()
+                    .filter(|block| dominators.dominates(passed, *block)),
+            );
+        }
+        MissingSignerChecker {
+            in_anchor_handler: true,
+            signer_checked_blocks,
+            unsigned_change: None,
+        }
+    }
+
+    /// Records a mutable borrow of the lamports of the given account of the Accounts struct in
+    /// the given block, if it is unchecked and no account of the instruction signs it.
+    pub fn record_lamports_change(
+        &mut self,
+        span: Span,
+        block: mir::BasicBlock,
+        account: Option<&(AnchorAccount, String)>,
+        has_signer: bool,
+    ) {
+        if !self.in_anchor_handler
+            || has_signer
+            || self.signer_checked_blocks.contains(&block)
+            || self.unsigned_change.is_some()
+        {
+            return;
+        }
+        if let Some((AnchorAccount::Unchecked, name)) = account {
+            self.unsigned_change = Some((span, name.clone()));
+        }
+    }
+}
+
+// Hold the UncheckedAccount and AccountInfo fields of the Accounts struct of an Anchor handler
+// that no constraint validates, and the first change to the lamports or data of one of them.
+// Anchor checks what the constraints of the fields say, such as has_one, address or constraint,
+// with guards of the try_accounts function that the Accounts derive generates, which return an
+// error unless the condition holds. A field that no guard depends on, other than the one that
+// checks that it is writable, can be any account that the caller passes.
+#[derive(Default)]
+pub struct AccountConstraintChecker {
+    // The spans of the declarations of the unchecked fields that no constraint validates, by name
+    pub unconstrained_fields: HashMap<String, Span>,
+    // The span of the declaration of the first unconstrained field whose lamports or data the
+    // handler changes, its name, and the span of the change
+    pub unconstrained_change: Option<(Span, String, Span)>,
+}
+
+impl AccountConstraintChecker {
+    /// Returns the checker for the given handler, with the fields of the Accounts struct of its
+    /// Context that try_accounts does not validate.
+    pub fn new<'tcx>(tcx: TyCtxt<'tcx>, mir: &mir::Body<'tcx>) -> AccountConstraintChecker {
+        let mut checker = AccountConstraintChecker::default();
+        let Some((_, accounts_ty)) = mir
+            .args_iter()
+            .find_map(|arg| solana_compat::anchor_context(tcx, mir.local_decls[arg].ty))
+        else {
+            return checker;
+        };
+        let TyKind::Adt(def, args) = accounts_ty.kind() else {
+            return checker;
+        };
+        // The checks of a struct whose Accounts implementation is not generated in this crate
+        // cannot be inspected
+        let Some(try_accounts) = anchor_accounts_method(tcx, def.did(), "try_accounts") else {
+            return checker;
+        };
+        let validated = validated_anchor_fields(tcx, try_accounts, def.did());
+        for (index, field) in def.non_enum_variant().fields.iter_enumerated() {
+            if solana_compat::anchor_account(tcx, field.ty(tcx, args))
+                == Some(AnchorAccount::Unchecked)
+                && !validated.contains(&index)
+            {
+                checker
+                    .unconstrained_fields
+                    .insert(field.name.to_string(), tcx.def_span(field.did));
+            }
+        }
+        checker
+    }
+
+    /// Records a change to the lamports or data of the given account of the Accounts struct, if
+    /// it is an unchecked field that no constraint validates.
+    pub fn record_change(&mut self, span: Span, account: Option<&(AnchorAccount, String)>) {
+        if self.unconstrained_change.is_some() {
+            return;
+        }
+        if let Some((AnchorAccount::Unchecked, name)) = account {
+            if let Some(declaration) = self.unconstrained_fields.get(name) {
+                self.unconstrained_change = Some((*declaration, name.clone(), span));
+            }
+        }
+    }
+}
+
+// Returns the method with the given name of the implementations that the Accounts derive of Anchor
+// generates for the struct with the given id, such as try_accounts or exit, if they are generated
+// in this crate
+fn anchor_accounts_method(tcx: TyCtxt<'_>, accounts: DefId, name: &str) -> Option<DefId> {
+    tcx.hir()
+        .body_owners()
+        .map(|owner| owner.to_def_id())
+        .find(|def_id| {
+            tcx.opt_item_name(*def_id)
+                .is_some_and(|item_name| item_name.as_str() == name)
+                && tcx.impl_of_method(*def_id).is_some_and(|impl_def_id| {
+                    matches!(tcx.type_of(impl_def_id).instantiate_identity().kind(),
+                        TyKind::Adt(def, _) if def.did() == accounts)
+                })
+        })
+}
+
+// Returns the fields of the Accounts struct with the given id that a guard of its try_accounts
+// function depends on, other than through the is_writable flag of the account, which is all that
+// the mut constraint checks. The fields are the locals that the struct is built from at the end.
+fn validated_anchor_fields(
+    tcx: TyCtxt<'_>,
+    try_accounts: DefId,
+    accounts: DefId,
+) -> HashSet<FieldIdx> {
+    let mir = tcx.optimized_mir(try_accounts);
+    // The fields that the value of each local depends on
+    let mut dependencies: HashMap<mir::Local, HashSet<FieldIdx>> = HashMap::new();
+    for data in mir.basic_blocks.iter() {
+        for statement in &data.statements {
+            if let mir::StatementKind::Assign(box (_, mir::Rvalue::Aggregate(kind, operands))) =
+                &statement.kind
+            {
+                if matches!(**kind, mir::AggregateKind::Adt(def_id, ..) if def_id == accounts) {
+                    for (index, operand) in operands.iter_enumerated() {
+                        if let Some(place) = operand.place() {
+                            dependencies.entry(place.local).or_default().insert(index);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    let fields_of = |dependencies: &HashMap<mir::Local, HashSet<FieldIdx>>,
+                     place: &mir::Place<'_>| {
+        if matches!(
+            solana_compat::projected_account_field(tcx, mir, place),
+            Some((_, AccountField::IsWritable))
+        ) {
+            return HashSet::new();
+        }
+        dependencies.get(&place.local).cloned().unwrap_or_default()
+    };
+    for _ in 0..MAX_PROVENANCE_PASSES {
+        let known_dependencies = dependencies.clone();
+        for data in mir.basic_blocks.iter() {
+            if data.is_cleanup {
+                continue;
+            }
+            for statement in &data.statements {
+                let mir::StatementKind::Assign(box (place, rvalue)) = &statement.kind else {
+                    continue;
+                };
+                let read_places: Vec<&mir::Place<'_>> = match rvalue {
+                    mir::Rvalue::Use(operand)
+                    | mir::Rvalue::Cast(_, operand, _)
+                    | mir::Rvalue::UnaryOp(_, operand)
+                    | mir::Rvalue::Repeat(operand, _) => operand.place().into_iter().collect(),
+                    mir::Rvalue::Ref(_, _, source)
+                    | mir::Rvalue::RawPtr(_, source)
+                    | mir::Rvalue::CopyForDeref(source)
+                    | mir::Rvalue::Discriminant(source)
+                    | mir::Rvalue::Len(source) => vec![source],
+                    mir::Rvalue::BinaryOp(_, box (left, right)) => {
+                        left.place().into_iter().chain(right.place()).collect()
+                    }
+                    mir::Rvalue::Aggregate(_, operands) => {
+                        operands.iter().filter_map(|operand| operand.place()).collect()
+                    }
+                    _ => Vec::new(),
+                };
+                let fields: HashSet<FieldIdx> = read_places
+                    .into_iter()
+                    .flat_map(|source| fields_of(&dependencies, source))
+                    .collect();
+                if !fields.is_empty() {
+                    dependencies.entry(place.local).or_default().extend(fields);
+                }
+                // The struct may be built from copies of the locals that the guards read
+                if let mir::Rvalue::Use(operand) = rvalue {
+                    if let Some(source) = operand.place().filter(|source| {
+                        source.projection.is_empty() && place.projection.is_empty()
+                    }) {
+                        let fields = dependencies.get(&place.local).cloned().unwrap_or_default();
+                        dependencies.entry(source.local).or_default().extend(fields);
+                    }
+                }
+            }
+            if let mir::TerminatorKind::Call {
+                args, destination, ..
+            } = &data.terminator().kind
+            {
+                let fields: HashSet<FieldIdx> = args
+                    .iter()
+                    .filter_map(|arg| arg.node.place())
+                    .flat_map(|source| fields_of(&dependencies, &source))
+                    .collect();
+                if !fields.is_empty() {
+                    dependencies
+                        .entry(destination.local)
+                        .or_default()
+                        .extend(fields);
+                }
+            }
+        }
+        if dependencies == known_dependencies {
+            break;
+        }
+    }
+    find_error_guards(mir)
+        .into_iter()
+        .filter_map(|guard| dependencies.get(&guard.condition))
+        .flatten()
+        .copied()
+        .collect()
+}
+
+// Hold the calls of an entrypoint to foreign functions that the registry of foreign models forbids
+// in entrypoints, such as std::process::exit or std::thread::spawn, which a program cannot call
+// in the virtual machine that it runs in.
+#[derive(Default)]
+pub struct ForbiddenCallChecker {
+    // True if the function is a program entrypoint
+    pub in_entrypoint: bool,
+    // The spans of the forbidden calls and the def paths of their callees
+    pub calls: Vec<(Span, String)>,
+}
+
+impl ForbiddenCallChecker {
+    /// Records a call of the given function, if the function is an entrypoint and has not
+    /// already called it at the same place.
+    pub fn record_call(&mut self, span: Span, callee: String) {
+        if self.in_entrypoint && !self.calls.iter().any(|(s, _)| *s == span) {
+            self.calls.push((span, callee));
+        }
+    }
+}
+
+// A balance that amounts are subtracted from
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Balance {
+    // The lamports of the account held by the local, if known
+    Lamports(Option<mir::Local>),
+    // An entry of a map of balances
+    Entry,
+}
+
+impl Balance {
+    // True if the balances may be the same, which they are unless they are the lamports of
+    // different accounts or of different kinds
+    fn may_be(self, other: Balance) -> bool {
+        match (self, other) {
+            (Balance::Lamports(Some(a)), Balance::Lamports(Some(b))) => a == b,
+            (Balance::Lamports(_), Balance::Lamports(_)) | (Balance::Entry, Balance::Entry) => true,
+            _ => false,
+        }
+    }
+}
+
+// Hold the subtractions from the lamports of an account, or from an entry of a map of balances,
+// that are not preceded by a comparison of the balance with the amount, such as
+// `**user_account.try_borrow_mut_lamports()? -= amount` without a check of user_account.lamports().
+// Whether the balance may actually be less than the amount is decided with the path condition.
+#[derive(Default)]
+pub struct LamportUnderflowChecker {
+    // The locals that hold a balance, a reference to one or a borrow of one
+    pub balance_locals: HashMap<mir::Local, Balance>,
+    // The first local to hold each value, by the locals that hold copies of it
+    pub value_aliases: HashMap<mir::Local, mir::Local>,
+    // The comparisons of a balance with an amount, by the blocks that contain them
+    pub comparisons: Vec<(mir::BasicBlock, Balance, mir::Local)>,
+    // The span of the first subtraction that may underflow, the balance it subtracts from and
+    // true if it underflows whenever it is reached
+    pub underflow: Option<(Span, Balance, bool)>,
+}
+
+impl LamportUnderflowChecker {
+    /// Describes the first subtraction that may underflow.
+    pub fn describe(&self) -> String {
+        let Some((_, balance, definite)) = self.underflow else {
+            return String::new();
+        };
+        let underflow = if definite {
+            "underflow"
+        } else {
+            "possible underflow"
+        };
+        let balance = match balance {
+            Balance::Lamports(_) => "the lamports of an account",
+            Balance::Entry => "a balance",
+        };
+        format!("{underflow} of {balance}, which is not compared with the amount subtracted from it, for the smart contract")
+    }
+
+    /// Follows balances and accounts through assignments, records the comparisons of balances
+    /// with amounts and returns the balance, the minuend and the subtrahend of a subtraction
+    /// from a balance that no comparison of the balance with the subtrahend dominates.
+    pub fn visit_assign<'a, 'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        provenance: &AmountProvenance,
+        location: mir::Location,
+        place: &mir::Place<'tcx>,
+        rvalue: &'a mir::Rvalue<'tcx>,
+    ) -> Option<(Balance, &'a mir::Operand<'tcx>, &'a mir::Operand<'tcx>)> {
+        if place.projection.is_empty() {
+            self.balance_locals.remove(&place.local);
+            self.value_aliases.remove(&place.local);
+        }
+        match rvalue {
+            mir::Rvalue::Use(mir::Operand::Copy(source) | mir::Operand::Move(source))
+            | mir::Rvalue::Ref(_, _, source)
+            | mir::Rvalue::CopyForDeref(source) => {
+                if !place.projection.is_empty() {
+                    return None;
+                }
+                if let Some(balance) = self.balance_of_place(source) {
+                    self.balance_locals.insert(place.local, balance);
+                } else if let Some((account, AccountField::Lamports)) =
+                    solana_compat::projected_account_field(tcx, mir, source)
+                {
+                    let account = provenance.account_of(account);
+                    self.balance_locals
+                        .insert(place.local, Balance::Lamports(Some(account)));
+                }
+                if source.projection.is_empty()
+                    && !is_account_reference(tcx, mir.local_decls[source.local].ty)
+                {
+                    let value = self.value_of(source.local);
+                    self.value_aliases.insert(place.local, value);
+                }
+                None
+            }
+            mir::Rvalue::BinaryOp(bin_op, box (left, right)) => match bin_op {
+                mir::BinOp::Lt | mir::BinOp::Le | mir::BinOp::Gt | mir::BinOp::Ge => {
+                    let comparison = match (
+                        self.balance_of_operand(left),
+                        self.balance_of_operand(right),
+                    ) {
+                        (Some(balance), None) => {
+                            self.value_of_operand(right).map(|amount| (balance, amount))
+                        }
+                        (None, Some(balance)) => {
+                            self.value_of_operand(left).map(|amount| (balance, amount))
+                        }
+                        _ => None,
+                    };
+                    if let Some((balance, amount)) = comparison {
+                        let comparison = (location.block, balance, amount);
+                        if !self.comparisons.contains(&comparison) {
+                            self.comparisons.push(comparison);
+                        }
+                    }
+                    None
+                }
+                mir::BinOp::Sub | mir::BinOp::SubWithOverflow | mir::BinOp::SubUnchecked => {
+                    let balance = self.balance_of_operand(left)?;
+                    let amount = self.value_of_operand(right);
+                    let dominators = mir.basic_blocks.dominators();
+                    let is_guarded =
+                        self.comparisons
+                            .iter()
+                            .any(|(block, compared, compared_amount)| {
+                                compared.may_be(balance)
+                                    && Some(*compared_amount) == amount
+                                    && dominators.dominates(*block, location.block)
+                            });
+                    if is_guarded {
+                        None
+                    } else {
+                        Some((balance, left, right))
+                    }
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Follows balances through the calls that read or borrow the lamports of an account, that
+    /// look up an entry of a map of balances, and that unwrap or dereference the result.
+    pub fn visit_call<'tcx>(
+        &mut self,
+        provenance: &AmountProvenance,
+        callee_name: &str,
+        item_name: &str,
+        account_access: Option<(AccountField, rustc_hir::Mutability)>,
+        args: &[Spanned<mir::Operand<'tcx>>],
+        destination: &mir::Place<'tcx>,
+    ) {
+        if !destination.projection.is_empty() {
+            return;
+        }
+        let receiver = args.first().map(|arg| &arg.node);
+        let balance = if matches!(account_access, Some((AccountField::Lamports, _))) {
+            // The receiver is either the account or a reference to its lamports field
+            let account = receiver
+                .and_then(|receiver| receiver.place())
+                .filter(|place| {
+                    place.projection.is_empty() && !self.balance_locals.contains_key(&place.local)
+                })
+                .map(|place| provenance.account_of(place.local));
+            Some(Balance::Lamports(account))
+        } else if callee_name.contains("std.collections.hash.map")
+            && matches!(item_name, "entry" | "get" | "get_mut")
+        {
+            Some(Balance::Entry)
+        } else if matches!(
+            item_name,
+            "branch"
+                | "deref"
+                | "deref_mut"
+                | "expect"
+                | "ok_or"
+                | "ok_or_else"
+                | "or_default"
+                | "or_insert"
+                | "or_insert_with"
+                | "unwrap"
+        ) {
+            receiver.and_then(|receiver| self.balance_of_operand(receiver))
+        } else {
+            None
+        };
+        if let Some(balance) = balance {
+            self.balance_locals.insert(destination.local, balance);
+        } else {
+            self.balance_locals.remove(&destination.local);
+        }
+    }
+
+    /// Records a subtraction from a balance that is not preceded by a comparison, unless it is
+    /// known not to underflow, with true if it underflows whenever it is reached.
+    pub fn record_subtraction(&mut self, span: Span, balance: Balance, no_underflow: Option<bool>) {
+        if no_underflow != Some(true) && self.underflow.is_none() {
+            self.underflow = Some((span, balance, no_underflow == Some(false)));
+        }
+    }
+
+    /// Returns the balance that an assignment to the given place writes, if the place is a
+    /// dereference of a local that refers to a balance.
+    pub fn written_balance(&self, place: &mir::Place<'_>) -> Option<Balance> {
+        if place.projection.last() != Some(&mir::ProjectionElem::Deref) {
+            return None;
+        }
+        self.balance_of_place(place)
+    }
+
+    /// Returns the account whose lamports the given value adds an amount to or subtracts one
+    /// from, the local holding the amount and true if the amount is added.
+    pub fn transfer(&self, rvalue: &mir::Rvalue<'_>) -> Option<(mir::Local, mir::Local, bool)> {
+        let mir::Rvalue::BinaryOp(bin_op, box (left, right)) = rvalue else {
+            return None;
+        };
+        let credit = match bin_op {
+            mir::BinOp::Add | mir::BinOp::AddWithOverflow | mir::BinOp::AddUnchecked => true,
+            mir::BinOp::Sub | mir::BinOp::SubWithOverflow | mir::BinOp::SubUnchecked => false,
+            _ => return None,
+        };
+        let Some(Balance::Lamports(Some(account))) = self.balance_of_operand(left) else {
+            return None;
+        };
+        Some((account, self.value_of_operand(right)?, credit))
+    }
+
+    // Returns the first local to hold the value that the given local holds
+    fn value_of(&self, local: mir::Local) -> mir::Local {
+        self.value_aliases.get(&local).copied().unwrap_or(local)
+    }
+
+    fn value_of_operand(&self, operand: &mir::Operand<'_>) -> Option<mir::Local> {
+        match operand {
+            mir::Operand::Copy(place) | mir::Operand::Move(place)
+                if place.projection.is_empty() =>
+            {
+                Some(self.value_of(place.local))
+            }
+            _ => None,
+        }
+    }
+
+    // Returns the balance that the place reads or refers to, if the place is reached from a local
+    // holding a balance through dereferences and the fields of wrappers
+    fn balance_of_place(&self, place: &mir::Place<'_>) -> Option<Balance> {
+        let balance = self.balance_locals.get(&place.local)?;
+        place
+            .projection
+            .iter()
+            .all(|elem| {
+                matches!(
+                    elem,
+                    mir::ProjectionElem::Deref
+                        | mir::ProjectionElem::Field(..)
+                        | mir::ProjectionElem::Downcast(..)
+                )
+            })
+            .then_some(*balance)
+    }
+
+    fn balance_of_operand(&self, operand: &mir::Operand<'_>) -> Option<Balance> {
+        match operand {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => self.balance_of_place(place),
+            mir::Operand::Constant(..) => None,
+        }
+    }
+}
+
+// Hold the writes of the lamports of accounts that leave them without lamports, which close the
+// accounts, and the writes of the data of accounts from its start. The runtime only deletes an
+// account without lamports at the end of the transaction, so a later instruction of the same
+// transaction can fund the account again and revive it with its data, unless the data is
+// overwritten when the account is closed, with zeros or with a discriminator that marks it closed.
+#[derive(Default)]
+pub struct AccountCloseChecker {
+    // The locals that hold a reference to the data field of an account, a mutable borrow of the
+    // data, or a mutable slice of it that starts at the start of the data, by the locals holding
+    // the accounts
+    pub data_locals: HashMap<mir::Local, mir::Local>,
+    // The fields of the Accounts struct of an Anchor handler that a close constraint closes, which
+    // Anchor does by overwriting the discriminator of the data when the handler returns
+    pub closed_fields: HashSet<String>,
+    // The blocks that write the data of an account from its start, with the accounts
+    pub overwrites: Vec<(mir::BasicBlock, mir::Local)>,
+    // The span and the block of each write that leaves an account without lamports, with the
+    // account, if known
+    pub drains: Vec<(Span, mir::BasicBlock, Option<mir::Local>)>,
+}
+
+impl AccountCloseChecker {
+    /// Returns the checker for the given Anchor handler, with the fields of the Accounts struct of
+    /// its Context that a close constraint closes.
+    pub fn new<'tcx>(tcx: TyCtxt<'tcx>, mir: &mir::Body<'tcx>) -> AccountCloseChecker {
+        let mut checker = AccountCloseChecker::default();
+        let Some((_, accounts_ty)) = mir
+            .args_iter()
+            .find_map(|arg| solana_compat::anchor_context(tcx, mir.local_decls[arg].ty))
+        else {
+            return checker;
+        };
+        let TyKind::Adt(def, _) = accounts_ty.kind() else {
+            return checker;
+        };
+        let Some(exit) = anchor_accounts_method(tcx, def.did(), "exit") else {
+            return checker;
+        };
+        // The exit function closes a field with AccountsClose::close(&self.field, destination)
+        let exit_mir = tcx.optimized_mir(exit);
+        let mut field_refs: HashMap<mir::Local, FieldIdx> = HashMap::new();
+        for data in exit_mir.basic_blocks.iter() {
+            for statement in &data.statements {
+                let mir::StatementKind::Assign(box (place, rvalue)) = &statement.kind else {
+                    continue;
+                };
+                let field = match rvalue {
+                    mir::Rvalue::Ref(_, _, source) => match source.projection.as_slice() {
+                        [mir::ProjectionElem::Deref, mir::ProjectionElem::Field(index, _)]
+                            if source.local == mir::Local::from_usize(1) =>
+                        {
+                            Some(*index)
+                        }
+                        _ => None,
+                    },
+                    mir::Rvalue::Use(operand) => operand
+                        .place()
+                        .and_then(|source| field_refs.get(&source.local).copied()),
+                    _ => None,
+                };
+                if let (Some(field), true) = (field, place.projection.is_empty()) {
+                    field_refs.insert(place.local, field);
+                }
+            }
+            if let mir::TerminatorKind::Call { func, args, .. } = &data.terminator().kind {
+                let closes = func.const_fn_def().is_some_and(|(callee_def_id, _)| {
+                    tcx.opt_item_name(callee_def_id)
+                        .is_some_and(|name| name.as_str() == "close")
+                });
+                let field = args
+                    .first()
+                    .and_then(|arg| arg.node.place())
+                    .and_then(|place| field_refs.get(&place.local));
+                if let (true, Some(field)) = (closes, field) {
+                    let name = def.non_enum_variant().fields[*field].name;
+                    checker.closed_fields.insert(name.to_string());
+                }
+            }
+        }
+        checker
+    }
+
+    /// Returns the span of the first write that leaves an account without lamports, on a path
+    /// that does not also write the data of the account from its start.
+    pub fn revivable_close(&self, mir: &mir::Body<'_>) -> Option<Span> {
+        let dominators = mir.basic_blocks.dominators();
+        self.drains
+            .iter()
+            .find(|(_, block, account)| {
+                !self.overwrites.iter().any(|(overwrite, overwritten)| {
+                    account.is_none_or(|account| account == *overwritten)
+                        && (dominators.dominates(*overwrite, *block)
+                            || dominators.dominates(*block, *overwrite))
+                })
+            })
+            .map(|(span, ..)| *span)
+    }
+
+    /// Follows the data of accounts into copies of the locals that hold it and reborrows of it,
+    /// and records the writes through them.
+    pub fn visit_assign<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        block: mir::BasicBlock,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+        account_of: impl Fn(mir::Local) -> mir::Local,
+    ) {
+        if !place.projection.is_empty() {
+            if let Some(account) = self.data_locals.get(&place.local).copied() {
+                self.record_overwrite(block, account);
+            }
+            return;
+        }
+        let account = match rvalue {
+            mir::Rvalue::Ref(_, _, source)
+            | mir::Rvalue::Use(mir::Operand::Copy(source) | mir::Operand::Move(source))
+            | mir::Rvalue::CopyForDeref(source) => {
+                match solana_compat::projected_account_field(tcx, mir, source) {
+                    Some((account, AccountField::Data))
+                        if matches!(rvalue, mir::Rvalue::Ref(..)) =>
+                    {
+                        Some(account_of(account))
+                    }
+                    // The borrows are unwrapped from the results of try_borrow_mut_data
+                    _ => source
+                        .projection
+                        .iter()
+                        .all(|elem| {
+                            matches!(
+                                elem,
+                                mir::ProjectionElem::Deref
+                                    | mir::ProjectionElem::Field(..)
+                                    | mir::ProjectionElem::Downcast(..)
+                            )
+                        })
+                        .then(|| self.data_locals.get(&source.local).copied())
+                        .flatten(),
+                }
+            }
+            _ => None,
+        };
+        if let Some(account) = account {
+            self.data_locals.insert(place.local, account);
+        } else {
+            self.data_locals.remove(&place.local);
+        }
+    }
+
+    /// Follows the data of accounts through the calls that borrow it mutably, dereference the
+    /// borrows or slice them from the start, and records the other calls that are passed a
+    /// mutable reference to it, such as fill or copy_from_slice, as writes of the data.
+    #[allow(clippy::too_many_arguments)]
+    pub fn visit_call<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        block: mir::BasicBlock,
+        item_name: &str,
+        account_access: Option<(AccountField, rustc_hir::Mutability)>,
+        args: &[Spanned<mir::Operand<'tcx>>],
+        destination: &mir::Place<'tcx>,
+        account_of: impl Fn(mir::Local) -> mir::Local,
+    ) {
+        let receiver = args.first().and_then(|arg| arg.node.place());
+        let receiver_data = receiver.and_then(|place| self.data_locals.get(&place.local).copied());
+        let borrowed = match (account_access, item_name) {
+            (Some((AccountField::Data, rustc_hir::Mutability::Mut)), _) => {
+                receiver_data.or_else(|| receiver.map(|place| account_of(place.local)))
+            }
+            (_, "branch" | "deref_mut" | "expect" | "unwrap") => receiver_data,
+            // A slice of the data from its start, such as data[..] or data[..8]
+            (_, "index_mut") => receiver_data.filter(|_| {
+                args.get(1).is_some_and(|index| {
+                    matches!(index.node.ty(mir, tcx).kind(), TyKind::Adt(def, _)
+                        if matches!(tcx.item_name(def.did()).as_str(),
+                            "RangeFull" | "RangeTo" | "RangeToInclusive"))
+                })
+            }),
+            _ => {
+                let written = args.iter().find_map(|arg| {
+                    let place = arg.node.place()?;
+                    let is_mutable = matches!(
+                        place.ty(mir, tcx).ty.kind(),
+                        TyKind::Ref(_, _, rustc_hir::Mutability::Mut)
+                    );
+                    is_mutable
+                        .then(|| self.data_locals.get(&place.local).copied())
+                        .flatten()
+                });
+                if let Some(account) = written {
+                    self.record_overwrite(block, account);
+                }
+                None
+            }
+        };
+        if !destination.projection.is_empty() {
+            return;
+        }
+        if let Some(account) = borrowed {
+            self.data_locals.insert(destination.local, account);
+        } else {
+            self.data_locals.remove(&destination.local);
+        }
+    }
+
+    /// Records a write of the lamports of an account that leaves it without lamports, unless the
+    /// account is a field of the Accounts struct of an Anchor handler that a close constraint
+    /// closes.
+    pub fn record_drain(
+        &mut self,
+        span: Span,
+        block: mir::BasicBlock,
+        account: Option<mir::Local>,
+        anchor_account: Option<&(AnchorAccount, String)>,
+    ) {
+        if anchor_account.is_some_and(|(_, name)| self.closed_fields.contains(name)) {
+            return;
+        }
+        if !self
+            .drains
+            .iter()
+            .any(|(drain_span, ..)| *drain_span == span)
+        {
+            self.drains.push((span, block, account));
+        }
+    }
+
+    fn record_overwrite(&mut self, block: mir::BasicBlock, account: mir::Local) {
+        if !self.overwrites.contains(&(block, account)) {
+            self.overwrites.push((block, account));
+        }
+    }
+}
+
+/// The bytes of the metadata of an account that rent is charged for besides its data.
+pub const ACCOUNT_STORAGE_OVERHEAD: u128 = 128;
+
+/// The lamports per byte that make an account exempt from rent under the default rent of the
+/// clusters, which charges 3480 lamports per byte-year and exempts the accounts that hold two years
+/// of rent.
+pub const RENT_EXEMPT_LAMPORTS_PER_BYTE: u128 = 6960;
+
+// Hold the accounts that a function creates, with the create_account instruction of the system
+// program or with allocate and assign, or whose data it reallocates, and the transfers of lamports
+// to them. An account that is credited with less than the rent-exempt minimum for the length of its
+// data may be reaped by the runtime, and the lamports of the user go with it. Whether a credit
+// reaches the minimum is decided with the path condition, for the default rent, with which
+// Rent::minimum_balance is modeled. The credit of an account whose data is reallocated adds to
+// the lamports that the function reads of the account, if it reads them.
+#[derive(Default)]
+pub struct RentDustChecker {
+    // The keys of the accounts allocated so far, with the lengths of their data, the spans of the
+    // instructions that allocate them, and the lamports of the accounts whose data is reallocated
+    pub allocations: Vec<(Rc<AbstractValue>, Rc<AbstractValue>, Span, Option<Rc<AbstractValue>>)>,
+    // The keys of the accounts whose lamports the function reads, with the locals that hold them
+    pub balance_locals: Vec<(Rc<AbstractValue>, mir::Local)>,
+    // The keys of the accounts that lamports are transferred to so far, with the amounts and the
+    // spans of the transfers
+    pub transfers: Vec<(Rc<AbstractValue>, Rc<AbstractValue>, Span)>,
+    // The span of the first credit that may be below the minimum, the span of the creation of the
+    // account, true if it is below whenever it is reached, and the shortfall, rendered as Rust
+    pub underfunded: Option<(Span, Span, bool, String)>,
+}
+
+impl RentDustChecker {
+    /// Describes the first credit that may be below the rent-exempt minimum.
+    pub fn describe(&self) -> String {
+        let Some((_, _, definite, _)) = &self.underfunded else {
+            return String::new();
+        };
+        let loss = if *definite { "loss" } else { "possible loss" };
+        format!("{loss} of the lamports credited to an account that is created with fewer lamports than the rent-exempt minimum for its data, which the runtime may reap, for the smart contract")
+    }
+
+    /// Records an instruction of the system program that allocates the data of the account with
+    /// the given key or credits it with lamports, and returns the credits of accounts created in
+    /// the function that are to be checked: the amount, the length of the data, the span of the
+    /// credit and the span of the allocation. An instruction that does both, such as
+    /// create_account, is a credit of the account it creates.
+    pub fn visit_instruction(
+        &mut self,
+        key: Rc<AbstractValue>,
+        lamports: Option<Rc<AbstractValue>>,
+        space: Option<Rc<AbstractValue>>,
+        span: Span,
+    ) -> Vec<(Rc<AbstractValue>, Rc<AbstractValue>, Span, Span)> {
+        match (lamports, space) {
+            (Some(lamports), Some(space)) => {
+                self.allocations.push((key, space.clone(), span, None));
+                vec![(lamports, space, span, span)]
+            }
+            (None, Some(space)) => {
+                let credits = self
+                    .transfers
+                    .iter()
+                    .filter(|(credited, _, _)| *credited == key)
+                    .map(|(_, lamports, transfer_span)| {
+                        (lamports.clone(), space.clone(), *transfer_span, span)
+                    })
+                    .collect();
+                self.allocations.push((key, space, span, None));
+                credits
+            }
+            (Some(lamports), None) => {
+                let credits = self
+                    .allocations
+                    .iter()
+                    .filter(|(allocated, ..)| *allocated == key)
+                    .map(|(_, space, allocation_span, balance)| {
+                        let credit = match balance {
+                            Some(balance) => lamports.addition(balance.clone()),
+                            None => lamports.clone(),
+                        };
+                        (credit, space.clone(), span, *allocation_span)
+                    })
+                    .collect();
+                self.transfers.push((key, lamports, span));
+                credits
+            }
+            (None, None) => Vec::new(),
+        }
+    }
+
+    /// Records a reallocation of the data of the account with the given key to the given length,
+    /// with the lamports that the account holds, if the function reads them, and returns the
+    /// credits of the account so far that are to be checked against the minimum for the length,
+    /// as visit_instruction does.
+    pub fn visit_realloc(
+        &mut self,
+        key: Rc<AbstractValue>,
+        space: Rc<AbstractValue>,
+        balance: Option<Rc<AbstractValue>>,
+        span: Span,
+    ) -> Vec<(Rc<AbstractValue>, Rc<AbstractValue>, Span, Span)> {
+        let credits = self
+            .transfers
+            .iter()
+            .filter(|(credited, _, _)| *credited == key)
+            .map(|(_, lamports, transfer_span)| {
+                let credit = match &balance {
+                    Some(balance) => lamports.addition(balance.clone()),
+                    None => lamports.clone(),
+                };
+                (credit, space.clone(), *transfer_span, span)
+            })
+            .collect();
+        self.allocations.push((key, space, span, balance));
+        credits
+    }
+
+    /// Returns the local that holds the lamports that the function last read of the account with
+    /// the given key, if any.
+    pub fn balance_local(&self, key: &Rc<AbstractValue>) -> Option<mir::Local> {
+        self.balance_locals
+            .iter()
+            .rev()
+            .find(|(read, _)| read == key)
+            .map(|(_, local)| *local)
+    }
+
+    /// Records a credit that is not known to reach the rent-exempt minimum of the account, with
+    /// true if it is below the minimum whenever it is reached.
+    pub fn record_credit(
+        &mut self,
+        span: Span,
+        creation_span: Span,
+        exempt: Option<bool>,
+        shortfall: String,
+    ) {
+        if exempt != Some(true) && self.underfunded.is_none() {
+            self.underfunded = Some((span, creation_span, exempt == Some(false), shortfall));
+        }
+    }
+}
+
+// Hold the first transfer of lamports that has no effect on any path that reaches it: a change of
+// the lamports of an account by zero, such as `**account.try_borrow_mut_lamports()? -= 0`, or a
+// transfer instruction of the system program of zero lamports or from an account to itself. They
+// lose nothing, but they are usually left over from an edit of the program, or are meant to move
+// lamports that they do not move. Whether the amount is zero or the keys are equal is decided with
+// the path condition, so that a transfer that only sometimes has no effect is not reported.
+#[derive(Default)]
+pub struct NoOpTransferChecker {
+    // The span of the first transfer that has no effect, and why it has none
+    pub no_op: Option<(Span, NoOpTransfer)>,
+}
+
+/// Why a transfer of lamports has no effect.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NoOpTransfer {
+    // The amount is zero
+    ZeroAmount,
+    // The lamports are transferred from an account to itself
+    SameAccount,
+}
+
+impl NoOpTransferChecker {
+    /// Describes the first transfer that has no effect.
+    pub fn describe(&self) -> String {
+        let Some((_, no_op)) = self.no_op else {
+            return String::new();
+        };
+        let transfer = match no_op {
+            NoOpTransfer::ZeroAmount => "transfer of zero lamports",
+            NoOpTransfer::SameAccount => "transfer of lamports from an account to itself",
+        };
+        format!("{transfer}, which has no effect, for the smart contract")
+    }
+
+    /// Records a transfer that has no effect whenever it is reached.
+    pub fn record(&mut self, span: Span, no_op: NoOpTransfer) {
+        if self.no_op.is_none() {
+            self.no_op = Some((span, no_op));
+        }
+    }
+}
+
+// Hold the updates of the ledger of a function, the balances kept in maps, and of the lamports of
+// the account that the ledger is declared with --ledger-account to add up to. The declared
+// invariant is that the total of the ledger equals the lamports of the account at entry, so it
+// still holds at an exit if the function changes both by the same amount. The changes are kept in
+// model fields of the account, so that they are joined along with the rest of the state, and they
+// are compared with the solver after each update and at the exits that return Ok.
+#[derive(Default)]
+pub struct LedgerChecker {
+    // The local that holds the account, if the function has a variable with the declared name
+    pub account: Option<mir::Local>,
+    // The updates of the ledger or of the lamports of the account, with their spans and true if
+    // both are known to have changed by the same amount after the update
+    pub updates: BTreeMap<mir::Location, (Span, bool)>,
+    // The span of the first update that leaves the ledger and the lamports apart, and the span of
+    // the exit where they are found to be apart
+    pub drift: Option<(Span, Span)>,
+}
+
+impl LedgerChecker {
+    /// The model field of the account that holds the change of the total of the ledger.
+    pub const LEDGER_DELTA: &'static str = "hepha_ledger_delta";
+    /// The model field of the account that holds the change of its lamports.
+    pub const LAMPORTS_DELTA: &'static str = "hepha_lamports_delta";
+
+    pub fn new<'tcx>(
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        account_name: Option<&str>,
+    ) -> LedgerChecker {
+        let Some(account_name) = account_name else {
+            return LedgerChecker::default();
+        };
+        let account = mir.var_debug_info.iter().find_map(|info| match info.value {
+            mir::VarDebugInfoContents::Place(place)
+                if info.name.as_str() == account_name
+                    && place.projection.is_empty()
+                    && is_single_account_type(tcx, mir.local_decls[place.local].ty) =>
+            {
+                Some(place.local)
+            }
+            _ => None,
+        });
+        LedgerChecker {
+            account,
+            ..LedgerChecker::default()
+        }
+    }
+
+    /// Returns the model field that an assignment to the given balance changes, if the balance
+    /// is an entry of the ledger or the lamports of the account.
+    pub fn delta_field(&self, balance: Balance) -> Option<&'static str> {
+        self.account?;
+        match balance {
+            Balance::Entry => Some(Self::LEDGER_DELTA),
+            Balance::Lamports(account) if account.is_some() && account == self.account => {
+                Some(Self::LAMPORTS_DELTA)
+            }
+            Balance::Lamports(_) => None,
+        }
+    }
+
+    /// Records an update, with true if the ledger and the lamports are known to have changed by
+    /// the same amount after it.
+    pub fn record_update(&mut self, location: mir::Location, span: Span, balanced: bool) {
+        self.updates.insert(location, (span, balanced));
+    }
+
+    /// Records an exit that returns Ok, although the ledger and the lamports may have changed by
+    /// different amounts, unless drift has already been found.
+    pub fn record_drift(&mut self, exit_span: Span) {
+        if self.drift.is_some() || self.updates.is_empty() {
+            return;
+        }
+        // The drift is introduced by the first update that leaves them apart after the last
+        // update that brings them together
+        let mut first_apart = None;
+        for (span, balanced) in self.updates.values() {
+            if *balanced {
+                first_apart = None;
+            } else if first_apart.is_none() {
+                first_apart = Some(*span);
+            }
+        }
+        self.drift = Some((first_apart.unwrap_or(exit_span), exit_span));
+    }
+}
+
+// Hold the updates of the balances of a function, the entries of maps of balances and the lamports
+// of accounts. A function that updates two or more balances of a kind on a path moves amounts
+// between them, so their total should be unchanged when it returns Ok, while a deposit or a
+// withdrawal updates a single balance of each kind. As for the ledger, the total change and the
+// number of updates of each kind are kept in model fields, so that they are joined along with the
+// rest of the state, and whether the total may have changed is decided with the solver at the
+// exits that return Ok. A credit without a debit of the same amount, such as a receiver that is
+// debited instead of credited, is found this way.
+#[derive(Default)]
+pub struct BalanceConservationChecker {
+    // The updates of balances, with the model field of their kind, their spans and true if the
+    // total of the balances of the kind is known to be unchanged after the update, unless it is
+    // the first on its path
+    pub updates: BTreeMap<mir::Location, (&'static str, Span, bool)>,
+    // The span of the last update that leaves the total of a kind changed, the span of the exit
+    // where it is found to be changed and the model field of the kind
+    pub imbalance: Option<(Span, Span, &'static str)>,
+}
+
+impl BalanceConservationChecker {
+    /// The model field that holds the change of the total of the entries of maps of balances.
+    pub const ENTRIES_DELTA: &'static str = "hepha_entries_delta";
+    /// The model field that holds the number of updates of the entries of maps of balances.
+    pub const ENTRIES_UPDATES: &'static str = "hepha_entries_updates";
+    /// The model field that holds the change of the total of the lamports of accounts.
+    pub const LAMPORTS_DELTA: &'static str = "hepha_accounts_lamports_delta";
+    /// The model field that holds the number of updates of the lamports of accounts.
+    pub const LAMPORTS_UPDATES: &'static str = "hepha_accounts_lamports_updates";
+    /// The model fields of the total change and of the number of updates of each kind of balance.
+    pub const KINDS: [(&'static str, &'static str); 2] = [
+        (Self::ENTRIES_DELTA, Self::ENTRIES_UPDATES),
+        (Self::LAMPORTS_DELTA, Self::LAMPORTS_UPDATES),
+    ];
+
+    /// Describes the first kind of balance whose total is found to change.
+    pub fn describe(&self) -> String {
+        let Some((_, _, delta_field)) = self.imbalance else {
+            return String::new();
+        };
+        let balances = if delta_field == Self::ENTRIES_DELTA {
+            "the balances kept in maps"
+        } else {
+            "the lamports of accounts"
+        };
+        format!("possible unbalanced transfer, whose credits and debits of {balances} do not cancel out, for the smart contract")
+    }
+
+    /// Returns the model fields of the total change and of the number of updates of the kind of
+    /// the given balance.
+    pub fn fields_of(balance: Balance) -> (&'static str, &'static str) {
+        match balance {
+            Balance::Entry => (Self::ENTRIES_DELTA, Self::ENTRIES_UPDATES),
+            Balance::Lamports(_) => (Self::LAMPORTS_DELTA, Self::LAMPORTS_UPDATES),
+        }
+    }
+
+    /// Records an update of a balance of the kind with the given model field, with true if the
+    /// total of the balances of the kind is known to be unchanged after it, or it is the first.
+    pub fn record_update(
+        &mut self,
+        location: mir::Location,
+        delta_field: &'static str,
+        span: Span,
+        conserved: bool,
+    ) {
+        self.updates
+            .insert(location, (delta_field, span, conserved));
+    }
+
+    /// Records an exit that returns Ok, although the total of the balances of the kind with the
+    /// given model field may have changed, unless an imbalance has already been found.
+    pub fn record_imbalance(&mut self, delta_field: &'static str, exit_span: Span) {
+        if self.imbalance.is_some() {
+            return;
+        }
+        // The change is made by the last update that leaves the total changed
+        let span = self
+            .updates
+            .values()
+            .filter(|(field, _, conserved)| *field == delta_field && !*conserved)
+            .map(|(_, span, _)| *span)
+            .last();
+        self.imbalance = Some((span.unwrap_or(exit_span), exit_span, delta_field));
+    }
+}
+
+// Hold the borrows of the data of accounts in an entrypoint that are not preceded by a check that
+// the owner of the account is the program, so that the data may come from a fake account that
+// the caller created with the same layout
+#[derive(Default)]
+pub struct OwnerCheckChecker {
+    // True if the function is a program entrypoint, the only kind of function that is checked
+    pub in_entrypoint: bool,
+    // The locals that hold a reference to the data field of an account, or to the RefCell inside
+    // it, by the locals holding the accounts
+    pub data_fields: HashMap<mir::Local, mir::Local>,
+    // The span of the first borrow of the data of an account whose owner is not checked, and the
+    // span of the entrypoint
+    pub unchecked_borrow: Option<(Span, Span)>,
+    // A guard that checks the owner of the account before the first unchecked borrow, if one can
+    // be inserted
+    pub guard: Option<Vec<(Span, String)>>,
+    // The blocks that end with a call of hepha_annotations::solana::require_owner, with the locals
+    // holding the accounts that they check
+    pub owner_guards: Vec<(mir::BasicBlock, mir::Local)>,
+}
+
+impl OwnerCheckChecker {
+    /// Records the locals that are assigned a reference to the data field of an account.
+    pub fn visit_assign<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+    ) {
+        if !self.in_entrypoint || !place.projection.is_empty() {
+            return;
+        }
+        let account = match rvalue {
+            mir::Rvalue::Ref(_, _, source) => {
+                match solana_compat::projected_account_field(tcx, mir, source) {
+                    Some((account, AccountField::Data)) => Some(account),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        if let Some(account) = account {
+            self.data_fields.insert(place.local, account);
+        } else {
+            self.data_fields.remove(&place.local);
+        }
+    }
+
+    /// Returns the local holding the account whose data the call borrows, either through an
+    /// accessor of AccountInfo or through the RefCell of its data field.
+    pub fn visit_call<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        callee_name: &str,
+        account_access: Option<(AccountField, rustc_hir::Mutability)>,
+        args: &[Spanned<mir::Operand<'tcx>>],
+        destination: &mir::Place<'tcx>,
+    ) -> Option<mir::Local> {
+        if !self.in_entrypoint {
+            return None;
+        }
+        let receiver = args.first().and_then(|arg| arg.node.place())?;
+        let data_field = self.data_fields.get(&receiver.local).copied();
+        if callee_name == "deref" && destination.projection.is_empty() {
+            if let Some(account) = data_field {
+                self.data_fields.insert(destination.local, account);
+            }
+            return None;
+        }
+        if !matches!(
+            callee_name,
+            "try_borrow_data" | "try_borrow_mut_data" | "borrow" | "borrow_mut"
+        ) || !matches!(account_access, Some((AccountField::Data, _)))
+        {
+            return None;
+        }
+        data_field.or_else(|| {
+            is_account_reference(tcx, receiver.ty(mir, tcx).ty).then_some(receiver.local)
+        })
+    }
+
+    /// Records a call of hepha_annotations::solana::require_owner at the end of the block, which
+    /// only returns if the account that the local holds is owned by the program.
+    pub fn record_owner_guard(&mut self, block: mir::BasicBlock, account: mir::Local) {
+        if self.in_entrypoint && !self.owner_guards.contains(&(block, account)) {
+            self.owner_guards.push((block, account));
+        }
+    }
+
+    /// True if a call of hepha_annotations::solana::require_owner for the account that the local
+    /// holds precedes the given block on every path.
+    pub fn is_owner_guarded(
+        &self,
+        mir: &mir::Body<'_>,
+        block: mir::BasicBlock,
+        account: mir::Local,
+    ) -> bool {
+        let dominators = mir.basic_blocks.dominators();
+        self.owner_guards
+            .iter()
+            .any(|(guard_block, guarded_account)| {
+                *guarded_account == account
+                    && *guard_block != block
+                    && dominators.dominates(*guard_block, block)
+            })
+    }
+
+    /// Records a borrow of the data of an account, which may come from a fake account unless
+    /// the owner of the account is checked.
+    pub fn record_borrow(
+        &mut self,
+        tcx: TyCtxt<'_>,
+        def_id: DefId,
+        span: Span,
+        owner_is_checked: bool,
+    ) {
+        if !owner_is_checked && self.unchecked_borrow.is_none() {
+            self.unchecked_borrow = Some((span, tcx.def_span(def_id)));
+            self.guard = def_id
+                .as_local()
+                .and_then(|entrypoint| fixes::owner_guard(tcx, entrypoint, span));
+        }
+    }
+}
+
+// Hold the changes to the lamports and data of accounts in an entrypoint that are not preceded by
+// a read of the is_writable flag of the account. The runtime rejects the change if the caller did
+// not pass the account as writable, which fails the transaction far from its cause, or shows that
+// the client and the program disagree about the accounts of the instruction.
+#[derive(Default)]
+pub struct WritableCheckChecker {
+    // True if the function is a program entrypoint, the only kind of function that is checked
+    pub in_entrypoint: bool,
+    // The locals that hold a reference to the lamports or data field of an account, or to the
+    // RefCell inside it, by the locals holding the accounts
+    pub mutable_fields: HashMap<mir::Local, mir::Local>,
+    // The blocks that read the is_writable flag of an account, with the locals holding the accounts
+    pub writable_reads: Vec<(mir::BasicBlock, mir::Local)>,
+    // The span of the first change to an account whose is_writable flag is not read before it, and
+    // the span of the entrypoint
+    pub unchecked_write: Option<(Span, Span)>,
+}
+
+impl WritableCheckChecker {
+    /// Follows accounts and their lamports and data fields through assignments and records the
+    /// reads of the is_writable flags of accounts.
+    pub fn visit_assign<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        provenance: &AmountProvenance,
+        location: mir::Location,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+    ) {
+        if !self.in_entrypoint {
+            return;
+        }
+        let read_places: Vec<mir::Place<'tcx>> = match rvalue {
+            mir::Rvalue::Use(operand) | mir::Rvalue::UnaryOp(_, operand) => {
+                operand.place().into_iter().collect()
+            }
+            mir::Rvalue::BinaryOp(_, box (left, right)) => [left, right]
+                .into_iter()
+                .filter_map(|o| o.place())
+                .collect(),
+            mir::Rvalue::Ref(_, _, source) | mir::Rvalue::CopyForDeref(source) => vec![*source],
+            _ => Vec::new(),
+        };
+        for read_place in read_places {
+            if let Some((account, AccountField::IsWritable)) =
+                solana_compat::projected_account_field(tcx, mir, &read_place)
+            {
+                let read = (location.block, provenance.account_of(account));
+                if !self.writable_reads.contains(&read) {
+                    self.writable_reads.push(read);
+                }
+            }
+        }
+        if !place.projection.is_empty() {
+            return;
+        }
+        self.mutable_fields.remove(&place.local);
+        if let mir::Rvalue::Use(mir::Operand::Copy(source) | mir::Operand::Move(source))
+        | mir::Rvalue::Ref(_, _, source)
+        | mir::Rvalue::CopyForDeref(source) = rvalue
+        {
+            if let Some((account, AccountField::Lamports | AccountField::Data)) =
+                solana_compat::projected_account_field(tcx, mir, source)
+            {
+                let account = provenance.account_of(account);
+                self.mutable_fields.insert(place.local, account);
+            }
+        }
+    }
+
+    /// Returns the local holding the account whose lamports or data the call borrows mutably,
+    /// either through an accessor of AccountInfo or through the RefCell of the field.
+    pub fn visit_call<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        provenance: &AmountProvenance,
+        callee_name: &str,
+        account_access: Option<(AccountField, rustc_hir::Mutability)>,
+        args: &[Spanned<mir::Operand<'tcx>>],
+        destination: &mir::Place<'tcx>,
+    ) -> Option<mir::Local> {
+        if !self.in_entrypoint {
+            return None;
+        }
+        let receiver = args.first().and_then(|arg| arg.node.place())?;
+        let field = self.mutable_fields.get(&receiver.local).copied();
+        if callee_name == "deref" && destination.projection.is_empty() {
+            if let Some(account) = field {
+                self.mutable_fields.insert(destination.local, account);
+            }
+            return None;
+        }
+        if !matches!(
+            account_access,
+            Some((
+                AccountField::Lamports | AccountField::Data,
+                rustc_hir::Mutability::Mut
+            ))
+        ) {
+            return None;
+        }
+        field.or_else(|| {
+            is_account_reference(tcx, receiver.ty(mir, tcx).ty)
+                .then(|| provenance.account_of(receiver.local))
+        })
+    }
+
+    /// Records a change to the lamports or data of the account that the local holds at the end of
+    /// the given block, unless a read of the is_writable flag of the account precedes it on every
+    /// path.
+    pub fn record_write(
+        &mut self,
+        tcx: TyCtxt<'_>,
+        mir: &mir::Body<'_>,
+        def_id: DefId,
+        block: mir::BasicBlock,
+        account: mir::Local,
+        span: Span,
+    ) {
+        if self.unchecked_write.is_some() {
+            return;
+        }
+        let dominators = mir.basic_blocks.dominators();
+        let is_checked = self
+            .writable_reads
+            .iter()
+            .any(|(read_block, read_account)| {
+                *read_account == account && dominators.dominates(*read_block, block)
+            });
+        if !is_checked {
+            self.unchecked_write = Some((span, tcx.def_span(def_id)));
+        }
+    }
+}
+
+// Hold the transfers of lamports between the account parameters of a function and the calls of
+// functions that require two accounts to be different. A transfer that debits one account and
+// credits another by the same amount does nothing if both are the same account, while the rest
+// of the function, such as the update of a map of balances, acts as if the lamports had moved.
+// Such a function requires its callers to pass different accounts. A caller meets the
+// requirement with a guard that returns an error if the keys of the accounts are equal, or, if
+// the accounts are its own parameters and it is not an entrypoint, passes it on to its callers,
+// so that the requirement is judged where the accounts come from.
+#[derive(Default)]
+pub struct DistinctAccountsChecker {
+    // True if the function is a program entrypoint, which cannot pass requirements on
+    pub in_entrypoint: bool,
+    // The accounts whose keys the locals hold or refer to, by the locals
+    pub key_locals: HashMap<mir::Local, mir::Local>,
+    // The comparisons of the keys of two accounts, by the locals that hold their results, with
+    // true if the result is true when the keys differ
+    pub key_comparisons: HashMap<mir::Local, (mir::Local, mir::Local, bool)>,
+    // The accounts whose lamports are debited or credited, with the locals holding the amounts
+    // and true for credits
+    pub transfers: BTreeSet<(mir::Local, mir::Local, bool)>,
+    // The calls of functions that require two accounts to be different, with the blocks and the
+    // spans of the calls, the accounts passed and what the callee requires
+    pub requirements: Vec<(mir::BasicBlock, Span, mir::Local, mir::Local, String)>,
+    // The values of the comparisons of the keys of two accounts that branches are taken on, with
+    // the accounts and true if the value is true when the keys differ
+    pub key_conditions: Vec<(mir::Local, mir::Local, bool, Rc<AbstractValue>)>,
+    // The accounts whose lamports or data are mutably borrowed, in the order of their borrows
+    pub mutably_borrowed: Vec<mir::Local>,
+    // The span of the first mutable borrow of the lamports or data of an account that may be the
+    // same account as another one whose lamports or data are mutably borrowed
+    pub aliased_borrow: Option<Span>,
+}
+
+impl DistinctAccountsChecker {
+    /// Follows accounts and their keys through assignments, and negations of comparisons of keys.
+    pub fn visit_assign<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        provenance: &AmountProvenance,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+    ) {
+        if !place.projection.is_empty() {
+            return;
+        }
+        self.key_locals.remove(&place.local);
+        self.key_comparisons.remove(&place.local);
+        match rvalue {
+            mir::Rvalue::Use(mir::Operand::Copy(source) | mir::Operand::Move(source))
+            | mir::Rvalue::Ref(_, _, source)
+            | mir::Rvalue::CopyForDeref(source) => {
+                if let Some(account) = self.account_of_key(tcx, mir, provenance, source) {
+                    self.key_locals.insert(place.local, account);
+                }
+            }
+            mir::Rvalue::UnaryOp(
+                mir::UnOp::Not,
+                mir::Operand::Copy(operand) | mir::Operand::Move(operand),
+            ) if operand.projection.is_empty() => {
+                if let Some((first, second, when_distinct)) =
+                    self.key_comparisons.get(&operand.local).copied()
+                {
+                    self.key_comparisons
+                        .insert(place.local, (first, second, !when_distinct));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Records the calls of eq and ne that compare the keys of two accounts.
+    pub fn visit_call<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        provenance: &AmountProvenance,
+        item_name: &str,
+        args: &[Spanned<mir::Operand<'tcx>>],
+        destination: &mir::Place<'tcx>,
+    ) {
+        if !destination.projection.is_empty() {
+            return;
+        }
+        self.key_locals.remove(&destination.local);
+        self.key_comparisons.remove(&destination.local);
+        if !matches!(item_name, "eq" | "ne") || args.len() != 2 {
+            return;
+        }
+        let key_of = |arg: &Spanned<mir::Operand<'tcx>>| {
+            let place = arg.node.place()?;
+            self.account_of_key(tcx, mir, provenance, &place)
+        };
+        if let (Some(first), Some(second)) = (key_of(&args[0]), key_of(&args[1])) {
+            if first != second {
+                self.key_comparisons
+                    .insert(destination.local, (first, second, item_name == "ne"));
+            }
+        }
+    }
+
+    /// Records the value of the discriminant of a branch if it is a comparison of the keys of two
+    /// accounts.
+    pub fn record_key_condition(
+        &mut self,
+        discriminant: mir::Place<'_>,
+        value: &Rc<AbstractValue>,
+    ) {
+        if !discriminant.projection.is_empty() {
+            return;
+        }
+        if let Some((first, second, when_distinct)) =
+            self.key_comparisons.get(&discriminant.local).copied()
+        {
+            self.key_conditions
+                .push((first, second, when_distinct, value.clone()));
+        }
+    }
+
+    /// Returns the account whose lamports or data the call mutably borrows, if its receiver is a
+    /// reference to an account.
+    pub fn mutably_borrowed_account<'tcx>(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        provenance: &AmountProvenance,
+        account_access: Option<(AccountField, rustc_hir::Mutability)>,
+        args: &[Spanned<mir::Operand<'tcx>>],
+    ) -> Option<mir::Local> {
+        if !matches!(
+            account_access,
+            Some((
+                AccountField::Lamports | AccountField::Data,
+                rustc_hir::Mutability::Mut
+            ))
+        ) {
+            return None;
+        }
+        let receiver = args.first()?.node.place()?;
+        (receiver.projection.is_empty() && is_account_reference(tcx, receiver.ty(mir, tcx).ty))
+            .then(|| provenance.account_of(receiver.local))
+    }
+
+    /// Returns the recorded values of comparisons of the keys of the two accounts, each with true
+    /// if it is true when the keys differ.
+    pub fn key_conditions_of(
+        &self,
+        first: mir::Local,
+        second: mir::Local,
+    ) -> Vec<(Rc<AbstractValue>, bool)> {
+        self.key_conditions
+            .iter()
+            .filter(|(a, b, _, _)| (*a, *b) == (first, second) || (*a, *b) == (second, first))
+            .map(|(_, _, when_distinct, value)| (value.clone(), *when_distinct))
+            .collect()
+    }
+
+    /// Records a debit or a credit of the lamports of the account held by the given local.
+    pub fn record_transfer(&mut self, account: mir::Local, amount: mir::Local, credit: bool) {
+        self.transfers.insert((account, amount, credit));
+    }
+
+    /// Records the requirements of the function that the given block calls, with the accounts
+    /// that the call passes for the parameters that must be different accounts.
+    pub fn record_call(
+        &mut self,
+        provenance: &AmountProvenance,
+        block: mir::BasicBlock,
+        span: Span,
+        callee: &str,
+        args: &[Spanned<mir::Operand<'_>>],
+        required: &[DistinctAccounts],
+    ) {
+        let account = |ordinal: usize| {
+            let place = args.get(ordinal.checked_sub(1)?)?.node.place()?;
+            place
+                .projection
+                .is_empty()
+                .then(|| provenance.account_of(place.local))
+        };
+        let requirements: Vec<_> = required
+            .iter()
+            .filter_map(|pair| {
+                let requirement = format!(
+                    "`{callee}` requires `{}` and `{}` to be different accounts",
+                    pair.first_name, pair.second_name
+                );
+                Some((
+                    block,
+                    span,
+                    account(pair.first)?,
+                    account(pair.second)?,
+                    requirement,
+                ))
+            })
+            .collect();
+        for requirement in requirements {
+            if !self.requirements.contains(&requirement) {
+                self.requirements.push(requirement);
+            }
+        }
+    }
+
+    /// Returns the pairs of parameters that the callers of the function must pass different
+    /// accounts for, because the function moves lamports between them or passes them on to a
+    /// function that requires them to be different.
+    pub fn required_distinct(&self, mir: &mir::Body<'_>) -> Vec<DistinctAccounts> {
+        let mut required = Vec::new();
+        let mut require = |first: mir::Local, second: mir::Local| {
+            if let Some(pair) = distinct_parameters(mir, first, second) {
+                if !required.contains(&pair) {
+                    required.push(pair);
+                }
+            }
+        };
+        for (debited, amount, credit) in &self.transfers {
+            if *credit {
+                continue;
+            }
+            for (credited, credited_amount, credit) in &self.transfers {
+                if *credit && credited_amount == amount && credited != debited {
+                    require(*debited, *credited);
+                }
+            }
+        }
+        if !self.in_entrypoint {
+            for (block, _, first, second, _) in &self.requirements {
+                if !self.is_checked(mir, *block, *first, *second) {
+                    require(*first, *second);
+                }
+            }
+        }
+        required
+    }
+
+    /// Returns the span of the first call that requires two accounts to be different, although
+    /// their keys are not compared before it and they cannot be required of the callers, and
+    /// what the callee requires.
+    pub fn unchecked_call(&self, mir: &mir::Body<'_>) -> Option<(Span, String)> {
+        self.requirements
+            .iter()
+            .find(|(block, _, first, second, _)| {
+                !self.is_checked(mir, *block, *first, *second)
+                    && (self.in_entrypoint || distinct_parameters(mir, *first, *second).is_none())
+            })
+            .map(|(_, span, _, _, requirement)| (*span, requirement.clone()))
+    }
+
+    // True if a guard that returns an error when the keys of the two accounts are equal
+    // precedes the given block on every path
+    fn is_checked(
+        &self,
+        mir: &mir::Body<'_>,
+        block: mir::BasicBlock,
+        first: mir::Local,
+        second: mir::Local,
+    ) -> bool {
+        if first == second {
+            return false;
+        }
+        let dominators = mir.basic_blocks.dominators();
+        find_error_guards(mir).iter().any(|guard| {
+            guard.block != block
+                && dominators.dominates(guard.block, block)
+                && self.key_comparisons.get(&guard.condition).is_some_and(
+                    |(a, b, when_distinct)| {
+                        ((*a, *b) == (first, second) || (*a, *b) == (second, first))
+                            && guard.passes_when == *when_distinct
+                    },
+                )
+        })
+    }
+
+    // Returns the account whose key the place reads or refers to, as in `account.key`,
+    // `*account.key` or a dereference of a local that holds one
+    fn account_of_key<'tcx>(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        provenance: &AmountProvenance,
+        place: &mir::Place<'tcx>,
+    ) -> Option<mir::Local> {
+        let mut length = place.projection.len();
+        while length > 0 && matches!(place.projection[length - 1], mir::ProjectionElem::Deref) {
+            length -= 1;
+        }
+        if length == 0 {
+            return self.key_locals.get(&place.local).copied();
+        }
+        let base = mir::Place {
+            local: place.local,
+            projection: tcx.mk_place_elems(&place.projection[..length]),
+        };
+        match solana_compat::projected_account_field(tcx, mir, &base) {
+            Some((account, AccountField::Key)) => Some(provenance.account_of(account)),
+            _ => None,
+        }
+    }
+}
+
+// Returns the pair of the given accounts if both are parameters of the function that hold an
+// account, ordered by their ordinals
+fn distinct_parameters(
+    mir: &mir::Body<'_>,
+    first: mir::Local,
+    second: mir::Local,
+) -> Option<DistinctAccounts> {
+    let is_parameter = |local: mir::Local| (1..=mir.arg_count).contains(&local.as_usize());
+    if first == second || !is_parameter(first) || !is_parameter(second) {
+        return None;
+    }
+    let (first, second) = if first < second {
+        (first, second)
+    } else {
+        (second, first)
+    };
+    let name = |local: mir::Local| debug_name(mir, local).unwrap_or_else(|| format!("{local:?}"));
+    Some(DistinctAccounts {
+        first: first.as_usize(),
+        second: second.as_usize(),
+        first_name: name(first),
+        second_name: name(second),
+    })
+}
+
+// The words of the names of values decoded from instruction data that identify an order, a claim
+// or another request that must be acted on only once
+const IDENTIFIER_WORDS: [&str; 4] = ["id", "nonce", "seq", "sequence"];
+
+// Hold the effects of an entrypoint that act on an identifier from its instruction data without a
+// guard that looks at a processed marker or at a stored identifier before them, which allows the
+// same instruction to be replayed. This is a heuristic: the names of the locals tell which values
+// are identifiers, and the guards must be in the entrypoint itself.
+#[derive(Default)]
+pub struct ReplayProtectionChecker {
+    // The name of the identifier and the span of the first effect that is not protected
+    pub unprotected_effect: Option<(String, Span)>,
+}
+
+impl ReplayProtectionChecker {
+    pub fn new<'tcx>(
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        provenance: &AmountProvenance,
+    ) -> ReplayProtectionChecker {
+        let mut checker = ReplayProtectionChecker::default();
+        if !provenance.in_entrypoint {
+            return checker;
+        }
+        let Some(identifier) = mir.var_debug_info.iter().find_map(|info| {
+            let mir::VarDebugInfoContents::Place(place) = info.value else {
+                return None;
+            };
+            let is_identifier = info
+                .name
+                .as_str()
+                .to_lowercase()
+                .split('_')
+                .any(|word| IDENTIFIER_WORDS.contains(&word));
+            (is_identifier
+                && place.projection.is_empty()
+                && provenance.sources.get(&place.local) == Some(&AmountSource::InstructionData))
+            .then(|| info.name.to_string())
+        }) else {
+            return checker;
+        };
+        let mut lamports_locals = HashSet::new();
+        let mut markers = HashSet::new();
+        let mut comparisons = HashSet::new();
+        let mut effects = Vec::new();
+        for (block, data) in mir.basic_blocks.iter_enumerated() {
+            if data.is_cleanup {
+                continue;
+            }
+            for statement in &data.statements {
+                let mir::StatementKind::Assign(box (place, rvalue)) = &statement.kind else {
+                    continue;
+                };
+                if !place.projection.is_empty() {
+                    continue;
+                }
+                match rvalue {
+                    mir::Rvalue::Ref(_, _, source) => {
+                        if let Some((_, AccountField::Lamports)) =
+                            solana_compat::projected_account_field(tcx, mir, source)
+                        {
+                            lamports_locals.insert(place.local);
+                        }
+                    }
+                    mir::Rvalue::Use(mir::Operand::Copy(source) | mir::Operand::Move(source)) => {
+                        let is_data_field = source
+                            .projection
+                            .iter()
+                            .any(|elem| matches!(elem, mir::ProjectionElem::Field(..)));
+                        if is_data_field
+                            && mir.local_decls[place.local].ty.is_bool()
+                            && matches!(
+                                provenance.sources.get(&source.local),
+                                Some(AmountSource::AccountData(_))
+                            )
+                        {
+                            markers.insert(place.local);
+                        }
+                    }
+                    mir::Rvalue::UnaryOp(mir::UnOp::Not, mir::Operand::Copy(source))
+                    | mir::Rvalue::UnaryOp(mir::UnOp::Not, mir::Operand::Move(source)) => {
+                        if markers.contains(&source.local) || comparisons.contains(&source.local) {
+                            markers.insert(place.local);
+                        }
+                    }
+                    mir::Rvalue::BinaryOp(
+                        mir::BinOp::Eq
+                        | mir::BinOp::Ne
+                        | mir::BinOp::Lt
+                        | mir::BinOp::Le
+                        | mir::BinOp::Gt
+                        | mir::BinOp::Ge,
+                        box (left, right),
+                    ) => {
+                        let source_of = |operand: &mir::Operand<'_>| {
+                            operand
+                                .place()
+                                .and_then(|place| provenance.sources.get(&place.local).copied())
+                        };
+                        if compares_identifier(source_of(left), source_of(right)) {
+                            comparisons.insert(place.local);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let mir::TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } = &data.terminator().kind
+            else {
+                continue;
+            };
+            let Some((callee_def_id, _)) = func.const_fn_def() else {
+                continue;
+            };
+            let arg_local = |i: usize| {
+                args.get(i)
+                    .and_then(|arg| arg.node.place())
+                    .map(|place| place.local)
+            };
+            let callee_name = tcx.opt_item_name(callee_def_id);
+            let callee_name = callee_name.as_ref().map_or("", |name| name.as_str());
+            let borrows_lamports = solana_compat::accessed_field(tcx, callee_def_id)
+                == Some((AccountField::Lamports, rustc_hir::Mutability::Mut))
+                || (callee_name == "borrow_mut"
+                    && arg_local(0).is_some_and(|local| lamports_locals.contains(&local)));
+            if borrows_lamports || matches!(callee_name, "invoke" | "invoke_signed") {
+                effects.push((block, data.terminator().source_info.span));
+            } else if arg_local(0).is_some_and(|local| lamports_locals.contains(&local)) {
+                lamports_locals.insert(destination.local);
+            } else if matches!(callee_name, "eq" | "ne" | "lt" | "le" | "gt" | "ge") {
+                let source_of = |i: usize| {
+                    arg_local(i).and_then(|local| provenance.sources.get(&local).copied())
+                };
+                if compares_identifier(source_of(0), source_of(1)) {
+                    comparisons.insert(destination.local);
+                }
+            }
+        }
+        let guards: Vec<ErrorGuard> = find_error_guards(mir)
+            .into_iter()
+            .filter(|guard| {
+                markers.contains(&guard.condition) || comparisons.contains(&guard.condition)
+            })
+            .collect();
+        let dominators = mir.basic_blocks.dominators();
+        checker.unprotected_effect = effects
+            .into_iter()
+            .find(|(block, _)| {
+                !guards
+                    .iter()
+                    .any(|guard| dominators.dominates(guard.block, *block))
+            })
+            .map(|(_, span)| (identifier, span));
+        checker
+    }
+}
+
+// The instructions of the system and token programs that move lamports or tokens out of an
+// account, which the signer of the instruction authorizes
+const VALUE_TRANSFER_INSTRUCTIONS: [&str; 6] = [
+    "transfer",
+    "transfer_checked",
+    "transfer_with_seed",
+    "burn",
+    "burn_checked",
+    "close_account",
+];
+
+// The functions that derive a program derived address from seeds and a program id
+const ADDRESS_DERIVATIONS: [&str; 3] = [
+    "find_program_address",
+    "try_find_program_address",
+    "create_program_address",
+];
+
+// Where a seed of a program derived address comes from
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SeedSource {
+    // A constant of the program, such as a byte string
+    Constant,
+    // The id of the program, the first parameter of the entrypoint
+    ProgramId,
+    // The key of the account held by the local
+    AccountKey(mir::Local),
+    // The bump seed found by the derivation of an address with the given index
+    Bump(usize),
+    // A value that is read from the instruction data or from the data of an account
+    Data(AmountSource),
+}
+
+// Hold the calls of invoke_signed by an entrypoint that sign for a program derived address whose
+// seeds the caller controls, so that the caller chooses which address of the program signs, and
+// the calls that sign for an address that the entrypoint derives but never compares with the key
+// of an account that it passes to the invoked program. Only the seeds that are built in the
+// entrypoint itself are classified.
+#[derive(Default)]
+pub struct SignerSeedsChecker {
+    // The span of the first call of invoke_signed that moves lamports or tokens with the signature
+    // of an address whose seeds the caller controls, with the span of the seeds and a description
+    // of the seed that the caller controls
+    pub caller_chosen_signer: Option<(Span, Span, String)>,
+    // The span of the first call of invoke_signed that signs for a derived address that no
+    // account passed to the call is checked to have, with the span of the derivation
+    pub unmatched_signer: Option<(Span, Span)>,
+}
+
+impl SignerSeedsChecker {
+    pub fn new<'tcx>(
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        provenance: &AmountProvenance,
+    ) -> SignerSeedsChecker {
+        let mut checker = SignerSeedsChecker::default();
+        if !provenance.in_entrypoint {
+            return checker;
+        }
+        let mut flow = SeedFlow::default();
+        flow.sources
+            .insert(mir::Local::from_usize(1), SeedSource::ProgramId);
+        let mut signatures = Vec::new();
+        for block in mir.basic_blocks.reverse_postorder() {
+            let data = &mir.basic_blocks[*block];
+            if data.is_cleanup {
+                continue;
+            }
+            for statement in &data.statements {
+                if let mir::StatementKind::Assign(box (place, rvalue)) = &statement.kind {
+                    let span = statement.source_info.span;
+                    flow.visit_assign(tcx, mir, provenance, place, rvalue, span);
+                }
+            }
+            let mir::TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } = &data.terminator().kind
+            else {
+                continue;
+            };
+            let Some((callee_def_id, _)) = func.const_fn_def() else {
+                continue;
+            };
+            let span = data.terminator().source_info.span;
+            let callee_name = tcx.item_name(callee_def_id);
+            if matches!(
+                callee_name.as_str(),
+                "invoke_signed" | "invoke_signed_unchecked"
+            ) {
+                let arg_local = |i: usize| {
+                    args.get(i)
+                        .and_then(|arg| arg.node.place())
+                        .map(|place| place.local)
+                };
+                signatures.push((span, arg_local(0), arg_local(1), arg_local(2)));
+            }
+            flow.visit_call(tcx, mir, provenance, callee_def_id, args, destination, span);
+        }
+        for (span, instruction, account_infos, signers_seeds) in signatures {
+            let Some(seed_lists) = signers_seeds.and_then(|local| flow.seed_lists.get(&local))
+            else {
+                continue;
+            };
+            let moves_value =
+                instruction.is_some_and(|local| flow.transfer_instructions.contains(&local));
+            let accounts = account_infos.and_then(|local| flow.account_infos.get(&local));
+            for (seeds, seeds_span) in seed_lists {
+                let caller_controlled = seeds.iter().find_map(|seed| match seed {
+                    Some(SeedSource::Data(source)) if provenance.is_caller_controlled(*source) => {
+                        Some(*source)
+                    }
+                    _ => None,
+                });
+                if let Some(source) = caller_controlled.filter(|_| moves_value) {
+                    if checker.caller_chosen_signer.is_none() {
+                        let description = provenance.describe_value(mir, source, "seed");
+                        checker.caller_chosen_signer = Some((span, *seeds_span, description));
+                    }
+                }
+                // The address is only known to be among the accounts if it is compared with the
+                // key of one of them
+                if let (Some(derivation), Some(accounts)) = (flow.derivation_of(seeds), accounts) {
+                    if !accounts
+                        .iter()
+                        .any(|account| flow.compared_addresses.contains(&(derivation, *account)))
+                        && checker.unmatched_signer.is_none()
+                    {
+                        let derivation_span = flow.derivations[derivation].1;
+                        checker.unmatched_signer = Some((span, derivation_span));
+                    }
+                }
+            }
+        }
+        checker
+    }
+}
+
+// The values that the statements of an entrypoint pass on to the signer seeds of its calls of
+// invoke_signed, and to the instructions and the accounts of the calls
+#[derive(Default)]
+struct SeedFlow {
+    // The source of the value of each local that is known to be a seed or a part of one
+    sources: HashMap<mir::Local, SeedSource>,
+    // The lists of seeds, with the spans where they are built, that the locals hold or refer to.
+    // An unknown seed is None.
+    seed_lists: HashMap<mir::Local, Vec<(Vec<Option<SeedSource>>, Span)>>,
+    // The seeds and the span of each derivation of a program derived address
+    derivations: Vec<(Vec<Option<SeedSource>>, Span)>,
+    // The index of the derivation whose result or address each local holds
+    derived_addresses: HashMap<mir::Local, usize>,
+    // The accounts whose AccountInfo values the locals hold or refer to
+    account_infos: HashMap<mir::Local, Vec<mir::Local>>,
+    // The locals that hold instructions that move lamports or tokens, or refer to them
+    transfer_instructions: HashSet<mir::Local>,
+    // The derivations whose address is compared with the key of an account, with the account
+    compared_addresses: HashSet<(usize, mir::Local)>,
+}
+
+impl SeedFlow {
+    fn visit_assign<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        provenance: &AmountProvenance,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+        span: Span,
+    ) {
+        if !place.projection.is_empty() {
+            return;
+        }
+        let local = place.local;
+        match rvalue {
+            mir::Rvalue::Use(mir::Operand::Copy(source) | mir::Operand::Move(source))
+            | mir::Rvalue::Cast(_, mir::Operand::Copy(source) | mir::Operand::Move(source), _)
+            | mir::Rvalue::Ref(_, _, source)
+            | mir::Rvalue::CopyForDeref(source) => {
+                if let Some((account, AccountField::Key)) =
+                    solana_compat::projected_account_field(tcx, mir, source)
+                {
+                    let account = provenance.account_of(account);
+                    self.sources.insert(local, SeedSource::AccountKey(account));
+                    return;
+                }
+                if let Some(derivation) = self.derived_addresses.get(&source.local).copied() {
+                    // The second field of the result of find_program_address is the bump seed
+                    let is_tuple =
+                        matches!(mir.local_decls[source.local].ty.kind(), TyKind::Tuple(..));
+                    let is_bump = is_tuple
+                        && matches!(source.projection.last(), Some(mir::ProjectionElem::Field(index, _)) if index.as_usize() == 1);
+                    if is_bump {
+                        self.sources.insert(local, SeedSource::Bump(derivation));
+                    } else {
+                        self.derived_addresses.insert(local, derivation);
+                    }
+                    return;
+                }
+                if !source
+                    .projection
+                    .iter()
+                    .all(|elem| matches!(elem, mir::ProjectionElem::Deref))
+                {
+                    return;
+                }
+                if let Some(seed) = self.sources.get(&source.local).copied() {
+                    self.sources.insert(local, seed);
+                }
+                if let Some(seed_lists) = self.seed_lists.get(&source.local).cloned() {
+                    self.seed_lists.insert(local, seed_lists);
+                }
+                if let Some(accounts) = self.account_infos.get(&source.local).cloned() {
+                    self.account_infos.insert(local, accounts);
+                }
+                if self.transfer_instructions.contains(&source.local) {
+                    self.transfer_instructions.insert(local);
+                }
+            }
+            mir::Rvalue::Aggregate(box mir::AggregateKind::Array(elem_ty), operands) => {
+                let nested = operands.iter().any(|operand| {
+                    operand
+                        .place()
+                        .is_some_and(|place| self.seed_lists.contains_key(&place.local))
+                });
+                if nested {
+                    // A list of the seeds of several signers. A list that is a constant has been
+                    // promoted, so all of its seeds are constants.
+                    let seed_lists: Vec<_> = operands
+                        .iter()
+                        .flat_map(|operand| match operand.place() {
+                            Some(place) => self
+                                .seed_lists
+                                .get(&place.local)
+                                .cloned()
+                                .unwrap_or_default(),
+                            None => vec![(vec![Some(SeedSource::Constant)], span)],
+                        })
+                        .collect();
+                    self.seed_lists.insert(local, seed_lists);
+                } else if is_byte_slice_reference(*elem_ty) {
+                    let seeds: Vec<_> = operands
+                        .iter()
+                        .map(|operand| self.source_of(tcx, mir, provenance, operand))
+                        .collect();
+                    self.seed_lists.insert(local, vec![(seeds, span)]);
+                } else {
+                    let accounts: Vec<mir::Local> = operands
+                        .iter()
+                        .filter_map(|operand| self.account_infos.get(&operand.place()?.local))
+                        .flatten()
+                        .copied()
+                        .collect();
+                    if !accounts.is_empty() {
+                        self.account_infos.insert(local, accounts);
+                    } else if let Some(seed) = self.join(tcx, mir, provenance, operands.iter()) {
+                        self.sources.insert(local, seed);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn visit_call<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        provenance: &AmountProvenance,
+        callee_def_id: DefId,
+        args: &[Spanned<mir::Operand<'tcx>>],
+        destination: &mir::Place<'tcx>,
+        span: Span,
+    ) {
+        if !destination.projection.is_empty() {
+            return;
+        }
+        let arg_local = |i: usize| {
+            args.get(i)
+                .and_then(|arg| arg.node.place())
+                .map(|place| place.local)
+        };
+        let callee_name = tcx.item_name(callee_def_id);
+        let callee_name = callee_name.as_str();
+        if ADDRESS_DERIVATIONS.contains(&callee_name) {
+            let seeds = arg_local(0)
+                .and_then(|seeds| self.seed_lists.get(&seeds))
+                .and_then(|seed_lists| seed_lists.first())
+                .map(|(seeds, _)| seeds.clone())
+                .unwrap_or_default();
+            self.derivations.push((seeds, span));
+            self.derived_addresses
+                .insert(destination.local, self.derivations.len() - 1);
+            return;
+        }
+        if VALUE_TRANSFER_INSTRUCTIONS.contains(&callee_name) {
+            self.transfer_instructions.insert(destination.local);
+            return;
+        }
+        if matches!(callee_name, "eq" | "ne") {
+            if let (Some(left), Some(right)) = (arg_local(0), arg_local(1)) {
+                for (address, key) in [(left, right), (right, left)] {
+                    if let (Some(derivation), Some(SeedSource::AccountKey(account))) =
+                        (self.derived_addresses.get(&address), self.sources.get(&key))
+                    {
+                        self.compared_addresses.insert((*derivation, *account));
+                    }
+                }
+            }
+            return;
+        }
+        let Some(first) = arg_local(0) else {
+            return;
+        };
+        if callee_name == "clone" && is_account_reference(tcx, mir.local_decls[first].ty) {
+            let account = provenance.account_of(first);
+            self.account_infos.insert(destination.local, vec![account]);
+            return;
+        }
+        // The results of ?, unwrap and the like hold what their argument holds
+        if let Some(derivation) = self.derived_addresses.get(&first).copied() {
+            self.derived_addresses.insert(destination.local, derivation);
+        }
+        if self.transfer_instructions.contains(&first) {
+            self.transfer_instructions.insert(destination.local);
+        }
+        if let Some(seed) = self.join(tcx, mir, provenance, args.iter().map(|arg| &arg.node)) {
+            self.sources.insert(destination.local, seed);
+        }
+    }
+
+    // Returns the index of the derivation of the address that the given seeds sign for, which is
+    // the derivation that found their bump seed, or else a derivation from the same seeds
+    fn derivation_of(&self, seeds: &[Option<SeedSource>]) -> Option<usize> {
+        seeds
+            .iter()
+            .find_map(|seed| match seed {
+                Some(SeedSource::Bump(derivation)) => Some(*derivation),
+                _ => None,
+            })
+            .or_else(|| {
+                self.derivations.iter().position(|(derivation_seeds, _)| {
+                    !derivation_seeds.is_empty()
+                        && derivation_seeds.iter().all(Option::is_some)
+                        && seeds.starts_with(derivation_seeds)
+                })
+            })
+    }
+
+    fn source_of<'tcx>(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        provenance: &AmountProvenance,
+        operand: &mir::Operand<'tcx>,
+    ) -> Option<SeedSource> {
+        let place = match operand {
+            mir::Operand::Constant(..) => return Some(SeedSource::Constant),
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => place,
+        };
+        if let Some((account, AccountField::Key)) =
+            solana_compat::projected_account_field(tcx, mir, place)
+        {
+            return Some(SeedSource::AccountKey(provenance.account_of(account)));
+        }
+        self.sources.get(&place.local).copied().or_else(|| {
+            match provenance.sources.get(&place.local)? {
+                AmountSource::Constant => Some(SeedSource::Constant),
+                source => Some(SeedSource::Data(*source)),
+            }
+        })
+    }
+
+    // Returns the source of a value that is computed from the given operands, if the sources of
+    // all of them are known. Constants do not change the source of the other operands.
+    fn join<'a, 'tcx: 'a>(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        provenance: &AmountProvenance,
+        operands: impl Iterator<Item = &'a mir::Operand<'tcx>>,
+    ) -> Option<SeedSource> {
+        let mut result = None;
+        for operand in operands {
+            let source = self.source_of(tcx, mir, provenance, operand)?;
+            result = match (result, source) {
+                (None, source) | (Some(SeedSource::Constant), source) => Some(source),
+                (Some(result), SeedSource::Constant) => Some(result),
+                (Some(result), source) if result == source => Some(result),
+                // The data that the caller controls is what matters about a mixed value
+                (Some(SeedSource::Data(data)), _) | (_, SeedSource::Data(data)) => {
+                    Some(SeedSource::Data(data))
+                }
+                _ => Some(SeedSource::Data(AmountSource::Computed)),
+            };
+        }
+        result
+    }
+}
+
+// Returns the first local to hold each account, by the locals that hold copies or reborrows of it
+fn find_account_aliases<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mir: &mir::Body<'tcx>,
+) -> HashMap<mir::Local, mir::Local> {
+    let mut aliases: HashMap<mir::Local, mir::Local> = HashMap::new();
+    for _ in 0..MAX_PROVENANCE_PASSES {
+        let known_aliases = aliases.clone();
+        for data in mir.basic_blocks.iter() {
+            for statement in &data.statements {
+                let mir::StatementKind::Assign(box (place, rvalue)) = &statement.kind else {
+                    continue;
+                };
+                let (mir::Rvalue::Use(mir::Operand::Copy(source) | mir::Operand::Move(source))
+                | mir::Rvalue::Ref(_, _, source)
+                | mir::Rvalue::CopyForDeref(source)) = rvalue
+                else {
+                    continue;
+                };
+                if place.projection.is_empty()
+                    && source
+                        .projection
+                        .iter()
+                        .all(|elem| matches!(elem, mir::ProjectionElem::Deref))
+                    && is_account_reference(tcx, mir.local_decls[source.local].ty)
+                {
+                    let account = aliases.get(&source.local).copied().unwrap_or(source.local);
+                    aliases.insert(place.local, account);
+                }
+            }
+        }
+        if aliases == known_aliases {
+            break;
+        }
+    }
+    aliases
+}
+
+// Check if the type is &[u8], the type of a seed of a program derived address
+fn is_byte_slice_reference(ty: Ty<'_>) -> bool {
+    matches!(ty.kind(), TyKind::Ref(_, ty, _) if matches!(ty.kind(), TyKind::Slice(elem) if matches!(elem.kind(), TyKind::Uint(rustc_middle::ty::UintTy::U8))))
+}
+
+// Check if a comparison of values of the given sources compares a value from the instruction data
+// with a value stored in the data of an account
+fn compares_identifier(left: Option<AmountSource>, right: Option<AmountSource>) -> bool {
+    matches!(
+        (left, right),
+        (Some(AmountSource::InstructionData), Some(AmountSource::AccountData(_)))
+            | (Some(AmountSource::AccountData(_)), Some(AmountSource::InstructionData))
+    )
+}
+
+// Check if the type is a reference to an AccountInfo value
+fn is_account_reference<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> bool {
+    matches!(ty.kind(), TyKind::Ref(_, ty, _) if matches!(ty.kind(), TyKind::Adt(def, _) if solana_compat::is_account_info(tcx, *def)))
+}
+
+// Check if the function has the signature of a program entrypoint, i.e. it takes the program id,
+// the accounts and the instruction data
+pub fn is_entrypoint<'tcx>(tcx: TyCtxt<'tcx>, mir: &mir::Body<'tcx>) -> bool {
+    if mir.arg_count != 3 {
+        return false;
+    }
+    let arg_types: Vec<Ty<'tcx>> = (1..=3)
+        .map(|i| mir.local_decls[mir::Local::from_usize(i)].ty)
+        .collect();
+    has_entrypoint_signature(tcx, &arg_types)
+}
+
+// Returns the program id parameter of an entrypoint, the reference to the Pubkey of the program
+// that comes before the accounts and the instruction data, or None if the body is not an entrypoint
+pub fn entrypoint_program_id<'tcx>(tcx: TyCtxt<'tcx>, mir: &mir::Body<'tcx>) -> Option<mir::Local> {
+    if !is_entrypoint(tcx, mir) {
+        return None;
+    }
+    mir.args_iter().next()
+}
+
+// Check if the crate is a Solana program, i.e. one of its functions has the signature of a program
+// entrypoint
+pub fn is_solana_program(tcx: TyCtxt<'_>) -> bool {
+    tcx.hir().body_owners().any(|owner| {
+        let def_id = owner.to_def_id();
+        matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+            && has_entrypoint_signature(
+                tcx,
+                tcx.fn_sig(def_id)
+                    .instantiate_identity()
+                    .skip_binder()
+                    .inputs(),
+            )
+    })
+}
+
+// Returns the instruction handlers of the #[program] module of an Anchor program, i.e. the
+// functions that take a Context of the accounts of an instruction and that share their names
+// with the dispatch functions that Anchor generates in __private::__global. The dispatch
+// functions deserialize the accounts, check their constraints and call the handlers, which are
+// the entrypoints of the instructions as far as the checkers are concerned.
+pub fn anchor_handlers(tcx: TyCtxt<'_>) -> HashSet<DefId> {
+    let functions: Vec<DefId> = tcx
+        .hir()
+        .body_owners()
+        .map(|owner| owner.to_def_id())
+        .filter(|def_id| matches!(tcx.def_kind(*def_id), DefKind::Fn | DefKind::AssocFn))
+        .collect();
+    let dispatched: HashSet<rustc_span::Symbol> = functions
+        .iter()
+        .filter(|def_id| tcx.def_path_str(**def_id).contains("__private::__global::"))
+        .map(|def_id| tcx.item_name(*def_id))
+        .collect();
+    if dispatched.is_empty() {
+        return HashSet::new();
+    }
+    functions
+        .into_iter()
+        .filter(|def_id| dispatched.contains(&tcx.item_name(*def_id)))
+        .filter(|def_id| {
+            let sig = tcx.fn_sig(*def_id).instantiate_identity().skip_binder();
+            sig.inputs()
+                .first()
+                .is_some_and(|ty| solana_compat::anchor_context(tcx, *ty).is_some())
+        })
+        .collect()
+}
+
+// Check if the types of the parameters of a function are those of a program entrypoint
+fn has_entrypoint_signature<'tcx>(tcx: TyCtxt<'tcx>, arg_types: &[Ty<'tcx>]) -> bool {
+    let [program_id, accounts, instruction_data] = arg_types else {
+        return false;
+    };
+    let is_pubkey = match program_id.kind() {
+        TyKind::Ref(_, ty, _) => {
+            matches!(ty.kind(), TyKind::Adt(def, _) if tcx.def_path_str(def.did()).ends_with("Pubkey"))
+        }
+        _ => false,
+    };
+    let is_accounts = match accounts.kind() {
+        TyKind::Ref(_, ty, _) => {
+            matches!(ty.kind(), TyKind::Slice(..)) && is_account_info_type(tcx, *ty)
+        }
+        _ => false,
+    };
+    let is_instruction_data = match instruction_data.kind() {
+        TyKind::Ref(_, ty, _) => matches!(ty.kind(), TyKind::Slice(elem) if matches!(elem.kind(), TyKind::Uint(rustc_middle::ty::UintTy::U8))),
+        _ => false,
+    };
+    is_pubkey && is_accounts && is_instruction_data
+}
+
+// True if the type is an account, or a reference to one.
+fn is_single_account_type<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> bool {
+    matches!(
+        ty.peel_refs().kind(),
+        TyKind::Adt(def, _) if solana_compat::is_account_info(tcx, *def)
+    )
+}
+
+// Check if the type is, or refers to, a collection of or an iterator over AccountInfo values
+pub fn is_account_info_type<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> bool {
+    ty.walk().any(|arg| {
+        if let GenericArgKind::Type(ty) = arg.unpack() {
+            if let TyKind::Adt(def, _) = ty.kind() {
+                return solana_compat::is_account_info(tcx, *def);
+            }
+        }
+        false
+    })
+}
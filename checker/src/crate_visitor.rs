@@ -9,28 +9,47 @@
 
 use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{Debug, Formatter, Result};
 use std::rc::Rc;
-use std::time::Instant;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use log::*;
 use log_derive::{logfn, logfn_inputs};
+use petgraph::algo::tarjan_scc;
+use petgraph::Graph;
 
 use hepha_annotations::*;
-use rustc_errors::Diag;
-use rustc_hir::def_id::{DefId, DefIndex};
+use rustc_data_structures::sync::{par_map, IntoDynSyncSend};
+use rustc_errors::{Diag, DiagInner, Level};
+use rustc_hir::def_id::{DefId, DefIndex, LocalDefId, LOCAL_CRATE};
+use rustc_hir::intravisit::{self, Visitor};
+use rustc_hir::{ConstContext, Expr, ExprKind};
 use rustc_middle::mir;
-use rustc_middle::ty::{GenericArgsRef, TyCtxt};
+use rustc_middle::ty::{GenericArgsRef, TyCtxt, TypeckResults};
 use rustc_session::Session;
+use rustc_span::Span;
 
 use crate::body_visitor::BodyVisitor;
+use crate::call_states::CallStates;
 use crate::call_graph::CallGraph;
+use crate::cancellation::CancellationToken;
+use crate::checker_registry::{DiagnosticOrigin, Severity};
 use crate::constant_domain::ConstantValueCache;
+use crate::contract_errors;
+use crate::diagnostic_export::{Baseline, ExportedDiagnostic};
 use crate::expected_errors;
+use crate::fact_export::Facts;
+use crate::fixes;
+use crate::foreign_models::ForeignModelRegistry;
+use crate::incremental::{self, AnalysisRecord, CachedDiagnostic, IncrementalAnalysis};
 use crate::known_names::KnownNamesCache;
-use crate::options::Options;
-use crate::summaries::SummaryCache;
+use crate::options::{CallGraphFormat, Options, OutputFormat};
+use crate::path_constraints_report::PathConstraintsReport;
+use crate::run_summary::RunSummary;
+use crate::summaries::{RecursiveSummary, SummaryCache, SummaryLookupStatistics};
+use crate::summary_view::{SummaryView, SummaryViews};
 use crate::tag_domain::Tag;
 use crate::type_visitor::TypeCache;
 use crate::utils;
@@ -42,21 +61,235 @@ use crate::utils;
 // 'compilation is the lifetime of the call to MiraiCallbacks::after_analysis.
 // 'tcx is the lifetime of the closure call that calls analyze_with_hepha, which calls analyze_some_bodies.
 pub struct CrateVisitor<'compilation, 'tcx> {
-    pub buffered_diagnostics: Vec<Diag<'compilation, ()>>,
+    /// The deserializations of the structs of the crate from the data of accounts, if the crate
+    /// is a Solana program, whose layouts are compared across the functions of the crate.
+    pub account_deserializations: Vec<contract_errors::AccountDeserialization<'tcx>>,
+    /// The instruction handlers of the #[program] module, if the crate is an Anchor program.
+    /// They are analyzed as entrypoints, with the accounts of their Context.
+    pub anchor_handlers: HashSet<DefId>,
+    pub buffered_diagnostics: Vec<(Diag<'compilation, ()>, DiagnosticOrigin)>,
+    /// The states at the calls of the functions chosen with --record-call-states.
+    pub call_states: CallStates,
+    /// Cancelled by Ctrl-C or once the time given by --crate_analysis_timeout has passed. The
+    /// workers of --threads share the token of the crate.
+    pub cancellation: Arc<CancellationToken>,
+    /// The const fns that are analyzed as roots of their own, with true for those that can also
+    /// be called at runtime, either by other crates or by functions of this crate.
+    pub compile_time_roots: HashMap<DefId, bool>,
     pub constant_time_tag_cache: Option<Tag>,
     pub constant_time_tag_not_found: bool,
     pub constant_value_cache: ConstantValueCache<'tcx>,
-    pub diagnostics_for: HashMap<DefId, Vec<Diag<'compilation, ()>>>,
+    /// The diagnostics of the analyzed functions, with what gave rise to them, ordered by function
+    /// so that diagnostics with the same span are emitted in the same order in every run.
+    pub diagnostics_for: BTreeMap<DefId, Vec<(Diag<'compilation, ()>, DiagnosticOrigin)>>,
+    /// The facts that the contract checkers observe, for --export-facts.
+    pub facts: Facts,
     pub file_name: &'compilation str,
+    /// The models of the functions of other crates, from the default registry and --models.
+    pub foreign_models: ForeignModelRegistry,
     pub generic_args_cache: HashMap<DefId, GenericArgsRef<'tcx>>,
+    /// The functions that are analyzed again and the records of the others, with --incremental.
+    pub incremental: Option<IncrementalAnalysis>,
+    /// True if the crate is a Solana program, i.e. one of its functions has the signature of a
+    /// program entrypoint. The instruction data of its entrypoints and the data that it borrows
+    /// from accounts then carry the built-in tag of untrusted input.
+    pub is_solana_program: bool,
     pub known_names_cache: KnownNamesCache,
+    /// The severity of the most severe finding reported for the crate, if any.
+    pub most_severe_finding: Option<Severity>,
     pub options: &'compilation Options,
+    /// True while functions are analyzed again with the semantics of the shipped code, in which
+    /// assume! does nothing.
+    pub shipped_semantics: bool,
+    /// The verification conditions that are proven while assumptions are used, with the
+    /// functions that were being analyzed when they were proven.
+    pub verified_conditions: Vec<(DefId, Span)>,
+    /// The verification conditions that are no longer proven with the semantics of the shipped
+    /// code, so that they rely on an assumption that is not present at runtime.
+    pub assumption_ledger: Vec<(DefId, Span)>,
+    /// True if arithmetic overflow aborts execution, false if it wraps around.
+    pub overflow_checks: bool,
+    pub path_constraints_report: PathConstraintsReport,
+    /// The summaries that stand for the recursive functions whose fixed points are being
+    /// computed, the innermost last.
+    pub recursive_summaries: Vec<RecursiveSummary<'tcx>>,
+    /// The counts of the analyzed functions and of the diagnostics, for the summary of the run.
+    pub run_summary: RunSummary,
     pub session: &'compilation Session,
     pub summary_cache: SummaryCache<'tcx>,
+    /// The views of the summaries of the analyzed functions, for tools that use HEPHA as a library.
+    pub summary_views: SummaryViews,
     pub tcx: TyCtxt<'tcx>,
     pub type_cache: Rc<RefCell<TypeCache<'tcx>>>,
     pub test_run: bool,
     pub call_graph: CallGraph<'tcx>,
+    /// The blocks on which the most time was spent in the functions whose analysis timed out,
+    /// as "function block file:line:column time", for the statistics.
+    pub timed_out_blocks: Vec<String>,
+}
+
+// Collects the functions that the expressions of a body refer to, by path or by method call.
+struct ReferencedFunctions<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    typeck_results: &'tcx TypeckResults<'tcx>,
+    functions: Vec<DefId>,
+}
+
+impl<'tcx> Visitor<'tcx> for ReferencedFunctions<'tcx> {
+    fn visit_expr(&mut self, expression: &'tcx Expr<'tcx>) {
+        let def_id = match &expression.kind {
+            ExprKind::Path(qpath) => self
+                .typeck_results
+                .qpath_res(qpath, expression.hir_id)
+                .opt_def_id(),
+            ExprKind::MethodCall(..) => {
+                self.typeck_results.type_dependent_def_id(expression.hir_id)
+            }
+            _ => None,
+        };
+        if let Some(def_id) = def_id {
+            if self.tcx.def_kind(def_id).is_fn_like() {
+                self.functions.push(def_id);
+            }
+        }
+        intravisit::walk_expr(self, expression);
+    }
+}
+
+// What the workers of --threads start from: the crate that is being compiled, the options, the
+// summary store and the facts about the crate that are found before any of its bodies is
+// analyzed. Unlike a crate visitor, this can be shared by the threads of the compiler.
+struct WorkerSetup<'compilation, 'tcx> {
+    session: &'compilation Session,
+    tcx: TyCtxt<'tcx>,
+    options: IntoDynSyncSend<&'compilation Options>,
+    file_name: &'compilation str,
+    test_run: bool,
+    db: IntoDynSyncSend<sled::Db>,
+    cancellation: Arc<CancellationToken>,
+    is_solana_program: bool,
+    anchor_handlers: HashSet<DefId>,
+    compile_time_roots: HashMap<DefId, bool>,
+    account_deserializations: Vec<contract_errors::AccountDeserialization<'tcx>>,
+}
+
+// What a worker of --threads found in a component of the call graph, in a form that can be sent
+// to the thread of the crate visitor.
+struct ComponentAnalysis {
+    diagnostics_for: BTreeMap<DefId, Vec<(DiagInner, DiagnosticOrigin)>>,
+    summary_views: BTreeMap<String, SummaryView>,
+    run_summary: RunSummary,
+    most_severe_finding: Option<Severity>,
+    timed_out_blocks: Vec<String>,
+    lookup_statistics: SummaryLookupStatistics,
+    deferred_writes: Vec<(String, Vec<u8>)>,
+}
+
+impl WorkerSetup<'_, '_> {
+    // Analyzes the given roots, which make up a component of the call graph, in order, with a
+    // crate visitor of its own.
+    fn analyze_component(&self, roots: Vec<DefId>) -> ComponentAnalysis {
+        let summary_cache = SummaryCache::for_worker(self.db.0.clone());
+        let mut worker = CrateVisitor::with_summary_cache(
+            self.session,
+            self.tcx,
+            self.options.0,
+            self.file_name,
+            summary_cache,
+            self.test_run,
+        );
+        worker.cancellation = self.cancellation.clone();
+        worker.is_solana_program = self.is_solana_program;
+        worker.anchor_handlers = self.anchor_handlers.clone();
+        worker.compile_time_roots = self.compile_time_roots.clone();
+        worker.account_deserializations = self.account_deserializations.clone();
+        for def_id in roots {
+            worker.analyze_body(def_id);
+            if worker.cancellation.is_cancelled() {
+                break;
+            }
+        }
+        // A Diag borrows the diagnostic context of the session, so only what it says is sent
+        let diagnostics_for = std::mem::take(&mut worker.diagnostics_for)
+            .into_iter()
+            .map(|(def_id, diagnostics)| {
+                let diagnostics = diagnostics
+                    .into_iter()
+                    .map(|(diagnostic, origin)| {
+                        let inner = (*diagnostic).clone();
+                        diagnostic.cancel();
+                        (inner, origin)
+                    })
+                    .collect();
+                (def_id, diagnostics)
+            })
+            .collect();
+        ComponentAnalysis {
+            diagnostics_for,
+            summary_views: std::mem::take(&mut worker.summary_views.functions),
+            run_summary: std::mem::take(&mut worker.run_summary),
+            most_severe_finding: worker.most_severe_finding,
+            timed_out_blocks: std::mem::take(&mut worker.timed_out_blocks),
+            lookup_statistics: std::mem::take(&mut worker.summary_cache.statistics),
+            deferred_writes: worker.summary_cache.take_deferred_writes(),
+        }
+    }
+}
+
+// Groups the given roots by the strongly connected components of the call graph of the crate,
+// given by the callees of its bodies, and the components by level, the lowest first. The level of
+// a component is one more than the highest level of the components that it calls, whether these
+// have roots or not, so that the components of a level do not call one another.
+fn components_by_level(
+    callees: &BTreeMap<DefId, Vec<DefId>>,
+    roots: &[DefId],
+) -> Vec<Vec<Vec<DefId>>> {
+    let mut graph: Graph<DefId, ()> = Graph::new();
+    let nodes: HashMap<DefId, _> = callees
+        .keys()
+        .map(|def_id| (*def_id, graph.add_node(*def_id)))
+        .collect();
+    for (caller, callees) in callees {
+        for callee in callees {
+            if let Some(callee_node) = nodes.get(callee) {
+                graph.add_edge(nodes[caller], *callee_node, ());
+            }
+        }
+    }
+    // The components come in post order, so that those that are called come first
+    let mut component_levels: HashMap<DefId, usize> = HashMap::new();
+    let mut levels: Vec<Vec<Vec<DefId>>> = Vec::new();
+    for component in tarjan_scc(&graph) {
+        let members: HashSet<DefId> = component.iter().map(|node| graph[*node]).collect();
+        let level = component
+            .iter()
+            .flat_map(|node| graph.neighbors(*node))
+            .map(|callee| graph[callee])
+            .filter(|callee| !members.contains(callee))
+            .filter_map(|callee| component_levels.get(&callee).map(|level| level + 1))
+            .max()
+            .unwrap_or(0);
+        for def_id in &members {
+            component_levels.insert(*def_id, level);
+        }
+        let mut component_roots: Vec<DefId> = roots
+            .iter()
+            .filter(|root| members.contains(root))
+            .copied()
+            .collect();
+        if component_roots.is_empty() {
+            continue;
+        }
+        component_roots.sort();
+        if levels.len() <= level {
+            levels.resize(level + 1, Vec::new());
+        }
+        levels[level].push(component_roots);
+    }
+    for level in &mut levels {
+        level.sort();
+    }
+    levels
 }
 
 impl Debug for CrateVisitor<'_, '_> {
@@ -65,6 +298,73 @@ impl Debug for CrateVisitor<'_, '_> {
     }
 }
 
+impl<'compilation, 'tcx> CrateVisitor<'compilation, 'tcx> {
+    /// A visitor for the crate that tcx is compiling, which stores the summaries of its functions
+    /// in the directory given by summary_store_path.
+    pub fn new(
+        session: &'compilation Session,
+        tcx: TyCtxt<'tcx>,
+        options: &'compilation Options,
+        file_name: &'compilation str,
+        summary_store_path: String,
+        test_run: bool,
+    ) -> CrateVisitor<'compilation, 'tcx> {
+        let summary_cache = SummaryCache::new(summary_store_path);
+        Self::with_summary_cache(session, tcx, options, file_name, summary_cache, test_run)
+    }
+
+    /// A visitor for the crate that tcx is compiling, which keeps the summaries of its functions
+    /// in the given cache.
+    fn with_summary_cache(
+        session: &'compilation Session,
+        tcx: TyCtxt<'tcx>,
+        options: &'compilation Options,
+        file_name: &'compilation str,
+        summary_cache: SummaryCache<'tcx>,
+        test_run: bool,
+    ) -> CrateVisitor<'compilation, 'tcx> {
+        let crate_analysis_timeout = Duration::from_secs(options.max_analysis_time_for_crate);
+        let foreign_models = ForeignModelRegistry::load(options.models.as_deref())
+            .unwrap_or_else(|e| session.dcx().fatal(e));
+        CrateVisitor {
+            account_deserializations: Vec::new(),
+            anchor_handlers: HashSet::new(),
+            buffered_diagnostics: Vec::new(),
+            call_states: CallStates::default(),
+            cancellation: Arc::new(CancellationToken::new(Some(crate_analysis_timeout))),
+            compile_time_roots: HashMap::new(),
+            constant_time_tag_cache: None,
+            constant_time_tag_not_found: false,
+            constant_value_cache: ConstantValueCache::default(),
+            diagnostics_for: BTreeMap::new(),
+            facts: Facts::default(),
+            file_name,
+            foreign_models,
+            known_names_cache: KnownNamesCache::create_cache_from_language_items(),
+            most_severe_finding: None,
+            options,
+            shipped_semantics: false,
+            verified_conditions: Vec::new(),
+            assumption_ledger: Vec::new(),
+            overflow_checks: session.overflow_checks(),
+            path_constraints_report: PathConstraintsReport::default(),
+            recursive_summaries: Vec::new(),
+            run_summary: RunSummary::default(),
+            session,
+            generic_args_cache: HashMap::new(),
+            incremental: None,
+            is_solana_program: false,
+            summary_cache,
+            summary_views: SummaryViews::default(),
+            tcx,
+            test_run,
+            type_cache: Rc::new(RefCell::new(TypeCache::new())),
+            call_graph: CallGraph::new(options.call_graph_config.to_owned(), tcx),
+            timed_out_blocks: Vec::new(),
+        }
+    }
+}
+
 impl<'compilation> CrateVisitor<'compilation, '_> {
     /// Analyze some of the bodies in the crate that is being compiled.
     #[logfn(TRACE)]
@@ -73,6 +373,15 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
         // Determine the functions we want to analyze.
         let selected_functions = self.get_selected_function_list();
 
+        self.is_solana_program = contract_errors::is_solana_program(self.tcx);
+        self.anchor_handlers = contract_errors::anchor_handlers(self.tcx);
+        if self.is_solana_program {
+            self.account_deserializations = contract_errors::account_deserializations(self.tcx);
+        }
+        if self.options.incremental {
+            self.incremental = Some(self.plan_incremental_analysis());
+        }
+
         // Get the entry function
         let entry_fn_def_id = if let Some((def_id, _)) = self.tcx.entry_fn(()) {
             def_id
@@ -82,6 +391,15 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
 
         // Analyze all functions that are whitelisted or public
         let building_standard_summaries = std::env::var("HEPHA_START_FRESH").is_ok();
+        let called_at_runtime = self.functions_called_from(|context| context.is_none());
+        let called_at_compile_time = self.functions_called_from(|context| {
+            matches!(
+                context,
+                Some(ConstContext::Const { .. } | ConstContext::Static(_))
+            )
+        });
+        // The roots that are analyzed with --threads, once all of them are known
+        let mut roots: Vec<DefId> = Vec::new();
         for local_def_id in self.tcx.hir().body_owners() {
             let def_id = local_def_id.to_def_id();
             let name = utils::summary_key_str(self.tcx, def_id);
@@ -97,19 +415,42 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
                 }
                 info!("analyzing selected function {}", name);
             } else if !building_standard_summaries {
-                if !utils::is_public(def_id, self.tcx) && def_id != entry_fn_def_id {
+                if self.options.adversarial_public_api {
+                    // Only the functions that other crates can call are entry points
+                    if !self
+                        .tcx
+                        .effective_visibilities(())
+                        .is_exported(local_def_id)
+                    {
+                        debug!("skipping function {} as it is not exported", name);
+                        continue;
+                    }
+                } else if !utils::is_public(def_id, self.tcx)
+                    && def_id != entry_fn_def_id
+                    && !called_at_compile_time.contains(&def_id)
+                    && !self.anchor_handlers.contains(&def_id)
+                {
                     debug!("skipping function {} as it is not public", name);
                     continue;
-                } else if self
+                }
+                if self
                     .tcx
                     .generics_of(def_id)
                     .requires_monomorphization(self.tcx)
                 {
                     debug!("skipping function {} as it is generic", name);
                     continue;
-                } else if self.tcx.is_const_fn(def_id) {
-                    debug!("skipping function {} as it is a constant function", name);
-                    continue;
+                } else if self.tcx.is_const_fn(def_id) && !self.options.adversarial_public_api {
+                    if !called_at_compile_time.contains(&def_id) {
+                        debug!("skipping function {} as it is a constant function", name);
+                        continue;
+                    }
+                    // The initializers of constants and statics and array lengths that evaluate
+                    // the function are not analyzed as its callers, so it is a root of its own
+                    let callable_at_runtime =
+                        utils::is_public(def_id, self.tcx) || called_at_runtime.contains(&def_id);
+                    self.compile_time_roots.insert(def_id, callable_at_runtime);
+                    info!("analyzing constant function {}", name);
                 } else if utils::is_higher_order_function(def_id, self.tcx) {
                     debug!(
                         "skipping function {} as it is a higher order function",
@@ -124,13 +465,92 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
             }
 
             self.call_graph.add_croot(def_id);
+            if self.replay_diagnostics(def_id) {
+                info!("reusing the diagnostics of {} as it did not change", name);
+                continue;
+            }
+            if self.options.threads.is_some() {
+                roots.push(def_id);
+                continue;
+            }
             self.analyze_body(def_id);
-            if start_instant.elapsed().as_secs() > self.options.max_analysis_time_for_crate {
-                info!("exceeded total time allowed for crate analysis");
+            if self.cancellation.is_cancelled() {
+                // The diagnostics found so far are still emitted, but the output says that
+                // they are not all of them.
+                info!("the analysis of the crate was cancelled or exceeded the time allowed");
+                self.run_summary.truncated = true;
                 break;
             }
         }
+        if !roots.is_empty() {
+            self.analyze_components(&roots);
+        }
+        if self.options.dual_build && !self.run_summary.truncated {
+            self.analyze_with_shipped_semantics();
+        }
+        self.record_incremental_analysis();
+        self.run_summary.crate_name = self.tcx.crate_name(LOCAL_CRATE).to_string();
+        for (def_id, diagnostics) in self.diagnostics_for.iter() {
+            for (_, origin) in diagnostics {
+                self.run_summary.count_diagnostic(*origin);
+                if matches!(origin, DiagnosticOrigin::Checker(_)) {
+                    self.call_graph.add_flagged(*def_id);
+                }
+            }
+        }
+        self.run_summary.wall_time_ms = start_instant.elapsed().as_millis() as u64;
         self.emit_or_check_diagnostics();
+        self.report_run_summary();
+    }
+
+    /// Prints the summary of the run to stderr, unless --quiet is given or the diagnostics are
+    /// checked against the expectations of a test, and writes it to the file given with
+    /// --stats-json, if any.
+    fn report_run_summary(&self) {
+        if !self.options.quiet && !self.test_run {
+            eprint!("{}", self.run_summary.to_table());
+        }
+        let Some(path) = &self.options.stats_json else {
+            return;
+        };
+        if let Err(e) = self.run_summary.write(std::path::Path::new(path)) {
+            self.session
+                .dcx()
+                .err(format!("could not write the run summary to {path}: {e}"));
+        }
+    }
+
+    /// Returns the functions that are called by the bodies whose const context satisfies the
+    /// given predicate, either directly or through the const fns that these bodies call. The
+    /// bodies of functions that run at runtime have no const context, while the initializers of
+    /// constants and statics and array lengths are evaluated at compile time.
+    fn functions_called_from(
+        &self,
+        predicate: impl Fn(Option<ConstContext>) -> bool,
+    ) -> HashSet<DefId> {
+        let hir = self.tcx.hir();
+        let mut called: HashSet<DefId> = HashSet::new();
+        let mut to_visit: Vec<LocalDefId> = hir
+            .body_owners()
+            .filter(|owner| predicate(hir.body_const_context(*owner)))
+            .collect();
+        while let Some(owner) = to_visit.pop() {
+            let mut visitor = ReferencedFunctions {
+                tcx: self.tcx,
+                typeck_results: self.tcx.typeck(owner),
+                functions: Vec::new(),
+            };
+            visitor.visit_body(hir.body_owned_by(owner));
+            for def_id in visitor.functions {
+                // The const fns that are called run in the same context as their callers
+                if called.insert(def_id) && self.tcx.is_const_fn(def_id) {
+                    if let Some(local_def_id) = def_id.as_local() {
+                        to_visit.push(local_def_id);
+                    }
+                }
+            }
+        }
+        called
     }
 
     /// Use compilation options to determine a list of functions to analyze.
@@ -158,6 +578,11 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
         }
     }
 
+    // Determine whether the function is one of the given set, by display name or summary key.
+    fn included_in_set(&self, set: &HashSet<String>, name: &str, def_id: DefId) -> bool {
+        set.contains(name) || set.contains(&utils::def_id_display_name(self.tcx, def_id))
+    }
+
     // Determine whether this function is included in the analysis.
     #[logfn(TRACE)]
     fn included_in(&self, list: &[String], name: &str, def_id: DefId) -> bool {
@@ -172,8 +597,11 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
     /// and collect any diagnostics into the buffer.
     #[logfn(TRACE)]
     fn analyze_body(&mut self, def_id: DefId) {
-        let mut diagnostics: Vec<Diag<'compilation, ()>> = Vec::new();
+        let mut diagnostics: Vec<(Diag<'compilation, ()>, DiagnosticOrigin)> = Vec::new();
         let mut active_calls_map: HashMap<DefId, u64> = HashMap::new();
+        self.run_summary.functions_analyzed += 1;
+        // The most severe finding of the function alone is kept in its record
+        let most_severe_finding = self.most_severe_finding.take();
         let mut body_visitor = BodyVisitor::new(
             self,
             def_id,
@@ -183,6 +611,7 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
         );
         // Analysis local foreign contracts are not summarized and cached on demand, so we need to do it here.
         let summary = body_visitor.visit_body(&[]);
+        let mir = body_visitor.mir;
         let kind = self.tcx.def_kind(def_id);
         if matches!(kind, rustc_hir::def::DefKind::Static { .. })
             || utils::is_foreign_contract(self.tcx, def_id)
@@ -191,11 +620,357 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
             self.summary_cache
                 .set_summary_for(def_id, self.tcx, summary.clone());
         }
+        let view = SummaryView::new(mir, utils::def_id_display_name(self.tcx, def_id), &summary);
+        self.summary_views
+            .functions
+            .insert(utils::summary_key_str(self.tcx, def_id).to_string(), view);
+        if let Some(incremental) = &mut self.incremental {
+            incremental.analyzed.insert(
+                def_id,
+                (
+                    incremental::preconditions_hash(&summary),
+                    self.most_severe_finding,
+                ),
+            );
+        }
+        self.most_severe_finding = self.most_severe_finding.max(most_severe_finding);
         let old_diags = self.diagnostics_for.insert(def_id, diagnostics);
         // info!("Summary: {:?}, Old_diags: {:?}", summary.clone(), old_diags);
         checked_assume!(old_diags.is_none());
     }
 
+    /// Analyzes the given roots with --threads. The roots are grouped by the strongly connected
+    /// components of the call graph of the crate, which are analyzed bottom-up, a level at a time.
+    /// The components of a level are analyzed on the thread pool of the compiler, each by a crate
+    /// visitor of its own, with its own caches, diagnostics and SMT solvers. What they find is
+    /// merged in the order of the components, so that it does not depend on the number of threads
+    /// or on the order in which the workers finish.
+    fn analyze_components(&mut self, roots: &[DefId]) {
+        let setup = WorkerSetup {
+            session: self.session,
+            tcx: self.tcx,
+            options: IntoDynSyncSend(self.options),
+            file_name: self.file_name,
+            test_run: self.test_run,
+            db: IntoDynSyncSend(self.summary_cache.db().clone()),
+            cancellation: self.cancellation.clone(),
+            is_solana_program: self.is_solana_program,
+            anchor_handlers: self.anchor_handlers.clone(),
+            compile_time_roots: self.compile_time_roots.clone(),
+            account_deserializations: self.account_deserializations.clone(),
+        };
+        for level in components_by_level(&self.callees_of_bodies(), roots) {
+            let analyses: Vec<ComponentAnalysis> =
+                par_map(level, |component| setup.analyze_component(component));
+            for analysis in analyses {
+                self.merge_component_analysis(analysis);
+            }
+            if self.cancellation.is_cancelled() {
+                info!("the analysis of the crate was cancelled or exceeded the time allowed");
+                self.run_summary.truncated = true;
+                break;
+            }
+        }
+    }
+
+    /// Adds what a worker of --threads found in a component of the call graph to what the crate
+    /// visitor found, and writes the summaries that the worker stored to the summary store.
+    fn merge_component_analysis(&mut self, analysis: ComponentAnalysis) {
+        for (def_id, diagnostics) in analysis.diagnostics_for {
+            let diagnostics = diagnostics
+                .into_iter()
+                .map(|(inner, origin)| {
+                    let mut diagnostic = Diag::new(self.session.dcx(), Level::Warning, "");
+                    *diagnostic = inner;
+                    (diagnostic, origin)
+                })
+                .collect();
+            let old_diags = self.diagnostics_for.insert(def_id, diagnostics);
+            checked_assume!(old_diags.is_none());
+        }
+        self.summary_views.functions.extend(analysis.summary_views);
+        self.run_summary.merge(&analysis.run_summary);
+        self.most_severe_finding = self.most_severe_finding.max(analysis.most_severe_finding);
+        self.timed_out_blocks.extend(analysis.timed_out_blocks);
+        self.summary_cache
+            .statistics
+            .merge(&analysis.lookup_statistics);
+        self.summary_cache.write_summaries(analysis.deferred_writes);
+    }
+
+    /// Determines the functions of the crate that changed since the last run, from the file given
+    /// with --changed-functions or else from the hashes of their sources, and the functions that
+    /// must be analyzed again because they call them, directly or through other functions of the
+    /// crate. A function whose record has a diagnostic in a function that changed, or that calls
+    /// a function of another crate whose preconditions changed, is analyzed again as well.
+    fn plan_incremental_analysis(&self) -> IncrementalAnalysis {
+        let mut incremental = IncrementalAnalysis {
+            callees: self.callees_of_bodies(),
+            ..IncrementalAnalysis::default()
+        };
+        let changed_names = self.options.changed_functions.as_ref().map(|path| {
+            match std::fs::read_to_string(path) {
+                Ok(list) => Some(
+                    list.lines()
+                        .map(str::trim)
+                        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                        .map(str::to_string)
+                        .collect::<HashSet<String>>(),
+                ),
+                Err(e) => {
+                    self.session.dcx().err(format!(
+                        "could not read the changed functions from {path}: {e}"
+                    ));
+                    None
+                }
+            }
+        });
+        let is_listed = |def_id: DefId| match &changed_names {
+            Some(Some(names)) => {
+                let name = utils::summary_key_str(self.tcx, def_id);
+                self.included_in_set(names, &name, def_id)
+            }
+            // Every function is taken to have changed if the list cannot be read
+            Some(None) => true,
+            None => false,
+        };
+        let mut changed: Vec<DefId> = Vec::new();
+        let mut callers: HashMap<DefId, Vec<DefId>> = HashMap::new();
+        for (def_id, callees) in &incremental.callees {
+            let key = utils::summary_key_str(self.tcx, *def_id);
+            let fingerprint = incremental::fingerprint(self.tcx, *def_id);
+            let record = self.summary_cache.get_analysis_record(&key);
+            let source_changed = if changed_names.is_some() {
+                is_listed(*def_id)
+            } else {
+                record
+                    .as_ref()
+                    .map_or(true, |r| r.fingerprint != fingerprint)
+            };
+            let preconditions_changed = record.as_ref().is_some_and(|r| {
+                r.callee_preconditions
+                    .iter()
+                    .any(|(callee, preconditions)| {
+                        let current = self.summary_cache.get_analysis_record(callee);
+                        current.and_then(|r| r.preconditions) != *preconditions
+                    })
+            });
+            if source_changed || preconditions_changed {
+                debug!("{} changed since the last run", key);
+                changed.push(*def_id);
+                // The calls of the method of a trait may be calls of this implementation
+                if let Some(trait_item) = self
+                    .tcx
+                    .opt_associated_item(*def_id)
+                    .and_then(|item| item.trait_item_def_id)
+                {
+                    changed.push(trait_item);
+                }
+            }
+            for callee in callees {
+                if !callee.is_local() && is_listed(*callee) {
+                    changed.push(*callee);
+                }
+                callers.entry(*callee).or_default().push(*def_id);
+            }
+            let body_span = self
+                .tcx
+                .hir()
+                .body_owned_by(def_id.expect_local())
+                .value
+                .span;
+            incremental.body_spans.insert(key.to_string(), body_span);
+            incremental.fingerprints.insert(*def_id, fingerprint);
+            if let Some(record) = record {
+                incremental.records.insert(*def_id, record);
+            }
+        }
+        let changed_keys: HashSet<String> = changed
+            .iter()
+            .map(|def_id| utils::summary_key_str(self.tcx, *def_id).to_string())
+            .collect();
+        incremental.dirty = incremental::callers_of(changed, &callers);
+        for (def_id, record) in &incremental.records {
+            if record.refers_to(&changed_keys) {
+                incremental.dirty.insert(*def_id);
+            }
+        }
+        info!(
+            "{} of {} functions changed or call a function that changed",
+            incremental
+                .dirty
+                .iter()
+                .filter(|def_id| incremental.callees.contains_key(def_id))
+                .count(),
+            incremental.callees.len()
+        );
+        incremental
+    }
+
+    /// Returns the functions that the bodies of the crate refer to, by body, which is the call
+    /// graph of the crate before any of it is analyzed.
+    fn callees_of_bodies(&self) -> BTreeMap<DefId, Vec<DefId>> {
+        let hir = self.tcx.hir();
+        hir.body_owners()
+            .map(|owner| {
+                let mut visitor = ReferencedFunctions {
+                    tcx: self.tcx,
+                    typeck_results: self.tcx.typeck(owner),
+                    functions: Vec::new(),
+                };
+                visitor.visit_body(hir.body_owned_by(owner));
+                let mut callees = visitor.functions;
+                callees.sort();
+                callees.dedup();
+                (owner.to_def_id(), callees)
+            })
+            .collect()
+    }
+
+    /// Adds the diagnostics that the record of the function with the given id has to those of
+    /// the crate, if the function did not change and does not call a function that changed.
+    /// Returns false if the function must be analyzed again.
+    fn replay_diagnostics(&mut self, def_id: DefId) -> bool {
+        let Some(incremental) = &self.incremental else {
+            return false;
+        };
+        if incremental.dirty.contains(&def_id) {
+            return false;
+        }
+        let Some(AnalysisRecord {
+            diagnostics: Some(cached_diagnostics),
+            most_severe_finding,
+            ..
+        }) = incremental.records.get(&def_id)
+        else {
+            return false;
+        };
+        let mut diagnostics = Vec::new();
+        for cached_diagnostic in cached_diagnostics {
+            let Some(diagnostic) = cached_diagnostic.replay(self.session, &incremental.body_spans)
+            else {
+                for (diagnostic, _) in diagnostics {
+                    diagnostic.cancel();
+                }
+                return false;
+            };
+            diagnostics.push(diagnostic);
+        }
+        self.most_severe_finding = self.most_severe_finding.max(*most_severe_finding);
+        self.run_summary.functions_reused += 1;
+        self.diagnostics_for.insert(def_id, diagnostics);
+        true
+    }
+
+    /// Updates the records of the functions of the crate in the summary store for the next run.
+    /// The functions that changed, or call a function that changed, but were not analyzed, such
+    /// as those that are not entry points, keep no diagnostics, so that they are analyzed in the
+    /// next run if they are entry points then.
+    fn record_incremental_analysis(&mut self) {
+        let Some(mut incremental) = self.incremental.take() else {
+            return;
+        };
+        if self.run_summary.truncated {
+            // The function that was being analyzed when the analysis was cancelled is incomplete
+            incremental.analyzed.clear();
+        }
+        for (def_id, callees) in &incremental.callees {
+            let fingerprint = incremental.fingerprints.remove(def_id).unwrap_or_default();
+            let record = if let Some((preconditions, most_severe_finding)) =
+                incremental.analyzed.remove(def_id)
+            {
+                let callee_preconditions = callees
+                    .iter()
+                    .filter(|callee| !callee.is_local())
+                    .map(|callee| {
+                        let key = utils::summary_key_str(self.tcx, *callee).to_string();
+                        let preconditions = self
+                            .summary_cache
+                            .get_analysis_record(&key)
+                            .and_then(|r| r.preconditions);
+                        (key, preconditions)
+                    })
+                    .collect();
+                let diagnostics = self
+                    .diagnostics_for
+                    .get(def_id)
+                    .map_or(Some(Vec::new()), |ds| {
+                        ds.iter()
+                            .map(|(diagnostic, origin)| {
+                                CachedDiagnostic::new(self.tcx, diagnostic, *origin)
+                            })
+                            .collect()
+                    });
+                AnalysisRecord {
+                    fingerprint,
+                    preconditions: Some(preconditions),
+                    callee_preconditions,
+                    diagnostics,
+                    most_severe_finding,
+                }
+            } else if let Some(record) = incremental
+                .records
+                .remove(def_id)
+                .filter(|_| !incremental.dirty.contains(def_id))
+            {
+                AnalysisRecord {
+                    fingerprint,
+                    ..record
+                }
+            } else {
+                AnalysisRecord {
+                    fingerprint,
+                    ..AnalysisRecord::default()
+                }
+            };
+            let key = utils::summary_key_str(self.tcx, *def_id);
+            self.summary_cache.set_analysis_record(&key, &record);
+        }
+    }
+
+    /// Analyze the functions with proven verification conditions again, treating assume! as a
+    /// no-op, and add a warning for every condition that is no longer proven. The summaries of
+    /// the functions they call are reused rather than computed again.
+    fn analyze_with_shipped_semantics(&mut self) {
+        let mut def_ids: Vec<DefId> = Vec::new();
+        for (def_id, _) in &self.verified_conditions {
+            if !def_ids.contains(def_id) {
+                def_ids.push(*def_id);
+            }
+        }
+        let most_severe_finding = self.most_severe_finding;
+        self.shipped_semantics = true;
+        for def_id in def_ids {
+            let mut diagnostics: Vec<(Diag<'compilation, ()>, DiagnosticOrigin)> = Vec::new();
+            let mut active_calls_map: HashMap<DefId, u64> = HashMap::new();
+            let mut body_visitor = BodyVisitor::new(
+                self,
+                def_id,
+                &mut diagnostics,
+                &mut active_calls_map,
+                self.type_cache.clone(),
+            );
+            body_visitor.visit_body(&[]);
+            // The other diagnostics of the second analysis repeat those of the first one.
+            for (diagnostic, _) in diagnostics {
+                diagnostic.cancel();
+            }
+        }
+        self.shipped_semantics = false;
+        self.most_severe_finding = most_severe_finding;
+        // The ledger is kept for the properties of the exported findings
+        for (def_id, span) in self.assumption_ledger.clone() {
+            let warning = self.session.dcx().struct_span_warn(
+                span,
+                "verification relies on assumption not present in shipped code",
+            );
+            self.diagnostics_for
+                .entry(def_id)
+                .or_default()
+                .push((warning, DiagnosticOrigin::Verification));
+        }
+    }
+
     /// Extract test functions from the promoted constants of a test runner main function.
     ///
     /// Currently, the #[test] attribute generates code like this:
@@ -243,23 +1018,53 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
         result
     }
 
+    /// Returns the file, line and column of each verification condition of the function with the
+    /// given id that relies on an assumption that is not present in the shipped code.
+    fn exported_assumption_ledger(&self, def_id: DefId) -> Vec<(String, usize, usize)> {
+        let source_map = self.session.source_map();
+        self.assumption_ledger
+            .iter()
+            .filter(|(ledger_def_id, _)| *ledger_def_id == def_id)
+            .map(|(_, span)| {
+                let location = source_map.lookup_char_pos(span.lo());
+                let file = location.file.name.prefer_local().to_string();
+                (file, location.line, location.col.0 + 1)
+            })
+            .collect()
+    }
+
     /// Emit any diagnostics or, if testing, check that they are as expected.
     #[logfn_inputs(TRACE)]
     fn emit_or_check_diagnostics(&mut self) {
         self.session.dcx().reset_err_count();
+        self.write_fixes();
         if self.options.statistics {
             let num_diags = self.diagnostics_for.values().flatten().count();
-            for (_, diags) in self.diagnostics_for.drain() {
-                for db in diags.into_iter() {
+            for (_, diags) in std::mem::take(&mut self.diagnostics_for) {
+                for (db, _) in diags.into_iter() {
                     db.cancel();
                 }
             }
-            print!("{}, analyzed, {}", self.file_name, num_diags);
+            let lookups = &self.summary_cache.statistics;
+            print!(
+                "{}, analyzed, {}, summary lookups, {}, store misses, {}, skipped store lookups, {}, \
+                 store lookup micros, {}, timed out blocks, {}",
+                self.file_name,
+                num_diags,
+                lookups.lookups,
+                lookups.store_misses,
+                lookups.skipped_store_lookups,
+                lookups.store_lookup_time.as_micros(),
+                self.timed_out_blocks.len()
+            );
+            for block in self.timed_out_blocks.iter() {
+                print!(", {block}");
+            }
         } else if self.test_run {
             let mut expected_errors = expected_errors::ExpectedErrors::new(self.file_name);
             let mut diags = vec![];
-            for (_, dbs) in self.diagnostics_for.drain() {
-                for db in dbs.into_iter() {
+            for (_, dbs) in std::mem::take(&mut self.diagnostics_for) {
+                for (db, _) in dbs.into_iter() {
                     diags.push(db);
                 }
             }
@@ -273,12 +1078,24 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
             }
         } else {
             let mut diagnostics = vec![];
-            for (_, dbs) in self.diagnostics_for.drain() {
-                for db in dbs.into_iter() {
-                    diagnostics.push(db);
+            for (def_id, dbs) in std::mem::take(&mut self.diagnostics_for) {
+                let ledger = self.exported_assumption_ledger(def_id);
+                for (db, _) in dbs.into_iter() {
+                    let mut exported = ExportedDiagnostic::new(self.tcx, def_id, &db);
+                    if let Some(ExportedDiagnostic {
+                        properties: Some(properties),
+                        ..
+                    }) = &mut exported
+                    {
+                        properties.add_assumption_ledger(ledger.clone());
+                    }
+                    diagnostics.push((db, exported));
                 }
             }
-            fn compare_diagnostics<'a>(x: &Diag<'a, ()>, y: &Diag<'a, ()>) -> Ordering {
+            fn compare_diagnostics<'a>(
+                (x, _): &(Diag<'a, ()>, Option<ExportedDiagnostic>),
+                (y, _): &(Diag<'a, ()>, Option<ExportedDiagnostic>),
+            ) -> Ordering {
                 if x.span.primary_spans().lt(y.span.primary_spans()) {
                     Ordering::Less
                 } else if x.span.primary_spans().gt(y.span.primary_spans()) {
@@ -290,8 +1107,44 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
 
             info!("Emitted diagnostics: {:?}", diagnostics);
             diagnostics.sort_by(compare_diagnostics);
-            for d in diagnostics.into_iter() {
-                d.emit()
+            if let Some(baseline) = self.load_baseline() {
+                let (new, known) = baseline.filter(diagnostics, |(_, exported)| {
+                    exported.as_ref().map(|e| e.fingerprint.as_str())
+                });
+                for (d, _) in known.into_iter() {
+                    d.cancel();
+                }
+                // The findings that are in the baseline do not make the run fail
+                self.most_severe_finding = new
+                    .iter()
+                    .filter(|(_, exported)| exported.as_ref().is_some_and(|e| e.checker.is_some()))
+                    .filter_map(|(d, _)| Severity::of_level(d.level))
+                    .max();
+                diagnostics = new;
+            }
+            for (d, exported) in diagnostics.into_iter() {
+                match (self.options.diag_format, exported) {
+                    (OutputFormat::Json, Some(mut exported)) => {
+                        exported.truncated = self.run_summary.truncated;
+                        println!("{}", exported.to_json());
+                        d.cancel();
+                    }
+                    _ => d.emit(),
+                }
+            }
+        }
+    }
+
+    // Reads the baseline given with --baseline, if any.
+    fn load_baseline(&self) -> Option<Baseline> {
+        let path = self.options.baseline.as_ref()?;
+        match Baseline::load(std::path::Path::new(path)) {
+            Ok(baseline) => Some(baseline),
+            Err(e) => {
+                self.session
+                    .dcx()
+                    .err(format!("could not read the baseline from {path}: {e}"));
+                None
             }
         }
     }
@@ -306,4 +1159,88 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
             .get_summaries_for_llm(self.tcx, calls_for_def_ids);
         print!("{}", summaries_for_llm.to_json());
     }
+
+    pub fn write_summaries(&self) {
+        let Some(path) = &self.options.emit_summaries else {
+            return;
+        };
+        if let Err(e) = self.summary_views.write(std::path::Path::new(path)) {
+            self.session
+                .dcx()
+                .err(format!("could not write the summaries to {path}: {e}"));
+        }
+    }
+
+    pub fn write_call_graph(&self) {
+        let Some(path) = &self.options.call_graph_out else {
+            return;
+        };
+        let written = std::fs::File::create(path).and_then(|file| {
+            let writer = std::io::BufWriter::new(file);
+            match self.options.call_graph_format {
+                CallGraphFormat::Dot => self.call_graph.to_dot(writer),
+                CallGraphFormat::GraphMl => self.call_graph.to_graphml(writer),
+            }
+        });
+        if let Err(e) = written {
+            self.session
+                .dcx()
+                .err(format!("could not write the call graph to {path}: {e}"));
+        }
+    }
+
+    pub fn write_facts(&self) {
+        let Some(directory) = &self.options.export_facts else {
+            return;
+        };
+        if let Err(e) = self.facts.write(std::path::Path::new(directory)) {
+            self.session
+                .dcx()
+                .err(format!("could not write the facts to {directory}: {e}"));
+        }
+    }
+
+    pub fn write_call_states(&self) {
+        let Some(path) = &self.options.emit_call_states else {
+            return;
+        };
+        if let Err(e) = self.call_states.write(std::path::Path::new(path)) {
+            self.session
+                .dcx()
+                .err(format!("could not write the call states to {path}: {e}"));
+        }
+    }
+
+    fn write_fixes(&self) {
+        let Some(path) = &self.options.emit_fixes else {
+            return;
+        };
+        let source_map = self.session.source_map();
+        let fixes = self
+            .diagnostics_for
+            .values()
+            .flatten()
+            .flat_map(|(diagnostic, _)| fixes::machine_applicable_fixes(diagnostic, source_map))
+            .collect();
+        if let Err(e) = fixes::write_patch(std::path::Path::new(path), fixes) {
+            self.session
+                .dcx()
+                .err(format!("could not write the fixes to {path}: {e}"));
+        }
+    }
+
+    pub fn write_path_constraints_report(&self) {
+        let Some(directory) = &self.options.path_constraints_report else {
+            return;
+        };
+        let crate_name = self.tcx.crate_name(LOCAL_CRATE);
+        if let Err(e) = self
+            .path_constraints_report
+            .write(std::path::Path::new(directory), crate_name.as_str())
+        {
+            self.session.dcx().err(format!(
+                "could not write the path constraints report to {directory}: {e}"
+            ));
+        }
+    }
 }
@@ -0,0 +1,715 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString};
+use std::fmt::{Debug, Formatter, Result};
+use std::rc::Rc;
+
+use cvc5_sys::Cvc5Kind::*;
+use log_derive::*;
+
+use crate::abstract_value::AbstractValue;
+use crate::constant_domain::ConstantDomain;
+use crate::expression::{Expression, ExpressionType};
+use crate::path::Path;
+use crate::smt_solver::SmtResult;
+use crate::smt_solver::SmtSolver;
+use crate::smt_solver::DEFAULT_TIMEOUT_MS;
+
+pub type Cvc5ExpressionType = cvc5_sys::Cvc5Term;
+
+/// The number of bits of the bit vectors that represent integers. Every value of i128 and u128
+/// is a value of a signed bit vector of this width, so integers of all types can be compared
+/// with the signed operations and the type of a value only matters where Rust truncates it.
+const NUM_BITS: u32 = 129;
+
+pub struct Cvc5Solver {
+    term_manager: *mut cvc5_sys::Cvc5TermManager,
+    solver: *mut cvc5_sys::Cvc5,
+    bool_sort: cvc5_sys::Cvc5Sort,
+    bv_sort: cvc5_sys::Cvc5Sort,
+    /// The constants made for paths and expressions, by their names and whether they are
+    /// Boolean. Unlike Z3, CVC5 makes a new constant every time a constant is made with a name,
+    /// so the constant of a name is made only once.
+    constants: RefCell<BTreeMap<(String, bool), cvc5_sys::Cvc5Term>>,
+    /// True if the last check ran out of time.
+    timed_out: Cell<bool>,
+}
+
+impl Debug for Cvc5Solver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        "Cvc5Solver".fmt(f)
+    }
+}
+
+impl Cvc5Solver {
+    /// Creates a solver that gives up on a query, and returns SmtResult::Undefined for it, once
+    /// it has taken timeout_ms milliseconds.
+    #[logfn_inputs(TRACE)]
+    pub fn new(timeout_ms: u64) -> Cvc5Solver {
+        unsafe {
+            let term_manager = cvc5_sys::cvc5_term_manager_new();
+            let solver = cvc5_sys::cvc5_new(term_manager);
+            let set_option = |option: &str, value: &str| {
+                let option = CString::new(option).unwrap();
+                let value = CString::new(value).unwrap();
+                cvc5_sys::cvc5_set_option(solver, option.as_ptr(), value.as_ptr());
+            };
+            set_option("incremental", "true");
+            set_option("produce-models", "true");
+            set_option("tlimit-per", &timeout_ms.to_string());
+            // CVC5 raises an error, rather than answering unknown, for a term that is outside of
+            // the logic that is set, so the logic does not restrict the terms of the predicates
+            let logic = CString::new("ALL").unwrap();
+            cvc5_sys::cvc5_set_logic(solver, logic.as_ptr());
+
+            let bool_sort = cvc5_sys::cvc5_get_boolean_sort(term_manager);
+            let bv_sort = cvc5_sys::cvc5_mk_bv_sort(term_manager, NUM_BITS);
+            Cvc5Solver {
+                term_manager,
+                solver,
+                bool_sort,
+                bv_sort,
+                constants: RefCell::new(BTreeMap::new()),
+                timed_out: Cell::new(false),
+            }
+        }
+    }
+}
+
+impl Default for Cvc5Solver {
+    #[logfn_inputs(TRACE)]
+    fn default() -> Self {
+        Cvc5Solver::new(DEFAULT_TIMEOUT_MS)
+    }
+}
+
+impl SmtSolver<Cvc5ExpressionType> for Cvc5Solver {
+    #[logfn_inputs(TRACE)]
+    fn as_debug_string(&self, expression: &Cvc5ExpressionType) -> String {
+        unsafe { copy_string(cvc5_sys::cvc5_term_to_string(*expression)) }
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn assert(&self, expression: &Cvc5ExpressionType) {
+        unsafe {
+            cvc5_sys::cvc5_assert_formula(self.solver, *expression);
+        }
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn backtrack(&self) {
+        unsafe {
+            cvc5_sys::cvc5_pop(self.solver, 1);
+        }
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn get_as_smt_predicate(&self, hepha_expression: &Expression) -> Cvc5ExpressionType {
+        self.get_as_bool_term(hepha_expression)
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn get_model_as_string(&self) -> String {
+        self.get_model_string().unwrap_or_default()
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn get_model_string(&self) -> Option<String> {
+        let mut assignments = String::new();
+        for ((name, is_bool), constant) in self.constants.borrow().iter() {
+            unsafe {
+                let value = cvc5_sys::cvc5_get_value(self.solver, *constant);
+                if value.is_null() {
+                    return None;
+                }
+                let value = if *is_bool {
+                    cvc5_sys::cvc5_term_get_boolean_value(value).to_string()
+                } else {
+                    bv_value_as_string(&copy_string(cvc5_sys::cvc5_term_get_bv_value(value, 2)))
+                };
+                assignments.push_str(&format!("{name} = {value}\n"));
+            }
+        }
+        Some(assignments)
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn get_solver_state_as_string(&self) -> String {
+        unsafe {
+            let mut size = 0;
+            let assertions = cvc5_sys::cvc5_get_assertions(self.solver, &mut size);
+            (0..size)
+                .map(|i| copy_string(cvc5_sys::cvc5_term_to_string(*assertions.add(i))))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn invert_predicate(&self, expression: &Cvc5ExpressionType) -> Cvc5ExpressionType {
+        self.term(CVC5_KIND_NOT, &[*expression])
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn set_backtrack_position(&self) {
+        unsafe {
+            cvc5_sys::cvc5_push(self.solver, 1);
+        }
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn solve(&self) -> SmtResult {
+        unsafe { self.as_smt_result(cvc5_sys::cvc5_check_sat(self.solver)) }
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn solve_under_assumptions(&self, assumptions: &[Cvc5ExpressionType]) -> SmtResult {
+        unsafe {
+            self.as_smt_result(cvc5_sys::cvc5_check_sat_assuming(
+                self.solver,
+                assumptions.len(),
+                assumptions.as_ptr(),
+            ))
+        }
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn timed_out(&self) -> bool {
+        self.timed_out.get()
+    }
+}
+
+/// Copies a string returned by CVC5, which is only valid until the next call.
+unsafe fn copy_string(string: *const std::os::raw::c_char) -> String {
+    CStr::from_ptr(string).to_string_lossy().into_owned()
+}
+
+/// Renders the binary digits of a value of a bit vector of NUM_BITS bits as a decimal integer,
+/// reading the leftmost bit as the sign.
+fn bv_value_as_string(bits: &str) -> String {
+    let (sign, magnitude) = bits.split_at(bits.len().saturating_sub(128));
+    let value = u128::from_str_radix(magnitude, 2).unwrap_or_default();
+    if sign.starts_with('1') {
+        (value as i128).to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+impl Cvc5Solver {
+    /// Returns the result of a check, and records if it ran out of time.
+    unsafe fn as_smt_result(&self, result: cvc5_sys::Cvc5Result) -> SmtResult {
+        self.timed_out.set(
+            cvc5_sys::cvc5_result_is_unknown(result)
+                && cvc5_sys::cvc5_result_get_unknown_explanation(result)
+                    == cvc5_sys::Cvc5UnknownExplanation::CVC5_UNKNOWN_EXPLANATION_TIMEOUT,
+        );
+        if cvc5_sys::cvc5_result_is_sat(result) {
+            SmtResult::Satisfiable
+        } else if cvc5_sys::cvc5_result_is_unsat(result) {
+            SmtResult::Unsatisfiable
+        } else {
+            SmtResult::Undefined
+        }
+    }
+
+    fn term(
+        &self,
+        kind: cvc5_sys::Cvc5Kind,
+        children: &[Cvc5ExpressionType],
+    ) -> Cvc5ExpressionType {
+        unsafe {
+            cvc5_sys::cvc5_mk_term(self.term_manager, kind, children.len(), children.as_ptr())
+        }
+    }
+
+    fn indexed_term(
+        &self,
+        kind: cvc5_sys::Cvc5Kind,
+        indices: &[u32],
+        child: Cvc5ExpressionType,
+    ) -> Cvc5ExpressionType {
+        unsafe {
+            let op = cvc5_sys::cvc5_mk_op(self.term_manager, kind, indices.len(), indices.as_ptr());
+            cvc5_sys::cvc5_mk_term_from_op(self.term_manager, op, 1, [child].as_ptr())
+        }
+    }
+
+    /// Returns the constant with the given name, which is the same constant every time.
+    #[logfn_inputs(TRACE)]
+    fn constant(&self, name: String, is_bool: bool) -> Cvc5ExpressionType {
+        let mut constants = self.constants.borrow_mut();
+        *constants
+            .entry((name, is_bool))
+            .or_insert_with_key(|(name, _)| {
+                let sort = if is_bool {
+                    self.bool_sort
+                } else {
+                    self.bv_sort
+                };
+                let symbol = CString::new(name.as_str()).unwrap();
+                unsafe { cvc5_sys::cvc5_mk_const(self.term_manager, sort, symbol.as_ptr()) }
+            })
+    }
+
+    /// Returns a new constant that is not the same as any other constant.
+    #[logfn_inputs(TRACE)]
+    fn fresh_constant(&self, is_bool: bool) -> Cvc5ExpressionType {
+        let sort = if is_bool {
+            self.bool_sort
+        } else {
+            self.bv_sort
+        };
+        let symbol = CString::new("").unwrap();
+        unsafe { cvc5_sys::cvc5_mk_const(self.term_manager, sort, symbol.as_ptr()) }
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn get_as_bool_term(&self, expression: &Expression) -> Cvc5ExpressionType {
+        match expression {
+            Expression::AddOverflows {
+                left,
+                right,
+                result_type,
+            } => self.overflows(CVC5_KIND_BITVECTOR_ADD, left, right, *result_type),
+            Expression::MulOverflows {
+                left,
+                right,
+                result_type,
+            } => self.overflows(CVC5_KIND_BITVECTOR_MULT, left, right, *result_type),
+            Expression::SubOverflows {
+                left,
+                right,
+                result_type,
+            } => self.overflows(CVC5_KIND_BITVECTOR_SUB, left, right, *result_type),
+            Expression::ShlOverflows {
+                right, result_type, ..
+            }
+            | Expression::ShrOverflows {
+                right, result_type, ..
+            } => {
+                let right_term = self.get_as_bv_term(&right.expression);
+                let num_bits = ConstantDomain::U128(u128::from(result_type.bit_length()));
+                let num_bits_term = self.bv_constant(NUM_BITS, &num_bits).unwrap();
+                self.term(CVC5_KIND_BITVECTOR_SGE, &[right_term, num_bits_term])
+            }
+            Expression::And { left, right } => self.term(
+                CVC5_KIND_AND,
+                &[
+                    self.get_as_bool_term(&left.expression),
+                    self.get_as_bool_term(&right.expression),
+                ],
+            ),
+            Expression::Or { left, right } => self.term(
+                CVC5_KIND_OR,
+                &[
+                    self.get_as_bool_term(&left.expression),
+                    self.get_as_bool_term(&right.expression),
+                ],
+            ),
+            Expression::LogicalNot { operand } => {
+                self.term(CVC5_KIND_NOT, &[self.get_as_bool_term(&operand.expression)])
+            }
+            Expression::Equals { left, right } => self.equals(left, right),
+            Expression::Ne { left, right } => self.term(CVC5_KIND_NOT, &[self.equals(left, right)]),
+            Expression::GreaterOrEqual { left, right } => {
+                self.relational(CVC5_KIND_BITVECTOR_SGE, left, right)
+            }
+            Expression::GreaterThan { left, right } => {
+                self.relational(CVC5_KIND_BITVECTOR_SGT, left, right)
+            }
+            Expression::LessOrEqual { left, right } => {
+                self.relational(CVC5_KIND_BITVECTOR_SLE, left, right)
+            }
+            Expression::LessThan { left, right } => {
+                self.relational(CVC5_KIND_BITVECTOR_SLT, left, right)
+            }
+            Expression::Cast { operand, .. } | Expression::TaggedExpression { operand, .. } => {
+                self.get_as_bool_term(&operand.expression)
+            }
+            Expression::CompileTimeConstant(const_domain) => match const_domain {
+                ConstantDomain::False => unsafe { cvc5_sys::cvc5_mk_false(self.term_manager) },
+                ConstantDomain::True => unsafe { cvc5_sys::cvc5_mk_true(self.term_manager) },
+                ConstantDomain::U128(val) => unsafe {
+                    if *val == 0 {
+                        cvc5_sys::cvc5_mk_false(self.term_manager)
+                    } else {
+                        cvc5_sys::cvc5_mk_true(self.term_manager)
+                    }
+                },
+                _ => self.constant(format!("{const_domain:?}"), true),
+            },
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => self.term(
+                CVC5_KIND_ITE,
+                &[
+                    self.get_as_bool_term(&condition.expression),
+                    self.get_as_bool_term(&consequent.expression),
+                    self.get_as_bool_term(&alternate.expression),
+                ],
+            ),
+            Expression::Join { left, right } => self.term(
+                CVC5_KIND_ITE,
+                &[
+                    self.fresh_constant(true),
+                    self.get_as_bool_term(&left.expression),
+                    self.get_as_bool_term(&right.expression),
+                ],
+            ),
+            Expression::Switch {
+                discriminator,
+                cases,
+                default,
+            } => self.switch(discriminator, cases, default, |e| self.get_as_bool_term(e)),
+            Expression::Top | Expression::Bottom => self.fresh_constant(true),
+            Expression::Reference(path) | Expression::WidenedJoin { path, .. } => {
+                self.constant(format!("{path:?}"), true)
+            }
+            Expression::UninterpretedCall {
+                result_type: var_type,
+                path,
+                ..
+            }
+            | Expression::InitialParameterValue { path, var_type }
+            | Expression::Variable { path, var_type } => {
+                if var_type.is_integer() {
+                    self.is_not_zero(self.bv_variable(path, *var_type))
+                } else {
+                    self.constant(format!("{path:?}"), true)
+                }
+            }
+            _ => {
+                if expression.infer_type().is_integer() {
+                    self.is_not_zero(self.get_as_bv_term(expression))
+                } else {
+                    self.constant(format!("{expression:?}"), true)
+                }
+            }
+        }
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn get_as_bv_term(&self, expression: &Expression) -> Cvc5ExpressionType {
+        match expression {
+            Expression::Add { left, right } => self.bv_binary(CVC5_KIND_BITVECTOR_ADD, left, right),
+            Expression::BitAnd { left, right } => {
+                self.bv_binary(CVC5_KIND_BITVECTOR_AND, left, right)
+            }
+            Expression::BitOr { left, right } => {
+                self.bv_binary(CVC5_KIND_BITVECTOR_OR, left, right)
+            }
+            Expression::BitXor { left, right } => {
+                self.bv_binary(CVC5_KIND_BITVECTOR_XOR, left, right)
+            }
+            Expression::Div { left, right } => {
+                self.bv_binary(CVC5_KIND_BITVECTOR_SDIV, left, right)
+            }
+            Expression::Mul { left, right } => {
+                self.bv_binary(CVC5_KIND_BITVECTOR_MULT, left, right)
+            }
+            Expression::Rem { left, right } => {
+                self.bv_binary(CVC5_KIND_BITVECTOR_SREM, left, right)
+            }
+            Expression::Sub { left, right } => self.bv_binary(CVC5_KIND_BITVECTOR_SUB, left, right),
+            Expression::Shl { left, right } => {
+                let shifted = self.bv_binary(CVC5_KIND_BITVECTOR_SHL, left, right);
+                self.truncate(shifted, left.expression.infer_type())
+            }
+            // The values of unsigned integers are not negative, so shifting in the sign bit
+            // shifts in zeros for them
+            Expression::Shr { left, right } => {
+                self.bv_binary(CVC5_KIND_BITVECTOR_ASHR, left, right)
+            }
+            Expression::BitNot {
+                operand,
+                result_type,
+            } => {
+                let operand_term = self.get_as_bv_term(&operand.expression);
+                if result_type.is_unsigned_integer() {
+                    // The bits above those of the type must stay zero
+                    let max_term = self
+                        .bv_constant(NUM_BITS, &result_type.max_value())
+                        .unwrap();
+                    self.term(CVC5_KIND_BITVECTOR_SUB, &[max_term, operand_term])
+                } else {
+                    self.term(CVC5_KIND_BITVECTOR_NOT, &[operand_term])
+                }
+            }
+            Expression::Neg { operand } => self.term(
+                CVC5_KIND_BITVECTOR_NEG,
+                &[self.get_as_bv_term(&operand.expression)],
+            ),
+            Expression::Cast {
+                operand,
+                target_type,
+            } => {
+                if operand.expression.infer_type() == ExpressionType::Bool {
+                    self.bool_as_bv(self.get_as_bool_term(&operand.expression))
+                } else if target_type.is_integer() {
+                    self.truncate(self.get_as_bv_term(&operand.expression), *target_type)
+                } else {
+                    self.constant(format!("{expression:?}"), false)
+                }
+            }
+            Expression::CompileTimeConstant(const_domain) => self
+                .bv_constant(NUM_BITS, const_domain)
+                .unwrap_or_else(|| self.constant(format!("{const_domain:?}"), false)),
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => self.term(
+                CVC5_KIND_ITE,
+                &[
+                    self.get_as_bool_term(&condition.expression),
+                    self.get_as_bv_term(&consequent.expression),
+                    self.get_as_bv_term(&alternate.expression),
+                ],
+            ),
+            Expression::Join { left, right } => self.term(
+                CVC5_KIND_ITE,
+                &[
+                    self.fresh_constant(true),
+                    self.get_as_bv_term(&left.expression),
+                    self.get_as_bv_term(&right.expression),
+                ],
+            ),
+            Expression::Switch {
+                discriminator,
+                cases,
+                default,
+            } => self.switch(discriminator, cases, default, |e| self.get_as_bv_term(e)),
+            Expression::TaggedExpression { operand, .. } => {
+                self.get_as_bv_term(&operand.expression)
+            }
+            Expression::Top | Expression::Bottom => self.fresh_constant(false),
+            Expression::And { .. }
+            | Expression::AddOverflows { .. }
+            | Expression::Equals { .. }
+            | Expression::GreaterOrEqual { .. }
+            | Expression::GreaterThan { .. }
+            | Expression::LessOrEqual { .. }
+            | Expression::LessThan { .. }
+            | Expression::LogicalNot { .. }
+            | Expression::MulOverflows { .. }
+            | Expression::Ne { .. }
+            | Expression::Or { .. }
+            | Expression::ShlOverflows { .. }
+            | Expression::ShrOverflows { .. }
+            | Expression::SubOverflows { .. } => self.bool_as_bv(self.get_as_bool_term(expression)),
+            Expression::UninterpretedCall {
+                result_type: var_type,
+                path,
+                ..
+            }
+            | Expression::InitialParameterValue { path, var_type }
+            | Expression::Variable { path, var_type } => {
+                if *var_type == ExpressionType::Bool {
+                    self.bool_as_bv(self.constant(format!("{path:?}"), true))
+                } else {
+                    self.bv_variable(path, *var_type)
+                }
+            }
+            Expression::WidenedJoin { path, operand } => {
+                self.bv_variable(path, operand.expression.infer_type())
+            }
+            _ => {
+                let path = Path::get_as_path(AbstractValue::make_from(expression.clone(), 1));
+                self.bv_variable(&path, expression.infer_type())
+            }
+        }
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn bv_binary(
+        &self,
+        kind: cvc5_sys::Cvc5Kind,
+        left: &Rc<AbstractValue>,
+        right: &Rc<AbstractValue>,
+    ) -> Cvc5ExpressionType {
+        let left_term = self.get_as_bv_term(&left.expression);
+        let right_term = self.get_as_bv_term(&right.expression);
+        self.term(kind, &[left_term, right_term])
+    }
+
+    /// Returns a bit vector constant of num_bits bits with the value of the given integer,
+    /// character or Boolean constant, or None if the constant is of another kind.
+    #[logfn_inputs(TRACE)]
+    fn bv_constant(
+        &self,
+        num_bits: u32,
+        const_domain: &ConstantDomain,
+    ) -> Option<Cvc5ExpressionType> {
+        let bits = match const_domain {
+            ConstantDomain::Char(v) => format!("{:b}", *v as u128),
+            ConstantDomain::False => String::from("0"),
+            ConstantDomain::True => String::from("1"),
+            ConstantDomain::I128(v) if *v < 0 => {
+                // The two's complement of the value, extended with its sign bit
+                let sign_bits = "1".repeat((num_bits - 128) as usize);
+                format!("{sign_bits}{:0128b}", *v as u128)
+            }
+            ConstantDomain::I128(v) => format!("{:b}", *v),
+            ConstantDomain::U128(v) => format!("{:b}", *v),
+            _ => return None,
+        };
+        let bits = CString::new(bits).unwrap();
+        unsafe {
+            Some(cvc5_sys::cvc5_mk_bv(
+                self.term_manager,
+                num_bits,
+                bits.as_ptr(),
+                2,
+            ))
+        }
+    }
+
+    /// Returns the constant of the path, and asserts that its value is in the range of its type,
+    /// which declares the constant much like Z3Solver::numeric_variable does.
+    #[logfn_inputs(TRACE)]
+    fn bv_variable(&self, path: &Rc<Path>, var_type: ExpressionType) -> Cvc5ExpressionType {
+        let term = self.constant(format!("{path:?}"), false);
+        if var_type.is_integer() {
+            let min_term = self.bv_constant(NUM_BITS, &var_type.min_value()).unwrap();
+            let max_term = self.bv_constant(NUM_BITS, &var_type.max_value()).unwrap();
+            let above_min = self.term(CVC5_KIND_BITVECTOR_SLE, &[min_term, term]);
+            let below_max = self.term(CVC5_KIND_BITVECTOR_SLE, &[term, max_term]);
+            self.assert(&self.term(CVC5_KIND_AND, &[above_min, below_max]));
+        }
+        term
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn bool_as_bv(&self, term: Cvc5ExpressionType) -> Cvc5ExpressionType {
+        let one = self.bv_constant(NUM_BITS, &ConstantDomain::True).unwrap();
+        let zero = self.bv_constant(NUM_BITS, &ConstantDomain::False).unwrap();
+        self.term(CVC5_KIND_ITE, &[term, one, zero])
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn is_not_zero(&self, term: Cvc5ExpressionType) -> Cvc5ExpressionType {
+        let zero = self.bv_constant(NUM_BITS, &ConstantDomain::False).unwrap();
+        self.term(CVC5_KIND_DISTINCT, &[term, zero])
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn equals(&self, left: &Rc<AbstractValue>, right: &Rc<AbstractValue>) -> Cvc5ExpressionType {
+        let (left_term, right_term) = if left.expression.infer_type() == ExpressionType::Bool
+            && right.expression.infer_type() == ExpressionType::Bool
+        {
+            (
+                self.get_as_bool_term(&left.expression),
+                self.get_as_bool_term(&right.expression),
+            )
+        } else {
+            (
+                self.get_as_bv_term(&left.expression),
+                self.get_as_bv_term(&right.expression),
+            )
+        };
+        self.term(CVC5_KIND_EQUAL, &[left_term, right_term])
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn relational(
+        &self,
+        kind: cvc5_sys::Cvc5Kind,
+        left: &Rc<AbstractValue>,
+        right: &Rc<AbstractValue>,
+    ) -> Cvc5ExpressionType {
+        let left_term = self.get_as_bv_term(&left.expression);
+        let right_term = self.get_as_bv_term(&right.expression);
+        self.term(kind, &[left_term, right_term])
+    }
+
+    /// Returns a predicate that is true if the result of the operation on left and right is not
+    /// a value of the result type. The operands are extended to twice as many bits first, so that
+    /// the operation itself cannot overflow.
+    #[logfn_inputs(TRACE)]
+    fn overflows(
+        &self,
+        kind: cvc5_sys::Cvc5Kind,
+        left: &Rc<AbstractValue>,
+        right: &Rc<AbstractValue>,
+        result_type: ExpressionType,
+    ) -> Cvc5ExpressionType {
+        if !result_type.is_integer() {
+            return self.fresh_constant(true);
+        }
+        let extend = |operand: &Rc<AbstractValue>| {
+            let term = self.get_as_bv_term(&operand.expression);
+            self.indexed_term(CVC5_KIND_BITVECTOR_SIGN_EXTEND, &[NUM_BITS], term)
+        };
+        let result = self.term(kind, &[extend(left), extend(right)]);
+        let min_term = self
+            .bv_constant(2 * NUM_BITS, &result_type.min_value())
+            .unwrap();
+        let max_term = self
+            .bv_constant(2 * NUM_BITS, &result_type.max_value())
+            .unwrap();
+        let below_min = self.term(CVC5_KIND_BITVECTOR_SLT, &[result, min_term]);
+        let above_max = self.term(CVC5_KIND_BITVECTOR_SGT, &[result, max_term]);
+        self.term(CVC5_KIND_OR, &[below_min, above_max])
+    }
+
+    /// Returns the value of the bits of the term that fit in the target type, as a value of
+    /// that type, the way that Rust truncates integers when casting them.
+    #[logfn_inputs(TRACE)]
+    fn truncate(
+        &self,
+        term: Cvc5ExpressionType,
+        target_type: ExpressionType,
+    ) -> Cvc5ExpressionType {
+        if !target_type.is_integer() {
+            return term;
+        }
+        let num_bits = u32::from(target_type.bit_length());
+        let low_bits = self.indexed_term(CVC5_KIND_BITVECTOR_EXTRACT, &[num_bits - 1, 0], term);
+        let extension = if target_type.is_signed_integer() {
+            CVC5_KIND_BITVECTOR_SIGN_EXTEND
+        } else {
+            CVC5_KIND_BITVECTOR_ZERO_EXTEND
+        };
+        self.indexed_term(extension, &[NUM_BITS - num_bits], low_bits)
+    }
+
+    fn switch<F>(
+        &self,
+        discriminator: &Rc<AbstractValue>,
+        cases: &[(Rc<AbstractValue>, Rc<AbstractValue>)],
+        default: &Rc<AbstractValue>,
+        get_result_term: F,
+    ) -> Cvc5ExpressionType
+    where
+        F: Fn(&Expression) -> Cvc5ExpressionType,
+    {
+        let discriminator_term = self.get_as_bv_term(&discriminator.expression);
+        let default_term = get_result_term(&default.expression);
+        cases
+            .iter()
+            .fold(default_term, |acc_term, (case_val, case_result)| {
+                let case_val_term = self.get_as_bv_term(&case_val.expression);
+                let condition = self.term(CVC5_KIND_EQUAL, &[discriminator_term, case_val_term]);
+                let case_result_term = get_result_term(&case_result.expression);
+                self.term(CVC5_KIND_ITE, &[condition, case_result_term, acc_term])
+            })
+    }
+}
+
+impl Drop for Cvc5Solver {
+    fn drop(&mut self) {
+        unsafe {
+            cvc5_sys::cvc5_delete(self.solver);
+            cvc5_sys::cvc5_term_manager_delete(self.term_manager);
+        };
+    }
+}
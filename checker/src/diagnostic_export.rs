@@ -0,0 +1,294 @@
+// The diagnostics of a run as JSON lines, so that the findings of two runs can be compared in CI,
+// and the baselines that suppress the findings that were already known. Each diagnostic has a
+// fingerprint that identifies it across edits of the source: it hashes the code of the finding,
+// the summary key of the function that contains the diagnostic and the offset of the diagnostic
+// from the start of the body of that function, so it does not change when lines are added or
+// removed elsewhere in the file or when the function is moved.
+// The diagnostics also have their notes and a hash of the source of their primary span, with
+// which --cluster-findings groups the findings of copies of the same code.
+// The findings of checkers also have the facts about the analysis that they depend on, such as the
+// entry condition of the block they are in, so that a finding can be triaged and reproduced from
+// the export alone. The facts are attached to the diagnostic as an argument when the finding is
+// emitted, and have a versioned schema whose lists and strings are bounded in size.
+
+use std::collections::HashSet;
+use std::io;
+
+use rustc_errors::{DiagArgValue, DiagInner, Level};
+use rustc_hir::def_id::{DefId, LocalDefId};
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Span;
+use serde::{Deserialize, Serialize};
+
+use crate::checker_registry;
+use crate::utils;
+
+/// A diagnostic as it is written by --diag-format=json.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ExportedDiagnostic {
+    pub file: String,
+    /// The line of the primary span, starting at 1.
+    pub line: usize,
+    /// The column of the primary span, starting at 1.
+    pub column: usize,
+    /// The code of the finding, such as HEPHA-REENTRANCY, or None if the diagnostic is not the
+    /// finding of a checker.
+    pub checker: Option<String>,
+    pub message: String,
+    pub fingerprint: String,
+    /// The hash of the source of the primary span without its comments and its whitespace, which
+    /// is the same for the copies of a piece of code.
+    #[serde(default)]
+    pub snippet_hash: String,
+    #[serde(default)]
+    pub notes: Vec<ExportedNote>,
+    /// True if the analysis was cancelled before it was complete, so that the diagnostics of the
+    /// run are not all of them.
+    #[serde(default)]
+    pub truncated: bool,
+    /// The facts about the analysis that the finding depends on, if the diagnostic is the finding
+    /// of a checker.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<FindingProperties>,
+}
+
+/// The version of the schema of FindingProperties, which changes when a field is renamed,
+/// removed or given another meaning, but not when a field is added.
+pub const FINDING_PROPERTIES_SCHEMA: u32 = 1;
+
+/// The maximum number of characters of the rendered entry condition of a finding.
+pub const MAX_RENDERED_CONDITION_LENGTH: usize = 1024;
+
+/// The maximum number of entries of each list of the properties of a finding.
+pub const MAX_PROPERTY_ENTRIES: usize = 16;
+
+/// The name of the argument of the diagnostic of a finding that holds its properties as JSON.
+pub const FINDING_PROPERTIES_ARG: &str = "hepha_finding_properties";
+
+/// The facts about the analysis that a finding depends on.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FindingProperties {
+    /// The version of the schema, FINDING_PROPERTIES_SCHEMA when the finding was exported.
+    pub schema: u32,
+    /// The entry condition of the block that the finding is in, as the analysis renders it, if
+    /// the finding is reported while the block is checked rather than after the whole body.
+    pub entry_condition: Option<String>,
+    /// The provenance classes of the values that the finding is about, such as instruction_data,
+    /// account_data or untrusted_input.
+    pub provenance: Vec<String>,
+    /// The file, line and column of each assume! call that the block of the finding follows, or
+    /// that the body contains, if the finding is not in a block.
+    pub assumptions: Vec<(String, usize, usize)>,
+    /// The file, line and column of each verification condition of the function that is only
+    /// proven with assumptions that are not present in the shipped code.
+    pub assumption_ledger: Vec<(String, usize, usize)>,
+    /// The functions that the body calls that could not be analyzed, whose results and side
+    /// effects are unknown values, so that a finding may be missed or reported in error.
+    pub havocked_callees: Vec<String>,
+    /// True if the entry condition or a list was cut off at its maximum size.
+    pub truncated: bool,
+}
+
+impl FindingProperties {
+    /// Returns properties with the given facts, cut off at their maximum sizes.
+    pub fn new(
+        entry_condition: Option<String>,
+        provenance: Vec<String>,
+        assumptions: Vec<(String, usize, usize)>,
+        havocked_callees: Vec<String>,
+    ) -> FindingProperties {
+        let mut properties = FindingProperties {
+            schema: FINDING_PROPERTIES_SCHEMA,
+            entry_condition,
+            provenance,
+            assumptions,
+            assumption_ledger: Vec::new(),
+            havocked_callees,
+            truncated: false,
+        };
+        properties.bound();
+        properties
+    }
+
+    /// Adds the verification conditions of the function that rely on assumptions.
+    pub fn add_assumption_ledger(&mut self, entries: Vec<(String, usize, usize)>) {
+        self.assumption_ledger.extend(entries);
+        self.bound();
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("finding properties should serialize")
+    }
+
+    // Cuts off the entry condition and the lists at their maximum sizes.
+    fn bound(&mut self) {
+        if let Some(condition) = &mut self.entry_condition {
+            if let Some((end, _)) = condition.char_indices().nth(MAX_RENDERED_CONDITION_LENGTH) {
+                condition.truncate(end);
+                self.truncated = true;
+            }
+        }
+        for list_length in [
+            self.provenance.len(),
+            self.assumptions.len(),
+            self.assumption_ledger.len(),
+            self.havocked_callees.len(),
+        ] {
+            self.truncated |= list_length > MAX_PROPERTY_ENTRIES;
+        }
+        self.provenance.truncate(MAX_PROPERTY_ENTRIES);
+        self.assumptions.truncate(MAX_PROPERTY_ENTRIES);
+        self.assumption_ledger.truncate(MAX_PROPERTY_ENTRIES);
+        self.havocked_callees.truncate(MAX_PROPERTY_ENTRIES);
+    }
+}
+
+/// A note of an exported diagnostic.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ExportedNote {
+    pub message: String,
+    /// The file, line and column of the primary span of the note, if it has one.
+    pub location: Option<(String, usize, usize)>,
+}
+
+impl ExportedDiagnostic {
+    /// Returns the export of the given diagnostic, which was reported while the function with
+    /// the given id was analyzed, or None if the diagnostic has no primary span.
+    pub fn new(tcx: TyCtxt<'_>, analyzed: DefId, diagnostic: &DiagInner) -> Option<Self> {
+        let span = diagnostic.span.primary_span()?.source_callsite();
+        let message = diagnostic.messages.first()?.0.as_str()?.to_string();
+        let checker = checker_registry::finding_code(&message).map(str::to_string);
+        // The diagnostics of the functions that are called at depth 1 are reported with the
+        // function that is analyzed, but their spans are in the called functions
+        let (function, body_start) = match enclosing_function(tcx, span) {
+            Some(owner) => (
+                owner.to_def_id(),
+                tcx.hir().body_owned_by(owner).value.span.lo(),
+            ),
+            None => (analyzed, tcx.def_span(analyzed).lo()),
+        };
+        let offset = span.lo().0.saturating_sub(body_start.0);
+        let key = utils::summary_key_str(tcx, function);
+        let source_map = tcx.sess.source_map();
+        let location = source_map.lookup_char_pos(span.lo());
+        let notes = diagnostic
+            .children
+            .iter()
+            .filter(|child| child.level == Level::Note)
+            .filter_map(|child| {
+                let message = child.messages.first()?.0.as_str()?.to_string();
+                let location = child.span.primary_span().map(|span| {
+                    let location = source_map.lookup_char_pos(span.source_callsite().lo());
+                    let file = location.file.name.prefer_local().to_string();
+                    (file, location.line, location.col.0 + 1)
+                });
+                Some(ExportedNote { message, location })
+            })
+            .collect();
+        let properties = match diagnostic.args.get(FINDING_PROPERTIES_ARG) {
+            Some(DiagArgValue::Str(properties)) => serde_json::from_str(properties).ok(),
+            _ => None,
+        };
+        Some(ExportedDiagnostic {
+            file: location.file.name.prefer_local().to_string(),
+            line: location.line,
+            column: location.col.0 + 1,
+            fingerprint: fingerprint(checker.as_deref(), &key, offset),
+            checker,
+            message,
+            snippet_hash: snippet_hash(&source_map.span_to_snippet(span).unwrap_or_default()),
+            notes,
+            truncated: false,
+            properties,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("diagnostics should serialize")
+    }
+}
+
+/// Returns the fingerprint of a diagnostic with the given code, in the function with the given
+/// summary key and at the given byte offset from the start of the body of the function. The hash
+/// is FNV-1a, which unlike the hasher of the standard library is the same for every build.
+pub fn fingerprint(checker: Option<&str>, function: &str, offset: u32) -> String {
+    fnv1a(format!("{}\0{function}\0{offset}", checker.unwrap_or_default()).as_bytes())
+}
+
+/// Returns the hash of the given source with its line comments and its whitespace left out, so
+/// that copies of the same code that are laid out or commented differently have the same hash.
+pub fn snippet_hash(snippet: &str) -> String {
+    let normalized = snippet
+        .lines()
+        .map(|line| line.split_once("//").map_or(line, |(code, _)| code))
+        .flat_map(str::split_whitespace)
+        .collect::<String>();
+    fnv1a(normalized.as_bytes())
+}
+
+/// Returns the FNV-1a hash of the given bytes, in hexadecimal.
+pub fn fnv1a(input: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in input {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+/// Returns the innermost function of the crate whose body contains the given span.
+pub fn enclosing_function(tcx: TyCtxt<'_>, span: Span) -> Option<LocalDefId> {
+    tcx.hir()
+        .body_owners()
+        .filter(|owner| tcx.def_kind(*owner).is_fn_like())
+        .filter(|owner| tcx.hir().body_owned_by(*owner).value.span.contains(span))
+        .min_by_key(|owner| {
+            let body_span = tcx.hir().body_owned_by(*owner).value.span;
+            body_span.hi() - body_span.lo()
+        })
+}
+
+/// Reads the diagnostics that --diag-format=json wrote to the file at the given path, one per
+/// line.
+pub fn read_diagnostics(path: &std::path::Path) -> io::Result<Vec<ExportedDiagnostic>> {
+    let mut diagnostics = Vec::new();
+    for line in std::fs::read_to_string(path)?.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let diagnostic = serde_json::from_str(line)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        diagnostics.push(diagnostic);
+    }
+    Ok(diagnostics)
+}
+
+/// The fingerprints of the diagnostics of an earlier run, which are not reported again.
+#[derive(Debug, Default)]
+pub struct Baseline {
+    fingerprints: HashSet<String>,
+}
+
+impl Baseline {
+    /// Reads the fingerprints of the diagnostics that --diag-format=json wrote to the file at the
+    /// given path.
+    pub fn load(path: &std::path::Path) -> io::Result<Baseline> {
+        let fingerprints = read_diagnostics(path)?
+            .into_iter()
+            .map(|diagnostic| diagnostic.fingerprint)
+            .collect();
+        Ok(Baseline { fingerprints })
+    }
+
+    /// Splits the given diagnostics into those that are not in the baseline and those that are,
+    /// using the given function to get the fingerprint of a diagnostic.
+    pub fn filter<T>(
+        &self,
+        diagnostics: Vec<T>,
+        fingerprint: impl Fn(&T) -> Option<&str>,
+    ) -> (Vec<T>, Vec<T>) {
+        diagnostics.into_iter().partition(|diagnostic| {
+            !fingerprint(diagnostic).is_some_and(|f| self.fingerprints.contains(f))
+        })
+    }
+}
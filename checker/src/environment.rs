@@ -54,6 +54,15 @@ impl Environment {
         self.value_map.get(path)
     }
 
+    /// Returns the entries of the path to value map sorted by path. The map iterates in an
+    /// order that changes from one run to the next, so iterations whose outcome depends on the
+    /// order use this instead.
+    pub fn sorted_entries(&self) -> Vec<(&Rc<Path>, &Rc<AbstractValue>)> {
+        let mut entries: Vec<_> = self.value_map.iter().collect();
+        entries.sort_by(|(p1, _), (p2, _)| p1.cmp(p2));
+        entries
+    }
+
     /// Updates the path to value map so that the given path now points to the given value.
     #[logfn_inputs(TRACE)]
     pub fn strong_update_value_at(&mut self, path: Rc<Path>, value: Rc<AbstractValue>) {
@@ -277,6 +277,18 @@ pub enum Expression {
         right: Rc<AbstractValue>,
     },
 
+    /// An expression that is true if operand is true for every value of the bound variable.
+    /// forall!
+    Quantified {
+        // The path of the bound variable, which is the only variable that operand does not share
+        // with the enclosing expressions. See Path::new_bound_variable.
+        bound_variable: Rc<Path>,
+        // The type of the bound variable.
+        var_type: ExpressionType,
+        // The value of the condition, in terms of the bound variable.
+        operand: Rc<AbstractValue>,
+    },
+
     /// The corresponding concrete value is the runtime address of location identified by the path.
     Reference(Rc<Path>),
 
@@ -609,6 +621,13 @@ impl Debug for Expression {
             } => f.write_fmt(format_args!(
                 "({operand:?}).check_tag({tag:?}, {checking_presence})",
             )),
+            Expression::Quantified {
+                bound_variable,
+                var_type,
+                operand,
+            } => f.write_fmt(format_args!(
+                "forall({bound_variable:?}: {var_type:?}, {operand:?})"
+            )),
             Expression::UnknownTagField { path } => path.fmt(f),
             Expression::Variable { path, var_type } => {
                 f.write_fmt(format_args!("{path:?}: {var_type:?}"))
@@ -711,6 +730,7 @@ impl Expression {
             }
             Expression::Neg { operand }
             | Expression::LogicalNot { operand }
+            | Expression::Quantified { operand, .. }
             | Expression::UnknownTagCheck { operand, .. } => operand
                 .expression
                 .contains_local_variable(is_post_condition),
@@ -815,6 +835,7 @@ impl Expression {
             }
             Expression::Neg { operand }
             | Expression::LogicalNot { operand }
+            | Expression::Quantified { operand, .. }
             | Expression::UnknownTagCheck { operand, .. } => operand.expression.contains_top(),
             Expression::Reference(path) => path.contains_top(),
             Expression::Switch {
@@ -880,6 +901,7 @@ impl Expression {
             Expression::Neg { .. } => Some(TagPropagation::Neg),
             Expression::Or { .. } => Some(TagPropagation::Or),
             Expression::Offset { .. } => Some(TagPropagation::Offset),
+            Expression::Quantified { .. } => None,
             Expression::Reference { .. } => None,
             Expression::InitialParameterValue { .. } => None,
             Expression::Rem { .. } => Some(TagPropagation::Rem),
@@ -979,6 +1001,7 @@ impl Expression {
             }
             Expression::Neg { operand }
             | Expression::LogicalNot { operand }
+            | Expression::Quantified { operand, .. }
             | Expression::UnknownTagCheck { operand, .. } => {
                 operand.expression.has_tagged_subcomponent(tag, env)
             }
@@ -1191,6 +1214,7 @@ impl Expression {
             Expression::Neg { operand } => operand.expression.infer_type(),
             Expression::Or { .. } => Bool,
             Expression::Offset { .. } => ThinPointer,
+            Expression::Quantified { .. } => Bool,
             Expression::Reference(_) => ThinPointer,
             Expression::InitialParameterValue { var_type, .. } => *var_type,
             Expression::Rem { right, .. } => right.expression.infer_type(),
@@ -1283,6 +1307,20 @@ impl Expression {
         }
     }
 
+    /// Determines if the given expression is a quantified expression, or a boolean combination
+    /// with one.
+    #[logfn_inputs(TRACE)]
+    pub fn is_quantified(&self) -> bool {
+        match self {
+            Expression::Quantified { .. } => true,
+            Expression::And { left, right } | Expression::Or { left, right } => {
+                left.expression.is_quantified() || right.expression.is_quantified()
+            }
+            Expression::LogicalNot { operand } => operand.expression.is_quantified(),
+            _ => false,
+        }
+    }
+
     /// Determines if the given expression is the compile time constant 0u128.
     #[logfn_inputs(TRACE)]
     pub fn is_zero(&self) -> bool {
@@ -1292,6 +1330,107 @@ impl Expression {
         )
     }
 
+    /// Adds the paths of the variables of the expression, which are the free variables of the
+    /// formulas that the SMT solver is given, to the given list, unless they are in it already.
+    #[logfn_inputs(TRACE)]
+    pub fn record_variables(&self, result: &mut Vec<Rc<Path>>) {
+        match &self {
+            Expression::Add { left, right }
+            | Expression::AddOverflows { left, right, .. }
+            | Expression::And { left, right }
+            | Expression::BitAnd { left, right }
+            | Expression::BitOr { left, right }
+            | Expression::BitXor { left, right }
+            | Expression::Div { left, right }
+            | Expression::Equals { left, right }
+            | Expression::GreaterOrEqual { left, right }
+            | Expression::GreaterThan { left, right }
+            | Expression::IntrinsicBinary { left, right, .. }
+            | Expression::Join { left, right, .. }
+            | Expression::LessOrEqual { left, right }
+            | Expression::LessThan { left, right }
+            | Expression::Mul { left, right }
+            | Expression::MulOverflows { left, right, .. }
+            | Expression::Ne { left, right }
+            | Expression::Offset { left, right }
+            | Expression::Or { left, right }
+            | Expression::Rem { left, right }
+            | Expression::Shl { left, right }
+            | Expression::ShlOverflows { left, right, .. }
+            | Expression::Shr { left, right, .. }
+            | Expression::ShrOverflows { left, right, .. }
+            | Expression::Sub { left, right }
+            | Expression::SubOverflows { left, right, .. } => {
+                left.expression.record_variables(result);
+                right.expression.record_variables(result);
+            }
+            Expression::BitNot { operand, .. }
+            | Expression::Cast { operand, .. }
+            | Expression::IntrinsicBitVectorUnary { operand, .. }
+            | Expression::IntrinsicFloatingPointUnary { operand, .. }
+            | Expression::Neg { operand }
+            | Expression::LogicalNot { operand }
+            | Expression::TaggedExpression { operand, .. }
+            | Expression::Transmute { operand, .. }
+            | Expression::UnknownModelField {
+                default: operand, ..
+            } => {
+                operand.expression.record_variables(result);
+            }
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => {
+                condition.expression.record_variables(result);
+                consequent.expression.record_variables(result);
+                alternate.expression.record_variables(result);
+            }
+            Expression::InitialParameterValue { path, .. }
+            | Expression::Variable { path, .. }
+            | Expression::WidenedJoin { path, .. } => {
+                if !result.contains(path) {
+                    result.push(path.clone());
+                }
+            }
+            Expression::Memcmp {
+                left,
+                right,
+                length,
+            } => {
+                left.expression.record_variables(result);
+                right.expression.record_variables(result);
+                length.expression.record_variables(result);
+            }
+            Expression::Switch {
+                discriminator,
+                cases,
+                default,
+            } => {
+                discriminator.expression.record_variables(result);
+                for (case_val, case_result) in cases {
+                    case_val.expression.record_variables(result);
+                    case_result.expression.record_variables(result);
+                }
+                default.expression.record_variables(result);
+            }
+            Expression::UninterpretedCall { arguments, .. } => {
+                for arg in arguments {
+                    arg.expression.record_variables(result);
+                }
+            }
+            Expression::Bottom
+            | Expression::CompileTimeConstant(..)
+            | Expression::HeapBlock { .. }
+            | Expression::HeapBlockLayout { .. }
+            | Expression::Quantified { .. }
+            | Expression::Reference(..)
+            | Expression::Top
+            | Expression::UnknownTagCheck { .. }
+            | Expression::UnknownTagField { .. } => (),
+        }
+    }
+
     /// Adds any heap blocks found in the associated expression to the given set.
     #[logfn_inputs(TRACE)]
     pub fn record_heap_blocks_and_strings(&self, result: &mut HashSet<Rc<AbstractValue>>) {
@@ -1331,6 +1470,7 @@ impl Expression {
             | Expression::IntrinsicFloatingPointUnary { operand, .. }
             | Expression::Neg { operand }
             | Expression::LogicalNot { operand }
+            | Expression::Quantified { operand, .. }
             | Expression::TaggedExpression { operand, .. }
             | Expression::Transmute { operand, .. }
             | Expression::UnknownTagCheck { operand, .. } => {
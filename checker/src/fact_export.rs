@@ -0,0 +1,132 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// The facts that the contract checkers observe while they analyze a crate, written as the input
+// relations of a Datalog program, so that custom queries can be run over the results of an
+// analysis, such as the functions that read the clock and later write the lamports of an account.
+// The facts are tab separated files, one per relation, that Soufflé reads with .input, and the
+// declarations of the relations are written to schema.dl next to them.
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::Path;
+
+/// A relation of the exported facts, with the names and meanings of its columns.
+struct Relation {
+    name: &'static str,
+    columns: &'static [&'static str],
+    doc: &'static str,
+}
+
+const RELATIONS: [Relation; 4] = [
+    Relation {
+        name: "calls",
+        columns: &["caller", "callee", "span"],
+        doc: "The function caller calls the function callee at span.",
+    },
+    Relation {
+        name: "reads_clock",
+        columns: &["fn", "span"],
+        doc: "The function fn reads the clock sysvar at span.",
+    },
+    Relation {
+        name: "writes_lamports",
+        columns: &["fn", "account_param", "span"],
+        doc: "The function fn writes the lamports of the account held by the variable account_param at span, or by a temporary if account_param is _.",
+    },
+    Relation {
+        name: "tagged_flow",
+        columns: &["fn", "source_kind", "sink_span"],
+        doc: "A value of the function fn that carries the built-in tag source_kind, such as predictable_entropy or untrusted_input, decides a branch or is passed to an invocation at sink_span.",
+    },
+];
+
+/// The facts of the analyzed functions of a crate. Functions are named by their summary keys and
+/// spans are rendered as file:line:column. The facts are only recorded once the fixed point of a
+/// body has been reached, and they are kept in sets, so that the output does not depend on how
+/// many times a body is analyzed or in which order.
+#[derive(Debug, Default)]
+pub struct Facts {
+    calls: BTreeSet<(String, String, String)>,
+    reads_clock: BTreeSet<(String, String)>,
+    writes_lamports: BTreeSet<(String, String, String)>,
+    tagged_flow: BTreeSet<(String, String, String)>,
+}
+
+impl Facts {
+    pub fn add_call(&mut self, caller: &str, callee: &str, span: String) {
+        self.calls
+            .insert((caller.to_string(), callee.to_string(), span));
+    }
+
+    pub fn add_clock_read(&mut self, function: &str, span: String) {
+        self.reads_clock.insert((function.to_string(), span));
+    }
+
+    pub fn add_lamports_write(&mut self, function: &str, account: &str, span: String) {
+        self.writes_lamports
+            .insert((function.to_string(), account.to_string(), span));
+    }
+
+    pub fn add_tagged_flow(&mut self, function: &str, source_kind: &str, sink_span: String) {
+        self.tagged_flow
+            .insert((function.to_string(), source_kind.to_string(), sink_span));
+    }
+
+    /// Renders the declarations of the relations, with an .input directive for each of them.
+    pub fn schema() -> String {
+        let mut schema = String::new();
+        let _ = writeln!(
+            schema,
+            "// The relations of the facts that hepha writes with --export-facts."
+        );
+        for relation in &RELATIONS {
+            let columns = relation
+                .columns
+                .iter()
+                .map(|column| format!("{column}: symbol"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let _ = writeln!(schema);
+            let _ = writeln!(schema, "// {}", relation.doc);
+            let _ = writeln!(schema, ".decl {}({columns})", relation.name);
+            let _ = writeln!(schema, ".input {}", relation.name);
+        }
+        schema
+    }
+
+    /// Writes <directory>/<relation>.facts for every relation and <directory>/schema.dl.
+    pub fn write(&self, directory: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(directory)?;
+        let rows = [
+            self.calls.iter().map(|(a, b, c)| row(&[a, b, c])).collect(),
+            self.reads_clock.iter().map(|(a, b)| row(&[a, b])).collect(),
+            self.writes_lamports
+                .iter()
+                .map(|(a, b, c)| row(&[a, b, c]))
+                .collect(),
+            self.tagged_flow
+                .iter()
+                .map(|(a, b, c)| row(&[a, b, c]))
+                .collect::<String>(),
+        ];
+        for (relation, rows) in RELATIONS.iter().zip(rows) {
+            std::fs::write(directory.join(format!("{}.facts", relation.name)), rows)?;
+        }
+        std::fs::write(directory.join("schema.dl"), Self::schema())
+    }
+}
+
+// Renders a row of a fact file, with the tabs and line breaks of the values replaced by spaces,
+// since the fact files of Soufflé cannot escape them.
+fn row(values: &[&String]) -> String {
+    let mut row = values
+        .iter()
+        .map(|value| value.replace(['\t', '\n', '\r'], " "))
+        .collect::<Vec<_>>()
+        .join("\t");
+    row.push('\n');
+    row
+}
@@ -0,0 +1,170 @@
+// The clusters of the findings of a run over a workspace whose programs were copied from the same
+// template, so that a vulnerable template that is reported in twenty programs is reported once,
+// with the places of its copies. Findings are in the same cluster if they have the same code, the
+// same source at their primary span, once comments and whitespace are left out, and notes of the
+// same shape. --cluster-findings reads the findings that --diag-format=json wrote for the crates of
+// the workspace, which still have every finding in full.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::diagnostic_export::{ExportedDiagnostic, ExportedNote};
+use crate::options::OutputFormat;
+
+/// Where a note is relative to the primary span of its finding.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NotePosition {
+    Unspanned,
+    OtherFile,
+    Before,
+    SameLine,
+    After,
+}
+
+/// What the findings of a cluster have in common.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ClusterKey {
+    pub code: String,
+    pub snippet_hash: String,
+    /// The messages of the notes, without the names in backticks and the numbers, and where the
+    /// notes are relative to the primary span.
+    pub trace_shape: Vec<(String, NotePosition)>,
+}
+
+impl ClusterKey {
+    /// Returns the key of the given diagnostic, or None if it is not the finding of a checker or
+    /// if it has no snippet hash, because an older version of HEPHA exported it.
+    pub fn of(diagnostic: &ExportedDiagnostic) -> Option<ClusterKey> {
+        let code = diagnostic.checker.clone()?;
+        if diagnostic.snippet_hash.is_empty() {
+            return None;
+        }
+        let trace_shape = diagnostic
+            .notes
+            .iter()
+            .map(|note| {
+                (
+                    normalize_message(&note.message),
+                    note_position(diagnostic, note),
+                )
+            })
+            .collect();
+        Some(ClusterKey {
+            code,
+            snippet_hash: diagnostic.snippet_hash.clone(),
+            trace_shape,
+        })
+    }
+}
+
+// Returns where the given note is relative to the primary span of the given diagnostic.
+fn note_position(diagnostic: &ExportedDiagnostic, note: &ExportedNote) -> NotePosition {
+    match &note.location {
+        None => NotePosition::Unspanned,
+        Some((file, _, _)) if *file != diagnostic.file => NotePosition::OtherFile,
+        Some((_, line, _)) => match line.cmp(&diagnostic.line) {
+            std::cmp::Ordering::Less => NotePosition::Before,
+            std::cmp::Ordering::Equal => NotePosition::SameLine,
+            std::cmp::Ordering::Greater => NotePosition::After,
+        },
+    }
+}
+
+// Leaves the names in backticks and the numbers out of a message, since the copies of a template
+// may name their accounts and their constants differently.
+fn normalize_message(message: &str) -> String {
+    let mut normalized = String::new();
+    let mut in_backticks = false;
+    for c in message.chars() {
+        if c == '`' {
+            in_backticks = !in_backticks;
+            if in_backticks {
+                normalized.push_str("`_`");
+            }
+        } else if c.is_ascii_digit() {
+            if !normalized.ends_with('#') {
+                normalized.push('#');
+            }
+        } else if !in_backticks {
+            normalized.push(c);
+        }
+    }
+    normalized
+}
+
+/// A finding and the findings with the same key.
+#[derive(Debug, Serialize)]
+pub struct Cluster {
+    pub representative: ExportedDiagnostic,
+    pub similar: Vec<ExportedDiagnostic>,
+}
+
+impl Cluster {
+    /// Renders the representative as file:line:column: message, followed by the places of the
+    /// similar findings.
+    pub fn to_text(&self) -> String {
+        let finding = &self.representative;
+        let mut text = format!(
+            "{}:{}:{}: {}\n",
+            finding.file, finding.line, finding.column, finding.message
+        );
+        if !self.similar.is_empty() {
+            let places: Vec<String> = self
+                .similar
+                .iter()
+                .map(|similar| format!("{}:{}", similar.file, similar.line))
+                .collect();
+            let _ = writeln!(
+                text,
+                "  and {} similar finding{} in: {}",
+                self.similar.len(),
+                if self.similar.len() == 1 { "" } else { "s" },
+                places.join(", ")
+            );
+        }
+        text
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("clusters should serialize")
+    }
+}
+
+/// Groups the given diagnostics by their keys, in the order of the first diagnostic of each
+/// cluster, which is its representative. The diagnostics without keys are clusters of their own.
+pub fn cluster(diagnostics: Vec<ExportedDiagnostic>) -> Vec<Cluster> {
+    let mut clusters: Vec<Cluster> = Vec::new();
+    let mut cluster_of: HashMap<ClusterKey, usize> = HashMap::new();
+    for diagnostic in diagnostics {
+        let key = ClusterKey::of(&diagnostic);
+        if let Some(index) = key.as_ref().and_then(|key| cluster_of.get(key)) {
+            clusters[*index].similar.push(diagnostic);
+            continue;
+        }
+        if let Some(key) = key {
+            cluster_of.insert(key, clusters.len());
+        }
+        clusters.push(Cluster {
+            representative: diagnostic,
+            similar: Vec::new(),
+        });
+    }
+    clusters
+}
+
+/// Renders the given clusters as text, or as a line of JSON per cluster.
+pub fn render(clusters: &[Cluster], format: OutputFormat) -> String {
+    let mut output = String::new();
+    for cluster in clusters {
+        match format {
+            OutputFormat::Text => output.push_str(&cluster.to_text()),
+            OutputFormat::Json => {
+                output.push_str(&cluster.to_json());
+                output.push('\n');
+            }
+        }
+    }
+    output
+}
@@ -3,7 +3,7 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::{Debug, Formatter, Result};
 use std::rc::Rc;
 
@@ -12,25 +12,70 @@ use rpds::{HashTrieMap, HashTrieSet};
 
 use hepha_annotations::*;
 use rustc_data_structures::graph::dominators::Dominators;
+use rustc_errors::{Applicability, Diag};
 use rustc_middle::mir;
-use rustc_span::{Span, SyntaxContext};
 
 use crate::abstract_value::{AbstractValue, AbstractValueTrait};
 use crate::block_visitor::BlockVisitor;
 use crate::body_visitor::BodyVisitor;
+use crate::constant_domain::ConstantDomain;
 use crate::environment::Environment;
+use crate::expression::Expression;
+use crate::known_names::KnownNames;
 use crate::options::DiagLevel;
-use crate::{abstract_value, k_limits};
+use crate::path::{Path, PathEnum, PathSelector};
+use crate::path_constraints_report::{BlockPanel, ConjunctEntry, FunctionPanel, SourceLocation};
+use crate::{abstract_value, utils};
 
 pub struct FixedPointVisitor<'fixed, 'analysis, 'compilation, 'tcx> {
     pub bv: &'fixed mut BodyVisitor<'analysis, 'compilation, 'tcx>,
     already_visited: HashTrieSet<mir::BasicBlock>,
     pub block_indices: Vec<mir::BasicBlock>,
     loop_anchors: HashSet<mir::BasicBlock>,
+    // The blocks that end with loop_invariant! calls, by the anchors of their innermost loops
+    loop_invariants: HashMap<mir::BasicBlock, Vec<mir::BasicBlock>>,
     dominators: Dominators<mir::BasicBlock>,
     in_state: HashMap<mir::BasicBlock, Environment>,
     out_state: HashMap<mir::BasicBlock, Environment>,
     pub terminator_state: HashMap<mir::BasicBlock, Environment>,
+    join_monotonicity: JoinMonotonicity,
+    // The constants that the blocks of loop bodies compare values with, by loop anchor. These are
+    // the thresholds that widening tries before it gives up on a bound of a loop variable.
+    widening_thresholds: HashMap<mir::BasicBlock, BTreeSet<i128>>,
+    // The bounds that widening with thresholds gave the loop variables of a loop anchor, with the
+    // entry condition of the anchor without them.
+    threshold_bounds: HashMap<mir::BasicBlock, (Rc<AbstractValue>, Vec<ThresholdBound>)>,
+    // The loop anchors whose threshold bounds were revised in the current iteration, which needs
+    // another iteration to confirm them.
+    revised_threshold_bounds: HashSet<mir::BasicBlock>,
+}
+
+/// A bound that widening with thresholds gives the widened value of a loop variable. It is assumed
+/// at the loop anchor and is kept only if the loop body does not take the value past it.
+#[derive(Clone, Debug)]
+struct ThresholdBound {
+    path: Rc<Path>,
+    widened_value: Rc<AbstractValue>,
+    threshold: i128,
+    // True if the threshold is an upper bound of the value, false if it is a lower bound.
+    is_upper: bool,
+}
+
+impl ThresholdBound {
+    /// Returns the condition that the given value is within the threshold.
+    fn condition_for(&self, value: &Rc<AbstractValue>) -> Rc<AbstractValue> {
+        let threshold: Rc<AbstractValue> =
+            if self.threshold >= 0 && !value.expression.infer_type().is_signed_integer() {
+                Rc::new(ConstantDomain::U128(self.threshold as u128).into())
+            } else {
+                Rc::new(ConstantDomain::I128(self.threshold).into())
+            };
+        if self.is_upper {
+            value.less_or_equal(threshold)
+        } else {
+            value.greater_or_equal(threshold)
+        }
+    }
 }
 
 impl Debug for FixedPointVisitor<'_, '_, '_, '_> {
@@ -50,6 +95,7 @@ impl<'fixed, 'analysis, 'compilation, 'tcx>
     ) -> FixedPointVisitor<'fixed, 'analysis, 'compilation, 'tcx> {
         let dominators = body_visitor.mir.basic_blocks.dominators();
         let (block_indices, loop_anchors) = get_sorted_block_indices(body_visitor.mir, dominators);
+        let loop_invariants = find_loop_invariants(body_visitor, &loop_anchors, dominators);
         // in_state[bb] is the join (or widening) of the out_state values of each predecessor of bb
         let mut in_state: HashMap<mir::BasicBlock, Environment> = HashMap::new();
         // out_state[bb] is the environment that results from analyzing block bb, given in_state[bb]
@@ -66,10 +112,15 @@ impl<'fixed, 'analysis, 'compilation, 'tcx>
             bv: body_visitor,
             block_indices,
             loop_anchors,
+            loop_invariants,
             dominators: dominators.clone(),
             in_state,
             out_state,
             terminator_state,
+            join_monotonicity: JoinMonotonicity::default(),
+            widening_thresholds: HashMap::new(),
+            threshold_bounds: HashMap::new(),
+            revised_threshold_bounds: HashSet::new(),
         }
     }
 
@@ -87,64 +138,127 @@ impl<'fixed, 'analysis, 'compilation, 'tcx>
                 }
             }
         }
-        
-        // Emit a warning if the analyzed body contains reentrancy
-        let is_reentrancy = self.bv.reentrancy_checker.check();
-        if is_reentrancy {
-            self.bv.reentrancy_checker.ending_reentrancy_span = self.bv.current_span.hi();
-            let warning_message = "possible reentrancy for the smart contract";
-            let span = Span::new(
-                self.bv.reentrancy_checker.starting_reentrancy_span,
-                self.bv.reentrancy_checker.ending_reentrancy_span,
-                SyntaxContext::root(),
-                None,
-            );
-            let warning = self
-                .bv
-                .cv
-                .session
-                .dcx()
-                .struct_span_warn(span, warning_message);
-            self.bv.emit_diagnostic(warning);
+
+        // Emit a diagnostic for each vulnerability found in the analyzed body
+        let findings = self.bv.contract_checkers.findings(self.bv.mir);
+        for finding in findings {
+            let severity = finding.configured_severity(self.bv.cv.options);
+            let mut diagnostic = Diag::new(
+                self.bv.cv.session.dcx(),
+                severity.level(),
+                finding.diagnostic_message(),
+            )
+            .with_span(finding.span);
+            for (span, note) in finding.notes {
+                diagnostic.span_note(span, note);
+            }
+            if let Some((message, parts)) = finding.suggestion {
+                diagnostic.multipart_suggestion(message, parts, Applicability::MachineApplicable);
+            }
+            let provenance = finding.provenance.iter().map(|c| c.to_string()).collect();
+            let properties = self.bv.finding_properties(None, provenance);
+            self.bv.emit_finding(diagnostic, severity, properties);
         }
+    }
 
-        // Emit a warning if the analyzed body contains time manipulation
-        let is_time_manipulation = self.bv.time_manipulation_checker.check();
-        if is_time_manipulation {
-            let warning_message = "possible time manipulation for the smart contract";
-            let warning = self
-                .bv
+    /// Reports each reachable block that is only entered under some condition, either as a note
+    /// or as a panel of the HTML report. A report lists the conjuncts of the entry condition and,
+    /// where known, links each conjunct to the branch that introduced it.
+    #[logfn_inputs(TRACE)]
+    pub fn report_path_constraints(&mut self) {
+        let mut panels = Vec::new();
+        for bb in self.block_indices.clone() {
+            if self.bv.mir[bb].is_cleanup {
+                continue;
+            }
+            let entry_condition = self.in_state[&bb].entry_condition.clone();
+            if entry_condition.as_bool_if_known().is_some() {
+                // The block is entered unconditionally, or not at all.
+                continue;
+            }
+            let mut conjuncts = Vec::new();
+            collect_conjuncts(&entry_condition, &mut conjuncts);
+            let span = self.bv.block_span(bb);
+            let conjuncts: Vec<(String, Option<(rustc_span::Span, mir::BasicBlock)>)> = conjuncts
+                .iter()
+                .map(|c| (render_condition(self.bv.mir, c), self.find_branch(bb, c)))
+                .collect();
+            if self.bv.cv.options.path_constraints {
+                let mut note = self.bv.cv.session.dcx().struct_span_note(
+                    span,
+                    format!(
+                        "{bb:?} is entered under {} path constraint{}",
+                        conjuncts.len(),
+                        if conjuncts.len() == 1 { "" } else { "s" }
+                    ),
+                );
+                for (condition, branch) in &conjuncts {
+                    if let Some((branch_span, branch_bb)) = branch {
+                        note.span_note(
+                            *branch_span,
+                            format!("{bb:?} requires {condition} from the branch in {branch_bb:?}"),
+                        );
+                    } else {
+                        note.note(format!("{bb:?} requires {condition}"));
+                    }
+                }
+                self.bv.emit_diagnostic(note);
+            }
+            if self.bv.cv.options.path_constraints_report.is_some() {
+                panels.push(BlockPanel {
+                    block: format!("{bb:?}"),
+                    location: self.source_location(span),
+                    conjuncts: conjuncts
+                        .into_iter()
+                        .map(|(condition, branch)| ConjunctEntry {
+                            condition,
+                            branch: branch.map(|(branch_span, branch_bb)| {
+                                (format!("{branch_bb:?}"), self.source_location(branch_span))
+                            }),
+                        })
+                        .collect(),
+                });
+            }
+        }
+        if self.bv.cv.options.path_constraints_report.is_some() {
+            let name = self.bv.function_name.to_string();
+            self.bv
                 .cv
-                .session
-                .dcx()
-                .struct_span_warn(self.bv.time_manipulation_checker.time_manipulation_span, warning_message);
-            self.bv.emit_diagnostic(warning);
+                .path_constraints_report
+                .add_function(FunctionPanel {
+                    name,
+                    blocks: panels,
+                });
         }
+    }
 
-        // Emit a warning if the analyzed body contains bad randomness
-        let is_bad_randomness = self.bv.bad_randomness_checker.check();
-        if is_bad_randomness {
-            let warning_message = "possible bad randomness for the smart contract";
-            let warning = self
-                .bv
-                .cv
-                .session
-                .dcx()
-                .struct_span_warn(self.bv.bad_randomness_checker.bad_randomness_span, warning_message);
-            self.bv.emit_diagnostic(warning);
+    fn source_location(&self, span: rustc_span::Span) -> SourceLocation {
+        let location = self.bv.cv.session.source_map().lookup_char_pos(span.lo());
+        SourceLocation {
+            file: location.file.name.prefer_local().to_string(),
+            line: location.line,
+            column: location.col_display + 1,
         }
+    }
 
-        // Emit a warning if the analyzed body contains numerical precision error
-        let is_numerical_precision_error = self.bv.numerical_precision_checker.check();
-        if is_numerical_precision_error {
-            let warning_message = "possible numerical precision error for the smart contract";
-            let warning = self
+    /// Returns the span and block of the branch that introduced the given conjunct of the entry
+    /// condition of bb. This is the closest branch with the conjunct as the condition of an edge
+    /// to bb or to one of the blocks that dominate bb.
+    fn find_branch(
+        &self,
+        bb: mir::BasicBlock,
+        conjunct: &Rc<AbstractValue>,
+    ) -> Option<(rustc_span::Span, mir::BasicBlock)> {
+        let mut block = bb;
+        loop {
+            if let Some(branch) = self
                 .bv
-                .cv
-                .session
-                .dcx()
-                .struct_span_warn(self.bv.numerical_precision_checker.numerical_precision_error_span, warning_message);
-            self.bv.emit_diagnostic(warning);
+                .branch_condition_spans
+                .get(&(block, conjunct.clone()))
+            {
+                return Some(*branch);
+            }
+            block = self.dominators.immediate_dominator(block)?;
         }
     }
 
@@ -177,14 +291,32 @@ impl<'fixed, 'analysis, 'compilation, 'tcx>
             } else {
                 previous_state.widen(i_state)
             };
-            i_state.entry_condition = invariant_entry_condition;
+            i_state.entry_condition = if iteration_count == 3 {
+                self.bound_widened_values(bb, &i_state, invariant_entry_condition)
+            } else {
+                invariant_entry_condition
+            };
         } else if iteration_count > 3 {
             // From iteration 3 onwards, the entry condition is not affected by changes in the loop
-            // body, so we just stick to the one computed in iteration 3.
+            // body, so we just stick to the one computed in iteration 3, less the threshold bounds
+            // that the loop body does not preserve.
+            self.confirm_threshold_bounds(bb, iteration_count, &i_state);
             let invariant_entry_condition = self.in_state[&bb].entry_condition.clone();
             i_state = self.in_state[&bb].widen(i_state);
             i_state.entry_condition = invariant_entry_condition;
         }
+        if iteration_count == 1 {
+            self.threshold_bounds.remove(&bb);
+        }
+        if cfg!(debug_assertions) && iteration_count > 0 {
+            self.check_join_monotonicity(bb, iteration_count, &i_state);
+        }
+        self.visit_basic_block_in_state(bb, i_state);
+    }
+
+    /// Visits a single basic block, starting with the given in_state, and updates out_state with
+    /// the final current_environment of the block. Also adds the block to the already_visited set.
+    fn visit_basic_block_in_state(&mut self, bb: mir::BasicBlock, i_state: Environment) {
         self.in_state.insert(bb, i_state.clone());
         self.bv.current_environment = i_state;
         let mut block_visitor = BlockVisitor::new(self.bv);
@@ -194,10 +326,176 @@ impl<'fixed, 'analysis, 'compilation, 'tcx>
         self.already_visited.insert_mut(bb);
     }
 
+    /// Bounds the loop variables of the loop anchor bb that widening left without an upper (or
+    /// lower) bound by the smallest (or largest) comparison constant of the loop body that is
+    /// beyond the values that they took in the ascending iterations. Returns the given entry
+    /// condition of bb with these bounds, which hold if the loop body preserves them.
+    fn bound_widened_values(
+        &mut self,
+        bb: mir::BasicBlock,
+        state: &Environment,
+        entry_condition: Rc<AbstractValue>,
+    ) -> Rc<AbstractValue> {
+        let mut bounds = Vec::new();
+        for (path, value) in state.sorted_entries() {
+            let Expression::WidenedJoin {
+                path: widened_path,
+                operand,
+            } = &value.expression
+            else {
+                continue;
+            };
+            if widened_path != path || !value.expression.infer_type().is_integer() {
+                continue;
+            }
+            let joined = operand.get_cached_interval();
+            let widened = value.get_cached_interval();
+            if joined.is_bottom() || widened.is_bottom() {
+                continue;
+            }
+            if let Some(upper) = joined.upper_bound() {
+                if widened.upper_bound() != Some(upper) {
+                    let threshold = self.next_threshold(bb, upper, true);
+                    let below_widened = |t: &i128| widened.upper_bound().is_none_or(|u| *t < u);
+                    if let Some(threshold) = threshold.filter(below_widened) {
+                        bounds.push(ThresholdBound {
+                            path: path.clone(),
+                            widened_value: value.clone(),
+                            threshold,
+                            is_upper: true,
+                        });
+                    }
+                }
+            }
+            if let Some(lower) = joined.lower_bound() {
+                if widened.lower_bound() != Some(lower) {
+                    let threshold = self.next_threshold(bb, lower, false);
+                    let above_widened = |t: &i128| widened.lower_bound().is_none_or(|l| *t > l);
+                    if let Some(threshold) = threshold.filter(above_widened) {
+                        bounds.push(ThresholdBound {
+                            path: path.clone(),
+                            widened_value: value.clone(),
+                            threshold,
+                            is_upper: false,
+                        });
+                    }
+                }
+            }
+        }
+        let bounded_entry_condition = bounded_entry_condition(&entry_condition, &bounds);
+        self.threshold_bounds.insert(bb, (entry_condition, bounds));
+        bounded_entry_condition
+    }
+
+    /// Returns the smallest threshold of the loop anchor bb that is at least the given value, or
+    /// the largest threshold that is at most the given value if the bound is a lower bound.
+    fn next_threshold(&self, bb: mir::BasicBlock, value: i128, is_upper: bool) -> Option<i128> {
+        let thresholds = self.widening_thresholds.get(&bb)?;
+        if is_upper {
+            thresholds.range(value..).next().copied()
+        } else {
+            thresholds.range(..=value).next_back().copied()
+        }
+    }
+
+    /// Checks that the values that the loop back edges of bb give to the loop variables with
+    /// threshold bounds are within the bounds. A bound that is not known to be preserved is
+    /// replaced with the next threshold, or dropped if there is none or too few iterations are
+    /// left to confirm it. The entry condition of bb is updated if a bound is revised.
+    fn confirm_threshold_bounds(
+        &mut self,
+        bb: mir::BasicBlock,
+        iteration_count: usize,
+        incoming_state: &Environment,
+    ) {
+        let Some((entry_condition, bounds)) = self.threshold_bounds.get(&bb).cloned() else {
+            return;
+        };
+        if bounds.is_empty() {
+            return;
+        }
+        let may_revise = iteration_count + 1 < self.bv.cv.options.k_limits.max_fixpoint_iterations;
+        let saved_environment =
+            std::mem::replace(&mut self.bv.current_environment, incoming_state.clone());
+        let mut confirmed_bounds = Vec::new();
+        let mut revised = false;
+        for bound in bounds {
+            let is_current = self.in_state[&bb].value_at(&bound.path) == Some(&bound.widened_value);
+            if let (true, Some(value)) = (is_current, incoming_state.value_at(&bound.path)) {
+                let condition = bound.condition_for(value);
+                let (holds, is_reachable) =
+                    self.bv.check_condition_value_and_reachability(&condition);
+                if holds == Some(true) || is_reachable == Some(false) {
+                    confirmed_bounds.push(bound);
+                    continue;
+                }
+                let beyond = if bound.is_upper {
+                    bound.threshold.checked_add(1)
+                } else {
+                    bound.threshold.checked_sub(1)
+                };
+                if let Some(threshold) = beyond
+                    .filter(|_| may_revise)
+                    .and_then(|beyond| self.next_threshold(bb, beyond, bound.is_upper))
+                {
+                    confirmed_bounds.push(ThresholdBound { threshold, ..bound });
+                }
+            }
+            revised = true;
+        }
+        self.bv.current_environment = saved_environment;
+        if revised {
+            if let Some(state) = self.in_state.get_mut(&bb) {
+                state.entry_condition =
+                    bounded_entry_condition(&entry_condition, &confirmed_bounds);
+            }
+            self.threshold_bounds
+                .insert(bb, (entry_condition, confirmed_bounds));
+            self.revised_threshold_bounds.insert(bb);
+        }
+    }
+
+    /// Checks that the value of every path at the start of the loop anchor bb is above its value in
+    /// the previous iteration, logging each path for which it is not before failing. If
+    /// HEPHA_DUMP_NON_MONOTONE_MIR is set, the MIR of the body is printed as well.
+    fn check_join_monotonicity(
+        &mut self,
+        bb: mir::BasicBlock,
+        iteration_count: usize,
+        i_state: &Environment,
+    ) {
+        if iteration_count == 1 {
+            self.join_monotonicity.restart(bb);
+        }
+        let violations = self.join_monotonicity.record(bb, i_state);
+        for violation in violations.iter() {
+            warn!(
+                "non monotone join at {:?} in iteration {} of {}: {:?} went from {:?} to {:?}",
+                violation.block,
+                iteration_count,
+                self.bv.function_name,
+                violation.path,
+                violation.previous,
+                violation.current
+            );
+        }
+        if !violations.is_empty() && std::env::var("HEPHA_DUMP_NON_MONOTONE_MIR").is_ok() {
+            utils::pretty_print_mir(self.bv.tcx, self.bv.def_id);
+        }
+        debug_assert!(
+            violations.is_empty(),
+            "non monotone join in {}",
+            self.bv.function_name
+        );
+    }
+
     /// Repeatedly evaluate the loop body starting at loop_anchor until widening
     /// kicked in and a fixed point has been reached.
     #[logfn_inputs(TRACE)]
     fn compute_fixed_point(&mut self, loop_anchor: mir::BasicBlock) -> mir::BasicBlock {
+        if let Some(last_block) = self.unroll_counted_loop(loop_anchor) {
+            return last_block;
+        }
         let saved_already_visited = self.already_visited.clone();
         let saved_fresh_variable_offset = self.bv.fresh_variable_offset;
         let mut iteration_count = 1;
@@ -208,16 +506,21 @@ impl<'fixed, 'analysis, 'compilation, 'tcx>
         while iteration_count <= 4 || changed {
             self.already_visited = saved_already_visited.clone();
             self.bv.fresh_variable_offset = saved_fresh_variable_offset;
+            for block in self.loop_invariants.get(&loop_anchor).into_iter().flatten() {
+                self.bv
+                    .loop_invariant_iterations
+                    .insert(*block, (loop_anchor, iteration_count));
+            }
             let result = self.visit_loop_body(loop_anchor, iteration_count);
             changed = result.0;
             last_block = result.1;
             check_for_early_break!(self.bv);
-            if iteration_count >= k_limits::MAX_FIXPOINT_ITERATIONS {
+            if iteration_count >= self.bv.cv.options.k_limits.max_fixpoint_iterations {
                 break;
             }
             iteration_count += 1;
         }
-        if iteration_count >= k_limits::MAX_FIXPOINT_ITERATIONS {
+        if iteration_count >= self.bv.cv.options.k_limits.max_fixpoint_iterations {
             if changed {
                 if self.bv.cv.options.diag_level == DiagLevel::Paranoid {
                     let span = self.bv.current_span;
@@ -225,7 +528,7 @@ impl<'fixed, 'analysis, 'compilation, 'tcx>
                         span,
                         format!(
                             "Fixed point loop iterations exceeded limit of {}",
-                            k_limits::MAX_FIXPOINT_ITERATIONS
+                            self.bv.cv.options.k_limits.max_fixpoint_iterations
                         ),
                     );
                     self.bv.emit_diagnostic(warning);
@@ -233,7 +536,7 @@ impl<'fixed, 'analysis, 'compilation, 'tcx>
                     warn!(
                         "Fixed point loop iterations {} exceeded limit of {} at {:?} in function {}.",
                         iteration_count,
-                        k_limits::MAX_FIXPOINT_ITERATIONS,
+                        self.bv.cv.options.k_limits.max_fixpoint_iterations,
                         self.bv.current_span,
                         self.bv.function_name
                     );
@@ -242,7 +545,7 @@ impl<'fixed, 'analysis, 'compilation, 'tcx>
                 trace!(
                     "Fixed point loop iterations {} exceeded limit of {} at {:?} in function {}.",
                     iteration_count,
-                    k_limits::MAX_FIXPOINT_ITERATIONS,
+                    self.bv.cv.options.k_limits.max_fixpoint_iterations,
                     self.bv.current_span,
                     self.bv.function_name
                 );
@@ -251,6 +554,141 @@ impl<'fixed, 'analysis, 'compilation, 'tcx>
         last_block
     }
 
+    /// Visits the body of the loop at loop_anchor once for every iteration of the loop, if the loop
+    /// always ends after a number of iterations that is a constant no larger than
+    /// --max-loop-unrolling, so that the loop variables keep the values that they have in each
+    /// iteration rather than being widened. The states of the blocks of the body are the joins of
+    /// their states in the iterations and the blocks after the loop see the state that the last
+    /// iteration leaves. Returns the last block of the loop body, or None if the trip count of
+    /// the loop is not such a constant, in which case the states are left as they were.
+    #[logfn_inputs(TRACE)]
+    fn unroll_counted_loop(&mut self, loop_anchor: mir::BasicBlock) -> Option<mir::BasicBlock> {
+        if self.loop_invariants.contains_key(&loop_anchor) {
+            // The invariant is checked by the fixed point computation.
+            return None;
+        }
+        let loop_blocks = self.loop_blocks(loop_anchor);
+        let saved_already_visited = self.already_visited.clone();
+        let saved_fresh_variable_offset = self.bv.fresh_variable_offset;
+        let saved_states = (
+            self.in_state.clone(),
+            self.out_state.clone(),
+            self.terminator_state.clone(),
+        );
+        let mut unrolled_states: HashMap<mir::BasicBlock, (Environment, Environment)> =
+            HashMap::new();
+        for trip_count in 0..=self.bv.cv.options.k_limits.max_loop_unrolling {
+            check_for_early_break!(self.bv);
+            self.already_visited = saved_already_visited.clone();
+            let last_block = self.visit_unrolled_iteration(loop_anchor, trip_count == 0);
+            for (bb, state) in self.in_state.iter() {
+                if saved_already_visited.contains(bb)
+                    || !self.dominators.dominates(loop_anchor, *bb)
+                    || state.entry_condition.as_bool_if_known() == Some(false)
+                {
+                    continue;
+                }
+                let terminator_state = &self.terminator_state[bb];
+                let unrolled_state = match unrolled_states.remove(bb) {
+                    Some((in_state, previous_terminator_state)) => (
+                        join_iterations(&in_state, state.clone()),
+                        join_iterations(&previous_terminator_state, terminator_state.clone()),
+                    ),
+                    None => (state.clone(), terminator_state.clone()),
+                };
+                unrolled_states.insert(*bb, unrolled_state);
+            }
+            if !self.is_entered_again(loop_anchor) {
+                for (bb, (in_state, terminator_state)) in unrolled_states {
+                    self.in_state.insert(bb, in_state);
+                    self.terminator_state.insert(bb, terminator_state);
+                }
+                return Some(last_block);
+            }
+            if self.may_leave_loop(&loop_blocks) {
+                // The loop ends in an iteration that depends on values that are not constants.
+                break;
+            }
+        }
+        self.already_visited = saved_already_visited;
+        self.bv.fresh_variable_offset = saved_fresh_variable_offset;
+        (self.in_state, self.out_state, self.terminator_state) = saved_states;
+        None
+    }
+
+    /// Visits the blocks of the loop body at loop_anchor for a single iteration of the loop,
+    /// starting the loop anchor in the state before the loop, if first is true, or else in the
+    /// state that the previous iteration leaves. Returns the last block visited.
+    fn visit_unrolled_iteration(
+        &mut self,
+        loop_anchor: mir::BasicBlock,
+        first: bool,
+    ) -> mir::BasicBlock {
+        let mut last_block = loop_anchor;
+        for bb in self.block_indices.clone() {
+            check_for_early_break!(self.bv);
+            if !self.already_visited.contains(&bb) && self.dominators.dominates(loop_anchor, bb) {
+                last_block = bb;
+                if bb == loop_anchor {
+                    // Only the loop back edges flow into the anchor after the first iteration.
+                    let iteration_count = if first { 1 } else { 2 };
+                    let i_state = self.get_initial_state_from_predecessors(bb, iteration_count);
+                    self.visit_basic_block_in_state(bb, i_state);
+                } else if self.loop_anchors.contains(&bb) {
+                    last_block = self.compute_fixed_point(bb);
+                } else {
+                    self.visit_basic_block(bb, 0);
+                }
+            }
+        }
+        last_block
+    }
+
+    /// Returns the blocks of the loop at loop_anchor, which are the blocks dominated by the anchor
+    /// from which the anchor can be reached again.
+    fn loop_blocks(&self, loop_anchor: mir::BasicBlock) -> HashSet<mir::BasicBlock> {
+        let predecessors = self.bv.mir.basic_blocks.predecessors();
+        let mut loop_blocks = HashSet::from([loop_anchor]);
+        let mut to_visit: Vec<mir::BasicBlock> = predecessors[loop_anchor].to_vec();
+        while let Some(bb) = to_visit.pop() {
+            if self.dominators.dominates(loop_anchor, bb) && loop_blocks.insert(bb) {
+                to_visit.extend(predecessors[bb].iter());
+            }
+        }
+        loop_blocks
+    }
+
+    /// True if a loop back edge to loop_anchor may be taken in the state that the blocks of the
+    /// loop body were last visited in.
+    fn is_entered_again(&self, loop_anchor: mir::BasicBlock) -> bool {
+        self.bv.mir.basic_blocks.predecessors()[loop_anchor]
+            .iter()
+            .filter(|pred_bb| self.dominators.dominates(loop_anchor, **pred_bb))
+            .any(|pred_bb| {
+                self.out_state[pred_bb]
+                    .exit_conditions
+                    .get(&loop_anchor)
+                    .is_some_and(|c| c.as_bool_if_known() != Some(false))
+            })
+    }
+
+    /// True if an edge from one of the given loop blocks to a block outside of the loop, other
+    /// than a clean up block, may be taken in the state that the blocks were last visited in.
+    fn may_leave_loop(&self, loop_blocks: &HashSet<mir::BasicBlock>) -> bool {
+        loop_blocks.iter().any(|bb| {
+            self.bv.mir[*bb]
+                .terminator()
+                .successors()
+                .filter(|succ| !loop_blocks.contains(succ) && !self.bv.mir[*succ].is_cleanup)
+                .any(|succ| {
+                    self.out_state[bb]
+                        .exit_conditions
+                        .get(&succ)
+                        .is_some_and(|c| c.as_bool_if_known() != Some(false))
+                })
+        })
+    }
+
     /// Visits a loop body. Return true if the out_state computed by this visit is not a subset
     /// of the out_state computed previously. When it is a subset, a fixed point has been reached.
     /// A loop body is all of the blocks that are dominated by the loop anchor.
@@ -276,6 +714,13 @@ impl<'fixed, 'analysis, 'compilation, 'tcx>
                 } else {
                     self.visit_basic_block(bb, 0); // conditional expressions
                 }
+                if iteration_count <= 2 {
+                    // Collect the thresholds for widening while the loop body is ascending.
+                    let constants = BlockVisitor::new(self.bv).comparison_constants(bb);
+                    let thresholds = self.widening_thresholds.entry(loop_anchor).or_default();
+                    thresholds.extend(constants);
+                    thresholds.insert(self.bv.cv.options.k_limits.max_elements_to_track as i128);
+                }
 
                 // Check for a fixed point, once two iterations with widened variables were executed.
                 if iteration_count > 3
@@ -288,6 +733,9 @@ impl<'fixed, 'analysis, 'compilation, 'tcx>
                 }
             }
         }
+        if self.revised_threshold_bounds.remove(&loop_anchor) {
+            changed = true;
+        }
         (changed, last_block)
     }
 
@@ -393,6 +841,57 @@ impl<'fixed, 'analysis, 'compilation, 'tcx>
     }
 }
 
+/// The values of the paths at the start of loop anchors in the successive iterations of fixed point
+/// computations. The state at the start of an anchor is the join (or widening) of its state in the
+/// previous iteration with the states of the loop back edges, so the value of a path can only go up
+/// from one iteration to the next. If it does not, a transfer function is not monotone and the
+/// computation may not converge.
+#[derive(Default)]
+pub struct JoinMonotonicity {
+    values: HashMap<(mir::BasicBlock, Rc<Path>), Vec<Rc<AbstractValue>>>,
+}
+
+/// A path whose value at the start of a block is not known to be above its previous value.
+#[derive(Debug)]
+pub struct MonotonicityViolation {
+    pub block: mir::BasicBlock,
+    pub path: Rc<Path>,
+    pub previous: Rc<AbstractValue>,
+    pub current: Rc<AbstractValue>,
+}
+
+impl JoinMonotonicity {
+    /// Forgets the values recorded for bb, so that a new fixed point computation can start there.
+    pub fn restart(&mut self, bb: mir::BasicBlock) {
+        self.values.retain(|(block, _), _| *block != bb);
+    }
+
+    /// Records the values of the paths of state at the start of an iteration of bb and returns
+    /// the paths whose values are not above their values in the previous iteration.
+    pub fn record(
+        &mut self,
+        bb: mir::BasicBlock,
+        state: &Environment,
+    ) -> Vec<MonotonicityViolation> {
+        let mut violations = Vec::new();
+        for (path, value) in state.value_map.iter() {
+            let values = self.values.entry((bb, path.clone())).or_default();
+            if let Some(previous) = values.last() {
+                if !previous.is_below(value) {
+                    violations.push(MonotonicityViolation {
+                        block: bb,
+                        path: path.clone(),
+                        previous: previous.clone(),
+                        current: value.clone(),
+                    });
+                }
+            }
+            values.push(value.clone());
+        }
+        violations
+    }
+}
+
 /// Do a topological sort, breaking loops by preferring lower block indices, using dominance
 /// to determine if there is a loop (if a is predecessor of b and b dominates a then they
 /// form a loop and we'll emit the one with the lower index first).
@@ -455,3 +954,165 @@ fn get_sorted_block_indices<'tcx>(
     }
     (block_indices, loop_anchors)
 }
+
+// Returns the blocks that end with calls of hepha_loop_invariant by the anchors of the innermost
+// loops whose bodies they are in, which are the loop anchors that dominate them.
+fn find_loop_invariants(
+    bv: &mut BodyVisitor<'_, '_, '_>,
+    loop_anchors: &HashSet<mir::BasicBlock>,
+    dominators: &Dominators<mir::BasicBlock>,
+) -> HashMap<mir::BasicBlock, Vec<mir::BasicBlock>> {
+    let mut loop_invariants: HashMap<mir::BasicBlock, Vec<mir::BasicBlock>> = HashMap::new();
+    for (bb, data) in bv.mir.basic_blocks.iter_enumerated() {
+        let mir::TerminatorKind::Call { func, .. } = &data.terminator().kind else {
+            continue;
+        };
+        let Some((def_id, _)) = func.const_fn_def() else {
+            continue;
+        };
+        if bv.cv.known_names_cache.get(bv.tcx, def_id) != KnownNames::MiraiLoopInvariant {
+            continue;
+        }
+        // The anchors of the loops around the block dominate one another
+        let innermost_anchor = loop_anchors
+            .iter()
+            .filter(|anchor| dominators.dominates(**anchor, bb))
+            .copied()
+            .reduce(|a1, a2| if dominators.dominates(a1, a2) { a2 } else { a1 });
+        if let Some(anchor) = innermost_anchor {
+            loop_invariants.entry(anchor).or_default().push(bb);
+        }
+    }
+    loop_invariants
+}
+
+// Returns the join of the states of a block in two iterations of an unrolled loop, which is entered
+// if the block is entered in either iteration.
+fn join_iterations(state: &Environment, other: Environment) -> Environment {
+    let entry_condition = state.entry_condition.or(other.entry_condition.clone());
+    let mut joined_state = state.join(other);
+    joined_state.entry_condition = entry_condition;
+    joined_state
+}
+
+// Returns the given entry condition of a loop anchor with the given bounds of its loop variables.
+fn bounded_entry_condition(
+    entry_condition: &Rc<AbstractValue>,
+    bounds: &[ThresholdBound],
+) -> Rc<AbstractValue> {
+    bounds
+        .iter()
+        .fold(entry_condition.clone(), |condition, bound| {
+            condition.and(bound.condition_for(&bound.widened_value))
+        })
+}
+
+// Splits a condition into the operands of its (nested) conjunctions.
+pub(crate) fn collect_conjuncts(
+    condition: &Rc<AbstractValue>,
+    conjuncts: &mut Vec<Rc<AbstractValue>>,
+) {
+    if let Expression::And { left, right } = &condition.expression {
+        collect_conjuncts(left, conjuncts);
+        collect_conjuncts(right, conjuncts);
+    } else {
+        conjuncts.push(condition.clone());
+    }
+}
+
+// Renders a condition in the syntax of Rust, using the names of the variables of the body.
+// Expressions without a Rust counterpart fall back to their debug representation.
+pub(crate) fn render_condition(mir: &mir::Body<'_>, condition: &Rc<AbstractValue>) -> String {
+    if let Some((left, operator, right)) = binary_operation(condition) {
+        return format!(
+            "{} {operator} {}",
+            render_operand(mir, left),
+            render_operand(mir, right)
+        );
+    }
+    match &condition.expression {
+        Expression::CompileTimeConstant(ConstantDomain::I128(value)) => value.to_string(),
+        Expression::CompileTimeConstant(ConstantDomain::U128(value)) => value.to_string(),
+        Expression::CompileTimeConstant(constant) => format!("{constant:?}"),
+        Expression::InitialParameterValue { path, .. } | Expression::Variable { path, .. } => {
+            render_path(mir, path)
+        }
+        Expression::LogicalNot { operand } => format!("!{}", render_operand(mir, operand)),
+        Expression::Neg { operand } => format!("-{}", render_operand(mir, operand)),
+        _ => format!("{condition:?}"),
+    }
+}
+
+// Returns the operands and the Rust operator of an expression that has a binary operator
+// counterpart in Rust.
+pub(crate) fn binary_operation(
+    value: &Rc<AbstractValue>,
+) -> Option<(&Rc<AbstractValue>, &'static str, &Rc<AbstractValue>)> {
+    let (left, operator, right) = match &value.expression {
+        Expression::Add { left, right } => (left, "+", right),
+        Expression::And { left, right } => (left, "&&", right),
+        Expression::BitAnd { left, right } => (left, "&", right),
+        Expression::BitOr { left, right } => (left, "|", right),
+        Expression::BitXor { left, right } => (left, "^", right),
+        Expression::Div { left, right } => (left, "/", right),
+        Expression::Equals { left, right } => (left, "==", right),
+        Expression::GreaterOrEqual { left, right } => (left, ">=", right),
+        Expression::GreaterThan { left, right } => (left, ">", right),
+        Expression::LessOrEqual { left, right } => (left, "<=", right),
+        Expression::LessThan { left, right } => (left, "<", right),
+        Expression::Mul { left, right } => (left, "*", right),
+        Expression::Ne { left, right } => (left, "!=", right),
+        Expression::Or { left, right } => (left, "||", right),
+        Expression::Rem { left, right } => (left, "%", right),
+        Expression::Shl { left, right } => (left, "<<", right),
+        Expression::Shr { left, right } => (left, ">>", right),
+        Expression::Sub { left, right } => (left, "-", right),
+        _ => return None,
+    };
+    Some((left, operator, right))
+}
+
+// Renders an operand of an expression, in parentheses unless it is atomic.
+fn render_operand(mir: &mir::Body<'_>, operand: &Rc<AbstractValue>) -> String {
+    let rendered = render_condition(mir, operand);
+    match &operand.expression {
+        Expression::CompileTimeConstant(..)
+        | Expression::InitialParameterValue { .. }
+        | Expression::LogicalNot { .. }
+        | Expression::Neg { .. }
+        | Expression::Variable { .. } => rendered,
+        _ => format!("({rendered})"),
+    }
+}
+
+// Renders a path rooted in a local as a Rust place expression, using the name of the local.
+pub(crate) fn render_path(mir: &mir::Body<'_>, path: &Rc<Path>) -> String {
+    match &path.value {
+        PathEnum::Parameter { ordinal } | PathEnum::LocalVariable { ordinal, .. } => mir
+            .var_debug_info
+            .iter()
+            .find(|info| {
+                matches!(info.value, mir::VarDebugInfoContents::Place(place)
+                    if place.projection.is_empty() && place.local.as_usize() == *ordinal)
+            })
+            .map(|info| info.name.to_string())
+            .unwrap_or_else(|| format!("_{ordinal}")),
+        PathEnum::Result => "_0".to_string(),
+        PathEnum::QualifiedPath {
+            qualifier,
+            selector,
+            ..
+        } => {
+            let qualifier = render_path(mir, qualifier);
+            match selector.as_ref() {
+                PathSelector::Deref => format!("*{qualifier}"),
+                PathSelector::Field(index) => format!("{qualifier}.{index}"),
+                PathSelector::Index(index) => {
+                    format!("{qualifier}[{}]", render_condition(mir, index))
+                }
+                _ => format!("{qualifier}.{selector:?}"),
+            }
+        }
+        _ => format!("{path:?}"),
+    }
+}
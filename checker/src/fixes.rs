@@ -0,0 +1,467 @@
+// Machine applicable fixes of findings and the unified diff that applies them. The checkers
+// attach fixes to their diagnostics as suggestions, so that they are shown along with the
+// findings, and --emit-fixes collects the suggestions of the reported findings into a patch.
+// Fixes are only made where the rewritten code is known to compile, which for now means in
+// functions that return a ProgramError of solana_program.
+
+use std::collections::BTreeMap;
+use std::io;
+
+use rustc_ast::util::parser::ExprPrecedence;
+use rustc_errors::{Applicability, DiagInner, Suggestions};
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::LocalDefId;
+use rustc_hir::intravisit::{self, Visitor};
+use rustc_hir::{BinOpKind, Expr, ExprKind, PatKind, Stmt};
+use rustc_middle::ty::{TyCtxt, TyKind};
+use rustc_span::source_map::SourceMap;
+use rustc_span::{BytePos, FileName, RealFileName, Span};
+
+/// The path by which the fixes name ProgramError, which is valid in every crate that depends on
+/// solana_program.
+const PROGRAM_ERROR: &str = "solana_program::program_error::ProgramError";
+
+/// The number of unchanged lines shown around the changed lines of a patch.
+const CONTEXT_LINES: usize = 3;
+
+/// A replacement of the text of a byte range of a source file.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Replacement {
+    pub file: String,
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+impl Replacement {
+    // Two replacements conflict if their ranges overlap or if they start at the same position,
+    // since the order of two insertions at the same position is not known.
+    fn conflicts_with(&self, other: &Replacement) -> bool {
+        self.file == other.file
+            && (self.start == other.start || (self.start < other.end && other.start < self.end))
+    }
+}
+
+/// The replacements of one suggestion, which are applied together or not at all.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Fix {
+    pub replacements: Vec<Replacement>,
+}
+
+/// Returns a rewrite of the addition, subtraction or multiplication at the given span into a
+/// checked operation whose overflow is returned as a ProgramError, if the operation is on
+/// integers and in a function that returns Result<_, ProgramError>.
+pub fn checked_arithmetic(tcx: TyCtxt<'_>, span: Span) -> Option<Vec<(Span, String)>> {
+    if span.from_expansion() {
+        return None;
+    }
+    let owner = tcx
+        .hir()
+        .body_owners()
+        .filter(|owner| tcx.def_kind(*owner).is_fn_like())
+        .filter(|owner| tcx.hir().body_owned_by(*owner).value.span.contains(span))
+        .min_by_key(|owner| {
+            let body_span = tcx.hir().body_owned_by(*owner).value.span;
+            body_span.hi() - body_span.lo()
+        })?;
+    if !returns_program_error(tcx, owner) {
+        return None;
+    }
+    let body = tcx.hir().body_owned_by(owner);
+    let mut finder = ExpressionFinder {
+        span,
+        expression: None,
+    };
+    finder.visit_body(body);
+    let expression = finder.expression?;
+    let (operator, left, right, is_assignment) = match expression.kind {
+        ExprKind::Binary(operator, left, right) => (operator.node, left, right, false),
+        ExprKind::AssignOp(operator, left, right) => (operator.node, left, right, true),
+        _ => return None,
+    };
+    let method = match operator {
+        BinOpKind::Add => "checked_add",
+        BinOpKind::Sub => "checked_sub",
+        BinOpKind::Mul => "checked_mul",
+        _ => return None,
+    };
+    let typeck_results = tcx.typeck(owner);
+    if !typeck_results.expr_ty(left).is_integral() || !typeck_results.expr_ty(right).is_integral() {
+        return None;
+    }
+    let source_map = tcx.sess.source_map();
+    let left_text = source_map.span_to_snippet(left.span).ok()?;
+    let right_text = source_map.span_to_snippet(right.span).ok()?;
+    let receiver = if left.precedence() < ExprPrecedence::Unambiguous {
+        format!("({left_text})")
+    } else {
+        left_text.clone()
+    };
+    let checked =
+        format!("{receiver}.{method}({right_text}).ok_or({PROGRAM_ERROR}::ArithmeticOverflow)?");
+    let replacement = if is_assignment {
+        format!("{left_text} = {checked}")
+    } else {
+        checked
+    };
+    Some(vec![(span, replacement)])
+}
+
+/// Returns the insertion of a guard that returns an error unless the owner of the account whose
+/// data is borrowed by the method call at the given span is the program. The guard is inserted
+/// before the statement of the call, in an entrypoint whose first parameter is the program id.
+pub fn owner_guard(
+    tcx: TyCtxt<'_>,
+    entrypoint: LocalDefId,
+    borrow_span: Span,
+) -> Option<Vec<(Span, String)>> {
+    if borrow_span.from_expansion() || !returns_program_error(tcx, entrypoint) {
+        return None;
+    }
+    let body = tcx.hir().maybe_body_owned_by(entrypoint)?;
+    let PatKind::Binding(_, _, program_id, None) = body.params.first()?.pat.kind else {
+        return None;
+    };
+    let mut finder = StatementFinder {
+        span: borrow_span,
+        call: None,
+        statement: None,
+    };
+    finder.visit_body(body);
+    let ExprKind::MethodCall(_, receiver, ..) = finder.call?.kind else {
+        return None;
+    };
+    // The data of the account is borrowed either by an accessor of the account or through its
+    // data field
+    let account = match receiver.kind {
+        ExprKind::Field(account, field) if field.name.as_str() == "data" => account,
+        _ => receiver,
+    };
+    let statement = finder.statement?;
+    let source_map = tcx.sess.source_map();
+    let account = source_map.span_to_snippet(account.span).ok()?;
+    let line_start = line_start(source_map, statement.span.lo())?;
+    let indentation = source_map
+        .span_to_snippet(
+            statement
+                .span
+                .with_lo(line_start)
+                .with_hi(statement.span.lo()),
+        )
+        .ok()?;
+    if !indentation.chars().all(char::is_whitespace) {
+        return None;
+    }
+    let guard = format!(
+        "{indentation}if {account}.owner != {program_id} {{\n\
+         {indentation}    return Err({PROGRAM_ERROR}::IncorrectProgramId);\n\
+         {indentation}}}\n"
+    );
+    let insertion_point = statement.span.with_lo(line_start).with_hi(line_start);
+    Some(vec![(insertion_point, guard)])
+}
+
+// True if the function returns Result<_, ProgramError> and the crate depends on solana_program,
+// so that the fixes can name ProgramError and use ? and return with it.
+fn returns_program_error(tcx: TyCtxt<'_>, def_id: LocalDefId) -> bool {
+    let depends_on_solana_program = tcx.crates(()).iter().any(|crate_num| {
+        tcx.crate_name(*crate_num).as_str() == "solana_program"
+            && tcx
+                .extern_crate(*crate_num)
+                .is_some_and(|extern_crate| extern_crate.is_direct())
+    });
+    if !depends_on_solana_program || !matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+    {
+        return false;
+    }
+    let output = tcx.fn_sig(def_id).skip_binder().output().skip_binder();
+    let TyKind::Adt(def, args) = output.kind() else {
+        return false;
+    };
+    if !tcx.is_diagnostic_item(rustc_span::sym::Result, def.did()) {
+        return false;
+    }
+    matches!(args.type_at(1).kind(), TyKind::Adt(error, _)
+        if tcx.item_name(error.did()).as_str() == "ProgramError")
+}
+
+// Returns the position of the start of the line that contains the given position.
+fn line_start(source_map: &SourceMap, position: BytePos) -> Option<BytePos> {
+    let file_and_line = source_map.lookup_line(position).ok()?;
+    Some(file_and_line.sf.line_bounds(file_and_line.line).start)
+}
+
+// Finds the expression with the given span.
+struct ExpressionFinder<'hir> {
+    span: Span,
+    expression: Option<&'hir Expr<'hir>>,
+}
+
+impl<'hir> Visitor<'hir> for ExpressionFinder<'hir> {
+    fn visit_expr(&mut self, expression: &'hir Expr<'hir>) {
+        if expression.span == self.span
+            && matches!(
+                expression.kind,
+                ExprKind::Binary(..) | ExprKind::AssignOp(..)
+            )
+        {
+            self.expression = Some(expression);
+            return;
+        }
+        intravisit::walk_expr(self, expression);
+    }
+}
+
+// Finds the method call with the given span and the innermost statement that contains it.
+struct StatementFinder<'hir> {
+    span: Span,
+    call: Option<&'hir Expr<'hir>>,
+    statement: Option<&'hir Stmt<'hir>>,
+}
+
+impl<'hir> Visitor<'hir> for StatementFinder<'hir> {
+    fn visit_stmt(&mut self, statement: &'hir Stmt<'hir>) {
+        if self.call.is_none() && statement.span.contains(self.span) {
+            self.statement = Some(statement);
+        }
+        intravisit::walk_stmt(self, statement);
+    }
+
+    fn visit_expr(&mut self, expression: &'hir Expr<'hir>) {
+        if self.call.is_none()
+            && expression.span == self.span
+            && matches!(expression.kind, ExprKind::MethodCall(..))
+        {
+            self.call = Some(expression);
+        }
+        intravisit::walk_expr(self, expression);
+    }
+}
+
+/// Returns the machine applicable suggestions of a diagnostic as fixes. Suggestions with
+/// alternatives contribute their first alternative.
+pub fn machine_applicable_fixes(diagnostic: &DiagInner, source_map: &SourceMap) -> Vec<Fix> {
+    let suggestions = match &diagnostic.suggestions {
+        Suggestions::Enabled(suggestions) => &suggestions[..],
+        Suggestions::Sealed(suggestions) => &suggestions[..],
+        Suggestions::Disabled => &[],
+    };
+    let mut fixes = Vec::new();
+    for suggestion in suggestions {
+        if suggestion.applicability != Applicability::MachineApplicable {
+            continue;
+        }
+        let Some(substitution) = suggestion.substitutions.first() else {
+            continue;
+        };
+        let replacements: Option<Vec<Replacement>> = substitution
+            .parts
+            .iter()
+            .map(|part| {
+                let FileName::Real(RealFileName::LocalPath(path)) =
+                    source_map.span_to_filename(part.span)
+                else {
+                    return None;
+                };
+                let start = source_map.lookup_byte_offset(part.span.lo()).pos;
+                let end = source_map.lookup_byte_offset(part.span.hi()).pos;
+                Some(Replacement {
+                    file: path.to_string_lossy().to_string(),
+                    start: start.0 as usize,
+                    end: end.0 as usize,
+                    text: part.snippet.clone(),
+                })
+            })
+            .collect();
+        if let Some(mut replacements) = replacements {
+            replacements.sort();
+            fixes.push(Fix { replacements });
+        }
+    }
+    fixes
+}
+
+/// Renders the fixes as a unified diff of the files that they change, with the files read by the
+/// given function. Fixes are taken in order of their first replacement and a fix that conflicts
+/// with a fix that was taken is skipped, so that the patch does not depend on the order in which
+/// the findings were reported.
+pub fn unified_diff(
+    mut fixes: Vec<Fix>,
+    read_file: impl Fn(&str) -> io::Result<String>,
+) -> io::Result<String> {
+    fixes.sort();
+    fixes.dedup();
+    let mut accepted: Vec<Replacement> = Vec::new();
+    for fix in fixes {
+        if fix
+            .replacements
+            .iter()
+            .all(|r| accepted.iter().all(|a| !r.conflicts_with(a)))
+        {
+            accepted.extend(fix.replacements);
+        }
+    }
+    let mut replacements_for: BTreeMap<String, Vec<Replacement>> = BTreeMap::new();
+    for replacement in accepted {
+        replacements_for
+            .entry(replacement.file.clone())
+            .or_default()
+            .push(replacement);
+    }
+    let mut diff = String::new();
+    for (file, mut replacements) in replacements_for {
+        replacements.sort();
+        let text = read_file(&file)?;
+        diff.push_str(&file_diff(&file, &text, &replacements));
+    }
+    Ok(diff)
+}
+
+/// Writes the fixes as a unified diff to the file at the given path.
+pub fn write_patch(path: &std::path::Path, fixes: Vec<Fix>) -> io::Result<()> {
+    let current_directory = std::env::current_dir()?;
+    let mut diff = unified_diff(fixes, |file| std::fs::read_to_string(file))?;
+    // The files are named relative to the current directory where possible
+    if let Some(prefix) = current_directory.to_str() {
+        diff = diff.replace(&format!("a/{prefix}/"), "a/");
+        diff = diff.replace(&format!("b/{prefix}/"), "b/");
+    }
+    std::fs::write(path, diff)
+}
+
+// A range of lines of a file, given by the index of its first line and the number of lines, and
+// the lines that replace it.
+struct Change {
+    first_line: usize,
+    line_count: usize,
+    new_lines: Vec<String>,
+}
+
+// Renders the diff of a file with the given text and sorted, non-overlapping replacements.
+fn file_diff(file: &str, text: &str, replacements: &[Replacement]) -> String {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let mut line_starts = Vec::with_capacity(lines.len() + 1);
+    let mut position = 0;
+    for line in &lines {
+        line_starts.push(position);
+        position += line.len();
+    }
+    line_starts.push(position);
+    let line_of = |position: usize| line_starts.partition_point(|start| *start <= position) - 1;
+
+    // The replacements that touch the same lines are applied together
+    let mut changes: Vec<Change> = Vec::new();
+    let mut index = 0;
+    while index < replacements.len() {
+        let first_line = line_of(replacements[index].start).min(lines.len().saturating_sub(1));
+        let mut last_line = line_of(replacements[index].end.max(replacements[index].start + 1) - 1);
+        let mut next = index + 1;
+        while next < replacements.len() && line_of(replacements[next].start) <= last_line {
+            last_line = last_line.max(line_of(replacements[next].end.max(1) - 1));
+            next += 1;
+        }
+        let last_line = last_line.min(lines.len().saturating_sub(1)).max(first_line);
+        let mut new_text = String::new();
+        let mut copied_up_to = line_starts[first_line];
+        for replacement in &replacements[index..next] {
+            new_text.push_str(&text[copied_up_to..replacement.start]);
+            new_text.push_str(&replacement.text);
+            copied_up_to = replacement.end;
+        }
+        new_text.push_str(&text[copied_up_to..line_starts[last_line + 1]]);
+        let mut old_lines: Vec<&str> = lines[first_line..=last_line].to_vec();
+        let mut new_lines: Vec<String> = new_text
+            .split_inclusive('\n')
+            .map(|line| line.to_string())
+            .collect();
+        // Lines that are the same before and after the change are shown as context
+        let mut first_line = first_line;
+        while !old_lines.is_empty() && !new_lines.is_empty() && old_lines[0] == new_lines[0] {
+            old_lines.remove(0);
+            new_lines.remove(0);
+            first_line += 1;
+        }
+        while !old_lines.is_empty()
+            && !new_lines.is_empty()
+            && old_lines.last().copied() == new_lines.last().map(|line| line.as_str())
+        {
+            old_lines.pop();
+            new_lines.pop();
+        }
+        changes.push(Change {
+            first_line,
+            line_count: old_lines.len(),
+            new_lines,
+        });
+        index = next;
+    }
+
+    let mut diff = format!("--- a/{file}\n+++ b/{file}\n");
+    let mut line_delta: isize = 0;
+    let mut index = 0;
+    while index < changes.len() {
+        // Changes whose context lines would overlap are shown in the same hunk
+        let mut next = index + 1;
+        while next < changes.len()
+            && changes[next].first_line
+                <= changes[next - 1].first_line + changes[next - 1].line_count + 2 * CONTEXT_LINES
+        {
+            next += 1;
+        }
+        let hunk_start = changes[index].first_line.saturating_sub(CONTEXT_LINES);
+        let last = &changes[next - 1];
+        let hunk_end = (last.first_line + last.line_count + CONTEXT_LINES).min(lines.len());
+        let mut body = String::new();
+        let mut old_count = 0;
+        let mut new_count = 0;
+        let mut line = hunk_start;
+        for change in &changes[index..next] {
+            while line < change.first_line {
+                push_line(&mut body, ' ', lines[line]);
+                old_count += 1;
+                new_count += 1;
+                line += 1;
+            }
+            for old_line in &lines[change.first_line..change.first_line + change.line_count] {
+                push_line(&mut body, '-', old_line);
+                old_count += 1;
+            }
+            for new_line in &change.new_lines {
+                push_line(&mut body, '+', new_line);
+                new_count += 1;
+            }
+            line = change.first_line + change.line_count;
+        }
+        while line < hunk_end {
+            push_line(&mut body, ' ', lines[line]);
+            old_count += 1;
+            new_count += 1;
+            line += 1;
+        }
+        let old_start = if old_count == 0 {
+            hunk_start
+        } else {
+            hunk_start + 1
+        };
+        let new_start = if new_count == 0 {
+            (hunk_start as isize + line_delta) as usize
+        } else {
+            (hunk_start as isize + line_delta) as usize + 1
+        };
+        diff.push_str(&format!(
+            "@@ -{old_start},{old_count} +{new_start},{new_count} @@\n{body}"
+        ));
+        line_delta += new_count as isize - old_count as isize;
+        index = next;
+    }
+    diff
+}
+
+// Appends a line of a hunk, marking a last line without a line break as the diff format wants.
+fn push_line(body: &mut String, marker: char, line: &str) {
+    body.push(marker);
+    body.push_str(line);
+    if !line.ends_with('\n') {
+        body.push_str("\n\\ No newline at end of file\n");
+    }
+}
@@ -0,0 +1,267 @@
+// The registry of the models of foreign functions, which describes what calls of functions of
+// other crates do, for the functions whose effects matter to the checkers but that are simple
+// enough not to be modeled in the source of HEPHA. The default registry, models.toml at the root
+// of the checker, is extended by the file given with --models, whose entries replace those of the
+// default registry for the same function.
+//
+// [[model]]
+// function = "solana_program::instruction::get_stack_height"
+// returns = { min = 0, max = 5 }
+//
+// [[model]]
+// function = "fastrand::*::u64"
+// tag = "predictable_entropy"
+//
+// [[model]]
+// function = "my_oracle::request_price"
+// external_call = true
+//
+// [[model]]
+// function = "std::process::exit"
+// forbidden_in_entrypoint = true
+//
+// A function is named by its def path, such as rustc prints it, without generic arguments. A * is
+// a segment that matches any number of segments, which allows for re-exports. A function with a
+// return range is not analyzed: it has no side effects and returns some value of the range, whose
+// bounds must be values of the type of the result. A bound that is not a value of i64, such as the
+// greatest u64, is written as a string, like max = "18446744073709551615". The
+// other kinds of models add to what the summary of the function says: its result carries a tag,
+// such as predictable_entropy or untrusted_input, it is a call out of the program that the
+// reentrancy checker treats like an invocation of another program with the accounts of its
+// arguments, or its calls in entrypoints are reported by the forbidden_call checker.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::tag_domain::Tag;
+
+/// The default registry, which is part of the checker.
+const DEFAULT_MODELS: &str = include_str!("../models.toml");
+
+/// The contents of a models file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ModelsFile {
+    model: Vec<ModelEntry>,
+}
+
+/// An entry of a models file, as it is written.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ModelEntry {
+    function: Spanned<String>,
+    returns: Option<Spanned<ReturnRange>>,
+    tag: Option<Spanned<String>>,
+    #[serde(default)]
+    external_call: bool,
+    #[serde(default)]
+    forbidden_in_entrypoint: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReturnRange {
+    min: Bound,
+    max: Bound,
+}
+
+/// A bound of a return range, which is written as a string if TOML integers cannot hold it.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Bound {
+    Integer(i64),
+    Decimal(String),
+}
+
+/// What a call of a foreign function does, according to the registry.
+#[derive(Clone, Debug)]
+pub struct ForeignModel {
+    /// The def path pattern of the function.
+    pub function: String,
+    /// The least and the greatest value that the function returns, if it is pure.
+    pub returns: Option<(i128, i128)>,
+    /// The file and line of the return range, for the error of a range that is not in the range
+    /// of the type of the result.
+    pub returns_location: String,
+    /// The built-in tag that the result of the function carries.
+    pub tag: Option<Tag>,
+    /// True if the function calls out of the program, like an invocation of another program.
+    pub external_call: bool,
+    /// True if the function must not be called from an entrypoint.
+    pub forbidden_in_entrypoint: bool,
+}
+
+/// The models of the foreign functions, with the models of the functions that have been called so
+/// far, by def id.
+#[derive(Debug, Default)]
+pub struct ForeignModelRegistry {
+    models: Vec<ForeignModel>,
+    models_by_def_id: HashMap<DefId, Option<Rc<ForeignModel>>>,
+}
+
+impl ForeignModelRegistry {
+    /// Returns the default registry, extended with the models of the file at the given path, if
+    /// any. The error says which line of which file is invalid.
+    pub fn load(path: Option<&str>) -> Result<ForeignModelRegistry, String> {
+        let mut models = parse(DEFAULT_MODELS, "models.toml")?;
+        if let Some(path) = path {
+            let text =
+                std::fs::read_to_string(path).map_err(|e| format!("cannot read {path}: {e}"))?;
+            for model in parse(&text, path)? {
+                match models.iter_mut().find(|m| m.function == model.function) {
+                    Some(default_model) => *default_model = model,
+                    None => models.push(model),
+                }
+            }
+        }
+        Ok(ForeignModelRegistry {
+            models,
+            models_by_def_id: HashMap::new(),
+        })
+    }
+
+    /// Returns the model of the function of another crate with the given def id, if it has one. A
+    /// pattern without a * takes precedence over the patterns with one, and a longer pattern over
+    /// a shorter one.
+    pub fn model_for(&mut self, tcx: TyCtxt<'_>, def_id: DefId) -> Option<Rc<ForeignModel>> {
+        if def_id.is_local() {
+            return None;
+        }
+        let models = &self.models;
+        self.models_by_def_id
+            .entry(def_id)
+            .or_insert_with(|| {
+                let path = def_path_without_generic_arguments(&tcx.def_path_str(def_id));
+                let segments: Vec<&str> = path.split("::").collect();
+                models
+                    .iter()
+                    .filter(|model| {
+                        let pattern: Vec<&str> = model.function.split("::").collect();
+                        matches_segments(&pattern, &segments)
+                    })
+                    .max_by_key(|model| {
+                        let wildcards = model.function.split("::").filter(|s| *s == "*").count();
+                        (wildcards == 0, model.function.len())
+                    })
+                    .map(|model| Rc::new(model.clone()))
+            })
+            .clone()
+    }
+}
+
+/// Parses the models of the given text, which is the contents of the file with the given name.
+fn parse(text: &str, file_name: &str) -> Result<Vec<ForeignModel>, String> {
+    let file: ModelsFile =
+        toml::from_str(text).map_err(|e| format!("invalid models in {file_name}: {e}"))?;
+    let location_at = |offset: usize| {
+        let line = text[..offset].matches('\n').count() + 1;
+        format!("{file_name}:{line}")
+    };
+    let error_at = |offset: usize, message: String| format!("{}: {message}", location_at(offset));
+    let mut models = Vec::new();
+    for entry in file.model {
+        let function = entry.function.get_ref();
+        if !is_def_path_pattern(function) {
+            return Err(error_at(
+                entry.function.span().start,
+                format!("`{function}` is not a def path, such as solana_program::program::invoke"),
+            ));
+        }
+        let mut returns_location = String::new();
+        let returns = match &entry.returns {
+            Some(range) => {
+                let bound = |bound: &Bound| match bound {
+                    Bound::Integer(value) => Ok(i128::from(*value)),
+                    Bound::Decimal(text) => text.parse::<i128>().map_err(|_| {
+                        error_at(
+                            range.span().start,
+                            format!("`{text}` is not a bound of the return range of `{function}`"),
+                        )
+                    }),
+                };
+                let (min, max) = (bound(&range.get_ref().min)?, bound(&range.get_ref().max)?);
+                if min > max {
+                    return Err(error_at(
+                        range.span().start,
+                        format!("the return range of `{function}` is empty: {min} > {max}"),
+                    ));
+                }
+                returns_location = location_at(range.span().start);
+                Some((min, max))
+            }
+            None => None,
+        };
+        let tag = match &entry.tag {
+            Some(name) => match Tag::built_in(name.get_ref()) {
+                Some(tag) => Some(tag),
+                None => {
+                    return Err(error_at(
+                        name.span().start,
+                        format!(
+                            "`{}` is not a built-in tag, such as predictable_entropy or untrusted_input",
+                            name.get_ref()
+                        ),
+                    ))
+                }
+            },
+            None => None,
+        };
+        models.push(ForeignModel {
+            function: function.clone(),
+            returns,
+            returns_location,
+            tag,
+            external_call: entry.external_call,
+            forbidden_in_entrypoint: entry.forbidden_in_entrypoint,
+        });
+    }
+    Ok(models)
+}
+
+/// True if the pattern is a sequence of identifiers and * separated by ::.
+fn is_def_path_pattern(pattern: &str) -> bool {
+    pattern.split("::").all(|segment| {
+        segment == "*"
+            || segment
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_alphabetic() || c == '_')
+                && segment.chars().all(|c| c.is_alphanumeric() || c == '_')
+    })
+}
+
+/// True if the segments of the path match those of the pattern, in which * matches any number of
+/// segments.
+fn matches_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"*", rest)) => (0..=path.len()).any(|skip| matches_segments(rest, &path[skip..])),
+        Some((segment, rest)) => {
+            path.first() == Some(segment) && matches_segments(rest, &path[1..])
+        }
+    }
+}
+
+/// Returns the def path without the generic arguments of its segments, such as the <'a> of
+/// AccountInfo::<'a>::try_borrow_data.
+fn def_path_without_generic_arguments(path: &str) -> String {
+    let mut result = String::with_capacity(path.len());
+    let mut depth = 0;
+    for c in path.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' if depth > 0 => depth -= 1,
+            _ if depth == 0 => result.push(c),
+            _ => {}
+        }
+    }
+    result
+        .replace("::::", "::")
+        .trim_end_matches("::")
+        .to_string()
+}
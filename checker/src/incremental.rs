@@ -0,0 +1,316 @@
+// The incremental analysis of a crate, with --incremental or --changed-functions. The summary store
+// of the output directory keeps a record of every function of the crate: a hash of its source and of
+// the structs, constants and type aliases that it uses and, once it has been analyzed, a hash of
+// the preconditions of its summary and its diagnostics. When the crate is analyzed again, only the
+// functions that changed and the functions that call them, directly or through other functions of
+// the crate, are analyzed. The diagnostics of the other functions are replayed from their records,
+// at the same offsets from the start of the bodies that they are in, so that they stay put when
+// code above them is edited. A function is also analyzed again if a function of another crate that
+// it calls has other preconditions than when it was analyzed, since its calls may no longer meet
+// them.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use rustc_errors::{Diag, DiagArgValue, DiagInner, Level};
+use rustc_hir::def::{DefKind, Res};
+use rustc_hir::def_id::DefId;
+use rustc_hir::intravisit::{self, Visitor};
+use rustc_hir::{Expr, ExprKind, HirId};
+use rustc_middle::ty::{GenericArgKind, Ty, TyCtxt, TyKind, TypeckResults};
+use rustc_session::Session;
+use rustc_span::{BytePos, Span};
+use serde::{Deserialize, Serialize};
+
+use crate::checker_registry::{DiagnosticOrigin, Severity};
+use crate::diagnostic_export::{self, FINDING_PROPERTIES_ARG};
+use crate::summaries::Summary;
+use crate::utils;
+
+/// What the summary store keeps about a function of a crate between two analyses of the crate.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct AnalysisRecord {
+    /// The hash of the source of the function, with its signature, and of the items it uses.
+    pub fingerprint: String,
+    /// The hash of the preconditions of the summary of the function, if it was analyzed.
+    pub preconditions: Option<String>,
+    /// The hashes of the preconditions of the functions of other crates that the function calls,
+    /// by summary key, as they were when it was analyzed.
+    pub callee_preconditions: BTreeMap<String, Option<String>>,
+    /// The diagnostics of the function, or None if it was not analyzed or if some of its
+    /// diagnostics cannot be replayed, such as those with spans in other crates.
+    pub diagnostics: Option<Vec<CachedDiagnostic>>,
+    /// The severity of the most severe finding of the function, if it has any.
+    pub most_severe_finding: Option<Severity>,
+}
+
+impl AnalysisRecord {
+    /// True if a diagnostic of the record, or one of its notes, is in one of the given functions.
+    pub fn refers_to(&self, functions: &HashSet<String>) -> bool {
+        self.diagnostics.iter().flatten().any(|diagnostic| {
+            std::iter::once(Some(&diagnostic.span))
+                .chain(diagnostic.notes.iter().map(|(_, span)| span.as_ref()))
+                .flatten()
+                .any(|span| functions.contains(&span.function))
+        })
+    }
+}
+
+/// A diagnostic as it is kept in the record of the function that was analyzed when it was
+/// reported.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CachedDiagnostic {
+    pub level: Severity,
+    pub message: String,
+    /// True if the diagnostic reports that the analysis of the function timed out.
+    pub timed_out: bool,
+    pub span: CachedSpan,
+    pub notes: Vec<(String, Option<CachedSpan>)>,
+    /// The facts about the analysis that a finding depends on, as JSON.
+    pub properties: Option<String>,
+}
+
+/// A span of the source of a function of the crate, as offsets from the start of its body.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CachedSpan {
+    /// The summary key of the function.
+    pub function: String,
+    pub lo: u32,
+    pub hi: u32,
+}
+
+impl CachedDiagnostic {
+    /// Returns the record of the given diagnostic, or None if it cannot be replayed.
+    pub fn new(tcx: TyCtxt<'_>, diagnostic: &DiagInner, origin: DiagnosticOrigin) -> Option<Self> {
+        let level = Severity::of_level(diagnostic.level)?;
+        let message = diagnostic.messages.first()?.0.as_str()?.to_string();
+        let span = CachedSpan::new(tcx, diagnostic.span.primary_span()?)?;
+        let mut notes = Vec::new();
+        for child in &diagnostic.children {
+            if child.level != Level::Note {
+                return None;
+            }
+            let note = child.messages.first()?.0.as_str()?.to_string();
+            let span = match child.span.primary_span() {
+                Some(span) => Some(CachedSpan::new(tcx, span)?),
+                None => None,
+            };
+            notes.push((note, span));
+        }
+        let properties = match diagnostic.args.get(FINDING_PROPERTIES_ARG) {
+            Some(DiagArgValue::Str(properties)) => Some(properties.to_string()),
+            _ => None,
+        };
+        Some(CachedDiagnostic {
+            level,
+            message,
+            timed_out: matches!(origin, DiagnosticOrigin::Timeout),
+            span,
+            notes,
+            properties,
+        })
+    }
+
+    /// Returns the diagnostic again, with the spans of the given bodies, or None if one of the
+    /// functions that it refers to no longer has a body.
+    pub fn replay<'a>(
+        &self,
+        session: &'a Session,
+        body_spans: &HashMap<String, Span>,
+    ) -> Option<(Diag<'a, ()>, DiagnosticOrigin)> {
+        let span = self.span.to_span(body_spans)?;
+        let mut notes = Vec::new();
+        for (note, span) in &self.notes {
+            match span {
+                Some(span) => notes.push((note, Some(span.to_span(body_spans)?))),
+                None => notes.push((note, None)),
+            }
+        }
+        let mut diagnostic: Diag<'a, ()> =
+            Diag::new(session.dcx(), self.level.level(), self.message.clone());
+        diagnostic.span(span);
+        for (note, span) in notes {
+            match span {
+                Some(span) => diagnostic.span_note(span, note.clone()),
+                None => diagnostic.note(note.clone()),
+            };
+        }
+        if let Some(properties) = &self.properties {
+            diagnostic.arg(FINDING_PROPERTIES_ARG, properties.clone());
+        }
+        let origin = if self.timed_out {
+            DiagnosticOrigin::Timeout
+        } else {
+            DiagnosticOrigin::of_message(&self.message)
+        };
+        Some((diagnostic, origin))
+    }
+}
+
+impl CachedSpan {
+    fn new(tcx: TyCtxt<'_>, span: Span) -> Option<Self> {
+        let span = span.source_callsite();
+        let owner = diagnostic_export::enclosing_function(tcx, span)?;
+        let body_start = tcx.hir().body_owned_by(owner).value.span.lo();
+        Some(CachedSpan {
+            function: utils::summary_key_str(tcx, owner.to_def_id()).to_string(),
+            lo: span.lo().0 - body_start.0,
+            hi: span.hi().0 - body_start.0,
+        })
+    }
+
+    fn to_span(&self, body_spans: &HashMap<String, Span>) -> Option<Span> {
+        let body_start = body_spans.get(&self.function)?.lo();
+        Some(Span::with_root_ctxt(
+            body_start + BytePos(self.lo),
+            body_start + BytePos(self.hi),
+        ))
+    }
+}
+
+/// The state of the incremental analysis of a crate.
+#[derive(Debug, Default)]
+pub struct IncrementalAnalysis {
+    /// The functions of the crate that the bodies of the crate refer to, by body.
+    pub callees: BTreeMap<DefId, Vec<DefId>>,
+    /// The hashes of the sources of the functions of the crate and of the items they use.
+    pub fingerprints: HashMap<DefId, String>,
+    /// The spans of the bodies of the functions of the crate, by summary key.
+    pub body_spans: HashMap<String, Span>,
+    /// The records of the last analysis of the functions of the crate.
+    pub records: HashMap<DefId, AnalysisRecord>,
+    /// The functions that changed and the functions that call them, which are analyzed again.
+    pub dirty: HashSet<DefId>,
+    /// The hashes of the preconditions and the most severe findings of the functions that are
+    /// analyzed in this run.
+    pub analyzed: HashMap<DefId, (String, Option<Severity>)>,
+}
+
+/// Returns the hash of the source of the function with the given id, with its signature, and of
+/// the items that it uses: the structs, enums and unions of the types of its expressions and
+/// signature, with those of their fields, and the constants, statics and type aliases that it
+/// names. An item of another crate is hashed as the hash of that crate.
+pub fn fingerprint(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    let mut source = item_source(tcx, def_id);
+    if let Some(local_def_id) = def_id.as_local() {
+        let typeck_results = tcx.typeck(local_def_id);
+        let mut visitor = UsedItems {
+            tcx,
+            typeck_results,
+            items: Vec::new(),
+        };
+        let hir_id = tcx.local_def_id_to_hir_id(local_def_id);
+        if let Some(decl) = tcx.hir().fn_decl_by_hir_id(hir_id) {
+            visitor.visit_fn_decl(decl);
+        }
+        visitor.visit_body(tcx.hir().body_owned_by(local_def_id));
+        for (_, ty) in typeck_results.node_types().items_in_stable_order() {
+            visitor.add_adts(*ty);
+        }
+        let mut used_items = BTreeMap::new();
+        while let Some(item) = visitor.items.pop() {
+            if used_items.contains_key(&item) {
+                continue;
+            }
+            let hashed = if item.is_local() {
+                let mut hashed = item_source(tcx, item);
+                if matches!(
+                    tcx.def_kind(item),
+                    DefKind::Struct | DefKind::Enum | DefKind::Union
+                ) {
+                    // The fields are hashed with the types that their aliases stand for
+                    for field in tcx.adt_def(item).all_fields() {
+                        let field_ty = tcx.type_of(field.did).instantiate_identity();
+                        hashed.push_str(&format!("\n{}: {field_ty}", field.name));
+                        visitor.add_adts(field_ty);
+                    }
+                }
+                hashed
+            } else {
+                format!("{:032x}", tcx.crate_hash(item.krate).as_u128())
+            };
+            used_items.insert(item, hashed);
+        }
+        let used_items: BTreeMap<String, String> = used_items
+            .into_iter()
+            .map(|(item, hashed)| (tcx.def_path_str(item), hashed))
+            .collect();
+        for (name, hashed) in used_items {
+            source.push_str(&format!("\n{name}\n{hashed}"));
+        }
+    }
+    diagnostic_export::fnv1a(source.as_bytes())
+}
+
+/// Returns the source of the item with the given id, with its body, if it is of the crate.
+fn item_source(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    def_id
+        .as_local()
+        .map(|local_def_id| {
+            tcx.hir()
+                .span_with_body(tcx.local_def_id_to_hir_id(local_def_id))
+        })
+        .and_then(|span| tcx.sess.source_map().span_to_snippet(span).ok())
+        .unwrap_or_default()
+}
+
+// Collects the items, other than functions, that a body and its signature refer to. The functions
+// are the callees of the body, which are dealt with by the call graph of the incremental analysis.
+struct UsedItems<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    typeck_results: &'tcx TypeckResults<'tcx>,
+    items: Vec<DefId>,
+}
+
+impl<'tcx> UsedItems<'tcx> {
+    /// Adds the structs, enums and unions that occur in the given type.
+    fn add_adts(&mut self, ty: Ty<'tcx>) {
+        for arg in ty.walk() {
+            if let GenericArgKind::Type(ty) = arg.unpack() {
+                if let TyKind::Adt(def, _) = ty.kind() {
+                    self.items.push(def.did());
+                }
+            }
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for UsedItems<'tcx> {
+    fn visit_expr(&mut self, expression: &'tcx Expr<'tcx>) {
+        if let ExprKind::Path(qpath) = &expression.kind {
+            if let Res::Def(DefKind::Const | DefKind::AssocConst | DefKind::Static { .. }, def_id) =
+                self.typeck_results.qpath_res(qpath, expression.hir_id)
+            {
+                self.items.push(def_id);
+            }
+        }
+        intravisit::walk_expr(self, expression);
+    }
+
+    fn visit_path(&mut self, path: &rustc_hir::Path<'tcx>, _id: HirId) {
+        if let Res::Def(DefKind::TyAlias, def_id) = path.res {
+            self.items.push(def_id);
+        }
+        intravisit::walk_path(self, path);
+    }
+}
+
+/// Returns the hash of the preconditions of the given summary.
+pub fn preconditions_hash(summary: &Summary) -> String {
+    let preconditions = bincode::serialize(&summary.preconditions).unwrap_or_default();
+    diagnostic_export::fnv1a(&preconditions)
+}
+
+/// Returns the functions of the crate that reach one of the given functions through the calls
+/// of the given reverse call graph, with the given functions themselves.
+pub fn callers_of(changed: Vec<DefId>, callers: &HashMap<DefId, Vec<DefId>>) -> HashSet<DefId> {
+    let mut reached = HashSet::new();
+    let mut to_visit = changed;
+    while let Some(def_id) = to_visit.pop() {
+        if reached.insert(def_id) {
+            if let Some(callers) = callers.get(&def_id) {
+                to_visit.extend(callers);
+            }
+        }
+    }
+    reached
+}
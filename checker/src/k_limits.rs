@@ -6,6 +6,8 @@
 // Somewhat arbitrary constants used to limit things in the abstract interpreter that may
 // take too long or use too much memory.
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 /// The maximum number of elements in a collection that will be individually tracked.
 pub const MAX_ELEMENTS_TO_TRACK: usize = 100;
 
@@ -18,8 +20,83 @@ pub const MAX_EXPRESSION_SIZE: u64 = 1_000;
 /// Double the observed maximum used in practice.
 pub const MAX_FIXPOINT_ITERATIONS: usize = 10;
 
+/// The largest number of iterations of a loop with a constant trip count that is unrolled rather
+/// than widened.
+pub const MAX_LOOP_UNROLLING: usize = 16;
+
+/// The iterations of the fixed point of the summary of a recursive function after which the
+/// summaries of the iterations are widened.
+pub const MAX_RECURSIVE_ITERATIONS_BEFORE_WIDENING: usize = 3;
+
+/// The iterations of the fixed point of the summary of a recursive function after which the
+/// summary is used as it is, but marked as incomplete.
+pub const MAX_RECURSIVE_ITERATIONS: usize = 8;
+
 /// Prevents the outer fixed point loop from creating ever more new abstract values of type Expression::Variable.
 pub const MAX_PATH_LENGTH: usize = 300;
 
 /// Refining values with a path condition that is a really deep expression leads to exponential blow up.
 pub const MAX_REFINE_DEPTH: usize = 40;
+
+/// The queries of the solver that --tighten-intervals may spend on narrowing the bounds of a value.
+pub const MAX_INTERVAL_TIGHTENING_QUERIES: usize = 16;
+
+/// The limits that can be raised or lowered from the command line, for crates whose state
+/// structs hold more elements than are tracked by default, for example.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct KLimits {
+    /// The maximum number of elements in a collection that will be individually tracked.
+    pub max_elements_to_track: usize,
+    /// The maximum length of a path, beyond which a path becomes bottom.
+    pub max_path_length: usize,
+    /// The maximum number of iterations of the fixed point loop of a loop body.
+    pub max_fixpoint_iterations: usize,
+    /// The maximum trip count of a loop that is unrolled.
+    pub max_loop_unrolling: usize,
+    /// The maximum number of iterations of the fixed point of the summaries of a cycle of
+    /// recursive functions.
+    pub max_recursive_iterations: usize,
+}
+
+impl Default for KLimits {
+    fn default() -> Self {
+        KLimits {
+            max_elements_to_track: MAX_ELEMENTS_TO_TRACK,
+            max_path_length: MAX_PATH_LENGTH,
+            max_fixpoint_iterations: MAX_FIXPOINT_ITERATIONS,
+            max_loop_unrolling: MAX_LOOP_UNROLLING,
+            max_recursive_iterations: MAX_RECURSIVE_ITERATIONS,
+        }
+    }
+}
+
+/// The largest value accepted for --max-elements-to-track. Every tracked element gets paths and
+/// values of its own, so much larger limits just exhaust memory.
+pub const MAX_ELEMENTS_TO_TRACK_LIMIT: usize = 100_000;
+
+/// The largest value accepted for --max-path-length.
+pub const MAX_PATH_LENGTH_LIMIT: usize = 10_000;
+
+/// The largest value accepted for --max-outer-fixpoint-iterations.
+pub const MAX_FIXPOINT_ITERATIONS_LIMIT: usize = 1_000;
+
+/// The largest value accepted for --max-loop-unrolling. Every unrolled iteration is a visit of the
+/// whole loop body.
+pub const MAX_LOOP_UNROLLING_LIMIT: usize = 1_000;
+
+/// The largest value accepted for --max-recursive-iterations.
+pub const MAX_RECURSIVE_ITERATIONS_LIMIT: usize = 1_000;
+
+// The maximum length of the paths of the current compilation. Paths are created without access
+// to the options, so like the maximum level of the logger this is global.
+static PATH_LENGTH: AtomicUsize = AtomicUsize::new(MAX_PATH_LENGTH);
+
+/// Returns the maximum length of a path, as given by --max-path-length.
+pub fn max_path_length() -> usize {
+    PATH_LENGTH.load(Ordering::Relaxed)
+}
+
+/// Sets the maximum length of the paths that are created from now on.
+pub fn set_max_path_length(max_path_length: usize) {
+    PATH_LENGTH.store(max_path_length, Ordering::Relaxed);
+}
@@ -5,10 +5,12 @@
 
 use rustc_hir::def_id::DefId;
 use rustc_hir::definitions::{DefPathData, DisambiguatedDefPathData};
-use rustc_middle::ty::TyCtxt;
+use rustc_middle::ty::{TyCtxt, TyKind};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::solana_compat;
+
 /// Well known definitions (language provided items) that are treated in special ways.
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialOrd, PartialEq, Hash, Ord)]
 pub enum KnownNames {
@@ -164,6 +166,35 @@ pub enum KnownNames {
     StdPanickingBeginPanicFmt,
     StdPtrSwapNonOverlapping,
     StdSliceCmpMemcmp,
+    // Appended, since the summaries in binaries/summary_store.tar refer to names by position
+    SolanaProgramGetReturnData,
+    SolanaProgramInvoke,
+    SolanaProgramSetReturnData,
+    HephaSolanaCheckedTransfer,
+    HephaSolanaRequireOwner,
+    HephaSolanaRequireSigner,
+    MiraiSuppress,
+    MiraiVerifyReachable,
+    MiraiLoopInvariant,
+    MiraiOld,
+    MiraiBoundVariable,
+    MiraiForall,
+    MiraiRemoveTag,
+    SolanaRentMinimumBalance,
+    HephaUntrustedInput,
+    StdCollectionsHashMapEntry,
+    StdCollectionsHashMapEntryOrInsert,
+    StdCollectionsHashMapGet,
+    StdCollectionsHashMapGetMut,
+    StdCollectionsHashMapInsert,
+    BorshTryFromSlice,
+    StdBorrowCowDeref,
+    StdBorrowCowToMut,
+    StdOpsTryBranch,
+    StdOptionCopied,
+    StdOptionMap,
+    StdOptionOkOr,
+    StdOptionUnwrapOr,
 }
 
 /// An analysis lifetime cache that contains a map from def ids to known names.
@@ -262,6 +293,96 @@ impl KnownNamesCache {
                 .unwrap_or(KnownNames::None)
         };
 
+        // The methods of HashMap and of its Entry are defined in impl blocks, which have no name
+        let get_known_name_for_hash_map_namespace = |mut def_path_data_iter: Iter<'_>| {
+            if !matches!(
+                def_path_data_iter.next(),
+                Some(DisambiguatedDefPathData { data: Impl, .. })
+            ) {
+                return KnownNames::None;
+            }
+            let Some(impl_def_id) = tcx.impl_of_method(def_id) else {
+                return KnownNames::None;
+            };
+            let TyKind::Adt(def, _) = tcx.type_of(impl_def_id).instantiate_identity().kind() else {
+                return KnownNames::None;
+            };
+            get_path_data_elem_name(def_path_data_iter.next())
+                .map(|n| match (tcx.item_name(def.did()).as_str(), n.as_str()) {
+                    ("Entry", "or_insert") => KnownNames::StdCollectionsHashMapEntryOrInsert,
+                    ("HashMap", "entry") => KnownNames::StdCollectionsHashMapEntry,
+                    ("HashMap", "get") => KnownNames::StdCollectionsHashMapGet,
+                    ("HashMap", "get_mut") => KnownNames::StdCollectionsHashMapGetMut,
+                    ("HashMap", "insert") => KnownNames::StdCollectionsHashMapInsert,
+                    _ => KnownNames::None,
+                })
+                .unwrap_or(KnownNames::None)
+        };
+
+        // The methods of Cow are defined in impl blocks, which have no name
+        let get_known_name_for_borrow_namespace = |mut def_path_data_iter: Iter<'_>| {
+            if !matches!(
+                def_path_data_iter.next(),
+                Some(DisambiguatedDefPathData { data: Impl, .. })
+            ) {
+                return KnownNames::None;
+            }
+            let Some(impl_def_id) = tcx.impl_of_method(def_id) else {
+                return KnownNames::None;
+            };
+            let TyKind::Adt(def, _) = tcx.type_of(impl_def_id).instantiate_identity().kind() else {
+                return KnownNames::None;
+            };
+            get_path_data_elem_name(def_path_data_iter.next())
+                .map(|n| match (tcx.item_name(def.did()).as_str(), n.as_str()) {
+                    ("Cow", "deref") => KnownNames::StdBorrowCowDeref,
+                    ("Cow", "to_mut") => KnownNames::StdBorrowCowToMut,
+                    _ => KnownNames::None,
+                })
+                .unwrap_or(KnownNames::None)
+        };
+
+        // The methods of Option and the implementations of Try for Option and Result are defined
+        // in impl blocks, which have no name
+        let get_known_name_for_option_or_result_namespace = |mut def_path_data_iter: Iter<'_>| {
+            if !matches!(
+                def_path_data_iter.next(),
+                Some(DisambiguatedDefPathData { data: Impl, .. })
+            ) {
+                return KnownNames::None;
+            }
+            let Some(impl_def_id) = tcx.impl_of_method(def_id) else {
+                return KnownNames::None;
+            };
+            let TyKind::Adt(def, _) = tcx.type_of(impl_def_id).instantiate_identity().kind() else {
+                return KnownNames::None;
+            };
+            get_path_data_elem_name(def_path_data_iter.next())
+                .map(|n| match (tcx.item_name(def.did()).as_str(), n.as_str()) {
+                    ("Option", "branch") | ("Result", "branch") => KnownNames::StdOpsTryBranch,
+                    ("Option", "copied") => KnownNames::StdOptionCopied,
+                    ("Option", "map") => KnownNames::StdOptionMap,
+                    ("Option", "ok_or") => KnownNames::StdOptionOkOr,
+                    ("Option", "unwrap_or") => KnownNames::StdOptionUnwrapOr,
+                    _ => KnownNames::None,
+                })
+                .unwrap_or(KnownNames::None)
+        };
+
+        let get_known_name_for_collections_namespace = |mut def_path_data_iter: Iter<'_>| {
+            get_path_data_elem_name(def_path_data_iter.next())
+                .map(|n| match n.as_str() {
+                    "hash" => get_path_data_elem_name(def_path_data_iter.next())
+                        .map(|n| match n.as_str() {
+                            "map" => get_known_name_for_hash_map_namespace(def_path_data_iter),
+                            _ => KnownNames::None,
+                        })
+                        .unwrap_or(KnownNames::None),
+                    _ => KnownNames::None,
+                })
+                .unwrap_or(KnownNames::None)
+        };
+
         let get_known_name_for_future_namespace = |mut def_path_data_iter: Iter<'_>| {
             get_path_data_elem_name(def_path_data_iter.next())
                 .map(|n| match n.as_str() {
@@ -476,10 +597,25 @@ impl KnownNamesCache {
                 .unwrap_or(KnownNames::None)
         };
 
+        let get_known_name_for_ops_try_trait_namespace = |mut def_path_data_iter: Iter<'_>| {
+            get_path_data_elem_name(def_path_data_iter.next())
+                .map(|n| match n.as_str() {
+                    "Try" => get_path_data_elem_name(def_path_data_iter.next())
+                        .map(|n| match n.as_str() {
+                            "branch" => KnownNames::StdOpsTryBranch,
+                            _ => KnownNames::None,
+                        })
+                        .unwrap_or(KnownNames::None),
+                    _ => KnownNames::None,
+                })
+                .unwrap_or(KnownNames::None)
+        };
+
         let get_known_name_for_ops_namespace = |mut def_path_data_iter: Iter<'_>| {
             get_path_data_elem_name(def_path_data_iter.next())
                 .map(|n| match n.as_str() {
                     "function" => get_known_name_for_ops_function_namespace(def_path_data_iter),
+                    "try_trait" => get_known_name_for_ops_try_trait_namespace(def_path_data_iter),
                     _ => KnownNames::None,
                 })
                 .unwrap_or(KnownNames::None)
@@ -583,45 +719,126 @@ impl KnownNamesCache {
                 .unwrap_or(KnownNames::None)
         };
 
+        // The wrappers of Solana operations in the solana module of hepha_annotations
+        let get_known_name_for_hepha_solana_namespace = |mut def_path_data_iter: Iter<'_>| {
+            get_path_data_elem_name(def_path_data_iter.next())
+                .map(|n| match n.as_str() {
+                    "checked_transfer" => KnownNames::HephaSolanaCheckedTransfer,
+                    "require_owner" => KnownNames::HephaSolanaRequireOwner,
+                    "require_signer" => KnownNames::HephaSolanaRequireSigner,
+                    _ => KnownNames::None,
+                })
+                .unwrap_or(KnownNames::None)
+        };
+
         let get_known_name_for_known_crate = |mut def_path_data_iter: Iter<'_>| {
             get_path_data_elem_name(def_path_data_iter.next())
                 .map(|n| match n.as_str() {
                     "alloc" => get_known_name_for_alloc_namespace(def_path_data_iter),
+                    "borrow" => get_known_name_for_borrow_namespace(def_path_data_iter),
                     "clone" => get_known_name_for_clone_namespace(def_path_data_iter),
+                    "collections" => get_known_name_for_collections_namespace(def_path_data_iter),
                     "future" => get_known_name_for_future_namespace(def_path_data_iter),
                     "intrinsics" => get_known_name_for_intrinsics_namespace(def_path_data_iter),
                     "marker" => get_known_name_for_marker_namespace(def_path_data_iter),
                     "mem" => get_known_name_for_mem_namespace(def_path_data_iter),
                     "ops" => get_known_name_for_ops_namespace(def_path_data_iter),
+                    "option" | "result" => {
+                        get_known_name_for_option_or_result_namespace(def_path_data_iter)
+                    }
                     "panicking" => get_known_name_for_panicking_namespace(def_path_data_iter),
                     "ptr" => get_known_name_for_ptr_namespace(def_path_data_iter),
                     "hepha_abstract_value" => KnownNames::MiraiAbstractValue,
                     "hepha_add_tag" => KnownNames::MiraiAddTag,
                     "hepha_assume" => KnownNames::MiraiAssume,
                     "hepha_assume_preconditions" => KnownNames::MiraiAssumePreconditions,
+                    "hepha_bound_variable" => KnownNames::MiraiBoundVariable,
                     "hepha_does_not_have_tag" => KnownNames::MiraiDoesNotHaveTag,
+                    "hepha_forall" => KnownNames::MiraiForall,
                     "hepha_get_model_field" => KnownNames::MiraiGetModelField,
                     "hepha_has_tag" => KnownNames::MiraiHasTag,
+                    "hepha_loop_invariant" => KnownNames::MiraiLoopInvariant,
+                    "hepha_old" => KnownNames::MiraiOld,
                     "hepha_postcondition" => KnownNames::MiraiPostcondition,
                     "hepha_precondition_start" => KnownNames::MiraiPreconditionStart,
                     "hepha_precondition" => KnownNames::MiraiPrecondition,
+                    "hepha_remove_tag" => KnownNames::MiraiRemoveTag,
                     "hepha_result" => KnownNames::MiraiResult,
                     "hepha_set_model_field" => KnownNames::MiraiSetModelField,
+                    "hepha_suppress" => KnownNames::MiraiSuppress,
                     "hepha_verify" => KnownNames::MiraiVerify,
+                    "hepha_verify_reachable" => KnownNames::MiraiVerifyReachable,
                     "raw_vec" => get_known_name_for_raw_vec_namespace(def_path_data_iter),
                     "rt" => get_known_name_for_panicking_namespace(def_path_data_iter),
                     "slice" => get_known_name_for_slice_namespace(def_path_data_iter),
+                    "solana" => get_known_name_for_hepha_solana_namespace(def_path_data_iter),
                     "sync" => get_known_name_for_sync_namespace(def_path_data_iter),
+                    "UntrustedInput" => KnownNames::HephaUntrustedInput,
                     _ => KnownNames::None,
                 })
                 .unwrap_or(KnownNames::None)
         };
 
+        // solana_cpi defines these at the crate root, solana_program in its program module
+        let get_known_name_for_solana_program_namespace = |mut def_path_data_iter: Iter<'_>| {
+            get_path_data_elem_name(def_path_data_iter.next())
+                .map(|n| match n.as_str() {
+                    "get_return_data" => KnownNames::SolanaProgramGetReturnData,
+                    "invoke" | "invoke_signed" | "invoke_signed_unchecked" | "invoke_unchecked" => {
+                        KnownNames::SolanaProgramInvoke
+                    }
+                    "set_return_data" => KnownNames::SolanaProgramSetReturnData,
+                    _ => KnownNames::None,
+                })
+                .unwrap_or(KnownNames::None)
+        };
+
+        let get_known_name_for_borsh_deserialize_namespace = |mut def_path_data_iter: Iter<'_>| {
+            get_path_data_elem_name(def_path_data_iter.next())
+                .map(|n| match n.as_str() {
+                    "try_from_slice" => KnownNames::BorshTryFromSlice,
+                    _ => KnownNames::None,
+                })
+                .unwrap_or(KnownNames::None)
+        };
+
+        // BorshDeserialize is defined in the de module of borsh
+        let get_known_name_for_borsh_namespace = |mut def_path_data_iter: Iter<'_>| {
+            if get_path_data_elem_name(def_path_data_iter.next()).is_none_or(|n| n.as_str() != "de")
+            {
+                return KnownNames::None;
+            }
+            get_path_data_elem_name(def_path_data_iter.next())
+                .map(|n| match n.as_str() {
+                    "BorshDeserialize" => {
+                        get_known_name_for_borsh_deserialize_namespace(def_path_data_iter)
+                    }
+                    _ => KnownNames::None,
+                })
+                .unwrap_or(KnownNames::None)
+        };
+
+        // Rent is defined by solana_program or by solana_rent, depending on the version
+        if solana_compat::is_rent_minimum_balance(tcx, def_id) {
+            return KnownNames::SolanaRentMinimumBalance;
+        }
+
         let crate_name = tcx.crate_name(def_id.krate);
         match crate_name.as_str() {
             "alloc" | "core" | "hepha_annotations" | "std" => {
                 get_known_name_for_known_crate(def_path_data_iter)
             }
+            "borsh" => get_known_name_for_borsh_namespace(def_path_data_iter),
+            "solana_cpi" => get_known_name_for_solana_program_namespace(def_path_data_iter),
+            "solana_program" => {
+                let mut def_path_data_iter = def_path_data_iter;
+                match get_path_data_elem_name(def_path_data_iter.next()) {
+                    Some(n) if n.as_str() == "program" => {
+                        get_known_name_for_solana_program_namespace(def_path_data_iter)
+                    }
+                    _ => KnownNames::None,
+                }
+            }
             _ => KnownNames::None,
         }
     }
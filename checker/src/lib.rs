@@ -41,15 +41,17 @@ extern crate rustc_trait_selection;
 
 /// If the currently analyzed function has been marked as angelic because was discovered
 /// to do something that cannot be analyzed, or if the time taken to analyze the current
-/// function exceeded options.max_analysis_time_for_body, break out of the current loop.
-/// When a timeout happens, currently analyzed function is marked as angelic.
+/// function exceeded options.max_analysis_time_for_body, or if the analysis has been cancelled,
+/// break out of the current loop.
+/// When a timeout or a cancellation happens, currently analyzed function is marked as angelic.
 macro_rules! check_for_early_break {
     ($sel:expr) => {
         if $sel.analysis_is_incomplete {
             break;
         }
-        let elapsed_time_in_seconds = $sel.start_instant.elapsed().as_secs();
-        if elapsed_time_in_seconds >= $sel.cv.options.max_analysis_time_for_body {
+        if $sel.start_instant.elapsed() >= $sel.cv.options.max_analysis_time_for_body
+            || $sel.cv.cancellation.is_cancelled()
+        {
             $sel.analysis_is_incomplete = true;
             break;
         }
@@ -58,15 +60,17 @@ macro_rules! check_for_early_break {
 
 /// If the currently analyzed function has been marked as angelic because was discovered
 /// to do something that cannot be analyzed, or if the time taken to analyze the current
-/// function exceeded options.max_analysis_time_for_body, return to the caller.
-/// When a timeout happens, currently analyzed function is marked as angelic.
+/// function exceeded options.max_analysis_time_for_body, or if the analysis has been cancelled,
+/// return to the caller.
+/// When a timeout or a cancellation happens, currently analyzed function is marked as angelic.
 macro_rules! check_for_early_return {
     ($sel:expr) => {
         if $sel.analysis_is_incomplete {
             return;
         }
-        let elapsed_time_in_seconds = $sel.start_instant.elapsed().as_secs();
-        if elapsed_time_in_seconds >= $sel.cv.options.max_analysis_time_for_body {
+        if $sel.start_instant.elapsed() >= $sel.cv.options.max_analysis_time_for_body
+            || $sel.cv.cancellation.is_cancelled()
+        {
             $sel.analysis_is_incomplete = true;
             return;
         }
@@ -78,22 +82,39 @@ pub mod block_visitor;
 pub mod body_visitor;
 pub mod bool_domain;
 pub mod call_graph;
+pub mod call_states;
 pub mod call_visitor;
 pub mod callbacks;
+pub mod cancellation;
+pub mod checker_registry;
+pub mod config;
 pub mod constant_domain;
 pub mod crate_visitor;
+#[cfg(feature = "cvc5")]
+pub mod cvc5_solver;
+pub mod diagnostic_export;
 pub mod environment;
 pub mod expected_errors;
 pub mod expression;
+pub mod fact_export;
+pub mod finding_clusters;
 pub mod fixed_point_visitor;
+pub mod fixes;
+pub mod foreign_models;
+pub mod incremental;
 pub mod interval_domain;
 pub mod k_limits;
 pub mod known_names;
 pub mod options;
 pub mod path;
+pub mod path_constraints_report;
+pub mod run_summary;
 pub mod smt_solver;
+pub mod solana_compat;
 pub mod summaries;
+pub mod summary_view;
 pub mod tag_domain;
+pub mod trace_scope;
 pub mod type_visitor;
 pub mod utils;
 #[cfg(feature = "z3")]
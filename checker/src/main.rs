@@ -24,12 +24,21 @@ extern crate rustc_session;
 use itertools::Itertools;
 use log::*;
 use hepha::callbacks;
+use hepha::cancellation;
+use hepha::checker_registry;
+use hepha::config::Config;
+use hepha::diagnostic_export;
+use hepha::finding_clusters;
 use hepha::options::Options;
+use hepha::trace_scope;
 use hepha::utils;
 use hepha_annotations::*;
 use std::env;
 use std::path::Path;
 
+/// The exit status of a run that reports findings of at least the severity given by --fail-on.
+const EXIT_FINDINGS: i32 = 2;
+
 fn main() {
     let early_error_handler =
         rustc_session::EarlyDiagCtxt::new(rustc_session::config::ErrorOutputType::default());
@@ -38,21 +47,22 @@ fn main() {
     if env::var("RUSTC_LOG").is_ok() {
         rustc_driver::init_rustc_env_logger(&early_error_handler);
     }
-    if env::var("HEPHA_LOG").is_ok() {
-        let e = env_logger::Env::new()
-            .filter("HEPHA_LOG")
-            .write_style("HEPHA_LOG_STYLE");
-        env_logger::init_from_env(e);
-    }
 
     // Get any options specified via the HEPHA_FLAGS environment variable
+    let hepha_flags = env::var("HEPHA_FLAGS").unwrap_or_default();
     let mut options = Options::default();
-    let rustc_args = options.parse_from_str(
-        &env::var("HEPHA_FLAGS").unwrap_or_default(),
-        &early_error_handler,
-        false,
-    );
-    info!("HEPHA options from environment: {:?}", options);
+    let mut rustc_args = options.parse_from_str(&hepha_flags, &early_error_handler, false);
+    // Let the environment variable override the configuration file, if there is one.
+    if let Some(config) = Config::load(options.config.as_deref(), &early_error_handler) {
+        options = Options::default();
+        rustc_args =
+            options.parse_from_config_and_str(&config, &hepha_flags, &early_error_handler, false);
+    }
+    let options_from_environment = format!("{options:?}");
+    if options.dump_config {
+        print!("{}", Config::of_options(&options).to_toml());
+        return;
+    }
 
     // Let arguments supplied on the command line override the environment variable.
     let mut args = env::args_os()
@@ -73,8 +83,58 @@ fn main() {
     }
 
     let mut rustc_command_line_arguments = options.parse(&args[1..], &early_error_handler, false);
+
+    // The logger of HEPHA depends on --trace-function, so it is set up once the options are known.
+    if env::var("HEPHA_LOG").is_ok() || options.trace_function.is_some() {
+        let env = || {
+            env_logger::Env::new()
+                .filter("HEPHA_LOG")
+                .write_style("HEPHA_LOG_STYLE")
+        };
+        let mut builder = env_logger::Builder::from_env(env());
+        if options.trace_function.is_some() {
+            // Let the trace messages of HEPHA through and let the trace scopes enable them.
+            let base_level = env_logger::Builder::from_env(env()).build().filter();
+            builder.filter_module("hepha", LevelFilter::Trace).init();
+            trace_scope::enable(base_level);
+        } else {
+            builder.init();
+        }
+    }
+    info!("HEPHA options from environment: {}", options_from_environment);
     info!("HEPHA options modified by command line: {:?}", options);
 
+    if options.list_checks {
+        print!("{}", checker_registry::list_checkers(options.output_format));
+        return;
+    }
+    if let Some(code) = &options.explain {
+        match checker_registry::explain(code, options.output_format) {
+            Some(explanation) => print!("{explanation}"),
+            None => early_error_handler.early_fatal(format!("no checker reports {code}")),
+        }
+        return;
+    }
+    if let Some(path) = &options.cluster_findings {
+        match diagnostic_export::read_diagnostics(Path::new(path)) {
+            Ok(diagnostics) => print!(
+                "{}",
+                finding_clusters::render(
+                    &finding_clusters::cluster(diagnostics),
+                    options.output_format
+                )
+            ),
+            Err(e) => early_error_handler
+                .early_fatal(format!("could not read the findings from {path}: {e}")),
+        }
+        return;
+    }
+
+    let test_only = options.test_only;
+    let fail_on = options.fail_on;
+    let mut callbacks = callbacks::MiraiCallbacks::new(options);
+    // Ctrl-C cancels the analysis, which still emits the diagnostics found so far.
+    cancellation::install_interrupt_handler();
     rustc_driver::install_ice_hook(rustc_driver::DEFAULT_BUG_REPORT_URL, |_| ());
     let result = rustc_driver::catch_fatal_errors(|| {
         // Add back the binary name
@@ -114,7 +174,7 @@ fn main() {
                 rustc_command_line_arguments.push(always_encode_mir);
             }
 
-            if options.test_only {
+            if test_only {
                 let prefix: String = "hepha_annotations=".into();
                 let postfix: String = ".rmeta".into();
 
@@ -129,7 +189,6 @@ fn main() {
             }
         }
 
-        let mut callbacks = callbacks::MiraiCallbacks::new(options);
         debug!(
             "rustc_command_line_arguments {:?}",
             rustc_command_line_arguments
@@ -138,9 +197,15 @@ fn main() {
             rustc_driver::RunCompiler::new(&rustc_command_line_arguments, &mut callbacks);
         compiler.run()
     });
-    let exit_code = match result {
-        Ok(_) => rustc_driver::EXIT_SUCCESS,
-        Err(_) => rustc_driver::EXIT_FAILURE,
+    // Findings that are denied are reported as errors, which also makes the compilation fail,
+    // so the findings are looked at before the result of the compilation.
+    let exit_code = if fail_on.is_some() && callbacks.most_severe_finding >= fail_on {
+        EXIT_FINDINGS
+    } else {
+        match result {
+            Ok(_) => rustc_driver::EXIT_SUCCESS,
+            Err(_) => rustc_driver::EXIT_FAILURE,
+        }
     };
     std::process::exit(exit_code);
 }
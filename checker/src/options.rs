@@ -3,20 +3,31 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::time::Duration;
+
 use clap::error::ErrorKind;
 use clap::parser::ValueSource;
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, ArgMatches, Command};
 use itertools::Itertools;
 
 use hepha_annotations::*;
 use rustc_session::EarlyDiagCtxt;
 
+use crate::checker_registry::{self, Severity, CHECKERS};
+use crate::config::{Config, FileSuppression};
+use crate::k_limits::{self, KLimits};
+use crate::smt_solver::DEFAULT_TIMEOUT_MS;
+
 /// Creates the clap::Command metadata for argument parsing.
 fn make_options_parser(running_test_harness: bool) -> Command {
     // We could put this into lazy_static! with a Mutex around, but we really do not expect
     // to construct this more than once per regular program run.
     let mut parser = Command::new("HEPHA")
         .no_binary_name(true)
+        // The options of the configuration file come first and may be given again as flags
+        .args_override_self(true)
         .version("v1.1.7")
         .arg(Arg::new("single_func")
             .long("single_func")
@@ -37,26 +48,73 @@ fn make_options_parser(running_test_harness: bool) -> Command {
             .long_help("Name is a top-level crate type"))
         .arg(Arg::new("body_analysis_timeout")
             .long("body_analysis_timeout")
+            .alias("max-analysis-time-for-body")
             .num_args(1)
             .default_value("30")
             .help("The maximum number of seconds that HEPHA will spend analyzing a function body.")
-            .long_help("The default is 30 seconds."))
+            .long_help("The default is 30 seconds. Fractions of a second, such as 0.5, are allowed."))
         .arg(Arg::new("crate_analysis_timeout")
             .long("crate_analysis_timeout")
             .num_args(1)
             .default_value("240")
             .help("The maximum number of seconds that HEPHA will spend analyzing a function body.")
             .long_help("The default is 240 seconds."))
+        .arg(Arg::new("max_elements_to_track")
+            .long("max-elements-to-track")
+            .num_args(1)
+            .help("The maximum number of elements of an array or slice that are tracked individually.")
+            .long_help("The default is 100. Raising it makes the analysis more precise for state structs with large arrays, at the cost of time and memory."))
+        .arg(Arg::new("max_path_length")
+            .long("max-path-length")
+            .num_args(1)
+            .help("The maximum length of the access paths that are tracked.")
+            .long_help("The default is 300. Longer paths are approximated by an unknown value."))
+        .arg(Arg::new("max_outer_fixpoint_iterations")
+            .long("max-outer-fixpoint-iterations")
+            .num_args(1)
+            .help("The maximum number of iterations of the fixed point of a loop.")
+            .long_help("The default is 10. Loops that have not reached a fixed point by then are reported as such with --diag=paranoid."))
+        .arg(Arg::new("max_loop_unrolling")
+            .long("max-loop-unrolling")
+            .num_args(1)
+            .help("The maximum trip count of a loop that is unrolled instead of widened.")
+            .long_help("The default is 16. A loop whose trip count is a constant no larger than this is analyzed one iteration at a time, so that the values it computes in each iteration are kept apart."))
+        .arg(Arg::new("max_recursive_iterations")
+            .long("max-recursive-iterations")
+            .num_args(1)
+            .help("The maximum number of iterations of the fixed point of the summaries of recursive functions.")
+            .long_help("The default is 8. The summaries of the functions of a cycle of calls are computed together, and are incomplete if they still change after this many iterations."))
         .arg(Arg::new("statistics")
             .long("statistics")
             .num_args(0)
             .help("Just print out whether crates were analyzed, etc.")
             .long_help("Just print out whether crates were analyzed and how many diagnostics were produced for each crate."))
+        .arg(Arg::new("quiet")
+            .long("quiet")
+            .num_args(0)
+            .help("Do not print the summary of the run to stderr.")
+            .long_help("Without this flag, the number of functions analyzed and timed out, the number of diagnostics of each checker and the wall time are printed to stderr once all of the functions of a crate have been analyzed."))
+        .arg(Arg::new("stats_json")
+            .long("stats-json")
+            .num_args(1)
+            .help("Write the summary of the run as a JSON object to the given file.")
+            .long_help("Writes the crate name, the number of functions analyzed and timed out, the number of diagnostics by the name of the checker that reported them, or by verification or timeout for the others, and the wall time in milliseconds."))
         .arg(Arg::new("call_graph_config")
             .long("call_graph_config")
             .num_args(1)
             .help("Path call graph config.")
             .long_help(r#"Path to a JSON file that configures call graph output. Please see the documentation for details (https://github.com/endorlabs/HEPHA/blob/main/documentation/CallGraph.md)."#))
+        .arg(Arg::new("call_graph_out")
+            .long("call-graph-out")
+            .num_args(1)
+            .help("Write the call graph of the analyzed functions to the given file.")
+            .long_help("Writes a node for every function that is analyzed or called, labeled with its summary key and marked if a finding of a checker was reported in it, and an edge for every call, cross-program invocation and dominance of one call by another, with its kind. Nodes and edges are sorted, so that the output is the same in every run."))
+        .arg(Arg::new("call_graph_format")
+            .long("call-graph-format")
+            .num_args(1)
+            .value_parser(["dot", "graphml"])
+            .default_value("dot")
+            .help("The format of the call graph written with --call-graph-out."))
         .arg(Arg::new("print_function_names")
             .long("print_function_names")
             .num_args(0)
@@ -64,7 +122,178 @@ fn make_options_parser(running_test_harness: bool) -> Command {
         .arg(Arg::new("print_summaries")
             .long("print_summaries")
             .num_args(0)
-            .help("Print out function summaries (work in progress)"));
+            .help("Print out function summaries (work in progress)"))
+        .arg(Arg::new("path_constraints")
+            .long("path-constraints")
+            .num_args(0)
+            .help("Report the conditions under which each basic block is entered.")
+            .long_help("Emits a note for every reachable basic block that lists the conjuncts of its entry condition, each linked to the branch that introduced it."))
+        .arg(Arg::new("path_constraints_report")
+            .long("path-constraints-report")
+            .num_args(1)
+            .help("Write the path constraints of every basic block to an HTML report in the given directory.")
+            .long_help("Writes <directory>/<crate name>.html with a panel per basic block that lists the conjuncts of its entry condition, each hyperlinked to the branch that introduced it."))
+        .arg(Arg::new("counterexamples")
+            .long("counterexamples")
+            .num_args(0)
+            .help("Note the values that make a possibly false condition false.")
+            .long_help("Attaches a note such as `counterexample: amount = 18446744073709551615, balance = 0` to the diagnostics of verification conditions, preconditions and arithmetic that may fail, with the values that the SMT solver assigns to the variables of the condition. Finding the values takes another query of the solver for every such diagnostic."))
+        .arg(Arg::new("smt_backend")
+            .long("smt-backend")
+            .num_args(1)
+            .value_parser(["z3", "cvc5", "none"])
+            .help("The SMT solver that decides the conditions of the analysis.")
+            .long_help("Selects the SMT solver among those that hepha was built with: z3 with the z3 feature, which is the default, and cvc5 with the cvc5 feature. With none, no solver is used and only the abstract domains decide conditions, so that more diagnostics say that something is possible. Defaults to z3 if it was built in, else to cvc5 if that was built in, else to none."))
+        .arg(Arg::new("smt_timeout_ms")
+            .long("smt-timeout-ms")
+            .num_args(1)
+            .default_value("500")
+            .help("The time in milliseconds that a query of the SMT solver may take.")
+            .long_help("A query that takes longer is given up on, and the condition that it was to decide is left to the abstract domains, as if the solver could not decide it. The number of queries, of those that timed out and the time spent on them are part of the summary of the run. The default is 500."))
+        .arg(Arg::new("tighten_intervals")
+            .long("tighten-intervals")
+            .num_args(0)
+            .help("Narrow the bounds of values that decide overflows and offsets with the SMT solver.")
+            .long_help("The checks of arithmetic overflow and of pointer offsets first look at the range of values that an operand can have, as it follows from the structure of its expression, and only ask the solver when the range does not settle the check. With this flag, the solver is also asked to narrow the range under the path condition, by a binary search of at most 16 queries per value."))
+        .arg(Arg::new("emit_summaries")
+            .long("emit-summaries")
+            .num_args(1)
+            .help("Write the summaries of the analyzed functions as JSON to the given file.")
+            .long_help("Writes the preconditions, post condition and side effects of every analyzed function, by the stable id of the function, both in the syntax of Rust and in a structured form whose schema has a version field."))
+        .arg(Arg::new("export_facts")
+            .long("export-facts")
+            .num_args(1)
+            .help("Write the facts that the contract checkers observe as Datalog relations to the given directory.")
+            .long_help("Writes the tab separated relations calls(caller, callee, span), reads_clock(fn, span), writes_lamports(fn, account_param, span) and tagged_flow(fn, source_kind, sink_span) to <directory>/<relation>.facts, and their declarations to <directory>/schema.dl, so that Soufflé can run custom queries over them."))
+        .arg(Arg::new("record_call_states")
+            .long("record-call-states")
+            .num_args(1)
+            .requires("emit_call_states")
+            .help("Record the state of the analysis at every call of the functions whose def path contains the given string.")
+            .long_help("Snapshots the entry condition and the values of the paths rooted in parameters, in accounts and in references to integers, such as balances, right before every call of a matching function, once the fixed point of its body has been reached."))
+        .arg(Arg::new("emit_call_states")
+            .long("emit-call-states")
+            .num_args(1)
+            .help("Write the states recorded with --record-call-states as JSON to the given file.")
+            .long_help("Writes the states by the stable id of the function and the source location of the call, as file:line:column, in a form whose schema has a version field."))
+        .arg(Arg::new("call_states_limit")
+            .long("call-states-limit")
+            .num_args(1)
+            .default_value("64")
+            .help("The maximum number of calls of a function whose states are recorded.")
+            .long_help("The states of further calls are dropped and the function is marked as truncated. The default is 64."))
+        .arg(Arg::new("emit_fixes")
+            .long("emit-fixes")
+            .num_args(1)
+            .help("Write the machine applicable fixes of the reported findings as a unified diff to the given file.")
+            .long_help("Collects the suggestions that come with findings and that are known to compile, such as checked arithmetic and owner guards, and writes them as a patch that can be applied with `patch -p1` or `git apply`. Of fixes that overlap, only the first in source order is kept."))
+        .arg(Arg::new("ledger_account")
+            .long("ledger-account")
+            .num_args(1)
+            .help("Check that the balances kept in maps add up to the lamports of the account held by the variable with the given name.")
+            .long_help("Assumes that the total of the balances kept in maps equals the lamports of the account at the entry of every function that has a variable with the given name holding an account, and checks with the solver that the function changes both by the same amount before each return of Ok. The first update of either that leaves them apart is reported."))
+        .arg(Arg::new("batch_solver_queries")
+            .long("batch-solver-queries")
+            .num_args(0)
+            .help("Discharge the solver queries of a function body in a single solver context.")
+            .long_help("Checks the conditions of a function body under assumption literals in one solver context, instead of pushing and popping a context per query."))
+        .arg(Arg::new("threads")
+            .long("threads")
+            .num_args(1)
+            .conflicts_with_all(["dual_build", "incremental", "changed_functions", "export_facts", "record_call_states", "path_constraints_report", "call_graph_config", "call_graph_out", "print_summaries", "trace_function", "constant_time"])
+            .help("Analyze the functions of the crate on the given number of threads.")
+            .long_help("Groups the functions that are analyzed as roots by the strongly connected components of the call graph of the crate and analyzes the components bottom-up, those that do not call one another on the worker threads of the compiler at the same time. Every component is analyzed with caches and SMT solvers of its own, and its diagnostics are merged with those of the others in the same order whatever the number of threads, so that the output is the same with --threads 1 as with any other number. Without this flag, the roots are analyzed one after the other and share the summaries of the functions that they call. Cannot be combined with the flags that collect the state of the whole crate, such as --dual-build, --incremental and --export-facts."))
+        .arg(Arg::new("trace_function")
+            .long("trace-function")
+            .num_args(1)
+            .help("Log at the trace level only while analyzing functions whose def path contains the given string.")
+            .long_help("Lets the trace messages of HEPHA through the logger, but enables them only while the body of a matching function, or of one of its callees up to --trace-depth calls deep, is analyzed."))
+        .arg(Arg::new("trace_depth")
+            .long("trace-depth")
+            .num_args(1)
+            .default_value("0")
+            .help("The depth of the callees of a function given with --trace-function that are traced as well."))
+        .arg(Arg::new("adversarial_public_api")
+            .long("adversarial-public-api")
+            .num_args(0)
+            .help("Analyze every exported function of a library crate as an entry point with arguments chosen by an adversary.")
+            .long_help("Analyzes every function that other crates can call, including constant functions, with nothing assumed about its arguments, and reports each precondition that the analysis infers for it as an obligation of its callers, instead of silently passing it on."))
+        .arg(Arg::new("dual_build")
+            .long("dual-build")
+            .num_args(0)
+            .help("Report verification conditions that are only proven thanks to an assume! that is not present in the shipped code.")
+            .long_help("Analyzes the functions whose verify! conditions are proven a second time, with assume! treated as the no-op that it is in a build without cfg(hepha), and reports each condition that is no longer proven. Functions without annotations are not analyzed again, their summaries are reused."))
+        .arg(Arg::new("factor_exits")
+            .long("factor-exits")
+            .num_args(0)
+            .help("Summarize the side effects of the Ok and the Err returns of a function apart.")
+            .long_help("Keeps the side effects and the post condition of each kind of result that a function returns, such as the Ok and the Err variant of a Result, apart in its summary, instead of joining the states at all of its returns. Callers apply them under the condition that the result is of that kind, so that the path that a caller takes after `?` only sees the effects of the returns that lead to it."))
+        .arg(Arg::new("config")
+            .long("config")
+            .num_args(1)
+            .help("Read options, severities and suppressions from the given configuration file.")
+            .long_help("Overrides the hepha.toml at the root of the workspace, which cargo hepha passes on with HEPHA_CONFIG. The flags take precedence over the options of the file."))
+        .arg(Arg::new("dump_config")
+            .long("dump-config")
+            .num_args(0)
+            .help("Just print out the configuration that results from the configuration file and the flags, as TOML."))
+        .arg(Arg::new("list_checks")
+            .long("list-checks")
+            .num_args(0)
+            .help("Just print out the checkers and the codes of the findings they can report."))
+        .arg(Arg::new("explain")
+            .long("explain")
+            .num_args(1)
+            .help("Just print out the checker that reports findings with the given code."))
+        .arg(Arg::new("cluster_findings")
+            .long("cluster-findings")
+            .num_args(1)
+            .help("Just print out the findings of the given file, which --diag-format=json wrote, with the similar findings of copied code reported once.")
+            .long_help("Groups the findings that have the same code, the same source at their primary span once comments and whitespace are left out, and notes of the same shape, as when a template is copied into many programs of a workspace. Each group is reported as its first finding with the places of the others. With --format=json, each group is a line of JSON with the similar findings in full."))
+        .arg(Arg::new("deny")
+            .long("deny")
+            .num_args(1)
+            .action(ArgAction::Append)
+            .help("Report the findings with the given code as errors. May be given more than once."))
+        .arg(Arg::new("fail_on")
+            .long("fail-on")
+            .num_args(1)
+            .value_parser(["note", "warning", "error"])
+            .default_value("error")
+            .help("Exit with status 2 if there are findings of at least the given severity.")
+            .long_help("Exits with status 2 if HEPHA reports findings whose severity, after --deny has been applied, is at least the given one, with status 1 if the compilation or the analysis fails, and with status 0 otherwise."))
+        .arg(Arg::new("format")
+            .long("format")
+            .num_args(1)
+            .value_parser(["text", "json"])
+            .default_value("text")
+            .help("The format of the output of --list-checks, --explain and --cluster-findings."))
+        .arg(Arg::new("diag_format")
+            .long("diag-format")
+            .num_args(1)
+            .value_parser(["text", "json"])
+            .help("The format of the diagnostics, text by default.")
+            .long_help("With json, every diagnostic is written to stdout as a line of JSON with its file, line, column, finding code, message and a fingerprint that stays the same when unrelated parts of the file are edited, rather than being reported by rustc."))
+        .arg(Arg::new("baseline")
+            .long("baseline")
+            .num_args(1)
+            .help("Do not report the diagnostics of the given file, which --diag-format=json wrote in an earlier run.")
+            .long_help("Diagnostics are matched by their fingerprints. The findings that are not reported do not make the run fail."))
+        .arg(Arg::new("models")
+            .long("models")
+            .num_args(1)
+            .help("Read the models of foreign functions from the given TOML file, in addition to the default models.")
+            .long_help("Every [[model]] of the file names a function of another crate by its def path, such as solana_program::program::invoke, in which * matches any number of segments, and says what its calls do: returns = { min, max } for a pure function that returns a value of the range, tag = \"predictable_entropy\" or another built-in tag for a function whose result carries the tag, external_call = true for a function that the reentrancy checker treats like an invocation of another program, and forbidden_in_entrypoint = true for a function whose calls in entrypoints are reported. A model of the file replaces the default model of the same function."))
+        .arg(Arg::new("incremental")
+            .long("incremental")
+            .num_args(0)
+            .help("Only analyze the functions that changed since the last run and the functions that call them.")
+            .long_help("Keeps a record of every analyzed function in the summary store of the output directory, with a hash of its source and its diagnostics. The functions whose source is the same and that do not call a changed function are not analyzed again, their diagnostics are taken from the record. A function is also analyzed again if a function of another crate that it calls has other preconditions than when it was analyzed."))
+        .arg(Arg::new("changed_functions")
+            .long("changed-functions")
+            .num_args(1)
+            .help("Like --incremental, but take the changed functions from the given file rather than from the hashes of their sources.")
+            .long_help("The file lists the summary keys or the names of the changed functions, one per line, and may name functions of other crates. Lines that start with # are ignored."));
     if running_test_harness {
         parser = parser.arg(Arg::new("test_only")
             .long("test_only")
@@ -76,18 +305,174 @@ fn make_options_parser(running_test_harness: bool) -> Command {
 }
 
 /// Represents options passed to HEPHA.
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Options {
     pub single_func: Option<String>,
     pub test_only: bool,
     pub diag_level: DiagLevel,
     pub constant_time_tag_name: Option<String>,
-    pub max_analysis_time_for_body: u64,
+    pub max_analysis_time_for_body: Duration,
     pub max_analysis_time_for_crate: u64,
+    /// The limits on the elements, paths and iterations that are tracked.
+    pub k_limits: KLimits,
     pub statistics: bool,
+    pub quiet: bool,
+    /// The file to which the summary of the run is written as JSON.
+    pub stats_json: Option<String>,
     pub call_graph_config: Option<String>,
+    /// The file to which the call graph is written.
+    pub call_graph_out: Option<String>,
+    pub call_graph_format: CallGraphFormat,
     pub print_function_names: bool,
     pub print_summaries: bool,
+    pub path_constraints: bool,
+    pub path_constraints_report: Option<String>,
+    pub counterexamples: bool,
+    /// The SMT solver that decides the conditions of the analysis.
+    pub smt_backend: SmtBackend,
+    /// The time in milliseconds that a query of the SMT solver may take.
+    pub smt_timeout_ms: u64,
+    /// True if the bounds of values are narrowed with the SMT solver.
+    pub tighten_intervals: bool,
+    pub emit_summaries: Option<String>,
+    pub emit_fixes: Option<String>,
+    /// The directory to which the facts are written as Datalog relations.
+    pub export_facts: Option<String>,
+    pub record_call_states: Option<String>,
+    pub emit_call_states: Option<String>,
+    pub call_states_limit: usize,
+    pub ledger_account: Option<String>,
+    pub batch_solver_queries: bool,
+    /// The number of threads on which the components of the call graph are analyzed, if the
+    /// roots are not analyzed one after the other.
+    pub threads: Option<usize>,
+    pub trace_function: Option<String>,
+    pub trace_depth: usize,
+    pub adversarial_public_api: bool,
+    pub dual_build: bool,
+    pub factor_exits: bool,
+    /// The configuration file given with --config.
+    pub config: Option<String>,
+    pub dump_config: bool,
+    pub list_checks: bool,
+    pub explain: Option<String>,
+    /// The file of findings that are printed in clusters of similar findings.
+    pub cluster_findings: Option<String>,
+    pub output_format: OutputFormat,
+    /// The format in which diagnostics are reported.
+    pub diag_format: OutputFormat,
+    /// The file with the diagnostics of an earlier run that are not reported again.
+    pub baseline: Option<String>,
+    /// The file with the models of foreign functions that extend the default models.
+    pub models: Option<String>,
+    /// True if only the functions that changed since the last run are analyzed again.
+    pub incremental: bool,
+    /// The file that lists the changed functions, with --changed-functions.
+    pub changed_functions: Option<String>,
+    /// The codes of the findings that are reported as errors.
+    pub deny: Vec<String>,
+    /// The severities that the configuration file gives the findings with the given codes.
+    pub severities: BTreeMap<String, Severity>,
+    /// The files whose findings the configuration file suppresses.
+    pub suppressions: Vec<FileSuppression>,
+    /// The least severity of the findings that make the run fail, if any do.
+    pub fail_on: Option<Severity>,
+}
+
+/// The default options, but for the time that a query of the SMT solver may take, which would be
+/// no time at all by default.
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            single_func: Default::default(),
+            test_only: Default::default(),
+            diag_level: Default::default(),
+            constant_time_tag_name: Default::default(),
+            max_analysis_time_for_body: Default::default(),
+            max_analysis_time_for_crate: Default::default(),
+            k_limits: Default::default(),
+            statistics: Default::default(),
+            quiet: Default::default(),
+            stats_json: Default::default(),
+            call_graph_config: Default::default(),
+            call_graph_out: Default::default(),
+            call_graph_format: Default::default(),
+            print_function_names: Default::default(),
+            print_summaries: Default::default(),
+            path_constraints: Default::default(),
+            path_constraints_report: Default::default(),
+            counterexamples: Default::default(),
+            smt_backend: Default::default(),
+            smt_timeout_ms: DEFAULT_TIMEOUT_MS,
+            tighten_intervals: Default::default(),
+            emit_summaries: Default::default(),
+            emit_fixes: Default::default(),
+            export_facts: Default::default(),
+            record_call_states: Default::default(),
+            emit_call_states: Default::default(),
+            call_states_limit: Default::default(),
+            ledger_account: Default::default(),
+            batch_solver_queries: Default::default(),
+            threads: Default::default(),
+            trace_function: Default::default(),
+            trace_depth: Default::default(),
+            adversarial_public_api: Default::default(),
+            dual_build: Default::default(),
+            factor_exits: Default::default(),
+            config: Default::default(),
+            dump_config: Default::default(),
+            list_checks: Default::default(),
+            explain: Default::default(),
+            cluster_findings: Default::default(),
+            output_format: Default::default(),
+            diag_format: Default::default(),
+            baseline: Default::default(),
+            models: Default::default(),
+            incremental: Default::default(),
+            changed_functions: Default::default(),
+            deny: Default::default(),
+            severities: Default::default(),
+            suppressions: Default::default(),
+            fail_on: Default::default(),
+        }
+    }
+}
+
+/// Represents the format of output that is meant for other tools.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Represents the format in which the call graph is written.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CallGraphFormat {
+    #[default]
+    Dot,
+    GraphMl,
+}
+
+/// Represents the SMT solver that decides the conditions of the analysis.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SmtBackend {
+    Z3,
+    Cvc5,
+    /// Conditions are only decided by the abstract domains.
+    None,
+}
+
+impl Default for SmtBackend {
+    fn default() -> Self {
+        if cfg!(feature = "z3") {
+            SmtBackend::Z3
+        } else if cfg!(feature = "cvc5") {
+            SmtBackend::Cvc5
+        } else {
+            SmtBackend::None
+        }
+    }
 }
 
 /// Represents diag level.
@@ -109,6 +494,11 @@ pub enum DiagLevel {
 }
 
 impl Options {
+    /// True if the path constraints of basic blocks are reported as notes or in an HTML report.
+    pub fn reports_path_constraints(&self) -> bool {
+        self.path_constraints || self.path_constraints_report.is_some()
+    }
+
     /// Parse options from an argument string. The argument string will be split using unix
     /// shell escaping rules. Any content beyond the leftmost `--` token will be returned
     /// (excluding this token).
@@ -127,6 +517,38 @@ impl Options {
         )
     }
 
+    /// Parses the options of the given configuration file followed by those of the argument
+    /// string, so that the latter take precedence, and then applies the severities and the
+    /// suppressions of the file. Returns the content beyond the leftmost `--` token, as
+    /// parse_from_str does.
+    pub fn parse_from_config_and_str(
+        &mut self,
+        config: &Config,
+        s: &str,
+        handler: &EarlyDiagCtxt,
+        running_test_harness: bool,
+    ) -> Vec<String> {
+        let mut args = config.options.to_args();
+        args.extend(shellwords::split(s).unwrap_or_else(|e| {
+            handler.early_fatal(format!("Cannot parse argument string: {e:?}"))
+        }));
+        let rustc_args = self.parse(&args, handler, running_test_harness);
+        config.apply(self, handler);
+        rustc_args
+    }
+
+    /// Returns the names of the checkers whose findings the configuration file suppresses in the
+    /// source file with the given name.
+    pub fn checkers_suppressed_in(&self, file: &str) -> HashSet<&'static str> {
+        self.suppressions
+            .iter()
+            .filter(|suppression| Path::new(file).ends_with(&suppression.file))
+            .flat_map(|suppression| suppression.checkers.iter())
+            .filter_map(|name| CHECKERS.iter().find(|c| c.name == name))
+            .map(|checker| checker.name)
+            .collect()
+    }
+
     /// Parses options from a list of strings. Any content beyond the leftmost `--` token
     /// will be returned (excluding this token).
     pub fn parse(
@@ -206,9 +628,11 @@ impl Options {
         if matches.contains_id("body_analysis_timeout") {
             self.max_analysis_time_for_body =
                 match matches.get_one::<String>("body_analysis_timeout") {
-                    Some(s) => match s.parse::<u64>() {
-                        Ok(v) => v,
-                        Err(_) => handler.early_fatal("--body_analysis_timeout expects an integer"),
+                    Some(s) => match s.parse::<f64>().map(Duration::try_from_secs_f64) {
+                        Ok(Ok(v)) if !v.is_zero() => v,
+                        _ => handler.early_fatal(
+                            "--body_analysis_timeout expects a positive number of seconds",
+                        ),
                     },
                     None => assume_unreachable!(),
                 }
@@ -224,15 +648,78 @@ impl Options {
                 None => assume_unreachable!(),
             }
         }
+        if let Some(v) = parse_limit(
+            &matches,
+            "max_elements_to_track",
+            k_limits::MAX_ELEMENTS_TO_TRACK_LIMIT,
+            handler,
+        ) {
+            self.k_limits.max_elements_to_track = v;
+        }
+        if let Some(v) = parse_limit(
+            &matches,
+            "max_path_length",
+            k_limits::MAX_PATH_LENGTH_LIMIT,
+            handler,
+        ) {
+            self.k_limits.max_path_length = v;
+        }
+        if let Some(v) = parse_limit(
+            &matches,
+            "max_outer_fixpoint_iterations",
+            k_limits::MAX_FIXPOINT_ITERATIONS_LIMIT,
+            handler,
+        ) {
+            self.k_limits.max_fixpoint_iterations = v;
+        }
+        if let Some(v) = parse_limit(
+            &matches,
+            "max_loop_unrolling",
+            k_limits::MAX_LOOP_UNROLLING_LIMIT,
+            handler,
+        ) {
+            self.k_limits.max_loop_unrolling = v;
+        }
+        if let Some(v) = parse_limit(
+            &matches,
+            "max_recursive_iterations",
+            k_limits::MAX_RECURSIVE_ITERATIONS_LIMIT,
+            handler,
+        ) {
+            self.k_limits.max_recursive_iterations = v;
+        }
         if !matches!(
             matches.value_source("statistics"),
             Some(ValueSource::DefaultValue)
         ) {
             self.statistics = true;
         }
+        if !matches!(
+            matches.value_source("quiet"),
+            Some(ValueSource::DefaultValue)
+        ) {
+            self.quiet = true;
+        }
+        if matches.contains_id("stats_json") {
+            self.stats_json = matches.get_one::<String>("stats_json").cloned();
+        }
         if matches.contains_id("call_graph_config") {
             self.call_graph_config = matches.get_one::<String>("call_graph_config").cloned();
         }
+        if matches.contains_id("call_graph_out") {
+            self.call_graph_out = matches.get_one::<String>("call_graph_out").cloned();
+        }
+        if matches.contains_id("call_graph_format") {
+            self.call_graph_format = match matches
+                .get_one::<String>("call_graph_format")
+                .unwrap()
+                .as_str()
+            {
+                "dot" => CallGraphFormat::Dot,
+                "graphml" => CallGraphFormat::GraphMl,
+                _ => assume_unreachable!(),
+            };
+        }
         if !matches!(
             matches.value_source("print_function_names"),
             Some(ValueSource::DefaultValue)
@@ -245,6 +732,212 @@ impl Options {
         ) {
             self.print_summaries = true;
         }
+        if !matches!(
+            matches.value_source("path_constraints"),
+            Some(ValueSource::DefaultValue)
+        ) {
+            self.path_constraints = true;
+        }
+        if matches.contains_id("path_constraints_report") {
+            self.path_constraints_report = matches
+                .get_one::<String>("path_constraints_report")
+                .cloned();
+        }
+        if !matches!(
+            matches.value_source("counterexamples"),
+            Some(ValueSource::DefaultValue)
+        ) {
+            self.counterexamples = true;
+        }
+        if matches.contains_id("smt_backend") {
+            self.smt_backend = match matches.get_one::<String>("smt_backend").unwrap().as_str() {
+                "z3" if cfg!(feature = "z3") => SmtBackend::Z3,
+                "cvc5" if cfg!(feature = "cvc5") => SmtBackend::Cvc5,
+                "none" => SmtBackend::None,
+                backend => handler.early_fatal(format!(
+                    "--smt-backend {backend}: hepha was built without the {backend} feature"
+                )),
+            };
+        }
+        if matches.contains_id("smt_timeout_ms") {
+            self.smt_timeout_ms = match matches.get_one::<String>("smt_timeout_ms") {
+                Some(s) => match s.parse::<u64>() {
+                    Ok(v) => v,
+                    Err(_) => handler.early_fatal("--smt-timeout-ms expects an integer"),
+                },
+                None => assume_unreachable!(),
+            }
+        }
+        if !matches!(
+            matches.value_source("tighten_intervals"),
+            Some(ValueSource::DefaultValue)
+        ) {
+            self.tighten_intervals = true;
+        }
+        if matches.contains_id("emit_summaries") {
+            self.emit_summaries = matches.get_one::<String>("emit_summaries").cloned();
+        }
+        if matches.contains_id("emit_fixes") {
+            self.emit_fixes = matches.get_one::<String>("emit_fixes").cloned();
+        }
+        if matches.contains_id("export_facts") {
+            self.export_facts = matches.get_one::<String>("export_facts").cloned();
+        }
+        if matches.contains_id("record_call_states") {
+            self.record_call_states = matches.get_one::<String>("record_call_states").cloned();
+        }
+        if matches.contains_id("emit_call_states") {
+            self.emit_call_states = matches.get_one::<String>("emit_call_states").cloned();
+        }
+        if matches.contains_id("ledger_account") {
+            self.ledger_account = matches.get_one::<String>("ledger_account").cloned();
+        }
+        if matches.contains_id("call_states_limit") {
+            self.call_states_limit = match matches.get_one::<String>("call_states_limit") {
+                Some(s) => match s.parse::<usize>() {
+                    Ok(v) => v,
+                    Err(_) => handler.early_fatal("--call-states-limit expects an integer"),
+                },
+                None => assume_unreachable!(),
+            }
+        }
+        if !matches!(
+            matches.value_source("batch_solver_queries"),
+            Some(ValueSource::DefaultValue)
+        ) {
+            self.batch_solver_queries = true;
+        }
+        if matches.contains_id("threads") {
+            self.threads = match matches.get_one::<String>("threads") {
+                Some(s) => match s.parse::<usize>() {
+                    Ok(v) if v > 0 => Some(v),
+                    _ => handler.early_fatal("--threads expects a positive integer"),
+                },
+                None => assume_unreachable!(),
+            }
+        }
+        if matches.contains_id("trace_function") {
+            self.trace_function = matches.get_one::<String>("trace_function").cloned();
+        }
+        if matches.contains_id("trace_depth") {
+            self.trace_depth = match matches.get_one::<String>("trace_depth") {
+                Some(s) => match s.parse::<usize>() {
+                    Ok(v) => v,
+                    Err(_) => handler.early_fatal("--trace-depth expects an integer"),
+                },
+                None => assume_unreachable!(),
+            }
+        }
+        if !matches!(
+            matches.value_source("adversarial_public_api"),
+            Some(ValueSource::DefaultValue)
+        ) {
+            self.adversarial_public_api = true;
+        }
+        if !matches!(
+            matches.value_source("dual_build"),
+            Some(ValueSource::DefaultValue)
+        ) {
+            self.dual_build = true;
+        }
+        if !matches!(
+            matches.value_source("factor_exits"),
+            Some(ValueSource::DefaultValue)
+        ) {
+            self.factor_exits = true;
+        }
+        if matches.contains_id("config") {
+            self.config = matches.get_one::<String>("config").cloned();
+        }
+        if !matches!(
+            matches.value_source("dump_config"),
+            Some(ValueSource::DefaultValue)
+        ) {
+            self.dump_config = true;
+        }
+        if !matches!(
+            matches.value_source("list_checks"),
+            Some(ValueSource::DefaultValue)
+        ) {
+            self.list_checks = true;
+        }
+        if matches.contains_id("explain") {
+            self.explain = matches.get_one::<String>("explain").cloned();
+        }
+        if matches.contains_id("cluster_findings") {
+            self.cluster_findings = matches.get_one::<String>("cluster_findings").cloned();
+        }
+        if let Some(codes) = matches.get_many::<String>("deny") {
+            for code in codes {
+                if checker_registry::checker_for_code(code).is_none() {
+                    handler.early_fatal(format!("--deny: no checker reports {code}"));
+                }
+                self.deny.push(code.clone());
+            }
+        }
+        // Do not let the default value override a level given by HEPHA_FLAGS
+        if matches.contains_id("fail_on")
+            && (self.fail_on.is_none()
+                || !matches!(
+                    matches.value_source("fail_on"),
+                    Some(ValueSource::DefaultValue)
+                ))
+        {
+            self.fail_on = match matches.get_one::<String>("fail_on").unwrap().as_str() {
+                "note" => Some(Severity::Note),
+                "warning" => Some(Severity::Warning),
+                "error" => Some(Severity::Error),
+                _ => assume_unreachable!(),
+            };
+        }
+        if matches.contains_id("format") {
+            self.output_format = match matches.get_one::<String>("format").unwrap().as_str() {
+                "text" => OutputFormat::Text,
+                "json" => OutputFormat::Json,
+                _ => assume_unreachable!(),
+            };
+        }
+        if matches.contains_id("diag_format") {
+            self.diag_format = match matches.get_one::<String>("diag_format").unwrap().as_str() {
+                "text" => OutputFormat::Text,
+                "json" => OutputFormat::Json,
+                _ => assume_unreachable!(),
+            };
+        }
+        if matches.contains_id("baseline") {
+            self.baseline = matches.get_one::<String>("baseline").cloned();
+        }
+        if matches.contains_id("models") {
+            self.models = matches.get_one::<String>("models").cloned();
+        }
+        if !matches!(
+            matches.value_source("incremental"),
+            Some(ValueSource::DefaultValue)
+        ) {
+            self.incremental = true;
+        }
+        if matches.contains_id("changed_functions") {
+            self.changed_functions = matches.get_one::<String>("changed_functions").cloned();
+            self.incremental = true;
+        }
         args[rustc_args_start..].to_vec()
     }
 }
+
+// Returns the value of the limit with the given id, if it is given, after checking that it is at
+// least 1 and at most the given maximum.
+fn parse_limit(
+    matches: &ArgMatches,
+    id: &str,
+    max: usize,
+    handler: &EarlyDiagCtxt,
+) -> Option<usize> {
+    let s = matches.get_one::<String>(id)?;
+    match s.parse::<usize>() {
+        Ok(v) if (1..=max).contains(&v) => Some(v),
+        _ => handler.early_fatal(format!(
+            "--{} expects an integer from 1 to {max}",
+            id.replace('_', "-")
+        )),
+    }
+}
@@ -245,7 +245,27 @@ impl PartialOrd for PathEnum {
                 Some(std::cmp::Ordering::Equal) => ls.partial_cmp(rs),
                 other => other,
             },
-            (_, _) => None,
+            // Paths of different kinds are ordered by kind, so that sorting a collection of paths
+            // does not depend on the order in which the paths were found
+            (_, _) => self.kind_rank().partial_cmp(&other.kind_rank()),
+        }
+    }
+}
+
+impl PathEnum {
+    // The position of the kind of the path in the declaration of PathEnum
+    fn kind_rank(&self) -> u8 {
+        match self {
+            PathEnum::Computed { .. } => 0,
+            PathEnum::HeapBlock { .. } => 1,
+            PathEnum::LocalVariable { .. } => 2,
+            PathEnum::Offset { .. } => 3,
+            PathEnum::Parameter { .. } => 4,
+            PathEnum::Result => 5,
+            PathEnum::StaticVariable { .. } => 6,
+            PathEnum::PhantomData => 7,
+            PathEnum::PromotedConstant { .. } => 8,
+            PathEnum::QualifiedPath { .. } => 9,
         }
     }
 }
@@ -486,6 +506,22 @@ impl Path {
         }
     }
 
+    /// Returns true if the path is that of a variable bound by a quantified expression.
+    #[logfn_inputs(TRACE)]
+    pub fn is_bound_variable(&self) -> bool {
+        if let PathEnum::QualifiedPath {
+            qualifier,
+            selector,
+            ..
+        } = &self.value
+        {
+            matches!(qualifier.value, PathEnum::PhantomData)
+                && matches!(selector.as_ref(), PathSelector::Field(..))
+        } else {
+            false
+        }
+    }
+
     /// Returns an abstract value for "true if the path is the same runtime location as other"
     #[logfn_inputs(TRACE)]
     pub fn equals(&self, other: &Rc<Path>) -> Rc<AbstractValue> {
@@ -713,6 +749,16 @@ impl Path {
         Rc::new(PathEnum::Result.into())
     }
 
+    /// Creates the path of the variable that a quantified expression binds, given the ordinal of
+    /// the local that holds it. The path is a field of PhantomData rather than a local, so that
+    /// quantified conditions are not kept out of summaries and the bound variable is not refined
+    /// in callers.
+    #[logfn_inputs(TRACE)]
+    pub fn new_bound_variable(ordinal: usize) -> Rc<Path> {
+        let phantom_data = Rc::new(PathEnum::PhantomData.into());
+        Self::new_qualified(phantom_data, Rc::new(PathSelector::Field(ordinal)))
+    }
+
     /// Creates a path to the local variable, parameter or result local, corresponding to the ordinal.
     #[logfn_inputs(TRACE)]
     pub fn new_local_parameter_or_result(
@@ -760,7 +806,7 @@ impl Path {
             }
         }
         let qualifier_length = qualifier.path_length();
-        if qualifier_length >= k_limits::MAX_PATH_LENGTH {
+        if qualifier_length >= k_limits::max_path_length() {
             return Path::new_computed(abstract_value::BOTTOM.into());
         }
         Rc::new(
@@ -1085,7 +1131,12 @@ impl PathRefinement for Rc<Path> {
             if let PathSelector::Downcast(_, _, discr_val) = selector.as_ref() {
                 let discriminator = Path::new_discriminant(canonical_qualifier.clone());
                 if let Some(val) = environment.value_at(&discriminator) {
-                    if val.is_compile_time_constant() && val.ne(discr_val) {
+                    // The discriminant may be a constant of another type than the tag, such as one
+                    // that a switch on the discriminant or a deserialized value put there, so
+                    // compare the values rather than the constants.
+                    if val.is_compile_time_constant()
+                        && val.equals(discr_val.clone()).as_bool_if_known() == Some(false)
+                    {
                         // The downcast is impossible in this calling context
                         return Path::new_computed(Rc::new(abstract_value::BOTTOM));
                     }
@@ -0,0 +1,142 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// An HTML report that shows, for every analyzed function, a panel per basic block that lists
+// the conjuncts of the entry condition of the block. Each conjunct links to the source location
+// of the branch that introduced it, so that auditors can explore the paths of a function.
+
+use std::fmt::Write;
+use std::path::Path;
+
+/// The path constraints of the analyzed functions of a crate.
+#[derive(Debug, Default)]
+pub struct PathConstraintsReport {
+    functions: Vec<FunctionPanel>,
+}
+
+/// The blocks of a function that are only entered under some condition.
+#[derive(Debug)]
+pub struct FunctionPanel {
+    pub name: String,
+    pub blocks: Vec<BlockPanel>,
+}
+
+/// A block and the conjuncts of its entry condition.
+#[derive(Debug)]
+pub struct BlockPanel {
+    pub block: String,
+    pub location: SourceLocation,
+    pub conjuncts: Vec<ConjunctEntry>,
+}
+
+/// A conjunct of an entry condition, rendered as Rust, and the block and location of the branch
+/// that introduced it, if known.
+#[derive(Debug)]
+pub struct ConjunctEntry {
+    pub condition: String,
+    pub branch: Option<(String, SourceLocation)>,
+}
+
+/// A position in a source file, with one based line and column numbers.
+#[derive(Clone, Debug)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl PathConstraintsReport {
+    pub fn add_function(&mut self, function: FunctionPanel) {
+        if !function.blocks.is_empty() {
+            self.functions.push(function);
+        }
+    }
+
+    /// Renders the report as a self contained HTML page.
+    pub fn to_html(&self, crate_name: &str) -> String {
+        let mut html = String::new();
+        let title = escape(&format!("Path constraints of {crate_name}"));
+        let _ = writeln!(html, "<!DOCTYPE html>");
+        let _ = writeln!(
+            html,
+            "<html><head><meta charset=\"utf-8\"><title>{title}</title>"
+        );
+        let _ = writeln!(
+            html,
+            "<style>section.block {{ border: 1px solid #ccc; margin: 0.5em 0; padding: 0.5em; }}</style>"
+        );
+        let _ = writeln!(html, "</head><body><h1>{title}</h1>");
+        for function in &self.functions {
+            let name = escape(&function.name);
+            let _ = writeln!(html, "<h2>{name}</h2>");
+            for block in &function.blocks {
+                let _ = writeln!(
+                    html,
+                    "<section class=\"block\" id=\"{name}-{}\" data-conjuncts=\"{}\">",
+                    block.block,
+                    block.conjuncts.len()
+                );
+                let _ = writeln!(
+                    html,
+                    "<h3>{} at {}</h3>",
+                    block.block,
+                    link(&block.location)
+                );
+                let _ = writeln!(html, "<ul>");
+                for conjunct in &block.conjuncts {
+                    let condition = escape(&conjunct.condition);
+                    match &conjunct.branch {
+                        Some((branch, location)) => {
+                            let _ = writeln!(
+                                html,
+                                "<li><code>{condition}</code> from the branch in {branch} at {}</li>",
+                                link(location)
+                            );
+                        }
+                        None => {
+                            let _ = writeln!(html, "<li><code>{condition}</code></li>");
+                        }
+                    }
+                }
+                let _ = writeln!(html, "</ul></section>");
+            }
+        }
+        let _ = writeln!(html, "</body></html>");
+        html
+    }
+
+    /// Writes the report for the given crate to <directory>/<crate_name>.html.
+    pub fn write(&self, directory: &Path, crate_name: &str) -> std::io::Result<()> {
+        std::fs::create_dir_all(directory)?;
+        std::fs::write(
+            directory.join(format!("{crate_name}.html")),
+            self.to_html(crate_name),
+        )
+    }
+}
+
+// Renders a hyperlink to the given source location.
+fn link(location: &SourceLocation) -> String {
+    let file = escape(&location.file);
+    format!(
+        "<a href=\"{file}#L{}\">{file}:{}:{}</a>",
+        location.line, location.line, location.column
+    )
+}
+
+// Escapes the characters that have a meaning in HTML.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
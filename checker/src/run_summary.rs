@@ -0,0 +1,108 @@
+// The summary of the analysis of a crate, which the crate visitor prints to stderr once all of
+// its bodies have been analyzed, unless --quiet is given, and writes as JSON to the file given
+// with --stats-json. When a workspace with many programs is analyzed, the summaries of the crates
+// show where the time went and which checkers reported what.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::checker_registry::DiagnosticOrigin;
+use crate::smt_solver::SolverStatistics;
+
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct RunSummary {
+    pub crate_name: String,
+    /// The functions that were analyzed as roots, rather than for the summaries of their callers.
+    pub functions_analyzed: usize,
+    /// The functions whose analysis timed out, including those that were analyzed for the
+    /// summaries of their callers.
+    pub functions_timed_out: usize,
+    /// The functions whose diagnostics were taken from the last run by --incremental, since
+    /// neither they nor the functions they call changed.
+    #[serde(default)]
+    pub functions_reused: usize,
+    /// The number of diagnostics by the name of the checker that reported them, or by
+    /// "verification" or "timeout" for the diagnostics of the abstract interpreter.
+    pub diagnostics: BTreeMap<String, usize>,
+    pub wall_time_ms: u64,
+    /// The queries of the SMT solver, of all of the analyzed functions.
+    #[serde(default)]
+    pub solver_queries: usize,
+    /// The queries of the SMT solver that ran out of the time allowed by --smt-timeout-ms.
+    #[serde(default)]
+    pub solver_timeouts: usize,
+    #[serde(default)]
+    pub solver_time_ms: u64,
+    /// True if the analysis was cancelled by Ctrl-C or by --crate_analysis_timeout before all of
+    /// the functions were analyzed.
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+impl RunSummary {
+    /// Counts a diagnostic of the given origin.
+    pub fn count_diagnostic(&mut self, origin: DiagnosticOrigin) {
+        *self
+            .diagnostics
+            .entry(origin.name().to_string())
+            .or_insert(0) += 1;
+    }
+
+    /// Adds the queries of the solver of an analyzed function.
+    pub fn count_solver_queries(&mut self, statistics: &SolverStatistics) {
+        self.solver_queries += statistics.queries;
+        self.solver_timeouts += statistics.timeouts;
+        self.solver_time_ms += statistics.time.as_millis() as u64;
+    }
+
+    /// Adds the counts of the functions and of the solver queries of the given summary, which is
+    /// that of a component analyzed by a worker of --threads.
+    pub fn merge(&mut self, other: &RunSummary) {
+        self.functions_analyzed += other.functions_analyzed;
+        self.functions_timed_out += other.functions_timed_out;
+        self.solver_queries += other.solver_queries;
+        self.solver_timeouts += other.solver_timeouts;
+        self.solver_time_ms += other.solver_time_ms;
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("run summaries should serialize")
+    }
+
+    /// Writes the summary as JSON to the file at the given path.
+    pub fn write(&self, path: &std::path::Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    /// Renders the summary as a table.
+    pub fn to_table(&self) -> String {
+        let mut table = format!("HEPHA summary of {}\n", self.crate_name);
+        let mut row = |name: &str, value: String| {
+            let _ = writeln!(table, "  {name:<24} {value:>10}");
+        };
+        row("functions analyzed", self.functions_analyzed.to_string());
+        row("functions timed out", self.functions_timed_out.to_string());
+        if self.functions_reused > 0 {
+            row("functions reused", self.functions_reused.to_string());
+        }
+        for (origin, count) in &self.diagnostics {
+            row(&format!("diagnostics: {origin}"), count.to_string());
+        }
+        row("solver queries", self.solver_queries.to_string());
+        row("solver timeouts", self.solver_timeouts.to_string());
+        row(
+            "solver time",
+            format!("{:.3} s", self.solver_time_ms as f64 / 1000.0),
+        );
+        row(
+            "wall time",
+            format!("{:.3} s", self.wall_time_ms as f64 / 1000.0),
+        );
+        if self.truncated {
+            table.push_str("  cancelled before all functions were analyzed\n");
+        }
+        table
+    }
+}
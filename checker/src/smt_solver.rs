@@ -3,11 +3,23 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "cvc5")]
+use crate::cvc5_solver::{Cvc5ExpressionType, Cvc5Solver};
 use crate::expression::Expression;
+use crate::options::SmtBackend;
+#[cfg(feature = "z3")]
+use crate::z3_solver::{Z3ExpressionType, Z3Solver};
 
 use hepha_annotations::{get_model_field, precondition, set_model_field};
 use serde::{Deserialize, Serialize};
 
+/// The time in milliseconds that a query of the solver may take, unless --smt-timeout-ms says
+/// otherwise.
+pub const DEFAULT_TIMEOUT_MS: u64 = 500;
+
 /// The result of using the solver to solve an expression.
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum SmtResult {
@@ -39,6 +51,12 @@ pub trait SmtSolver<SmtExpressionType> {
     /// assertions in the solver. Can only be called after self.solve return SmtResult::Satisfiable.
     fn get_model_as_string(&self) -> String;
 
+    /// Provides the values that the model found by the last call of self.solve assigns to the
+    /// free variables, one "name = value" line per variable, where the name is the symbol that
+    /// get_as_smt_predicate gives the variable. Returns None if there is no model, which is the
+    /// case unless self.solve returned SmtResult::Satisfiable.
+    fn get_model_string(&self) -> Option<String>;
+
     /// Provides a string that contains a listing of all of the definitions and assertions that
     /// have been added to the solver.
     fn get_solver_state_as_string(&self) -> String;
@@ -61,6 +79,13 @@ pub trait SmtSolver<SmtExpressionType> {
     /// current context are all true.
     fn solve(&self) -> SmtResult;
 
+    /// True if the last call of self.solve or self.solve_under_assumptions returned
+    /// SmtResult::Undefined because the query ran out of the time allowed for it, rather than
+    /// because the solver could not decide it.
+    fn timed_out(&self) -> bool {
+        false
+    }
+
     /// Establish if the given expression can be satisfied (or not) without changing the current context.
     fn solve_expression(&self, expression: &SmtExpressionType) -> SmtResult {
         self.set_backtrack_position();
@@ -69,6 +94,18 @@ pub trait SmtSolver<SmtExpressionType> {
         self.backtrack();
         result
     }
+
+    /// Establish if the assertions in the current context, together with the given assumptions,
+    /// can be satisfied, without making the assumptions hold in later queries.
+    fn solve_under_assumptions(&self, assumptions: &[SmtExpressionType]) -> SmtResult {
+        self.set_backtrack_position();
+        for assumption in assumptions {
+            self.assert(assumption);
+        }
+        let result = self.solve();
+        self.backtrack();
+        result
+    }
 }
 
 /// A dummy implementation of SmtSolver to use in configurations where a real SMT solver is not available or required.
@@ -92,6 +129,10 @@ impl SmtSolver<usize> for SolverStub {
         String::from("not implemented")
     }
 
+    fn get_model_string(&self) -> Option<String> {
+        None
+    }
+
     fn get_solver_state_as_string(&self) -> String {
         String::from("not implemented")
     }
@@ -106,3 +147,227 @@ impl SmtSolver<usize> for SolverStub {
         SmtResult::Undefined
     }
 }
+
+/// An expression of the solver that BackendSolver dispatches to.
+#[derive(Clone, Copy, Debug)]
+pub enum SmtExpression {
+    None(usize),
+    #[cfg(feature = "z3")]
+    Z3(Z3ExpressionType),
+    #[cfg(feature = "cvc5")]
+    Cvc5(Cvc5ExpressionType),
+}
+
+impl SmtExpression {
+    #[cfg(feature = "z3")]
+    fn z3(&self) -> Z3ExpressionType {
+        match self {
+            SmtExpression::Z3(expression) => *expression,
+            _ => unreachable!("{self:?} is not an expression of Z3"),
+        }
+    }
+
+    #[cfg(feature = "cvc5")]
+    fn cvc5(&self) -> Cvc5ExpressionType {
+        match self {
+            SmtExpression::Cvc5(expression) => *expression,
+            _ => unreachable!("{self:?} is not an expression of CVC5"),
+        }
+    }
+}
+
+enum Backend {
+    None(SolverStub),
+    #[cfg(feature = "z3")]
+    Z3(Z3Solver),
+    #[cfg(feature = "cvc5")]
+    Cvc5(Cvc5Solver),
+}
+
+/// The queries that a solver was asked, how many of them ran out of time and how long they took.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SolverStatistics {
+    pub queries: usize,
+    pub timeouts: usize,
+    pub time: Duration,
+}
+
+/// The solver that is selected with --smt-backend, among the solvers that are built in, which
+/// keeps the statistics of the queries that it is asked.
+pub struct BackendSolver {
+    backend: Backend,
+    statistics: Cell<SolverStatistics>,
+}
+
+impl BackendSolver {
+    /// Creates a solver of the given backend that gives up on a query after timeout_ms
+    /// milliseconds. The options only accept the backends that are built in, so the others are
+    /// not expected here.
+    pub fn new(backend: SmtBackend, timeout_ms: u64) -> BackendSolver {
+        let backend = match backend {
+            #[cfg(feature = "z3")]
+            SmtBackend::Z3 => Backend::Z3(Z3Solver::new(timeout_ms)),
+            #[cfg(feature = "cvc5")]
+            SmtBackend::Cvc5 => Backend::Cvc5(Cvc5Solver::new(timeout_ms)),
+            _ => Backend::None(SolverStub::default()),
+        };
+        BackendSolver {
+            backend,
+            statistics: Cell::new(SolverStatistics::default()),
+        }
+    }
+
+    pub fn statistics(&self) -> SolverStatistics {
+        self.statistics.get()
+    }
+
+    /// Runs the query and counts it, and its time, in the statistics of the solver.
+    fn count_query(&self, query: impl FnOnce() -> SmtResult) -> SmtResult {
+        let start_instant = Instant::now();
+        let result = query();
+        let mut statistics = self.statistics.get();
+        statistics.queries += 1;
+        statistics.time += start_instant.elapsed();
+        if result == SmtResult::Undefined && self.timed_out() {
+            statistics.timeouts += 1;
+        }
+        self.statistics.set(statistics);
+        result
+    }
+}
+
+// Without a built in solver, only the stub is dispatched to and the expressions are not used.
+#[cfg_attr(not(any(feature = "z3", feature = "cvc5")), allow(unused_variables))]
+impl SmtSolver<SmtExpression> for BackendSolver {
+    fn as_debug_string(&self, expression: &SmtExpression) -> String {
+        match &self.backend {
+            Backend::None(solver) => solver.as_debug_string(&0),
+            #[cfg(feature = "z3")]
+            Backend::Z3(solver) => solver.as_debug_string(&expression.z3()),
+            #[cfg(feature = "cvc5")]
+            Backend::Cvc5(solver) => solver.as_debug_string(&expression.cvc5()),
+        }
+    }
+
+    fn assert(&self, expression: &SmtExpression) {
+        match &self.backend {
+            Backend::None(solver) => solver.assert(&0),
+            #[cfg(feature = "z3")]
+            Backend::Z3(solver) => solver.assert(&expression.z3()),
+            #[cfg(feature = "cvc5")]
+            Backend::Cvc5(solver) => solver.assert(&expression.cvc5()),
+        }
+    }
+
+    fn backtrack(&self) {
+        match &self.backend {
+            Backend::None(solver) => solver.backtrack(),
+            #[cfg(feature = "z3")]
+            Backend::Z3(solver) => solver.backtrack(),
+            #[cfg(feature = "cvc5")]
+            Backend::Cvc5(solver) => solver.backtrack(),
+        }
+    }
+
+    fn get_as_smt_predicate(&self, hepha_expression: &Expression) -> SmtExpression {
+        match &self.backend {
+            Backend::None(solver) => {
+                SmtExpression::None(solver.get_as_smt_predicate(hepha_expression))
+            }
+            #[cfg(feature = "z3")]
+            Backend::Z3(solver) => SmtExpression::Z3(solver.get_as_smt_predicate(hepha_expression)),
+            #[cfg(feature = "cvc5")]
+            Backend::Cvc5(solver) => {
+                SmtExpression::Cvc5(solver.get_as_smt_predicate(hepha_expression))
+            }
+        }
+    }
+
+    fn get_model_as_string(&self) -> String {
+        match &self.backend {
+            Backend::None(solver) => solver.get_model_as_string(),
+            #[cfg(feature = "z3")]
+            Backend::Z3(solver) => solver.get_model_as_string(),
+            #[cfg(feature = "cvc5")]
+            Backend::Cvc5(solver) => solver.get_model_as_string(),
+        }
+    }
+
+    fn get_model_string(&self) -> Option<String> {
+        match &self.backend {
+            Backend::None(solver) => solver.get_model_string(),
+            #[cfg(feature = "z3")]
+            Backend::Z3(solver) => solver.get_model_string(),
+            #[cfg(feature = "cvc5")]
+            Backend::Cvc5(solver) => solver.get_model_string(),
+        }
+    }
+
+    fn get_solver_state_as_string(&self) -> String {
+        match &self.backend {
+            Backend::None(solver) => solver.get_solver_state_as_string(),
+            #[cfg(feature = "z3")]
+            Backend::Z3(solver) => solver.get_solver_state_as_string(),
+            #[cfg(feature = "cvc5")]
+            Backend::Cvc5(solver) => solver.get_solver_state_as_string(),
+        }
+    }
+
+    fn invert_predicate(&self, expression: &SmtExpression) -> SmtExpression {
+        match &self.backend {
+            Backend::None(solver) => SmtExpression::None(solver.invert_predicate(&0)),
+            #[cfg(feature = "z3")]
+            Backend::Z3(solver) => SmtExpression::Z3(solver.invert_predicate(&expression.z3())),
+            #[cfg(feature = "cvc5")]
+            Backend::Cvc5(solver) => {
+                SmtExpression::Cvc5(solver.invert_predicate(&expression.cvc5()))
+            }
+        }
+    }
+
+    fn set_backtrack_position(&self) {
+        match &self.backend {
+            Backend::None(solver) => solver.set_backtrack_position(),
+            #[cfg(feature = "z3")]
+            Backend::Z3(solver) => solver.set_backtrack_position(),
+            #[cfg(feature = "cvc5")]
+            Backend::Cvc5(solver) => solver.set_backtrack_position(),
+        }
+    }
+
+    fn solve(&self) -> SmtResult {
+        self.count_query(|| match &self.backend {
+            Backend::None(solver) => solver.solve(),
+            #[cfg(feature = "z3")]
+            Backend::Z3(solver) => solver.solve(),
+            #[cfg(feature = "cvc5")]
+            Backend::Cvc5(solver) => solver.solve(),
+        })
+    }
+
+    fn solve_under_assumptions(&self, assumptions: &[SmtExpression]) -> SmtResult {
+        self.count_query(|| match &self.backend {
+            Backend::None(solver) => solver.solve_under_assumptions(&[]),
+            #[cfg(feature = "z3")]
+            Backend::Z3(solver) => {
+                let assumptions: Vec<_> = assumptions.iter().map(SmtExpression::z3).collect();
+                solver.solve_under_assumptions(&assumptions)
+            }
+            #[cfg(feature = "cvc5")]
+            Backend::Cvc5(solver) => {
+                let assumptions: Vec<_> = assumptions.iter().map(SmtExpression::cvc5).collect();
+                solver.solve_under_assumptions(&assumptions)
+            }
+        })
+    }
+
+    fn timed_out(&self) -> bool {
+        match &self.backend {
+            Backend::None(solver) => solver.timed_out(),
+            #[cfg(feature = "z3")]
+            Backend::Z3(solver) => solver.timed_out(),
+            #[cfg(feature = "cvc5")]
+            Backend::Cvc5(solver) => solver.timed_out(),
+        }
+    }
+}
@@ -0,0 +1,541 @@
+// A compatibility layer over the versions of solana_program that a workspace may pin. The
+// AccountInfo type has moved between crates and the way its fields are borrowed differs between
+// layouts, so the checkers ask this module which field of an account a place or a call accesses,
+// rather than matching the names and field paths of one particular version.
+
+use std::collections::HashMap;
+
+use rustc_hir::def_id::DefId;
+use rustc_hir::Mutability;
+use rustc_middle::mir;
+use rustc_middle::ty::{AdtDef, Ty, TyCtxt, TyKind};
+use rustc_span::source_map::Spanned;
+use rustc_target::abi::FieldIdx;
+
+/// The crate that defines the AccountInfo type, which depends on the pinned solana_program version.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AccountInfoCrate {
+    /// solana_program before 2.1, which defines AccountInfo itself.
+    SolanaProgram,
+    /// solana_program 2.1 and later, which re-exports AccountInfo from solana_account_info.
+    SolanaAccountInfo,
+    /// A crate that declares a type named AccountInfo with the same fields, such as a test stub.
+    Other,
+}
+
+/// The fields of an account that the checkers reason about.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AccountField {
+    Lamports,
+    Data,
+    Owner,
+    IsSigner,
+    IsWritable,
+    Key,
+}
+
+/// The layout of the AccountInfo type of the analyzed crate.
+#[derive(Debug)]
+pub struct AccountInfoLayout {
+    pub krate: AccountInfoCrate,
+    /// True if lamports and data are held as Rc<RefCell<&mut T>>, so that they can be borrowed
+    /// through the fields as well as through the accessors of AccountInfo.
+    pub borrows_through_ref_cell: bool,
+    fields: HashMap<FieldIdx, AccountField>,
+}
+
+impl AccountInfoLayout {
+    /// Returns the layout of the given type, if it is an AccountInfo struct.
+    pub fn of<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Option<AccountInfoLayout> {
+        let TyKind::Adt(def, args) = ty.kind() else {
+            return None;
+        };
+        if !is_account_info(tcx, *def) {
+            return None;
+        }
+        let krate = match tcx.crate_name(def.did().krate).as_str() {
+            "solana_program" => AccountInfoCrate::SolanaProgram,
+            "solana_account_info" => AccountInfoCrate::SolanaAccountInfo,
+            _ => AccountInfoCrate::Other,
+        };
+        let mut fields = HashMap::new();
+        let mut borrows_through_ref_cell = false;
+        for (index, field) in def.non_enum_variant().fields.iter_enumerated() {
+            let account_field = match field.name.as_str() {
+                "lamports" => AccountField::Lamports,
+                "data" => AccountField::Data,
+                "owner" => AccountField::Owner,
+                "is_signer" => AccountField::IsSigner,
+                "is_writable" => AccountField::IsWritable,
+                "key" => AccountField::Key,
+                _ => continue,
+            };
+            if account_field == AccountField::Lamports {
+                borrows_through_ref_cell = is_rc_of_ref_cell(tcx, field.ty(tcx, args));
+            }
+            fields.insert(index, account_field);
+        }
+        Some(AccountInfoLayout {
+            krate,
+            borrows_through_ref_cell,
+            fields,
+        })
+    }
+
+    /// Returns the account field that is stored at the given index, if the checkers model it.
+    pub fn field(&self, index: FieldIdx) -> Option<AccountField> {
+        self.fields.get(&index).copied()
+    }
+
+    /// Returns the index at which the given account field is stored.
+    pub fn index_of(&self, field: AccountField) -> Option<FieldIdx> {
+        self.fields
+            .iter()
+            .find(|(_, f)| **f == field)
+            .map(|(index, _)| *index)
+    }
+}
+
+/// True if the given type is the AccountInfo type of some solana_program version.
+pub fn is_account_info<'tcx>(tcx: TyCtxt<'tcx>, def: AdtDef<'tcx>) -> bool {
+    def.is_struct() && tcx.item_name(def.did()).as_str() == "AccountInfo"
+}
+
+/// Returns the field of an account that the given function reads or borrows, and whether the
+/// access is mutable, if the function is an accessor method of AccountInfo.
+pub fn accessed_field(tcx: TyCtxt<'_>, callee_def_id: DefId) -> Option<(AccountField, Mutability)> {
+    let impl_def_id = tcx.impl_of_method(callee_def_id)?;
+    let self_ty = tcx.type_of(impl_def_id).instantiate_identity();
+    if !matches!(self_ty.kind(), TyKind::Adt(def, _) if is_account_info(tcx, *def)) {
+        return None;
+    }
+    let access = match tcx.item_name(callee_def_id).as_str() {
+        "lamports" | "try_lamports" | "try_borrow_lamports" => {
+            (AccountField::Lamports, Mutability::Not)
+        }
+        "try_borrow_mut_lamports" => (AccountField::Lamports, Mutability::Mut),
+        "data_len" | "data_is_empty" | "try_data_len" | "try_data_is_empty" | "try_borrow_data" => {
+            (AccountField::Data, Mutability::Not)
+        }
+        "try_borrow_mut_data" | "realloc" | "resize" => (AccountField::Data, Mutability::Mut),
+        "assign" => (AccountField::Owner, Mutability::Mut),
+        "signer_key" => (AccountField::IsSigner, Mutability::Not),
+        _ => return None,
+    };
+    Some(access)
+}
+
+/// True if the function is Rent::minimum_balance, which returns the least number of lamports
+/// that makes an account with the given length of data exempt from rent.
+pub fn is_rent_minimum_balance(tcx: TyCtxt<'_>, callee_def_id: DefId) -> bool {
+    if tcx
+        .opt_item_name(callee_def_id)
+        .is_none_or(|name| name.as_str() != "minimum_balance")
+    {
+        return false;
+    }
+    let Some(impl_def_id) = tcx.impl_of_method(callee_def_id) else {
+        return false;
+    };
+    let self_ty = tcx.type_of(impl_def_id).instantiate_identity();
+    matches!(self_ty.kind(), TyKind::Adt(def, _) if tcx.item_name(def.did()).as_str() == "Rent")
+}
+
+/// The wrappers of accounts of Anchor, the types of the fields of a struct that derives Accounts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AnchorAccount {
+    /// Signer, whose is_signer flag Anchor checks before the handler runs.
+    Signer,
+    /// Account, AccountLoader, InterfaceAccount, Sysvar or SystemAccount, whose owner Anchor
+    /// checks when it deserializes the account.
+    Account,
+    /// Program or Interface, whose key Anchor checks.
+    Program,
+    /// UncheckedAccount or a bare AccountInfo, which Anchor does not check at all.
+    Unchecked,
+}
+
+impl AnchorAccount {
+    /// True if Anchor checks that the account is owned by the program, or by the program that
+    /// the type names, before the handler runs.
+    pub fn owner_is_checked(self) -> bool {
+        matches!(self, AnchorAccount::Account | AnchorAccount::Program)
+    }
+}
+
+/// Returns the kind of the given type, or of the type that it refers to, if it is a field type
+/// of an Anchor Accounts struct.
+pub fn anchor_account<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Option<AnchorAccount> {
+    let TyKind::Adt(def, _) = ty.peel_refs().kind() else {
+        return None;
+    };
+    if is_account_info(tcx, *def) {
+        return Some(AnchorAccount::Unchecked);
+    }
+    if tcx.crate_name(def.did().krate).as_str() != "anchor_lang" {
+        return None;
+    }
+    let kind = match tcx.item_name(def.did()).as_str() {
+        "Signer" => AnchorAccount::Signer,
+        "Account" | "AccountLoader" | "InterfaceAccount" | "Sysvar" | "SystemAccount" => {
+            AnchorAccount::Account
+        }
+        "Program" | "Interface" => AnchorAccount::Program,
+        "UncheckedAccount" => AnchorAccount::Unchecked,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Returns the index of the accounts field of the given type and the struct that the field
+/// refers to, if the type is the Context<'_, '_, '_, '_, T> of Anchor, or a reference to one.
+pub fn anchor_context<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Option<(FieldIdx, Ty<'tcx>)> {
+    let TyKind::Adt(def, args) = ty.peel_refs().kind() else {
+        return None;
+    };
+    if tcx.crate_name(def.did().krate).as_str() != "anchor_lang"
+        || tcx.item_name(def.did()).as_str() != "Context"
+    {
+        return None;
+    }
+    let accounts_type = args.types().next()?;
+    let (index, _) = def
+        .non_enum_variant()
+        .fields
+        .iter_enumerated()
+        .find(|(_, field)| field.name.as_str() == "accounts")?;
+    Some((index, accounts_type))
+}
+
+/// Follows the accounts of the Accounts struct of an Anchor handler, which the handler reaches
+/// through ctx.accounts, into the locals that refer to them and to the AccountInfo values that
+/// they deref to.
+#[derive(Debug, Default)]
+pub struct AnchorAccounts {
+    /// True if the Accounts struct of the handler has a Signer field.
+    pub has_signer: bool,
+    // The kinds and the names of the accounts that the locals refer to
+    locals: HashMap<mir::Local, (AnchorAccount, String)>,
+}
+
+impl AnchorAccounts {
+    /// Returns the tracker for the given body, which looks at the Accounts struct of its Context
+    /// parameter, if it has one.
+    pub fn new<'tcx>(tcx: TyCtxt<'tcx>, mir: &mir::Body<'tcx>) -> AnchorAccounts {
+        let mut accounts = AnchorAccounts::default();
+        let accounts_type = mir
+            .args_iter()
+            .find_map(|arg| anchor_context(tcx, mir.local_decls[arg].ty))
+            .map(|(_, ty)| ty);
+        if let Some(TyKind::Adt(def, args)) = accounts_type.map(|ty| ty.kind()) {
+            accounts.has_signer = def.all_fields().any(|field| {
+                anchor_account(tcx, field.ty(tcx, args)) == Some(AnchorAccount::Signer)
+            });
+        }
+        accounts
+    }
+
+    /// Records the locals that are assigned a reference to a field of an Accounts struct, or a
+    /// copy of a local that refers to one.
+    pub fn visit_assign<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+    ) {
+        if !place.projection.is_empty() {
+            return;
+        }
+        let account = match rvalue {
+            mir::Rvalue::Ref(_, _, source) => self.account_of_place(tcx, mir, source),
+            mir::Rvalue::Use(mir::Operand::Copy(source) | mir::Operand::Move(source))
+            | mir::Rvalue::CopyForDeref(source) => self.account_of_place(tcx, mir, source),
+            _ => None,
+        };
+        if let Some(account) = account {
+            self.locals.insert(place.local, account);
+        } else {
+            self.locals.remove(&place.local);
+        }
+    }
+
+    /// Records the AccountInfo that a call of deref, as_ref or to_account_info returns for a
+    /// local that refers to an account.
+    pub fn visit_call<'tcx>(
+        &mut self,
+        callee_name: &str,
+        args: &[Spanned<mir::Operand<'tcx>>],
+        destination: &mir::Place<'tcx>,
+    ) {
+        if !destination.projection.is_empty() {
+            return;
+        }
+        let account = args
+            .first()
+            .and_then(|arg| arg.node.place())
+            .filter(|_| matches!(callee_name, "deref" | "as_ref" | "to_account_info"))
+            .and_then(|receiver| self.locals.get(&receiver.local).cloned());
+        if let Some(account) = account {
+            self.locals.insert(destination.local, account);
+        } else {
+            self.locals.remove(&destination.local);
+        }
+    }
+
+    /// Returns the kind and the name of the account that the local refers to, if it is a field
+    /// of an Accounts struct.
+    pub fn account_of(&self, local: mir::Local) -> Option<&(AnchorAccount, String)> {
+        self.locals.get(&local)
+    }
+
+    // Returns the kind and the name of the account at the given place, if it is a field of an
+    // Accounts struct, or the account that a local refers to.
+    fn account_of_place<'tcx>(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        place: &mir::Place<'tcx>,
+    ) -> Option<(AnchorAccount, String)> {
+        if let Some((base, mir::ProjectionElem::Field(index, ty))) =
+            place.as_ref().iter_projections().last()
+        {
+            let kind = anchor_account(tcx, ty)?;
+            let TyKind::Adt(def, _) = base.ty(mir, tcx).ty.kind() else {
+                return None;
+            };
+            let name = def.non_enum_variant().fields[index].name.to_string();
+            return Some((kind, name));
+        }
+        if place
+            .projection
+            .iter()
+            .all(|elem| matches!(elem, mir::ProjectionElem::Deref))
+        {
+            return self.locals.get(&place.local).cloned();
+        }
+        None
+    }
+}
+
+/// A format that instruction data is commonly serialized with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SerializationFormat {
+    Borsh,
+    Bincode,
+}
+
+impl SerializationFormat {
+    /// The number of bytes that the index of the variant of an enum is serialized into.
+    pub fn variant_index_size(self) -> u128 {
+        match self {
+            SerializationFormat::Borsh => 1,
+            SerializationFormat::Bincode => 4,
+        }
+    }
+}
+
+/// Returns the format that the given function deserializes a value from bytes with, if it is
+/// BorshDeserialize::try_from_slice or deserialize, or bincode::deserialize.
+pub fn deserialization_format(
+    tcx: TyCtxt<'_>,
+    callee_def_id: DefId,
+) -> Option<SerializationFormat> {
+    let name = tcx.opt_item_name(callee_def_id)?;
+    match (tcx.crate_name(callee_def_id.krate).as_str(), name.as_str()) {
+        ("borsh", "try_from_slice" | "deserialize") => Some(SerializationFormat::Borsh),
+        ("bincode", "deserialize") => Some(SerializationFormat::Bincode),
+        _ => None,
+    }
+}
+
+/// The positions of the arguments of a function that builds an instruction of the system program
+/// that creates an account, allocates its data or credits it with lamports.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SystemInstruction {
+    /// The key of the account that is created, allocated or credited.
+    pub key: usize,
+    /// The key of the account that the lamports are transferred from, if the instruction only
+    /// transfers lamports.
+    pub source: Option<usize>,
+    /// The lamports that the account is credited with, if any.
+    pub lamports: Option<usize>,
+    /// The length of the data of the account, if the instruction allocates it.
+    pub space: Option<usize>,
+}
+
+/// Returns the arguments of the given function, if it builds an instruction of the system program
+/// that creates an account, allocates its data or credits it with lamports.
+pub fn system_instruction(tcx: TyCtxt<'_>, callee_def_id: DefId) -> Option<SystemInstruction> {
+    if tcx.crate_name(callee_def_id.krate).as_str() != "solana_system_interface"
+        && !tcx
+            .def_path_str(callee_def_id)
+            .contains("system_instruction")
+    {
+        return None;
+    }
+    let (key, source, lamports, space) = match tcx.opt_item_name(callee_def_id)?.as_str() {
+        "create_account" => (1, None, Some(2), Some(3)),
+        "create_account_with_seed" => (1, None, Some(4), Some(5)),
+        "allocate" => (0, None, None, Some(1)),
+        "allocate_with_seed" => (0, None, None, Some(3)),
+        "transfer" => (1, Some(0), Some(2), None),
+        "transfer_with_seed" => (4, Some(0), Some(5), None),
+        _ => return None,
+    };
+    Some(SystemInstruction {
+        key,
+        source,
+        lamports,
+        space,
+    })
+}
+
+/// True if the function builds an instruction of the system program or of a token program, such
+/// as system_instruction::transfer or spl_token::instruction::transfer, which returns the
+/// Instruction, or a Result of it, for the caller to invoke.
+pub fn builds_instruction(tcx: TyCtxt<'_>, callee_def_id: DefId) -> bool {
+    let path = tcx.def_path_str(callee_def_id);
+    let is_builder_crate = matches!(
+        tcx.crate_name(callee_def_id.krate).as_str(),
+        "solana_system_interface" | "spl_token" | "spl_token_2022" | "spl_associated_token_account"
+    ) || path.contains("system_instruction");
+    if !is_builder_crate || !path.contains("instruction") {
+        return false;
+    }
+    let output = tcx
+        .fn_sig(callee_def_id)
+        .skip_binder()
+        .skip_binder()
+        .output();
+    match output.kind() {
+        TyKind::Adt(def, args) if tcx.item_name(def.did()).as_str() == "Result" => args
+            .types()
+            .next()
+            .is_some_and(|ty| is_instruction(tcx, ty)),
+        _ => is_instruction(tcx, output),
+    }
+}
+
+// True if the type is the Instruction struct of solana_program
+fn is_instruction<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> bool {
+    matches!(ty.kind(), TyKind::Adt(def, _) if tcx.item_name(def.did()).as_str() == "Instruction")
+}
+
+/// Follows the fields of accounts that are borrowed through Rc and RefCell, such as
+/// `account.lamports.borrow_mut()`, to the calls that borrow their values.
+#[derive(Debug, Default)]
+pub struct AccountFieldBorrows {
+    // The locals that hold a reference to a field of an account, or to the RefCell inside it
+    locals: HashMap<mir::Local, AccountField>,
+}
+
+impl AccountFieldBorrows {
+    /// Records the locals that are assigned a reference to a field of an account.
+    pub fn visit_assign<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        mir: &mir::Body<'tcx>,
+        place: &mir::Place<'tcx>,
+        rvalue: &mir::Rvalue<'tcx>,
+    ) {
+        if !place.projection.is_empty() {
+            return;
+        }
+        let field = match rvalue {
+            mir::Rvalue::Ref(_, _, source) => account_field_of_place(tcx, mir, source),
+            _ => None,
+        };
+        if let Some(field) = field {
+            self.locals.insert(place.local, field);
+        } else {
+            self.locals.remove(&place.local);
+        }
+    }
+
+    /// Returns the field of an account that the given call reads or borrows, and whether the
+    /// access is mutable, either because the callee is an accessor of AccountInfo, or because it
+    /// borrows the RefCell of a field that is reached through a reference held by an argument.
+    pub fn visit_call<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        callee_def_id: DefId,
+        args: &[Spanned<mir::Operand<'tcx>>],
+        destination: &mir::Place<'tcx>,
+    ) -> Option<(AccountField, Mutability)> {
+        if let Some(access) = accessed_field(tcx, callee_def_id) {
+            return Some(access);
+        }
+        let field = args.first().and_then(|arg| match &arg.node {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => {
+                self.locals.get(&place.local).copied()
+            }
+            mir::Operand::Constant(..) => None,
+        })?;
+        match tcx.item_name(callee_def_id).as_str() {
+            // <Rc<RefCell<&mut T>> as Deref>::deref gives a reference to the RefCell
+            "deref" => {
+                if destination.projection.is_empty() {
+                    self.locals.insert(destination.local, field);
+                }
+                None
+            }
+            "borrow" | "try_borrow" => Some((field, Mutability::Not)),
+            "borrow_mut" | "try_borrow_mut" => Some((field, Mutability::Mut)),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the local that holds the account and the account field that the given place projects
+/// last, if the account is reached from the local through references only.
+pub fn projected_account_field<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mir: &mir::Body<'tcx>,
+    place: &mir::Place<'tcx>,
+) -> Option<(mir::Local, AccountField)> {
+    let (base, elem) = place.as_ref().iter_projections().last()?;
+    let mir::ProjectionElem::Field(index, _) = elem else {
+        return None;
+    };
+    if !base
+        .projection
+        .iter()
+        .all(|elem| matches!(elem, mir::ProjectionElem::Deref))
+    {
+        return None;
+    }
+    let field = AccountInfoLayout::of(tcx, base.ty(mir, tcx).ty)?.field(index)?;
+    Some((base.local, field))
+}
+
+// Returns the account field that is the last field projected by the given place, if any.
+fn account_field_of_place<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mir: &mir::Body<'tcx>,
+    place: &mir::Place<'tcx>,
+) -> Option<AccountField> {
+    let (base, elem) = place.as_ref().iter_projections().last()?;
+    let mir::ProjectionElem::Field(index, _) = elem else {
+        return None;
+    };
+    let layout = AccountInfoLayout::of(tcx, base.ty(mir, tcx).ty)?;
+    let field = layout.field(index)?;
+    if matches!(field, AccountField::Lamports | AccountField::Data)
+        && !layout.borrows_through_ref_cell
+    {
+        return None;
+    }
+    Some(field)
+}
+
+// True if the type is Rc<RefCell<T>>.
+fn is_rc_of_ref_cell<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> bool {
+    let TyKind::Adt(rc, args) = ty.kind() else {
+        return false;
+    };
+    if tcx.item_name(rc.did()).as_str() != "Rc" {
+        return false;
+    }
+    matches!(args.type_at(0).kind(), TyKind::Adt(cell, _) if tcx.item_name(cell.did()).as_str() == "RefCell")
+}
@@ -3,6 +3,7 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::env::current_dir;
@@ -11,6 +12,7 @@ use std::hash::{Hash, Hasher};
 use std::ops::Deref;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use itertools::Itertools;
 use log_derive::{logfn, logfn_inputs};
@@ -26,7 +28,8 @@ use crate::abstract_value::AbstractValue;
 use crate::abstract_value::AbstractValueTrait;
 use crate::constant_domain::FunctionReference;
 use crate::environment::Environment;
-use crate::expression::Expression;
+use crate::expression::{Expression, ExpressionType};
+use crate::incremental::AnalysisRecord;
 use crate::path::{Path, PathEnum, PathRoot, PathSelector};
 use crate::utils;
 
@@ -94,7 +97,9 @@ pub struct Summary {
 
     // A condition that should hold after a call that completes normally.
     // Callers should substitute parameter values with argument values and simplify the results
-    // under the current path condition.
+    // under the current path condition. The initial values of parameters, such as those that
+    // old! refers to, are substituted with the values of the arguments before the call, the
+    // other parameter values with those after the call.
     // The resulting value should be conjoined to the current path condition.
     pub post_condition: Option<Rc<AbstractValue>>,
 
@@ -103,6 +108,69 @@ pub struct Summary {
     /// but the return type specification is abstract.
     #[serde(skip)]
     pub return_type_index: usize,
+
+    /// What the function does to state outside of the program, so that the checkers of callers
+    /// can compose it with what they do themselves. It is not persisted, so that the summaries
+    /// shipped in the store keep their format, which means that callers only see the effects of
+    /// the functions of their own crate.
+    #[serde(skip)]
+    pub external_effects: ExternalEffects,
+
+    /// The pairs of account parameters that callers must pass different accounts for. They are
+    /// preconditions on the keys of the accounts, which are opaque to the abstract domain, so
+    /// they are kept apart from the other preconditions and, like the external effects, are not
+    /// persisted.
+    #[serde(skip)]
+    pub distinct_accounts: Vec<DistinctAccounts>,
+
+    /// The names of the checkers that the function suppresses with suppress!, so that callers
+    /// do not report the findings of these checkers that come from the summary either.
+    #[serde(skip)]
+    pub suppressed_checkers: Vec<&'static str>,
+
+    /// The side effects and post conditions of each kind of result that the function returns,
+    /// such as the Ok and the Err variant of a Result, if --factor-exits is given. A caller
+    /// applies them under the condition that the result is of that kind, so that the path that
+    /// takes a kind of result only sees what the function does before it returns that kind.
+    /// Like the external effects, they are not persisted.
+    #[serde(skip)]
+    pub exit_classes: Vec<ExitClass>,
+}
+
+/// The interactions of a function with other programs and the state it writes, as seen by
+/// the reentrancy checker of its callers.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ExternalEffects {
+    /// True if the function, or a function it calls, transfers lamports or invokes a program.
+    pub transfers_lamports: bool,
+    /// True if the function, or a function it calls, writes a balance that its caller can see.
+    pub writes_state: bool,
+    /// True if such a write follows a transfer on every path through the transfer.
+    pub writes_state_after_transfer: bool,
+}
+
+/// The side effects and the post condition of the returns of a function that produce one kind of
+/// result, such as the Ok variant of a Result.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct ExitClass {
+    /// The condition, in terms of the parameters and the result, that the result is of this kind.
+    pub condition: Rc<AbstractValue>,
+    /// The side effects of the returns of this kind, in the same form as those of the summary.
+    pub side_effects: Vec<(Rc<Path>, Rc<AbstractValue>)>,
+    /// A condition that holds after the returns of this kind.
+    pub post_condition: Option<Rc<AbstractValue>>,
+}
+
+/// Two account parameters of a function that must be different accounts, because the function
+/// moves lamports from one to the other, which does nothing if both are the same account.
+/// The parameters are given by their ordinals, which start at 1 as in the paths of parameters.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct DistinctAccounts {
+    pub first: usize,
+    pub second: usize,
+    /// The names of the parameters, for diagnostics and summary views.
+    pub first_name: String,
+    pub second_name: String,
 }
 
 /// Bundles together the condition of a precondition with the provenance (place where defined) of
@@ -130,6 +198,19 @@ pub struct Precondition {
 }
 
 impl Summary {
+    /// True if the function suppresses the findings of the checker with the given name.
+    pub fn suppresses(&self, checker: &str) -> bool {
+        self.suppressed_checkers.contains(&checker)
+    }
+
+    /// True if the summaries have the same preconditions and side effects, in the sense that the
+    /// values of each one are subsets of the values of the other, and are equally complete.
+    pub fn approx_eq(&self, other: &Summary) -> bool {
+        self.is_incomplete == other.is_incomplete
+            && self.is_subset_of(other)
+            && other.is_subset_of(self)
+    }
+
     #[logfn_inputs(TRACE)]
     pub fn is_subset_of(&self, other: &Summary) -> bool {
         if !Self::is_subset_of_preconditions(&self.preconditions[0..], &other.preconditions[0..]) {
@@ -205,22 +286,151 @@ impl Summary {
                 }
             }
         }
+        // The exit classes no longer describe the joined side effects
+        self.exit_classes.clear();
+    }
+
+    /// Returns the side effects that a caller applies. If the summary has exit classes, the value
+    /// at each path is that of the first class whose condition holds, so that the caller can
+    /// resolve it with the condition on the result that it branches on. Otherwise, and for the
+    /// structured values that cannot be selected by a condition, it is the joined value.
+    pub fn side_effects_by_exit_class(&self) -> Cow<'_, [(Rc<Path>, Rc<AbstractValue>)]> {
+        if self.exit_classes.is_empty() {
+            return Cow::Borrowed(&self.side_effects);
+        }
+        let joined_values: HashMap<&Rc<Path>, &Rc<AbstractValue>> = self
+            .side_effects
+            .iter()
+            .map(|(path, value)| (path, value))
+            .collect();
+        let mut paths: Vec<&Rc<Path>> = self
+            .exit_classes
+            .iter()
+            .flat_map(|class| class.side_effects.iter().map(|(path, _)| path))
+            .collect();
+        paths.sort();
+        paths.dedup();
+        let mut side_effects = Vec::with_capacity(paths.len());
+        for path in paths {
+            let class_values: Vec<Option<&Rc<AbstractValue>>> = self
+                .exit_classes
+                .iter()
+                .map(|class| {
+                    class
+                        .side_effects
+                        .iter()
+                        .find(|(p, _)| p == path)
+                        .map(|(_, value)| value)
+                })
+                .collect();
+            let Some(some_value) = class_values.iter().flatten().next() else {
+                continue;
+            };
+            let value_type = some_value.expression.infer_type();
+            // A class that leaves the path alone keeps the value that it has at entry
+            let values: Vec<Rc<AbstractValue>> = class_values
+                .iter()
+                .map(|value| match value {
+                    Some(value) => (*value).clone(),
+                    None if path.is_rooted_by_parameter() => {
+                        AbstractValue::make_initial_parameter_value(value_type, path.clone())
+                    }
+                    None => joined_values
+                        .get(path)
+                        .map_or_else(|| (*some_value).clone(), |value| (*value).clone()),
+                })
+                .collect();
+            let value = if values.iter().all(|value| value == &values[0]) {
+                values[0].clone()
+            } else if value_type == ExpressionType::NonPrimitive {
+                joined_values
+                    .get(path)
+                    .map_or_else(|| (*some_value).clone(), |value| (*value).clone())
+            } else {
+                // The classes cover all of the returns, so the last one needs no condition
+                let mut classes = self.exit_classes.iter().zip(values).rev();
+                let (_, last_value) = classes.next().expect("there are exit classes");
+                classes.fold(last_value, |value, (class, class_value)| {
+                    class.condition.conditional_expression(class_value, value)
+                })
+            };
+            side_effects.push((path.clone(), value));
+        }
+        Cow::Owned(side_effects)
     }
 
     pub fn widen_side_effects(&mut self) {
         for (path, value) in self.side_effects.iter_mut() {
             *value = value.widen(path);
         }
+        self.exit_classes.clear();
+    }
+
+    /// Joins the summary computed in an iteration of the fixed point of a recursive function into
+    /// this summary, which approximates the function in that iteration. A side effect that only
+    /// one of the summaries has is joined with the initial value of its path if the path is
+    /// rooted in a parameter, since the other summary leaves the value at the path unchanged.
+    pub fn join_iteration(&mut self, next: &Summary) {
+        let mut joined = next.clone();
+        joined.join_side_effects(self);
+        for (path, value) in self.side_effects.iter() {
+            if joined.side_effects.iter().any(|(p, _)| p == path) {
+                continue;
+            }
+            let value = if path.is_rooted_by_parameter() {
+                value.join(AbstractValue::make_initial_parameter_value(
+                    value.expression.infer_type(),
+                    path.clone(),
+                ))
+            } else {
+                value.clone()
+            };
+            joined.side_effects.push((path.clone(), value));
+        }
+        joined.side_effects.sort();
+        joined.post_condition = None;
+        joined.is_incomplete |= self.is_incomplete;
+        *self = joined;
     }
 }
 
+/// The summary that stands for a recursive function while the fixed point of its summary is
+/// computed. Calls of the function that are reached while its body is summarized use this
+/// summary instead of summarizing the body again.
+pub struct RecursiveSummary<'tcx> {
+    pub key: CallSiteKey<'tcx>,
+    pub func_ref: Rc<FunctionReference>,
+    pub func_args: Option<Rc<Vec<Rc<FunctionReference>>>>,
+    pub type_args: Option<Rc<HashMap<Rc<Path>, Ty<'tcx>>>>,
+    pub summary: Summary,
+    /// The number of calls that have used the summary so far.
+    pub uses: usize,
+    /// The functions that were being summarized further up the call stack when the fixed point
+    /// started. A recursive call of one of them closes a cycle through this function.
+    pub enclosing_functions: HashSet<DefId>,
+    /// If the function is part of the cycle of another recursive function, the position of the
+    /// summary of that function, whose fixed point is the fixed point of the whole cycle.
+    pub cycle_head: Option<usize>,
+    /// The iteration of the fixed point of the cycle that the summary was last computed in.
+    pub iteration: Option<usize>,
+    /// True if the summary of a function of a cycle grew in the current iteration.
+    pub grew: bool,
+}
+
+/// Returns the number of calls that have used the given summaries of recursive functions.
+pub fn approximation_uses(recursive_summaries: &[RecursiveSummary<'_>]) -> usize {
+    recursive_summaries.iter().map(|r| r.uses).sum()
+}
+
 /// Constructs a summary of a function body by processing state information gathered during
-/// abstract interpretation of the body.
+/// abstract interpretation of the body. The exit environments of the kinds of results that the
+/// function returns, if they have been recorded, become the exit classes of the summary.
 #[allow(clippy::too_many_arguments)]
 #[logfn(TRACE)]
 pub fn summarize(
     argument_count: usize,
     exit_environment: Option<&Environment>,
+    exit_environments: &[(Rc<AbstractValue>, Environment)],
     preconditions: &[Precondition],
     post_condition: &Option<Rc<AbstractValue>>,
     return_type_index: usize,
@@ -241,6 +451,32 @@ pub fn summarize(
 
     preconditions.sort();
     side_effects.sort();
+    let exit_classes = exit_environments
+        .iter()
+        .map(|(condition, environment)| {
+            let mut side_effects = extract_side_effects(environment, argument_count);
+            side_effects.sort();
+            // The function only returns this kind of result if the entry condition of the return
+            // holds, so the caller can assume it on the path that takes this kind of result
+            let class_post_condition = environment
+                .entry_condition
+                .extract_promotable_conjuncts(true)
+                .filter(|condition| condition.as_bool_if_known().is_none());
+            let post_condition = match (post_condition, class_post_condition) {
+                (Some(post_condition), Some(class_post_condition)) => {
+                    Some(post_condition.and(class_post_condition))
+                }
+                (post_condition, class_post_condition) => {
+                    class_post_condition.or_else(|| post_condition.clone())
+                }
+            };
+            ExitClass {
+                condition: condition.clone(),
+                side_effects,
+                post_condition,
+            }
+        })
+        .collect();
 
     Summary {
         is_computed: true,
@@ -249,6 +485,10 @@ pub fn summarize(
         side_effects,
         post_condition: post_condition.clone(),
         return_type_index,
+        external_effects: ExternalEffects::default(),
+        distinct_accounts: Vec::new(),
+        suppressed_checkers: Vec::new(),
+        exit_classes,
     }
 }
 
@@ -398,6 +638,33 @@ impl Hash for CallSiteKey<'_> {
     }
 }
 
+/// Counts the lookups of the summary cache and the time spent in the persistent summary store.
+/// These are printed when the statistics option is set.
+#[derive(Debug, Default)]
+pub struct SummaryLookupStatistics {
+    /// The number of calls to get_summary_for_call_site, including recursive calls.
+    pub lookups: usize,
+    /// The number of lookups of type specific summaries in the persistent store that found nothing.
+    pub store_misses: usize,
+    /// The number of lookups of the persistent store that were skipped because they missed before.
+    pub skipped_store_lookups: usize,
+    /// The time spent looking up type specific summaries in the persistent store.
+    pub store_lookup_time: Duration,
+}
+
+impl SummaryLookupStatistics {
+    /// Adds the counts of the given statistics, such as those of a worker of --threads.
+    pub fn merge(&mut self, other: &SummaryLookupStatistics) {
+        self.lookups += other.lookups;
+        self.store_misses += other.store_misses;
+        self.skipped_store_lookups += other.skipped_store_lookups;
+        self.store_lookup_time += other.store_lookup_time;
+    }
+}
+
+/// The tree of the summary store with the records of the functions analyzed with --incremental.
+const ANALYSIS_RECORDS: &str = "analysis_records";
+
 /// A database and collection of in-memory caches for function summaries.
 pub struct SummaryCache<'tcx> {
     /// The sled database that stores the summaries when persisted between runs.
@@ -423,6 +690,19 @@ pub struct SummaryCache<'tcx> {
     /// which is expensive to do and can be done more than once per def_id if there are more than
     /// one call site that references the def_id.
     key_cache: HashMap<DefId, Rc<str>>,
+    /// The function_ids of function references for which the persistent store has no type specific
+    /// summary. Since every instantiation of a generic function has its own function_id, this
+    /// also distinguishes the generic arguments. Without this, every call to a foreign function
+    /// with no summary of its own would look up the store again before falling back to the
+    /// def_id_cache. The def_id of each function_id is kept, so that the function_ids of a
+    /// def_id are removed from here when a summary is set for either of them.
+    missing_summaries: HashMap<usize, DefId>,
+    /// Counts of lookups that are printed with the statistics option.
+    pub statistics: SummaryLookupStatistics,
+    /// The summaries that are to be written to the store, by their persistent keys, if the
+    /// writes are deferred. The workers of --threads defer them until every component of the
+    /// same level has been analyzed, so that the components do not see each other's summaries.
+    deferred_writes: Option<Vec<(String, Vec<u8>)>>,
 }
 
 impl Debug for SummaryCache<'_> {
@@ -456,6 +736,16 @@ impl<'tcx> SummaryCache<'tcx> {
             debug!("{} ", err);
             assume_unreachable!();
         });
+        Self::with_db(db, None)
+    }
+
+    /// Creates a cache for a worker of --threads, with the database of the cache of the crate,
+    /// that defers its writes to the database until they are taken with take_deferred_writes.
+    pub fn for_worker(db: Db) -> SummaryCache<'tcx> {
+        Self::with_db(db, Some(Vec::new()))
+    }
+
+    fn with_db(db: Db, deferred_writes: Option<Vec<(String, Vec<u8>)>>) -> SummaryCache<'tcx> {
         SummaryCache {
             db,
             def_id_cache: HashMap::new(),
@@ -463,6 +753,35 @@ impl<'tcx> SummaryCache<'tcx> {
             call_site_cache: HashMap::new(),
             reference_cache: HashMap::new(),
             key_cache: HashMap::new(),
+            missing_summaries: HashMap::new(),
+            statistics: SummaryLookupStatistics::default(),
+            deferred_writes,
+        }
+    }
+
+    /// The database that stores the summaries, which the workers of --threads share.
+    pub fn db(&self) -> &Db {
+        &self.db
+    }
+
+    /// Returns the writes to the database that were deferred, in the order in which they were
+    /// made.
+    pub fn take_deferred_writes(&mut self) -> Vec<(String, Vec<u8>)> {
+        self.deferred_writes
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Writes the given serialized summaries to the database, by their persistent keys.
+    pub fn write_summaries(&self, writes: Vec<(String, Vec<u8>)>) {
+        for (persistent_key, serialized_summary) in writes {
+            let result = self
+                .db
+                .insert(persistent_key.as_bytes(), serialized_summary);
+            if result.is_err() {
+                println!("unable to set key in summary database: {result:?}");
+            }
         }
     }
 
@@ -555,6 +874,8 @@ impl<'tcx> SummaryCache<'tcx> {
                 LLMSummary::from_summary(value, calls),
             ));
         }
+        // The cache is a hash map, so the entries are sorted by name to make the output stable
+        entries.sort_by(|(_, name1, ..), (_, name2, ..)| name1.cmp(name2));
         SummariesForLLM { entries }
     }
 
@@ -579,6 +900,7 @@ impl<'tcx> SummaryCache<'tcx> {
         func_args: &Option<Rc<Vec<Rc<FunctionReference>>>>,
         type_args: &Option<Rc<HashMap<Rc<Path>, Ty<'tcx>>>>,
     ) -> &Summary {
+        self.statistics.lookups += 1;
         match (func_ref.def_id, func_ref.function_id) {
             // Use the ids as keys if they are available, since they make much better keys.
             (Some(def_id), Some(function_id)) => {
@@ -604,10 +926,9 @@ impl<'tcx> SummaryCache<'tcx> {
                     let result = self.function_id_cache.get(&function_id);
                     result.expect("value disappeared from typed_cache")
                 } else {
-                    if let Some(summary) = self.get_persistent_summary_using_arg_types_if_possible(
-                        &func_ref.summary_cache_key,
-                        &func_ref.argument_type_key,
-                    ) {
+                    if let Some(summary) =
+                        self.get_type_specific_summary(func_ref, def_id, function_id)
+                    {
                         return self.function_id_cache.entry(function_id).or_insert(summary);
                     }
 
@@ -660,6 +981,31 @@ impl<'tcx> SummaryCache<'tcx> {
         }
     }
 
+    /// Returns the summary from the persistent summary store that is specific to the argument types
+    /// of func_ref, unless an earlier lookup for function_id found none.
+    fn get_type_specific_summary(
+        &mut self,
+        func_ref: &FunctionReference,
+        def_id: DefId,
+        function_id: usize,
+    ) -> Option<Summary> {
+        if self.missing_summaries.contains_key(&function_id) {
+            self.statistics.skipped_store_lookups += 1;
+            return None;
+        }
+        let start_instant = Instant::now();
+        let summary = self.get_persistent_summary_using_arg_types_if_possible(
+            &func_ref.summary_cache_key,
+            &func_ref.argument_type_key,
+        );
+        self.statistics.store_lookup_time += start_instant.elapsed();
+        if summary.is_none() {
+            self.statistics.store_misses += 1;
+            self.missing_summaries.insert(function_id, def_id);
+        }
+        summary
+    }
+
     /// Returns a summary from the persistent summary cache, preferentially using the concatenation
     /// of persistent_key with arg_types_key as the cache key and falling back to just the
     /// persistent_key if arg_types_key is None.
@@ -715,6 +1061,7 @@ impl<'tcx> SummaryCache<'tcx> {
             //         self.def_id_cache.insert(def_id, summary.clone());
             //     }
             // }
+            self.missing_summaries.remove(&func_id);
             if func_args.is_some() || type_args.is_some() {
                 let typed_cache_key =
                     CallSiteKey::new(func_args.clone(), type_args.clone(), func_id);
@@ -728,6 +1075,25 @@ impl<'tcx> SummaryCache<'tcx> {
         }
     }
 
+    /// Removes the summary of the referenced function that is cached for the call site, so that
+    /// the summary is computed again the next time that it is looked up.
+    pub fn remove_summary_for_call_site(
+        &mut self,
+        func_ref: &Rc<FunctionReference>,
+        func_args: &Option<Rc<Vec<Rc<FunctionReference>>>>,
+        type_args: &Option<Rc<HashMap<Rc<Path>, Ty<'tcx>>>>,
+    ) {
+        if let Some(func_id) = func_ref.function_id {
+            if func_args.is_some() || type_args.is_some() {
+                let typed_cache_key =
+                    CallSiteKey::new(func_args.clone(), type_args.clone(), func_id);
+                self.call_site_cache.remove(&typed_cache_key);
+            } else {
+                self.function_id_cache.remove(&func_id);
+            }
+        }
+    }
+
     /// Sets or updates the DefId cache so that from now on def_id maps to the given summary.
     pub fn set_summary_for(
         &mut self,
@@ -737,13 +1103,40 @@ impl<'tcx> SummaryCache<'tcx> {
     ) -> Option<Summary> {
         let persistent_key = utils::summary_key_str(tcx, def_id);
         let serialized_summary = bincode::serialize(&summary).unwrap();
+        if let Some(deferred_writes) = &mut self.deferred_writes {
+            deferred_writes.push((persistent_key.to_string(), serialized_summary));
+        } else {
+            let result = self
+                .db
+                .insert(persistent_key.as_bytes(), serialized_summary);
+            if result.is_err() {
+                println!("unable to set key in summary database: {result:?}");
+            }
+        }
+        self.missing_summaries
+            .retain(|_, missing_def_id| *missing_def_id != def_id);
+        self.def_id_cache.insert(def_id, summary)
+    }
+
+    /// Returns the record of the last analysis of the function with the given summary key, which
+    /// --incremental keeps in a tree of the store of its own.
+    pub fn get_analysis_record(&self, persistent_key: &str) -> Option<AnalysisRecord> {
+        let records = self.db.open_tree(ANALYSIS_RECORDS).ok()?;
+        let pinned_value = records.get(persistent_key.as_bytes()).ok()??;
+        // A record of another version of HEPHA is as good as none
+        bincode::deserialize(pinned_value.deref()).ok()
+    }
+
+    /// Sets the record of the analysis of the function with the given summary key.
+    pub fn set_analysis_record(&self, persistent_key: &str, record: &AnalysisRecord) {
+        let serialized_record = bincode::serialize(record).unwrap();
         let result = self
             .db
-            .insert(persistent_key.as_bytes(), serialized_summary);
+            .open_tree(ANALYSIS_RECORDS)
+            .and_then(|records| records.insert(persistent_key.as_bytes(), serialized_record));
         if result.is_err() {
             println!("unable to set key in summary database: {result:?}");
         }
-        self.def_id_cache.insert(def_id, summary)
     }
 }
 
@@ -787,15 +1180,26 @@ pub struct LLMSummary {
     // The set of function calls made by this function. The first element is the source snippet of
     // the call and the second is the fully qualified name of the function being called.
     calls: Vec<(String, String)>,
+
+    // The messages of the preconditions, which detail what goes wrong if a caller does not meet
+    // them. For the entry points of a library analyzed with --adversarial-public-api these are
+    // the obligations of its callers.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    obligations: Vec<String>,
 }
 
 impl LLMSummary {
-    pub fn from_summary(_summary: &Summary, calls: Vec<(String, String)>) -> LLMSummary {
+    pub fn from_summary(summary: &Summary, calls: Vec<(String, String)>) -> LLMSummary {
         LLMSummary {
             // preconditions: vec![],
             // side_effects: vec![],
             // post_condition: vec![],
             calls,
+            obligations: summary
+                .preconditions
+                .iter()
+                .map(|precondition| precondition.message.to_string())
+                .collect(),
         }
     }
 }
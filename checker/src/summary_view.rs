@@ -0,0 +1,263 @@
+// A serializable view of the summaries that HEPHA computes for the analyzed functions of a crate,
+// for tools such as documentation generators and contract registries. Every expression is given
+// in the syntax of Rust, as in the path constraints report, and in a structured form whose schema
+// is versioned, so that tools do not have to parse the rendered form.
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use rustc_middle::mir;
+use serde::{Deserialize, Serialize};
+
+use crate::abstract_value::AbstractValue;
+use crate::constant_domain::ConstantDomain;
+use crate::expression::Expression;
+use crate::fixed_point_visitor::{binary_operation, render_condition, render_path};
+use crate::path::{Path, PathEnum, PathRoot};
+use crate::summaries::Summary;
+
+/// The version of the schema of summary views, which changes whenever a field is removed or
+/// changes its meaning.
+pub const SUMMARY_VIEW_VERSION: u32 = 1;
+
+/// The summaries of the analyzed functions of a crate, by the stable ids of the functions, which
+/// are the keys of the summary store.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct SummaryViews {
+    pub version: u32,
+    pub functions: BTreeMap<String, SummaryView>,
+}
+
+impl Default for SummaryViews {
+    fn default() -> Self {
+        SummaryViews {
+            version: SUMMARY_VIEW_VERSION,
+            functions: BTreeMap::new(),
+        }
+    }
+}
+
+impl SummaryViews {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("summary views should serialize")
+    }
+
+    /// Writes the summary views as JSON to the file at the given path.
+    pub fn write(&self, path: &std::path::Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+}
+
+/// What HEPHA knows about a function once it has analyzed its body.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SummaryView {
+    /// The name of the function as it is shown in diagnostics.
+    pub name: String,
+    /// True if the analysis of the body did not complete, so that conditions and side effects
+    /// may be missing.
+    pub is_incomplete: bool,
+    /// True if the function has no side effects other than returning its result.
+    pub is_pure: bool,
+    /// The conditions that callers must meet.
+    pub preconditions: Vec<PreconditionView>,
+    /// The condition that holds when the function returns normally, if known.
+    pub post_condition: Option<ExpressionView>,
+    /// The values that the function leaves in its result, its mutable parameters and statics.
+    pub side_effects: Vec<SideEffectView>,
+    /// The messages of the preconditions, which detail what goes wrong if a caller does not meet
+    /// them, so that they are the obligations of the callers.
+    pub obligations: Vec<String>,
+}
+
+/// A condition that callers of a function must meet.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PreconditionView {
+    pub condition: ExpressionView,
+    /// What goes wrong at runtime if the condition is not met.
+    pub message: String,
+    /// The source location of the definition of the precondition, if known.
+    pub provenance: Option<String>,
+}
+
+/// A value that a function leaves at a path that its caller can see.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SideEffectView {
+    /// The path, rendered as a Rust place expression.
+    pub path: String,
+    pub value: ExpressionView,
+}
+
+/// An expression, in the syntax of Rust and in structured form.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ExpressionView {
+    pub rendered: String,
+    pub structured: StructuredExpression,
+}
+
+/// The structure of an expression. Expressions without a counterpart in Rust are opaque and only
+/// given by their debug representation.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StructuredExpression {
+    Constant {
+        value: String,
+    },
+    Variable {
+        path: String,
+    },
+    Unary {
+        operator: String,
+        operand: Box<StructuredExpression>,
+    },
+    Binary {
+        operator: String,
+        left: Box<StructuredExpression>,
+        right: Box<StructuredExpression>,
+    },
+    Conditional {
+        condition: Box<StructuredExpression>,
+        consequent: Box<StructuredExpression>,
+        alternate: Box<StructuredExpression>,
+    },
+    Opaque {
+        debug: String,
+    },
+}
+
+impl SummaryView {
+    /// Returns the view of the summary of the function with the given body and name.
+    pub fn new(mir: &mir::Body<'_>, name: String, summary: &Summary) -> SummaryView {
+        let mut preconditions: Vec<PreconditionView> = summary
+            .preconditions
+            .iter()
+            .map(|precondition| PreconditionView {
+                condition: ExpressionView::new(mir, &precondition.condition),
+                message: precondition.message.to_string(),
+                provenance: precondition.provenance.as_ref().map(|p| p.to_string()),
+            })
+            .collect();
+        preconditions.extend(summary.distinct_accounts.iter().map(|pair| PreconditionView {
+            condition: ExpressionView::distinct_keys(&pair.first_name, &pair.second_name),
+            message: format!(
+                "`{}` and `{}` must be different accounts, or the lamports moved between them stay where they are",
+                pair.first_name, pair.second_name
+            ),
+            provenance: None,
+        }));
+        let obligations = preconditions
+            .iter()
+            .map(|precondition| precondition.message.clone())
+            .collect();
+        // The side effects are sorted by path so that the views do not depend on the order in
+        // which the side effects were found.
+        let mut side_effects: Vec<SideEffectView> = summary
+            .side_effects
+            .iter()
+            .map(|(path, value)| SideEffectView {
+                path: render_path(mir, path),
+                value: ExpressionView::new(mir, value),
+            })
+            .collect();
+        side_effects.sort_by(|a, b| a.path.cmp(&b.path));
+        SummaryView {
+            name,
+            is_incomplete: summary.is_incomplete,
+            is_pure: summary
+                .side_effects
+                .iter()
+                .all(|(path, _)| is_rooted_by_result(path)),
+            preconditions,
+            post_condition: summary
+                .post_condition
+                .as_ref()
+                .map(|condition| ExpressionView::new(mir, condition)),
+            side_effects,
+            obligations,
+        }
+    }
+}
+
+impl ExpressionView {
+    pub fn new(mir: &mir::Body<'_>, value: &Rc<AbstractValue>) -> ExpressionView {
+        ExpressionView {
+            rendered: render_condition(mir, value),
+            structured: StructuredExpression::new(mir, value),
+        }
+    }
+
+    /// Returns the view of the condition that the keys of the accounts with the given names
+    /// differ, which is not an abstract value because keys are opaque to the analysis.
+    pub fn distinct_keys(first: &str, second: &str) -> ExpressionView {
+        let key = |account: &str| format!("{account}.key");
+        ExpressionView {
+            rendered: format!("{} != {}", key(first), key(second)),
+            structured: StructuredExpression::Binary {
+                operator: "!=".to_string(),
+                left: Box::new(StructuredExpression::Variable { path: key(first) }),
+                right: Box::new(StructuredExpression::Variable { path: key(second) }),
+            },
+        }
+    }
+}
+
+impl StructuredExpression {
+    pub fn new(mir: &mir::Body<'_>, value: &Rc<AbstractValue>) -> StructuredExpression {
+        if let Some((left, operator, right)) = binary_operation(value) {
+            return StructuredExpression::Binary {
+                operator: operator.to_string(),
+                left: Box::new(StructuredExpression::new(mir, left)),
+                right: Box::new(StructuredExpression::new(mir, right)),
+            };
+        }
+        let unary = |operator: &str, operand: &Rc<AbstractValue>| StructuredExpression::Unary {
+            operator: operator.to_string(),
+            operand: Box::new(StructuredExpression::new(mir, operand)),
+        };
+        match &value.expression {
+            Expression::CompileTimeConstant(ConstantDomain::I128(value)) => {
+                StructuredExpression::Constant {
+                    value: value.to_string(),
+                }
+            }
+            Expression::CompileTimeConstant(ConstantDomain::U128(value)) => {
+                StructuredExpression::Constant {
+                    value: value.to_string(),
+                }
+            }
+            Expression::CompileTimeConstant(ConstantDomain::True) => {
+                StructuredExpression::Constant {
+                    value: "true".to_string(),
+                }
+            }
+            Expression::CompileTimeConstant(ConstantDomain::False) => {
+                StructuredExpression::Constant {
+                    value: "false".to_string(),
+                }
+            }
+            Expression::InitialParameterValue { path, .. } | Expression::Variable { path, .. } => {
+                StructuredExpression::Variable {
+                    path: render_path(mir, path),
+                }
+            }
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => StructuredExpression::Conditional {
+                condition: Box::new(StructuredExpression::new(mir, condition)),
+                consequent: Box::new(StructuredExpression::new(mir, consequent)),
+                alternate: Box::new(StructuredExpression::new(mir, alternate)),
+            },
+            Expression::LogicalNot { operand } => unary("!", operand),
+            Expression::Neg { operand } => unary("-", operand),
+            _ => StructuredExpression::Opaque {
+                debug: format!("{value:?}"),
+            },
+        }
+    }
+}
+
+// Check if the path is the result of the function or a part of it
+fn is_rooted_by_result(path: &Rc<Path>) -> bool {
+    matches!(path.get_path_root().value, PathEnum::Result)
+}
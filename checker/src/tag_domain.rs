@@ -9,8 +9,10 @@ use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 use hepha_annotations::*;
 use rustc_hir::def_id::{CrateNum, DefId, DefIndex};
+use std::rc::Rc;
 
 use crate::bool_domain::BoolDomain;
+use crate::path::{Path, PathEnum, PathSelector};
 
 /// A replication of the `DefId` type from rustc. The type is used to implement serialization.
 #[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone, Hash)]
@@ -30,6 +32,10 @@ impl From<DefId> for SerializableDefId {
 
 impl std::fmt::Debug for SerializableDefId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The def ids of built-in tags belong to no crate, so there is no def path to show
+        if self.krate == BUILT_IN_TAG_CRATE {
+            return f.write_fmt(format_args!("BuiltInTag({})", self.index.as_u32()));
+        }
         f.write_fmt(format_args!(
             "{:?}",
             DefId {
@@ -89,13 +95,169 @@ pub struct Tag {
     pub prop_set: TagPropagationSet,
 }
 
+/// The crate of the def ids of the built-in tags, which is not the number of any crate that can
+/// be loaded, so that a built-in tag cannot be confused with a tag type of the analyzed code.
+const BUILT_IN_TAG_CRATE: CrateNum = CrateNum::MAX;
+
 impl Tag {
+    /// The built-in tag of values that validators can predict or influence, such as the clock,
+    /// slot numbers, blockhashes and the values of random number generators with a predictable
+    /// seed. It is propagated by every kind of expression.
+    pub const PREDICTABLE_ENTROPY: Tag = Tag {
+        def_id: SerializableDefId {
+            krate: BUILT_IN_TAG_CRATE,
+            index: DefIndex::from_u32(0),
+        },
+        prop_set: TAG_PROPAGATION_ALL,
+    };
+
+    /// The built-in tag of amounts in lamports, which are the lamports of accounts and the
+    /// amounts that were converted from SOL by a multiplication with LAMPORTS_PER_SOL or one of
+    /// its multiples. Sums, differences, products and casts of amounts are amounts in the same
+    /// unit, but a quotient is not, since dividing by LAMPORTS_PER_SOL converts it back to SOL.
+    pub const LAMPORTS: Tag = Tag {
+        def_id: SerializableDefId {
+            krate: BUILT_IN_TAG_CRATE,
+            index: DefIndex::from_u32(1),
+        },
+        prop_set: tag_propagation_set!(
+            TagPropagation::Add,
+            TagPropagation::Cast,
+            TagPropagation::Mul,
+            TagPropagation::Sub,
+            TagPropagation::SubComponent,
+            TagPropagation::SuperComponent
+        ),
+    };
+
+    /// The built-in tag of amounts in SOL, which are the values of the constants whose names
+    /// mention SOL, other than the multiples of LAMPORTS_PER_SOL, and the amounts in lamports
+    /// that were divided by such a multiple. Unlike LAMPORTS, the tag is not propagated by
+    /// products, since multiplying by LAMPORTS_PER_SOL converts an amount to lamports.
+    pub const SOL: Tag = Tag {
+        def_id: SerializableDefId {
+            krate: BUILT_IN_TAG_CRATE,
+            index: DefIndex::from_u32(2),
+        },
+        prop_set: tag_propagation_set!(
+            TagPropagation::Add,
+            TagPropagation::Cast,
+            TagPropagation::Div,
+            TagPropagation::Sub,
+            TagPropagation::SubComponent,
+            TagPropagation::SuperComponent
+        ),
+    };
+
+    /// The built-in tag of untrusted input, which is the instruction data of the entrypoints of a
+    /// Solana program and the data of accounts that is borrowed with try_borrow_data. It is the
+    /// tag of hepha_annotations::UntrustedInput. It is propagated by every kind of expression
+    /// other than comparisons, whose results only tell whether the input has been checked.
+    pub const UNTRUSTED_INPUT: Tag = Tag {
+        def_id: SerializableDefId {
+            krate: BUILT_IN_TAG_CRATE,
+            index: DefIndex::from_u32(3),
+        },
+        prop_set: TAG_PROPAGATION_ALL
+            & !tag_propagation_set!(
+                TagPropagation::Equals,
+                TagPropagation::GreaterOrEqual,
+                TagPropagation::GreaterThan,
+                TagPropagation::LessOrEqual,
+                TagPropagation::LessThan,
+                TagPropagation::Memcmp,
+                TagPropagation::Ne
+            ),
+    };
+
+    /// The built-in tag of values that are computed with floating point arithmetic, which are the
+    /// results of arithmetic on floats, of casts to floats and of calls that return floats. Like
+    /// UNTRUSTED_INPUT, it is propagated by every kind of expression other than comparisons, so
+    /// that an integer that is cast back from a float still has it.
+    pub const FLOATING_POINT: Tag = Tag {
+        def_id: SerializableDefId {
+            krate: BUILT_IN_TAG_CRATE,
+            index: DefIndex::from_u32(4),
+        },
+        prop_set: TAG_PROPAGATION_ALL
+            & !tag_propagation_set!(
+                TagPropagation::Equals,
+                TagPropagation::GreaterOrEqual,
+                TagPropagation::GreaterThan,
+                TagPropagation::LessOrEqual,
+                TagPropagation::LessThan,
+                TagPropagation::Memcmp,
+                TagPropagation::Ne
+            ),
+    };
+
+    /// Returns the built-in tag with the given name, such as predictable_entropy.
+    pub fn built_in(name: &str) -> Option<Tag> {
+        match name {
+            "floating_point" => Some(Tag::FLOATING_POINT),
+            "lamports" => Some(Tag::LAMPORTS),
+            "predictable_entropy" => Some(Tag::PREDICTABLE_ENTROPY),
+            "sol" => Some(Tag::SOL),
+            "untrusted_input" => Some(Tag::UNTRUSTED_INPUT),
+            _ => None,
+        }
+    }
+
     /// Check if a value of enum type `TagPropagation` is included in `self`'s propagation set.
     #[logfn_inputs(TRACE)]
     pub fn is_propagated_by(&self, exp_tag_prop: TagPropagation) -> bool {
         precondition!((exp_tag_prop as u8) < 128);
         self.prop_set & (1 << (exp_tag_prop as u8)) != 0
     }
+
+    /// Check if the tag is propagated between a value and its component at the given selector,
+    /// given that it is propagated by SubComponent or SuperComponent. A propagation set that
+    /// enables none of Deref, Field and Index enables all of them.
+    #[logfn_inputs(TRACE)]
+    pub fn is_propagated_by_selector(&self, selector: &PathSelector) -> bool {
+        let projections = tag_propagation_set!(
+            TagPropagation::Deref,
+            TagPropagation::Field,
+            TagPropagation::Index
+        );
+        if self.prop_set & projections == 0 {
+            return true;
+        }
+        match selector {
+            PathSelector::Deref => self.is_propagated_by(TagPropagation::Deref),
+            PathSelector::Field(..) | PathSelector::UnionField { .. } => {
+                self.is_propagated_by(TagPropagation::Field)
+            }
+            PathSelector::Index(..)
+            | PathSelector::Slice(..)
+            | PathSelector::ConstantIndex { .. }
+            | PathSelector::ConstantSlice { .. } => self.is_propagated_by(TagPropagation::Index),
+            _ => true,
+        }
+    }
+
+    /// Check if the tag is propagated by every selector on the way from `component` up to
+    /// `container`.
+    #[logfn_inputs(TRACE)]
+    pub fn is_propagated_between(&self, container: &Rc<Path>, component: &Rc<Path>) -> bool {
+        let mut path = component;
+        while path != container {
+            if let PathEnum::QualifiedPath {
+                qualifier,
+                selector,
+                ..
+            } = &path.value
+            {
+                if !self.is_propagated_by_selector(selector) {
+                    return false;
+                }
+                path = qualifier;
+            } else {
+                break;
+            }
+        }
+        true
+    }
 }
 
 /// The Tag domain implements an abstraction for the Expression domain. A tag is attached to an
@@ -0,0 +1,75 @@
+// Trace logging that is scoped to the analysis of particular function bodies. The visitors are
+// instrumented with #[logfn_inputs(TRACE)] throughout, which makes a crate wide trace far too
+// large and slow to be of use. With --trace-function, the logger lets the trace messages of HEPHA
+// through, but the maximum level of the log crate is only raised to TRACE while a body whose def
+// path contains the given string, or one of its callees up to --trace-depth calls deep, is
+// analyzed. The log macros compare the level of a message with the maximum level before they
+// format any of its arguments, so messages outside of a traced body cost no more than that.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+use log::LevelFilter;
+
+// The maximum level of the log crate outside of traced bodies, set if scoped tracing is enabled.
+// Like the maximum level itself, this is global rather than thread local, because rustc analyzes
+// the crate on a thread of its own.
+static BASE_LEVEL: OnceLock<LevelFilter> = OnceLock::new();
+
+// The number of calls between the traced function and the body that is being analyzed, or
+// NOT_TRACED if no enclosing body is traced
+static DEPTH: AtomicUsize = AtomicUsize::new(NOT_TRACED);
+
+const NOT_TRACED: usize = usize::MAX;
+
+/// Enables scoped tracing, with the given maximum level outside of traced bodies. The logger must
+/// already let the trace messages of HEPHA through.
+pub fn enable(base_level: LevelFilter) {
+    if BASE_LEVEL.set(base_level).is_ok() {
+        log::set_max_level(base_level);
+    }
+}
+
+/// Raises the maximum level of the log crate to TRACE for as long as it lives, if the body that is
+/// about to be analyzed is traced, and restores the level of the enclosing body when dropped.
+pub struct TraceScope {
+    saved: Option<(usize, LevelFilter)>,
+}
+
+impl TraceScope {
+    /// Enters the analysis of a body, whose def path is computed only if scoped tracing is
+    /// enabled and no enclosing body is traced.
+    pub fn enter(
+        trace_function: Option<&str>,
+        trace_depth: usize,
+        def_path: impl FnOnce() -> String,
+    ) -> TraceScope {
+        let (Some(base_level), Some(trace_function)) = (BASE_LEVEL.get(), trace_function) else {
+            return TraceScope { saved: None };
+        };
+        let saved = (DEPTH.load(Ordering::Relaxed), log::max_level());
+        let depth = if saved.0 != NOT_TRACED {
+            saved.0.saturating_add(1).min(NOT_TRACED - 1)
+        } else if def_path().contains(trace_function) {
+            0
+        } else {
+            NOT_TRACED
+        };
+        DEPTH.store(depth, Ordering::Relaxed);
+        if depth <= trace_depth {
+            log::set_max_level(LevelFilter::Trace);
+        } else {
+            log::set_max_level(*base_level);
+        }
+        TraceScope { saved: Some(saved) }
+    }
+}
+
+impl Drop for TraceScope {
+    fn drop(&mut self) {
+        if let Some((depth, level)) = self.saved {
+            DEPTH.store(depth, Ordering::Relaxed);
+            log::set_max_level(level);
+        }
+    }
+}
@@ -27,7 +27,7 @@ use crate::constant_domain::ConstantDomain;
 use crate::environment::Environment;
 use crate::expression::{Expression, ExpressionType};
 use crate::path::{Path, PathEnum, PathRefinement, PathRoot, PathSelector};
-use crate::{type_visitor, utils};
+use crate::{solana_compat, type_visitor, utils};
 
 #[derive(Debug)]
 pub struct TypeCache<'tcx> {
@@ -190,6 +190,52 @@ impl<'tcx> TypeVisitor<'tcx> {
         }
     }
 
+    /// The accounts of an Anchor instruction are the fields of the struct that derives Accounts,
+    /// which the accounts field of the Context parameter of the handler refers to. We pre-populate
+    /// the environment with entries for them, so that the paths that reach them through
+    /// ctx.accounts have the precise types of the fields, such as Signer<'info> or
+    /// Account<'info, Vault>, rather than the types of the generic parameters of Context.
+    #[logfn_inputs(TRACE)]
+    pub fn add_any_anchor_account_fields_for(
+        &mut self,
+        path_ty: Ty<'tcx>,
+        path: &Rc<Path>,
+        first_state: &mut Environment,
+    ) {
+        let Some((accounts_index, accounts_ty)) = solana_compat::anchor_context(self.tcx, path_ty)
+        else {
+            return;
+        };
+        let TyKind::Adt(def, args) = accounts_ty.kind() else {
+            return;
+        };
+        if !def.is_struct() {
+            return;
+        }
+        let mut context_path = path.clone();
+        if let TyKind::Ref(_, context_ty, _) = path_ty.kind() {
+            context_path = Path::new_deref(path.clone(), ExpressionType::NonPrimitive);
+            self.set_path_rustc_type(context_path.clone(), *context_ty);
+        }
+        let accounts_field_path = Path::new_field(context_path, accounts_index.as_usize());
+        self.set_path_rustc_type(
+            accounts_field_path.clone(),
+            Ty::new_mut_ref(self.tcx, self.tcx.lifetimes.re_erased, accounts_ty),
+        );
+        let accounts_path = Path::new_deref(accounts_field_path, ExpressionType::NonPrimitive);
+        self.set_path_rustc_type(accounts_path.clone(), accounts_ty);
+        for (i, field) in def.non_enum_variant().fields.iter().enumerate() {
+            let ty = field.ty(self.tcx, args);
+            let account_path = Path::new_field(accounts_path.clone(), i);
+            self.set_path_rustc_type(account_path.clone(), ty);
+            let account_val = AbstractValue::make_typed_unknown(
+                ExpressionType::from(ty.kind()),
+                account_path.clone(),
+            );
+            first_state.value_map.insert_mut(account_path, account_val);
+        }
+    }
+
     /// Returns the size in bytes (including padding) of an element of the given collection type.
     /// If the type is not a collection, it returns one.
     pub fn get_elem_type_size(&self, ty: Ty<'tcx>) -> u64 {
@@ -714,6 +760,79 @@ impl<'tcx> TypeVisitor<'tcx> {
         }
     }
 
+    /// Returns the number of bytes that a value of the given type occupies when it is serialized
+    /// with Borsh or bincode, if every value of the type occupies the same number of bytes. The
+    /// formats write integers in little endian order, the elements of arrays, tuples and structs
+    /// one after the other, and the index of the variant of an enum in variant_index_size bytes
+    /// before its fields, so an enum has a fixed size if all of its variants do.
+    #[logfn_inputs(TRACE)]
+    pub fn fixed_serialized_size(&self, ty: Ty<'tcx>, variant_index_size: u128) -> Option<u128> {
+        match ty.kind() {
+            TyKind::Bool => Some(1),
+            TyKind::Int(int_ty) => int_ty.bit_width().map(|bits| u128::from(bits / 8)),
+            TyKind::Uint(uint_ty) => uint_ty.bit_width().map(|bits| u128::from(bits / 8)),
+            TyKind::Float(float_ty) => Some(u128::from(float_ty.bit_width() / 8)),
+            TyKind::Array(element_ty, length) => {
+                let length = length.try_to_target_usize(self.tcx)?;
+                let element_size = self.fixed_serialized_size(*element_ty, variant_index_size)?;
+                element_size.checked_mul(u128::from(length))
+            }
+            TyKind::Tuple(element_types) => element_types.iter().try_fold(0u128, |size, ty| {
+                size.checked_add(self.fixed_serialized_size(ty, variant_index_size)?)
+            }),
+            TyKind::Adt(def, args) if def.is_struct() || def.is_enum() => {
+                let mut variant_sizes = def.variants().iter().map(|variant| {
+                    variant.fields.iter().try_fold(0u128, |size, field| {
+                        let field_ty = field.ty(self.tcx, args);
+                        size.checked_add(self.fixed_serialized_size(field_ty, variant_index_size)?)
+                    })
+                });
+                let size = variant_sizes.next()??;
+                if def.is_struct() {
+                    return Some(size);
+                }
+                if !variant_sizes.all(|variant_size| variant_size == Some(size)) {
+                    return None;
+                }
+                size.checked_add(variant_index_size)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the number of bytes that the shortest serialization of a value of the given type
+    /// occupies with Borsh or bincode, where the index of a variant occupies the given number of
+    /// bytes. The types whose serialized size is not known here, such as vectors, count as zero.
+    pub fn min_serialized_size(&self, ty: Ty<'tcx>, variant_index_size: u128) -> u128 {
+        match ty.kind() {
+            TyKind::Array(element_ty, length) => {
+                let length = length.try_to_target_usize(self.tcx).unwrap_or(0);
+                let element_size = self.min_serialized_size(*element_ty, variant_index_size);
+                element_size.saturating_mul(u128::from(length))
+            }
+            TyKind::Tuple(element_types) => element_types.iter().fold(0u128, |size, ty| {
+                size.saturating_add(self.min_serialized_size(ty, variant_index_size))
+            }),
+            TyKind::Adt(def, args) if def.is_struct() || def.is_enum() => {
+                let variant_sizes = def.variants().iter().map(|variant| {
+                    variant.fields.iter().fold(0u128, |size, field| {
+                        let field_ty = field.ty(self.tcx, args);
+                        size.saturating_add(self.min_serialized_size(field_ty, variant_index_size))
+                    })
+                });
+                let size = variant_sizes.min().unwrap_or(0);
+                if def.is_struct() {
+                    size
+                } else {
+                    size.saturating_add(variant_index_size)
+                }
+            }
+            _ => self
+                .fixed_serialized_size(ty, variant_index_size)
+                .unwrap_or(0),
+        }
+    }
+
     /// Returns the type of the field with the given ordinal.
     #[logfn_inputs(TRACE)]
     pub fn get_field_type(
@@ -19,9 +19,10 @@ use crate::abstract_value::AbstractValue;
 use crate::abstract_value::AbstractValueTrait;
 use crate::constant_domain::ConstantDomain;
 use crate::expression::{Expression, ExpressionType};
-use crate::path::Path;
+use crate::path::{Path, PathEnum, PathSelector};
 use crate::smt_solver::SmtResult;
 use crate::smt_solver::SmtSolver;
+use crate::smt_solver::DEFAULT_TIMEOUT_MS;
 use crate::tag_domain::Tag;
 
 pub type Z3ExpressionType = z3_sys::Z3_ast;
@@ -56,17 +57,28 @@ impl Debug for Z3Solver {
 }
 
 impl Z3Solver {
+    /// Creates a solver that gives up on a query, and returns SmtResult::Undefined for it, once
+    /// it has taken timeout_ms milliseconds.
     #[logfn_inputs(TRACE)]
-    pub fn new() -> Z3Solver {
+    pub fn new(timeout_ms: u64) -> Z3Solver {
         unsafe {
             let _guard = Z3_MUTEX.lock().unwrap();
             let z3_sys_cfg = z3_sys::Z3_mk_config();
-            let time_out = CString::new("timeout").unwrap().into_raw();
-            let ms = CString::new("100").unwrap().into_raw();
-            z3_sys::Z3_set_param_value(z3_sys_cfg, time_out, ms);
-
             let z3_context = z3_sys::Z3_mk_context(z3_sys_cfg);
             let z3_solver = z3_sys::Z3_mk_solver(z3_context);
+
+            // The timeout is a parameter of the solver, so that it applies to every check
+            let params = z3_sys::Z3_mk_params(z3_context);
+            z3_sys::Z3_params_inc_ref(z3_context, params);
+            let timeout = CString::new("timeout").unwrap();
+            z3_sys::Z3_params_set_uint(
+                z3_context,
+                params,
+                z3_sys::Z3_mk_string_symbol(z3_context, timeout.as_ptr()),
+                u32::try_from(timeout_ms).unwrap_or(u32::MAX),
+            );
+            z3_sys::Z3_solver_set_params(z3_context, z3_solver, params);
+            z3_sys::Z3_params_dec_ref(z3_context, params);
             let empty_str = CString::new("").unwrap().into_raw();
             let symbol = z3_sys::Z3_mk_string_symbol(z3_context, empty_str);
 
@@ -117,7 +129,7 @@ impl Z3Solver {
 impl Default for Z3Solver {
     #[logfn_inputs(TRACE)]
     fn default() -> Self {
-        Z3Solver::new()
+        Z3Solver::new(DEFAULT_TIMEOUT_MS)
     }
 }
 
@@ -161,6 +173,39 @@ impl SmtSolver<Z3ExpressionType> for Z3Solver {
         }
     }
 
+    #[logfn_inputs(TRACE)]
+    fn get_model_string(&self) -> Option<String> {
+        let _guard = Z3_MUTEX.lock().unwrap();
+        unsafe {
+            let model = z3_sys::Z3_solver_get_model(self.z3_context, self.z3_solver);
+            if model.is_null() {
+                return None;
+            }
+            let mut assignments = String::new();
+            for i in 0..z3_sys::Z3_model_get_num_consts(self.z3_context, model) {
+                let decl = z3_sys::Z3_model_get_const_decl(self.z3_context, model, i);
+                let value = z3_sys::Z3_model_get_const_interp(self.z3_context, model, decl);
+                if value.is_null() {
+                    continue;
+                }
+                // The strings returned by Z3 are only valid until the next call, so they are
+                // copied right away
+                let symbol = z3_sys::Z3_get_decl_name(self.z3_context, decl);
+                let name = CStr::from_ptr(z3_sys::Z3_get_symbol_string(self.z3_context, symbol))
+                    .to_string_lossy()
+                    .into_owned();
+                let value_str = if z3_sys::Z3_is_numeral_ast(self.z3_context, value) {
+                    z3_sys::Z3_get_numeral_string(self.z3_context, value)
+                } else {
+                    z3_sys::Z3_ast_to_string(self.z3_context, value)
+                };
+                let value = CStr::from_ptr(value_str).to_string_lossy();
+                assignments.push_str(&format!("{name} = {value}\n"));
+            }
+            Some(assignments)
+        }
+    }
+
     #[logfn_inputs(TRACE)]
     fn get_solver_state_as_string(&self) -> String {
         let _guard = Z3_MUTEX.lock().unwrap();
@@ -195,6 +240,47 @@ impl SmtSolver<Z3ExpressionType> for Z3Solver {
             }
         }
     }
+
+    #[logfn_inputs(TRACE)]
+    fn timed_out(&self) -> bool {
+        let _guard = Z3_MUTEX.lock().unwrap();
+        unsafe {
+            let reason = z3_sys::Z3_solver_get_reason_unknown(self.z3_context, self.z3_solver);
+            // Z3 says that a check was canceled when its timer stopped it
+            matches!(CStr::from_ptr(reason).to_bytes(), b"timeout" | b"canceled")
+        }
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn solve_under_assumptions(&self, assumptions: &[Z3ExpressionType]) -> SmtResult {
+        let _guard = Z3_MUTEX.lock().unwrap();
+        unsafe {
+            // Guard each assumption with a fresh literal and check the literals. The implications
+            // stay asserted in the context, but since no later check assumes these literals,
+            // they do not constrain the assertions that follow.
+            let prefix = CString::new("assumption").unwrap();
+            let literals: Vec<Z3ExpressionType> = assumptions
+                .iter()
+                .map(|assumption| {
+                    let literal =
+                        z3_sys::Z3_mk_fresh_const(self.z3_context, prefix.as_ptr(), self.bool_sort);
+                    let guarded = z3_sys::Z3_mk_implies(self.z3_context, literal, *assumption);
+                    z3_sys::Z3_solver_assert(self.z3_context, self.z3_solver, guarded);
+                    literal
+                })
+                .collect();
+            match z3_sys::Z3_solver_check_assumptions(
+                self.z3_context,
+                self.z3_solver,
+                literals.len() as u32,
+                literals.as_ptr(),
+            ) {
+                z3_sys::Z3_L_TRUE => SmtResult::Satisfiable,
+                z3_sys::Z3_L_FALSE => SmtResult::Unsatisfiable,
+                _ => SmtResult::Undefined,
+            }
+        }
+    }
 }
 
 impl Z3Solver {
@@ -217,6 +303,84 @@ impl Z3Solver {
         }
     }
 
+    #[logfn_inputs(TRACE)]
+    fn boolean_quantified(
+        &self,
+        bound_variable: &Rc<Path>,
+        var_type: ExpressionType,
+        operand: &Rc<AbstractValue>,
+    ) -> z3_sys::Z3_ast {
+        let (_, bound_ast) = self.numeric_variable(bound_variable, var_type);
+        let operand_ast = self.get_as_bool_z3_ast(&operand.expression);
+        unsafe {
+            // The range of the bound variable is that of its type. numeric_variable only asserts
+            // it for the free constant with the same name, which the quantifier does not bind.
+            let body_ast = if var_type.is_integer() {
+                let min_ast = self.get_constant_as_ast(&var_type.min_value());
+                let max_ast = self.get_constant_as_ast(&var_type.max_value());
+                let range_check = self.get_range_check(bound_ast, min_ast, max_ast);
+                z3_sys::Z3_mk_implies(self.z3_context, range_check, operand_ast)
+            } else {
+                operand_ast
+            };
+            let bound_app = z3_sys::Z3_to_app(self.z3_context, bound_ast);
+            z3_sys::Z3_mk_forall_const(
+                self.z3_context,
+                0,
+                1,
+                [bound_app].as_ptr(),
+                0,
+                std::ptr::null(),
+                body_ast,
+            )
+        }
+    }
+
+    /// If the path is that of an element of an array or slice, at an index that is a variable
+    /// bound by a quantified expression or a constant, returns the application of a function
+    /// from the indices of the array to its elements, so that a quantifier over the indices
+    /// says something about the elements at constant indices.
+    #[logfn_inputs(TRACE)]
+    fn element_at_index(&self, path: &Rc<Path>, sort: z3_sys::Z3_sort) -> Option<z3_sys::Z3_ast> {
+        let PathEnum::QualifiedPath {
+            qualifier,
+            selector,
+            ..
+        } = &path.value
+        else {
+            return None;
+        };
+        let PathSelector::Index(index) = selector.as_ref() else {
+            return None;
+        };
+        let index_ast = match &index.expression {
+            Expression::CompileTimeConstant(ConstantDomain::U128(..)) => {
+                self.get_as_numeric_z3_ast(&index.expression).1
+            }
+            Expression::Variable {
+                path: index_path,
+                var_type,
+            } if index_path.is_bound_variable() => self.numeric_variable(index_path, *var_type).1,
+            _ => return None,
+        };
+        let function_symbol = self.get_symbol_for(qualifier);
+        unsafe {
+            let function = z3_sys::Z3_mk_func_decl(
+                self.z3_context,
+                function_symbol,
+                1,
+                [self.int_sort].as_ptr(),
+                sort,
+            );
+            Some(z3_sys::Z3_mk_app(
+                self.z3_context,
+                function,
+                1,
+                [index_ast].as_ptr(),
+            ))
+        }
+    }
+
     #[logfn_inputs(TRACE)]
     fn get_as_z3_ast(&self, expression: &Expression) -> z3_sys::Z3_ast {
         match expression {
@@ -323,6 +487,7 @@ impl Z3Solver {
             Expression::Or { left, right } => {
                 self.general_boolean_op(left, right, z3_sys::Z3_mk_or)
             }
+            Expression::Quantified { .. } => self.get_as_bool_z3_ast(expression),
             Expression::Reference(path) => self.general_reference(path),
             Expression::Shl { left, right } => {
                 self.bv_binary(128, left, right, z3_sys::Z3_mk_bvshl)
@@ -655,7 +820,9 @@ impl Z3Solver {
         unsafe {
             let path_symbol = self.get_symbol_for(path);
             let sort = self.get_sort_for(var_type);
-            let ast = z3_sys::Z3_mk_const(self.z3_context, path_symbol, sort);
+            let ast = self
+                .element_at_index(path, sort)
+                .unwrap_or_else(|| z3_sys::Z3_mk_const(self.z3_context, path_symbol, sort));
             if var_type.is_integer() {
                 let min_ast = self.get_constant_as_ast(&var_type.min_value());
                 let max_ast = self.get_constant_as_ast(&var_type.max_value());
@@ -698,6 +865,7 @@ impl Z3Solver {
             | Expression::CompileTimeConstant { .. }
             | Expression::HeapBlock { .. }
             | Expression::HeapBlockLayout { .. }
+            | Expression::Quantified { .. }
             | Expression::Reference { .. }
             | Expression::UnknownTagCheck { .. } => unsafe {
                 return z3_sys::Z3_mk_false(self.z3_context);
@@ -1669,7 +1837,9 @@ impl Z3Solver {
                 F64 => self.f64_sort,
                 _ => self.int_sort,
             };
-            let ast = z3_sys::Z3_mk_const(self.z3_context, path_symbol, sort);
+            let ast = self
+                .element_at_index(path, sort)
+                .unwrap_or_else(|| z3_sys::Z3_mk_const(self.z3_context, path_symbol, sort));
             if var_type.is_integer() {
                 let min_ast = self.get_constant_as_ast(&var_type.min_value());
                 let max_ast = self.get_constant_as_ast(&var_type.max_value());
@@ -1745,6 +1915,11 @@ impl Z3Solver {
                 }
             }
             Expression::Join { left, right } => self.boolean_join(left, right),
+            Expression::Quantified {
+                bound_variable,
+                var_type,
+                operand,
+            } => self.boolean_quantified(bound_variable, *var_type, operand),
             Expression::Reference(path) => unsafe {
                 let path_symbol = self.get_symbol_for(path);
                 z3_sys::Z3_mk_const(self.z3_context, path_symbol, self.bool_sort)
@@ -1769,6 +1944,9 @@ impl Z3Solver {
                 if *var_type != ExpressionType::Bool {
                     debug!("path {:?}, type {:?}", path, var_type);
                 }
+                if let Some(ast) = self.element_at_index(path, self.bool_sort) {
+                    return ast;
+                }
                 unsafe {
                     let path_symbol = self.get_symbol_for(path);
                     z3_sys::Z3_mk_const(self.z3_context, path_symbol, self.bool_sort)
@@ -0,0 +1,37 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// The application of the incremental_analysis integration test, which calls share of the library
+// and whose scale function and Fees struct the test edits. The diagnostic of per_share is replayed
+// when the function is not analyzed again, after code above it has moved.
+
+// HEPHA_FLAGS --diag=paranoid --incremental
+
+use incremental_library::share;
+
+fn scale(amount: u64) -> u64 {
+    amount / 3
+}
+
+pub fn scaled(amount: u64) -> u64 {
+    scale(amount)
+}
+
+pub fn per_share(total: u64, shares: u64) -> u64 {
+    total / shares //~ possible attempt to divide by zero
+}
+
+pub fn halves(total: u64) -> u64 {
+    share(total, 2)
+}
+
+pub struct Fees {
+    pub rate: u64,
+}
+
+pub fn fee(fees: &Fees) -> u64 {
+    fees.rate
+}
@@ -0,0 +1,20 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// The library of the incremental_analysis integration test, which gives share a precondition
+// instead of its check of the divisor once the application has been analyzed.
+
+// HEPHA_FLAGS --diag=paranoid --incremental
+
+#[allow(unused_imports)]
+use hepha_annotations::*;
+
+pub fn share(total: u64, parts: u64) -> u64 {
+    if parts == 0 {
+        return 0;
+    }
+    total / parts
+}
@@ -20,26 +20,55 @@ extern crate rayon;
 extern crate rustc_ast;
 extern crate rustc_data_structures;
 extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate rustc_middle;
 extern crate rustc_session;
 extern crate tempfile;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::fs::read_to_string;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::rc::Rc;
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
 use regex::Regex;
+use rustc_driver::Compilation;
+use rustc_interface::interface;
+use rustc_middle::mir;
+use rustc_middle::ty::TyCtxt;
 use rustc_session::*;
 use serde::Deserialize;
 use tempfile::TempDir;
 use walkdir::WalkDir;
 
+use hepha::abstract_value::{self, AbstractValue, AbstractValueTrait};
+use hepha::block_visitor::BlockVisitor;
+use hepha::body_visitor::BodyVisitor;
 use hepha::call_graph::{CallGraphConfig, CallGraphReduction, DatalogBackend, DatalogConfig};
+use hepha::call_states::{CallStates, CALL_STATES_VERSION};
 use hepha::callbacks;
-use hepha::options::{DiagLevel, Options};
+use hepha::cancellation::CancellationToken;
+use hepha::checker_registry::{checker_for_code, Severity, CHECKERS};
+use hepha::config::Config;
+use hepha::crate_visitor::CrateVisitor;
+use hepha::diagnostic_export::{
+    self, ExportedDiagnostic, ExportedNote, FindingProperties, FINDING_PROPERTIES_SCHEMA,
+    MAX_PROPERTY_ENTRIES, MAX_RENDERED_CONDITION_LENGTH,
+};
+use hepha::environment::Environment;
+use hepha::expression::{Expression, ExpressionType, LayoutSource};
+use hepha::fixed_point_visitor::JoinMonotonicity;
+use hepha::options::{DiagLevel, Options, SmtBackend};
+use hepha::path::Path as ValuePath;
+use hepha::run_summary::RunSummary;
+use hepha::summary_view::{SummaryViews, SUMMARY_VIEW_VERSION};
+use hepha::tag_domain::Tag;
+use hepha::trace_scope;
 use hepha::utils;
 use hepha_annotations::{assume, unrecoverable};
 
@@ -68,6 +97,1538 @@ fn run_pass() {
     run_call_graph_tests();
 }
 
+// Run cargo hepha on the smart contracts in the contracts directory and check that every finding
+// that a contract expects with a "//~ message" comment is reported at the line of the comment, and
+// that no finding that a contract rules out with a "//~! message" comment is reported anywhere in
+// the contract.
+// Also check that the inventory of checkers lists exactly the codes of the findings reported for
+// the contracts, and that the first finding with each code has properties that follow their
+// schema. Findings of wrapping arithmetic need a run of the overflow contracts without overflow
+// checks. The runs also check the summaries that they write with --stats-json.
+#[test]
+fn contracts() {
+    let mut missing = 0;
+    let mut unexpected = 0;
+    let mut observed_codes = BTreeSet::new();
+    let mut first_properties = BTreeMap::new();
+    let runs = run_contracts(None, true, "--diag-format json")
+        .into_iter()
+        .chain(run_contracts(Some("overflow"), false, "--diag-format json"))
+        .chain(run_contracts(
+            Some("reentrancy"),
+            true,
+            "--diag-format json --ledger-account contract_account",
+        ));
+    for run in runs {
+        check_run_summary(&run);
+        let ContractRun {
+            contract,
+            diagnostics,
+            ..
+        } = run;
+        for diagnostic in &diagnostics {
+            let Some(code) = finding_code(&diagnostic.message) else {
+                continue;
+            };
+            first_properties
+                .entry(code.clone())
+                .or_insert_with(|| diagnostic.properties.clone().unwrap_or_default());
+            observed_codes.insert(code);
+        }
+        let source = read_to_string(contract.join("src/lib.rs")).unwrap_or_default();
+        for (index, line) in source.lines().enumerate() {
+            if let Some((_, message)) = line.split_once("//~!") {
+                let message = message.trim();
+                for d in diagnostics.iter().filter(|d| d.message.contains(message)) {
+                    println!(
+                        "{}:{}: unexpected {:?}",
+                        contract.display(),
+                        d.line,
+                        d.message
+                    );
+                    unexpected += 1;
+                }
+            } else if let Some((_, message)) = line.split_once("//~") {
+                let message = message.trim();
+                if !diagnostics
+                    .iter()
+                    .any(|d| d.line == index + 1 && d.message.contains(message))
+                {
+                    println!("{}:{}: expected {message:?}", contract.display(), index + 1);
+                    missing += 1;
+                }
+            }
+        }
+    }
+    assert_eq!(missing, 0);
+    assert_eq!(unexpected, 0);
+    let inventory: BTreeSet<String> = CHECKERS
+        .iter()
+        .flat_map(|checker| checker.codes.iter().map(|code| code.to_string()))
+        .collect();
+    assert_eq!(observed_codes, inventory);
+    for (code, properties) in &first_properties {
+        check_finding_properties(code, properties);
+        let properties: FindingProperties = serde_json::from_value(properties.clone()).unwrap();
+        assert_eq!(properties.schema, FINDING_PROPERTIES_SCHEMA);
+    }
+}
+
+// Checks that the summary of the given run counts the functions that were analyzed and, for every
+// checker, at least the findings of the checker that were reported, and that the summary is
+// printed to stderr unless --quiet is given.
+fn check_run_summary(run: &ContractRun) {
+    let summary = &run.summary;
+    assert!(summary.functions_analyzed > 0, "{}", run.contract.display());
+    for checker in CHECKERS {
+        let reported = run
+            .diagnostics
+            .iter()
+            .filter_map(|d| finding_code(&d.message))
+            .filter(|code| checker_for_code(code).is_some_and(|c| c.name == checker.name))
+            .count();
+        let counted = summary.diagnostics.get(checker.name).copied().unwrap_or(0);
+        assert!(
+            counted >= reported && (counted > 0) == (reported > 0),
+            "{}: {} counted {counted}, reported {reported}",
+            run.contract.display(),
+            checker.name
+        );
+    }
+    assert_eq!(
+        run.stderr
+            .contains(&format!("HEPHA summary of {}", summary.crate_name)),
+        !run.quiet,
+        "{}",
+        run.contract.display()
+    );
+}
+
+// Returns the code at the start of the message of a HEPHA finding, such as [HEPHA-REENTRANCY].
+fn finding_code(message: &str) -> Option<String> {
+    let code = message.strip_prefix('[')?.split_once(']')?.0;
+    code.starts_with("HEPHA-").then(|| code.to_string())
+}
+
+// A diagnostic that cargo hepha reported for a smart contract.
+struct ContractDiagnostic {
+    message: String,
+    line: usize,
+    // The messages of the notes attached to the diagnostic
+    notes: Vec<String>,
+    // The lines of the spans of the notes that have spans
+    note_lines: Vec<usize>,
+    // The diagnostic as rustc renders it, if rustc reported it
+    rendered: String,
+    // The properties of the finding, if it was written with --diag-format json
+    properties: Option<serde_json::Value>,
+}
+
+// Runs cargo hepha with the given flags on each of the smart contracts, or only on those of the
+// given category, and collects the diagnostics reported for them.
+fn analyze_contracts(
+    category: Option<&str>,
+    overflow_checks: bool,
+    hepha_flags: &str,
+) -> Vec<(PathBuf, Vec<ContractDiagnostic>)> {
+    run_contracts(category, overflow_checks, hepha_flags)
+        .into_iter()
+        .map(|run| (run.contract, run.diagnostics))
+        .collect()
+}
+
+// A run of cargo hepha on one of the smart contracts.
+struct ContractRun {
+    contract: PathBuf,
+    diagnostics: Vec<ContractDiagnostic>,
+    // What cargo hepha wrote to stderr
+    stderr: String,
+    // The summary of the run, which it wrote with --stats-json
+    summary: RunSummary,
+    // True if the run was given --quiet, which every other run is
+    quiet: bool,
+}
+
+// Runs cargo hepha with the given flags on each of the smart contracts, or only on those of the
+// given category, and collects the diagnostics and the summaries of the runs. Every run must
+// succeed, since a contract that cannot be built, or whose manifest cargo cannot read, has no
+// findings to check.
+fn run_contracts(
+    category: Option<&str>,
+    overflow_checks: bool,
+    hepha_flags: &str,
+) -> Vec<ContractRun> {
+    let mut contracts_path = PathBuf::from_str("../contracts").unwrap();
+    if !contracts_path.exists() {
+        contracts_path = PathBuf::from_str("contracts").unwrap();
+    }
+    if let Some(category) = category {
+        contracts_path = contracts_path.join(category);
+    }
+    // Share the dependencies of the contracts, but do not let the findings of an earlier run be replayed.
+    let target_dir = TempDir::new().expect("failed to create a temp dir");
+    let mut result = Vec::new();
+    for (index, entry) in WalkDir::new(contracts_path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name() == "Cargo.toml")
+        .enumerate()
+    {
+        let contract = entry.path().parent().unwrap().to_path_buf();
+        let stats_path = target_dir.path().join(format!("stats-{index}.json"));
+        let quiet = index % 2 == 1;
+        // The flags beyond a -- are for rustc, so the flags of the summary go first
+        let mut flags = format!("--stats-json {}", stats_path.display());
+        if quiet {
+            flags.push_str(" --quiet");
+        }
+        let (success, stderr, diagnostics) = analyze_contract(
+            entry.path(),
+            target_dir.path(),
+            overflow_checks,
+            &format!("{flags} {hepha_flags}"),
+        );
+        assert!(
+            success,
+            "{}: cargo hepha failed\n{}",
+            contract.display(),
+            stderr
+        );
+        let summary = read_to_string(&stats_path)
+            .ok()
+            .and_then(|stats| serde_json::from_str(&stats).ok())
+            .unwrap_or_else(|| panic!("{}: no run summary\n{}", contract.display(), stderr));
+        result.push(ContractRun {
+            contract,
+            diagnostics,
+            stderr,
+            summary,
+            quiet,
+        });
+    }
+    result
+}
+
+// Runs cargo hepha with the given flags on the smart contract with the given manifest, and returns
+// whether it succeeded, what it wrote to stderr and the diagnostics that it reported.
+fn analyze_contract(
+    manifest: &Path,
+    target_dir: &Path,
+    overflow_checks: bool,
+    hepha_flags: &str,
+) -> (bool, String, Vec<ContractDiagnostic>) {
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-hepha"))
+        .arg("hepha")
+        .arg("--manifest-path")
+        .arg(manifest)
+        .arg("--target-dir")
+        .arg(target_dir)
+        .arg("--message-format=json")
+        .env(
+            "CARGO_PROFILE_DEV_OVERFLOW_CHECKS",
+            overflow_checks.to_string(),
+        )
+        .env("HEPHA_FLAGS", hepha_flags)
+        .output()
+        .expect("could not run cargo hepha");
+    let diagnostics = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter_map(|m| {
+            if m["reason"] != "compiler-message" {
+                // A diagnostic that --diag-format json writes instead of rustc
+                let properties = m.get("properties").cloned();
+                let exported = serde_json::from_value::<ExportedDiagnostic>(m).ok()?;
+                return Some(ContractDiagnostic {
+                    line: exported.line,
+                    note_lines: exported
+                        .notes
+                        .iter()
+                        .filter_map(|note| note.location.as_ref().map(|(_, line, _)| *line))
+                        .collect(),
+                    notes: exported
+                        .notes
+                        .into_iter()
+                        .map(|note| note.message)
+                        .collect(),
+                    message: exported.message,
+                    rendered: String::new(),
+                    properties,
+                });
+            }
+            let message = &m["message"];
+            let span = message["spans"]
+                .as_array()?
+                .iter()
+                .find(|s| s["is_primary"] == true)?;
+            Some(ContractDiagnostic {
+                message: message["message"].as_str()?.to_string(),
+                line: span["line_start"].as_u64()? as usize,
+                notes: message["children"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|child| child["level"] == "note")
+                    .filter_map(|child| child["message"].as_str().map(str::to_string))
+                    .collect(),
+                note_lines: message["children"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|child| child["level"] == "note")
+                    .filter_map(|child| child["spans"].as_array()?.first()?["line_start"].as_u64())
+                    .map(|line| line as usize)
+                    .collect(),
+                rendered: message["rendered"].as_str().unwrap_or_default().to_string(),
+                properties: None,
+            })
+        })
+        .collect();
+    (
+        output.status.success(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+        diagnostics,
+    )
+}
+
+// Check that the return data of a program that is invoked with a constant program id is known to
+// come from that program, so that an assertion about its program id holds, while an assertion
+// about the program id of the return data of a program chosen by the caller may fail.
+#[test]
+fn return_data_program_ids() {
+    for (contract, diagnostics) in analyze_contracts(Some("return_data"), true, "--diag=paranoid") {
+        let flagged = diagnostics
+            .iter()
+            .any(|d| d.message == "possible assertion failed");
+        assert_eq!(
+            flagged,
+            contract.ends_with("contract_one"),
+            "{}",
+            contract.display()
+        );
+    }
+}
+
+// Check that an invoked program only makes the balances of the accounts passed to it reentrant,
+// so that a balance written after a transfer between two other accounts is not reported, and that
+// only a write after the invocation is reported, so that a balance that is decreased before the
+// invocation is not. The transfer and the write are also found through the summaries of the
+// functions that do them.
+#[test]
+fn reentrancy_per_account() {
+    for (contract, diagnostics) in analyze_contracts(Some("reentrancy"), true, "") {
+        let flagged = diagnostics
+            .iter()
+            .any(|d| d.message.contains("possible reentrancy"));
+        if contract.ends_with("contract_twenty_two")
+            || contract.ends_with("contract_twenty_five")
+            || contract.ends_with("contract_twenty_six")
+        {
+            assert!(flagged, "{}", contract.display());
+        } else if contract.ends_with("contract_twenty_three")
+            || contract.ends_with("contract_twenty_four")
+        {
+            assert!(!flagged, "{}", contract.display());
+        }
+    }
+}
+
+// Check that the cast of truncation/contract_one and its conversion with try_into, whose result is
+// unwrapped, are reported as possible truncations with the widths of the types, the latter with a
+// note at the unwrap, and that contract_two, which checks the amount before the cast and returns
+// the error of the conversion, is not.
+#[test]
+fn truncations() {
+    for (contract, diagnostics) in analyze_contracts(Some("truncation"), true, "") {
+        let findings: Vec<&ContractDiagnostic> = diagnostics
+            .iter()
+            .filter(|d| d.message.contains("[HEPHA-TRUNCATION]"))
+            .collect();
+        if contract.ends_with("contract_two") {
+            assert!(findings.is_empty(), "{}", contract.display());
+            continue;
+        }
+        let lines: BTreeSet<usize> = findings.iter().map(|d| d.line).collect();
+        assert_eq!(lines, BTreeSet::from([29, 30]), "{}", contract.display());
+        assert!(findings.iter().any(|d| d.line == 29
+            && d.message
+                .ends_with("it is cast from u64 (64 bits) to u32 (32 bits)")
+            && d.notes.is_empty()));
+        assert!(findings.iter().any(|d| d.line == 30
+            && d.message
+                .ends_with("it is cast from u64 (64 bits) to u16 (16 bits)")
+            && d.notes.iter().any(|note| note.ends_with("which may panic"))));
+    }
+}
+
+// Check that the division of div_by_zero/contract_one by a number of shares that is read from the
+// instruction data is reported as a possible division by zero, with the range of the data that the
+// divisor is read from, and not also as a loss of precision, although its quotient is multiplied,
+// and that the checked remainder and the guarded division of contract_two are not reported. The
+// findings of contract_three are checked by its annotations.
+#[test]
+fn divisions_by_zero() {
+    for (contract, diagnostics) in analyze_contracts(Some("div_by_zero"), true, "") {
+        let findings: Vec<&ContractDiagnostic> = diagnostics
+            .iter()
+            .filter(|d| d.message.contains("[HEPHA-DIV-BY-ZERO]"))
+            .collect();
+        if contract.ends_with("contract_two") {
+            assert!(findings.is_empty(), "{}", contract.display());
+            continue;
+        }
+        if !contract.ends_with("contract_one") {
+            continue;
+        }
+        assert!(
+            findings.iter().any(|d| d.line == 30
+                && d.notes.iter().any(|note| note
+                    == "the value is read from instruction_data[0..8], which the caller controls")),
+            "{}",
+            contract.display()
+        );
+        assert!(
+            diagnostics
+                .iter()
+                .all(|d| !d.message.contains("[HEPHA-NUMERICAL-PRECISION]")),
+            "{}",
+            contract.display()
+        );
+    }
+}
+
+// Check that the finding of a change to the lamports of an account whose is_writable flag the
+// entrypoint does not read is only a note.
+#[test]
+fn writable_checks() {
+    let writable_check = CHECKERS
+        .iter()
+        .find(|c| c.name == "writable_check")
+        .unwrap();
+    assert_eq!(writable_check.default_severity, Severity::Note);
+}
+
+// Check that a call of invoke_signed is only reported if the caller controls a seed of the address
+// that signs a transfer, which the seed is described for, or if the address is derived by the
+// entrypoint but not compared with the key of any account passed to the call.
+#[test]
+fn signer_seeds() {
+    for (contract, diagnostics) in analyze_contracts(Some("signer_seeds"), true, "") {
+        let findings: Vec<&ContractDiagnostic> = diagnostics
+            .iter()
+            .filter(|d| d.message.contains("[HEPHA-SIGNER-SEEDS]"))
+            .collect();
+        if contract.ends_with("contract_one") {
+            assert!(findings.is_empty(), "{}", contract.display());
+        } else if contract.ends_with("contract_two") {
+            assert!(
+                findings.iter().flat_map(|d| &d.notes).any(|note| note
+                    == "the seed is read from the instruction data, which the caller controls"),
+                "{}",
+                contract.display()
+            );
+        } else {
+            assert!(
+                findings
+                    .iter()
+                    .any(|d| d.message.contains("not one of the accounts passed")),
+                "{}",
+                contract.display()
+            );
+        }
+    }
+}
+
+// Check that the subtraction from a map entry of underflow/contract_two, which is not compared with
+// the amount, is flagged, while the same subtraction and a subtraction from the lamports of an
+// account that are both compared first are not.
+#[test]
+fn lamport_underflows() {
+    let analyzed_contracts = analyze_contracts(Some("underflow"), true, "")
+        .into_iter()
+        .chain(analyze_contracts(Some("lamport_underflow"), true, ""));
+    for (contract, diagnostics) in analyzed_contracts {
+        let flagged = diagnostics
+            .iter()
+            .any(|d| d.message.contains("[HEPHA-LAMPORT-UNDERFLOW]"));
+        if contract.ends_with("underflow/contract_two") {
+            assert!(flagged, "{}", contract.display());
+        } else if contract.parent().unwrap().ends_with("lamport_underflow") {
+            assert!(!flagged, "{}", contract.display());
+        }
+    }
+}
+
+// Check that the lottery of bad_randomness/contract_two, whose payout is decided by a random value
+// with a predictable seed, is flagged, that a random value that is only logged is just noted, and
+// that a random value seeded from a verifiable random function is not reported.
+#[test]
+fn predictable_randomness() {
+    let analyzed_contracts = analyze_contracts(Some("bad_randomness"), true, "")
+        .into_iter()
+        .chain(analyze_contracts(Some("seeded_randomness"), true, ""));
+    for (contract, diagnostics) in analyzed_contracts {
+        let findings: Vec<&ContractDiagnostic> = diagnostics
+            .iter()
+            .filter(|d| d.message.contains("[HEPHA-BAD-RANDOMNESS]"))
+            .collect();
+        if contract.ends_with("bad_randomness/contract_two") {
+            assert!(
+                findings
+                    .iter()
+                    .any(|d| d.message.contains("possible bad randomness")),
+                "{}",
+                contract.display()
+            );
+        } else if contract.ends_with("bad_randomness/contract_one") {
+            assert!(
+                !findings.is_empty()
+                    && findings
+                        .iter()
+                        .all(|d| d.message.contains("decides no transfer of lamports")),
+                "{}",
+                contract.display()
+            );
+        } else if contract.parent().unwrap().ends_with("seeded_randomness") {
+            assert!(findings.is_empty(), "{}", contract.display());
+        }
+    }
+}
+
+// Check that a models file given with --models overrides the default model of the same function:
+// once the data of accounts is modeled as predictable, the seed that seeded_randomness/contract_one
+// reads from its verifiable random function is too, and its lottery is flagged. A models file with
+// an unknown tag makes the analysis fail with the line of the tag, and so does one with a return
+// range that is not a range of the type of the result, such as a negative number of lamports.
+#[test]
+fn foreign_model_overrides() {
+    let mut contracts_path = PathBuf::from_str("../contracts").unwrap();
+    if !contracts_path.exists() {
+        contracts_path = PathBuf::from_str("contracts").unwrap();
+    }
+    let manifest = contracts_path.join("seeded_randomness/contract_one/Cargo.toml");
+    let target_dir = TempDir::new().expect("failed to create a temp dir");
+    let models_path = target_dir.path().join("models.toml");
+    fs::write(
+        &models_path,
+        "[[model]]\nfunction = \"*::AccountInfo::try_borrow_data\"\ntag = \"predictable_entropy\"\n",
+    )
+    .unwrap();
+    let flags = format!("--models {}", models_path.display());
+    let (success, stderr, diagnostics) =
+        analyze_contract(&manifest, target_dir.path(), true, &flags);
+    assert!(success, "{stderr}");
+    let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert!(
+        messages
+            .iter()
+            .any(|m| m.starts_with("[HEPHA-BAD-RANDOMNESS] possible bad randomness")),
+        "{messages:?}"
+    );
+
+    fs::write(
+        &models_path,
+        "[[model]]\nfunction = \"*::AccountInfo::try_borrow_data\"\ntag = \"lottery\"\n",
+    )
+    .unwrap();
+    let (success, stderr, _) = analyze_contract(&manifest, target_dir.path(), true, &flags);
+    assert!(!success, "{stderr}");
+    assert!(
+        stderr.contains(&format!("{}:3:", models_path.display())),
+        "{stderr}"
+    );
+
+    fs::write(
+        &models_path,
+        "[[model]]\nfunction = \"*::AccountInfo::lamports\"\nreturns = { min = -1, max = 5 }\n",
+    )
+    .unwrap();
+    let (success, stderr, _) = analyze_contract(&manifest, target_dir.path(), true, &flags);
+    assert!(!success, "{stderr}");
+    assert!(
+        stderr.contains(&format!(
+            "{}:3: the return range of `*::AccountInfo::lamports` is not a range of u64",
+            models_path.display()
+        )),
+        "{stderr}"
+    );
+}
+
+// Check that the withdrawal of owner_check/contract_one, rewritten against the wrappers of
+// hepha_annotations::solana, has no findings.
+#[test]
+fn solana_prelude() {
+    for (contract, diagnostics) in analyze_contracts(Some("prelude"), true, "") {
+        let findings: Vec<&str> = diagnostics
+            .iter()
+            .filter(|d| finding_code(&d.message).is_some())
+            .map(|d| d.message.as_str())
+            .collect();
+        assert!(findings.is_empty(), "{}: {findings:?}", contract.display());
+    }
+}
+
+// Check that every contract of the overflow and underflow corpora has a finding of arithmetic that
+// wraps around, and that the deposit of overflow/contract_one names the range of the instruction
+// data that its amount is read from and says that the amount is untrusted input at the `+=`, while
+// the same deposit with checked arithmetic has none.
+#[test]
+fn arithmetic_overflows() {
+    let analyzed_contracts = analyze_contracts(Some("overflow"), false, "")
+        .into_iter()
+        .chain(analyze_contracts(Some("underflow"), false, ""))
+        .chain(analyze_contracts(Some("checked_arithmetic"), false, ""));
+    for (contract, diagnostics) in analyzed_contracts {
+        let findings: Vec<&ContractDiagnostic> = diagnostics
+            .iter()
+            .filter(|d| d.message.contains("[HEPHA-ARITH-WRAP]"))
+            .collect();
+        let checked = contract.parent().unwrap().ends_with("checked_arithmetic");
+        assert_eq!(findings.is_empty(), checked, "{}", contract.display());
+        if contract.ends_with("overflow/contract_one") {
+            assert!(
+                findings.iter().flat_map(|d| &d.notes).any(|note| note
+                    == "the value is read from instruction_data[1..9], which the caller controls"),
+                "{}",
+                contract.display()
+            );
+            assert!(
+                findings.iter().any(|d| d
+                    .message
+                    .ends_with("with an operand that is untrusted input")),
+                "{}",
+                contract.display()
+            );
+        }
+    }
+}
+
+// Check that the patch written by --emit-fixes resolves the findings that come with fixes: once it
+// is applied to a copy of a contract, the contract still builds and the findings are gone. The
+// fixes are an owner guard and a checked addition.
+#[test]
+fn emitted_fixes() {
+    let mut contracts_path = PathBuf::from_str("../contracts").unwrap();
+    if !contracts_path.exists() {
+        contracts_path = PathBuf::from_str("contracts").unwrap();
+    }
+    let target_dir = TempDir::new().expect("failed to create a temp dir");
+    for (contract, code) in [
+        ("owner_check/contract_one", "[HEPHA-OWNER-CHECK]"),
+        ("overflow/contract_one", "[HEPHA-ARITH-WRAP]"),
+    ] {
+        let copy = TempDir::new().expect("failed to create a temp dir");
+        let original = contracts_path.join(contract);
+        for entry in WalkDir::new(&original).into_iter().filter_map(|e| e.ok()) {
+            let destination = copy
+                .path()
+                .join(entry.path().strip_prefix(&original).unwrap());
+            if entry.file_type().is_dir() {
+                fs::create_dir_all(destination).unwrap();
+            } else {
+                fs::copy(entry.path(), destination).unwrap();
+            }
+        }
+        let manifest = copy.path().join("Cargo.toml");
+        let patch = copy.path().join("fixes.patch");
+        let flags = format!("--emit-fixes {}", patch.display());
+        let (_, _, diagnostics) = analyze_contract(&manifest, target_dir.path(), false, &flags);
+        assert!(
+            diagnostics.iter().any(|d| d.message.contains(code)),
+            "{contract}"
+        );
+        let applied = Command::new("patch")
+            .arg("-p1")
+            .arg("--input")
+            .arg(&patch)
+            .current_dir(copy.path())
+            .status()
+            .expect("could not run patch");
+        assert!(applied.success(), "{contract}");
+        let (success, stderr, diagnostics) =
+            analyze_contract(&manifest, target_dir.path(), false, &flags);
+        assert!(success, "{contract}\n{stderr}");
+        assert!(
+            !diagnostics.iter().any(|d| d.message.contains(code)),
+            "{contract}"
+        );
+    }
+}
+
+// Check the exit status of cargo hepha: 0 if there are no findings of at least the --fail-on
+// severity, which is error by default, and 2 if there are, including findings that are reported as
+// errors because their code is denied. Cargo does not run hepha again on a contract whose last
+// run succeeded, so the runs of a contract that are expected to fail come first.
+#[test]
+fn exit_status_of_findings() {
+    let mut contracts_path = PathBuf::from_str("../contracts").unwrap();
+    if !contracts_path.exists() {
+        contracts_path = PathBuf::from_str("contracts").unwrap();
+    }
+    let target_dir = TempDir::new().expect("failed to create a temp dir");
+    let exit_status = |contract: &str, hepha_flags: &str| {
+        Command::new(env!("CARGO_BIN_EXE_cargo-hepha"))
+            .arg("hepha")
+            .arg("--manifest-path")
+            .arg(contracts_path.join(contract).join("Cargo.toml"))
+            .arg("--target-dir")
+            .arg(target_dir.path())
+            .env("HEPHA_FLAGS", hepha_flags)
+            .output()
+            .expect("could not run cargo hepha")
+            .status
+            .code()
+    };
+    // No findings at all
+    assert_eq!(
+        exit_status("return_data/contract_two", "--fail-on note"),
+        Some(0)
+    );
+    // Only findings that are warnings
+    let warnings = "reentrancy/contract_twenty";
+    assert_eq!(exit_status(warnings, "--fail-on warning"), Some(2));
+    assert_eq!(exit_status(warnings, "--deny HEPHA-REENTRANCY"), Some(2));
+    assert_eq!(exit_status(warnings, ""), Some(0));
+}
+
+// Check that the summary cache remembers the foreign functions that have no type specific summary
+// in the persistent store, so that the calls of such a function after the first one do not look up
+// the store again. The counts are printed by --statistics.
+#[test]
+fn summary_store_misses_are_cached() {
+    let mut contracts_path = PathBuf::from_str("../contracts").unwrap();
+    if !contracts_path.exists() {
+        contracts_path = PathBuf::from_str("contracts").unwrap();
+    }
+    let target_dir = TempDir::new().expect("failed to create a temp dir");
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-hepha"))
+        .arg("hepha")
+        .arg("--manifest-path")
+        .arg(contracts_path.join("reentrancy/contract_twenty/Cargo.toml"))
+        .arg("--target-dir")
+        .arg(target_dir.path())
+        .env("HEPHA_FLAGS", "--statistics")
+        .output()
+        .expect("could not run cargo hepha");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let count_of = |name: &str| -> usize {
+        let fields: Vec<&str> = stdout.split(", ").collect();
+        let i = fields.iter().position(|f| *f == name).expect(name);
+        fields[i + 1].trim().parse().unwrap()
+    };
+    assert!(count_of("store misses") > 0);
+    assert!(count_of("skipped store lookups") > 0);
+}
+
+// Check that a loop state whose value goes down from one iteration to the next is detected. A
+// stub transfer function that flips a value between 1 and 2 is not monotone unless its result is
+// joined with the state of the previous iteration, which the fixed point visitor does.
+#[test]
+fn non_monotone_joins() {
+    let x = ValuePath::new_local(1, 0);
+    let flip = |state: &Environment| {
+        let one: Rc<AbstractValue> = Rc::new(1u128.into());
+        let two: Rc<AbstractValue> = Rc::new(2u128.into());
+        let mut result = state.clone();
+        let next = if state.value_at(&x) == Some(&one) {
+            two
+        } else {
+            one
+        };
+        result.strong_update_value_at(x.clone(), next);
+        result
+    };
+    let anchor = mir::BasicBlock::from_u32(1);
+    let mut state = flip(&Environment::default());
+    let mut monotonicity = JoinMonotonicity::default();
+    assert!(monotonicity.record(anchor, &state).is_empty());
+    let next_state = flip(&state);
+    let violations = monotonicity.record(anchor, &next_state);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].path, x);
+
+    monotonicity.restart(anchor);
+    assert!(monotonicity.record(anchor, &state).is_empty());
+    for _ in 0..3 {
+        state = state.join(flip(&state));
+        assert!(monotonicity.record(anchor, &state).is_empty());
+    }
+}
+
+// Check that the entries of an environment are sorted in the same order whatever the order in
+// which they were inserted, including paths of different kinds, which used to be incomparable.
+#[test]
+fn sorted_environment_entries() {
+    let paths = [
+        ValuePath::new_field(ValuePath::new_parameter(1), 0),
+        ValuePath::new_result(),
+        ValuePath::new_local(3, 0),
+        ValuePath::new_parameter(2),
+    ];
+    let environment = |order: &[usize]| {
+        let mut environment = Environment::default();
+        for i in order {
+            environment.strong_update_value_at(paths[*i].clone(), Rc::new((*i as u128).into()));
+        }
+        environment
+            .sorted_entries()
+            .into_iter()
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>()
+    };
+    let sorted = environment(&[0, 1, 2, 3]);
+    assert_eq!(sorted, environment(&[3, 2, 1, 0]));
+    assert_eq!(sorted, environment(&[1, 3, 0, 2]));
+    assert!(sorted.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+// Check the bounds that follow from the structure of expressions, including operations that may
+// wrap around, and that bounds that fit the type of an operation rule out its overflow.
+#[test]
+fn bounded_intervals() {
+    let x = AbstractValue::make_typed_unknown(ExpressionType::U64, ValuePath::new_parameter(1));
+    let y = AbstractValue::make_typed_unknown(ExpressionType::I32, ValuePath::new_parameter(2));
+    let z = AbstractValue::make_typed_unknown(ExpressionType::I128, ValuePath::new_parameter(3));
+    let unsigned = |u: u128| -> Rc<AbstractValue> { Rc::new(u.into()) };
+    let signed = |i: i128| -> Rc<AbstractValue> { Rc::new(i.into()) };
+    let u64_max = i128::from(u64::MAX);
+    let i32_max = i128::from(i32::MAX);
+
+    assert_eq!(unsigned(42).bound_interval(), Some((42, 42)));
+    assert_eq!(x.bound_interval(), Some((0, u64_max)));
+    assert_eq!(y.bound_interval(), Some((-i32_max - 1, i32_max)));
+    assert_eq!(z.bound_interval(), None);
+
+    let low_byte = x.bit_and(unsigned(255));
+    assert_eq!(low_byte.bound_interval(), Some((0, 255)));
+    assert_eq!(
+        unsigned(10).addition(low_byte.clone()).bound_interval(),
+        Some((10, 265))
+    );
+    assert_eq!(
+        low_byte.subtract(unsigned(1)).bound_interval(),
+        Some((0, u64_max))
+    );
+    // x + 1 wraps around if x is u64::MAX, so it can be any u64.
+    assert_eq!(x.addition(unsigned(1)).bound_interval(), Some((0, u64_max)));
+
+    assert_eq!(x.unsigned_modulo(4).bound_interval(), Some((0, 15)));
+    assert_eq!(y.unsigned_modulo(8).bound_interval(), Some((0, 255)));
+    assert_eq!(y.remainder(signed(10)).bound_interval(), Some((-9, 9)));
+    assert_eq!(
+        low_byte.remainder(unsigned(100)).bound_interval(),
+        Some((0, 99))
+    );
+    assert_eq!(
+        low_byte.divide(unsigned(16)).bound_interval(),
+        Some((0, 15))
+    );
+
+    assert_eq!(x.cast(ExpressionType::U8).bound_interval(), Some((0, 255)));
+    assert_eq!(
+        y.cast(ExpressionType::U32).bound_interval(),
+        Some((0, i128::from(u32::MAX)))
+    );
+    assert_eq!(
+        low_byte.cast(ExpressionType::I16).bound_interval(),
+        Some((0, 255))
+    );
+
+    assert!(BlockVisitor::bounds_exclude_overflow(
+        mir::BinOp::Add,
+        ExpressionType::U8,
+        (0, 200),
+        (0, 55)
+    ));
+    assert!(!BlockVisitor::bounds_exclude_overflow(
+        mir::BinOp::Add,
+        ExpressionType::U8,
+        (0, 200),
+        (0, 56)
+    ));
+    assert!(!BlockVisitor::bounds_exclude_overflow(
+        mir::BinOp::Sub,
+        ExpressionType::U64,
+        (0, 255),
+        (0, 1)
+    ));
+    assert!(BlockVisitor::bounds_exclude_overflow(
+        mir::BinOp::Mul,
+        ExpressionType::I16,
+        (-100, 100),
+        (-300, 300)
+    ));
+    assert!(BlockVisitor::bounds_exclude_overflow(
+        mir::BinOp::Shl,
+        ExpressionType::U32,
+        (0, u64_max),
+        (0, 31)
+    ));
+    assert!(!BlockVisitor::bounds_exclude_overflow(
+        mir::BinOp::Shl,
+        ExpressionType::U32,
+        (0, 1),
+        (0, 32)
+    ));
+    let (_, overflow_flag) = BlockVisitor::do_checked_binary_op(
+        mir::BinOp::Mul,
+        ExpressionType::U64,
+        low_byte.clone(),
+        low_byte,
+    );
+    assert_eq!(overflow_flag.as_bool_if_known(), Some(false));
+}
+
+// The source of the crate whose function gives the body visitors of the transfer_and_refine tests
+// their parameters, whose types are those of the targets of the transferred effects.
+const TRANSFER_FIXTURE: &str = "
+pub struct Pair { pub first: u64, pub second: u64 }
+pub fn fixture(_pair: Pair, _values: [u64; 4], _reference: &u64) {}
+";
+
+type BodyVisitorTest = fn(&mut BodyVisitor<'_, '_, '_>);
+
+// Compiler callbacks that stop after the analysis of the crate and run each test on a fresh body
+// visitor for the function with the given name, without visiting the body of the function.
+struct BodyVisitorHarness {
+    function_name: &'static str,
+    tests: Vec<BodyVisitorTest>,
+    ran: usize,
+}
+
+impl rustc_driver::Callbacks for BodyVisitorHarness {
+    fn after_analysis<'tcx>(
+        &mut self,
+        compiler: &interface::Compiler,
+        tcx: TyCtxt<'tcx>,
+    ) -> Compilation {
+        compiler.sess.dcx().abort_if_errors();
+        let def_id = tcx
+            .hir()
+            .body_owners()
+            .map(|local_def_id| local_def_id.to_def_id())
+            .find(|def_id| {
+                tcx.opt_item_name(*def_id)
+                    .is_some_and(|name| name.as_str() == self.function_name)
+            })
+            .unwrap_or_else(|| panic!("no function named {}", self.function_name));
+        let options = Options::default();
+        let summary_store = TempDir::new().expect("failed to create a temp dir");
+        let summary_store_path = String::from(summary_store.path().to_str().unwrap());
+        let mut crate_visitor = CrateVisitor::new(
+            &compiler.sess,
+            tcx,
+            &options,
+            "fixture.rs",
+            summary_store_path,
+            true,
+        );
+        for test in &self.tests {
+            let mut diagnostics = Vec::new();
+            let mut active_calls_map = HashMap::new();
+            {
+                let type_cache = crate_visitor.type_cache.clone();
+                let mut body_visitor = BodyVisitor::new(
+                    &mut crate_visitor,
+                    def_id,
+                    &mut diagnostics,
+                    &mut active_calls_map,
+                    type_cache,
+                );
+                test(&mut body_visitor);
+            }
+            for (diagnostic, _) in diagnostics {
+                diagnostic.cancel();
+            }
+            self.ran += 1;
+        }
+        Compilation::Stop
+    }
+}
+
+// Compiles the source and runs each test on a fresh body visitor for the named function.
+fn run_body_visitor_tests(source: &str, function_name: &'static str, tests: Vec<BodyVisitorTest>) {
+    let temp_dir = TempDir::new().expect("failed to create a temp dir");
+    let file_path = temp_dir.path().join("fixture.rs");
+    fs::write(&file_path, source).unwrap();
+    let command_line_arguments: Vec<String> = vec![
+        String::from("--crate-name fixture"),
+        String::from(file_path.to_str().unwrap()),
+        String::from("--crate-type"),
+        String::from("lib"),
+        String::from("--edition=2021"),
+        String::from("--out-dir"),
+        String::from(temp_dir.path().to_str().unwrap()),
+        String::from("--sysroot"),
+        utils::find_sysroot(),
+        String::from("-Z"),
+        String::from("span_free_formats"),
+        String::from("-Z"),
+        String::from("mir-emit-retag"),
+    ];
+    let test_count = tests.len();
+    let mut harness = BodyVisitorHarness {
+        function_name,
+        tests,
+        ran: 0,
+    };
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let compiler = rustc_driver::RunCompiler::new(&command_line_arguments, &mut harness);
+        compiler.run()
+    }));
+    assert!(result.is_ok(), "a test of the body visitor failed");
+    assert_eq!(harness.ran, test_count);
+}
+
+// Check the transfer of the side effects of a callee to the environment of its caller, with
+// effects on synthetic paths whose targets are the parameters of a fixture function.
+#[test]
+fn transfer_and_refine_effects() {
+    run_body_visitor_tests(
+        TRANSFER_FIXTURE,
+        "fixture",
+        vec![
+            transfer_parameter_rooted_effects,
+            transfer_heap_rooted_reference,
+            transfer_tag_field,
+            transfer_deallocation,
+            transfer_to_index_target,
+        ],
+    );
+}
+
+// The effects on the fields of the first parameter are transferred to the fields of the target,
+// with the initial value of a parameter refined to the value of its argument before the call,
+// while an effect on another parameter is left out.
+fn transfer_parameter_rooted_effects(bv: &mut BodyVisitor<'_, '_, '_>) {
+    let source = ValuePath::new_parameter(1);
+    let target = ValuePath::new_parameter(1);
+    let argument = ValuePath::new_local(10, 0);
+    let five: Rc<AbstractValue> = Rc::new(5u128.into());
+    let nine: Rc<AbstractValue> = Rc::new(9u128.into());
+    let effects = vec![
+        (ValuePath::new_field(source.clone(), 0), five.clone()),
+        (
+            ValuePath::new_field(source.clone(), 1),
+            AbstractValue::make_initial_parameter_value(
+                ExpressionType::U64,
+                ValuePath::new_parameter(2),
+            ),
+        ),
+        (
+            ValuePath::new_index(ValuePath::new_parameter(2), Rc::new(0u128.into())),
+            five.clone(),
+        ),
+    ];
+    let arguments = vec![
+        (
+            target.clone(),
+            AbstractValue::make_typed_unknown(ExpressionType::NonPrimitive, target.clone()),
+        ),
+        (argument.clone(), nine.clone()),
+    ];
+    let mut pre_environment = Environment::default();
+    pre_environment.strong_update_value_at(argument, nine.clone());
+    bv.transfer_and_refine(
+        &effects,
+        target.clone(),
+        &source,
+        &None,
+        &arguments,
+        &pre_environment,
+    );
+    let environment = &bv.current_environment;
+    assert_eq!(
+        environment.value_at(&ValuePath::new_field(target.clone(), 0)),
+        Some(&five)
+    );
+    assert_eq!(
+        environment.value_at(&ValuePath::new_field(target, 1)),
+        Some(&nine)
+    );
+    assert!(environment
+        .value_at(&ValuePath::new_index(
+            ValuePath::new_parameter(2),
+            Rc::new(0u128.into())
+        ))
+        .is_none());
+}
+
+// A reference to a heap block is transferred as is, and the heap block gets the type of the
+// values that the target refers to.
+fn transfer_heap_rooted_reference(bv: &mut BodyVisitor<'_, '_, '_>) {
+    let block = AbstractValue::make_from(bv.cv.constant_value_cache.get_new_heap_block(false), 1);
+    let heap_path = ValuePath::new_heap_block(block);
+    let reference = AbstractValue::make_reference(heap_path.clone());
+    let source = ValuePath::new_parameter(1);
+    let target = ValuePath::new_parameter(3);
+    bv.transfer_and_refine(
+        &[(source.clone(), reference.clone())],
+        target.clone(),
+        &source,
+        &None,
+        &[],
+        &Environment::default(),
+    );
+    assert_eq!(bv.current_environment.value_at(&target), Some(&reference));
+    assert_eq!(
+        bv.type_visitor().get_path_type_cache().get(&heap_path),
+        Some(&bv.tcx.types.u64)
+    );
+}
+
+// The tags of the tag field of the source are attached to the value of the target, rather than
+// the tag field value being copied.
+fn transfer_tag_field(bv: &mut BodyVisitor<'_, '_, '_>) {
+    let source = ValuePath::new_parameter(1);
+    let target = ValuePath::new_parameter(1);
+    let tagged = Rc::new(abstract_value::DUMMY_UNTAGGED_VALUE).add_tag(Tag::UNTRUSTED_INPUT);
+    bv.transfer_and_refine(
+        &[(ValuePath::new_tag_field(source.clone()), tagged)],
+        target.clone(),
+        &source,
+        &None,
+        &[],
+        &Environment::default(),
+    );
+    let tag_field_value = bv
+        .current_environment
+        .value_at(&ValuePath::new_tag_field(target))
+        .expect("the target has a tag field");
+    assert_eq!(
+        tag_field_value
+            .has_tag(&Tag::UNTRUSTED_INPUT)
+            .as_bool_if_known(),
+        Some(true)
+    );
+}
+
+// The deallocation of the heap block of the target purges the paths rooted in the block and
+// records the layout of the deallocation.
+fn transfer_deallocation(bv: &mut BodyVisitor<'_, '_, '_>) {
+    let eight: Rc<AbstractValue> = Rc::new(8u128.into());
+    let u64_type = bv.tcx.types.u64;
+    let (_, heap_path) = bv.get_new_heap_block(eight.clone(), eight.clone(), false, u64_type);
+    let element = ValuePath::new_index(heap_path.clone(), Rc::new(0u128.into()));
+    bv.update_value_at(element.clone(), Rc::new(3u128.into()));
+    let dealloc_layout = AbstractValue::make_from(
+        Expression::HeapBlockLayout {
+            length: eight.clone(),
+            alignment: eight,
+            source: LayoutSource::DeAlloc,
+        },
+        1,
+    );
+    let source = ValuePath::new_parameter(1);
+    let pre_environment = bv.current_environment.clone();
+    bv.transfer_and_refine(
+        &[(
+            ValuePath::new_layout(source.clone()),
+            dealloc_layout.clone(),
+        )],
+        heap_path.clone(),
+        &source,
+        &None,
+        &[],
+        &pre_environment,
+    );
+    let environment = &bv.current_environment;
+    assert!(environment.value_at(&element).is_none());
+    assert_eq!(
+        environment.value_at(&ValuePath::new_layout(heap_path)),
+        Some(&dealloc_layout)
+    );
+}
+
+// An effect on an element of the source is copied to the element of the target, unless the
+// element already had the value of the effect before the call.
+fn transfer_to_index_target(bv: &mut BodyVisitor<'_, '_, '_>) {
+    let seven: Rc<AbstractValue> = Rc::new(7u128.into());
+    let source = ValuePath::new_parameter(1);
+    let target = ValuePath::new_parameter(2);
+    let effects = [(
+        ValuePath::new_index(source.clone(), Rc::new(1u128.into())),
+        seven.clone(),
+    )];
+    let element = ValuePath::new_index(target.clone(), Rc::new(1u128.into()));
+    let mut pre_environment = Environment::default();
+    pre_environment.strong_update_value_at(element.clone(), seven.clone());
+    bv.transfer_and_refine(
+        &effects,
+        target.clone(),
+        &source,
+        &None,
+        &[],
+        &pre_environment,
+    );
+    assert!(bv.current_environment.value_at(&element).is_none());
+
+    bv.transfer_and_refine(
+        &effects,
+        target,
+        &source,
+        &None,
+        &[],
+        &Environment::default(),
+    );
+    assert_eq!(bv.current_environment.value_at(&element), Some(&seven));
+}
+
+// Check that the HTML report of path constraints has a panel with the conjuncts of each
+// conditionally entered block of a function with two nested branches.
+#[test]
+fn path_constraints_report() {
+    let mut file_path = PathBuf::from_str("tests/run-pass/path_constraints.rs").unwrap();
+    if !file_path.exists() {
+        file_path = PathBuf::from_str("checker/tests/run-pass/path_constraints.rs").unwrap();
+    }
+    let early_error_handler = EarlyDiagCtxt::new(config::ErrorOutputType::default());
+    let temp_dir = TempDir::new().expect("failed to create a temp dir");
+    let report_dir = temp_dir.path().join("report");
+    let mut options = build_options(&early_error_handler);
+    options.path_constraints_report = Some(report_dir.to_str().unwrap().to_string());
+    let result = invoke_driver(
+        &early_error_handler,
+        file_path.to_str().unwrap().to_string(),
+        temp_dir.path().to_str().unwrap().to_string(),
+        utils::find_sysroot(),
+        Vec::new(),
+        options,
+    );
+    assert_eq!(result, 0);
+    let report = fs::read_dir(&report_dir)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .find(|e| e.path().extension().is_some_and(|x| x == "html"))
+        .map(|e| read_to_string(e.path()).unwrap())
+        .expect("no report was written");
+    assert!(report.contains("nested_branches-bb1\" data-conjuncts=\"1\""));
+    assert!(report.contains("nested_branches-bb2\" data-conjuncts=\"2\""));
+    assert!(report.contains("<code>y</code> from the branch in bb1"));
+}
+
+// Check that tests/run-pass/smt_division_by_zero.rs passes with every SMT backend that is built
+// in, so that each of them proves the divisor that depends on two parameters to be nonzero.
+#[test]
+fn smt_backends() {
+    let mut file_path = PathBuf::from_str("tests/run-pass/smt_division_by_zero.rs").unwrap();
+    if !file_path.exists() {
+        file_path = PathBuf::from_str("checker/tests/run-pass/smt_division_by_zero.rs").unwrap();
+    }
+    let backends = [
+        (cfg!(feature = "z3"), SmtBackend::Z3),
+        (cfg!(feature = "cvc5"), SmtBackend::Cvc5),
+    ];
+    for (_, backend) in backends.into_iter().filter(|(built_in, _)| *built_in) {
+        let early_error_handler = EarlyDiagCtxt::new(config::ErrorOutputType::default());
+        let temp_dir = TempDir::new().expect("failed to create a temp dir");
+        let mut options = build_options(&early_error_handler);
+        options.smt_backend = backend;
+        let result = invoke_driver(
+            &early_error_handler,
+            file_path.to_str().unwrap().to_string(),
+            temp_dir.path().to_str().unwrap().to_string(),
+            utils::find_sysroot(),
+            Vec::new(),
+            options,
+        );
+        assert_eq!(result, 0, "{backend:?}");
+    }
+}
+
+// Check that CVC5 decides a satisfiable and an unsatisfiable query about an unsigned parameter,
+// the second of which only holds because the value of the parameter is an integer.
+#[cfg(feature = "cvc5")]
+#[test]
+fn cvc5_queries() {
+    use hepha::smt_solver::{BackendSolver, SmtResult, SmtSolver, DEFAULT_TIMEOUT_MS};
+
+    let solver = BackendSolver::new(SmtBackend::Cvc5, DEFAULT_TIMEOUT_MS);
+    let x = AbstractValue::make_typed_unknown(ExpressionType::U64, ValuePath::new_parameter(1));
+    let twice_x = x.multiply(Rc::new(2u128.into()));
+    let query = |value: u128| {
+        let condition = twice_x.equals(Rc::new(value.into()));
+        solver.solve_expression(&solver.get_as_smt_predicate(&condition.expression))
+    };
+    assert_eq!(query(8), SmtResult::Satisfiable);
+    assert_eq!(query(7), SmtResult::Unsatisfiable);
+}
+
+// Check that the query of tests/run-pass/smt_timeout.rs, which is nonlinear and may only take
+// a millisecond, is counted as timed out in the summary of the run, and that the analysis of the
+// function still completes.
+#[test]
+fn smt_timeouts() {
+    let mut file_path = PathBuf::from_str("tests/run-pass/smt_timeout.rs").unwrap();
+    if !file_path.exists() {
+        file_path = PathBuf::from_str("checker/tests/run-pass/smt_timeout.rs").unwrap();
+    }
+    let early_error_handler = EarlyDiagCtxt::new(config::ErrorOutputType::default());
+    let temp_dir = TempDir::new().expect("failed to create a temp dir");
+    let stats_path = temp_dir.path().join("stats.json");
+    let mut options = build_options(&early_error_handler);
+    options.stats_json = Some(stats_path.to_str().unwrap().to_string());
+    let result = invoke_driver(
+        &early_error_handler,
+        file_path.to_str().unwrap().to_string(),
+        temp_dir.path().to_str().unwrap().to_string(),
+        utils::find_sysroot(),
+        vec![(
+            "hepha_annotations",
+            find_extern_library("hepha_annotations"),
+        )],
+        options,
+    );
+    assert_eq!(result, 0);
+    let summary: RunSummary =
+        serde_json::from_str(&read_to_string(&stats_path).expect("no run summary was written"))
+            .unwrap();
+    assert!(summary.functions_analyzed > 0);
+    assert_eq!(summary.functions_timed_out, 0);
+    assert!(summary.solver_timeouts > 0, "{summary:?}");
+    assert!(summary.solver_queries >= summary.solver_timeouts);
+}
+
+// Check that the summaries written by --emit-summaries for the methods of a deposit contract are
+// those of the golden file next to it. Set HEPHA_BLESS to update the golden file instead.
+#[test]
+fn emitted_summaries() {
+    let mut directory = PathBuf::from_str("tests/summaries").unwrap();
+    if !directory.exists() {
+        directory = PathBuf::from_str("checker/tests/summaries").unwrap();
+    }
+    let file_path = directory.join("deposit_contract.rs");
+    let golden_path = directory.join("deposit_contract.json");
+    let early_error_handler = EarlyDiagCtxt::new(config::ErrorOutputType::default());
+    let temp_dir = TempDir::new().expect("failed to create a temp dir");
+    let summaries_path = temp_dir.path().join("summaries.json");
+    let mut options = build_options(&early_error_handler);
+    options.emit_summaries = Some(summaries_path.to_str().unwrap().to_string());
+    let result = invoke_driver(
+        &early_error_handler,
+        file_path.to_str().unwrap().to_string(),
+        temp_dir.path().to_str().unwrap().to_string(),
+        utils::find_sysroot(),
+        Vec::new(),
+        options,
+    );
+    assert_eq!(result, 0);
+    let summaries = read_to_string(&summaries_path).expect("no summaries were written");
+    if std::env::var("HEPHA_BLESS").is_ok() {
+        fs::write(&golden_path, &summaries).unwrap();
+        return;
+    }
+    let views: SummaryViews = serde_json::from_str(&summaries).unwrap();
+    assert_eq!(views.version, SUMMARY_VIEW_VERSION);
+    assert_eq!(summaries, read_to_string(&golden_path).unwrap());
+}
+
+// Check that --incremental only analyzes the functions of the application that changed or that
+// call a function that changed, and replays the diagnostics of the others: all of them in the
+// first run, none when nothing changed, scaled when the private function that it calls is edited,
+// halves when the function of the library that it calls is given a precondition, and fee when only
+// the struct that it uses is edited. The replayed diagnostic of per_share, which has moved, is
+// checked against its expectation in every run.
+#[test]
+fn incremental_analysis() {
+    let mut directory = PathBuf::from_str("tests/incremental").unwrap();
+    if !directory.exists() {
+        directory = PathBuf::from_str("checker/tests/incremental").unwrap();
+    }
+    let temp_dir = TempDir::new().expect("failed to create a temp dir");
+    let library_path = temp_dir.path().join("incremental_library.rs");
+    let application_path = temp_dir.path().join("incremental_application.rs");
+    let library = read_to_string(directory.join("incremental_library.rs")).unwrap();
+    let application = read_to_string(directory.join("incremental_application.rs")).unwrap();
+    fs::write(&library_path, &library).unwrap();
+    fs::write(&application_path, &application).unwrap();
+
+    assert_eq!(
+        analyze_incrementally(&library_path, temp_dir.path()),
+        ["share"]
+    );
+    assert_eq!(
+        analyze_incrementally(&application_path, temp_dir.path()),
+        ["fee", "halves", "per_share", "scaled"]
+    );
+    assert!(analyze_incrementally(&application_path, temp_dir.path()).is_empty());
+
+    fs::write(
+        &application_path,
+        application.replace("amount / 3", "amount / 1000"),
+    )
+    .unwrap();
+    assert_eq!(
+        analyze_incrementally(&application_path, temp_dir.path()),
+        ["scaled"]
+    );
+
+    fs::write(
+        &library_path,
+        library.replace(
+            "    if parts == 0 {\n        return 0;\n    }\n",
+            "    precondition!(parts > 0);\n",
+        ),
+    )
+    .unwrap();
+    assert_eq!(
+        analyze_incrementally(&library_path, temp_dir.path()),
+        ["share"]
+    );
+    assert_eq!(
+        analyze_incrementally(&application_path, temp_dir.path()),
+        ["halves"]
+    );
+
+    // Only the struct that fee uses changes, not the source of fee itself
+    fs::write(
+        &application_path,
+        application
+            .replace("amount / 3", "amount / 1000")
+            .replace("    pub rate: u64,\n", "    pub rate: u64,\n    pub cap: u64,\n"),
+    )
+    .unwrap();
+    assert_eq!(
+        analyze_incrementally(&application_path, temp_dir.path()),
+        ["fee"]
+    );
+}
+
+// Analyzes the crate of incremental_analysis at the given path with the flags of the file, with
+// the summary store in the given directory, and returns the names of the functions that were
+// analyzed again. The library is also compiled to the metadata, with the MIR of its functions,
+// that the application is compiled against, since the driver of the tests generates no code.
+fn analyze_incrementally(file_path: &Path, temp_dir: &Path) -> Vec<String> {
+    let annotations = find_extern_library("hepha_annotations");
+    let library_metadata = temp_dir.join("libincremental_library.rmeta");
+    let mut extern_deps = vec![("hepha_annotations", annotations.clone())];
+    if file_path.ends_with("incremental_library.rs") {
+        let status = Command::new(Path::new(&utils::find_sysroot()).join("bin").join("rustc"))
+            .arg(file_path)
+            .args(["--crate-type", "lib", "--edition=2021", "--emit=metadata"])
+            .args(["-Z", "always-encode-mir", "--cfg", "hepha"])
+            .arg("--extern")
+            .arg(format!("hepha_annotations={annotations}"))
+            .arg("--out-dir")
+            .arg(temp_dir)
+            .status()
+            .expect("could not run rustc");
+        assert!(status.success());
+    } else {
+        extern_deps.push((
+            "incremental_library",
+            library_metadata.to_str().unwrap().to_string(),
+        ));
+    }
+    let early_error_handler = EarlyDiagCtxt::new(config::ErrorOutputType::default());
+    let summaries_path = temp_dir.join("summaries.json");
+    let mut options = build_options(&early_error_handler);
+    options.emit_summaries = Some(summaries_path.to_str().unwrap().to_string());
+    let result = invoke_driver(
+        &early_error_handler,
+        file_path.to_str().unwrap().to_string(),
+        temp_dir.to_str().unwrap().to_string(),
+        utils::find_sysroot(),
+        extern_deps,
+        options,
+    );
+    assert_eq!(result, 0, "{}", file_path.display());
+    let views: SummaryViews =
+        serde_json::from_str(&read_to_string(&summaries_path).unwrap()).unwrap();
+    views
+        .functions
+        .keys()
+        .map(|key| key.rsplit('.').next().unwrap().to_string())
+        .collect()
+}
+
+// Check that the states recorded with --record-call-states for the withdraw function of a
+// reentrancy contract show what is known about the balance right before the invoke, and that no
+// more calls are recorded for a function than --call-states-limit allows.
+#[test]
+fn recorded_call_states() {
+    let mut contracts_path = PathBuf::from_str("../contracts").unwrap();
+    if !contracts_path.exists() {
+        contracts_path = PathBuf::from_str("contracts").unwrap();
+    }
+    let manifest = contracts_path.join("reentrancy/contract_twenty_two/Cargo.toml");
+    let record = |limit: usize| {
+        let target_dir = TempDir::new().expect("failed to create a temp dir");
+        let states_path = target_dir.path().join("call_states.json");
+        let flags = format!(
+            "--record-call-states withdraw --emit-call-states {} --call-states-limit {limit}",
+            states_path.display()
+        );
+        let (success, stderr, _) = analyze_contract(&manifest, target_dir.path(), true, &flags);
+        assert!(success, "{stderr}");
+        let states = read_to_string(&states_path).expect("no call states were written");
+        let states: CallStates = serde_json::from_str(&states).unwrap();
+        assert_eq!(states.version, CALL_STATES_VERSION);
+        states
+            .functions
+            .into_values()
+            .find(|function| function.name.ends_with("withdraw"))
+            .expect("the calls of withdraw were not recorded")
+    };
+    let withdraw = record(64);
+    assert!(!withdraw.truncated);
+    let invoke = withdraw
+        .calls
+        .values()
+        .find(|state| state.callee.ends_with("program::invoke"))
+        .expect("the invoke was not recorded");
+    assert!(
+        invoke.entry_condition.rendered.contains("balance")
+            || invoke.values.iter().any(|v| v.path.contains("balance")),
+        "{invoke:?}"
+    );
+    let truncated = record(1);
+    assert!(truncated.truncated);
+    assert_eq!(truncated.calls.len(), 1);
+}
+
+// Measure the cost of --trace-function when the traced function is not analyzed, which should be
+// no more than a few percent. The fastest of a few analyses of a run-pass file with and without
+// the option are compared, with some slack for tests that run at the same time.
+#[test]
+fn trace_function_overhead() {
+    let mut file_path = PathBuf::from_str("tests/run-pass/account_window.rs").unwrap();
+    if !file_path.exists() {
+        file_path = PathBuf::from_str("checker/tests/run-pass/account_window.rs").unwrap();
+    }
+    let early_error_handler = EarlyDiagCtxt::new(config::ErrorOutputType::default());
+    trace_scope::enable(log::max_level());
+    let analyze = |trace_function: Option<&str>| {
+        let temp_dir = TempDir::new().expect("failed to create a temp dir");
+        let mut options = build_options(&early_error_handler);
+        options.trace_function = trace_function.map(|f| f.to_string());
+        let start = Instant::now();
+        let result = invoke_driver(
+            &early_error_handler,
+            file_path.to_str().unwrap().to_string(),
+            temp_dir.path().to_str().unwrap().to_string(),
+            utils::find_sysroot(),
+            Vec::new(),
+            options,
+        );
+        assert_eq!(result, 0);
+        start.elapsed()
+    };
+    let mut untraced = Duration::MAX;
+    let mut traced = Duration::MAX;
+    for _ in 0..3 {
+        untraced = untraced.min(analyze(None));
+        traced = traced.min(analyze(Some("no_such_function")));
+    }
+    let overhead = traced.as_secs_f64() / untraced.as_secs_f64() - 1.0;
+    println!(
+        "analysis took {untraced:?} without and {traced:?} with --trace-function, an overhead of {:.1}%",
+        overhead * 100.0
+    );
+    assert!(overhead < 0.25);
+}
+
 // Run the tests in the tests/call_graph directory.
 fn run_call_graph_tests() {
     let mut call_graph_tests_path = PathBuf::from_str("tests/call_graph").unwrap();
@@ -154,7 +1715,7 @@ fn build_options(early_error_handler: &EarlyDiagCtxt) -> Options {
     let mut options = Options::default();
     options.parse_from_str("", early_error_handler, true); // get defaults
     options.diag_level = DiagLevel::Paranoid; // override default
-    options.max_analysis_time_for_body = 20;
+    options.max_analysis_time_for_body = Duration::from_secs(20);
     options.max_analysis_time_for_crate = 60;
     options
 }
@@ -487,3 +2048,1140 @@ fn start_driver_call_graph(config: DriverConfig) -> usize {
         result
     }
 }
+
+// Check that payouts decided by a modulo of the clock and random values made of the timestamp and
+// the slot are flagged at the read of the clock, with a note at the sensitive use, and that an
+// escrow that only logs the clock and compares it with a deadline stored in account data is not.
+#[test]
+fn clock_manipulation() {
+    let analyzed_contracts = analyze_contracts(Some("time_manipulation"), true, "")
+        .into_iter()
+        .chain(analyze_contracts(Some("clock_deadline"), true, ""));
+    for (contract, diagnostics) in analyzed_contracts {
+        let findings: Vec<&ContractDiagnostic> = diagnostics
+            .iter()
+            .filter(|d| d.message.contains("[HEPHA-TIME-MANIPULATION]"))
+            .collect();
+        if contract.ends_with("time_manipulation/contract_eight") {
+            assert!(
+                findings
+                    .iter()
+                    .any(|d| d.message.contains("a modulo of the clock")
+                        && d.notes
+                            .iter()
+                            .any(|n| n.starts_with("the transfer is decided here"))),
+                "{}",
+                contract.display()
+            );
+        } else if contract.ends_with("time_manipulation/contract_five") {
+            assert!(
+                findings
+                    .iter()
+                    .any(|d| d.message.contains("combined into a random value")
+                        && d.notes
+                            .iter()
+                            .any(|n| n.starts_with("they are combined here"))),
+                "{}",
+                contract.display()
+            );
+        } else if contract.parent().unwrap().ends_with("clock_deadline") {
+            assert!(findings.is_empty(), "{}", contract.display());
+        }
+    }
+}
+
+// Check that withdraw_all of reentrancy/contract_five, which zeroes the lamports of the contract
+// account but only the balance of one user, is found to let the balances drift from the lamports
+// they are declared to add up to, at the update of the lamports, and that the deposits and
+// withdrawals of reentrancy/contract_one, which change both by the amount, keep them together.
+#[test]
+fn ledger_drift() {
+    let flags = "--ledger-account contract_account";
+    for (contract, diagnostics) in analyze_contracts(Some("reentrancy"), true, flags) {
+        let findings: Vec<&ContractDiagnostic> = diagnostics
+            .iter()
+            .filter(|d| d.message.contains("[HEPHA-LEDGER-DRIFT]"))
+            .collect();
+        if contract.ends_with("contract_five") {
+            assert!(
+                findings.iter().any(|d| d.line == 80),
+                "{}",
+                contract.display()
+            );
+        } else if contract.ends_with("contract_one") {
+            assert!(findings.is_empty(), "{}", contract.display());
+        }
+    }
+}
+
+// Check that transfer_lamports of reentrancy/contract_two, which debits the receiver instead of
+// crediting it, and withdraw_all of reentrancy/contract_five, which credits the user with less than
+// it debits the contract, are flagged at the update that leaves the total changed, and that the
+// same functions of balance_conservation/contract_one, whose credits match their debits, are not.
+#[test]
+fn balance_conservation() {
+    let analyzed_contracts = analyze_contracts(Some("reentrancy"), true, "")
+        .into_iter()
+        .chain(analyze_contracts(Some("balance_conservation"), true, ""));
+    for (contract, diagnostics) in analyzed_contracts {
+        let flagged_lines: BTreeSet<usize> = diagnostics
+            .iter()
+            .filter(|d| d.message.contains("[HEPHA-BALANCE-CONSERVATION]"))
+            .map(|d| d.line)
+            .collect();
+        if contract.ends_with("reentrancy/contract_two") {
+            assert!(flagged_lines.contains(&91), "{}", contract.display());
+        } else if contract.ends_with("reentrancy/contract_five") {
+            assert!(flagged_lines.contains(&81), "{}", contract.display());
+        } else if contract.ends_with("balance_conservation/contract_one") {
+            assert!(flagged_lines.is_empty(), "{}", contract.display());
+        }
+    }
+}
+
+// Check that the quotients of numerical_precision/div_before_mul that are multiplied are found,
+// with a note at the division, and that fee, which multiplies before it divides, is not.
+#[test]
+fn division_before_multiplication() {
+    for (contract, diagnostics) in analyze_contracts(Some("numerical_precision"), true, "") {
+        if !contract.ends_with("div_before_mul") {
+            continue;
+        }
+        let findings: Vec<&ContractDiagnostic> = diagnostics
+            .iter()
+            .filter(|d| d.message.contains("[HEPHA-NUMERICAL-PRECISION]"))
+            .filter(|d| d.notes.iter().any(|n| n.starts_with("the division is")))
+            .collect();
+        assert_eq!(findings.len(), 2, "{}", contract.display());
+    }
+}
+
+// Check that the reward of numerical_precision/contract_twenty_six, which is computed with floating
+// point arithmetic and then transferred, is reported at the transfer with a note at the arithmetic,
+// and that the same arithmetic in contract_seventeen, which never reaches a transfer or a balance,
+// is not.
+#[test]
+fn floats_in_money_paths() {
+    for (contract, diagnostics) in analyze_contracts(Some("numerical_precision"), true, "") {
+        let findings: Vec<&ContractDiagnostic> = diagnostics
+            .iter()
+            .filter(|d| d.message.starts_with("[HEPHA-FLOAT-MONEY]"))
+            .collect();
+        if contract.ends_with("contract_twenty_six") {
+            assert_eq!(findings.len(), 1, "{}", contract.display());
+            assert_eq!(findings[0].line, 40);
+            assert!(findings[0]
+                .notes
+                .iter()
+                .any(|n| n.contains("fixed-point integer arithmetic")));
+            assert_eq!(findings[0].note_lines, [38]);
+        } else if contract.ends_with("contract_seventeen") {
+            assert!(findings.is_empty(), "{}", contract.display());
+        }
+    }
+}
+
+// Check that the timeout of the analysis of analysis_timeout/contract_one, which is forced with a
+// tiny budget, has notes with the blocks on which the time was spent, and that one of them is in
+// the nested loops that the analysis spends its time on.
+#[test]
+fn timeout_notes() {
+    for (contract, diagnostics) in analyze_contracts(
+        Some("analysis_timeout"),
+        true,
+        "--diag=paranoid --body_analysis_timeout 0.05",
+    ) {
+        let source = read_to_string(contract.join("src/lib.rs")).unwrap();
+        let lines: Vec<&str> = source.lines().collect();
+        let loop_start = lines
+            .iter()
+            .position(|l| l.trim_start().starts_with("for "))
+            .unwrap()
+            + 1;
+        let loop_end = lines.iter().position(|l| l.contains("msg!")).unwrap();
+        let timeout = diagnostics
+            .iter()
+            .find(|d| d.message == "The analysis of this function timed out")
+            .unwrap_or_else(|| panic!("{} did not time out", contract.display()));
+        assert!(timeout
+            .notes
+            .iter()
+            .all(|n| n.contains(" ms were spent in bb")));
+        assert!(
+            timeout
+                .note_lines
+                .iter()
+                .any(|line| (loop_start..=loop_end).contains(line)),
+            "{}",
+            contract.display()
+        );
+    }
+}
+
+// Check the notes of the reentrancy of reentrancy/contract_five as rustc renders them. They trace
+// the read of the balance, the call that may reenter the program and the write of the balance
+// after it, in this order.
+#[test]
+fn reentrancy_trace_notes() {
+    let (contract, diagnostics) = analyze_contracts(Some("reentrancy/contract_five"), true, "")
+        .pop()
+        .unwrap();
+    let reentrancy = diagnostics
+        .iter()
+        .find(|d| d.message.contains("possible reentrancy"))
+        .unwrap_or_else(|| panic!("no reentrancy in {}", contract.display()));
+    assert_eq!(
+        rendered_notes(&reentrancy.rendered),
+        [
+            (
+                "the balance that the key of `user_account` selects is read here",
+                78
+            ),
+            ("then this call may reenter the program", 81),
+            ("and the balance is written here, after the call", 83),
+        ]
+        .map(|(note, line)| (note.to_string(), line))
+    );
+}
+
+// Returns the notes of a rendered diagnostic, each with the line of the location under it
+fn rendered_notes(rendered: &str) -> Vec<(String, usize)> {
+    let mut notes = Vec::new();
+    let mut lines = rendered.lines();
+    while let Some(line) = lines.next() {
+        let Some(note) = line.strip_prefix("note: ") else {
+            continue;
+        };
+        let line = lines
+            .next()
+            .and_then(|location| location.trim_start().strip_prefix("--> "))
+            .and_then(|location| location.rsplit(':').nth(1))
+            .and_then(|line| line.parse().ok())
+            .unwrap_or_default();
+        notes.push((note.to_string(), line));
+    }
+    notes
+}
+
+// Check that the helpers of distinct_accounts/contract_one that move lamports between the user
+// and the contract account, directly or through another helper, require the keys of the accounts
+// to differ in their summaries, and that the guard of the entrypoint meets the requirement, while
+// the entrypoint of reentrancy/contract_five, which has no such guard, is found at its call.
+#[test]
+fn distinct_accounts() {
+    let mut contracts_path = PathBuf::from_str("../contracts").unwrap();
+    if !contracts_path.exists() {
+        contracts_path = PathBuf::from_str("contracts").unwrap();
+    }
+    let manifest = contracts_path.join("distinct_accounts/contract_one/Cargo.toml");
+    let target_dir = TempDir::new().expect("failed to create a temp dir");
+    let summaries_path = target_dir.path().join("summaries.json");
+    let flags = format!("--emit-summaries {}", summaries_path.display());
+    let (success, stderr, diagnostics) =
+        analyze_contract(&manifest, target_dir.path(), true, &flags);
+    assert!(success, "{stderr}");
+    assert!(!diagnostics
+        .iter()
+        .any(|d| d.message.contains("[HEPHA-DISTINCT-ACCOUNTS]")));
+    let summaries = read_to_string(&summaries_path).expect("no summaries were written");
+    let views: SummaryViews = serde_json::from_str(&summaries).unwrap();
+    for helper in ["deposit", "transfer"] {
+        let view = views
+            .functions
+            .values()
+            .find(|view| view.name.ends_with(helper))
+            .unwrap_or_else(|| panic!("no summary of {helper}"));
+        assert!(
+            view.preconditions
+                .iter()
+                .any(|p| p.condition.rendered == "user_account.key != contract_account.key"),
+            "{view:?}"
+        );
+    }
+
+    let (contract, diagnostics) = analyze_contracts(Some("reentrancy/contract_five"), true, "")
+        .pop()
+        .unwrap();
+    assert!(
+        diagnostics
+            .iter()
+            .any(|d| d.message.contains("[HEPHA-DISTINCT-ACCOUNTS]") && d.line == 28),
+        "{}",
+        contract.display()
+    );
+}
+
+// Check that the call graph that --call-graph-out writes for reentrancy/contract_five has the
+// call of withdraw_all from the entrypoint, and flags withdraw_all, which has a finding, in both
+// of the formats of --call-graph-format.
+#[test]
+fn call_graph_export() {
+    let mut contracts_path = PathBuf::from_str("../contracts").unwrap();
+    if !contracts_path.exists() {
+        contracts_path = PathBuf::from_str("contracts").unwrap();
+    }
+    let manifest = contracts_path.join("reentrancy/contract_five/Cargo.toml");
+
+    let target_dir = TempDir::new().expect("failed to create a temp dir");
+    let graph_path = target_dir.path().join("call_graph.dot");
+    let flags = format!("--call-graph-out {}", graph_path.display());
+    let (success, stderr, _) = analyze_contract(&manifest, target_dir.path(), true, &flags);
+    assert!(success, "{stderr}");
+    let dot = read_to_string(&graph_path).expect("no call graph was written");
+    assert!(dot.starts_with("digraph call_graph {"), "{dot}");
+    let node = Regex::new(r#"^\s*(n\d+) \[label="([^"]*)", flagged=(true|false)"#).unwrap();
+    let edge = Regex::new(r"^\s*(n\d+) -> (n\d+) \[kind=(\w+)\];").unwrap();
+    let mut labels = HashMap::new();
+    let mut flagged = HashMap::new();
+    for line in dot.lines() {
+        if let Some(captures) = node.captures(line) {
+            labels.insert(captures[1].to_string(), captures[2].to_string());
+            flagged.insert(captures[2].to_string(), &captures[3] == "true");
+        }
+    }
+    assert!(
+        dot.lines().filter_map(|line| edge.captures(line)).any(|c| {
+            &c[3] == "call"
+                && labels[&c[1]].ends_with("process_instruction")
+                && labels[&c[2]].ends_with("withdraw_all")
+        }),
+        "{dot}"
+    );
+    assert!(
+        flagged
+            .iter()
+            .any(|(label, flagged)| label.ends_with("withdraw_all") && *flagged),
+        "{dot}"
+    );
+
+    let target_dir = TempDir::new().expect("failed to create a temp dir");
+    let graph_path = target_dir.path().join("call_graph.graphml");
+    let flags = format!(
+        "--call-graph-out {} --call-graph-format graphml",
+        graph_path.display()
+    );
+    let (success, stderr, _) = analyze_contract(&manifest, target_dir.path(), true, &flags);
+    assert!(success, "{stderr}");
+    let graphml = read_to_string(&graph_path).expect("no call graph was written");
+    assert!(graphml.contains("<graphml"), "{graphml}");
+    assert_eq!(graphml.matches("<node ").count(), labels.len(), "{graphml}");
+    assert_eq!(
+        graphml.matches("<edge ").count(),
+        dot.lines().filter(|line| edge.is_match(line)).count(),
+        "{graphml}"
+    );
+}
+
+// Check that the facts that --export-facts writes for bad_randomness/contract_two have the calls
+// of the helpers from the entrypoint, the writes of the lamports of the accounts that the helpers
+// are given and the branch on the random number, each once, and that schema.dl declares them.
+#[test]
+fn fact_export() {
+    let mut contracts_path = PathBuf::from_str("../contracts").unwrap();
+    if !contracts_path.exists() {
+        contracts_path = PathBuf::from_str("contracts").unwrap();
+    }
+    let manifest = contracts_path.join("bad_randomness/contract_two/Cargo.toml");
+    let target_dir = TempDir::new().expect("failed to create a temp dir");
+    let facts_dir = target_dir.path().join("facts");
+    let flags = format!("--export-facts {}", facts_dir.display());
+    let (success, stderr, _) = analyze_contract(&manifest, target_dir.path(), true, &flags);
+    assert!(success, "{stderr}");
+
+    let schema = read_to_string(facts_dir.join("schema.dl")).expect("no schema was written");
+    for declaration in [
+        ".decl calls(caller: symbol, callee: symbol, span: symbol)",
+        ".decl reads_clock(fn: symbol, span: symbol)",
+        ".decl writes_lamports(fn: symbol, account_param: symbol, span: symbol)",
+        ".decl tagged_flow(fn: symbol, source_kind: symbol, sink_span: symbol)",
+    ] {
+        assert!(schema.contains(declaration), "{schema}");
+    }
+    // Reads the rows of a relation, with the function in the first column shortened to its
+    // name and the span in the last column shortened to its line
+    let rows = |relation: &str| -> Vec<Vec<String>> {
+        let facts = read_to_string(facts_dir.join(format!("{relation}.facts")))
+            .unwrap_or_else(|_| panic!("no facts of {relation} were written"));
+        let lines: Vec<&str> = facts.lines().collect();
+        assert!(lines.windows(2).all(|w| w[0] < w[1]), "{facts}");
+        lines
+            .iter()
+            .map(|line| {
+                let mut columns: Vec<String> = line.split('\t').map(str::to_string).collect();
+                let first = columns[0].rsplit('.').next().unwrap().to_string();
+                columns[0] = first;
+                let last = columns.pop().unwrap();
+                columns.push(last.split(':').nth_back(1).unwrap().to_string());
+                columns
+            })
+            .collect()
+    };
+    let calls = rows("calls");
+    for (callee, line) in [("deposit", "31"), ("announce_winner", "35")] {
+        assert!(
+            calls.iter().any(|row| row[0] == "process_instruction"
+                && row[1].ends_with(callee)
+                && row[2] == line),
+            "{calls:?}"
+        );
+    }
+    assert!(rows("reads_clock").is_empty());
+    let writes_lamports = rows("writes_lamports");
+    for expected in [
+        ["deposit", "user_account", "52"],
+        ["deposit", "contract_account", "53"],
+        ["announce_winner", "contract_account", "67"],
+        ["announce_winner", "user_account", "68"],
+    ] {
+        assert!(
+            writes_lamports.contains(&expected.map(str::to_string).to_vec()),
+            "{writes_lamports:?}"
+        );
+    }
+    assert!(
+        rows("tagged_flow").contains(
+            &["announce_winner", "predictable_entropy", "66"]
+                .map(str::to_string)
+                .to_vec()
+        ),
+        "{:?}",
+        rows("tagged_flow")
+    );
+}
+
+// Check that the fingerprints of the diagnostics that --diag-format=json writes for
+// reentrancy/contract_five stay the same when the entrypoint is moved to the end of the file, and
+// that a baseline with the diagnostics of the first run suppresses all of them in the second one.
+#[test]
+fn diagnostic_fingerprints() {
+    let mut contracts_path = PathBuf::from_str("../contracts").unwrap();
+    if !contracts_path.exists() {
+        contracts_path = PathBuf::from_str("contracts").unwrap();
+    }
+    let original = contracts_path.join("reentrancy/contract_five");
+    let copy = TempDir::new().expect("failed to create a temp dir");
+    for entry in WalkDir::new(&original).into_iter().filter_map(|e| e.ok()) {
+        let destination = copy
+            .path()
+            .join(entry.path().strip_prefix(&original).unwrap());
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(destination).unwrap();
+        } else {
+            fs::copy(entry.path(), destination).unwrap();
+        }
+    }
+    let manifest = copy.path().join("Cargo.toml");
+    // Cargo does not run hepha again on a contract whose last run succeeded, so every run has a
+    // target directory of its own
+    let export = |hepha_flags: &str| {
+        let target_dir = TempDir::new().expect("failed to create a temp dir");
+        let output = Command::new(env!("CARGO_BIN_EXE_cargo-hepha"))
+            .arg("hepha")
+            .arg("--manifest-path")
+            .arg(&manifest)
+            .arg("--target-dir")
+            .arg(target_dir.path())
+            .env("HEPHA_FLAGS", format!("--diag-format json {hepha_flags}"))
+            .output()
+            .expect("could not run cargo hepha");
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect::<Vec<ExportedDiagnostic>>()
+    };
+    let first = export("");
+    let finding = first
+        .iter()
+        .find(|d| d.checker.as_deref() == Some("HEPHA-DISTINCT-ACCOUNTS"))
+        .expect("the finding of the entrypoint was not exported");
+    assert_eq!(finding.line, 28);
+
+    let source_path = copy.path().join("src/lib.rs");
+    let source = read_to_string(&source_path).unwrap();
+    let start = source.find("pub fn process_instruction(").unwrap();
+    let end = source.find("pub fn deposit(").unwrap();
+    let moved = format!(
+        "{}{}\n{}",
+        &source[..start],
+        &source[end..],
+        &source[start..end]
+    );
+    fs::write(&source_path, moved).unwrap();
+    let second = export("");
+    let moved_finding = second
+        .iter()
+        .find(|d| d.checker.as_deref() == Some("HEPHA-DISTINCT-ACCOUNTS"))
+        .expect("the finding of the moved entrypoint was not exported");
+    assert_ne!(moved_finding.line, finding.line);
+    assert_eq!(moved_finding.fingerprint, finding.fingerprint);
+    let fingerprints = |diagnostics: &[ExportedDiagnostic]| {
+        diagnostics
+            .iter()
+            .map(|d| (d.checker.clone(), d.fingerprint.clone()))
+            .collect::<BTreeSet<_>>()
+    };
+    assert_eq!(fingerprints(&first), fingerprints(&second));
+
+    let baseline_path = copy.path().join("baseline.json");
+    let lines: Vec<String> = first.iter().map(ExportedDiagnostic::to_json).collect();
+    fs::write(&baseline_path, lines.join("\n")).unwrap();
+    let suppressed = export(&format!("--baseline {}", baseline_path.display()));
+    assert!(suppressed.is_empty(), "{suppressed:?}");
+}
+
+// Check that the findings of twenty copies of a template, which are indented and commented
+// differently and name their accounts differently in their notes, form a single cluster, while
+// findings with another code, another snippet or notes of another shape, and diagnostics that are
+// not findings, form clusters of their own.
+#[test]
+fn finding_clusters() {
+    let finding = |file: &str, code: Option<&str>, snippet: &str, notes: Vec<ExportedNote>| {
+        ExportedDiagnostic {
+            file: file.to_string(),
+            line: 20,
+            column: 9,
+            checker: code.map(str::to_string),
+            message: format!("possible reentrancy [{}]", code.unwrap_or_default()),
+            fingerprint: String::new(),
+            snippet_hash: diagnostic_export::snippet_hash(snippet),
+            notes,
+            truncated: false,
+            properties: None,
+        }
+    };
+    let note = |file: &str, line: usize, message: &str| ExportedNote {
+        message: message.to_string(),
+        location: Some((file.to_string(), line, 5)),
+    };
+    let mut diagnostics = Vec::new();
+    for n in 0..20 {
+        let file = format!("contract_{n}/src/lib.rs");
+        let snippet = if n % 2 == 0 {
+            "**vault.lamports.borrow_mut() -= amount;"
+        } else {
+            "**vault.lamports\n    .borrow_mut() -= amount; // pay out"
+        };
+        let notes = vec![note(
+            &file,
+            12,
+            &format!("`account_{n}` is transferred to here"),
+        )];
+        diagnostics.push(finding(&file, Some("HEPHA-REENTRANCY"), snippet, notes));
+    }
+    let snippet = "**vault.lamports.borrow_mut() -= amount;";
+    let file = "contract_20/src/lib.rs";
+    diagnostics.push(finding(
+        file,
+        Some("HEPHA-LAMPORT-UNDERFLOW"),
+        snippet,
+        vec![],
+    ));
+    let notes = vec![note(file, 12, "`vault` is transferred to here")];
+    let other_snippet = "**vault.lamports.borrow_mut() += amount;";
+    diagnostics.push(finding(
+        file,
+        Some("HEPHA-REENTRANCY"),
+        other_snippet,
+        notes,
+    ));
+    let later_note = vec![note(file, 30, "`vault` is transferred to here")];
+    diagnostics.push(finding(file, Some("HEPHA-REENTRANCY"), snippet, later_note));
+    diagnostics.push(finding(file, None, snippet, vec![]));
+    diagnostics.push(finding(file, None, snippet, vec![]));
+
+    let clusters = hepha::finding_clusters::cluster(diagnostics);
+    let sizes: Vec<usize> = clusters.iter().map(|c| c.similar.len()).collect();
+    assert_eq!(sizes, vec![19, 0, 0, 0, 0, 0]);
+    let text = clusters[0].to_text();
+    assert!(
+        text.starts_with("contract_0/src/lib.rs:20:9: possible reentrancy [HEPHA-REENTRANCY]\n"),
+        "{text}"
+    );
+    assert!(
+        text.contains("  and 19 similar findings in: contract_1/src/lib.rs:20, "),
+        "{text}"
+    );
+    assert!(text.ends_with("contract_19/src/lib.rs:20\n"), "{text}");
+    assert_eq!(clusters[1].to_text().lines().count(), 1);
+    // The JSON of a cluster still has the similar findings in full
+    let json: serde_json::Value = serde_json::from_str(&clusters[0].to_json()).unwrap();
+    assert_eq!(
+        json["similar"][18]["notes"][0]["message"],
+        "`account_19` is transferred to here"
+    );
+}
+
+#[test]
+fn sol_units() {
+    let analyzed_contracts = analyze_contracts(Some("sol_units"), true, "");
+    let sol_unit_findings = |name: &str| {
+        let (_, diagnostics) = analyzed_contracts
+            .iter()
+            .find(|(contract, _)| contract.ends_with(name))
+            .unwrap_or_else(|| panic!("{name} was not analyzed"));
+        diagnostics
+            .iter()
+            .filter(|d| d.message.contains("[HEPHA-SOL-UNIT-MIXING]"))
+            .map(|d| d.line)
+            .collect::<BTreeSet<_>>()
+    };
+    assert_eq!(sol_unit_findings("contract_one"), BTreeSet::from([39, 40]));
+    assert!(sol_unit_findings("contract_two").is_empty());
+}
+
+// Check that the accounts that rent_dust/contract_one, contract_three and contract_four create with
+// fewer lamports than the rent-exempt minimum are flagged, with the shortfall in the notes, and so
+// is the account that contract_five reallocates after a fixed top-up, and that the accounts that
+// contract_two and contract_six fund with the minimum of the rent sysvar are not.
+#[test]
+fn rent_dust() {
+    let analyzed_contracts = analyze_contracts(Some("rent_dust"), true, "");
+    let rent_dust_findings = |name: &str| {
+        let (_, diagnostics) = analyzed_contracts
+            .iter()
+            .find(|(contract, _)| contract.ends_with(name))
+            .unwrap_or_else(|| panic!("{name} was not analyzed"));
+        diagnostics
+            .iter()
+            .filter(|d| d.message.contains("[HEPHA-RENT-DUST]"))
+            .collect::<Vec<&ContractDiagnostic>>()
+    };
+    let findings = rent_dust_findings("contract_one");
+    assert!(!findings.is_empty());
+    assert!(findings.iter().all(|d| d
+        .notes
+        .iter()
+        .any(|note| note.contains("fall short of the rent-exempt minimum"))));
+    let findings = rent_dust_findings("contract_three");
+    assert!(findings.iter().any(|d| d
+        .notes
+        .iter()
+        .any(|note| note.contains("by 1039280 lamports"))));
+    assert!(!rent_dust_findings("contract_four").is_empty());
+    let findings = rent_dust_findings("contract_five");
+    assert!(!findings.is_empty());
+    assert!(findings.iter().all(|d| d
+        .notes
+        .iter()
+        .any(|note| note.contains("the data of the account is allocated here"))));
+    assert!(rent_dust_findings("contract_two").is_empty());
+    assert!(rent_dust_findings("contract_six").is_empty());
+}
+
+// Check that the transfer of no_op_transfer/contract_one from the payer to itself and the transfer
+// of contract_two in the branch where the fee is zero are flagged as notes, and that the transfer
+// of contract_three, which is made only if the fee is not zero, is not.
+#[test]
+fn no_op_transfer() {
+    let analyzed_contracts = analyze_contracts(Some("no_op_transfer"), true, "");
+    let no_op_findings = |name: &str| {
+        let (_, diagnostics) = analyzed_contracts
+            .iter()
+            .find(|(contract, _)| contract.ends_with(name))
+            .unwrap_or_else(|| panic!("{name} was not analyzed"));
+        diagnostics
+            .iter()
+            .filter(|d| d.message.contains("[HEPHA-NO-OP-TRANSFER]"))
+            .collect::<Vec<&ContractDiagnostic>>()
+    };
+    let findings = no_op_findings("contract_one");
+    assert!(findings
+        .iter()
+        .any(|d| d.message.contains("from an account to itself")));
+    let findings = no_op_findings("contract_two");
+    assert!(findings
+        .iter()
+        .any(|d| d.message.contains("transfer of zero lamports")));
+    assert!(findings.iter().all(|d| d.rendered.starts_with("note: ")));
+    assert!(no_op_findings("contract_three").is_empty());
+}
+
+// Check that the slices of instruction_data_bounds/contract_one and contract_three are flagged on
+// the slice expression, with the length that the instruction data must have, and that the reads of
+// contract_two, which first checks the length of the instruction data, are not.
+#[test]
+fn instruction_data_bounds() {
+    let analyzed_contracts = analyze_contracts(Some("instruction_data_bounds"), true, "");
+    let bounds_findings = |name: &str| {
+        let (_, diagnostics) = analyzed_contracts
+            .iter()
+            .find(|(contract, _)| contract.ends_with(name))
+            .unwrap_or_else(|| panic!("{name} was not analyzed"));
+        diagnostics
+            .iter()
+            .filter(|d| d.message.contains("[HEPHA-INSTRUCTION-DATA-BOUNDS]"))
+            .collect::<Vec<&ContractDiagnostic>>()
+    };
+    for name in ["contract_one", "contract_three"] {
+        let findings = bounds_findings(name);
+        assert!(
+            findings
+                .iter()
+                .any(|d| d.message.contains("shorter than 9 bytes")
+                    && d.rendered.contains("instruction_data[1..")),
+            "{name}"
+        );
+    }
+    assert!(bounds_findings("contract_two").is_empty());
+}
+
+// Check that the reads of the amount in deserialization_offset/contract_one, which skips a byte
+// after the deserialized instruction, and in contract_two, whose instruction has no fixed size, are
+// flagged with a note at the deserialization, and that contract_three and
+// reentrancy/contract_nineteen, which read the amount right after the deserialized bytes, are not.
+#[test]
+fn deserialization_offsets() {
+    let analyzed_contracts = analyze_contracts(Some("deserialization_offset"), true, "")
+        .into_iter()
+        .chain(analyze_contracts(
+            Some("reentrancy/contract_nineteen"),
+            true,
+            "",
+        ))
+        .collect::<Vec<_>>();
+    let offset_findings = |name: &str| {
+        let (_, diagnostics) = analyzed_contracts
+            .iter()
+            .find(|(contract, _)| contract.ends_with(name))
+            .unwrap_or_else(|| panic!("{name} was not analyzed"));
+        diagnostics
+            .iter()
+            .filter(|d| d.message.contains("[HEPHA-DESERIALIZATION-OFFSET]"))
+            .collect::<Vec<&ContractDiagnostic>>()
+    };
+    for name in ["contract_one", "contract_two"] {
+        let findings = offset_findings(name);
+        assert!(
+            findings.iter().any(|d| d.notes.iter().any(
+                |note| note.contains("the prefix of the instruction data is deserialized here")
+            )),
+            "{name}"
+        );
+    }
+    assert!(offset_findings("contract_three").is_empty());
+    assert!(offset_findings("contract_nineteen").is_empty());
+}
+
+// Check that the map of balances that reentrancy/contract_one creates in its entrypoint and passes
+// to deposit and withdraw is flagged, with a note at the call that transfers lamports, and that the
+// map of non_persistent_state/contract_one, which is deserialized from the data of the contract
+// account and serialized back into it, is not.
+#[test]
+fn non_persistent_state() {
+    let analyzed_contracts = analyze_contracts(Some("non_persistent_state"), true, "")
+        .into_iter()
+        .chain(analyze_contracts(Some("reentrancy/contract_one"), true, ""))
+        .collect::<Vec<_>>();
+    let state_findings = |name: &str| {
+        let (_, diagnostics) = analyzed_contracts
+            .iter()
+            .find(|(contract, _)| contract.ends_with(name))
+            .unwrap_or_else(|| panic!("{name} was not analyzed"));
+        diagnostics
+            .iter()
+            .filter(|d| d.message.contains("[HEPHA-NON-PERSISTENT-STATE]"))
+            .collect::<Vec<&ContractDiagnostic>>()
+    };
+    let findings = state_findings("reentrancy/contract_one");
+    assert!(
+        findings.iter().any(|d| d.line == 22
+            && d.notes.iter().any(
+                |note| note.contains("it is passed to a function that transfers lamports here")
+            )),
+        "{findings:?}"
+    );
+    assert!(state_findings("non_persistent_state/contract_one").is_empty());
+}
+
+// Check that unvalidated_deserialization/contract_one, which deserializes a vault from account data
+// of any length and reads the amount of a deposit in the arm of the deserialized instruction, and
+// reentrancy/contract_nineteen, which does the latter, are flagged with notes at the deserialization,
+// and that contract_two, which checks both lengths, is not.
+#[test]
+fn unvalidated_deserializations() {
+    let analyzed_contracts = analyze_contracts(Some("unvalidated_deserialization"), true, "")
+        .into_iter()
+        .chain(analyze_contracts(
+            Some("reentrancy/contract_nineteen"),
+            true,
+            "",
+        ))
+        .collect::<Vec<_>>();
+    let deserialization_findings = |name: &str| {
+        let (_, diagnostics) = analyzed_contracts
+            .iter()
+            .find(|(contract, _)| contract.ends_with(name))
+            .unwrap_or_else(|| panic!("{name} was not analyzed"));
+        diagnostics
+            .iter()
+            .filter(|d| d.message.contains("[HEPHA-UNVALIDATED-DESERIALIZATION]"))
+            .collect::<Vec<&ContractDiagnostic>>()
+    };
+    let findings = deserialization_findings("contract_one");
+    assert!(
+        findings
+            .iter()
+            .any(|d| d.message.contains("possible deserialization of Vault")),
+        "{findings:?}"
+    );
+    for name in ["contract_one", "contract_nineteen"] {
+        let findings = deserialization_findings(name);
+        assert!(
+            findings.iter().any(|d| d.message.contains("at offset 1")
+                && d.notes
+                    .iter()
+                    .any(|note| note.contains("the variant is deserialized here"))),
+            "{name}: {findings:?}"
+        );
+    }
+    assert!(deserialization_findings("contract_two").is_empty());
+}
+
+// Check that keeping the side effects of the Ok and Err returns apart in summaries, with
+// --factor-exits, does not change what is found in the contracts whose callers use `?` the most.
+#[test]
+fn factored_exits() {
+    for category in ["result_unused", "lamport_underflow", "reentrancy"] {
+        let findings = |flags: &str| {
+            analyze_contracts(Some(category), true, flags)
+                .into_iter()
+                .map(|(contract, diagnostics)| {
+                    let mut findings: Vec<(String, usize)> = diagnostics
+                        .into_iter()
+                        .map(|d| (d.message, d.line))
+                        .collect();
+                    findings.sort();
+                    (contract, findings)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(findings(""), findings("--factor-exits"), "{category}");
+    }
+}
+
+// The hash maps of every process are seeded at random, so two runs of a contract iterate over
+// them in different orders, and with --threads, the components of the call graph of a contract
+// are analyzed by any number of worker threads. The diagnostics and the summaries must depend on
+// neither, so every contract of the corpus is run with different hash seeds, both one body after
+// the other and with 1 and 8 threads, and the outputs of the runs are compared byte for byte.
+#[test]
+fn repeatable_output() {
+    let mut contracts_path = PathBuf::from_str("../contracts").unwrap();
+    if !contracts_path.exists() {
+        contracts_path = PathBuf::from_str("contracts").unwrap();
+    }
+    let target_dir = TempDir::new().expect("failed to create a temp dir");
+    let summaries_path = target_dir.path().join("summaries.json");
+    let run = |manifest: &Path, hash_seed: &str, flags: &str| {
+        let _ = fs::remove_file(&summaries_path);
+        let output = Command::new(env!("CARGO_BIN_EXE_cargo-hepha"))
+            .arg("hepha")
+            .arg("--manifest-path")
+            .arg(manifest)
+            .arg("--target-dir")
+            .arg(target_dir.path())
+            .env("RUST_HASH_SEED", hash_seed)
+            .env(
+                "HEPHA_FLAGS",
+                format!(
+                    "--diag-format json --emit-summaries {} {flags}",
+                    summaries_path.display()
+                ),
+            )
+            .output()
+            .expect("could not run cargo hepha");
+        assert!(
+            output.status.success(),
+            "{}\n{}",
+            manifest.display(),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let summaries = fs::read(&summaries_path).unwrap_or_default();
+        // Cargo does not run hepha again on a contract whose last run succeeded, so remove the
+        // build of the contract, but not those of its dependencies, which the runs share.
+        let manifest_source = read_to_string(manifest).unwrap();
+        let package: toml::Value = toml::from_str(&manifest_source).unwrap();
+        let cleaned = Command::new(std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into()))
+            .arg("clean")
+            .arg("--manifest-path")
+            .arg(manifest)
+            .arg("--target-dir")
+            .arg(target_dir.path())
+            .arg("--package")
+            .arg(package["package"]["name"].as_str().unwrap())
+            .status()
+            .expect("could not run cargo clean");
+        assert!(cleaned.success(), "{}", manifest.display());
+        (output.stdout, summaries)
+    };
+    let mut differing = 0;
+    for entry in WalkDir::new(contracts_path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name() == "Cargo.toml")
+    {
+        let manifest = entry.path();
+        for (first_flags, second_flags) in [("", ""), ("--threads 1", "--threads 8")] {
+            let (first_output, first_summaries) = run(manifest, "1", first_flags);
+            let (second_output, second_summaries) = run(manifest, "2", second_flags);
+            if first_output != second_output {
+                println!(
+                    "{}: the outputs differ with {first_flags:?} and {second_flags:?}\n{}\n{}",
+                    manifest.display(),
+                    String::from_utf8_lossy(&first_output),
+                    String::from_utf8_lossy(&second_output)
+                );
+                differing += 1;
+            }
+            if first_summaries != second_summaries {
+                println!(
+                    "{}: the summaries differ with {first_flags:?} and {second_flags:?}",
+                    manifest.display()
+                );
+                differing += 1;
+            }
+        }
+    }
+    assert_eq!(differing, 0);
+}
+
+// Check that the flags take precedence over the options of a configuration file, that the codes
+// denied by both are denied, and that the severities and the suppressions of the file are applied.
+#[test]
+fn config_precedence() {
+    let early_error_handler = EarlyDiagCtxt::new(config::ErrorOutputType::default());
+    let config = Config::parse(
+        r#"
+[options]
+diag = "library"
+max_elements_to_track = 200
+deny = ["HEPHA-REENTRANCY"]
+
+[severities]
+HEPHA-SOL-UNIT-MIXING = "error"
+
+[[suppress]]
+file = "src/legacy.rs"
+checkers = ["arithmetic"]
+"#,
+    )
+    .unwrap();
+    assert!(config.unknown_keys().is_empty());
+    let mut options = Options::default();
+    options.parse_from_config_and_str(
+        &config,
+        "--diag=paranoid --deny HEPHA-OWNER-CHECK",
+        &early_error_handler,
+        false,
+    );
+    assert_eq!(options.diag_level, DiagLevel::Paranoid);
+    assert_eq!(options.k_limits.max_elements_to_track, 200);
+    assert_eq!(options.deny, ["HEPHA-REENTRANCY", "HEPHA-OWNER-CHECK"]);
+    assert_eq!(
+        options.severities.get("HEPHA-SOL-UNIT-MIXING"),
+        Some(&Severity::Error)
+    );
+    assert_eq!(
+        options.checkers_suppressed_in("contracts/vault/src/legacy.rs"),
+        ["arithmetic"].into()
+    );
+    assert!(options.checkers_suppressed_in("src/lib.rs").is_empty());
+    // What --dump-config prints reads back as the same configuration
+    let dumped = Config::of_options(&options);
+    assert_eq!(Config::parse(&dumped.to_toml()).unwrap(), dumped);
+}
+
+// Check that the keys that a configuration file does not know are reported with their sections,
+// and that values of the wrong type are errors.
+#[test]
+fn config_unknown_keys() {
+    let config = Config::parse(
+        r#"
+[options]
+diag = "library"
+max_elements = 200
+
+[models]
+transfer = "checked"
+
+[[suppress]]
+file = "src/lib.rs"
+checker = "arithmetic"
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.unknown_keys(),
+        ["models", "options.max_elements", "suppress.checker"]
+    );
+    assert!(Config::parse("[options]\nmax_path_length = \"long\"\n").is_err());
+}
+
+// Check that a run of the corpus can be configured by a configuration file alone: the findings of
+// the checker that the file suppresses in src/lib.rs are not reported.
+#[test]
+fn config_file_corpus_run() {
+    let config_dir = TempDir::new().expect("failed to create a temp dir");
+    let config_path = config_dir.path().join("hepha.toml");
+    fs::write(
+        &config_path,
+        "[options]\ndiag = \"library\"\n\n[[suppress]]\nfile = \"src/lib.rs\"\ncheckers = [\"sol_units\"]\n",
+    )
+    .unwrap();
+    for (contract, diagnostics) in analyze_contracts(
+        Some("sol_units"),
+        true,
+        &format!("--config {}", config_path.display()),
+    ) {
+        assert!(
+            diagnostics
+                .iter()
+                .all(|d| !d.message.contains("[HEPHA-SOL-UNIT-MIXING]")),
+            "{}",
+            contract.display()
+        );
+    }
+}
+
+// Check that a token is cancelled by cancel and once its deadline has passed, and that the analysis
+// of a contract that is cancelled mid-run, here by a crate analysis timeout of zero that passes
+// while the first function is analyzed, still writes well-formed machine output that is marked as
+// truncated.
+#[test]
+fn cancelled_analysis() {
+    let token = CancellationToken::new(None);
+    assert!(!token.is_cancelled());
+    token.cancel();
+    assert!(token.is_cancelled());
+    assert!(CancellationToken::new(Some(Duration::ZERO)).is_cancelled());
+    assert!(!CancellationToken::new(Some(Duration::from_secs(3600))).is_cancelled());
+
+    let mut contracts_path = PathBuf::from_str("../contracts").unwrap();
+    if !contracts_path.exists() {
+        contracts_path = PathBuf::from_str("contracts").unwrap();
+    }
+    let target_dir = TempDir::new().expect("failed to create a temp dir");
+    let stats_dir = TempDir::new().expect("failed to create a temp dir");
+    let stats_path = stats_dir.path().join("stats.json");
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-hepha"))
+        .arg("hepha")
+        .arg("--manifest-path")
+        .arg(contracts_path.join("reentrancy/contract_twenty/Cargo.toml"))
+        .arg("--target-dir")
+        .arg(target_dir.path())
+        .env(
+            "HEPHA_FLAGS",
+            format!(
+                "--crate_analysis_timeout 0 --diag-format json --stats-json {}",
+                stats_path.display()
+            ),
+        )
+        .output()
+        .expect("could not run cargo hepha");
+    let stats = read_to_string(&stats_path).expect("no run summary was written");
+    let summary: RunSummary = serde_json::from_str(&stats).unwrap();
+    assert!(summary.truncated);
+    for line in String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| line.starts_with('{'))
+    {
+        let diagnostic: ExportedDiagnostic = serde_json::from_str(line).unwrap();
+        assert!(diagnostic.truncated, "{line}");
+    }
+
+    // The summaries written by earlier versions of HEPHA are complete
+    let earlier: RunSummary = serde_json::from_str(
+        r#"{"crate_name":"c","functions_analyzed":1,"functions_timed_out":0,"diagnostics":{},"wall_time_ms":5}"#,
+    )
+    .unwrap();
+    assert!(!earlier.truncated);
+}
+
+// Check that the properties of a finding are cut off at their maximum sizes, and marked as
+// truncated when they are. The properties of the findings of the corpus are checked by contracts().
+#[test]
+fn finding_properties() {
+    let cut_off = FindingProperties::new(
+        Some("x".repeat(MAX_RENDERED_CONDITION_LENGTH + 1)),
+        vec!["instruction_data".to_string(); MAX_PROPERTY_ENTRIES + 1],
+        Vec::new(),
+        Vec::new(),
+    );
+    assert!(cut_off.truncated);
+    assert_eq!(
+        cut_off.entry_condition.as_deref().map(str::len),
+        Some(MAX_RENDERED_CONDITION_LENGTH)
+    );
+    assert_eq!(cut_off.provenance.len(), MAX_PROPERTY_ENTRIES);
+    assert!(!FindingProperties::new(None, Vec::new(), Vec::new(), Vec::new()).truncated);
+}
+
+// Checks that the properties of an exported finding have exactly the fields of their schema, with
+// the types of the schema, and that their strings and lists are within their maximum sizes.
+fn check_finding_properties(code: &str, properties: &serde_json::Value) {
+    let fields = properties
+        .as_object()
+        .unwrap_or_else(|| panic!("{code}: the finding has no properties"));
+    let names: BTreeSet<&str> = fields.keys().map(String::as_str).collect();
+    assert_eq!(
+        names,
+        BTreeSet::from([
+            "assumption_ledger",
+            "assumptions",
+            "entry_condition",
+            "havocked_callees",
+            "provenance",
+            "schema",
+            "truncated",
+        ]),
+        "{code}"
+    );
+    assert_eq!(properties["schema"], FINDING_PROPERTIES_SCHEMA, "{code}");
+    assert!(properties["truncated"].is_boolean(), "{code}");
+    let entry_condition = &properties["entry_condition"];
+    assert!(
+        entry_condition.is_null()
+            || entry_condition
+                .as_str()
+                .is_some_and(|c| c.chars().count() <= MAX_RENDERED_CONDITION_LENGTH),
+        "{code}: {entry_condition}"
+    );
+    let classes = [
+        "instruction_data",
+        "account_data",
+        "program_owned_account_data",
+        "constant",
+        "computed",
+        "untrusted_input",
+        "clock",
+    ];
+    let entries = |list: &str| {
+        let entries = properties[list]
+            .as_array()
+            .unwrap_or_else(|| panic!("{code}: {list} is not a list"))
+            .clone();
+        assert!(entries.len() <= MAX_PROPERTY_ENTRIES, "{code}: {list}");
+        entries
+    };
+    assert!(
+        entries("provenance")
+            .iter()
+            .all(|class| class.as_str().is_some_and(|c| classes.contains(&c))),
+        "{code}: {properties}"
+    );
+    assert!(
+        entries("havocked_callees").iter().all(|c| c.is_string()),
+        "{code}: {properties}"
+    );
+    for list in ["assumptions", "assumption_ledger"] {
+        assert!(
+            entries(list).iter().all(|location| matches!(
+                location.as_array().map(Vec::as_slice),
+                Some([file, line, column])
+                    if file.is_string() && line.is_u64() && column.is_u64()
+            )),
+            "{code}: {properties}"
+        );
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that mutable borrows of the lamports of two accounts are reported unless the keys of the
+// accounts are known to differ, because the same account can be passed for both.
+
+use std::cell::{RefCell, RefMut};
+use std::rc::Rc;
+
+#[derive(PartialEq)]
+pub struct Pubkey {
+    pub bytes: [u8; 32],
+}
+
+pub struct AccountInfo<'a> {
+    pub key: &'a Pubkey,
+    pub lamports: Rc<RefCell<&'a mut u64>>,
+}
+
+impl<'a> AccountInfo<'a> {
+    pub fn try_borrow_mut_lamports(&self) -> Result<RefMut<&'a mut u64>, ProgramError> {
+        self.lamports
+            .try_borrow_mut()
+            .map_err(|_| ProgramError::AccountBorrowFailed)
+    }
+}
+
+pub enum ProgramError {
+    AccountBorrowFailed,
+    InvalidArgument,
+}
+
+pub fn borrow_both(from: &AccountInfo, to: &AccountInfo) -> Result<(), ProgramError> {
+    let _from_lamports = from.try_borrow_mut_lamports()?;
+    let _to_lamports = to.try_borrow_mut_lamports()?; //~ [HEPHA-ACCOUNT-ALIASING] accounts may alias; add a key inequality check
+    Ok(())
+}
+
+pub fn borrow_distinct(from: &AccountInfo, to: &AccountInfo) -> Result<(), ProgramError> {
+    if from.key == to.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let _from_lamports = from.try_borrow_mut_lamports()?;
+    let _to_lamports = to.try_borrow_mut_lamports()?;
+    Ok(())
+}
+
+pub fn main() {}
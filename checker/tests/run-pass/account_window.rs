@@ -0,0 +1,70 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that an entrypoint does not skip an account that it reads by index in another branch,
+// or that it requires the client to supply.
+
+// HEPHA_FLAGS --diag=default
+
+pub struct Pubkey {}
+
+pub struct AccountInfo {
+    pub is_signer: bool,
+    pub lamports: u64,
+}
+
+pub fn skipped_in_other_arm(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> u64 {
+    if data.is_empty() {
+        return 0;
+    }
+    match data[0] {
+        0 => {
+            if !accounts[0].is_signer {
+                return 0;
+            }
+            accounts.iter().skip(1).map(|a| a.lamports).sum()
+        }
+        _ => accounts.iter().skip(1).map(|a| a.lamports).sum(), //~ possible unchecked account at position 0, which is read in another branch but skipped by an iterator
+    }
+}
+
+pub enum ProgramError {
+    NotEnoughAccountKeys,
+}
+
+pub fn skipped_after_length_check(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> Result<u64, ProgramError> {
+    if accounts.len() < 3 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    Ok(accounts.iter().skip(1).take(2).map(|a| a.lamports).sum()) //~ possible unchecked account at position 0, which is counted by accounts.len() but skipped by an iterator
+}
+
+pub fn checked_before_skip(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> u64 {
+    if data.is_empty() || accounts.len() != 3 {
+        return 0;
+    }
+    if !accounts[0].is_signer {
+        return 0;
+    }
+    match data[0] {
+        0 => accounts.iter().skip(1).map(|a| a.lamports).sum(),
+        _ => accounts.iter().skip(1).take(1).map(|a| a.lamports).sum::<u64>() + accounts[2].lamports,
+    }
+}
+
+// Not an entrypoint, so the skip is not checked.
+pub fn helper(accounts: &[AccountInfo], data: &[u8]) -> u64 {
+    match data.first() {
+        Some(0) => accounts[0].lamports,
+        _ => accounts.iter().skip(1).map(|a| a.lamports).sum(),
+    }
+}
+
+pub fn main() {}
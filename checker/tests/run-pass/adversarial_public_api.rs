@@ -0,0 +1,44 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that the exported functions of a math library are analyzed as entry points whose callers
+// are obliged to meet the preconditions inferred for them.
+
+// HEPHA_FLAGS --diag=default --adversarial-public-api
+
+pub mod math {
+    fn ratio(numerator: u64, denominator: u64) -> u64 {
+        numerator / denominator //~ related location
+    }
+
+    pub fn average(total: u64, count: u64) -> u64 {
+        ratio(total, count) //~ caller obligation: attempt to divide by zero
+    }
+
+    pub fn share(total: u64, parts: u64) -> u64 {
+        if parts == 0 {
+            return 0;
+        }
+        ratio(total, parts)
+    }
+
+    pub const fn half(value: u64, divisor: u64) -> u64 {
+        value / divisor //~ caller obligation: attempt to divide by zero
+    }
+}
+
+mod internal {
+    // Public, but not exported from the crate, so not an entry point
+    pub fn per_item(total: u64, items: u64) -> u64 {
+        total / items
+    }
+}
+
+pub fn per_dozen(total: u64) -> u64 {
+    internal::per_item(total, 12)
+}
+
+pub fn main() {}
@@ -0,0 +1,84 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that arithmetic findings in an entrypoint tell whether the amounts come from the
+// instruction data or from the data of an account, and that findings on amounts read from an
+// account that is checked to be owned by the program are only notes.
+
+// HEPHA_FLAGS --diag=library
+
+#[derive(PartialEq)]
+pub struct Pubkey {
+    pub bytes: [u8; 32],
+}
+
+pub struct AccountInfo<'a> {
+    pub key: &'a Pubkey,
+    pub owner: &'a Pubkey,
+    pub data: &'a [u8],
+}
+
+impl<'a> AccountInfo<'a> {
+    pub fn try_borrow_data(&self) -> Result<&[u8], ProgramError> {
+        Ok(self.data)
+    }
+}
+
+pub enum ProgramError {
+    IncorrectProgramId,
+    NotEnoughAccountKeys,
+}
+
+pub fn read_amount(bytes: &[u8]) -> u64 {
+    let mut amount = 0;
+    for byte in bytes.iter().take(8) {
+        amount = (amount << 8) | *byte as u64;
+    }
+    amount
+}
+
+pub fn from_instruction_data(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> Result<u64, ProgramError> {
+    let amount = read_amount(instruction_data);
+    Ok(amount * 1000) //~ [HEPHA-ARITH-ABORT] possible attempt to multiply with overflow, which aborts execution
+    //~ the amount is read from the instruction data, which the caller controls
+}
+
+pub fn from_user_account( //~ in this entrypoint, which does not compare the owner of the account with the program id
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> Result<u64, ProgramError> {
+    let Some(user_account) = accounts.first() else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let data = user_account.try_borrow_data()?; //~ [HEPHA-OWNER-CHECK] possible fake account, whose data is read without checking that its owner is the program
+    let amount = read_amount(data);
+    Ok(amount * 1000) //~ [HEPHA-ARITH-ABORT] possible attempt to multiply with overflow, which aborts execution
+    //~ the amount is read from the data of `user_account`, whose owner is not checked, so the caller controls it
+}
+
+pub fn from_program_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> Result<u64, ProgramError> {
+    let Some(vault_account) = accounts.first() else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if vault_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let data = vault_account.try_borrow_data()?;
+    let amount = read_amount(data);
+    Ok(amount * 1000) //~ [HEPHA-ARITH-ABORT] possible attempt to multiply with overflow, which aborts execution
+    //~ the amount is read from the data of `vault_account`, which is checked to be owned by the program
+}
+
+pub fn main() {}
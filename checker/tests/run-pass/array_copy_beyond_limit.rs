@@ -0,0 +1,19 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the elements of a copied array are not tracked when the array has more
+// elements than are tracked by default. See max_elements_to_track.rs for the same copy with a
+// larger limit.
+
+use hepha_annotations::*;
+
+pub fn copy(state: &mut [u64; 150]) {
+    state[149] = 7;
+    let copy = *state;
+    verify!(copy[149] == 7); //~ possible false verification condition
+}
+
+pub fn main() {}
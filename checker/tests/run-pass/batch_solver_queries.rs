@@ -0,0 +1,38 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that conditions are decided the same way when the solver queries of a body are
+// discharged under assumptions in a single solver context.
+
+// HEPHA_FLAGS --batch-solver-queries
+
+use hepha_annotations::*;
+
+pub fn bounded_sum(x: u32, y: u32) -> u32 {
+    if x < 10 && y < 10 {
+        let z = x + y;
+        verify!(z < 20);
+        z
+    } else {
+        0
+    }
+}
+
+pub fn unbounded_sum(x: u32, y: u32) {
+    if x < 10 {
+        verify!(x + y < 20); //~ possible false verification condition
+    }
+}
+
+pub fn contradicting_branches(x: i32) {
+    if x > 5 {
+        if x < 3 {
+            verify!(false);
+        }
+    }
+}
+
+pub fn main() {}
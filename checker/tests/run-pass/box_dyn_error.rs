@@ -0,0 +1,36 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that converts errors into Box<dyn Error>, which erases their types, and checks that the
+// condition under which the error is returned is not lost
+
+use hepha_annotations::*;
+use std::error::Error;
+
+pub fn check_amount(amount: u64) -> Result<u64, Box<dyn Error>> {
+    if amount > 100 {
+        return Err("amount is too large".into());
+    }
+    Ok(amount)
+}
+
+pub fn checked_before_call(amount: u64) -> u64 {
+    if amount > 100 {
+        return 0;
+    }
+    let checked = check_amount(amount).unwrap();
+    verify!(checked == amount);
+    checked
+}
+
+pub fn is_too_large(amount: u64) -> bool {
+    check_amount(amount).is_err()
+}
+
+pub fn main() {
+    verify!(is_too_large(101));
+    verify!(!is_too_large(100));
+}
@@ -0,0 +1,44 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that analyzes the const fns that build tables for statics and array lengths, whose
+// findings are qualified as compile-time evaluation. A const fn that is only evaluated at compile
+// time has its findings reported as notes, since rustc already evaluated the constants that use it.
+
+// HEPHA_FLAGS --diag=library
+
+const fn scaled_entry(base: u8, scale: u8, at_runtime: bool) -> u8 {
+    if at_runtime {
+        base * scale //~ possible attempt to multiply with overflow, which aborts execution (in compile-time evaluation)
+    } else {
+        base
+    }
+}
+
+const fn build_table(scale: u8) -> [u8; 4] {
+    [
+        scaled_entry(1, scale, false),
+        scaled_entry(2, scale, false),
+        scaled_entry(3, scale, false),
+        scaled_entry(4, scale, false),
+    ]
+}
+
+pub static TABLE: [u8; 4] = build_table(100);
+
+const fn table_len(entries: usize, padding: usize) -> usize {
+    entries + padding //~ possible attempt to add with overflow, which aborts execution (in compile-time evaluation)
+}
+
+pub fn padded_table() -> [u8; table_len(4, 2)] {
+    [0; table_len(4, 2)]
+}
+
+pub fn runtime_len() -> usize {
+    table_len(4, 2)
+}
+
+pub fn main() {}
@@ -0,0 +1,25 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that --counterexamples notes the values of the variables of a condition that may be
+// false, for an underflow and for a verification condition. The conditions are false for a
+// single value, so that the model of the solver is known.
+
+// HEPHA_FLAGS --diag=paranoid --counterexamples -- -C overflow-checks=on
+
+use hepha_annotations::*;
+
+pub fn withdraw(balance: u64) -> u64 {
+    balance - 1 //~ [HEPHA-ARITH-ABORT] possible attempt to subtract with underflow, which aborts execution
+    //~ counterexample: balance = 0
+}
+
+pub fn check_limit(amount: u8) {
+    verify!(amount != 255); //~ possible false verification condition
+    //~ counterexample: amount = 255
+}
+
+pub fn main() {}
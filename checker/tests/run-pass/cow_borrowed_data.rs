@@ -0,0 +1,33 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that a guard on the data that a Cow borrows constrains a later read through the Cow
+// while it is borrowed, but not a read of the copy of the data that to_mut makes.
+
+use hepha_annotations::*;
+use std::borrow::Cow;
+
+pub fn read_borrowed(data: &[u8]) {
+    let cow: Cow<[u8]> = Cow::Borrowed(data);
+    if cow.len() < 2 || cow[0] != 1 {
+        return;
+    }
+    verify!(cow.len() >= 2);
+    verify!(cow[0] == 1);
+}
+
+pub fn read_after_to_mut(data: &[u8]) {
+    let mut cow: Cow<[u8]> = Cow::Borrowed(data);
+    if cow.len() < 2 || cow[0] != 1 {
+        return;
+    }
+    let _ = cow.to_mut();
+    if !cow.is_empty() {
+        verify!(cow[0] == 1); //~ possible false verification condition
+    }
+}
+
+pub fn main() {}
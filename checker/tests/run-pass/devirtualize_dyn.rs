@@ -0,0 +1,32 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that a call through a trait object whose concrete type is known in the caller is
+// resolved to the method of that type, so that the result of the method can be verified.
+
+use hepha_annotations::*;
+
+pub trait Storage {
+    fn get(&self) -> u64;
+}
+
+pub struct MemStore {
+    value: u64,
+}
+
+impl Storage for MemStore {
+    fn get(&self) -> u64 {
+        self.value + 1
+    }
+}
+
+pub fn read_through_reference() {
+    let store = MemStore { value: 5 };
+    let storage: &dyn Storage = &store;
+    verify!(storage.get() == 6);
+}
+
+pub fn main() {}
@@ -0,0 +1,34 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that verification conditions that are only proven thanks to an assume!, which a build
+// without cfg(hepha) does not have, are reported, while those proven by runtime checks are not.
+
+// HEPHA_FLAGS --dual-build
+
+use hepha_annotations::*;
+
+pub fn assumed_bound(x: u32) {
+    assume!(x < 20);
+    verify!(x < 20); //~ verification relies on assumption not present in shipped code
+}
+
+pub fn checked_bound(x: u32) {
+    if x >= 20 {
+        return;
+    }
+    verify!(x < 20);
+}
+
+pub fn redundant_assumption(x: u32) {
+    if x >= 20 {
+        return;
+    }
+    assume!(x < 20); //~ assumption is provably true and can be deleted
+    verify!(x < 20);
+}
+
+pub fn main() {}
@@ -0,0 +1,85 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that branches returning an error are recognized as guards by the structure of the MIR,
+// whether they are written out or expanded from helper macros like the ones of Anchor.
+// Only a check of accounts.len() that guards the return of an error requires the client to
+// supply accounts.
+
+// HEPHA_FLAGS --diag=default
+
+#[derive(PartialEq)]
+pub struct Pubkey {
+    pub bytes: [u8; 32],
+}
+
+pub struct AccountInfo {
+    pub key: Pubkey,
+    pub lamports: u64,
+}
+
+pub enum ProgramError {
+    NotEnoughAccountKeys,
+    InvalidArgument,
+}
+
+macro_rules! require {
+    ($condition:expr, $error:expr) => {
+        if !($condition) {
+            return Err($error.into());
+        }
+    };
+}
+
+macro_rules! require_keys_eq {
+    ($left:expr, $right:expr, $error:expr) => {
+        if $left != $right {
+            return Err($error.into());
+        }
+    };
+}
+
+pub fn required_by_if(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> Result<u64, ProgramError> {
+    if accounts.len() < 3 {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+    Ok(accounts.iter().skip(1).map(|a| a.lamports).sum()) //~ possible unchecked account at position 0, which is counted by accounts.len() but skipped by an iterator
+}
+
+pub fn required_by_macro(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> Result<u64, ProgramError> {
+    require!(accounts.len() >= 3, ProgramError::NotEnoughAccountKeys);
+    Ok(accounts.iter().skip(1).map(|a| a.lamports).sum()) //~ possible unchecked account at position 0, which is counted by accounts.len() but skipped by an iterator
+}
+
+pub fn checked_by_macros(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> Result<u64, ProgramError> {
+    require!(accounts.len() == 3, ProgramError::NotEnoughAccountKeys);
+    require_keys_eq!(accounts[0].key, *program_id, ProgramError::InvalidArgument);
+    Ok(accounts.iter().skip(1).map(|a| a.lamports).sum())
+}
+
+// The length is only used to pick a value, so it does not require any accounts.
+pub fn not_a_guard(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> Result<u64, ProgramError> {
+    let extra = if accounts.len() > 3 { 1 } else { 0 };
+    Ok(accounts.iter().skip(1).map(|a| a.lamports).sum::<u64>() + extra)
+}
+
+pub fn main() {}
@@ -0,0 +1,42 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that the summary of a function that writes different values on its Ok and Err returns
+// lets a caller that returns early on errors verify what holds after the Ok returns only.
+
+// HEPHA_FLAGS --factor-exits
+
+use hepha_annotations::*;
+
+pub enum ProgramError {
+    InvalidArgument,
+}
+
+fn set_counter(counter: &mut u64, amount: u64) -> Result<(), ProgramError> {
+    if amount > 100 {
+        *counter = u64::MAX;
+        return Err(ProgramError::InvalidArgument);
+    }
+    *counter = amount;
+    Ok(())
+}
+
+pub fn deposit(amount: u64) -> Result<u64, ProgramError> {
+    let mut counter = 0;
+    set_counter(&mut counter, amount)?;
+    verify!(counter <= 100);
+    Ok(counter)
+}
+
+pub fn reset(amount: u64) -> u64 {
+    let mut counter = 0;
+    if set_counter(&mut counter, amount).is_err() {
+        verify!(counter == u64::MAX);
+    }
+    counter
+}
+
+pub fn main() {}
@@ -0,0 +1,41 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that forall! states a condition of every element of an array, which is left to the SMT
+// solver, and that quantified preconditions and postconditions are carried by the summaries of
+// their functions, so that they say something about the elements at constant indices.
+
+use hepha_annotations::*;
+
+pub fn fill(buf: &mut [u8; 4]) {
+    let mut i = 0;
+    while i < 4 {
+        loop_invariant!(forall!(|j: usize| j < i => buf[j] == 1));
+        buf[i] = 1;
+        i += 1;
+    }
+    postcondition!(forall!(|j: usize| j < 4 => buf[j] == 1));
+}
+
+pub fn fill_all_but_last(buf: &mut [u8; 4]) {
+    let mut i = 0;
+    while i < 3 {
+        buf[i] = 1;
+        i += 1;
+    }
+    postcondition!(forall!(|j: usize| j < 4 => buf[j] == 1)); //~ possible unsatisfied postcondition
+}
+
+pub fn first_quotient(buf: &[u8; 4]) -> u8 {
+    precondition!(forall!(|j: usize| j < 4 => buf[j] > 0));
+    100 / buf[0]
+}
+
+pub fn main() {
+    let mut buf = [0u8; 4];
+    fill(&mut buf);
+    verify!(buf[2] == 1);
+}
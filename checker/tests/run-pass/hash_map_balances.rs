@@ -0,0 +1,40 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that the value that a HashMap associates with a key is found again by a later lookup
+// of the same key, and is only possibly associated with a different key.
+
+use hepha_annotations::*;
+use std::collections::HashMap;
+
+fn deposit(balances: &mut HashMap<u64, u64>, user: u64, amount: u64) {
+    balances.insert(user, amount);
+}
+
+pub fn withdraw_same_user(user: u64, amount: u64) {
+    let mut balances = HashMap::new();
+    deposit(&mut balances, user, amount);
+    if let Some(balance) = balances.get_mut(&user) {
+        verify!(*balance >= amount);
+    }
+}
+
+pub fn withdraw_other_user(user: u64, other: u64, amount: u64) {
+    let mut balances = HashMap::new();
+    deposit(&mut balances, user, amount);
+    if let Some(balance) = balances.get_mut(&other) {
+        verify!(*balance >= amount); //~ possible false verification condition
+    }
+}
+
+pub fn entry_of_same_user(user: u64, amount: u64) {
+    let mut balances = HashMap::new();
+    deposit(&mut balances, user, amount);
+    let balance = balances.entry(user).or_insert(0);
+    verify!(*balance == amount);
+}
+
+pub fn main() {}
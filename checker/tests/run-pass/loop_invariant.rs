@@ -0,0 +1,43 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that loop_invariant! keeps a relation between loop variables that widening loses, so that
+// an index that the relation bounds is not reported as possibly out of bounds, and that an
+// invariant that does not hold on entry to its loop is reported.
+
+use hepha_annotations::*;
+
+pub fn unannotated(buf: &mut [u8; 16]) {
+    let mut i = 0;
+    let mut j = 1;
+    while j < 16 {
+        buf[i] = 1; //~ possible index out of bounds
+        i += 1;
+        j += 1;
+    }
+}
+
+pub fn annotated(buf: &mut [u8; 16]) {
+    let mut i = 0;
+    let mut j = 1;
+    while j < 16 {
+        loop_invariant!(i < j);
+        buf[i] = 1;
+        i += 1;
+        j += 1;
+    }
+}
+
+pub fn not_established(n: usize) {
+    let mut i = 0;
+    while i < n {
+        //~ in the iterations of this loop
+        loop_invariant!(i > 0); //~ loop invariant possibly not preserved
+        i += 1;
+    }
+}
+
+pub fn main() {}
@@ -0,0 +1,39 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that the fields that a match on a reference to an enum binds by reference are the fields
+// of the matched value, so that a guard on such a field constrains the field for later matches.
+
+use hepha_annotations::*;
+
+pub enum State {
+    Uninitialized,
+    Initialized { owner: u64, balance: u64 },
+}
+
+pub fn withdraw(state: &State, signer: u64) -> u64 {
+    match state {
+        State::Initialized { owner, .. } if *owner == signer => {}
+        _ => return 0,
+    }
+    match state {
+        State::Initialized { owner, balance } => {
+            verify!(*owner == signer);
+            *balance
+        }
+        State::Uninitialized => 0,
+    }
+}
+
+pub fn main() {
+    let state = State::Initialized {
+        owner: 7,
+        balance: 10,
+    };
+    if let State::Initialized { owner, .. } = &state {
+        verify!(*owner == 7);
+    }
+}
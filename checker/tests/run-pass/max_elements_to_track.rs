@@ -0,0 +1,20 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the elements of a copied array are tracked when --max-elements-to-track
+// is larger than the array, unlike in array_copy_beyond_limit.rs.
+
+// HEPHA_FLAGS --max-elements-to-track 200
+
+use hepha_annotations::*;
+
+pub fn copy(state: &mut [u64; 150]) {
+    state[149] = 7;
+    let copy = *state;
+    verify!(copy[149] == 7);
+}
+
+pub fn main() {}
@@ -0,0 +1,35 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the summaries of two mutually recursive functions are computed as a
+// single fixed point. Every path through the pair of functions returns the balance that it is
+// given, which is only known once the summaries of both functions have stabilized, so the
+// verification condition holds.
+
+use hepha_annotations::*;
+
+fn deposit(steps: u32, balance: u64) -> u64 {
+    if steps == 0 {
+        balance
+    } else {
+        withdraw(steps - 1, balance)
+    }
+}
+
+fn withdraw(steps: u32, balance: u64) -> u64 {
+    if steps == 0 {
+        balance
+    } else {
+        deposit(steps - 1, balance)
+    }
+}
+
+pub fn settle(steps: u32, balance: u64) {
+    let result = deposit(steps, balance);
+    verify!(result == balance);
+}
+
+pub fn main() {}
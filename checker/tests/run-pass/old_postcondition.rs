@@ -0,0 +1,29 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that old! in a postcondition refers to the value that its argument had when the function
+// was entered, so that a postcondition relating the new balance to the old one is provable in the
+// function and refined against the arguments of its callers.
+
+use hepha_annotations::*;
+
+pub fn withdraw(balance: &mut u64, amount: u64) {
+    precondition!(*balance >= amount);
+    *balance -= amount;
+    postcondition!(*balance == old!(*balance) - amount);
+}
+
+pub fn withdraw_without_change(balance: &mut u64, amount: u64) {
+    precondition!(*balance >= amount);
+    *balance -= amount;
+    postcondition!(*balance == old!(*balance)); //~ possible unsatisfied postcondition
+}
+
+pub fn main() {
+    let mut balance = 10;
+    withdraw(&mut balance, 3);
+    verify!(balance == 7);
+}
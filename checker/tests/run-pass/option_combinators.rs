@@ -0,0 +1,43 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that the value of Some carries over through ok_or, the ? operator, copied, unwrap_or
+// and map, so that a value that is looked up in a HashMap is known to be the value that was
+// inserted for the same key.
+
+use hepha_annotations::*;
+use std::collections::HashMap;
+
+pub fn ok_or_then_try(user: u64, amount: u64) -> Result<(), u32> {
+    let mut balances = HashMap::new();
+    balances.insert(user, amount);
+    let balance = balances.get(&user).ok_or(1u32)?;
+    verify!(*balance == amount);
+    Ok(())
+}
+
+pub fn copied_then_try(user: u64, other: u64, amount: u64) -> Option<u64> {
+    let mut balances = HashMap::new();
+    balances.insert(user, amount);
+    let balance = balances.get(&other).copied()?;
+    verify!(balance == amount); //~ possible false verification condition
+    Some(balance)
+}
+
+pub fn copied_then_unwrap_or(user: u64, amount: u64) {
+    let mut balances = HashMap::new();
+    balances.insert(user, amount);
+    let balance = balances.get(&user).copied().unwrap_or(0);
+    verify!(balance == amount);
+}
+
+pub fn map_some(amount: u64) {
+    precondition!(amount < 100);
+    let doubled = Some(amount).map(|a| a * 2).unwrap_or(0);
+    verify!(doubled == amount * 2);
+}
+
+pub fn main() {}
@@ -0,0 +1,49 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that arithmetic overflow wraps around when overflow checks are disabled
+// See overflow_checks_on.rs for the same code with overflow checks enabled.
+
+// HEPHA_FLAGS --diag=paranoid -- -C overflow-checks=off
+
+use hepha_annotations::*;
+
+pub fn add(x: u8) -> u8 {
+    precondition!(x == 100);
+    let y = x + 200; //~ [HEPHA-ARITH-WRAP] possible attempt to add with overflow, which wraps around
+    verify!(y == 44);
+    y
+}
+
+pub fn subtract(x: u8) -> u8 {
+    precondition!(x == 10);
+    let y = x - 20; //~ [HEPHA-ARITH-WRAP] possible attempt to subtract with underflow, which wraps around
+    verify!(y == 246);
+    y
+}
+
+pub fn multiply(x: u8) -> u8 {
+    let y = x * 2; //~ [HEPHA-ARITH-WRAP] possible attempt to multiply with overflow, which wraps around
+    y
+}
+
+pub fn shift_left(x: u8, y: u32) -> u8 {
+    x << y //~ [HEPHA-ARITH-WRAP] possible attempt to shift left with overflow, which wraps around
+}
+
+pub fn shift_right(x: u8, y: u32) -> u8 {
+    x >> y //~ [HEPHA-ARITH-WRAP] possible attempt to shift right with overflow, which wraps around
+}
+
+pub fn negate(x: i8) -> i8 {
+    -x //~ [HEPHA-ARITH-WRAP] possible attempt to negate with overflow, which wraps around
+}
+
+pub fn wrapping_add(x: u8, y: u8) -> u8 {
+    x.wrapping_add(y)
+}
+
+pub fn main() {}
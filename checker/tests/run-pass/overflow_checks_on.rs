@@ -0,0 +1,45 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that arithmetic overflow aborts when overflow checks are enabled
+// See overflow_checks_off.rs for the same code with overflow checks disabled.
+
+// HEPHA_FLAGS --diag=paranoid -- -C overflow-checks=on
+
+use hepha_annotations::*;
+
+pub fn add(x: u8) -> u8 {
+    precondition!(x == 100);
+    let y = x + 200; //~ [HEPHA-ARITH-ABORT] possible attempt to add with overflow, which aborts execution
+    verify!(y == 44); //~ this is unreachable, mark it as such by using the verify_unreachable! macro
+    y
+}
+
+pub fn subtract(x: u8) -> u8 {
+    precondition!(x == 10);
+    let y = x - 20; //~ [HEPHA-ARITH-ABORT] possible attempt to subtract with underflow, which aborts execution
+    verify!(y == 246); //~ this is unreachable, mark it as such by using the verify_unreachable! macro
+    y
+}
+
+pub fn multiply(x: u8) -> u8 {
+    let y = x * 2; //~ [HEPHA-ARITH-ABORT] possible attempt to multiply with overflow, which aborts execution
+    y
+}
+
+pub fn shift_left(x: u8, y: u32) -> u8 {
+    x << y //~ [HEPHA-ARITH-ABORT] possible attempt to shift left with overflow, which aborts execution
+}
+
+pub fn shift_right(x: u8, y: u32) -> u8 {
+    x >> y //~ [HEPHA-ARITH-ABORT] possible attempt to shift right with overflow, which aborts execution
+}
+
+pub fn negate(x: i8) -> i8 {
+    -x //~ [HEPHA-ARITH-ABORT] possible attempt to negate with overflow, which aborts execution
+}
+
+pub fn main() {}
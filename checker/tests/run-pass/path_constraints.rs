@@ -0,0 +1,38 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that reports the path constraints of the blocks of functions with branches
+
+// HEPHA_FLAGS --path-constraints
+
+pub fn nested_branches(x: bool, y: bool) -> u32 {
+    let mut result = 0;
+    if x {
+        result = 1; //~ bb1 is entered under 1 path constraint
+        //~ bb1 requires x from the branch in bb0
+        if y {
+            result = 2; //~ bb2 is entered under 2 path constraints
+            //~ bb2 requires x from the branch in bb0
+            //~ bb2 requires y from the branch in bb1
+        }
+    }
+    result
+}
+
+pub fn repeated_condition(x: u32) -> u32 {
+    let mut result = 0;
+    if x > 10 {
+        result = 1; //~ bb1 is entered under 1 path constraint
+        //~ bb1 requires 10 < x from the branch in bb0
+    }
+    if x > 10 {
+        result = 2; //~ bb3 is entered under 1 path constraint
+        //~ bb3 requires 10 < x from the branch in bb2
+    }
+    result
+}
+
+pub fn main() {}
@@ -0,0 +1,43 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that the analysis of promoted constants, here arrays, leaves no state behind that
+// changes the findings of the bodies that contain them.
+
+// HEPHA_FLAGS --diag=default
+
+pub struct Pubkey {}
+
+pub struct AccountInfo {
+    pub is_signer: bool,
+    pub lamports: u64,
+}
+
+pub fn pay(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> u64 {
+    let fees: &[u64] = &[10, 20, 30];
+    if data.is_empty() {
+        return 0;
+    }
+    match data[0] {
+        0 => {
+            if !accounts[0].is_signer {
+                return 0;
+            }
+            accounts.iter().skip(1).map(|a| a.lamports / fees[0]).sum()
+        }
+        _ => accounts.iter().skip(1).map(|a| a.lamports).sum(), //~ possible unchecked account at position 0, which is read in another branch but skipped by an iterator
+    }
+}
+
+pub fn fee(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> u64 {
+    let fees: &[u64; 3] = &[10, 20, u64::MAX];
+    if data.is_empty() || accounts.is_empty() {
+        return 0;
+    }
+    accounts[0].lamports * fees[data[0] as usize % 3] //~ [HEPHA-ARITH-ABORT] possible attempt to multiply with overflow, which aborts execution
+}
+
+pub fn main() {}
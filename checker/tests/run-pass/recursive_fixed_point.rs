@@ -0,0 +1,27 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the summary of a recursive walk over balances is a fixed point.
+// With the summary of the first iteration, in which the recursive call returns bottom, the
+// walk returns the total it is given, so that the verification condition would be false.
+// Only the summary of the second iteration, which joins in the balances added by the
+// recursive call, makes the condition possibly false.
+
+use hepha_annotations::*;
+
+fn walk(balances: &[u64; 4], index: usize, total: u64) -> u64 {
+    if index >= 4 {
+        total
+    } else {
+        walk(balances, index + 1, total + balances[index])
+    }
+}
+
+pub fn main() {
+    let balances = [1, 2, 3, 4];
+    let total = walk(&balances, 0, 0);
+    verify!(total == 10); //~ possible false verification condition
+}
@@ -0,0 +1,54 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that computes offsets into account data with size_of, align_of and size_of_val, whose
+// values are compile time constants
+
+use hepha_annotations::*;
+use std::mem::{align_of, size_of, size_of_val};
+
+#[repr(C)]
+pub struct Header {
+    pub kind: u64,
+    pub len: u64,
+}
+
+pub fn first_payload_byte(data: &[u8]) -> u8 {
+    if data.len() <= size_of::<Header>() {
+        return 0;
+    }
+    data[size_of::<Header>()]
+}
+
+pub fn first_byte_after<T>(data: &[u8]) -> u8 {
+    if data.len() <= size_of::<T>() {
+        return 0;
+    }
+    data[size_of::<T>()]
+}
+
+pub fn slice_size(data: &[u64]) -> usize {
+    size_of_val(data)
+}
+
+pub fn same_size<T, U>() -> bool {
+    size_of::<T>() == size_of::<U>()
+}
+
+pub fn main() {
+    verify!(size_of::<Header>() == 16);
+    verify!(align_of::<Header>() == 8);
+    let header = Header { kind: 1, len: 2 };
+    verify!(size_of_val(&header) == 16);
+    let mut data = [0u8; 17];
+    data[16] = 7;
+    verify!(first_payload_byte(&data) == 7);
+    verify!(first_byte_after::<Header>(&data) == 7);
+    verify!(first_byte_after::<Header>(&data[1..]) == 0);
+    verify!(slice_size(&[1, 2, 3]) == 24);
+    verify!(!same_size::<u8, u64>());
+    verify!(same_size::<Header, u128>());
+}
@@ -0,0 +1,25 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that the SMT solver proves that a divisor is not zero, and that the subtraction that
+// computes it does not underflow, from a relation between two parameters that the abstract
+// domains do not track. The integration tests run it with every SMT backend that is built in.
+
+// HEPHA_FLAGS --diag=paranoid -- -C overflow-checks=on
+
+pub fn per_share(total: u64, shares: u64) -> u64 {
+    total / shares //~ possible attempt to divide by zero
+}
+
+pub fn per_excess(total: u64, supply: u64, reserve: u64) -> u64 {
+    if supply > reserve {
+        total / (supply - reserve)
+    } else {
+        0
+    }
+}
+
+pub fn main() {}
@@ -0,0 +1,24 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that a nonlinear condition that the solver cannot decide in the time allowed for a
+// query is left undecided, and that the analysis of the function still completes. The
+// integration tests check that the query is counted as timed out in the summary of the run.
+
+// HEPHA_FLAGS --diag=paranoid --smt-timeout-ms 1
+
+use hepha_annotations::*;
+
+fn cube(v: u64) -> u64 {
+    v.wrapping_mul(v).wrapping_mul(v)
+}
+
+pub fn sum_of_cubes(x: u64, y: u64, z: u64) {
+    assume!(x > 1 && y > 1 && z > 1);
+    verify!(cube(x).wrapping_add(cube(y)) != cube(z)); //~ possible false verification condition
+}
+
+pub fn main() {}
@@ -0,0 +1,29 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that suppress! drops the findings of the named checker in the function where it appears,
+// as well as those that its callers would report because of its summary, and that a name that is
+// not that of a checker is reported.
+
+// HEPHA_FLAGS --diag=library
+
+use hepha_annotations::*;
+
+pub fn scaled(amount: u64) -> u64 {
+    suppress!("arithmetic");
+    amount * 1000
+}
+
+pub fn scaled_twice(amount: u64) -> u64 {
+    scaled(scaled(amount))
+}
+
+pub fn misspelled(amount: u64) -> u64 {
+    suppress!("arithmetics"); //~ suppress! names no checker: `arithmetics`, see --list-checks for the names
+    amount * 1000 //~ [HEPHA-ARITH-ABORT] possible attempt to multiply with overflow, which aborts execution
+}
+
+pub fn main() {}
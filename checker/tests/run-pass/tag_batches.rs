@@ -0,0 +1,86 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// A test for several tags that a callee attaches to the same value, which the caller attaches
+// together, and which must end up where they would be if they were attached one after the other
+
+#![feature(generic_const_exprs)]
+#![allow(incomplete_features)]
+
+use hepha_annotations::*;
+
+struct SecretTaintKind<const MASK: TagPropagationSet> {}
+
+const SECRET_TAINT: TagPropagationSet = tag_propagation_set!(TagPropagation::SubComponent);
+
+type SecretTaint = SecretTaintKind<SECRET_TAINT>;
+
+struct ArrayTaintKind<const MASK: TagPropagationSet> {}
+
+const ARRAY_TAINT: TagPropagationSet =
+    tag_propagation_set!(TagPropagation::SubComponent, TagPropagation::Index);
+
+type ArrayTaint = ArrayTaintKind<ARRAY_TAINT>;
+
+struct RootTaintKind<const MASK: TagPropagationSet> {}
+
+const ROOT_TAINT: TagPropagationSet = tag_propagation_set!();
+
+type RootTaint = RootTaintKind<ROOT_TAINT>;
+
+pub struct Inner {
+    values: [i32; 2],
+}
+
+pub struct Foo {
+    content: i32,
+    inner: Inner,
+}
+
+fn taint(foo: &Foo) {
+    add_tag!(foo, SecretTaint);
+    add_tag!(foo, ArrayTaint);
+    add_tag!(foo, RootTaint);
+}
+
+pub fn test1() {
+    let foo = Foo {
+        content: 99991,
+        inner: Inner { values: [1, 2] },
+    };
+    taint(&foo);
+    verify!(has_tag!(&foo, SecretTaint));
+    verify!(has_tag!(&foo, ArrayTaint));
+    verify!(has_tag!(&foo, RootTaint));
+    verify!(has_tag!(&foo.content, SecretTaint));
+    verify!(does_not_have_tag!(&foo.content, ArrayTaint));
+    verify!(does_not_have_tag!(&foo.content, RootTaint));
+    verify!(has_tag!(&foo.inner, SecretTaint));
+    verify!(does_not_have_tag!(&foo.inner, RootTaint));
+    verify!(has_tag!(&foo.inner.values[1], SecretTaint));
+    verify!(does_not_have_tag!(&foo.inner.values[1], RootTaint));
+}
+
+pub fn test2() {
+    let foo = Foo {
+        content: 99991,
+        inner: Inner { values: [1, 2] },
+    };
+    add_tag!(&foo, SecretTaint);
+    add_tag!(&foo, ArrayTaint);
+    add_tag!(&foo, RootTaint);
+    verify!(has_tag!(&foo, SecretTaint));
+    verify!(has_tag!(&foo, ArrayTaint));
+    verify!(has_tag!(&foo, RootTaint));
+    verify!(has_tag!(&foo.content, SecretTaint));
+    verify!(does_not_have_tag!(&foo.content, ArrayTaint));
+    verify!(does_not_have_tag!(&foo.content, RootTaint));
+    verify!(has_tag!(&foo.inner, SecretTaint));
+    verify!(does_not_have_tag!(&foo.inner, RootTaint));
+    verify!(has_tag!(&foo.inner.values[1], SecretTaint));
+    verify!(does_not_have_tag!(&foo.inner.values[1], RootTaint));
+}
+
+pub fn main() {}
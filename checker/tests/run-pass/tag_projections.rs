@@ -0,0 +1,47 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// A test for tags that are propagated to sub-components through some kinds of projections only
+
+#![feature(generic_const_exprs)]
+#![allow(incomplete_features)]
+
+use hepha_annotations::*;
+
+struct SecretTaintKind<const MASK: TagPropagationSet> {}
+
+const SECRET_TAINT: TagPropagationSet = tag_propagation_set!(
+    TagPropagation::SubComponent,
+    TagPropagation::Deref,
+    TagPropagation::Index
+);
+
+type SecretTaint = SecretTaintKind<SECRET_TAINT>;
+
+pub struct Foo {
+    content: i32,
+}
+
+pub fn test1() {
+    let foo = Foo { content: 99991 };
+    add_tag!(&foo, SecretTaint);
+    verify!(has_tag!(&foo, SecretTaint));
+    verify!(does_not_have_tag!(&foo.content, SecretTaint));
+}
+
+pub fn test2() {
+    let foo = Foo { content: 99991 };
+    add_tag!(&foo, SecretTaint);
+    verify!(has_tag!(&foo.content, SecretTaint)); //~ provably false verification condition
+}
+
+pub fn test3() {
+    let arr = [1, 2, 3];
+    add_tag!(&arr, SecretTaint);
+    verify!(has_tag!(&arr, SecretTaint));
+    verify!(has_tag!(&arr[1], SecretTaint));
+}
+
+pub fn main() {}
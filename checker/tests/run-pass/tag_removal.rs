@@ -0,0 +1,50 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// A test for removing tags from values, e.g. after sanitizing them
+
+#![feature(generic_const_exprs)]
+#![allow(incomplete_features)]
+
+use hepha_annotations::*;
+
+struct SecretTaintKind<const MASK: TagPropagationSet> {}
+
+const SECRET_TAINT: TagPropagationSet = tag_propagation_set!(TagPropagation::SubComponent);
+
+type SecretTaint = SecretTaintKind<SECRET_TAINT>;
+
+pub struct Foo {
+    content: i32,
+}
+
+pub fn test1() {
+    let secret = 23333;
+    add_tag!(&secret, SecretTaint);
+    remove_tag!(&secret, SecretTaint);
+    verify!(does_not_have_tag!(&secret, SecretTaint));
+    add_tag!(&secret, SecretTaint);
+    verify!(has_tag!(&secret, SecretTaint));
+}
+
+pub fn test2() {
+    let foo = Foo { content: 99991 };
+    add_tag!(&foo, SecretTaint);
+    remove_tag!(&foo, SecretTaint);
+    verify!(does_not_have_tag!(&foo, SecretTaint));
+    verify!(does_not_have_tag!(&foo.content, SecretTaint));
+}
+
+pub fn test3(cond: bool) {
+    let secret = 23333;
+    add_tag!(&secret, SecretTaint);
+    if cond {
+        remove_tag!(&secret, SecretTaint);
+        verify!(does_not_have_tag!(&secret, SecretTaint));
+    }
+    verify!(does_not_have_tag!(&secret, SecretTaint)); //~ possible false verification condition
+}
+
+pub fn main() {}
@@ -0,0 +1,55 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that values derived from clock fields of different time units are not mixed
+
+// HEPHA_FLAGS --diag=default
+
+pub mod clock {
+    pub struct Clock {
+        pub slot: u64,
+        pub epoch_start_timestamp: i64,
+        pub epoch: u64,
+        pub leader_schedule_epoch: u64,
+        pub unix_timestamp: i64,
+    }
+}
+
+use crate::clock::Clock;
+
+pub fn add(clock: &Clock) -> u64 {
+    let slot = clock.slot;
+    let epoch = clock.epoch;
+    slot + epoch //~ possible mixing of time units (slots and epochs) for the smart contract
+}
+
+pub fn compare(clock: &Clock) -> bool {
+    let timestamp = clock.unix_timestamp;
+    let slot = clock.slot as i64;
+    timestamp < slot //~ possible mixing of time units (seconds and slots) for the smart contract
+}
+
+pub fn multiply(clock: &Clock) -> i64 {
+    let timestamp = clock.unix_timestamp;
+    let slot = clock.slot as i64;
+    timestamp * (slot + 250) //~ possible mixing of time units (seconds and slots) for the smart contract
+}
+
+pub fn square(clock: &Clock) -> i64 {
+    let timestamp = clock.unix_timestamp;
+    let start = clock.epoch_start_timestamp;
+    timestamp * start //~ possible product of two times (seconds times seconds) for the smart contract
+}
+
+pub fn same_unit(clock: &Clock) -> bool {
+    let timestamp = clock.unix_timestamp;
+    let start = clock.epoch_start_timestamp;
+    let epoch = clock.epoch;
+    let next_epoch = clock.leader_schedule_epoch;
+    timestamp - start > 60 && epoch + 1 < next_epoch && epoch / 2 == 10
+}
+
+pub fn main() {}
@@ -0,0 +1,39 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that the instruction data of an entrypoint, and the values read from it, carry the built-in
+// tag of untrusted input, which can be checked with has_tag! and the UntrustedInput type.
+
+use hepha_annotations::*;
+
+pub struct Pubkey {
+    pub bytes: [u8; 32],
+}
+
+pub struct AccountInfo<'a> {
+    pub key: &'a Pubkey,
+    pub data: &'a [u8],
+}
+
+pub enum ProgramError {
+    InvalidInstructionData,
+}
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> Result<(), ProgramError> {
+    if instruction_data.len() < 2 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    verify!(has_tag!(instruction_data, UntrustedInput));
+    let kind = instruction_data[1];
+    verify!(has_tag!(&kind, UntrustedInput));
+    Ok(())
+}
+
+pub fn main() {}
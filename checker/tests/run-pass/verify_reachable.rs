@@ -0,0 +1,33 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that verify_reachable! is reported when an assumption upstream of it is stronger than
+// intended and prunes the branch that leads to it, with a note at that assumption, and that it is
+// not reported when it can be reached.
+
+// HEPHA_FLAGS --diag=library
+
+use hepha_annotations::*;
+
+pub fn pruned(fee: u64) -> u64 {
+    assume!(fee == 3); //~ this assumption contradicts the condition of the branch to the statement
+    if fee != 3 {
+        verify_reachable!(); //~ annotated-reachable statement is unreachable under current assumptions
+        return 0;
+    }
+    fee
+}
+
+pub fn covered(fee: u64) -> u64 {
+    assume!(fee <= 3);
+    if fee < 3 {
+        verify_reachable!();
+        return 0;
+    }
+    fee
+}
+
+pub fn main() {}
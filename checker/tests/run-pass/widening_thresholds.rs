@@ -0,0 +1,27 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that widening with thresholds keeps the bound that the loop condition gives a counter, so
+// that an index derived from the counter after the loop is known to be in bounds, while a counter
+// with a bound that is not a constant still gets a possible index out of bounds.
+
+pub fn constant_bound(buf: &[u8; 32]) -> u8 {
+    let mut i = 0;
+    while i < 25 {
+        i += 1;
+    }
+    buf[i + 6]
+}
+
+pub fn parameter_bound(buf: &[u8; 32], n: usize) -> u8 {
+    let mut i = 0;
+    while i < n {
+        i += 1;
+    }
+    buf[i] //~ possible index out of bounds
+}
+
+pub fn main() {}
@@ -0,0 +1,38 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// The methods of a deposit contract, whose summaries are compared with deposit_contract.json
+
+pub struct DepositContract {
+    balance: u64,
+    deposits: u64,
+}
+
+impl DepositContract {
+    pub fn new() -> DepositContract {
+        DepositContract {
+            balance: 0,
+            deposits: 0,
+        }
+    }
+
+    pub fn balance(&self) -> u64 {
+        self.balance
+    }
+
+    pub fn deposit(&mut self, amount: u64) {
+        self.balance = self.balance.wrapping_add(amount);
+        self.deposits = self.deposits.wrapping_add(1);
+    }
+
+    pub fn withdraw(&mut self, amount: u64) -> bool {
+        if amount > self.balance {
+            return false;
+        }
+        self.balance = self.balance.wrapping_sub(amount);
+        true
+    }
+}
@@ -0,0 +1,39 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// Closes an escrow account by moving all of its lamports to the recipient. The data of the escrow
+// is left as it is, so a later instruction of the same transaction can fund the escrow again
+// before the runtime deletes it, and use it as if it had never been closed.
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let escrow_account = next_account_info(accounts_iter)?;
+    let recipient_account = next_account_info(accounts_iter)?;
+    if escrow_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !recipient_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    close(escrow_account, recipient_account)
+}
+
+pub fn close(escrow_account: &AccountInfo, recipient_account: &AccountInfo) -> ProgramResult {
+    let lamports = escrow_account.lamports();
+    **recipient_account.try_borrow_mut_lamports()? = recipient_account
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **escrow_account.try_borrow_mut_lamports()? = 0; //~ [HEPHA-ACCOUNT-CLOSE] possible revival of a closed account
+    Ok(())
+}
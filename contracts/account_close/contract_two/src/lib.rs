@@ -0,0 +1,39 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// The escrow of account_close/contract_one, whose data is filled with zeros when it is closed, so
+// that it cannot be used again even if it is funded again in the same transaction.
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let escrow_account = next_account_info(accounts_iter)?;
+    let recipient_account = next_account_info(accounts_iter)?;
+    if escrow_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !recipient_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    close(escrow_account, recipient_account)
+}
+
+pub fn close(escrow_account: &AccountInfo, recipient_account: &AccountInfo) -> ProgramResult {
+    let lamports = escrow_account.lamports();
+    **recipient_account.try_borrow_mut_lamports()? = recipient_account
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **escrow_account.try_borrow_mut_lamports()? = 0; //~! [HEPHA-ACCOUNT-CLOSE]
+    escrow_account.try_borrow_mut_data()?.fill(0);
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    // The authority signs the withdrawal, but nothing ties the vault to the authority, so the
+    // authority of one vault can withdraw the lamports of any other vault of the program.
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        if ctx.accounts.vault.lamports() < amount {
+            return err!(VaultError::InsufficientFunds);
+        }
+        **ctx.accounts.vault.try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.authority.try_borrow_mut_lamports()? += amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// CHECK: the vault is owned by the program, but nothing else about it is checked
+    #[account(mut)]
+    pub vault: UncheckedAccount<'info>, //~ [HEPHA-ACCOUNT-CONSTRAINT] possible missing constraint
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[error_code]
+pub enum VaultError {
+    #[msg("The vault holds fewer lamports than the amount")]
+    InsufficientFunds,
+}
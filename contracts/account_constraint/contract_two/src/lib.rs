@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    // The withdrawal of account_constraint/contract_one, where the configuration of the vault
+    // names both the vault and its authority, which Anchor checks before the handler runs.
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        if ctx.accounts.vault.lamports() < amount {
+            return err!(VaultError::InsufficientFunds);
+        }
+        **ctx.accounts.vault.try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.authority.try_borrow_mut_lamports()? += amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(has_one = vault, has_one = authority)]
+    pub config: Account<'info, Config>,
+    /// CHECK: the vault is the one that the configuration names
+    #[account(mut)]
+    pub vault: UncheckedAccount<'info>, //~! [HEPHA-ACCOUNT-CONSTRAINT]
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[account]
+pub struct Config {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[error_code]
+pub enum VaultError {
+    #[msg("The vault holds fewer lamports than the amount")]
+    InsufficientFunds,
+}
@@ -0,0 +1,49 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// The state of a vault, which is laid out in 16 bytes of account data followed by a bump seed.
+pub struct Vault {
+    pub balance: u64,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl Vault {
+    pub const LEN: usize = 16;
+
+    pub fn pack(&self) -> [u8; Vault::LEN] {
+        let mut bytes = [0u8; Vault::LEN];
+        bytes[0..8].copy_from_slice(&self.balance.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.updated_at.to_le_bytes());
+        bytes
+    }
+}
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault_account = next_account_info(accounts_iter)?;
+    // The check leaves out the byte of the bump seed.
+    if vault_account.data_len() < Vault::LEN {
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+    let vault = Vault {
+        balance: u64::from_le_bytes(instruction_data[0..8].try_into().unwrap()),
+        updated_at: 0,
+        bump: instruction_data[8],
+    };
+    let mut data = vault_account.data.borrow_mut();
+    data[..Vault::LEN].copy_from_slice(&vault.pack());
+    data[Vault::LEN] = vault.bump; //~ [HEPHA-ACCOUNT-DATA-LENGTH] possible write past the end of the data of an account
+    Ok(())
+}
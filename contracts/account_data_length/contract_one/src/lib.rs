@@ -0,0 +1,41 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// The state of a counter, which is laid out in 16 bytes of account data.
+pub struct Counter {
+    pub count: u64,
+    pub updated_at: i64,
+}
+
+impl Counter {
+    pub const LEN: usize = 16;
+}
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let counter_account = next_account_info(accounts_iter)?;
+    // The account is checked to hold 9 bytes, which is too small for the layout of the counter.
+    if counter_account.data_len() != 9 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let count = u64::from_le_bytes(instruction_data[0..8].try_into().unwrap());
+    let counter = Counter {
+        count,
+        updated_at: 0,
+    };
+    let mut data = counter_account.data.borrow_mut();
+    data[0..8].copy_from_slice(&counter.count.to_le_bytes());
+    data[8..Counter::LEN].copy_from_slice(&counter.updated_at.to_le_bytes()); //~ [HEPHA-ACCOUNT-DATA-LENGTH] possible write past the end of the data of an account
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// The state of a counter, which is laid out in 16 bytes of account data.
+pub struct Counter {
+    pub count: u64,
+    pub updated_at: i64,
+}
+
+impl Counter {
+    pub const LEN: usize = 16;
+
+    pub fn pack(&self) -> [u8; Counter::LEN] {
+        let mut bytes = [0u8; Counter::LEN];
+        bytes[0..8].copy_from_slice(&self.count.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.updated_at.to_le_bytes());
+        bytes
+    }
+}
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let counter_account = next_account_info(accounts_iter)?;
+    let count = u64::from_le_bytes(instruction_data[0..8].try_into().unwrap());
+    let counter = Counter {
+        count,
+        updated_at: 0,
+    };
+    // The length of the data of the account is never checked.
+    let mut data = counter_account.try_borrow_mut_data()?;
+    data[..Counter::LEN].copy_from_slice(&counter.pack()); //~ [HEPHA-ACCOUNT-DATA-LENGTH] possible write past the end of the data of an account whose length is not checked
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use solana_program::{
+    msg,
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// Accounts: [authority, vault, user]
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let instruction = instruction_data[0];
+    let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+    match instruction {
+        0 => {
+            let authority = &accounts[0];
+            if !authority.is_signer {
+                msg!("Authority must sign the deposit");
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            let accounts_iter = &mut accounts.iter().skip(1);
+            let vault = next_account_info(accounts_iter)?;
+            let user_account = next_account_info(accounts_iter)?;
+            **user_account.try_borrow_mut_lamports()? -= amount;
+            **vault.try_borrow_mut_lamports()? += amount;
+        }
+        1 => {
+            // The authority is supplied by the client, but skipped without being checked
+            let accounts_iter = &mut accounts.iter().skip(1); //~ possible unchecked account at position 0
+            let vault = next_account_info(accounts_iter)?;
+            let user_account = next_account_info(accounts_iter)?;
+            **vault.try_borrow_mut_lamports()? -= amount;
+            **user_account.try_borrow_mut_lamports()? += amount;
+        }
+        _ => {
+            msg!("Invalid action");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use solana_program::{
+    msg,
+    account_info::AccountInfo,
+    entrypoint,
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// Logs a checksum of the data of the accounts, mixed with every byte of the instruction data.
+// The analysis spends its time on the nested loops, which makes this a contract that times out
+// in the loops when the analysis of a function is given a tiny budget.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let mut checksum: u64 = 0;
+    for (round, byte) in instruction_data.iter().enumerate() {
+        for account in accounts {
+            let data = account.try_borrow_data()?;
+            for (index, value) in data.iter().enumerate() {
+                if index % 3 == round % 3 {
+                    checksum = checksum
+                        .wrapping_mul(31)
+                        .wrapping_add((*value ^ *byte) as u64);
+                }
+            }
+        }
+    }
+    msg!("Checksum: {}", checksum);
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    // The vault is an UncheckedAccount and no account of the instruction signs it, so anyone can
+    // withdraw the lamports of the vault to an account of their choosing.
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        if ctx.accounts.vault.lamports() < amount {
+            return err!(VaultError::InsufficientFunds);
+        }
+        **ctx.accounts.vault.try_borrow_mut_lamports()? -= amount; //~ [HEPHA-MISSING-SIGNER] possible missing signer check
+        **ctx.accounts.recipient.try_borrow_mut_lamports()? += amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// CHECK: the vault is owned by the program, but nothing else about it is checked
+    #[account(mut)]
+    pub vault: UncheckedAccount<'info>,
+    /// CHECK: any account can receive the lamports
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+}
+
+#[error_code]
+pub enum VaultError {
+    #[msg("The vault holds fewer lamports than the amount")]
+    InsufficientFunds,
+}
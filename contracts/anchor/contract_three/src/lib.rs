@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    // The withdrawal of anchor/contract_one, whose authority is an UncheckedAccount that the
+    // handler requires to sign, and to be the recipient of the lamports, with the require!
+    // macros of Anchor rather than with a Signer field.
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        require!(ctx.accounts.authority.is_signer, VaultError::Unauthorized);
+        require_keys_eq!(
+            ctx.accounts.recipient.key(),
+            ctx.accounts.authority.key(),
+            VaultError::Unauthorized
+        );
+        if ctx.accounts.vault.lamports() < amount {
+            return err!(VaultError::InsufficientFunds);
+        }
+        **ctx.accounts.vault.try_borrow_mut_lamports()? -= amount; //~! [HEPHA-MISSING-SIGNER]
+        **ctx.accounts.recipient.try_borrow_mut_lamports()? += amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// CHECK: the vault is owned by the program, but nothing else about it is checked
+    #[account(mut)]
+    pub vault: UncheckedAccount<'info>,
+    /// CHECK: the handler requires the authority to sign
+    pub authority: UncheckedAccount<'info>,
+    /// CHECK: the handler requires the recipient to be the authority
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+}
+
+#[error_code]
+pub enum VaultError {
+    #[msg("The vault holds fewer lamports than the amount")]
+    InsufficientFunds,
+    #[msg("The authority of the vault must sign and receive the lamports")]
+    Unauthorized,
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    // The withdrawal of anchor/contract_one, signed by the authority of the vault, which Anchor
+    // checks before the handler runs, and which receives the lamports.
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        if ctx.accounts.vault.lamports() < amount {
+            return err!(VaultError::InsufficientFunds);
+        }
+        **ctx.accounts.vault.try_borrow_mut_lamports()? -= amount; //~! [HEPHA-MISSING-SIGNER]
+        **ctx.accounts.authority.try_borrow_mut_lamports()? += amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// CHECK: the vault is owned by the program, but nothing else about it is checked
+    #[account(mut)]
+    pub vault: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[error_code]
+pub enum VaultError {
+    #[msg("The vault holds fewer lamports than the amount")]
+    InsufficientFunds,
+}
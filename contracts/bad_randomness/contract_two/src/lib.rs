@@ -63,8 +63,8 @@ pub fn announce_winner(
 ) -> Result<(), ProgramError> {
     let random_number = fastrand::u64(1..1000000);
 
-    if input_number == random_number {
-        **contract_account.try_borrow_mut_lamports()? -= 0;
+    if input_number == random_number { //~ [HEPHA-BAD-RANDOMNESS] possible bad randomness
+        **contract_account.try_borrow_mut_lamports()? -= 0; //~ [HEPHA-NO-OP-TRANSFER] transfer of zero lamports
         **user_account.try_borrow_mut_lamports()? += *contract_balance;
         *contract_balance = 0;
     }
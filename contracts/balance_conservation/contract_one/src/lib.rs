@@ -0,0 +1,87 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use std::collections::HashMap;
+
+entrypoint!(process_instruction);
+
+// The transfers of reentrancy/contract_two and withdraw_all of reentrancy/contract_five, with the
+// receiver credited by the amount that the user is debited, and the contract debited by the
+// balance that the user is credited.
+// Accounts: [user, receiver or contract]
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user_account = next_account_info(accounts_iter)?;
+    let external_account = next_account_info(accounts_iter)?;
+
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+    match instruction_data.first() {
+        Some(1) => {
+            let amount = instruction_data
+                .get(1..9)
+                .and_then(|bytes| bytes.try_into().ok())
+                .map(u64::from_le_bytes)
+                .ok_or(ProgramError::InvalidInstructionData)?;
+            msg!("User transfers {} lamports", amount);
+            transfer_lamports(&mut balances, *user_account.key, *external_account.key, amount)
+        }
+        Some(2) => {
+            msg!("User withdraws all lamports");
+            withdraw_all(&mut balances, user_account, external_account)
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+pub fn transfer_lamports(
+    balances: &mut HashMap<Pubkey, u64>,
+    user: Pubkey,
+    receiver: Pubkey,
+    amount: u64,
+) -> Result<(), ProgramError> {
+    // A transfer to the user itself would credit the amount without debiting it
+    if user == receiver {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let user_balance = balances.get(&user).ok_or(ProgramError::InvalidAccountData).copied()?;
+    let receiver_balance = balances.get(&receiver).ok_or(ProgramError::InvalidAccountData).copied()?;
+
+    if user_balance < amount {
+        msg!("Insufficient balance for transfer");
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    balances.insert(user, user_balance - amount);
+    balances.insert(receiver, receiver_balance + amount);
+    Ok(())
+}
+
+pub fn withdraw_all(
+    balances: &mut HashMap<Pubkey, u64>,
+    user_account: &AccountInfo,
+    contract_account: &AccountInfo,
+) -> Result<(), ProgramError> {
+    if !contract_account.is_signer {
+        msg!("Contract account must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let balance = balances.get_mut(user_account.key).ok_or(ProgramError::InvalidAccountData)?;
+    if contract_account.lamports() < *balance {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    **contract_account.try_borrow_mut_lamports()? -= *balance;
+    **user_account.try_borrow_mut_lamports()? += *balance;
+
+    *balance = 0;
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use solana_program::{
+    msg,
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use std::collections::HashMap;
+
+entrypoint!(process_instruction);
+
+// The deposit of overflow/contract_one, with checked arithmetic on the amount that the caller
+// passes in the instruction data, so that no balance can wrap around.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user_account = next_account_info(accounts_iter)?;
+    let contract_account = next_account_info(accounts_iter)?;
+
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+
+    if !user_account.is_signer {
+        msg!("User account must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let instruction = instruction_data[0];
+    let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+    match instruction {
+        0 => {
+            msg!("User deposits {} lamports", amount);
+            deposit(&mut balances, *user_account.key, amount, user_account, contract_account)?;
+        }
+        _ => {
+            msg!("Invalid action");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn deposit(
+    balances: &mut HashMap<Pubkey, u64>,
+    user: Pubkey,
+    amount: u64,
+    user_account: &AccountInfo,
+    contract_account: &AccountInfo
+) -> Result<(), ProgramError>  {
+    let entry = balances.entry(user).or_insert(0);
+    *entry = entry.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+
+    let user_lamports = user_account
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let contract_lamports = contract_account
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **user_account.try_borrow_mut_lamports()? = user_lamports;
+    **contract_account.try_borrow_mut_lamports()? = contract_lamports;
+
+    Ok(())
+}
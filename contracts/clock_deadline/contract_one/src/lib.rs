@@ -0,0 +1,52 @@
+use solana_program::{
+    msg,
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar}
+};
+
+entrypoint!(process_instruction);
+
+// An escrow that releases its lamports to the beneficiary once the deadline that the depositor
+// stored in the data of the escrow account has passed. The clock is only logged and compared with
+// that deadline.
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let beneficiary_account = next_account_info(accounts_iter)?;
+    let escrow_account = next_account_info(accounts_iter)?;
+
+    if !beneficiary_account.is_signer {
+        msg!("Beneficiary account must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if escrow_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let deadline = {
+        let data = escrow_account.try_borrow_data()?;
+        if data.len() < 8 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        i64::from_le_bytes(data[0..8].try_into().unwrap())
+    };
+
+    let clock = Clock::get()?;
+    msg!("Current timestamp: {}, slot: {}", clock.unix_timestamp, clock.slot);
+    if clock.unix_timestamp < deadline {
+        msg!("The escrow is locked until {}", deadline);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let amount = escrow_account.lamports();
+    **escrow_account.try_borrow_mut_lamports()? -= amount;
+    **beneficiary_account.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, msg,
+    program_error::ProgramError, pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub enum Instruction {
+    Deposit,
+    WithdrawAll,
+}
+
+// Deserializes the kind of the instruction from its first byte, but reads the amount from the
+// third byte on, so the second byte is never read and the amount is shifted by a byte.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.len() < 10 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let instruction = Instruction::try_from_slice(&instruction_data[..1])?;
+    match instruction {
+        Instruction::Deposit => {
+            let amount = u64::from_le_bytes(instruction_data[2..10].try_into().unwrap()); //~ [HEPHA-DESERIALIZATION-OFFSET] possible misaligned read of instruction data at offset 2: the Instruction that is deserialized before it occupies 1 byte
+            msg!("User deposits {} lamports", amount);
+        }
+        Instruction::WithdrawAll => {
+            msg!("User withdraws all lamports");
+        }
+    }
+    Ok(())
+}
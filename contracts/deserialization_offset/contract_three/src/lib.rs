@@ -0,0 +1,33 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, msg,
+    program_error::ProgramError, pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Header {
+    pub version: u8,
+    pub kind: u16,
+}
+
+// The header occupies three bytes, and the amount is read right after it.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.len() < 11 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let header = Header::try_from_slice(&instruction_data[..3])?;
+    let amount = u64::from_le_bytes(instruction_data[3..11].try_into().unwrap());
+    msg!(
+        "Instruction {} of version {} with {} lamports",
+        header.kind,
+        header.version,
+        amount
+    );
+    Ok(())
+}
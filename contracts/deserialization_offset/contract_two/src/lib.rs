@@ -0,0 +1,36 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, msg,
+    program_error::ProgramError, pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// Deposit has gained a memo, so the instruction no longer fits in the first byte, but the amount
+// is still read from the second byte on.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub enum Instruction {
+    Deposit { memo: u8 },
+    WithdrawAll,
+}
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.len() < 9 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let instruction = Instruction::try_from_slice(&[instruction_data[0]])?;
+    match instruction {
+        Instruction::Deposit { memo } => {
+            let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap()); //~ [HEPHA-DESERIALIZATION-OFFSET] possible misaligned read of instruction data at offset 1: Instruction has no fixed serialized size
+            msg!("User deposits {} lamports with memo {}", amount, memo);
+        }
+        Instruction::WithdrawAll => {
+            msg!("User withdraws all lamports");
+        }
+    }
+    Ok(())
+}
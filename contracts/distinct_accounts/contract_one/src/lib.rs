@@ -0,0 +1,66 @@
+use solana_program::{
+    msg,
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use std::collections::HashMap;
+
+entrypoint!(process_instruction);
+
+// Deposits the lamports of the user into the contract account and credits the balance of the
+// user. The entrypoint rejects the same account passed as both the user and the contract account
+// before the deposit, which would credit the balance without moving any lamports.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user_account = next_account_info(accounts_iter)?;
+    let contract_account = next_account_info(accounts_iter)?;
+
+    if user_account.key == contract_account.key {
+        msg!("The user account cannot be the contract account");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+    let amount = u64::from_le_bytes(instruction_data[0..8].try_into().unwrap());
+    transfer(&mut balances, amount, user_account, contract_account)?;
+    Ok(())
+}
+
+pub fn transfer(
+    balances: &mut HashMap<Pubkey, u64>,
+    amount: u64,
+    user_account: &AccountInfo,
+    contract_account: &AccountInfo,
+) -> Result<(), ProgramError> {
+    msg!("User deposits {} lamports", amount);
+    deposit(balances, amount, user_account, contract_account)
+}
+
+pub fn deposit(
+    balances: &mut HashMap<Pubkey, u64>,
+    amount: u64,
+    user_account: &AccountInfo,
+    contract_account: &AccountInfo,
+) -> Result<(), ProgramError> {
+    if !user_account.is_signer {
+        msg!("User account must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if user_account.lamports() < amount {
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    let entry = balances.entry(*user_account.key).or_insert(0);
+    *entry += amount;
+
+    **user_account.try_borrow_mut_lamports()? -= amount;
+    **contract_account.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
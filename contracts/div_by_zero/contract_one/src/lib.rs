@@ -0,0 +1,33 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// Computes the payout of a holder of a pool, which is the share of the lamports of the pool for
+// each of the shares that are given in the instruction data, times the shares of the holder. A
+// caller that passes zero shares makes the program panic.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let pool_account = next_account_info(accounts_iter)?;
+
+    if instruction_data.len() < 16 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let total_shares = u64::from_le_bytes(instruction_data[0..8].try_into().unwrap());
+    let holder_shares = u64::from_le_bytes(instruction_data[8..16].try_into().unwrap());
+
+    let pool_lamports = pool_account.lamports();
+    let payout = pool_lamports / total_shares * holder_shares; //~ [HEPHA-DIV-BY-ZERO] possible division by zero (panics in BPF)
+    msg!("The payout of the holder is {} lamports", payout);
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// Computes the price of an order of the lots of a market, which is the lamports of the market for
+// each of the lots that are given in the instruction data, times the lots of the order. The
+// quotient is multiplied, but a caller that passes zero lots makes the program panic, which is
+// the finding at the division, rather than the precision that the quotient loses.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let market_account = next_account_info(accounts_iter)?;
+
+    if instruction_data.len() < 16 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let market_lots = u64::from_le_bytes(instruction_data[0..8].try_into().unwrap());
+    let order_lots = u64::from_le_bytes(instruction_data[8..16].try_into().unwrap()); //~! [HEPHA-NUMERICAL-PRECISION]
+
+    let market_lamports = market_account.lamports();
+    let price = market_lamports / market_lots * order_lots; //~ [HEPHA-DIV-BY-ZERO] possible division by zero (panics in BPF)
+    msg!("The price of the order is {} lamports", price);
+    Ok(())
+}
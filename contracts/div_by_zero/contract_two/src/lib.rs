@@ -0,0 +1,42 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// Computes the payout of div_by_zero/contract_one and the dust that is left in the pool, with
+// checked_rem for the dust and a check that rejects zero shares before the division.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let pool_account = next_account_info(accounts_iter)?;
+
+    if instruction_data.len() < 16 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let total_shares = u64::from_le_bytes(instruction_data[0..8].try_into().unwrap());
+    let holder_shares = u64::from_le_bytes(instruction_data[8..16].try_into().unwrap());
+
+    let pool_lamports = pool_account.lamports();
+    let dust = pool_lamports
+        .checked_rem(total_shares)
+        .ok_or(ProgramError::InvalidArgument)?;
+    if total_shares == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let payout = pool_lamports * holder_shares / total_shares;
+    msg!(
+        "The payout of the holder is {} lamports, {} are left",
+        payout,
+        dust
+    );
+    Ok(())
+}
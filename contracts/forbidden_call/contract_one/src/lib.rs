@@ -0,0 +1,20 @@
+use solana_program::{
+    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, msg, pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// The entrypoint gives up on an empty instruction by exiting the process, which programs cannot
+// do in the virtual machine that they run in, rather than by returning an error.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.is_empty() {
+        msg!("Empty instruction");
+        std::process::exit(1); //~ [HEPHA-FORBIDDEN-CALL] call of std::process::exit
+    }
+    msg!("Instruction {}", instruction_data[0]);
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use solana_program::{
+    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, msg,
+    program_error::ProgramError, pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// Reads the kind of the instruction and the amount that follows it. Only the kind is checked to be
+// in the instruction data, so instruction data of fewer than 9 bytes aborts the program when the
+// amount is read.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.len() < 1 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let kind = instruction_data[0];
+    let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap()); //~ [HEPHA-INSTRUCTION-DATA-BOUNDS] possible index out of bounds: instruction data may be shorter than 9 bytes
+    msg!("Instruction {} with {} lamports", kind, amount);
+    Ok(())
+}
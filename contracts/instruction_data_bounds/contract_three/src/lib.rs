@@ -0,0 +1,17 @@
+use solana_program::{
+    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, msg, pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// Reads the amount from an inclusive range of the instruction data, whose length is not checked,
+// so instruction data of fewer than 9 bytes aborts the program.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let amount = u64::from_le_bytes(instruction_data[1..=8].try_into().unwrap()); //~ [HEPHA-INSTRUCTION-DATA-BOUNDS] possible index out of bounds: instruction data may be shorter than 9 bytes
+    msg!("Amount of {} lamports", amount);
+    Ok(())
+}
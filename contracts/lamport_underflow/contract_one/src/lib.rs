@@ -0,0 +1,68 @@
+use solana_program::{
+    msg,
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use std::collections::HashMap;
+
+entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+
+    let mut values: HashMap<Pubkey, u64> = HashMap::new();
+
+    if !user_account.is_signer {
+        msg!("User account must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let instruction = instruction_data[0];
+    let data = user_account.try_borrow_data()?;
+    let sample_number = u64::from_le_bytes(data[..8].try_into().unwrap());
+    drop(data);
+    match instruction {
+        0 => {
+            substract(&mut values, *user_account.key, sample_number)?;
+        }
+        1 => {
+            if user_account.lamports() < sample_number {
+                msg!("Insufficient lamports");
+                return Err(ProgramError::InsufficientFunds);
+            }
+            **user_account.try_borrow_mut_lamports()? -= sample_number;
+            **vault_account.try_borrow_mut_lamports()? += sample_number;
+        }
+        _ => {
+            msg!("Invalid action");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn substract(
+    values: &mut HashMap<Pubkey, u64>,
+    user: Pubkey,
+    sample_number: u64,
+) -> Result<(), ProgramError> {
+    let amount = sample_number.checked_mul(50).ok_or(ProgramError::ArithmeticOverflow)?;
+    let entry = values.entry(user).or_insert(0);
+    if *entry < amount {
+        msg!("Insufficient balance");
+        return Err(ProgramError::InsufficientFunds);
+    }
+    *entry -= amount;
+
+    Ok(())
+}
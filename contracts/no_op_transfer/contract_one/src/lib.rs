@@ -0,0 +1,39 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction::transfer,
+};
+
+entrypoint!(process_instruction);
+
+// Pays the tip that the payer asks for into the treasury. The key of the payer is passed twice,
+// so the tip is transferred from the payer to itself and the treasury never receives it.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let payer_account = next_account_info(accounts_iter)?;
+    let treasury_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    let tip = instruction_data
+        .get(0..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let instruction = transfer(payer_account.key, payer_account.key, tip); //~ [HEPHA-NO-OP-TRANSFER] transfer of lamports from an account to itself
+    invoke(
+        &instruction,
+        &[
+            payer_account.clone(),
+            treasury_account.clone(),
+            system_program.clone(),
+        ],
+    )
+}
@@ -0,0 +1,42 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction::transfer,
+};
+
+entrypoint!(process_instruction);
+
+// Settles the fee that the payer owes to the treasury. The branch for a waived fee was meant to
+// skip the transfer, but it makes it anyway, with an amount that is always zero there.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let payer_account = next_account_info(accounts_iter)?;
+    let treasury_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    let fee = instruction_data
+        .get(0..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    if fee == 0 {
+        let instruction = transfer(payer_account.key, treasury_account.key, fee); //~ [HEPHA-NO-OP-TRANSFER] transfer of zero lamports
+        invoke(
+            &instruction,
+            &[
+                payer_account.clone(),
+                treasury_account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use std::collections::HashMap;
+
+entrypoint!(process_instruction);
+
+// The balances are read from the data of the contract account at the start of every instruction
+// and written back to it at the end, so that withdrawals see the deposits of earlier instructions.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user_account = next_account_info(accounts_iter)?;
+    let contract_account = next_account_info(accounts_iter)?;
+
+    let mut balances: HashMap<[u8; 32], u64> =
+        HashMap::try_from_slice(&contract_account.data.borrow())?;
+    if instruction_data.len() < 9 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let instruction = instruction_data[0];
+    let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+    match instruction {
+        0 => {
+            msg!("User deposits {} lamports", amount);
+            deposit(&mut balances, amount, user_account, contract_account)?;
+        }
+        1 => {
+            msg!("User withdraws {} lamports", amount);
+            withdraw(&mut balances, amount, user_account, contract_account)?;
+        }
+        _ => {
+            msg!("Invalid action");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+    }
+
+    balances.serialize(&mut &mut contract_account.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+pub fn deposit(
+    balances: &mut HashMap<[u8; 32], u64>,
+    amount: u64,
+    user_account: &AccountInfo,
+    contract_account: &AccountInfo,
+) -> ProgramResult {
+    if !user_account.is_signer {
+        msg!("User account must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let balance = balances.entry(user_account.key.to_bytes()).or_insert(0);
+    *balance = balance
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    **user_account.try_borrow_mut_lamports()? -= amount;
+    **contract_account.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+pub fn withdraw(
+    balances: &mut HashMap<[u8; 32], u64>,
+    amount: u64,
+    user_account: &AccountInfo,
+    contract_account: &AccountInfo,
+) -> ProgramResult {
+    if !user_account.is_signer {
+        msg!("User account must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let balance = balances
+        .get_mut(&user_account.key.to_bytes())
+        .ok_or(ProgramError::InvalidAccountData)?;
+    if *balance < amount {
+        msg!("Insufficient balance for withdrawal");
+        return Err(ProgramError::InsufficientFunds);
+    }
+    *balance -= amount;
+
+    **contract_account.try_borrow_mut_lamports()? -= amount;
+    **user_account.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
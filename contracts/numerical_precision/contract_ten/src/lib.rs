@@ -19,7 +19,7 @@ pub fn process_instruction(
     let user_account = next_account_info(accounts_iter)?;
     let contract_account = next_account_info(accounts_iter)?;
 
-    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new(); //~ [HEPHA-NON-PERSISTENT-STATE] possible loss of state: balances is created by the instruction rather than read from the data of an account
 
     if !user_account.is_signer {
         msg!("User account must sign the transaction");
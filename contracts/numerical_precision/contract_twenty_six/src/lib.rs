@@ -0,0 +1,47 @@
+use solana_program::{
+    msg,
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction::transfer,
+    sysvar::{clock::Clock, Sysvar}
+};
+
+entrypoint!(process_instruction);
+
+// The float arithmetic of numerical_precision/contract_seventeen, whose result is routed into a
+// transfer of lamports from the vault to the user rather than kept in a local.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    let values = vec![15.0, 20.0, 35.0, 40.0, 65.0];
+
+    if !user_account.is_signer {
+        msg!("User account must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let clock = Clock::get()?;
+    let block_timestamp = clock.unix_timestamp as f64;
+    let random_number = (block_timestamp / (60 as f64)).round();
+
+    let reward = (values[3] * random_number) as u64;
+    invoke(
+        &transfer(vault_account.key, user_account.key, reward), //~ [HEPHA-FLOAT-MONEY] possible loss of precision in an amount
+        &[
+            vault_account.clone(),
+            user_account.clone(),
+            system_program.clone(),
+        ],
+    )
+}
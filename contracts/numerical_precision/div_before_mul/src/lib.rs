@@ -0,0 +1,68 @@
+use solana_program::{
+    msg,
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+const BASIS_POINTS: u64 = 10_000;
+const FEE_BPS: u64 = 30;
+const INTEREST_BPS_PER_DAY: u64 = 7;
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+
+    if !user_account.is_signer {
+        msg!("User account must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if instruction_data.len() < 9 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+    let fee = match instruction_data[0] {
+        0 => truncated_fee(amount),
+        1 => truncated_interest(amount, 30)?,
+        2 => fee(amount)?,
+        _ => return Err(ProgramError::InvalidInstructionData),
+    };
+    msg!("Fee of {} lamports", fee);
+
+    **user_account.try_borrow_mut_lamports()? -= fee;
+    **vault_account.try_borrow_mut_lamports()? += fee;
+    Ok(())
+}
+
+// Dividing first truncates the amount to whole multiples of 10_000 lamports, so that any amount
+// below 10_000 lamports pays no fee at all
+pub fn truncated_fee(amount: u64) -> u64 {
+    amount / BASIS_POINTS * FEE_BPS //~ [HEPHA-NUMERICAL-PRECISION] the result of an integer division is multiplied
+}
+
+// The checked arithmetic does not help, the remainder of the daily rate is lost for every day
+pub fn truncated_interest(amount: u64, days: u64) -> Result<u64, ProgramError> {
+    let daily = amount
+        .checked_div(BASIS_POINTS)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let interest = daily.checked_mul(INTEREST_BPS_PER_DAY * days); //~ [HEPHA-NUMERICAL-PRECISION] the result of an integer division is multiplied
+    interest.ok_or(ProgramError::ArithmeticOverflow)
+}
+
+// Multiplying first keeps the precision, the overflow of the product is checked instead
+pub fn fee(amount: u64) -> Result<u64, ProgramError> {
+    amount
+        .checked_mul(FEE_BPS)
+        .ok_or(ProgramError::ArithmeticOverflow)?
+        .checked_div(BASIS_POINTS)
+        .ok_or(ProgramError::ArithmeticOverflow)
+}
@@ -26,7 +26,7 @@ pub fn process_instruction(
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    let instruction = instruction_data[0];
+    let instruction = instruction_data[0]; //~ [HEPHA-INSTRUCTION-DATA-BOUNDS] possible index out of bounds: instruction data may be shorter than 1 byte
     let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
     match instruction {
         0 => {
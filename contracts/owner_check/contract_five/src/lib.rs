@@ -0,0 +1,72 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// Returns the error unless the condition holds, like the require! macro of Anchor.
+macro_rules! require {
+    ($condition:expr, $error:expr) => {
+        if !($condition) {
+            return Err($error.into());
+        }
+    };
+}
+
+// Returns the error unless the keys are equal, like the require_keys_eq! macro of Anchor.
+macro_rules! require_keys_eq {
+    ($left:expr, $right:expr, $error:expr) => {
+        if $left != $right {
+            return Err($error.into());
+        }
+    };
+}
+
+// The state of a vault, laid out as its authority and its balance.
+pub struct Vault {
+    pub authority: Pubkey,
+    pub balance: u64,
+}
+
+impl Vault {
+    pub const LEN: usize = 40;
+
+    pub fn unpack(src: &[u8]) -> Result<Vault, ProgramError> {
+        require!(src.len() >= Vault::LEN, ProgramError::InvalidAccountData);
+        let authority =
+            Pubkey::try_from(&src[0..32]).map_err(|_| ProgramError::InvalidAccountData)?;
+        let balance = u64::from_le_bytes(src[32..40].try_into().unwrap());
+        Ok(Vault { authority, balance })
+    }
+}
+
+// The withdrawal of owner_check/contract_two, whose checks are written with macros that return
+// the error unless the condition holds.
+// Accounts: [vault, authority]
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    // Only the program can write the data of an account that it owns
+    require_keys_eq!(
+        vault_account.owner,
+        program_id,
+        ProgramError::IncorrectProgramId
+    );
+    let vault = Vault::unpack(&vault_account.try_borrow_data()?)?; //~! [HEPHA-OWNER-CHECK]
+    require!(authority.is_signer, ProgramError::MissingRequiredSignature);
+    require_keys_eq!(
+        vault.authority,
+        *authority.key,
+        ProgramError::MissingRequiredSignature
+    );
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// The state of a vault, laid out as its authority and its balance.
+pub struct Vault {
+    pub authority: Pubkey,
+    pub balance: u64,
+}
+
+impl Vault {
+    pub const LEN: usize = 40;
+
+    pub fn unpack(src: &[u8]) -> Result<Vault, ProgramError> {
+        if src.len() < Vault::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let authority =
+            Pubkey::try_from(&src[0..32]).map_err(|_| ProgramError::InvalidAccountData)?;
+        let balance = u64::from_le_bytes(src[32..40].try_into().unwrap());
+        Ok(Vault { authority, balance })
+    }
+}
+
+// Withdraws from the vault, which is trusted to hold the authority that may withdraw.
+// Accounts: [vault, authority]
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    // Anyone can create an account with the same layout and pass it as the vault
+    let vault = Vault::unpack(&vault_account.try_borrow_data()?)?; //~ [HEPHA-OWNER-CHECK] possible fake account, whose data is read without checking that its owner is the program
+    if !authority.is_signer || vault.authority != *authority.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use hepha_annotations::solana::{checked_transfer, require_owner, require_signer};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// The state of a vault, laid out as its authority and its balance.
+pub struct Vault {
+    pub authority: Pubkey,
+    pub balance: u64,
+}
+
+impl Vault {
+    pub const LEN: usize = 40;
+
+    pub fn unpack(src: &[u8]) -> Result<Vault, ProgramError> {
+        if src.len() < Vault::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let authority =
+            Pubkey::try_from(&src[0..32]).map_err(|_| ProgramError::InvalidAccountData)?;
+        let balance = u64::from_le_bytes(src[32..40].try_into().unwrap());
+        Ok(Vault { authority, balance })
+    }
+}
+
+// The withdrawal of owner_check/contract_one, with the checks and the transfer done by the
+// wrappers of hepha_annotations::solana.
+// Accounts: [vault, authority]
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    require_owner(vault_account, program_id)?;
+    require_signer(authority)?;
+    let vault = Vault::unpack(&vault_account.try_borrow_data()?)?;
+    if vault.authority != *authority.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let amount: [u8; 8] = instruction_data
+        .get(..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    checked_transfer(vault_account, authority, u64::from_le_bytes(amount))
+}
@@ -59,7 +59,7 @@ pub fn deposit(balances: &mut HashMap<Pubkey, u64>, accounts: &[AccountInfo], am
     let entry = balances.entry(*user_account.key).or_insert(0);
     *entry += amount;
 
-    instruction::transfer(user_account.key, contract_account.key, amount);
+    instruction::transfer(user_account.key, contract_account.key, amount); //~ [HEPHA-UNINVOKED-INSTRUCTION] possible transfer that never happens
     Ok(())
 }
 
@@ -74,7 +74,7 @@ pub fn withdraw_all(balances: &mut HashMap<Pubkey, u64>, accounts: &[AccountInfo
     }
 
     let balance = balances.get_mut(user_account.key).ok_or(ProgramError::InvalidAccountData)?;
-    instruction::transfer(contract_account.key, user_account.key, *balance);
+    instruction::transfer(contract_account.key, user_account.key, *balance); //~ [HEPHA-UNINVOKED-INSTRUCTION] possible transfer that never happens
 
     *balance = 0;
     Ok(())
@@ -25,7 +25,7 @@ pub fn process_instruction(
         0 => {
             let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
             msg!("User deposits {} lamports", amount);
-            deposit(&mut balances, amount, user_account, contract_account)?;
+            deposit(&mut balances, amount, user_account, contract_account)?; //~ [HEPHA-DISTINCT-ACCOUNTS]
         }
         1 => {
             withdraw_all(&mut balances, user_account, contract_account)?;
@@ -61,7 +61,7 @@ pub fn deposit(
     *entry += amount;
     
     **user_account.try_borrow_mut_lamports()? -= amount;
-    **contract_account.try_borrow_mut_lamports()? += amount;
+    **contract_account.try_borrow_mut_lamports()? += amount; //~ [HEPHA-ACCOUNT-ALIASING] accounts may alias; add a key inequality check
     Ok(())
 }
 
@@ -77,8 +77,8 @@ pub fn withdraw_all(
 
     let balance = balances.get_mut(user_account.key).ok_or(ProgramError::InvalidAccountData)?;
 
-    **contract_account.try_borrow_mut_lamports()? = 0;
-    **user_account.try_borrow_mut_lamports()? += *balance;
+    **contract_account.try_borrow_mut_lamports()? = 0; //~ [HEPHA-ACCOUNT-CLOSE] possible revival of a closed account
+    **user_account.try_borrow_mut_lamports()? += *balance; //~ [HEPHA-BALANCE-CONSERVATION] possible unbalanced transfer, whose credits and debits of the lamports of accounts do not cancel out
 
     *balance = 0;
     Ok(())
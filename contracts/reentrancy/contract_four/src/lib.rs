@@ -64,7 +64,7 @@ pub fn deposit(balances: &mut HashMap<Pubkey, u64>, accounts: &[AccountInfo], am
     invoke(
         &system_instruction::transfer(
             user_account.key, 
-            contract_account.key, 
+            contract_account.key, //~! [HEPHA-UNINVOKED-INSTRUCTION]
             amount
         ),
         &[user_account.clone(), contract_account.clone()],
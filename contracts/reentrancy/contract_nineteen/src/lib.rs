@@ -31,7 +31,7 @@ pub fn process_instruction(
     let instruction = Instruction::try_from_slice(&[instruction_data[0]])?;
     match instruction {
         Instruction::Deposit => {
-            let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+            let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap()); //~ [HEPHA-UNVALIDATED-DESERIALIZATION] possible read of instruction data at offset 1 that the matched variant of the deserialized Instruction relies on
             msg!("User deposits {} lamports", amount);
             deposit(&mut balances, accounts, amount)?;
         }
@@ -63,7 +63,7 @@ pub fn deposit(balances: &mut HashMap<Pubkey, u64>, accounts: &[AccountInfo], am
     let entry = balances.entry(*user_account.key).or_insert(0);
     *entry += amount;
 
-    instruction::transfer(user_account.key, contract_account.key, amount);
+    instruction::transfer(user_account.key, contract_account.key, amount); //~ [HEPHA-UNINVOKED-INSTRUCTION] possible transfer that never happens
     Ok(())
 }
 
@@ -78,7 +78,7 @@ pub fn withdraw_all(balances: &mut HashMap<Pubkey, u64>, accounts: &[AccountInfo
     }
 
     let balance = balances.get_mut(user_account.key).ok_or(ProgramError::InvalidAccountData)?;
-    instruction::transfer(contract_account.key, user_account.key, *balance);
+    instruction::transfer(contract_account.key, user_account.key, *balance); //~ [HEPHA-UNINVOKED-INSTRUCTION] possible transfer that never happens
 
     *balance = 0;
     Ok(())
@@ -19,7 +19,7 @@ pub fn process_instruction(
     let user_account = next_account_info(accounts_iter)?;
     let contract_account = next_account_info(accounts_iter)?;
 
-    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new(); //~ [HEPHA-NON-PERSISTENT-STATE] possible loss of state: balances is created by the instruction rather than read from the data of an account
     let instruction = instruction_data[0];
     let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
     match instruction {
@@ -62,7 +62,7 @@ pub fn deposit(
     *entry += amount;
     
     **user_account.try_borrow_mut_lamports()? -= amount;
-    **contract_account.try_borrow_mut_lamports()? += amount;
+    **contract_account.try_borrow_mut_lamports()? += amount; //~ [HEPHA-ACCOUNT-ALIASING] accounts may alias; add a key inequality check
     Ok(())
 }
 
@@ -62,7 +62,7 @@ pub fn deposit(
     *entry += amount;
     
     **user_account.try_borrow_mut_lamports()? -= amount;
-    **contract_account.try_borrow_mut_lamports()? += amount;
+    **contract_account.try_borrow_mut_lamports()? += amount; //~ [HEPHA-ACCOUNT-ALIASING] accounts may alias; add a key inequality check
     Ok(())
 }
 
@@ -64,7 +64,7 @@ pub fn deposit(balances: &mut HashMap<Pubkey, u64>, accounts: &[AccountInfo], am
         &system_instruction::transfer(
             user_account.key, 
             contract_account.key, 
-            amount
+            amount //~! [HEPHA-UNINVOKED-INSTRUCTION]
         ),
         &[user_account.clone(), contract_account.clone()],
     )?;
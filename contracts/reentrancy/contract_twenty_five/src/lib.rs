@@ -0,0 +1,40 @@
+use solana_program::{
+    msg,
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    program::invoke,
+    system_instruction
+};
+use std::collections::HashMap;
+
+entrypoint!(process_instruction);
+
+// Pays out the balance of the user with a helper and only then clears it, so the invoked program
+// can withdraw the same balance again.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+    let accounts_iter = &mut accounts.iter();
+    let user_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+
+    let balance = balances.get_mut(user_account.key).ok_or(ProgramError::InvalidAccountData)?;
+    msg!("Withdrawing the whole balance");
+    withdraw_all(*balance, vault_account, user_account)?;
+
+    *balance = 0; //~ [HEPHA-REENTRANCY] possible reentrancy for the smart contract
+    Ok(())
+}
+
+pub fn withdraw_all<'a>(amount: u64, vault_account: &AccountInfo<'a>, user_account: &AccountInfo<'a>) -> ProgramResult {
+    invoke(
+        &system_instruction::transfer(vault_account.key, user_account.key, amount), //~! [HEPHA-UNINVOKED-INSTRUCTION]
+        &[vault_account.clone(), user_account.clone()],
+    )
+}
@@ -0,0 +1,43 @@
+use solana_program::{
+    msg,
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    program::invoke,
+    system_instruction
+};
+use std::collections::HashMap;
+
+entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+    let amount = u64::from_le_bytes(instruction_data[0..8].try_into().unwrap());
+    withdraw(&mut balances, accounts, amount)
+}
+
+// Decreases the balance of the user before paying the user, so a withdrawal that the invoked
+// program makes through the account of the user sees the decreased balance.
+pub fn withdraw(balances: &mut HashMap<Pubkey, u64>, accounts: &[AccountInfo], amount: u64) -> Result<(), ProgramError> {
+    let accounts_iter = &mut accounts.iter();
+    let user_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+
+    let balance = balances.get_mut(user_account.key).ok_or(ProgramError::InvalidAccountData)?;
+    if *balance < amount {
+        msg!("Insufficient balance for withdrawal");
+        return Err(ProgramError::InsufficientFunds);
+    }
+    *balance -= amount;
+    invoke(
+        &system_instruction::transfer(vault_account.key, user_account.key, amount), //~! [HEPHA-UNINVOKED-INSTRUCTION]
+        &[vault_account.clone(), user_account.clone()],
+    )?;
+    Ok(())
+}
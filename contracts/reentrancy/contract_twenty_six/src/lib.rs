@@ -0,0 +1,53 @@
+use solana_program::{
+    msg,
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    program::invoke,
+    system_instruction
+};
+use std::collections::HashMap;
+
+entrypoint!(process_instruction);
+
+// Pays the user with one helper and settles the balance with another, so the invoked program
+// can withdraw again before the balance is decreased.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+    let accounts_iter = &mut accounts.iter();
+    let user_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let amount = u64::from_le_bytes(instruction_data[0..8].try_into().unwrap());
+
+    pay_out(amount, vault_account, user_account)?;
+    settle(&mut balances, user_account.key, amount)?; //~ [HEPHA-REENTRANCY] possible reentrancy for the smart contract
+    Ok(())
+}
+
+pub fn pay_out<'a>(amount: u64, vault_account: &AccountInfo<'a>, user_account: &AccountInfo<'a>) -> ProgramResult {
+    msg!("Paying out {}", amount);
+    transfer(amount, vault_account, user_account)
+}
+
+pub fn transfer<'a>(amount: u64, from: &AccountInfo<'a>, to: &AccountInfo<'a>) -> ProgramResult {
+    invoke(
+        &system_instruction::transfer(from.key, to.key, amount), //~! [HEPHA-UNINVOKED-INSTRUCTION]
+        &[from.clone(), to.clone()],
+    )
+}
+
+pub fn settle(balances: &mut HashMap<Pubkey, u64>, user: &Pubkey, amount: u64) -> ProgramResult {
+    let balance = balances.get_mut(user).ok_or(ProgramError::InvalidAccountData)?;
+    if *balance < amount {
+        msg!("Insufficient balance for withdrawal");
+        return Err(ProgramError::InsufficientFunds);
+    }
+    *balance -= amount;
+    Ok(())
+}
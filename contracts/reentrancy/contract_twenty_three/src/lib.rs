@@ -0,0 +1,46 @@
+use solana_program::{
+    msg,
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    program::invoke,
+    system_instruction
+};
+use std::collections::HashMap;
+
+entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+    let fee = u64::from_le_bytes(instruction_data[0..8].try_into().unwrap());
+    collect_fee(&mut balances, accounts, fee)
+}
+
+// Moves a fee from the vault to the treasury and then charges it to the user. The invoked
+// program only receives the vault and the treasury, so it cannot reenter through the account
+// of the user whose balance is written afterwards.
+pub fn collect_fee(balances: &mut HashMap<Pubkey, u64>, accounts: &[AccountInfo], fee: u64) -> Result<(), ProgramError> {
+    let accounts_iter = &mut accounts.iter();
+    let user_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let treasury_account = next_account_info(accounts_iter)?;
+
+    let balance = balances.get_mut(user_account.key).ok_or(ProgramError::InvalidAccountData)?;
+    if *balance < fee {
+        msg!("Insufficient balance for the fee");
+        return Err(ProgramError::InsufficientFunds);
+    }
+    invoke(
+        &system_instruction::transfer(vault_account.key, treasury_account.key, fee), //~! [HEPHA-UNINVOKED-INSTRUCTION]
+        &[vault_account.clone(), treasury_account.clone()],
+    )?;
+
+    *balance -= fee;
+    Ok(())
+}
@@ -69,7 +69,7 @@ pub fn deposit_lamports(
     *entry += amount;
     
     **user_account.try_borrow_mut_lamports()? -= amount;
-    **contract_account.try_borrow_mut_lamports()? += amount;
+    **contract_account.try_borrow_mut_lamports()? += amount; //~ [HEPHA-ACCOUNT-ALIASING] accounts may alias; add a key inequality check
     Ok(())
 }
 
@@ -88,7 +88,7 @@ pub fn transfer_lamports(
     }
 
     balances.insert(user, user_balance - amount);
-    balances.insert(receiver, receiver_balance - amount);
+    balances.insert(receiver, receiver_balance - amount); //~ [HEPHA-BALANCE-CONSERVATION] possible unbalanced transfer, whose credits and debits of the balances kept in maps do not cancel out
     Ok(())
 }
 
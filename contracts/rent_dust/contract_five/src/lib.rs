@@ -0,0 +1,40 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program::invoke,
+    pubkey::Pubkey,
+    system_instruction::transfer,
+};
+
+entrypoint!(process_instruction);
+
+// The length of the data of a vault after it grows
+const GROWN_VAULT_LEN: usize = 1024;
+
+// The lamports that the payer adds to the vault when its data grows
+const GROWTH_FEE: u64 = 5000;
+
+// Grows the data of a vault and tops it up with a fixed fee, which is not related to the
+// rent-exempt minimum for the new length, and falls short of it unless the vault already holds
+// most of it.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let payer_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    invoke(
+        &transfer(payer_account.key, vault_account.key, GROWTH_FEE), //~ [HEPHA-RENT-DUST] loss of the lamports credited to an account
+        &[
+            payer_account.clone(),
+            vault_account.clone(),
+            system_program.clone(),
+        ],
+    )?;
+    vault_account.realloc(GROWN_VAULT_LEN, false)
+}
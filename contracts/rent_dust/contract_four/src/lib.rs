@@ -0,0 +1,36 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program::invoke,
+    pubkey::Pubkey,
+    system_instruction::create_account,
+};
+
+entrypoint!(process_instruction);
+
+// The length of the data of a vault
+const VAULT_LEN: u64 = 41;
+
+// Creates the vault of the payer and funds it with a hard-coded number of lamports, which is far
+// below the rent-exempt minimum for the data of the vault.
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let payer_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    let instruction = create_account(payer_account.key, vault_account.key, 1000, VAULT_LEN, program_id); //~ [HEPHA-RENT-DUST] loss of the lamports credited to an account
+    invoke(
+        &instruction,
+        &[
+            payer_account.clone(),
+            vault_account.clone(),
+            system_program.clone(),
+        ],
+    )
+}
@@ -0,0 +1,42 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction::create_account,
+};
+
+entrypoint!(process_instruction);
+
+// The length of the data of a vault
+const VAULT_LEN: u64 = 41;
+
+// Creates the vault of the payer and funds it with as many lamports as the payer asks for, which
+// may be fewer than the rent-exempt minimum for the data of the vault.
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let payer_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    let lamports = instruction_data
+        .get(0..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let instruction = create_account(payer_account.key, vault_account.key, lamports, VAULT_LEN, program_id); //~ [HEPHA-RENT-DUST] possible loss of the lamports credited to an account
+    invoke(
+        &instruction,
+        &[
+            payer_account.clone(),
+            vault_account.clone(),
+            system_program.clone(),
+        ],
+    )
+}
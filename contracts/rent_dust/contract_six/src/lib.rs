@@ -0,0 +1,42 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program::invoke,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::transfer,
+    sysvar::Sysvar,
+};
+
+entrypoint!(process_instruction);
+
+// The length of the data of a vault after it grows
+const GROWN_VAULT_LEN: usize = 1024;
+
+// Grows the data of the vault of rent_dust/contract_five, and tops it up with the lamports that it
+// lacks to reach the rent-exempt minimum for the new length.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let payer_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    let minimum_balance = Rent::get()?.minimum_balance(GROWN_VAULT_LEN);
+    let lamports = vault_account.lamports();
+    if lamports < minimum_balance {
+        invoke(
+            &transfer(payer_account.key, vault_account.key, minimum_balance - lamports),
+            &[
+                payer_account.clone(),
+                vault_account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+    vault_account.realloc(GROWN_VAULT_LEN, false)
+}
@@ -0,0 +1,48 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program::invoke,
+    pubkey::Pubkey,
+    system_instruction::{allocate, assign, transfer},
+};
+
+entrypoint!(process_instruction);
+
+// The length of the data of a vault
+const VAULT_LEN: u64 = 165;
+
+// The fee that the payer pays for a vault, in lamports
+const VAULT_FEE: u64 = 1_000_000;
+
+// Creates the vault of the payer the way the system program does for an address that may already
+// hold lamports: the fee is transferred to it and its data is then allocated and assigned to the
+// program. The fee is below the rent-exempt minimum for the data of the vault.
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let payer_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    let instruction = transfer(payer_account.key, vault_account.key, VAULT_FEE); //~ [HEPHA-RENT-DUST] loss of the lamports credited to an account
+    invoke(
+        &instruction,
+        &[
+            payer_account.clone(),
+            vault_account.clone(),
+            system_program.clone(),
+        ],
+    )?;
+    invoke(
+        &allocate(vault_account.key, VAULT_LEN),
+        &[vault_account.clone(), system_program.clone()],
+    )?;
+    invoke(
+        &assign(vault_account.key, program_id),
+        &[vault_account.clone(), system_program.clone()],
+    )
+}
@@ -0,0 +1,44 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program::invoke,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::create_account,
+    sysvar::Sysvar,
+};
+
+entrypoint!(process_instruction);
+
+// The length of the data of a vault
+const VAULT_LEN: u64 = 41;
+
+// Creates the vault of the payer and funds it with the rent-exempt minimum for its data.
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let payer_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    let lamports = Rent::get()?.minimum_balance(VAULT_LEN as usize);
+    let instruction = create_account(
+        payer_account.key,
+        vault_account.key,
+        lamports,
+        VAULT_LEN,
+        program_id,
+    );
+    invoke(
+        &instruction,
+        &[
+            payer_account.clone(),
+            vault_account.clone(),
+            system_program.clone(),
+        ],
+    )
+}
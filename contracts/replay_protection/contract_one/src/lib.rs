@@ -0,0 +1,33 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// Pays out the reward of a claim from the vault to the claimant.
+// Accounts: [vault, claimant, claim record]
+// Instruction data: [claim id (8 bytes), reward (8 bytes)]
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault = next_account_info(accounts_iter)?;
+    let claimant = next_account_info(accounts_iter)?;
+    let claim_record = next_account_info(accounts_iter)?;
+    if claim_record.data_len() < 8 || instruction_data.len() < 16 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let claim_id = u64::from_le_bytes(instruction_data[0..8].try_into().unwrap());
+    let reward = u64::from_le_bytes(instruction_data[8..16].try_into().unwrap());
+    // The claim is paid out, but nothing stops the same claim from being paid out again
+    **vault.try_borrow_mut_lamports()? -= reward; //~ [HEPHA-REPLAY-PROTECTION] no replay protection detected for externally provided identifier `claim_id`
+    **claimant.try_borrow_mut_lamports()? += reward;
+    claim_record.try_borrow_mut_data()?[0..8].copy_from_slice(&claim_id.to_le_bytes());
+    Ok(())
+}
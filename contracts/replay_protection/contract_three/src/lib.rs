@@ -0,0 +1,37 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// Settles an order by paying its amount from the escrow to the seller. Orders are settled in
+// the order of their ids, and the escrow records the id of the last settled order.
+// Accounts: [escrow, seller]
+// Instruction data: [order id (8 bytes), amount (8 bytes)]
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let escrow = next_account_info(accounts_iter)?;
+    let seller = next_account_info(accounts_iter)?;
+    if escrow.data_len() < 8 || instruction_data.len() < 16 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let order_id = u64::from_le_bytes(instruction_data[0..8].try_into().unwrap());
+    let amount = u64::from_le_bytes(instruction_data[8..16].try_into().unwrap());
+    let last_order_id = u64::from_le_bytes(escrow.try_borrow_data()?[0..8].try_into().unwrap());
+    // An order that is not newer than the last settled one has already been settled
+    if order_id <= last_order_id {
+        return Err(ProgramError::InvalidArgument);
+    }
+    **escrow.try_borrow_mut_lamports()? -= amount;
+    **seller.try_borrow_mut_lamports()? += amount;
+    escrow.try_borrow_mut_data()?[0..8].copy_from_slice(&order_id.to_le_bytes());
+    Ok(())
+}
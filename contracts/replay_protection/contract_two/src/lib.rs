@@ -0,0 +1,68 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// The record of a claim, laid out as the claim id and a processed flag.
+pub struct ClaimRecord {
+    pub claim_id: u64,
+    pub processed: bool,
+}
+
+impl ClaimRecord {
+    pub const LEN: usize = 9;
+
+    pub fn pack(&self) -> [u8; ClaimRecord::LEN] {
+        let mut bytes = [0u8; ClaimRecord::LEN];
+        bytes[0..8].copy_from_slice(&self.claim_id.to_le_bytes());
+        bytes[8] = self.processed as u8;
+        bytes
+    }
+
+    pub fn unpack(src: &[u8]) -> Result<ClaimRecord, ProgramError> {
+        if src.len() < ClaimRecord::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(ClaimRecord {
+            claim_id: u64::from_le_bytes(src[0..8].try_into().unwrap()),
+            processed: src[8] != 0,
+        })
+    }
+}
+
+// Pays out the reward of a claim from the vault to the claimant, once.
+// Accounts: [vault, claimant, claim record]
+// Instruction data: [claim id (8 bytes), reward (8 bytes)]
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault = next_account_info(accounts_iter)?;
+    let claimant = next_account_info(accounts_iter)?;
+    let claim_record = next_account_info(accounts_iter)?;
+    if claim_record.data_len() < ClaimRecord::LEN || instruction_data.len() < 16 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let claim_id = u64::from_le_bytes(instruction_data[0..8].try_into().unwrap());
+    let reward = u64::from_le_bytes(instruction_data[8..16].try_into().unwrap());
+    let record = ClaimRecord::unpack(&claim_record.try_borrow_data()?)?;
+    // The processed flag of the claim is checked before it is paid out
+    if record.processed {
+        return Err(ProgramError::InvalidArgument);
+    }
+    **vault.try_borrow_mut_lamports()? -= reward;
+    **claimant.try_borrow_mut_lamports()? += reward;
+    let processed = ClaimRecord {
+        claim_id,
+        processed: true,
+    };
+    claim_record.try_borrow_mut_data()?[..ClaimRecord::LEN].copy_from_slice(&processed.pack());
+    Ok(())
+}
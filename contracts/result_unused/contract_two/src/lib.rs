@@ -0,0 +1,51 @@
+use solana_program::{
+    msg,
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use std::collections::HashMap;
+
+entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+    let amount = u64::from_le_bytes(instruction_data[0..8].try_into().unwrap());
+    withdraw(&mut balances, amount, user_account, vault_account)
+}
+
+// The balance of the user is only decreased once the transfer has succeeded.
+pub fn withdraw(
+    balances: &mut HashMap<Pubkey, u64>,
+    amount: u64,
+    user_account: &AccountInfo,
+    vault_account: &AccountInfo,
+) -> ProgramResult {
+    let balance = balances.get_mut(user_account.key).ok_or(ProgramError::InvalidAccountData)?;
+    if *balance < amount {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    transfer(vault_account, user_account, amount)?; //~! [HEPHA-RESULT-UNUSED]
+    *balance -= amount;
+    Ok(())
+}
+
+pub fn transfer(from: &AccountInfo, to: &AccountInfo, amount: u64) -> ProgramResult {
+    if from.lamports() < amount {
+        msg!("Insufficient lamports for the transfer");
+        return Err(ProgramError::InsufficientFunds);
+    }
+    **from.try_borrow_mut_lamports()? -= amount;
+    **to.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program::{get_return_data, invoke},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+// The ids of the price oracles start with this byte.
+const ORACLE_TAG: u8 = 7;
+
+entrypoint!(process_instruction);
+
+// Asks the program of the first account for a price and trusts the return data to come from an
+// oracle, although the program is chosen by the caller.
+fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle = next_account_info(accounts_iter)?;
+    invoke(
+        &Instruction::new_with_bytes(*oracle.key, &[], vec![]),
+        accounts,
+    )?;
+    let (program, price) = get_return_data().ok_or(ProgramError::InvalidAccountData)?;
+    assert_eq!(program.to_bytes()[0], ORACLE_TAG);
+    if price.len() < 8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
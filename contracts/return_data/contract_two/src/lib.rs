@@ -0,0 +1,34 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint,
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program::{get_return_data, invoke},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+// The ids of the price oracles start with this byte.
+const ORACLE_TAG: u8 = 7;
+const ORACLE_ID: Pubkey = Pubkey::new_from_array([ORACLE_TAG; 32]);
+
+entrypoint!(process_instruction);
+
+// Asks the oracle for a price. The return data can only come from the oracle, since it is the
+// program that is invoked.
+fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    invoke(
+        &Instruction::new_with_bytes(ORACLE_ID, &[], vec![]),
+        accounts,
+    )?;
+    let (program, price) = get_return_data().ok_or(ProgramError::InvalidAccountData)?;
+    assert_eq!(program.to_bytes()[0], ORACLE_TAG);
+    if price.len() < 8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use solana_program::{
+    msg,
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use oorandom::Rand64;
+
+entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user_account = next_account_info(accounts_iter)?;
+    let contract_account = next_account_info(accounts_iter)?;
+    let vrf_account = next_account_info(accounts_iter)?;
+
+    if !user_account.is_signer {
+        msg!("User account must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let input_number = u64::from_le_bytes(instruction_data[0..8].try_into().unwrap());
+    announce_winner(input_number, user_account, contract_account, vrf_account)
+}
+
+pub fn announce_winner(
+    input_number: u64,
+    user_account: &AccountInfo,
+    contract_account: &AccountInfo,
+    vrf_account: &AccountInfo,
+) -> Result<(), ProgramError> {
+    // The seed is the output of a verifiable random function, which validators cannot predict
+    let vrf_data = vrf_account.try_borrow_data()?;
+    let seed = u128::from_le_bytes(vrf_data[..16].try_into().unwrap());
+    let mut rng = Rand64::new(seed);
+    let random_number = rng.rand_range(1..1000000);
+
+    if input_number == random_number {
+        let prize = contract_account.lamports() / 2;
+        **contract_account.try_borrow_mut_lamports()? -= prize;
+        **user_account.try_borrow_mut_lamports()? += prize;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+entrypoint!(process_instruction);
+
+// Pays lamports out of the vault of the program, the address derived from constant seeds, which
+// the program signs for once the vault is checked to be that address.
+// Accounts: [vault, recipient, authority, system_program]
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault = next_account_info(accounts_iter)?;
+    let recipient = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let (vault_address, bump) = Pubkey::find_program_address(&[b"vault"], program_id);
+    if vault_address != *vault.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let amount = instruction_data
+        .get(..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    invoke_signed(
+        &system_instruction::transfer(vault.key, recipient.key, amount),
+        &[vault.clone(), recipient.clone(), system_program.clone()],
+        &[&[b"vault", &[bump]]],
+    )
+}
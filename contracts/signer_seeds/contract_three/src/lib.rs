@@ -0,0 +1,40 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+entrypoint!(process_instruction);
+
+// Pays lamports out of the vault of the authority, the address derived from the key of the
+// authority, without checking that the vault that is passed is that address.
+// Accounts: [vault, recipient, authority, system_program]
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault = next_account_info(accounts_iter)?;
+    let recipient = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let (_, bump) = Pubkey::find_program_address(&[b"vault", authority.key.as_ref()], program_id);
+    let amount = instruction_data
+        .get(..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    invoke_signed( //~ [HEPHA-SIGNER-SEEDS] possible signature of a program derived address that is not one of the accounts passed to the invoked program
+        &system_instruction::transfer(vault.key, recipient.key, amount),
+        &[vault.clone(), recipient.clone(), system_program.clone()],
+        &[&[b"vault", authority.key.as_ref(), &[bump]]],
+    )
+}
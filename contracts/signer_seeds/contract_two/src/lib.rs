@@ -0,0 +1,46 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+entrypoint!(process_instruction);
+
+// Pays lamports out of a named vault of the program, whose name and bump seed come from the
+// instruction data, so that the caller picks the vault that pays.
+// Accounts: [vault, recipient, authority, system_program]
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault = next_account_info(accounts_iter)?;
+    let recipient = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let amount = instruction_data
+        .get(..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let name = instruction_data
+        .get(8..16)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let bump = instruction_data
+        .get(16)
+        .copied()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    invoke_signed( //~ [HEPHA-SIGNER-SEEDS] possible signature of a program derived address that the caller chooses
+        &system_instruction::transfer(vault.key, recipient.key, amount),
+        &[vault.clone(), recipient.clone(), system_program.clone()],
+        &[&[b"vault", name, &[bump]]],
+    )
+}
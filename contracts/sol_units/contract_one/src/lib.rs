@@ -0,0 +1,53 @@
+use solana_program::{
+    msg,
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    native_token::LAMPORTS_PER_SOL,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// The smallest deposit that the vault accepts, in SOL
+const MIN_DEPOSIT_SOL: u64 = 2;
+
+// Deposits into the vault an amount that the depositor gives in whole SOL, and takes a fee of
+// one percent of it. The amount is converted to lamports once by to_lamports and then again for
+// the fee, and the balance of the depositor, which is in lamports, is compared with a minimum
+// that is in SOL.
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let depositor_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+
+    if !depositor_account.is_signer {
+        msg!("Depositor account must sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if vault_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let amount = u64::from(u32::from_le_bytes(instruction_data[0..4].try_into().unwrap()));
+    let lamports = to_lamports(amount);
+    let fee = lamports * LAMPORTS_PER_SOL / 100; //~ [HEPHA-SOL-UNIT-MIXING] possible conversion to lamports of an amount that is already in lamports
+    if depositor_account.lamports() < MIN_DEPOSIT_SOL { //~ [HEPHA-SOL-UNIT-MIXING] possible mixing of an amount in lamports with an amount in SOL
+        msg!("The deposit is below the minimum");
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    **depositor_account.try_borrow_mut_lamports()? -= lamports;
+    **vault_account.try_borrow_mut_lamports()? += lamports - fee;
+    Ok(())
+}
+
+// Converts an amount of SOL to lamports
+fn to_lamports(amount: u64) -> u64 {
+    amount * LAMPORTS_PER_SOL
+}
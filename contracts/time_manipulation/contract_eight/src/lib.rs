@@ -62,11 +62,11 @@ pub fn announce_winner(
     user_account: &AccountInfo,
     contract_account: &AccountInfo
 ) -> Result<(), ProgramError> {
-    let clock = Clock::get()?;
+    let clock = Clock::get()?; //~ [HEPHA-TIME-MANIPULATION] possible time manipulation for the smart contract, a modulo of the clock decides a transfer of lamports
     let timestamp = clock.unix_timestamp;
 
     if timestamp % 7 == 0 {
-        **contract_account.try_borrow_mut_lamports()? -= 0;
+        **contract_account.try_borrow_mut_lamports()? -= 0; //~ [HEPHA-NO-OP-TRANSFER] transfer of zero lamports
         **user_account.try_borrow_mut_lamports()? += *contract_balance;
         *contract_balance = 0;
     }
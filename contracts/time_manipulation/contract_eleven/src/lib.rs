@@ -66,7 +66,7 @@ pub fn announce_winner(
     let timestamp = clock.unix_timestamp;
 
     if timestamp % 17 == 0 {
-        **contract_account.try_borrow_mut_lamports()? -= 0;
+        **contract_account.try_borrow_mut_lamports()? -= 0; //~ [HEPHA-NO-OP-TRANSFER] transfer of zero lamports
         **user_account.try_borrow_mut_lamports()? += *contract_balance;
         *contract_balance = 0;
     }
@@ -23,10 +23,10 @@ pub fn process_instruction(
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    let clock = Clock::get()?;
+    let clock = Clock::get()?; //~ possible time manipulation for the smart contract
     let block_timestamp = clock.unix_timestamp;
     let block_number = clock.slot as i64;
-    let random_number = block_timestamp * (block_number + 250);
+    let random_number = block_timestamp * (block_number + 250); //~ possible mixing of time units (seconds and slots) for the smart contract
 
     msg!("Current random number: {}", random_number);
     Ok(())
@@ -0,0 +1,73 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+// The state of a token account, laid out as mint, owner and amount.
+pub struct TokenAccount {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+impl Sealed for TokenAccount {}
+
+impl IsInitialized for TokenAccount {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+
+impl Pack for TokenAccount {
+    const LEN: usize = 72;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[..32].copy_from_slice(self.mint.as_ref());
+        dst[32..64].copy_from_slice(self.owner.as_ref());
+        dst[64..72].copy_from_slice(&self.amount.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let mint = Pubkey::try_from(&src[..32]).map_err(|_| ProgramError::InvalidAccountData)?;
+        let owner = Pubkey::try_from(&src[32..64]).map_err(|_| ProgramError::InvalidAccountData)?;
+        let amount = src[64..72]
+            .try_into()
+            .map(u64::from_le_bytes)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        Ok(TokenAccount {
+            mint,
+            owner,
+            amount,
+        })
+    }
+}
+
+entrypoint!(process_instruction);
+
+// Wraps tokens of the user into wrapped tokens held by the vault.
+// Accounts: [user token account, wrapped token account]
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user_account = next_account_info(accounts_iter)?;
+    let wrapped_account = next_account_info(accounts_iter)?;
+    let amount = instruction_data
+        .get(..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let mut user = TokenAccount::unpack(&user_account.try_borrow_data()?)?;
+    let mut wrapped = TokenAccount::unpack(&wrapped_account.try_borrow_data()?)?;
+    user.amount = user.amount.checked_sub(amount).ok_or(ProgramError::InsufficientFunds)?;
+    wrapped.amount = wrapped.amount.checked_add(amount).ok_or(ProgramError::InvalidArgument)?;
+    TokenAccount::pack(user, &mut user_account.try_borrow_mut_data()?)?;
+    TokenAccount::pack(wrapped, &mut wrapped_account.try_borrow_mut_data()?)?;
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// Records a deposit in the ledger of truncation/contract_one, but checks the amount against the
+// range of u32 before the cast, and converts the number of days with try_into, whose error is
+// returned to the caller.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let ledger_account = next_account_info(accounts_iter)?;
+
+    if instruction_data.len() < 16 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let amount = u64::from_le_bytes(instruction_data[0..8].try_into().unwrap());
+    let days = u64::from_le_bytes(instruction_data[8..16].try_into().unwrap());
+
+    if amount > u64::from(u32::MAX) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let recorded_amount = amount as u32;
+    let lock_days: u16 = days.try_into().map_err(|_| ProgramError::InvalidArgument)?;
+
+    let mut data = ledger_account.try_borrow_mut_data()?;
+    if data.len() < 6 {
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+    data[0..4].copy_from_slice(&recorded_amount.to_le_bytes());
+    data[4..6].copy_from_slice(&lock_days.to_le_bytes());
+    msg!("Recorded a deposit of {} lamports", recorded_amount);
+    Ok(())
+}
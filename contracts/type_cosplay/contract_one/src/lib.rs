@@ -0,0 +1,73 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+// A Vault and a User have the same layout, and nothing in their data tells them apart, so the
+// owner of a User account can pass it as a Vault and withdraw its points as lamports.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Vault {
+    pub owner: Pubkey,
+    pub balance: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct User {
+    pub authority: Pubkey,
+    pub points: u64,
+}
+
+entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    match instruction_data.first() {
+        Some(0) => withdraw(program_id, accounts),
+        Some(1) => redeem(program_id, accounts),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+pub fn withdraw(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault_account = next_account_info(accounts_iter)?;
+    let owner_account = next_account_info(accounts_iter)?;
+    if vault_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let vault = Vault::try_from_slice(&vault_account.data.borrow())?; //~ [HEPHA-TYPE-COSPLAY] possible type cosplay
+    if !owner_account.is_signer || vault.owner != *owner_account.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    **vault_account.try_borrow_mut_lamports()? -= vault.balance;
+    **owner_account.try_borrow_mut_lamports()? += vault.balance;
+    Vault {
+        owner: vault.owner,
+        balance: 0,
+    }
+    .serialize(&mut &mut vault_account.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+pub fn redeem(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user_account = next_account_info(accounts_iter)?;
+    let authority_account = next_account_info(accounts_iter)?;
+    if user_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mut user = User::try_from_slice(&user_account.data.borrow())?; //~ [HEPHA-TYPE-COSPLAY] possible type cosplay
+    if !authority_account.is_signer || user.authority != *authority_account.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    user.points = 0;
+    user.serialize(&mut &mut user_account.data.borrow_mut()[..])?;
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+// The Vault and User of type_cosplay/contract_one, whose data starts with an 8 byte discriminator
+// like the accounts of Anchor programs, which tells them apart before they are deserialized.
+const VAULT_DISCRIMINATOR: [u8; 8] = *b"vault\0\0\0";
+const USER_DISCRIMINATOR: [u8; 8] = *b"user\0\0\0\0";
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Vault {
+    pub owner: Pubkey,
+    pub balance: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct User {
+    pub authority: Pubkey,
+    pub points: u64,
+}
+
+entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    match instruction_data.first() {
+        Some(0) => withdraw(program_id, accounts),
+        Some(1) => redeem(program_id, accounts),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+pub fn withdraw(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault_account = next_account_info(accounts_iter)?;
+    let owner_account = next_account_info(accounts_iter)?;
+    if vault_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let vault = {
+        let data = vault_account.data.borrow();
+        if data.len() < 8 || data[..8] != VAULT_DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Vault::try_from_slice(&data[8..])? //~! [HEPHA-TYPE-COSPLAY]
+    };
+    if !owner_account.is_signer || vault.owner != *owner_account.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    **vault_account.try_borrow_mut_lamports()? -= vault.balance;
+    **owner_account.try_borrow_mut_lamports()? += vault.balance;
+    Vault {
+        owner: vault.owner,
+        balance: 0,
+    }
+    .serialize(&mut &mut vault_account.data.borrow_mut()[8..])?;
+    Ok(())
+}
+
+pub fn redeem(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user_account = next_account_info(accounts_iter)?;
+    let authority_account = next_account_info(accounts_iter)?;
+    if user_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mut user = {
+        let data = user_account.data.borrow();
+        if data.len() < 8 || data[..8] != USER_DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        User::try_from_slice(&data[8..])? //~! [HEPHA-TYPE-COSPLAY]
+    };
+    if !authority_account.is_signer || user.authority != *authority_account.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    user.points = 0;
+    user.serialize(&mut &mut user_account.data.borrow_mut()[8..])?;
+    Ok(())
+}
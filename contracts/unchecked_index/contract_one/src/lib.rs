@@ -0,0 +1,26 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// Looks up the fee tier that the caller selects in the instruction data, without checking that
+// the tier exists, so that a caller can abort the program with any index past the last tier.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let fees: [u64; 4] = [5, 10, 25, 50];
+    let Some(tier) = instruction_data.first() else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+    let fee = fees[*tier as usize]; //~ [HEPHA-UNCHECKED-INDEX] possible index out of bounds of `fees` with an index that the caller controls
+    msg!("Fee: {}", fee);
+    Ok(())
+}
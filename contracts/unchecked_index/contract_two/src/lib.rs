@@ -0,0 +1,26 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// Looks up the fee tier that the caller selects in the instruction data, wrapping the selection
+// around the number of tiers, so that every index is within the tiers.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let fees: [u64; 4] = [5, 10, 25, 50];
+    let Some(tier) = instruction_data.first() else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+    let fee = fees[*tier as usize % fees.len()]; //~! [HEPHA-UNCHECKED-INDEX]
+    msg!("Fee: {}", fee);
+    Ok(())
+}
@@ -47,7 +47,7 @@ pub fn substract(
     sample_number: u64,
 ) -> Result<(), ProgramError>  {
     let entry = values.entry(user).or_insert(0);
-    *entry -= sample_number * 50;
+    *entry -= sample_number * 50; //~ [HEPHA-LAMPORT-UNDERFLOW] possible underflow
     
     Ok(())
 }
\ No newline at end of file
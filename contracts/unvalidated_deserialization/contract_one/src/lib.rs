@@ -0,0 +1,51 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub enum Instruction {
+    Deposit,
+    WithdrawAll,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Vault {
+    pub owner: [u8; 32],
+    pub total: u64,
+}
+
+// The vault is deserialized from the data of its account without checking that the data holds
+// one, and the amount of a deposit is read after the instruction without checking that the
+// instruction data is long enough for it.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault_account = next_account_info(accounts_iter)?;
+
+    let vault = Vault::try_from_slice(&vault_account.data.borrow())?; //~ [HEPHA-UNVALIDATED-DESERIALIZATION] possible deserialization of Vault from a slice that may be shorter than the 40 bytes of its shortest serialization
+    if instruction_data.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let instruction = Instruction::try_from_slice(&instruction_data[..1])?;
+    match instruction {
+        Instruction::Deposit => {
+            let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap()); //~ [HEPHA-UNVALIDATED-DESERIALIZATION] possible read of instruction data at offset 1 that the matched variant of the deserialized Instruction relies on
+            msg!("Deposit of {} lamports into a vault of {}", amount, vault.total);
+        }
+        Instruction::WithdrawAll => {
+            msg!("Withdrawal of {} lamports", vault.total);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub enum Instruction {
+    Deposit,
+    WithdrawAll,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Vault {
+    pub owner: [u8; 32],
+    pub total: u64,
+}
+
+// The same as contract_one, except that the length of the data of the vault account is checked
+// before the vault is deserialized, and the deposit checks that the instruction data holds the
+// amount before it reads it.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault_account = next_account_info(accounts_iter)?;
+
+    let data = vault_account.data.borrow();
+    let bytes: &[u8] = &data;
+    if bytes.len() != 40 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let vault = Vault::try_from_slice(bytes)?;
+    if instruction_data.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let instruction = Instruction::try_from_slice(&instruction_data[..1])?;
+    match instruction {
+        Instruction::Deposit => {
+            if instruction_data.len() < 9 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+            msg!("Deposit of {} lamports into a vault of {}", amount, vault.total);
+        }
+        Instruction::WithdrawAll => {
+            msg!("Withdrawal of {} lamports", vault.total);
+        }
+    }
+    Ok(())
+}
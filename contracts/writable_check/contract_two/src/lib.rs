@@ -0,0 +1,51 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+
+// Moves lamports from the vault, which is owned by the program, to the recipient, once both
+// accounts are checked to be writable.
+// Accounts: [vault, recipient, authority]
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault = next_account_info(accounts_iter)?;
+    let recipient = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    if vault.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !vault.is_writable || !recipient.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if vault.key == recipient.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let amount = instruction_data
+        .get(..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let vault_lamports = vault
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    let recipient_lamports = recipient
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **vault.try_borrow_mut_lamports()? = vault_lamports; //~! [HEPHA-WRITABLE-CHECK]
+    **recipient.try_borrow_mut_lamports()? = recipient_lamports;
+    Ok(())
+}